@@ -1,19 +1,232 @@
 use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use zeroize::Zeroize;
 
 // Re-export constants for backward compatibility
 pub use crate::constants::{
-    AUTO_LOCK_DEFAULT_SECONDS, AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS,
+    ATTEMPT_COOLDOWN_SECONDS, AUTO_LOCK_DEFAULT_SECONDS, AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS,
+    AUTO_LOCK_WARNING_DEFAULT_SECONDS, AUTO_LOCK_WARNING_MAX_SECONDS,
     AUTO_UNLOCK_DEFAULT_SECONDS, AUTO_UNLOCK_MAX_SECONDS, AUTO_UNLOCK_MIN_SECONDS,
-    BUFFER_RESET_DEFAULT_SECONDS, DEFAULT_LOCK_KEYCODE, DEFAULT_TALK_KEYCODE,
+    BUFFER_RESET_DEFAULT_SECONDS, BUFFER_RESET_MAX_SECONDS, BUFFER_RESET_MIN_SECONDS,
+    DEFAULT_LOCK_KEYCODE, MAX_BUFFER_LEN_DEFAULT, DEFAULT_TALK_KEYCODE,
+    DEFAULT_UNLOCK_KEYCODE, SPACEBAR_KEYCODE, UNLOCK_GRACE_DEFAULT_SECONDS,
+    UNLOCK_GRACE_MAX_SECONDS,
 };
-use crate::constants::REENABLE_DEBOUNCE_SECS;
+use crate::audit::{self, AuditEvent, UnlockMethod};
+use crate::constants::{
+    LOCK_DOUBLE_PRESS_MIN_GAP_MS, MAX_LOCK_DURATION_DEFAULT_MINUTES, REENABLE_DEBOUNCE_SECS,
+    WATCHDOG_TIMEOUT_DEFAULT_SECS,
+};
+use crate::input_blocking::chord::{ChordRecognizer, UnlockChord};
+use crate::input_blocking::filter::{EventFilter, EventFilterFn};
+use crate::input_blocking::hold_unlock::{HoldRecognizer, HoldUnlock};
+use crate::input_blocking::hotkeys::HotkeyIds;
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::power_source::{self, PowerAutoLockPolicy, PowerSource};
+use crate::utils::activity_debounce::ActivityDebounce;
+use crate::utils::keycode::KeyboardLayout;
+use crate::utils::modifiers::HotkeyModifiers;
+use crate::utils::secret::SecretString;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Which input devices are blocked while locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockScope {
+    /// Block keyboard and mouse/trackpad (default)
+    #[default]
+    All,
+    /// Block keyboard only; mouse/trackpad keeps working
+    KeyboardOnly,
+    /// Block mouse/trackpad only; keyboard keeps working (passphrase entry is not
+    /// available, unlock with Touch ID or auto-unlock instead)
+    MouseOnly,
+}
+
+impl LockScope {
+    /// Whether keyboard events (including passphrase entry) are intercepted while locked
+    pub fn blocks_keyboard(self) -> bool {
+        matches!(self, LockScope::All | LockScope::KeyboardOnly)
+    }
+
+    /// Whether mouse/trackpad events are blocked while locked
+    pub fn blocks_mouse(self) -> bool {
+        matches!(self, LockScope::All | LockScope::MouseOnly)
+    }
+}
+
+impl FromStr for LockScope {
+    type Err = anyhow::Error;
+
+    /// Parse "all", "keyboard"/"keyboard_only" or "mouse"/"mouse_only" (case insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "all" => Ok(LockScope::All),
+            "keyboard" | "keyboard_only" => Ok(LockScope::KeyboardOnly),
+            "mouse" | "mouse_only" => Ok(LockScope::MouseOnly),
+            other => Err(anyhow::anyhow!(
+                "Invalid lock scope '{}' (must be all, keyboard or mouse)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LockScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockScope::All => write!(f, "all"),
+            LockScope::KeyboardOnly => write!(f, "keyboard_only"),
+            LockScope::MouseOnly => write!(f, "mouse_only"),
+        }
+    }
+}
+
+/// How the Talk hotkey opens the passthrough key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TalkMode {
+    /// Passthrough is open only while the hotkey is held (default)
+    #[default]
+    Hold,
+    /// Each press of the hotkey latches passthrough on or off
+    Toggle,
+}
+
+impl FromStr for TalkMode {
+    type Err = anyhow::Error;
+
+    /// Parse "hold" or "toggle" (case insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "hold" => Ok(TalkMode::Hold),
+            "toggle" => Ok(TalkMode::Toggle),
+            other => Err(anyhow::anyhow!(
+                "Invalid talk mode '{}' (must be hold or toggle)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TalkMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TalkMode::Hold => write!(f, "hold"),
+            TalkMode::Toggle => write!(f, "toggle"),
+        }
+    }
+}
+
+/// What happens when accessibility permissions are lost while running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionLossPolicy {
+    /// Release the lock and stop blocking (default). The tray app waits for permissions
+    /// to come back; the CLI exits once the loss is confirmed.
+    #[default]
+    Unlock,
+    /// Release the lock and exit right away with a non-zero status, so a supervisor
+    /// (e.g. a launchd KeepAlive job) relaunches the app
+    Exit,
+    /// Keep the lock engaged and restart blocking once permissions are restored
+    Retry,
+}
+
+/// What a running app does after its event tap stopped for lost permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionLossAction {
+    /// Keep running and restart blocking when permissions are restored
+    Wait,
+    /// Exit once further checks confirm the loss (a transient blip restarts blocking)
+    ExitWhenConfirmed,
+    /// Exit right away
+    ExitNow,
+}
+
+impl PermissionLossPolicy {
+    /// Whether the lock is released when permissions are lost
+    pub fn releases_lock(self) -> bool {
+        !matches!(self, PermissionLossPolicy::Retry)
+    }
+
+    /// Action for an app that can (tray) or can't (CLI) keep running unlocked
+    pub fn action(self, runs_unlocked: bool) -> PermissionLossAction {
+        match self {
+            PermissionLossPolicy::Unlock if runs_unlocked => PermissionLossAction::Wait,
+            PermissionLossPolicy::Unlock => PermissionLossAction::ExitWhenConfirmed,
+            PermissionLossPolicy::Exit => PermissionLossAction::ExitNow,
+            PermissionLossPolicy::Retry => PermissionLossAction::Wait,
+        }
+    }
+}
+
+impl FromStr for PermissionLossPolicy {
+    type Err = anyhow::Error;
+
+    /// Parse "unlock", "exit" or "retry" (case insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "unlock" => Ok(PermissionLossPolicy::Unlock),
+            "exit" => Ok(PermissionLossPolicy::Exit),
+            "retry" => Ok(PermissionLossPolicy::Retry),
+            other => Err(anyhow::anyhow!(
+                "Invalid permission loss policy '{}' (must be unlock, exit or retry)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PermissionLossPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermissionLossPolicy::Unlock => write!(f, "unlock"),
+            PermissionLossPolicy::Exit => write!(f, "exit"),
+            PermissionLossPolicy::Retry => write!(f, "retry"),
+        }
+    }
+}
+
+/// Why a lock request was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockRefused {
+    /// Accessibility permissions aren't granted, so the lock couldn't block anything
+    PermissionsMissing,
+    /// No passphrase is set, so locked input couldn't be unlocked
+    NoPassphrase,
+}
+
+impl fmt::Display for LockRefused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockRefused::PermissionsMissing => write!(f, "accessibility permissions missing"),
+            LockRefused::NoPassphrase => write!(f, "no passphrase configured"),
+        }
+    }
+}
 
 /// Application state shared across modules
 #[derive(Clone)]
 pub struct AppState {
     inner: Arc<Mutex<AppStateInner>>,
+    /// Events blocked since the current (or last) lock engaged
+    ///
+    /// Kept outside the mutex: it's bumped from the event tap callback for every blocked
+    /// event, where relaxed atomics avoid contending for the state lock.
+    blocked_events: Arc<AtomicU64>,
+    /// Process-lifetime counters served at `GET /metrics` (atomics, like `blocked_events`)
+    metrics: Arc<Metrics>,
+    /// Set by the CLI's SIGINT/SIGTERM handlers to request a clean shutdown
+    ///
+    /// Kept outside the mutex: signal handlers may only touch atomics.
+    shutdown_requested: Arc<AtomicBool>,
 }
 
 pub struct AppStateInner {
@@ -27,18 +240,54 @@ pub struct AppStateInner {
     pub last_input_time: Instant,
     /// Current passphrase hash (SHA-256, hex-encoded)
     pub passphrase_hash: Option<String>,
+    /// One-time emergency recovery code hash (SHA-256, hex-encoded, None = no code or consumed)
+    pub recovery_code_hash: Option<String>,
+    /// Flag to signal that the recovery code was consumed and the config must be persisted
+    pub recovery_code_consumed: bool,
     /// Auto-lock timeout in seconds (see AUTO_LOCK_DEFAULT_SECONDS)
     pub auto_lock_timeout: u64,
+    /// Seconds before auto-lock to warn the user (0 = no warning)
+    pub auto_lock_warning_secs: u64,
+    /// Whether the auto-lock warning was already shown for the current idle period
+    pub auto_lock_warned: bool,
+    /// Auto-lock is suspended until this time (None = not snoozed)
+    pub snooze_until: Option<Instant>,
+    /// HandsOff is disabled until this time, then re-enabled (None = not paused)
+    pub pause_until: Option<Instant>,
+    /// Timestamp of the last unlock (for the post-unlock grace period)
+    pub last_unlock_time: Option<Instant>,
+    /// Seconds after unlocking during which auto-lock cannot re-engage (0 = none)
+    pub unlock_grace_secs: u64,
+    /// Whether active media playback holds off auto-lock
+    pub respect_media_playback: bool,
+    /// Cached media playback state (updated by the auto-lock check)
+    pub media_playback_active: bool,
+    /// Whether pointer motion (moves and drags) leaves the auto-lock timer running
+    pub auto_lock_ignore_mouse_move: bool,
+    /// Recent input events, so isolated stray ones don't reset the auto-lock timer
+    pub activity_debounce: ActivityDebounce,
+    /// How auto-lock depends on the power source (battery timeout, AC on/off)
+    pub power_auto_lock: PowerAutoLockPolicy,
+    /// Cached power source (updated by the auto-lock check while a policy is set)
+    pub power_source: PowerSource,
     /// Input buffer reset timeout in seconds (see BUFFER_RESET_DEFAULT_SECONDS)
     pub buffer_reset_timeout: u64,
+    /// Maximum characters kept in the input buffer (see MAX_BUFFER_LEN_DEFAULT)
+    pub max_buffer_len: usize,
     /// Whether the Talk hotkey is currently pressed (for passthrough)
     pub talk_key_pressed: bool,
+    /// Whether the Talk hotkey works while held or latches on/off per press
+    pub talk_mode: TalkMode,
     /// Timestamp when device was locked (for auto-unlock)
     pub lock_start_time: Option<Instant>,
     /// Auto-unlock timeout in seconds (None = disabled)
     pub auto_unlock_timeout: Option<u64>,
+    /// What the permission monitor and the binaries do when permissions are lost
+    pub permission_loss_policy: PermissionLossPolicy,
     /// Cached accessibility permissions state (updated by background thread)
     pub has_accessibility_permissions: bool,
+    /// Accessibility permission changes (granted <-> revoked) seen this session
+    pub permission_transitions: u64,
     /// Flag to signal that event tap should be stopped (set by permission monitor)
     pub should_stop_event_tap: bool,
     /// Flag to signal that event tap should be started (set by permission monitor on restoration)
@@ -51,12 +300,138 @@ pub struct AppStateInner {
     pub last_reenable_time: Option<Instant>,
     /// Flag to signal that app should exit (CLI only - set by event tap callback on permission loss)
     pub should_exit: bool,
+    /// Flag to signal main thread to disable HandsOff (requested over the control socket)
+    pub should_disable: bool,
+    /// Flag to signal main thread to re-enable HandsOff (requested over the control socket)
+    pub should_enable: bool,
     /// Whether the app is currently disabled (minimal CPU mode)
     pub is_disabled: bool,
-    /// Lock hotkey keycode (macOS keycode, see DEFAULT_LOCK_KEYCODE)
-    pub lock_keycode: i64,
-    /// Talk hotkey keycode (macOS keycode, see DEFAULT_TALK_KEYCODE)
-    pub talk_keycode: i64,
+    /// Lock hotkey keycode (macOS keycode, see DEFAULT_LOCK_KEYCODE; None = disabled)
+    pub lock_keycode: Option<i64>,
+    /// Talk hotkey keycode (macOS keycode, see DEFAULT_TALK_KEYCODE; None = disabled)
+    pub talk_keycode: Option<i64>,
+    /// Modifiers held with the lock hotkey key (default: Ctrl+Cmd+Shift)
+    pub lock_modifiers: HotkeyModifiers,
+    /// Modifiers held with the talk hotkey key (default: Ctrl+Cmd+Shift)
+    pub talk_modifiers: HotkeyModifiers,
+    /// Window for the second lock hotkey press (None = a single press locks)
+    pub lock_double_press_window: Option<Duration>,
+    /// When the lock hotkey was last pressed without engaging lock (double-press mode)
+    pub last_lock_hotkey_press: Option<Instant>,
+    /// Keyboard layout used to translate keycodes during passphrase entry
+    pub keyboard_layout: KeyboardLayout,
+    /// Key passed through while the Talk hotkey is held (macOS keycode, default: spacebar)
+    pub talk_passthrough_keycode: i64,
+    /// Touch ID unlock hotkey keycode (macOS keycode, see DEFAULT_UNLOCK_KEYCODE)
+    pub unlock_keycode: i64,
+    /// IDs of the registered global hotkeys (read by the hotkey listener thread)
+    pub hotkey_ids: HotkeyIds,
+    /// Emergency unlock hotkey keycode (macOS keycode, None = disabled)
+    pub panic_keycode: Option<i64>,
+    /// Modifiers held with the emergency unlock hotkey key (default: Ctrl+Opt+Cmd+Shift)
+    pub panic_modifiers: HotkeyModifiers,
+    /// Whether the emergency unlock hotkey asks for Touch ID before unlocking
+    pub panic_requires_touchid: bool,
+    /// Modifier chord sequence that unlocks without the passphrase (None = disabled)
+    pub unlock_chord: Option<UnlockChord>,
+    /// Progress through the unlock chord sequence
+    pub chord_recognizer: ChordRecognizer,
+    /// Key held for a few seconds to unlock without the passphrase (None = disabled)
+    pub hold_unlock: Option<HoldUnlock>,
+    /// Hold of the hold-to-unlock key in progress
+    pub hold_recognizer: HoldRecognizer,
+    /// Whether a Touch ID prompt is currently being shown
+    pub touchid_in_progress: bool,
+    /// Whether unlocking takes the passphrase followed by Touch ID
+    pub two_factor_unlock: bool,
+    /// Two-factor mode: the passphrase was verified, input stays locked until Touch ID succeeds
+    pub awaiting_biometric: bool,
+    /// Whether media/volume/brightness keys (NX system-defined events) are blocked while locked
+    pub block_media_keys: bool,
+    /// Whether the power / Touch ID key (NX system-defined events) is blocked while locked
+    pub block_power_key: bool,
+    /// Embedder's event filter, consulted before the built-in blocking decision
+    pub event_filter: Option<Arc<EventFilterFn>>,
+    /// Which input devices are blocked while locked
+    pub lock_scope: LockScope,
+    /// Bundle identifiers of apps that stay interactive while locked
+    pub app_allowlist: Vec<String>,
+    /// Cache of process ID -> bundle identifier lookups (None if the lookup failed)
+    pub bundle_id_cache: HashMap<i64, Option<String>>,
+    /// Audit log file (None = audit events are not recorded)
+    pub audit_log_path: Option<PathBuf>,
+    /// Dry-run mode: events that would be blocked are logged and passed through
+    pub dry_run: bool,
+    /// Beep when a passphrase attempt times out while locked
+    pub feedback_sound: bool,
+    /// Clear the buffer as soon as it is longer than any secret it could still match
+    pub clear_overlong_attempts: bool,
+    /// Characters in the passphrase (None = unknown, e.g. an older Keychain entry)
+    pub passphrase_len: Option<usize>,
+    /// Ignore passphrase input for a while after a full-length wrong attempt
+    pub attempt_cooldown: bool,
+    /// Passphrase input is ignored until then, after a full-length wrong attempt
+    pub attempt_cooldown_until: Option<Instant>,
+    /// Last time the CFRunLoop thread polled (None while it isn't running)
+    pub event_loop_heartbeat: Option<Instant>,
+    /// Heartbeat age in seconds after which the watchdog force-unlocks
+    pub watchdog_timeout_secs: u64,
+    /// Hard cap: seconds after which input is force-unlocked, even with auto-unlock off
+    pub max_lock_duration_secs: u64,
+}
+
+impl AppStateInner {
+    /// Whether the buffer could still be the start of the configured recovery code
+    fn is_recovery_code_prefix(&self) -> bool {
+        self.recovery_code_hash.is_some()
+            && crate::auth::could_be_recovery_code_prefix(&self.input_buffer)
+    }
+
+    /// Forget per-lock-session input state: a latched Talk passthrough, the attempt
+    /// cooldown, a partial unlock sequence or hold and a pending two-factor prompt
+    fn reset_lock_session(&mut self) {
+        self.talk_key_pressed = false;
+        self.attempt_cooldown_until = None;
+        self.chord_recognizer.reset();
+        self.hold_recognizer.release();
+        self.auto_lock_warned = false;
+        // A verified passphrase only counts for the lock session it was typed in
+        self.awaiting_biometric = false;
+    }
+
+    /// Whether auto-lock is held off because input was unlocked moments ago
+    fn is_in_unlock_grace(&self) -> bool {
+        self.last_unlock_time
+            .is_some_and(|t| t.elapsed().as_secs() < self.unlock_grace_secs)
+    }
+
+    /// Whether media playback currently counts as activity for auto-lock
+    fn is_media_playback_holding_auto_lock(&self) -> bool {
+        self.respect_media_playback && self.media_playback_active
+    }
+
+    /// Auto-lock timeout for the current power source (None = no auto-lock)
+    fn effective_auto_lock_timeout(&self) -> Option<u64> {
+        power_source::effective_auto_lock_timeout(
+            self.power_source,
+            self.auto_lock_timeout,
+            &self.power_auto_lock,
+        )
+    }
+
+    /// Seconds of inactivity counted towards auto-lock (None while snoozed)
+    ///
+    /// Once a snooze ends the idle time is measured from the end of the snooze, so
+    /// auto-lock resumes with a full countdown instead of firing immediately.
+    fn auto_lock_idle_secs(&self) -> Option<u64> {
+        let now = Instant::now();
+        let idle_since = match self.snooze_until {
+            Some(until) if until > now => return None,
+            Some(until) => until.max(self.last_input_time),
+            None => self.last_input_time,
+        };
+        Some(now.duration_since(idle_since).as_secs())
+    }
 }
 
 impl AppState {
@@ -64,25 +439,84 @@ impl AppState {
         Self {
             inner: Arc::new(Mutex::new(AppStateInner {
                 is_locked: false,
-                input_buffer: String::new(),
+                // Pre-allocate for the maximum buffer size (up to 4 bytes per char) so the
+                // buffer never reallocates and leaves unscrubbed copies on the heap
+                input_buffer: String::with_capacity(MAX_BUFFER_LEN_DEFAULT * 4),
                 last_key_time: None,
                 last_input_time: Instant::now(),
                 passphrase_hash: None,
+                recovery_code_hash: None,
+                recovery_code_consumed: false,
                 auto_lock_timeout: AUTO_LOCK_DEFAULT_SECONDS,
+                auto_lock_warning_secs: AUTO_LOCK_WARNING_DEFAULT_SECONDS,
+                auto_lock_warned: false,
+                snooze_until: None,
+                pause_until: None,
+                last_unlock_time: None,
+                unlock_grace_secs: UNLOCK_GRACE_DEFAULT_SECONDS,
+                respect_media_playback: false,
+                media_playback_active: false,
+                auto_lock_ignore_mouse_move: false,
+                activity_debounce: ActivityDebounce::default(),
+                power_auto_lock: PowerAutoLockPolicy::default(),
+                power_source: PowerSource::Unknown,
                 buffer_reset_timeout: BUFFER_RESET_DEFAULT_SECONDS,
+                max_buffer_len: MAX_BUFFER_LEN_DEFAULT,
                 talk_key_pressed: false,
+                talk_mode: TalkMode::Hold,
                 lock_start_time: None,
                 auto_unlock_timeout: None,
+                permission_loss_policy: PermissionLossPolicy::Unlock,
                 has_accessibility_permissions: false,
+                permission_transitions: 0,
                 should_stop_event_tap: false,
                 should_start_event_tap: false,
                 should_reenable_event_tap: false,
                 last_reenable_time: None,
                 should_exit: false,
+                should_disable: false,
+                should_enable: false,
                 is_disabled: false,
-                lock_keycode: DEFAULT_LOCK_KEYCODE,
-                talk_keycode: DEFAULT_TALK_KEYCODE,
+                lock_keycode: Some(DEFAULT_LOCK_KEYCODE),
+                talk_keycode: Some(DEFAULT_TALK_KEYCODE),
+                lock_modifiers: HotkeyModifiers::DEFAULT,
+                talk_modifiers: HotkeyModifiers::DEFAULT,
+                lock_double_press_window: None,
+                keyboard_layout: KeyboardLayout::Us,
+                last_lock_hotkey_press: None,
+                hotkey_ids: HotkeyIds::default(),
+                panic_keycode: None,
+                panic_modifiers: HotkeyModifiers::PANIC_DEFAULT,
+                panic_requires_touchid: false,
+                unlock_chord: None,
+                chord_recognizer: ChordRecognizer::default(),
+                hold_unlock: None,
+                hold_recognizer: HoldRecognizer::default(),
+                talk_passthrough_keycode: SPACEBAR_KEYCODE,
+                unlock_keycode: DEFAULT_UNLOCK_KEYCODE,
+                touchid_in_progress: false,
+                two_factor_unlock: false,
+                awaiting_biometric: false,
+                block_media_keys: true,
+                block_power_key: true,
+                event_filter: None,
+                lock_scope: LockScope::All,
+                app_allowlist: Vec::new(),
+                bundle_id_cache: HashMap::new(),
+                audit_log_path: None,
+                dry_run: false,
+                feedback_sound: false,
+                clear_overlong_attempts: false,
+                passphrase_len: None,
+                attempt_cooldown: true,
+                attempt_cooldown_until: None,
+                event_loop_heartbeat: None,
+                watchdog_timeout_secs: WATCHDOG_TIMEOUT_DEFAULT_SECS,
+                max_lock_duration_secs: MAX_LOCK_DURATION_DEFAULT_MINUTES * 60,
             })),
+            blocked_events: Arc::new(AtomicU64::new(0)),
+            metrics: Arc::new(Metrics::new()),
+            shutdown_requested: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -94,24 +528,151 @@ impl AppState {
         self.inner.lock().is_locked
     }
 
+    /// Whether input could be locked now: the lock must be able to block input and
+    /// the passphrase must be able to end it
+    pub fn can_lock(&self) -> Result<(), LockRefused> {
+        let state = self.inner.lock();
+        if !state.has_accessibility_permissions {
+            Err(LockRefused::PermissionsMissing)
+        } else if state.passphrase_hash.is_none() {
+            Err(LockRefused::NoPassphrase)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Lock input if `can_lock` allows it (every lock request goes through here)
+    pub fn try_lock(&self) -> Result<(), LockRefused> {
+        self.can_lock()?;
+        self.set_locked(true);
+        Ok(())
+    }
+
+    /// Lock or unlock input (unlocks are audited with `UnlockMethod::Unspecified`;
+    /// prefer `unlock_with`)
     pub fn set_locked(&self, locked: bool) {
+        if locked {
+            if self.apply_locked(true) {
+                self.record_audit(AuditEvent::Locked);
+            }
+        } else {
+            self.unlock_with(UnlockMethod::Unspecified);
+        }
+    }
+
+    /// Unlock input, recording how in the audit log
+    pub fn unlock_with(&self, method: UnlockMethod) {
+        if self.apply_locked(false) {
+            self.record_audit(AuditEvent::unlocked(method));
+        }
+    }
+
+    /// Update the lock state, returning whether it changed
+    fn apply_locked(&self, locked: bool) -> bool {
         let mut state = self.inner.lock();
+        let was_locked = state.is_locked;
         state.is_locked = locked;
 
         if locked {
-            // Record when lock was engaged
-            state.lock_start_time = Some(Instant::now());
-            log::debug!("Lock engaged at {:?}", state.lock_start_time);
+            // Locking ends any auto-lock snooze
+            state.snooze_until = None;
+            // Re-locking keeps the running session: its cooldown, pending two-factor
+            // prompt and auto-unlock deadline
+            if !was_locked {
+                state.reset_lock_session();
+                // A new lock session starts counting blocked events from zero
+                self.blocked_events.store(0, Ordering::Relaxed);
+                // Record when lock was engaged
+                state.lock_start_time = Some(Instant::now());
+                log::debug!("Lock engaged at {:?}", state.lock_start_time);
+            }
         } else {
+            state.reset_lock_session();
             // Clear lock time when manually unlocked
             state.lock_start_time = None;
+            // Start the post-unlock grace period
+            state.last_unlock_time = Some(Instant::now());
             log::debug!("Lock disengaged");
         }
+        was_locked != locked
+    }
+
+    /// Count an event blocked by the event tap (hot path: no state lock taken)
+    pub fn record_blocked_event(&self) {
+        self.blocked_events.fetch_add(1, Ordering::Relaxed);
+        self.metrics.record_event_blocked();
+    }
+
+    /// Events blocked since the current (or, once unlocked, the last) lock engaged
+    pub fn get_blocked_event_count(&self) -> u64 {
+        self.blocked_events.load(Ordering::Relaxed)
+    }
+
+    /// Current metrics, with the lock state as the gauge
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot(self.is_locked())
+    }
+
+    /// Set the audit log file (None = don't record audit events)
+    pub fn set_audit_log_path(&self, path: Option<PathBuf>) {
+        self.inner.lock().audit_log_path = path;
+    }
+
+    /// Append an event to the audit log, if one is configured
+    ///
+    /// Failures are logged and otherwise ignored: auditing must never block unlocking.
+    pub fn record_audit(&self, event: AuditEvent) {
+        // Audited transitions double as the metrics' increment points
+        match event {
+            AuditEvent::Locked => self.metrics.record_lock(),
+            AuditEvent::AutoUnlock { .. } | AuditEvent::MaxLockDuration { .. } => {
+                self.metrics.record_auto_unlock()
+            }
+            AuditEvent::PermissionLost => self.metrics.record_permission_loss(),
+            AuditEvent::Unlocked { .. } | AuditEvent::Disabled => {}
+        }
+
+        let Some(path) = self.inner.lock().audit_log_path.clone() else {
+            return;
+        };
+        if let Err(e) = audit::append(&path, &event) {
+            log::warn!("Failed to record audit event: {:#}", e);
+        }
+    }
+
+    /// Count an input event from the event tap as activity for auto-lock
+    ///
+    /// Resets the inactivity timer unless the activity debounce holds it back as an
+    /// isolated stray event.
+    pub fn record_input_activity(&self) {
+        self.record_input_activity_at(Instant::now());
+    }
+
+    /// `record_input_activity` as of `now`
+    pub fn record_input_activity_at(&self, now: Instant) {
+        let mut state = self.inner.lock();
+        if state.activity_debounce.record(now) {
+            state.last_input_time = now;
+            state.auto_lock_warned = false;
+        }
+    }
+
+    /// Set the activity debounce: `min_events` input events within `window` count as
+    /// activity (0 or 1 = every event counts)
+    pub fn set_activity_debounce(&self, min_events: u32, window: Duration) {
+        self.inner.lock().activity_debounce = ActivityDebounce::new(min_events, window);
+    }
+
+    /// Get the activity debounce event count and window
+    pub fn get_activity_debounce(&self) -> (u32, Duration) {
+        let state = self.inner.lock();
+        (state.activity_debounce.min_events(), state.activity_debounce.window())
     }
 
     pub fn update_input_time(&self) {
         let mut state = self.inner.lock();
         state.last_input_time = Instant::now();
+        state.auto_lock_warned = false;
     }
 
     pub fn update_key_time(&self) {
@@ -119,28 +680,207 @@ impl AppState {
         state.last_key_time = Some(Instant::now());
     }
 
+    /// Append a character to the input buffer, dropping the oldest characters
+    /// once `max_buffer_len` is reached so the buffer stays bounded
     pub fn append_to_buffer(&self, ch: char) {
         let mut state = self.inner.lock();
+        let max_len = state.max_buffer_len.max(1);
+        if state.input_buffer.chars().count() >= max_len {
+            state.input_buffer.remove(0);
+        }
         state.input_buffer.push(ch);
     }
 
+    /// Clear the input buffer, scrubbing its contents from memory
     pub fn clear_buffer(&self) {
         let mut state = self.inner.lock();
-        state.input_buffer.clear();
+        state.input_buffer.zeroize();
+    }
+
+    /// Remove the last character from the input buffer (backspace)
+    pub fn pop_buffer(&self) {
+        self.inner.lock().input_buffer.pop();
+    }
+
+    /// Clear the input buffer after the reset timeout
+    ///
+    /// Returns true if this discarded a failed passphrase attempt (characters typed
+    /// while locked that never matched), false for an empty or unlocked buffer.
+    pub fn clear_buffer_after_timeout(&self) -> bool {
+        let mut state = self.inner.lock();
+        let failed_attempt = state.is_locked && !state.input_buffer.is_empty();
+        state.input_buffer.zeroize();
+        failed_attempt
+    }
+
+    /// Set the passphrase length used by `clear_buffer_if_overlong` (None = unknown)
+    pub fn set_passphrase_len(&self, len: Option<usize>) {
+        self.inner.lock().passphrase_len = len;
+    }
+
+    /// Start a cooldown after each full-length wrong attempt (on by default)
+    pub fn set_attempt_cooldown(&self, enabled: bool) {
+        self.inner.lock().attempt_cooldown = enabled;
+    }
+
+    pub fn is_attempt_cooldown_enabled(&self) -> bool {
+        self.inner.lock().attempt_cooldown
+    }
+
+    /// Clear the buffer as soon as it grows past the passphrase length
+    pub fn set_clear_overlong_attempts(&self, enabled: bool) {
+        self.inner.lock().clear_overlong_attempts = enabled;
+    }
+
+    /// Discard a failed attempt that is already longer than the passphrase
+    ///
+    /// While a recovery code is configured the buffer is kept as long as it could still
+    /// be the start of the code, so the code can still be typed. Returns true if the buffer
+    /// was cleared; does nothing unless locked, enabled and the passphrase length is known.
+    pub fn clear_buffer_if_overlong(&self) -> bool {
+        let mut state = self.inner.lock();
+        let Some(passphrase_len) = state.passphrase_len else {
+            return false;
+        };
+        if !state.clear_overlong_attempts || !state.is_locked {
+            return false;
+        }
+        if state.input_buffer.chars().count() <= passphrase_len || state.is_recovery_code_prefix() {
+            return false;
+        }
+        state.input_buffer.zeroize();
+        true
+    }
+
+    /// Start the attempt cooldown once the buffer holds a full-length wrong attempt
+    ///
+    /// Call after the buffer failed to match. Full length is the passphrase length; while
+    /// a recovery code is configured, an attempt that could still be the start of the
+    /// code is kept so the code can still be typed. Clears the buffer and returns true
+    /// when the cooldown starts; does nothing unless enabled, locked and the passphrase
+    /// length is known.
+    pub fn start_attempt_cooldown_if_full_length(&self) -> bool {
+        self.start_attempt_cooldown_if_full_length_at(Instant::now())
+    }
+
+    /// `start_attempt_cooldown_if_full_length` as of `now`
+    pub fn start_attempt_cooldown_if_full_length_at(&self, now: Instant) -> bool {
+        let mut state = self.inner.lock();
+        let Some(passphrase_len) = state.passphrase_len else {
+            return false;
+        };
+        if !state.attempt_cooldown
+            || !state.is_locked
+            || state.input_buffer.chars().count() < passphrase_len
+            || state.is_recovery_code_prefix()
+        {
+            return false;
+        }
+        state.input_buffer.zeroize();
+        state.attempt_cooldown_until = Some(now + Duration::from_secs(ATTEMPT_COOLDOWN_SECONDS));
+        true
+    }
+
+    /// Seconds until passphrase input is accepted again (None when no cooldown is running)
+    pub fn get_attempt_cooldown_remaining(&self) -> Option<u64> {
+        self.get_attempt_cooldown_remaining_at(Instant::now())
+    }
+
+    /// `get_attempt_cooldown_remaining` as of `now`
+    pub fn get_attempt_cooldown_remaining_at(&self, now: Instant) -> Option<u64> {
+        self.inner
+            .lock()
+            .attempt_cooldown_until
+            .map(|until| until.saturating_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+            .map(|remaining| remaining.as_secs_f64().ceil() as u64)
+    }
+
+    /// Number of characters in the input buffer (avoids copying the contents)
+    pub fn buffer_len(&self) -> usize {
+        self.inner.lock().input_buffer.chars().count()
     }
 
-    pub fn get_buffer(&self) -> String {
-        self.inner.lock().input_buffer.clone()
+    /// Copy of the typed passphrase buffer
+    ///
+    /// Returned as a `SecretString`, so logging it shows only the length; log
+    /// `buffer_len()` instead of formatting the buffer.
+    pub fn get_buffer(&self) -> SecretString {
+        SecretString::new(self.inner.lock().input_buffer.clone())
     }
 
     pub fn set_passphrase_hash(&self, hash: String) {
         self.inner.lock().passphrase_hash = Some(hash);
     }
 
+    /// Whether a passphrase is set (input is never locked without one)
+    pub fn has_passphrase(&self) -> bool {
+        self.inner.lock().passphrase_hash.is_some()
+    }
+
     pub fn get_passphrase_hash(&self) -> Option<String> {
         self.inner.lock().passphrase_hash.clone()
     }
 
+    /// Set the one-time recovery code hash (None = no recovery code configured)
+    pub fn set_recovery_code_hash(&self, hash: Option<String>) {
+        self.inner.lock().recovery_code_hash = hash;
+    }
+
+    /// Check if an unused recovery code is configured
+    pub fn has_recovery_code(&self) -> bool {
+        self.inner.lock().recovery_code_hash.is_some()
+    }
+
+    /// Try to unlock with the one-time recovery code
+    ///
+    /// On success the device is unlocked, the stored hash is invalidated (single use),
+    /// and a request is raised for the main thread to persist the cleared config.
+    /// Returns false if no code is configured or the input does not match.
+    pub fn try_recovery_code(&self, input: &str) -> bool {
+        let mut state = self.inner.lock();
+
+        let matches = match state.recovery_code_hash {
+            Some(ref hash) => crate::auth::verify_passphrase(input, hash),
+            None => false,
+        };
+        if !matches {
+            return false;
+        }
+
+        log::warn!("Recovery code accepted - unlocking and invalidating the code");
+        state.recovery_code_hash = None;
+        state.recovery_code_consumed = true;
+        state.is_locked = false;
+        state.reset_lock_session();
+        state.lock_start_time = None;
+        state.last_input_time = Instant::now();
+        state.last_unlock_time = Some(Instant::now());
+        state.input_buffer.zeroize();
+        drop(state);
+
+        self.record_audit(AuditEvent::unlocked(UnlockMethod::RecoveryCode));
+        true
+    }
+
+    /// Check if the consumed recovery code should be persisted and clear the flag
+    pub fn should_persist_recovery_code_consumed_and_clear(&self) -> bool {
+        let mut state = self.inner.lock();
+        let consumed = state.recovery_code_consumed;
+        state.recovery_code_consumed = false;
+        consumed
+    }
+
+    /// Set the input buffer reset timeout in seconds
+    pub fn set_buffer_reset_timeout(&self, timeout_seconds: u64) {
+        self.inner.lock().buffer_reset_timeout = timeout_seconds;
+    }
+
+    /// Get the input buffer reset timeout in seconds
+    pub fn get_buffer_reset_timeout(&self) -> u64 {
+        self.inner.lock().buffer_reset_timeout
+    }
+
     pub fn should_reset_buffer(&self) -> bool {
         let state = self.inner.lock();
         if let Some(last_key) = state.last_key_time {
@@ -150,22 +890,207 @@ impl AppState {
         }
     }
 
+    /// Time left until the buffer resets (None while the buffer is empty)
+    pub fn get_buffer_reset_remaining_ms(&self) -> Option<u64> {
+        let state = self.inner.lock();
+        if state.input_buffer.is_empty() {
+            return None;
+        }
+        let elapsed = state.last_key_time?.elapsed().as_millis() as u64;
+        Some((state.buffer_reset_timeout * 1000).saturating_sub(elapsed))
+    }
+
     pub fn should_auto_lock(&self) -> bool {
         let state = self.inner.lock();
-        // Only auto-lock if: not locked, timeout exceeded, AND permissions are available
-        // This prevents auto-lock from triggering when permissions are lost
+        // Only auto-lock if: not locked, not just unlocked, not snoozed, no media playing,
+        // timeout exceeded, AND permissions are available and a passphrase is set
+        // This prevents auto-lock from triggering when permissions are lost, or locking
+        // input with no passphrase to unlock it
         !state.is_locked
-            && state.last_input_time.elapsed().as_secs() >= state.auto_lock_timeout
+            && !state.is_in_unlock_grace()
+            && !state.is_media_playback_holding_auto_lock()
+            && state
+                .effective_auto_lock_timeout()
+                .zip(state.auto_lock_idle_secs())
+                .is_some_and(|(timeout, idle)| idle >= timeout)
             && state.has_accessibility_permissions
+            && state.passphrase_hash.is_some()
+    }
+
+    /// Set the post-unlock grace period in seconds (0 = none)
+    pub fn set_unlock_grace_secs(&self, secs: u64) {
+        self.inner.lock().unlock_grace_secs = secs;
+    }
+
+    /// Get the post-unlock grace period in seconds
+    pub fn get_unlock_grace_secs(&self) -> u64 {
+        self.inner.lock().unlock_grace_secs
+    }
+
+    /// Set whether active media playback holds off auto-lock
+    pub fn set_respect_media_playback(&self, respect: bool) {
+        let mut state = self.inner.lock();
+        state.respect_media_playback = respect;
+        if !respect {
+            state.media_playback_active = false;
+        }
+    }
+
+    /// Check whether active media playback holds off auto-lock
+    pub fn get_respect_media_playback(&self) -> bool {
+        self.inner.lock().respect_media_playback
+    }
+
+    /// Set how auto-lock depends on the power source
+    pub fn set_power_auto_lock_policy(&self, policy: PowerAutoLockPolicy) {
+        let mut state = self.inner.lock();
+        state.power_auto_lock = policy;
+        if !policy.is_active() {
+            state.power_source = PowerSource::Unknown;
+        }
+    }
+
+    /// Get how auto-lock depends on the power source
+    pub fn get_power_auto_lock_policy(&self) -> PowerAutoLockPolicy {
+        self.inner.lock().power_auto_lock
     }
 
+    /// Update the cached power source (called by the auto-lock check)
+    pub fn set_power_source(&self, source: PowerSource) {
+        self.inner.lock().power_source = source;
+    }
+
+    /// Get the cached power source
+    pub fn get_power_source(&self) -> PowerSource {
+        self.inner.lock().power_source
+    }
+
+    /// Set whether pointer motion (moves and drags) resets the auto-lock timer
+    pub fn set_auto_lock_ignore_mouse_move(&self, ignore: bool) {
+        self.inner.lock().auto_lock_ignore_mouse_move = ignore;
+    }
+
+    /// Check whether pointer motion is ignored for auto-lock
+    pub fn get_auto_lock_ignore_mouse_move(&self) -> bool {
+        self.inner.lock().auto_lock_ignore_mouse_move
+    }
+
+    /// Set cached media playback state (called by the auto-lock check)
+    pub fn set_media_playback_active(&self, active: bool) {
+        self.inner.lock().media_playback_active = active;
+    }
+
+    /// Get cached media playback state
+    pub fn is_media_playback_active(&self) -> bool {
+        self.inner.lock().media_playback_active
+    }
+
+    /// Check whether auto-lock is currently paused because media is playing
+    pub fn is_auto_lock_paused_for_media(&self) -> bool {
+        self.inner.lock().is_media_playback_holding_auto_lock()
+    }
+
+    /// Suspend auto-lock for `duration` (replaces any existing snooze)
+    pub fn snooze_auto_lock(&self, duration: Duration) {
+        let mut state = self.inner.lock();
+        state.snooze_until = Some(Instant::now() + duration);
+        state.auto_lock_warned = false;
+    }
+
+    /// End an auto-lock snooze early
+    pub fn clear_auto_lock_snooze(&self) {
+        self.inner.lock().snooze_until = None;
+    }
+
+    /// Seconds until an auto-lock snooze ends (None if not snoozed)
+    pub fn get_snooze_remaining_secs(&self) -> Option<u64> {
+        let state = self.inner.lock();
+        state
+            .snooze_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+            .map(|remaining| remaining.as_secs_f64().ceil() as u64)
+    }
+
+    /// Re-enable after `duration` (the caller disables; replaces any existing pause)
+    pub fn pause_for(&self, duration: Duration) {
+        self.inner.lock().pause_until = Some(Instant::now() + duration);
+    }
+
+    /// Forget a pending pause (on resume, or when enabled/disabled manually)
+    pub fn clear_pause(&self) {
+        self.inner.lock().pause_until = None;
+    }
+
+    /// Seconds until a pause ends (None if not paused)
+    pub fn get_pause_remaining_secs(&self) -> Option<u64> {
+        self.inner
+            .lock()
+            .pause_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .map(|remaining| remaining.as_secs_f64().ceil() as u64)
+    }
+
+    /// Whether a pause has run out and HandsOff should be re-enabled
+    pub fn is_pause_expired(&self) -> bool {
+        self.is_pause_expired_at(Instant::now())
+    }
+
+    /// `is_pause_expired` with an explicit timestamp (for tests)
+    pub fn is_pause_expired_at(&self, now: Instant) -> bool {
+        self.inner.lock().pause_until.is_some_and(|until| now >= until)
+    }
+
+    /// Set how many seconds before auto-lock the user is warned (0 = no warning)
+    pub fn set_auto_lock_warning_secs(&self, secs: u64) {
+        let mut state = self.inner.lock();
+        state.auto_lock_warning_secs = secs;
+        state.auto_lock_warned = false;
+    }
+
+    /// Get the auto-lock warning lead time in seconds (0 = no warning)
+    pub fn get_auto_lock_warning_secs(&self) -> u64 {
+        self.inner.lock().auto_lock_warning_secs
+    }
+
+    /// Check whether the pre-lock warning is due, marking it as shown
+    ///
+    /// Returns the seconds remaining until auto-lock at most once per idle period;
+    /// any input (`update_input_time`) or lock change re-arms the warning.
+    pub fn take_auto_lock_warning(&self) -> Option<u64> {
+        let mut state = self.inner.lock();
+        if state.is_locked
+            || state.auto_lock_warned
+            || state.auto_lock_warning_secs == 0
+            || state.is_in_unlock_grace()
+            || state.is_media_playback_holding_auto_lock()
+            || !state.has_accessibility_permissions
+            || state.passphrase_hash.is_none()
+        {
+            return None;
+        }
+
+        let timeout = state.effective_auto_lock_timeout()?;
+        let idle = state.auto_lock_idle_secs()?;
+        let remaining = timeout.saturating_sub(idle);
+        if remaining == 0 || remaining > state.auto_lock_warning_secs {
+            return None;
+        }
+
+        state.auto_lock_warned = true;
+        Some(remaining)
+    }
+
+    /// Seconds until auto-lock (None while locked, snoozed, disabled or off on AC power)
     pub fn get_auto_lock_remaining_secs(&self) -> Option<u64> {
         let state = self.inner.lock();
-        if state.is_locked {
+        // Auto-lock isn't checked while disabled, so there's no countdown to show
+        if state.is_locked || state.is_disabled {
             return None;
         }
-        let elapsed = state.last_input_time.elapsed().as_secs();
-        Some(state.auto_lock_timeout.saturating_sub(elapsed))
+        let timeout = state.effective_auto_lock_timeout()?;
+        let idle = state.auto_lock_idle_secs()?;
+        Some(timeout.saturating_sub(idle))
     }
 
     pub fn set_talk_key_pressed(&self, pressed: bool) {
@@ -176,6 +1101,28 @@ impl AppState {
         self.inner.lock().talk_key_pressed
     }
 
+    /// Set whether the Talk hotkey works while held or latches on/off
+    pub fn set_talk_mode(&self, mode: TalkMode) {
+        let mut state = self.inner.lock();
+        state.talk_mode = mode;
+        state.talk_key_pressed = false;
+    }
+
+    /// Get whether the Talk hotkey works while held or latches on/off
+    pub fn get_talk_mode(&self) -> TalkMode {
+        self.inner.lock().talk_mode
+    }
+
+    /// Set what happens when accessibility permissions are lost
+    pub fn set_permission_loss_policy(&self, policy: PermissionLossPolicy) {
+        self.inner.lock().permission_loss_policy = policy;
+    }
+
+    /// Get what happens when accessibility permissions are lost
+    pub fn get_permission_loss_policy(&self) -> PermissionLossPolicy {
+        self.inner.lock().permission_loss_policy
+    }
+
     /// Sets the auto-unlock timeout (called at startup)
     pub fn set_auto_unlock_timeout(&self, timeout_seconds: Option<u64>) {
         let mut state = self.inner.lock();
@@ -209,24 +1156,78 @@ impl AppState {
 
     /// Trigger auto-unlock (called by background thread)
     pub fn trigger_auto_unlock(&self) {
-        let mut state = self.inner.lock();
-
-        if state.is_locked {
-            let elapsed = state
-                .lock_start_time
-                .map(|t| t.elapsed().as_secs())
-                .unwrap_or(0);
-
+        if let Some(elapsed) = self.force_unlock() {
             log::warn!("AUTO-UNLOCK TRIGGERED after {} seconds", elapsed);
+            self.record_audit(AuditEvent::AutoUnlock {
+                locked_for_secs: elapsed,
+            });
+        }
+    }
 
-            // Reset last_input_time for fresh auto-lock countdown
-            // Note: if don't do this first, auto-lock may kick in right after unlock
-            state.last_input_time = Instant::now();
+    /// Sets the max lock duration hard cap (called at startup)
+    pub fn set_max_lock_duration_secs(&self, secs: u64) {
+        self.inner.lock().max_lock_duration_secs = secs;
+    }
 
-            state.is_locked = false;
-            state.lock_start_time = None;
-            state.input_buffer.clear();
-        }
+    /// Get the max lock duration hard cap in seconds
+    pub fn get_max_lock_duration_secs(&self) -> u64 {
+        self.inner.lock().max_lock_duration_secs
+    }
+
+    /// Get remaining time until the max lock duration hard cap (in seconds)
+    /// Returns None if not locked or the cap is disabled
+    pub fn get_max_lock_duration_remaining_secs(&self) -> Option<u64> {
+        let state = self.inner.lock();
+        if !state.is_locked || state.max_lock_duration_secs == 0 {
+            return None;
+        }
+        let elapsed = state.lock_start_time?.elapsed().as_secs();
+        Some(state.max_lock_duration_secs.saturating_sub(elapsed))
+    }
+
+    /// Check if input has been locked for the max lock duration (independent of auto-unlock)
+    pub fn should_enforce_max_lock_duration(&self) -> bool {
+        let state = self.inner.lock();
+        if !state.is_locked || state.max_lock_duration_secs == 0 {
+            return false;
+        }
+        state
+            .lock_start_time
+            .is_some_and(|t| t.elapsed() >= Duration::from_secs(state.max_lock_duration_secs))
+    }
+
+    /// Force-unlock once the max lock duration hard cap is reached (called by background thread)
+    pub fn trigger_max_lock_duration_unlock(&self) {
+        if let Some(elapsed) = self.force_unlock() {
+            log::warn!("HARD CAP UNLOCK TRIGGERED after {} seconds (max lock duration)", elapsed);
+            self.record_audit(AuditEvent::MaxLockDuration {
+                locked_for_secs: elapsed,
+            });
+        }
+    }
+
+    /// Unlock without a passphrase; returns how long input was locked (None if it wasn't)
+    fn force_unlock(&self) -> Option<u64> {
+        let mut state = self.inner.lock();
+        if !state.is_locked {
+            return None;
+        }
+        let elapsed = state
+            .lock_start_time
+            .map(|t| t.elapsed().as_secs())
+            .unwrap_or(0);
+
+        // Reset last_input_time for fresh auto-lock countdown
+        // Note: if don't do this first, auto-lock may kick in right after unlock
+        state.last_input_time = Instant::now();
+
+        state.is_locked = false;
+        // Leave the user in a clean state: no attempt cooldown or partial unlock sequence
+        state.reset_lock_session();
+        state.lock_start_time = None;
+        state.last_unlock_time = Some(Instant::now());
+        state.input_buffer.zeroize();
+        Some(elapsed)
     }
 
     /// Get the elapsed time since lock was engaged (in seconds)
@@ -236,12 +1237,12 @@ impl AppState {
     }
 
     /// Get remaining time until auto-unlock (in seconds)
-    /// Returns None if not locked, auto-unlock disabled, or no lock start time
+    /// Returns None if not locked, auto-unlock disabled, no lock start time, or HandsOff disabled
     pub fn get_auto_unlock_remaining_secs(&self) -> Option<u64> {
         let state = self.inner.lock();
 
-        // Must be locked with auto-unlock enabled
-        if !state.is_locked || state.auto_unlock_timeout.is_none() {
+        // Must be locked with auto-unlock enabled (and not skipped because HandsOff is disabled)
+        if !state.is_locked || state.auto_unlock_timeout.is_none() || state.is_disabled {
             return None;
         }
 
@@ -261,11 +1262,60 @@ impl AppState {
         self.inner.lock().has_accessibility_permissions
     }
 
-    /// Set cached accessibility permissions state (called by permission monitor thread)
+    /// Set cached accessibility permissions state (called by the permission check)
     pub fn set_cached_accessibility_permissions(&self, has_permissions: bool) {
         self.inner.lock().has_accessibility_permissions = has_permissions;
     }
 
+    /// Count an accessibility permission change (called by the permission check)
+    pub fn record_permission_transition(&self) {
+        let mut state = self.inner.lock();
+        state.permission_transitions = state.permission_transitions.saturating_add(1);
+    }
+
+    /// Accessibility permission changes seen this session (flapping shows as a high count)
+    pub fn permission_transition_count(&self) -> u64 {
+        self.inner.lock().permission_transitions
+    }
+
+    /// Record that the event loop is alive (called by the CFRunLoop thread each poll)
+    pub fn pet_watchdog(&self) {
+        self.inner.lock().event_loop_heartbeat = Some(Instant::now());
+    }
+
+    /// Forget the heartbeat when the event loop stops, so it isn't seen as stalled
+    pub fn clear_watchdog_heartbeat(&self) {
+        self.inner.lock().event_loop_heartbeat = None;
+    }
+
+    /// Set the heartbeat age in seconds after which the watchdog force-unlocks
+    pub fn set_watchdog_timeout_secs(&self, secs: u64) {
+        self.inner.lock().watchdog_timeout_secs = secs;
+    }
+
+    /// Get the watchdog timeout in seconds
+    pub fn get_watchdog_timeout_secs(&self) -> u64 {
+        self.inner.lock().watchdog_timeout_secs
+    }
+
+    /// Whether input is locked while the event loop has stopped sending heartbeats
+    pub fn is_event_loop_stalled(&self) -> bool {
+        self.is_event_loop_stalled_at(Instant::now())
+    }
+
+    /// `is_event_loop_stalled` with an explicit timestamp (for tests)
+    pub fn is_event_loop_stalled_at(&self, now: Instant) -> bool {
+        self.is_locked() && self.is_event_loop_heartbeat_stale_at(now)
+    }
+
+    /// Whether the running event loop's last heartbeat is older than the watchdog timeout
+    pub fn is_event_loop_heartbeat_stale_at(&self, now: Instant) -> bool {
+        let state = self.inner.lock();
+        state.event_loop_heartbeat.is_some_and(|beat| {
+            now.saturating_duration_since(beat) > Duration::from_secs(state.watchdog_timeout_secs)
+        })
+    }
+
     /// Request event tap to be stopped (called by permission monitor when permissions lost)
     pub fn request_stop_event_tap(&self) {
         self.inner.lock().should_stop_event_tap = true;
@@ -332,7 +1382,10 @@ impl AppState {
         self.inner.lock().last_reenable_time = Some(Instant::now());
     }
 
-    /// Request that the application exit (CLI only)
+    /// Request that the application exit (permissions lost while running)
+    ///
+    /// The CLI exits according to its permission loss policy; the tray app only exits
+    /// under `PermissionLossPolicy::Exit`.
     pub fn request_exit(&self) {
         self.inner.lock().should_exit = true;
     }
@@ -345,48 +1398,712 @@ impl AppState {
         should_exit
     }
 
-    /// Check if the app is currently disabled
-    pub fn is_disabled(&self) -> bool {
-        self.inner.lock().is_disabled
+    /// Flag for signal handlers to set to request a clean shutdown (CLI only)
+    ///
+    /// Register it with `signal_hook::flag::register`; the handler then only stores to
+    /// the atomic, which is async-signal-safe.
+    pub fn shutdown_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.shutdown_requested)
+    }
+
+    /// Check if a clean shutdown was requested (by SIGINT/SIGTERM)
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.shutdown_requested.load(Ordering::SeqCst)
+    }
+
+    /// Request that the main thread disable HandsOff
+    pub fn request_disable(&self) {
+        let mut state = self.inner.lock();
+        state.should_disable = true;
+        state.should_enable = false;
+    }
+
+    /// Check if HandsOff should be disabled and clear the flag
+    pub fn should_disable_and_clear(&self) -> bool {
+        let mut state = self.inner.lock();
+        let should_disable = state.should_disable;
+        state.should_disable = false;
+        should_disable
+    }
+
+    /// Request that the main thread re-enable HandsOff
+    pub fn request_enable(&self) {
+        let mut state = self.inner.lock();
+        state.should_enable = true;
+        state.should_disable = false;
+    }
+
+    /// Check if HandsOff should be re-enabled and clear the flag
+    pub fn should_enable_and_clear(&self) -> bool {
+        let mut state = self.inner.lock();
+        let should_enable = state.should_enable;
+        state.should_enable = false;
+        should_enable
+    }
+
+    /// Check if the app is currently disabled
+    pub fn is_disabled(&self) -> bool {
+        self.inner.lock().is_disabled
+    }
+
+    /// Set the disabled state
+    pub fn set_disabled(&self, disabled: bool) {
+        self.inner.lock().is_disabled = disabled;
+    }
+
+    /// Set the lock hotkey keycode (macOS keycode, None = lock hotkey disabled)
+    pub fn set_lock_keycode(&self, keycode: Option<i64>) {
+        self.inner.lock().lock_keycode = keycode;
+    }
+
+    /// Set the talk hotkey keycode (macOS keycode, None = talk hotkey disabled)
+    pub fn set_talk_keycode(&self, keycode: Option<i64>) {
+        self.inner.lock().talk_keycode = keycode;
+    }
+
+    /// Get the lock hotkey keycode (macOS keycode, None when disabled)
+    pub fn get_lock_keycode(&self) -> Option<i64> {
+        self.inner.lock().lock_keycode
+    }
+
+    /// Get the talk hotkey keycode (macOS keycode, None when disabled)
+    pub fn get_talk_keycode(&self) -> Option<i64> {
+        self.inner.lock().talk_keycode
+    }
+
+    /// Set the modifiers held with the lock hotkey key
+    pub fn set_lock_modifiers(&self, modifiers: HotkeyModifiers) {
+        self.inner.lock().lock_modifiers = modifiers;
+    }
+
+    /// Get the modifiers held with the lock hotkey key
+    pub fn get_lock_modifiers(&self) -> HotkeyModifiers {
+        self.inner.lock().lock_modifiers
+    }
+
+    /// Set the modifiers held with the talk hotkey key
+    pub fn set_talk_modifiers(&self, modifiers: HotkeyModifiers) {
+        self.inner.lock().talk_modifiers = modifiers;
+    }
+
+    /// Get the modifiers held with the talk hotkey key
+    pub fn get_talk_modifiers(&self) -> HotkeyModifiers {
+        self.inner.lock().talk_modifiers
+    }
+
+    /// Require a second lock hotkey press within `window` to lock (None = single press)
+    pub fn set_lock_double_press_window(&self, window: Option<Duration>) {
+        let mut state = self.inner.lock();
+        state.lock_double_press_window = window;
+        state.last_lock_hotkey_press = None;
+    }
+
+    /// Get the double-press window for the lock hotkey (None = single press)
+    pub fn get_lock_double_press_window(&self) -> Option<Duration> {
+        self.inner.lock().lock_double_press_window
+    }
+
+    /// Record a lock hotkey press and decide whether it should engage lock
+    ///
+    /// Always true in single-press mode. In double-press mode only the second press
+    /// within the window locks; presses closer together than
+    /// LOCK_DOUBLE_PRESS_MIN_GAP_MS count as the same press.
+    pub fn register_lock_hotkey_press(&self) -> bool {
+        self.register_lock_hotkey_press_at(Instant::now())
+    }
+
+    /// `register_lock_hotkey_press` with an explicit timestamp (for tests)
+    pub fn register_lock_hotkey_press_at(&self, now: Instant) -> bool {
+        let mut state = self.inner.lock();
+        let Some(window) = state.lock_double_press_window else {
+            return true;
+        };
+
+        match state.last_lock_hotkey_press {
+            Some(last) => {
+                let gap = now.saturating_duration_since(last);
+                if gap < Duration::from_millis(LOCK_DOUBLE_PRESS_MIN_GAP_MS) {
+                    false
+                } else if gap <= window {
+                    state.last_lock_hotkey_press = None;
+                    true
+                } else {
+                    state.last_lock_hotkey_press = Some(now);
+                    false
+                }
+            }
+            None => {
+                state.last_lock_hotkey_press = Some(now);
+                false
+            }
+        }
+    }
+
+    /// Publish the IDs of the registered global hotkeys to the listener thread
+    pub fn set_hotkey_ids(&self, ids: HotkeyIds) {
+        self.inner.lock().hotkey_ids = ids;
+    }
+
+    /// Get the IDs of the registered global hotkeys
+    pub fn get_hotkey_ids(&self) -> HotkeyIds {
+        self.inner.lock().hotkey_ids
+    }
+
+    /// Configure the emergency unlock hotkey (keycode None disables it)
+    pub fn set_panic_hotkey(&self, keycode: Option<i64>, modifiers: HotkeyModifiers) {
+        let mut inner = self.inner.lock();
+        inner.panic_keycode = keycode;
+        inner.panic_modifiers = modifiers;
+    }
+
+    /// Get the emergency unlock hotkey keycode (None when disabled)
+    pub fn get_panic_keycode(&self) -> Option<i64> {
+        self.inner.lock().panic_keycode
+    }
+
+    /// Get the modifiers held with the emergency unlock hotkey key
+    pub fn get_panic_modifiers(&self) -> HotkeyModifiers {
+        self.inner.lock().panic_modifiers
+    }
+
+    /// Set whether the emergency unlock hotkey requires Touch ID
+    pub fn set_panic_requires_touchid(&self, required: bool) {
+        self.inner.lock().panic_requires_touchid = required;
+    }
+
+    /// Check whether the emergency unlock hotkey requires Touch ID
+    pub fn get_panic_requires_touchid(&self) -> bool {
+        self.inner.lock().panic_requires_touchid
+    }
+
+    /// Set the unlock chord sequence (None = disabled)
+    pub fn set_unlock_chord(&self, chord: Option<UnlockChord>) {
+        let mut state = self.inner.lock();
+        state.unlock_chord = chord;
+        state.chord_recognizer.reset();
+    }
+
+    /// Get the unlock chord sequence (None = disabled)
+    pub fn get_unlock_chord(&self) -> Option<UnlockChord> {
+        self.inner.lock().unlock_chord
+    }
+
+    /// Feed the modifiers held after a FlagsChanged event to the unlock sequence
+    ///
+    /// Returns true when the sequence completes; always false while unlocked or when
+    /// no unlock chord is configured.
+    pub fn record_unlock_chord(&self, held: HotkeyModifiers) -> bool {
+        self.record_unlock_chord_at(held, Instant::now())
+    }
+
+    /// `record_unlock_chord` as of `now`
+    pub fn record_unlock_chord_at(&self, held: HotkeyModifiers, now: Instant) -> bool {
+        let mut state = self.inner.lock();
+        let Some(chord) = state.unlock_chord.filter(|_| state.is_locked) else {
+            return false;
+        };
+        state.chord_recognizer.record(chord, held, now)
+    }
+
+    /// Start the unlock sequence over (a non-modifier key was pressed)
+    pub fn reset_unlock_chord(&self) {
+        self.inner.lock().chord_recognizer.reset();
+    }
+
+    /// Set the hold-to-unlock key and duration (None = disabled)
+    pub fn set_hold_unlock(&self, hold: Option<HoldUnlock>) {
+        let mut state = self.inner.lock();
+        state.hold_unlock = hold;
+        state.hold_recognizer.release();
+    }
+
+    /// Get the hold-to-unlock key and duration (None = disabled)
+    pub fn get_hold_unlock(&self) -> Option<HoldUnlock> {
+        self.inner.lock().hold_unlock
+    }
+
+    /// Record a KeyDown of the hold-to-unlock key (auto-repeats included)
+    ///
+    /// Returns true when the key has been held long enough; always false while unlocked
+    /// or when hold-to-unlock is disabled.
+    pub fn press_hold_unlock_key(&self) -> bool {
+        self.press_hold_unlock_key_at(Instant::now())
+    }
+
+    /// `press_hold_unlock_key` as of `now`
+    pub fn press_hold_unlock_key_at(&self, now: Instant) -> bool {
+        let mut state = self.inner.lock();
+        let Some(hold) = state.hold_unlock.filter(|_| state.is_locked) else {
+            return false;
+        };
+        state.hold_recognizer.press(hold, now)
+    }
+
+    /// Record a KeyUp of the hold-to-unlock key: the hold is cancelled
+    pub fn release_hold_unlock_key(&self) {
+        self.inner.lock().hold_recognizer.release();
+    }
+
+    /// Check a hold in progress (for the scheduler, when the key doesn't auto-repeat)
+    ///
+    /// `is_key_down` reports whether the key is really still held; if it isn't, the
+    /// KeyUp was missed and the hold is cancelled. Returns true when the hold completes.
+    pub fn poll_hold_unlock_with<F>(&self, is_key_down: F) -> bool
+    where
+        F: FnOnce(i64) -> bool,
+    {
+        self.poll_hold_unlock_with_at(is_key_down, Instant::now())
+    }
+
+    /// `poll_hold_unlock_with` as of `now`
+    pub fn poll_hold_unlock_with_at<F>(&self, is_key_down: F, now: Instant) -> bool
+    where
+        F: FnOnce(i64) -> bool,
+    {
+        let mut state = self.inner.lock();
+        let Some(hold) = state.hold_unlock.filter(|_| state.is_locked) else {
+            return false;
+        };
+        if !state.hold_recognizer.is_holding() {
+            return false;
+        }
+        if !is_key_down(hold.keycode) {
+            state.hold_recognizer.release();
+            return false;
+        }
+        state.hold_recognizer.poll(hold, now)
+    }
+
+    /// Fraction of the hold-to-unlock hold done, 0.0-1.0 (None = no hold in progress)
+    pub fn get_hold_unlock_progress(&self) -> Option<f64> {
+        let state = self.inner.lock();
+        let hold = state.hold_unlock.filter(|_| state.is_locked)?;
+        state.hold_recognizer.progress(hold, Instant::now())
+    }
+
+    /// Time left until the hold-to-unlock hold completes (None = no hold in progress)
+    pub fn get_hold_unlock_remaining(&self) -> Option<Duration> {
+        let state = self.inner.lock();
+        let hold = state.hold_unlock.filter(|_| state.is_locked)?;
+        state.hold_recognizer.remaining(hold, Instant::now())
+    }
+
+    /// Set the keyboard layout used for passphrase entry
+    pub fn set_keyboard_layout(&self, layout: KeyboardLayout) {
+        self.inner.lock().keyboard_layout = layout;
+    }
+
+    /// Get the keyboard layout used for passphrase entry
+    pub fn get_keyboard_layout(&self) -> KeyboardLayout {
+        self.inner.lock().keyboard_layout
+    }
+
+    /// Set the keycode passed through while the Talk hotkey is held
+    pub fn set_talk_passthrough_keycode(&self, keycode: i64) {
+        self.inner.lock().talk_passthrough_keycode = keycode;
+    }
+
+    /// Get the keycode passed through while the Talk hotkey is held
+    pub fn get_talk_passthrough_keycode(&self) -> i64 {
+        self.inner.lock().talk_passthrough_keycode
+    }
+
+    /// Set the Touch ID unlock hotkey keycode (macOS keycode)
+    pub fn set_unlock_keycode(&self, keycode: i64) {
+        self.inner.lock().unlock_keycode = keycode;
+    }
+
+    /// Get the Touch ID unlock hotkey keycode (macOS keycode)
+    pub fn get_unlock_keycode(&self) -> i64 {
+        self.inner.lock().unlock_keycode
+    }
+
+    /// Set whether media/volume/brightness keys are blocked while locked
+    pub fn set_block_media_keys(&self, block: bool) {
+        self.inner.lock().block_media_keys = block;
+    }
+
+    /// Check whether media/volume/brightness keys are blocked while locked
+    pub fn get_block_media_keys(&self) -> bool {
+        self.inner.lock().block_media_keys
+    }
+
+    /// Install (or with None, remove) a custom event filter
+    pub fn set_event_filter(&self, filter: Option<EventFilter>) {
+        self.inner.lock().event_filter = filter.map(Arc::from);
+    }
+
+    /// The installed event filter, cloned out so it runs without holding the state lock
+    pub fn get_event_filter(&self) -> Option<Arc<EventFilterFn>> {
+        self.inner.lock().event_filter.clone()
+    }
+
+    /// Set whether the power / Touch ID key is blocked while locked
+    pub fn set_block_power_key(&self, block: bool) {
+        self.inner.lock().block_power_key = block;
+    }
+
+    /// Check whether the power / Touch ID key is blocked while locked
+    pub fn get_block_power_key(&self) -> bool {
+        self.inner.lock().block_power_key
+    }
+
+    /// Set dry-run mode (nothing is blocked; would-be blocks are logged)
+    pub fn set_dry_run(&self, dry_run: bool) {
+        self.inner.lock().dry_run = dry_run;
+    }
+
+    /// Check whether dry-run mode is enabled
+    pub fn is_dry_run(&self) -> bool {
+        self.inner.lock().dry_run
+    }
+
+    /// Beep when a failed passphrase attempt is discarded
+    pub fn set_feedback_sound(&self, enabled: bool) {
+        self.inner.lock().feedback_sound = enabled;
+    }
+
+    pub fn is_feedback_sound_enabled(&self) -> bool {
+        self.inner.lock().feedback_sound
+    }
+
+    /// Set which input devices are blocked while locked
+    pub fn set_lock_scope(&self, scope: LockScope) {
+        self.inner.lock().lock_scope = scope;
+    }
+
+    /// Get which input devices are blocked while locked
+    pub fn get_lock_scope(&self) -> LockScope {
+        self.inner.lock().lock_scope
+    }
+
+    /// Set the bundle identifiers of apps that stay interactive while locked
+    pub fn set_app_allowlist(&self, allowlist: Vec<String>) {
+        let mut state = self.inner.lock();
+        state.app_allowlist = allowlist
+            .into_iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+        state.bundle_id_cache.clear();
+    }
+
+    /// Get the bundle identifiers of apps that stay interactive while locked
+    pub fn get_app_allowlist(&self) -> Vec<String> {
+        self.inner.lock().app_allowlist.clone()
+    }
+
+    /// Mark a Touch ID prompt as started
+    ///
+    /// Returns false if a prompt is already in progress (caller should not start another).
+    pub fn try_begin_touchid(&self) -> bool {
+        let mut state = self.inner.lock();
+        if state.touchid_in_progress {
+            return false;
+        }
+        state.touchid_in_progress = true;
+        true
+    }
+
+    /// Mark the current Touch ID prompt as finished
+    pub fn finish_touchid(&self) {
+        self.inner.lock().touchid_in_progress = false;
+    }
+
+    /// Set whether unlocking requires Touch ID after the passphrase
+    pub fn set_two_factor_unlock(&self, enabled: bool) {
+        self.inner.lock().two_factor_unlock = enabled;
+    }
+
+    /// Check whether unlocking requires Touch ID after the passphrase
+    pub fn get_two_factor_unlock(&self) -> bool {
+        self.inner.lock().two_factor_unlock
+    }
+
+    /// Record a verified passphrase in two-factor mode (input stays locked)
+    ///
+    /// Returns false if input isn't locked, so there's nothing to wait for.
+    pub fn begin_biometric_wait(&self) -> bool {
+        let mut state = self.inner.lock();
+        state.awaiting_biometric = state.is_locked;
+        state.awaiting_biometric
+    }
+
+    /// Check whether a verified passphrase is waiting for Touch ID
+    pub fn is_awaiting_biometric(&self) -> bool {
+        self.inner.lock().awaiting_biometric
+    }
+
+    /// Complete a two-factor unlock with the Touch ID result, returning whether input unlocked
+    ///
+    /// Fails closed: a failed, cancelled or unavailable Touch ID discards the verified
+    /// passphrase, so it has to be typed again.
+    pub fn finish_biometric_wait(&self, touchid_verified: bool) -> bool {
+        let awaiting = std::mem::take(&mut self.inner.lock().awaiting_biometric);
+        if !(awaiting && touchid_verified && self.is_locked()) {
+            return false;
+        }
+        self.unlock_with(UnlockMethod::TwoFactor);
+        true
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_new_uses_centralized_defaults() {
+        let state = AppState::new();
+        assert_eq!(state.lock().auto_lock_timeout, crate::constants::AUTO_LOCK_DEFAULT_SECONDS);
+        assert_eq!(state.get_buffer_reset_timeout(), crate::constants::BUFFER_RESET_DEFAULT_SECONDS);
+        assert_eq!(state.get_unlock_grace_secs(), crate::constants::UNLOCK_GRACE_DEFAULT_SECONDS);
+        assert_eq!(state.lock().max_buffer_len, crate::constants::MAX_BUFFER_LEN_DEFAULT);
+        assert_eq!(state.get_lock_keycode(), Some(crate::constants::DEFAULT_LOCK_KEYCODE));
+        assert_eq!(state.get_talk_keycode(), Some(crate::constants::DEFAULT_TALK_KEYCODE));
+        assert_eq!(state.get_unlock_keycode(), crate::constants::DEFAULT_UNLOCK_KEYCODE);
+        assert_eq!(state.get_talk_passthrough_keycode(), crate::constants::SPACEBAR_KEYCODE);
+        assert_eq!(state.get_auto_unlock_timeout(), None);
+    }
+
+    #[test]
+    fn test_auto_lock_warning_fires_once_per_idle_period() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        state.lock().auto_lock_timeout = 3; // shrunken for testing
+        state.set_auto_lock_warning_secs(2);
+
+        // 3 seconds remaining: outside the 2 second warning window
+        assert_eq!(state.take_auto_lock_warning(), None);
+
+        thread::sleep(Duration::from_millis(1100));
+        assert_eq!(state.take_auto_lock_warning(), Some(2));
+        assert_eq!(state.take_auto_lock_warning(), None, "Warning is one-shot");
+
+        // Input cancels the pending lock and re-arms the warning
+        state.update_input_time();
+        assert_eq!(state.take_auto_lock_warning(), None);
+        thread::sleep(Duration::from_millis(1100));
+        assert_eq!(state.take_auto_lock_warning(), Some(2));
+    }
+
+    #[test]
+    fn test_auto_lock_snooze_suspends_and_resumes() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        state.lock().auto_lock_timeout = 1; // shrunken for testing
+
+        thread::sleep(Duration::from_millis(1100));
+        assert!(state.should_auto_lock());
+
+        state.snooze_auto_lock(Duration::from_millis(500));
+        assert!(!state.should_auto_lock(), "Snooze should suspend auto-lock");
+        assert_eq!(state.get_auto_lock_remaining_secs(), None);
+        assert_eq!(state.get_snooze_remaining_secs(), Some(1));
+
+        // After the snooze ends, the countdown restarts from the end of the snooze
+        thread::sleep(Duration::from_millis(600));
+        assert_eq!(state.get_snooze_remaining_secs(), None);
+        assert!(!state.should_auto_lock(), "Countdown should restart after snooze");
+        thread::sleep(Duration::from_millis(1000));
+        assert!(state.should_auto_lock(), "Auto-lock should resume after snooze");
+    }
+
+    #[test]
+    fn test_no_auto_lock_within_unlock_grace() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        state.lock().auto_lock_timeout = 0; // tiny timeout: would lock on every check
+        state.set_unlock_grace_secs(1);
+
+        state.set_locked(true);
+        state.set_locked(false);
+        assert!(!state.should_auto_lock(), "No auto-lock right after manual unlock");
+
+        thread::sleep(Duration::from_millis(1100));
+        assert!(state.should_auto_lock(), "Auto-lock allowed once grace expires");
+
+        state.set_locked(true);
+        state.trigger_auto_unlock();
+        assert!(!state.should_auto_lock(), "No auto-lock right after auto-unlock");
+    }
+
+    #[test]
+    fn test_blocked_event_count_resets_on_new_lock() {
+        let state = AppState::new();
+        state.set_locked(true);
+        state.record_blocked_event();
+        state.record_blocked_event();
+        assert_eq!(state.get_blocked_event_count(), 2);
+
+        // Still readable after unlocking (for the unlock notification)
+        state.set_locked(false);
+        assert_eq!(state.get_blocked_event_count(), 2);
+
+        state.set_locked(true);
+        assert_eq!(state.get_blocked_event_count(), 0, "New lock starts from zero");
+        state.record_blocked_event();
+        state.set_locked(true);
+        assert_eq!(state.get_blocked_event_count(), 1, "Re-locking while locked keeps counting");
+    }
+
+    #[test]
+    fn test_max_lock_duration_without_auto_unlock() {
+        let state = AppState::new();
+        assert_eq!(state.get_auto_unlock_timeout(), None);
+        assert_eq!(
+            state.get_max_lock_duration_secs(),
+            MAX_LOCK_DURATION_DEFAULT_MINUTES * 60,
+            "Hard cap is on by default"
+        );
+        state.set_max_lock_duration_secs(1);
+        assert_eq!(state.get_max_lock_duration_remaining_secs(), None, "Not locked");
+
+        state.set_locked(true);
+        assert!(!state.should_enforce_max_lock_duration(), "Not right after locking");
+        assert_eq!(state.get_max_lock_duration_remaining_secs(), Some(1));
+
+        thread::sleep(Duration::from_millis(1100));
+        assert_eq!(state.get_max_lock_duration_remaining_secs(), Some(0));
+        assert!(!state.should_auto_unlock(), "Auto-unlock stays off");
+        assert!(
+            state.should_enforce_max_lock_duration(),
+            "Hard cap triggers even with auto-unlock disabled"
+        );
+
+        state.trigger_max_lock_duration_unlock();
+        assert!(!state.is_locked());
+        assert_eq!(state.get_lock_elapsed_secs(), None);
+        assert!(!state.should_enforce_max_lock_duration(), "Nothing to enforce once unlocked");
     }
 
-    /// Set the disabled state
-    pub fn set_disabled(&self, disabled: bool) {
-        self.inner.lock().is_disabled = disabled;
+    #[test]
+    fn test_no_auto_lock_without_passphrase() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.lock().auto_lock_timeout = 0; // idle timeout already exceeded
+        state.set_unlock_grace_secs(0);
+        state.set_auto_lock_warning_secs(AUTO_LOCK_WARNING_MAX_SECONDS);
+        assert!(!state.has_passphrase());
+        assert!(!state.should_auto_lock(), "Nothing could unlock it");
+        assert_eq!(state.take_auto_lock_warning(), None);
+
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        assert!(state.should_auto_lock());
     }
 
-    /// Set the lock hotkey keycode (macOS keycode)
-    pub fn set_lock_keycode(&self, keycode: i64) {
-        self.inner.lock().lock_keycode = keycode;
+    #[test]
+    fn test_auto_lock_follows_power_source() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        state.set_unlock_grace_secs(0);
+        state.set_power_auto_lock_policy(PowerAutoLockPolicy {
+            timeout_on_battery: Some(0), // shrunken for testing
+            lock_on_ac: true,
+        });
+
+        state.set_power_source(PowerSource::Ac);
+        assert!(!state.should_auto_lock(), "The regular timeout applies on AC");
+        state.set_power_source(PowerSource::Battery);
+        assert!(state.should_auto_lock());
+        assert_eq!(state.get_auto_lock_remaining_secs(), Some(0));
+
+        // Only on battery: no auto-lock (and no countdown) while plugged in
+        state.lock().auto_lock_timeout = 0;
+        state.set_power_auto_lock_policy(PowerAutoLockPolicy {
+            timeout_on_battery: None,
+            lock_on_ac: false,
+        });
+        state.set_power_source(PowerSource::Ac);
+        assert!(!state.should_auto_lock());
+        assert_eq!(state.get_auto_lock_remaining_secs(), None);
     }
 
-    /// Set the talk hotkey keycode (macOS keycode)
-    pub fn set_talk_keycode(&self, keycode: i64) {
-        self.inner.lock().talk_keycode = keycode;
+    #[test]
+    fn test_unlock_grace_disabled() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        state.lock().auto_lock_timeout = 0;
+        state.set_unlock_grace_secs(0);
+
+        state.set_locked(true);
+        state.set_locked(false);
+        assert!(state.should_auto_lock());
     }
 
-    /// Get the lock hotkey keycode (macOS keycode)
-    pub fn get_lock_keycode(&self) -> i64 {
-        self.inner.lock().lock_keycode
+    #[test]
+    fn test_pause_expiry() {
+        let state = AppState::new();
+        let t0 = Instant::now();
+        assert!(!state.is_pause_expired_at(t0 + Duration::from_secs(3600)), "Not paused");
+        assert_eq!(state.get_pause_remaining_secs(), None);
+
+        state.pause_for(Duration::from_secs(300));
+        assert!(!state.is_pause_expired_at(t0));
+        assert!(!state.is_pause_expired_at(t0 + Duration::from_secs(299)));
+        assert!(state.is_pause_expired_at(t0 + Duration::from_secs(301)));
+        assert!(state.get_pause_remaining_secs().is_some_and(|secs| secs <= 300 && secs > 290));
+
+        state.clear_pause();
+        assert!(!state.is_pause_expired_at(t0 + Duration::from_secs(301)));
+        assert_eq!(state.get_pause_remaining_secs(), None);
     }
 
-    /// Get the talk hotkey keycode (macOS keycode)
-    pub fn get_talk_keycode(&self) -> i64 {
-        self.inner.lock().talk_keycode
+    #[test]
+    fn test_auto_lock_snooze_cleared_on_lock() {
+        let state = AppState::new();
+        state.snooze_auto_lock(Duration::from_secs(60));
+        assert!(state.get_snooze_remaining_secs().is_some());
+
+        state.set_locked(true);
+        assert_eq!(state.get_snooze_remaining_secs(), None);
+
+        state.set_locked(false);
+        state.snooze_auto_lock(Duration::from_secs(60));
+        state.clear_auto_lock_snooze();
+        assert_eq!(state.get_snooze_remaining_secs(), None);
     }
-}
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn test_auto_lock_warning_suppressed() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.lock().auto_lock_timeout = 1;
+        state.set_auto_lock_warning_secs(0);
+        assert_eq!(state.take_auto_lock_warning(), None, "0 disables the warning");
+
+        state.set_auto_lock_warning_secs(5);
+        state.set_locked(true);
+        assert_eq!(state.take_auto_lock_warning(), None, "No warning while locked");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::thread;
-    use std::time::Duration;
+    #[test]
+    fn test_no_countdowns_while_disabled() {
+        let state = AppState::new();
+        assert!(state.get_auto_lock_remaining_secs().is_some());
+        state.set_disabled(true);
+        assert_eq!(state.get_auto_lock_remaining_secs(), None, "Auto-lock isn't checked");
+
+        state.set_disabled(false);
+        state.set_auto_unlock_timeout(Some(60));
+        state.set_locked(true);
+        assert_eq!(state.get_auto_unlock_remaining_secs(), Some(60));
+        state.set_disabled(true);
+        assert_eq!(state.get_auto_unlock_remaining_secs(), None, "Auto-unlock isn't checked");
+    }
 
     #[test]
     fn test_auto_unlock_disabled_by_default() {
@@ -494,7 +2211,7 @@ mod tests {
 
         // Verify state is cleared
         assert!(!state.is_locked(), "Should be unlocked after trigger");
-        assert_eq!(state.get_buffer(), "", "Buffer should be cleared");
+        assert_eq!(state.get_buffer().expose(), "", "Buffer should be cleared");
 
         // Verify lock_start_time is cleared
         let inner = state.lock();
@@ -504,6 +2221,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_relock_keeps_lock_session() {
+        let state = AppState::new();
+        state.set_passphrase_len(Some(3));
+        state.set_locked(true);
+        for ch in "abc".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert!(state.start_attempt_cooldown_if_full_length());
+        let lock_start_time = state.lock().lock_start_time;
+
+        // Locking again must not clear the cooldown or push back the auto-unlock cap
+        state.set_locked(true);
+        assert!(state.get_attempt_cooldown_remaining().is_some());
+        assert_eq!(state.lock().lock_start_time, lock_start_time);
+    }
+
+    #[test]
+    fn test_trigger_auto_unlock_resets_attempt_state() {
+        let cmd_shift = HotkeyModifiers {
+            control: false,
+            option: false,
+            command: true,
+            shift: true,
+        };
+        let state = AppState::new();
+        state.set_passphrase_len(Some(3));
+        state.set_unlock_chord(Some(UnlockChord {
+            modifiers: cmd_shift,
+            presses: 3,
+        }));
+        state.set_locked(true);
+
+        // A cooldown running and an unlock sequence under way when auto-unlock fires
+        for ch in "abc".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert!(state.start_attempt_cooldown_if_full_length());
+        assert!(!state.record_unlock_chord(cmd_shift));
+        state.trigger_auto_unlock();
+        assert_eq!(state.get_attempt_cooldown_remaining(), None);
+        assert_eq!(state.lock().chord_recognizer, ChordRecognizer::default());
+
+        // The next lock starts with a fresh attempt
+        state.set_locked(true);
+        assert_eq!(state.get_attempt_cooldown_remaining(), None);
+        state.append_to_buffer('a');
+        state.append_to_buffer('b');
+        assert!(!state.start_attempt_cooldown_if_full_length());
+        assert_eq!(state.buffer_len(), 2);
+    }
+
+    #[test]
+    fn test_hold_unlock_state_machine() {
+        let hold = HoldUnlock {
+            keycode: crate::constants::ESCAPE_KEYCODE,
+            duration: Duration::from_secs(3),
+        };
+        let state = AppState::new();
+        let t0 = Instant::now();
+        let held = |_: i64| true;
+        assert!(!state.press_hold_unlock_key_at(t0), "Disabled by default");
+
+        state.set_hold_unlock(Some(hold));
+        assert!(!state.press_hold_unlock_key_at(t0), "Only while locked");
+        assert!(!state.poll_hold_unlock_with_at(held, t0 + Duration::from_secs(5)));
+
+        // Early release cancels the hold
+        state.set_locked(true);
+        assert!(!state.press_hold_unlock_key_at(t0));
+        state.release_hold_unlock_key();
+        assert!(!state.poll_hold_unlock_with_at(held, t0 + Duration::from_secs(3)));
+
+        // Held through the duration: the scheduler completes it
+        assert!(!state.press_hold_unlock_key_at(t0));
+        assert!(!state.poll_hold_unlock_with_at(held, t0 + Duration::from_secs(2)));
+        assert!(state.poll_hold_unlock_with_at(held, t0 + Duration::from_secs(3)));
+
+        // A missed KeyUp: the key isn't down any more, so the hold is cancelled
+        assert!(!state.press_hold_unlock_key_at(t0));
+        assert!(!state.poll_hold_unlock_with_at(|_| false, t0 + Duration::from_secs(3)));
+        assert!(!state.poll_hold_unlock_with_at(held, t0 + Duration::from_secs(4)));
+
+        // Unlocking forgets a hold in progress
+        assert!(!state.press_hold_unlock_key_at(t0));
+        state.set_locked(false);
+        state.set_locked(true);
+        assert_eq!(state.get_hold_unlock_progress(), None);
+    }
+
+    #[test]
+    fn test_activity_debounce_holds_back_stray_events() {
+        let state = AppState::new();
+        let t0 = Instant::now();
+        let idle_since = t0 - Duration::from_secs(60);
+        state.lock().last_input_time = idle_since;
+
+        // Off by default: every event counts
+        state.record_input_activity_at(t0);
+        assert_eq!(state.lock().last_input_time, t0);
+
+        state.lock().last_input_time = idle_since;
+        state.set_activity_debounce(3, Duration::from_secs(1));
+        assert_eq!(state.get_activity_debounce(), (3, Duration::from_secs(1)));
+        state.record_input_activity_at(t0);
+        state.record_input_activity_at(t0 + Duration::from_millis(100));
+        assert_eq!(state.lock().last_input_time, idle_since, "A stray click");
+
+        let burst = t0 + Duration::from_millis(200);
+        state.record_input_activity_at(burst);
+        assert_eq!(state.lock().last_input_time, burst);
+    }
+
     #[test]
     fn test_auto_unlock_only_when_locked() {
         let state = AppState::new();
@@ -602,6 +2432,392 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_recovery_code_is_single_use() {
+        let state = AppState::new();
+        let code = crate::auth::generate_recovery_code().unwrap();
+        state.set_recovery_code_hash(Some(crate::auth::hash_passphrase(&code)));
+        state.set_locked(true);
+
+        assert!(state.try_recovery_code(&code), "Recovery code should unlock");
+        assert!(!state.is_locked());
+        assert!(!state.has_recovery_code(), "Recovery code should be invalidated");
+        assert!(state.should_persist_recovery_code_consumed_and_clear());
+        assert!(!state.should_persist_recovery_code_consumed_and_clear());
+
+        // Second use must fail cleanly
+        state.set_locked(true);
+        assert!(!state.try_recovery_code(&code), "Consumed code must be rejected");
+        assert!(state.is_locked());
+    }
+
+    #[test]
+    fn test_recovery_code_rejects_wrong_input() {
+        let state = AppState::new();
+        state.set_recovery_code_hash(Some(crate::auth::hash_passphrase("abcde-fghij")));
+        state.set_locked(true);
+
+        assert!(!state.try_recovery_code("abcde-fghik"));
+        assert!(state.is_locked());
+        assert!(state.has_recovery_code(), "Failed attempt must not consume the code");
+    }
+
+    #[test]
+    fn test_clear_buffer_scrubs_contents() {
+        // Best-effort: zeroize wipes the bytes before truncating, so we can only
+        // observe that the buffer is empty and no copies remain reachable
+        let state = AppState::new();
+        for ch in "secret".chars() {
+            state.append_to_buffer(ch);
+        }
+        state.clear_buffer();
+        assert_eq!(state.buffer_len(), 0);
+        assert_eq!(state.get_buffer().expose(), "");
+
+        for ch in "secret".chars() {
+            state.append_to_buffer(ch);
+        }
+        state.set_auto_unlock_timeout(Some(60));
+        state.set_locked(true);
+        state.trigger_auto_unlock();
+        assert_eq!(state.buffer_len(), 0, "Auto-unlock should scrub the buffer");
+    }
+
+    #[test]
+    fn test_buffer_timeout_detects_failed_attempt() {
+        let state = AppState::new();
+        state.set_locked(true);
+        assert!(!state.clear_buffer_after_timeout(), "Empty buffer is not an attempt");
+
+        for ch in "wrong".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert!(state.clear_buffer_after_timeout(), "Discarded input while locked");
+        assert_eq!(state.buffer_len(), 0);
+        assert!(!state.clear_buffer_after_timeout(), "Already cleared");
+
+        state.set_locked(false);
+        state.append_to_buffer('x');
+        assert!(!state.clear_buffer_after_timeout(), "Not an attempt while unlocked");
+    }
+
+    #[test]
+    fn test_buffer_len_tracks_edits() {
+        let state = AppState::new();
+        assert_eq!(state.buffer_len(), 0);
+
+        for ch in "qwét".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert_eq!(state.buffer_len(), 4, "Length counts characters, not bytes");
+
+        state.pop_buffer();
+        state.pop_buffer();
+        assert_eq!(state.buffer_len(), 2);
+
+        // Backspace on an empty buffer stays at zero
+        state.clear_buffer();
+        state.pop_buffer();
+        assert_eq!(state.buffer_len(), 0);
+    }
+
+    #[test]
+    fn test_event_loop_stall_detection() {
+        let state = AppState::new();
+        state.set_watchdog_timeout_secs(30);
+        let t0 = Instant::now();
+
+        // No heartbeat yet (event loop not running) is never a stall
+        state.set_locked(true);
+        assert!(!state.is_event_loop_stalled_at(t0 + Duration::from_secs(120)));
+
+        state.lock().event_loop_heartbeat = Some(t0);
+        assert!(!state.is_event_loop_stalled_at(t0 + Duration::from_secs(10)));
+        assert!(!state.is_event_loop_stalled_at(t0 + Duration::from_secs(30)));
+        assert!(state.is_event_loop_stalled_at(t0 + Duration::from_secs(31)));
+
+        // A fresh heartbeat clears the stall
+        state.lock().event_loop_heartbeat = Some(t0 + Duration::from_secs(30));
+        assert!(!state.is_event_loop_stalled_at(t0 + Duration::from_secs(31)));
+
+        // Only matters while locked
+        state.set_locked(false);
+        assert!(!state.is_event_loop_stalled_at(t0 + Duration::from_secs(600)));
+        assert!(state.is_event_loop_heartbeat_stale_at(t0 + Duration::from_secs(600)));
+
+        // A stopped event loop isn't stalled
+        state.set_locked(true);
+        state.clear_watchdog_heartbeat();
+        assert!(!state.is_event_loop_stalled_at(t0 + Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_lock_hotkey_single_press_by_default() {
+        let state = AppState::new();
+        assert!(state.register_lock_hotkey_press_at(Instant::now()));
+    }
+
+    #[test]
+    fn test_lock_hotkey_double_press_within_window() {
+        let state = AppState::new();
+        state.set_lock_double_press_window(Some(Duration::from_millis(500)));
+        let t0 = Instant::now();
+
+        assert!(!state.register_lock_hotkey_press_at(t0), "First press only arms");
+        assert!(state.register_lock_hotkey_press_at(t0 + Duration::from_millis(300)));
+
+        // The window is re-armed from scratch after locking
+        assert!(!state.register_lock_hotkey_press_at(t0 + Duration::from_millis(600)));
+    }
+
+    #[test]
+    fn test_lock_hotkey_presses_outside_window_rearm() {
+        let state = AppState::new();
+        state.set_lock_double_press_window(Some(Duration::from_millis(500)));
+        let t0 = Instant::now();
+
+        assert!(!state.register_lock_hotkey_press_at(t0));
+        assert!(!state.register_lock_hotkey_press_at(t0 + Duration::from_millis(900)));
+        // Second press counts from the re-armed press, not the first one
+        assert!(state.register_lock_hotkey_press_at(t0 + Duration::from_millis(1200)));
+    }
+
+    #[test]
+    fn test_lock_hotkey_duplicate_delivery_is_one_press() {
+        let state = AppState::new();
+        state.set_lock_double_press_window(Some(Duration::from_millis(500)));
+        let t0 = Instant::now();
+
+        assert!(!state.register_lock_hotkey_press_at(t0));
+        assert!(
+            !state.register_lock_hotkey_press_at(t0 + Duration::from_millis(10)),
+            "Same press seen by the tap and the global hotkey must not lock"
+        );
+        assert!(state.register_lock_hotkey_press_at(t0 + Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn test_buffer_stays_bounded() {
+        let state = AppState::new();
+        let hash = crate::auth::hash_passphrase("qwet");
+
+        for ch in "qwet".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert!(crate::auth::verify_passphrase(&state.get_buffer(), &hash));
+
+        for _ in 0..10_000 {
+            state.append_to_buffer('x');
+        }
+        assert_eq!(state.get_buffer().chars().count(), MAX_BUFFER_LEN_DEFAULT);
+        assert!(state.get_buffer().chars().all(|c| c == 'x'), "Oldest chars should be dropped");
+
+        // After a reset, a short passphrase still verifies normally
+        state.clear_buffer();
+        for ch in "qwet".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert!(crate::auth::verify_passphrase(&state.get_buffer(), &hash));
+    }
+
+    #[test]
+    fn test_lock_scope_parse_and_display() {
+        assert_eq!("all".parse::<LockScope>().unwrap(), LockScope::All);
+        assert_eq!("Keyboard".parse::<LockScope>().unwrap(), LockScope::KeyboardOnly);
+        assert_eq!("mouse-only".parse::<LockScope>().unwrap(), LockScope::MouseOnly);
+        assert!("trackpad".parse::<LockScope>().is_err());
+
+        for scope in [LockScope::All, LockScope::KeyboardOnly, LockScope::MouseOnly] {
+            assert_eq!(scope.to_string().parse::<LockScope>().unwrap(), scope);
+        }
+    }
+
+    #[test]
+    fn test_talk_mode_parse_and_display() {
+        assert_eq!("hold".parse::<TalkMode>().unwrap(), TalkMode::Hold);
+        assert_eq!("Toggle".parse::<TalkMode>().unwrap(), TalkMode::Toggle);
+        assert!("latch".parse::<TalkMode>().is_err());
+
+        for mode in [TalkMode::Hold, TalkMode::Toggle] {
+            assert_eq!(mode.to_string().parse::<TalkMode>().unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn test_attempt_cooldown_waits_for_recovery_code_prefix() {
+        let state = AppState::new();
+        state.set_locked(true);
+        state.set_passphrase_len(Some(3));
+        state.set_recovery_code_hash(Some(crate::auth::hash_passphrase("unused")));
+
+        // The recovery code can still be typed past the passphrase length
+        let code = "abcde-fghij-kmnpq-rstuv";
+        assert_eq!(code.len(), crate::auth::RECOVERY_CODE_LEN);
+        for ch in code.chars().take(crate::auth::RECOVERY_CODE_LEN - 1) {
+            state.append_to_buffer(ch);
+            assert!(!state.start_attempt_cooldown_if_full_length());
+        }
+        // A full-length wrong code
+        state.append_to_buffer('v');
+        assert!(state.start_attempt_cooldown_if_full_length());
+
+        // A character that can't be part of a recovery code ends the attempt
+        state.set_locked(false);
+        state.set_locked(true);
+        for ch in "abcde".chars() {
+            state.append_to_buffer(ch);
+            assert!(!state.start_attempt_cooldown_if_full_length());
+        }
+        state.append_to_buffer('f');
+        assert!(state.start_attempt_cooldown_if_full_length());
+
+        // Unknown passphrase length (Keychain): no cooldown
+        state.set_locked(false);
+        state.set_locked(true);
+        state.set_passphrase_len(None);
+        for _ in 0..crate::auth::RECOVERY_CODE_LEN {
+            state.append_to_buffer('x');
+        }
+        assert!(!state.start_attempt_cooldown_if_full_length());
+    }
+
+    #[test]
+    fn test_wrong_passphrase_starts_cooldown_with_recovery_code() {
+        let state = AppState::new();
+        state.set_locked(true);
+        state.set_passphrase_len(Some(8));
+        state.set_recovery_code_hash(Some(crate::auth::hash_passphrase("unused")));
+
+        // No dash after the fifth character, so this can't be the recovery code
+        for ch in "password".chars() {
+            state.append_to_buffer(ch);
+        }
+        assert!(state.start_attempt_cooldown_if_full_length());
+        assert_eq!(state.buffer_len(), 0);
+        assert!(state.get_attempt_cooldown_remaining().is_some());
+    }
+
+    #[test]
+    fn test_permission_transition_count() {
+        let state = AppState::new();
+        assert_eq!(state.permission_transition_count(), 0);
+        state.record_permission_transition();
+        state.record_permission_transition();
+        assert_eq!(state.permission_transition_count(), 2);
+
+        // Shared between clones, like the rest of the state
+        state.clone().record_permission_transition();
+        assert_eq!(state.permission_transition_count(), 3);
+    }
+
+    #[test]
+    fn test_permission_loss_policy_parse_and_display() {
+        assert_eq!("Exit".parse::<PermissionLossPolicy>().unwrap(), PermissionLossPolicy::Exit);
+        assert!("restart".parse::<PermissionLossPolicy>().is_err());
+
+        for policy in [
+            PermissionLossPolicy::Unlock,
+            PermissionLossPolicy::Exit,
+            PermissionLossPolicy::Retry,
+        ] {
+            assert_eq!(policy.to_string().parse::<PermissionLossPolicy>().unwrap(), policy);
+        }
+    }
+
+    #[test]
+    fn test_permission_loss_policy_actions() {
+        use PermissionLossAction::*;
+
+        // Unlock keeps the tray app running and exits the CLI once the loss is confirmed
+        assert!(PermissionLossPolicy::Unlock.releases_lock());
+        assert_eq!(PermissionLossPolicy::Unlock.action(true), Wait);
+        assert_eq!(PermissionLossPolicy::Unlock.action(false), ExitWhenConfirmed);
+
+        // Exit leaves it to a supervisor in both apps
+        assert!(PermissionLossPolicy::Exit.releases_lock());
+        assert_eq!(PermissionLossPolicy::Exit.action(true), ExitNow);
+        assert_eq!(PermissionLossPolicy::Exit.action(false), ExitNow);
+
+        // Retry holds the lock and waits for permissions in both apps
+        assert!(!PermissionLossPolicy::Retry.releases_lock());
+        assert_eq!(PermissionLossPolicy::Retry.action(true), Wait);
+        assert_eq!(PermissionLossPolicy::Retry.action(false), Wait);
+
+        assert_eq!(AppState::new().get_permission_loss_policy(), PermissionLossPolicy::Unlock);
+    }
+
+    #[test]
+    fn test_lock_and_unlock_reset_talk_latch() {
+        let state = AppState::new();
+        state.set_talk_mode(TalkMode::Toggle);
+
+        state.set_talk_key_pressed(true);
+        state.set_locked(true);
+        assert!(!state.is_talk_key_pressed(), "Locking should reset the talk latch");
+
+        state.set_talk_key_pressed(true);
+        state.set_locked(false);
+        assert!(!state.is_talk_key_pressed(), "Unlocking should reset the talk latch");
+    }
+
+    #[test]
+    fn test_lock_scope_devices() {
+        assert!(LockScope::All.blocks_keyboard() && LockScope::All.blocks_mouse());
+        assert!(LockScope::KeyboardOnly.blocks_keyboard());
+        assert!(!LockScope::KeyboardOnly.blocks_mouse());
+        assert!(!LockScope::MouseOnly.blocks_keyboard());
+        assert!(LockScope::MouseOnly.blocks_mouse());
+    }
+
+    #[test]
+    fn test_touchid_in_progress_guard() {
+        let state = AppState::new();
+
+        assert!(state.try_begin_touchid());
+        assert!(!state.try_begin_touchid(), "Second prompt must not start while one is active");
+
+        state.finish_touchid();
+        assert!(state.try_begin_touchid());
+    }
+
+    #[test]
+    fn test_two_factor_unlock_after_passphrase_and_touchid() {
+        let state = AppState::new();
+        state.set_two_factor_unlock(true);
+        assert!(!state.begin_biometric_wait(), "Nothing to unlock while unlocked");
+
+        state.set_locked(true);
+        assert!(!state.finish_biometric_wait(true), "Touch ID alone doesn't unlock");
+        assert!(state.is_locked());
+
+        assert!(state.begin_biometric_wait());
+        assert!(state.is_awaiting_biometric());
+        assert!(state.is_locked(), "Passphrase alone doesn't unlock");
+
+        assert!(state.finish_biometric_wait(true));
+        assert!(!state.is_locked());
+        assert!(!state.is_awaiting_biometric());
+    }
+
+    #[test]
+    fn test_two_factor_rejected_touchid_fails_closed() {
+        let state = AppState::new();
+        state.set_two_factor_unlock(true);
+        state.set_locked(true);
+
+        assert!(state.begin_biometric_wait());
+        assert!(!state.finish_biometric_wait(false));
+        assert!(state.is_locked());
+        assert!(!state.is_awaiting_biometric(), "Passphrase must be typed again");
+        assert!(!state.finish_biometric_wait(true), "A late success doesn't count");
+        assert!(state.is_locked());
+
+        // Unlocking any other way ends the wait
+        assert!(state.begin_biometric_wait());
+        state.unlock_with(UnlockMethod::RecoveryCode);
+        assert!(!state.is_awaiting_biometric());
+    }
+
     #[test]
     fn test_auto_unlock_zero_timeout_does_not_trigger() {
         // Regression test for bug where Some(0) would cause immediate unlock
@@ -631,4 +2847,56 @@ mod tests {
             "Auto-unlock with timeout=0 should remain disabled"
         );
     }
+
+    #[test]
+    fn test_lock_transitions_are_audited() {
+        let dir = std::env::temp_dir().join(format!("handsoff_state_audit_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+
+        let state = AppState::new();
+        state.set_audit_log_path(Some(path.clone()));
+        state.set_recovery_code_hash(Some(crate::auth::hash_passphrase("recover")));
+
+        state.set_locked(true);
+        state.set_locked(true); // Already locked: not recorded again
+        state.unlock_with(UnlockMethod::Passphrase);
+        state.unlock_with(UnlockMethod::TouchId); // Already unlocked: not recorded
+        state.set_locked(true);
+        assert!(state.try_recovery_code("recover"));
+        state.set_locked(true);
+        state.trigger_auto_unlock();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let events: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let summary: Vec<(String, String)> = events
+            .iter()
+            .map(|e| {
+                (
+                    e["event"].as_str().unwrap().to_string(),
+                    e["identity"].as_str().unwrap_or("").to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("locked", ""),
+            ("unlocked", "primary"),
+            ("locked", ""),
+            ("unlocked", "recovery"),
+            ("locked", ""),
+            ("auto_unlock", ""),
+        ];
+        assert_eq!(
+            summary,
+            expected
+                .iter()
+                .map(|(e, i)| (e.to_string(), i.to_string()))
+                .collect::<Vec<_>>()
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
@@ -0,0 +1,216 @@
+//! Append-only audit trail of lock/unlock events
+//!
+//! Each event is appended to the audit file as one JSON object per line:
+//!
+//! ```text
+//! {"ts":"2026-03-01T22:14:05.120+01:00","event":"locked"}
+//! {"ts":"2026-03-01T22:20:41.877+01:00","event":"unlocked","method":"passphrase","identity":"primary"}
+//! ```
+//!
+//! The file lives next to the config file (`audit.jsonl`) and is created owner-only
+//! (0600). Existing lines are never rewritten.
+
+use crate::constants::AUDIT_LOG_PERMISSIONS;
+use anyhow::{Context, Result};
+use chrono::{Local, SecondsFormat};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// Audit file name, stored in the same directory as the config file
+const AUDIT_FILE_NAME: &str = "audit.jsonl";
+
+/// Something worth keeping a record of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// Input was locked
+    Locked,
+    /// Input was unlocked
+    Unlocked {
+        method: UnlockMethod,
+        /// Which secret was used (None for methods that don't use one)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        identity: Option<UnlockIdentity>,
+    },
+    /// The auto-unlock timeout expired
+    AutoUnlock { locked_for_secs: u64 },
+    /// The max lock duration hard cap was reached (enforced even with auto-unlock off)
+    MaxLockDuration { locked_for_secs: u64 },
+    /// Accessibility permissions were lost (input is unlocked and blocking stops)
+    PermissionLost,
+    /// HandsOff was disabled from the menu
+    Disabled,
+}
+
+impl AuditEvent {
+    /// Unlock event with the identity implied by `method`
+    pub fn unlocked(method: UnlockMethod) -> Self {
+        AuditEvent::Unlocked {
+            method,
+            identity: method.identity(),
+        }
+    }
+}
+
+/// How input was unlocked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockMethod {
+    /// Passphrase typed while locked
+    Passphrase,
+    /// One-time emergency recovery code
+    RecoveryCode,
+    TouchId,
+    /// Passphrase followed by Touch ID (two-factor mode)
+    TwoFactor,
+    PanicHotkey,
+    /// Modifier chord pressed in quick succession (`unlock_chord`)
+    UnlockChord,
+    /// Key held down for a few seconds (`hold_to_unlock`)
+    HoldToUnlock,
+    /// Passphrase sent over the control socket
+    ControlSocket,
+    /// Tray "Reset" menu item
+    Reset,
+    /// Emergency unlock after accessibility permissions were lost
+    PermissionLost,
+    /// Emergency unlock after the event loop stopped responding
+    Watchdog,
+    /// No method given (`AppState::set_locked(false)`)
+    Unspecified,
+}
+
+impl UnlockMethod {
+    /// Which secret the method checks, if any
+    pub fn identity(self) -> Option<UnlockIdentity> {
+        match self {
+            UnlockMethod::Passphrase | UnlockMethod::ControlSocket | UnlockMethod::TwoFactor => {
+                Some(UnlockIdentity::Primary)
+            }
+            UnlockMethod::RecoveryCode => Some(UnlockIdentity::Recovery),
+            _ => None,
+        }
+    }
+}
+
+/// Which secret unlocked input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockIdentity {
+    /// The configured passphrase
+    Primary,
+    /// The one-time recovery code
+    Recovery,
+}
+
+/// Line written to the audit file
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    ts: String,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+}
+
+/// Default audit file location for a config file path
+pub fn default_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(AUDIT_FILE_NAME)
+}
+
+/// Serialize an event as a single JSON line (without the trailing newline)
+pub fn to_json_line(event: &AuditEvent) -> Result<String> {
+    let record = AuditRecord {
+        ts: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+        event,
+    };
+    serde_json::to_string(&record).context("Failed to serialize audit event")
+}
+
+/// Append an event to the audit file, creating it (0600) if needed
+pub fn append(path: &Path, event: &AuditEvent) -> Result<()> {
+    let mut line = to_json_line(event)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(AUDIT_LOG_PERMISSIONS)
+        .open(path)
+        .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+    file.set_permissions(fs::Permissions::from_mode(AUDIT_LOG_PERMISSIONS))
+        .context("Failed to set audit log permissions")?;
+    // Single write so concurrent appends never interleave within a line
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to write audit log {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn json(event: AuditEvent) -> Value {
+        serde_json::from_str(&to_json_line(&event).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_serialize_each_event() {
+        let locked = json(AuditEvent::Locked);
+        assert_eq!(locked["event"], "locked");
+        assert!(locked["ts"].as_str().is_some_and(|ts| !ts.is_empty()));
+
+        let passphrase = json(AuditEvent::unlocked(UnlockMethod::Passphrase));
+        assert_eq!(passphrase["event"], "unlocked");
+        assert_eq!(passphrase["method"], "passphrase");
+        assert_eq!(passphrase["identity"], "primary");
+
+        let recovery = json(AuditEvent::unlocked(UnlockMethod::RecoveryCode));
+        assert_eq!(recovery["method"], "recovery_code");
+        assert_eq!(recovery["identity"], "recovery");
+
+        let touchid = json(AuditEvent::unlocked(UnlockMethod::TouchId));
+        assert_eq!(touchid["method"], "touch_id");
+        assert!(touchid.get("identity").is_none());
+
+        let two_factor = json(AuditEvent::unlocked(UnlockMethod::TwoFactor));
+        assert_eq!(two_factor["method"], "two_factor");
+        assert_eq!(two_factor["identity"], "primary");
+
+        let auto = json(AuditEvent::AutoUnlock { locked_for_secs: 300 });
+        assert_eq!(auto["event"], "auto_unlock");
+        assert_eq!(auto["locked_for_secs"], 300);
+
+        let cap = json(AuditEvent::MaxLockDuration { locked_for_secs: 3600 });
+        assert_eq!(cap["event"], "max_lock_duration");
+        assert_eq!(cap["locked_for_secs"], 3600);
+
+        assert_eq!(json(AuditEvent::PermissionLost)["event"], "permission_lost");
+        assert_eq!(json(AuditEvent::Disabled)["event"], "disabled");
+    }
+
+    #[test]
+    fn test_append_to_file() {
+        let dir = std::env::temp_dir().join(format!("handsoff_audit_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = default_path(&dir.join("config.toml"));
+
+        append(&path, &AuditEvent::Locked).unwrap();
+        append(&path, &AuditEvent::unlocked(UnlockMethod::Passphrase)).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let events: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["event"], "locked");
+        assert_eq!(events[1]["method"], "passphrase");
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, AUDIT_LOG_PERMISSIONS);
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,96 @@
+//! macOS Keychain storage for the passphrase hash
+//!
+//! When `use_keychain` is enabled in the config, the SHA-256 passphrase hash is kept
+//! as a generic password item in the user's login keychain instead of the
+//! AES-encrypted passphrase in config.toml. The passphrase length is stored next to
+//! it, so the attempt cooldown still knows when an attempt is complete.
+
+use anyhow::{Context, Result};
+use security_framework::passwords::{
+    delete_generic_password, get_generic_password, set_generic_password,
+};
+
+/// Keychain service name (matches the app bundle identifier)
+pub const KEYCHAIN_SERVICE: &str = "com.handsoff.inputlock";
+
+/// Keychain account name for the passphrase hash item
+const PASSPHRASE_HASH_ACCOUNT: &str = "passphrase_hash";
+
+/// Keychain account name for the passphrase length item
+const PASSPHRASE_LEN_ACCOUNT: &str = "passphrase_len";
+
+/// Security framework status code for a missing keychain item
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// Store the passphrase hash in the login keychain (replaces any existing entry)
+pub fn store_passphrase_hash(hash: &str) -> Result<()> {
+    store_passphrase_hash_for_service(KEYCHAIN_SERVICE, hash)
+}
+
+/// Retrieve the passphrase hash from the login keychain
+///
+/// Returns Ok(None) if no entry exists, so callers can fall back to the config file.
+pub fn retrieve_passphrase_hash() -> Result<Option<String>> {
+    retrieve_passphrase_hash_for_service(KEYCHAIN_SERVICE)
+}
+
+/// Store the passphrase length in the login keychain (replaces any existing entry)
+pub fn store_passphrase_len(len: usize) -> Result<()> {
+    set_generic_password(KEYCHAIN_SERVICE, PASSPHRASE_LEN_ACCOUNT, len.to_string().as_bytes())
+        .context("Failed to store passphrase length in Keychain")
+}
+
+/// Retrieve the passphrase length from the login keychain
+///
+/// Returns Ok(None) if no entry exists (e.g. the hash was stored by an older version).
+pub fn retrieve_passphrase_len() -> Result<Option<usize>> {
+    match get_generic_password(KEYCHAIN_SERVICE, PASSPHRASE_LEN_ACCOUNT) {
+        Ok(bytes) => {
+            let len = String::from_utf8(bytes)
+                .ok()
+                .and_then(|len| len.parse().ok())
+                .context("Passphrase length in Keychain is not a number")?;
+            Ok(Some(len))
+        }
+        Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+        Err(e) => Err(e).context("Failed to read passphrase length from Keychain"),
+    }
+}
+
+/// Remove the passphrase hash from the login keychain (no-op if missing)
+pub fn delete_passphrase_hash() -> Result<()> {
+    delete_passphrase_hash_for_service(KEYCHAIN_SERVICE)
+}
+
+/// Store the passphrase hash under a specific keychain service name
+pub fn store_passphrase_hash_for_service(service: &str, hash: &str) -> Result<()> {
+    set_generic_password(service, PASSPHRASE_HASH_ACCOUNT, hash.as_bytes())
+        .context("Failed to store passphrase hash in Keychain")
+}
+
+/// Retrieve the passphrase hash stored under a specific keychain service name
+pub fn retrieve_passphrase_hash_for_service(service: &str) -> Result<Option<String>> {
+    match get_generic_password(service, PASSPHRASE_HASH_ACCOUNT) {
+        Ok(bytes) => {
+            let hash = String::from_utf8(bytes)
+                .context("Passphrase hash in Keychain is not valid UTF-8")?;
+            Ok(Some(hash))
+        }
+        Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+        Err(e) => Err(e).context("Failed to read passphrase hash from Keychain"),
+    }
+}
+
+/// Remove the passphrase hash stored under a specific keychain service name
+pub fn delete_passphrase_hash_for_service(service: &str) -> Result<()> {
+    match delete_generic_password(service, PASSPHRASE_LEN_ACCOUNT) {
+        Ok(()) => {}
+        Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => {}
+        Err(e) => return Err(e).context("Failed to delete passphrase length from Keychain"),
+    }
+    match delete_generic_password(service, PASSPHRASE_HASH_ACCOUNT) {
+        Ok(()) => Ok(()),
+        Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(()),
+        Err(e) => Err(e).context("Failed to delete passphrase hash from Keychain"),
+    }
+}
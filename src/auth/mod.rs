@@ -1,4 +1,42 @@
+pub mod keychain;
+pub mod touchid;
+
+use crate::app_state::AppState;
+use crate::audit::UnlockMethod;
+use crate::constants::TOUCHID_TIMEOUT_SECS;
+use crate::strings::{self, MessageKey};
+use crate::ui::{self, notify::Level};
 use crate::utils;
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use std::time::Duration;
+
+/// Alphabet for recovery codes: lowercase letters and digits only, so the code can be
+/// typed while locked without Shift (see keycode_to_char). Ambiguous 0/o/1/l are omitted.
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+/// Number of dash-separated groups in a recovery code
+const RECOVERY_CODE_GROUPS: usize = 4;
+
+/// Characters per recovery code group (4 x 5 chars x 5 bits = 100 bits of entropy)
+const RECOVERY_CODE_GROUP_LEN: usize = 5;
+
+/// Length of a typed recovery code, dashes included
+pub(crate) const RECOVERY_CODE_LEN: usize =
+    RECOVERY_CODE_GROUPS * (RECOVERY_CODE_GROUP_LEN + 1) - 1;
+
+/// Whether `input` could still be the start of a recovery code: recovery code alphabet
+/// with a dash after each group, and shorter than a full code
+pub(crate) fn could_be_recovery_code_prefix(input: &str) -> bool {
+    input.chars().count() < RECOVERY_CODE_LEN
+        && input.chars().enumerate().all(|(i, ch)| {
+            if (i + 1) % (RECOVERY_CODE_GROUP_LEN + 1) == 0 {
+                ch == '-'
+            } else {
+                ch.is_ascii() && RECOVERY_CODE_ALPHABET.contains(&(ch as u8))
+            }
+        })
+}
 
 /// Verify if a passphrase matches the stored hash
 pub fn verify_passphrase(input: &str, stored_hash: &str) -> bool {
@@ -9,3 +47,175 @@ pub fn verify_passphrase(input: &str, stored_hash: &str) -> bool {
 pub fn hash_passphrase(passphrase: &str) -> String {
     utils::hash_passphrase(passphrase)
 }
+
+/// Generate a random one-time emergency recovery code (e.g. "k7m2q-9xdfa-...")
+///
+/// Only the hash of the code should be persisted (see `hash_passphrase`).
+pub fn generate_recovery_code() -> Result<String> {
+    let mut random = [0u8; RECOVERY_CODE_GROUPS * RECOVERY_CODE_GROUP_LEN];
+    getrandom::getrandom(&mut random)
+        .map_err(|e| anyhow::anyhow!("Failed to generate random recovery code: {:?}", e))?;
+
+    let groups: Vec<String> = random
+        .chunks(RECOVERY_CODE_GROUP_LEN)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|b| RECOVERY_CODE_ALPHABET[(*b as usize) % RECOVERY_CODE_ALPHABET.len()] as char)
+                .collect()
+        })
+        .collect();
+
+    Ok(groups.join("-"))
+}
+
+/// Run a Touch ID prompt on a background thread and unlock on success
+///
+/// Does nothing if a prompt is already showing. Safe to call from the event tap
+/// callback since the blocking wait happens off-thread.
+pub fn spawn_touchid_unlock(state: AppState) {
+    spawn_touchid_prompt(state, "unlock HandsOff input", |state, result| match result {
+        Ok(true) => {
+            if state.is_locked() {
+                info!("Touch ID verified - input unlocked");
+                state.unlock_with(UnlockMethod::TouchId);
+                state.clear_buffer();
+                state.update_input_time();
+            }
+        }
+        Ok(false) => warn!("Touch ID authentication failed - input remains locked"),
+        Err(e) => warn!("Touch ID unlock unavailable: {}", e),
+    });
+}
+
+/// Act on a verified passphrase: unlock, or in two-factor mode ask for Touch ID first
+///
+/// Returns whether input was unlocked right away.
+pub fn unlock_with_passphrase(state: &AppState, method: UnlockMethod) -> bool {
+    if !state.get_two_factor_unlock() {
+        state.unlock_with(method);
+        return true;
+    }
+    if state.begin_biometric_wait() {
+        info!("Passphrase verified - confirm with Touch ID to unlock");
+        spawn_two_factor_touchid(state.clone());
+    }
+    false
+}
+
+/// Second factor of a two-factor unlock: prompt for Touch ID and unlock only on success
+///
+/// Fails closed: if Touch ID fails, is cancelled or is unavailable, input stays locked,
+/// the user is notified and the passphrase has to be typed again.
+fn spawn_two_factor_touchid(state: AppState) {
+    spawn_touchid_prompt(state, "confirm unlocking HandsOff input", |state, result| {
+        let verified = matches!(result, Ok(true));
+        if state.finish_biometric_wait(verified) {
+            info!("Touch ID verified - input unlocked (two-factor)");
+            state.clear_buffer();
+            state.update_input_time();
+            return;
+        }
+        if !state.is_locked() {
+            return; // Unlocked another way while the prompt was showing
+        }
+
+        let message = match result {
+            Ok(_) => {
+                warn!("Touch ID not confirmed - input remains locked (two-factor)");
+                strings::get(MessageKey::TwoFactorFailed)
+            }
+            Err(e) => {
+                warn!("Touch ID unavailable for two-factor unlock - input remains locked: {}", e);
+                strings::format(MessageKey::TwoFactorUnavailable, &[("error", &e.to_string())])
+            }
+        };
+        ui::notify::notify_with(
+            Level::Important,
+            &strings::get(MessageKey::NotificationTitle),
+            &message,
+        );
+    });
+}
+
+/// Show a Touch ID prompt on a background thread and hand its result to `on_result`
+///
+/// Does nothing if a prompt is already showing (its result then completes a pending
+/// two-factor unlock).
+fn spawn_touchid_prompt<F>(state: AppState, reason: &'static str, on_result: F)
+where
+    F: FnOnce(&AppState, Result<bool>) + Send + 'static,
+{
+    if !state.try_begin_touchid() {
+        debug!("Touch ID prompt already in progress - not starting another");
+        return;
+    }
+
+    let thread_state = state.clone();
+    let spawned = std::thread::Builder::new()
+        .name("touchid-unlock".to_string())
+        .spawn(move || {
+            let state = thread_state;
+            let timeout = Duration::from_secs(TOUCHID_TIMEOUT_SECS);
+            on_result(&state, touchid::authenticate(reason, timeout));
+            state.finish_touchid();
+        });
+
+    if let Err(e) = spawned {
+        error!("Failed to spawn Touch ID thread: {}", e);
+        state.finish_touchid();
+        state.finish_biometric_wait(false);
+    }
+}
+
+/// Emergency unlock via the panic hotkey, bypassing the passphrase
+///
+/// When `panic_requires_touchid` is set this only starts a Touch ID prompt; otherwise
+/// input is unlocked immediately. Logged at warn level so every use is visible.
+pub fn panic_unlock(state: &AppState) {
+    if !state.is_locked() {
+        debug!("Panic hotkey pressed while unlocked - ignoring");
+        return;
+    }
+
+    if state.get_panic_requires_touchid() {
+        warn!("PANIC HOTKEY PRESSED - requesting Touch ID for emergency unlock");
+        spawn_touchid_unlock(state.clone());
+        return;
+    }
+
+    warn!("!!! PANIC HOTKEY PRESSED - EMERGENCY UNLOCK WITHOUT PASSPHRASE !!!");
+    state.unlock_with(UnlockMethod::PanicHotkey);
+    state.clear_buffer();
+    state.update_input_time();
+}
+
+/// Unlock via the configured unlock chord sequence, bypassing the passphrase
+///
+/// Ignored in two-factor mode, where only passphrase + Touch ID unlock.
+pub fn chord_unlock(state: &AppState) {
+    if state.get_two_factor_unlock() {
+        info!("Unlock sequence ignored in two-factor mode - type the passphrase");
+        return;
+    }
+
+    warn!("Unlock sequence recognized - unlocking without the passphrase");
+    state.unlock_with(UnlockMethod::UnlockChord);
+    state.clear_buffer();
+    state.update_input_time();
+}
+
+/// Unlock via the hold-to-unlock key, bypassing the passphrase
+///
+/// Ignored in two-factor mode, where only passphrase + Touch ID unlock.
+pub fn hold_unlock(state: &AppState) {
+    if state.get_two_factor_unlock() {
+        info!("Hold-to-unlock ignored in two-factor mode - type the passphrase");
+        return;
+    }
+
+    warn!("Unlock key held - unlocking without the passphrase");
+    state.unlock_with(UnlockMethod::HoldToUnlock);
+    state.clear_buffer();
+    state.update_input_time();
+}
@@ -0,0 +1,217 @@
+//! Touch ID authentication via the LocalAuthentication framework
+//!
+//! Binds `LAContext` through the Objective-C runtime (`objc_msgSend`) so that a
+//! genuine biometric prompt is shown. `evaluatePolicy:localizedReason:reply:` is
+//! asynchronous; the reply block forwards the result over a channel and
+//! `authenticate()` blocks on it with a timeout.
+
+use anyhow::{anyhow, Result};
+use core_foundation::base::TCFType;
+use core_foundation::string::CFString;
+use log::{debug, info, warn};
+use std::ffi::{c_char, c_void};
+use std::os::raw::c_ulong;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+
+/// LAPolicyDeviceOwnerAuthenticationWithBiometrics
+const LA_POLICY_BIOMETRICS: isize = 1;
+
+/// Block flag marking a block literal as global (never copied or freed by the runtime)
+const BLOCK_IS_GLOBAL: i32 = 1 << 28;
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+}
+
+#[link(name = "System")]
+extern "C" {
+    static _NSConcreteGlobalBlock: [*const c_void; 32];
+}
+
+// Ensure LAContext is registered with the Objective-C runtime
+#[link(name = "LocalAuthentication", kind = "framework")]
+extern "C" {}
+
+/// Block descriptor (no copy/dispose helpers needed for a global block)
+#[repr(C)]
+struct BlockDescriptor {
+    reserved: c_ulong,
+    size: c_ulong,
+}
+
+/// Block literal for `void (^)(BOOL success, NSError *error)`
+#[repr(C)]
+struct ReplyBlock {
+    isa: *const c_void,
+    flags: i32,
+    reserved: i32,
+    invoke: unsafe extern "C" fn(*mut ReplyBlock, u8, Id),
+    descriptor: *const BlockDescriptor,
+}
+
+// The block is immutable after creation and only read by the ObjC runtime
+unsafe impl Sync for ReplyBlock {}
+unsafe impl Send for ReplyBlock {}
+
+static REPLY_DESCRIPTOR: BlockDescriptor = BlockDescriptor {
+    reserved: 0,
+    size: std::mem::size_of::<ReplyBlock>() as c_ulong,
+};
+
+static REPLY_BLOCK: OnceLock<ReplyBlock> = OnceLock::new();
+
+/// Sender for the in-flight evaluation (only one prompt may be active at a time)
+static REPLY_SENDER: Mutex<Option<Sender<bool>>> = Mutex::new(None);
+
+/// Invoked by LocalAuthentication on a private queue when evaluation finishes
+unsafe extern "C" fn reply_invoke(_block: *mut ReplyBlock, success: u8, _error: Id) {
+    let sender = REPLY_SENDER.lock().ok().and_then(|mut s| s.take());
+    if let Some(sender) = sender {
+        let _ = sender.send(success != 0);
+    }
+}
+
+fn reply_block() -> *const c_void {
+    REPLY_BLOCK.get_or_init(|| ReplyBlock {
+        isa: unsafe { _NSConcreteGlobalBlock.as_ptr() as *const c_void },
+        flags: BLOCK_IS_GLOBAL,
+        reserved: 0,
+        invoke: reply_invoke,
+        descriptor: &REPLY_DESCRIPTOR,
+    }) as *const ReplyBlock as *const c_void
+}
+
+unsafe fn sel(name: &[u8]) -> Sel {
+    sel_registerName(name.as_ptr() as *const c_char)
+}
+
+/// Owned `LAContext` instance (released on drop)
+struct LAContext(Id);
+
+impl LAContext {
+    fn new() -> Result<Self> {
+        unsafe {
+            let class = objc_getClass(c"LAContext".as_ptr());
+            if class.is_null() {
+                return Err(anyhow!("LAContext class not available"));
+            }
+            let msg: unsafe extern "C" fn(Id, Sel) -> Id =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            let obj = msg(class, sel(b"alloc\0"));
+            let obj = msg(obj, sel(b"init\0"));
+            if obj.is_null() {
+                return Err(anyhow!("Failed to create LAContext"));
+            }
+            Ok(Self(obj))
+        }
+    }
+
+    fn can_evaluate_biometrics(&self) -> bool {
+        unsafe {
+            let msg: unsafe extern "C" fn(Id, Sel, isize, *mut Id) -> u8 =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            let mut error: Id = std::ptr::null_mut();
+            msg(
+                self.0,
+                sel(b"canEvaluatePolicy:error:\0"),
+                LA_POLICY_BIOMETRICS,
+                &mut error,
+            ) != 0
+        }
+    }
+
+    fn evaluate_biometrics(&self, reason: &str) {
+        let reason = CFString::new(reason);
+        unsafe {
+            let msg: unsafe extern "C" fn(Id, Sel, isize, Id, *const c_void) =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            // CFStringRef is toll-free bridged with NSString
+            msg(
+                self.0,
+                sel(b"evaluatePolicy:localizedReason:reply:\0"),
+                LA_POLICY_BIOMETRICS,
+                reason.as_concrete_TypeRef() as Id,
+                reply_block(),
+            );
+        }
+    }
+
+    /// Dismiss any prompt still on screen (used after a timeout)
+    fn invalidate(&self) {
+        unsafe {
+            let msg: unsafe extern "C" fn(Id, Sel) =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            msg(self.0, sel(b"invalidate\0"));
+        }
+    }
+}
+
+impl Drop for LAContext {
+    fn drop(&mut self) {
+        unsafe {
+            let msg: unsafe extern "C" fn(Id, Sel) =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            msg(self.0, sel(b"release\0"));
+        }
+    }
+}
+
+/// Check whether Touch ID is available and enrolled on this machine
+pub fn is_available() -> bool {
+    match LAContext::new() {
+        Ok(context) => context.can_evaluate_biometrics(),
+        Err(e) => {
+            debug!("Touch ID unavailable: {}", e);
+            false
+        }
+    }
+}
+
+/// Show a Touch ID prompt and wait for the result
+///
+/// Returns Ok(true) on successful authentication, Ok(false) if the user failed or
+/// cancelled, and an error if Touch ID is unavailable, another prompt is already
+/// active, or no reply arrives within `timeout`.
+pub fn authenticate(reason: &str, timeout: Duration) -> Result<bool> {
+    let context = LAContext::new()?;
+    if !context.can_evaluate_biometrics() {
+        return Err(anyhow!("Touch ID is not available or not enrolled"));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    {
+        let mut pending = REPLY_SENDER
+            .lock()
+            .map_err(|_| anyhow!("Touch ID reply lock poisoned"))?;
+        if pending.is_some() {
+            return Err(anyhow!("Touch ID authentication already in progress"));
+        }
+        *pending = Some(tx);
+    }
+
+    info!("Requesting Touch ID authentication");
+    context.evaluate_biometrics(reason);
+
+    match rx.recv_timeout(timeout) {
+        Ok(success) => {
+            info!("Touch ID authentication {}", if success { "succeeded" } else { "failed" });
+            Ok(success)
+        }
+        Err(_) => {
+            warn!("Touch ID authentication timed out after {:?}", timeout);
+            context.invalidate();
+            if let Ok(mut pending) = REPLY_SENDER.lock() {
+                pending.take();
+            }
+            Err(anyhow!("Touch ID authentication timed out"))
+        }
+    }
+}
@@ -3,19 +3,37 @@
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use handsoff::app_state::AUTO_UNLOCK_DEFAULT_SECONDS;
+use handsoff::app_state::{PermissionLossAction, AUTO_UNLOCK_DEFAULT_SECONDS};
 use handsoff::constants::{
-    NOTIFICATION_ERROR_TIMEOUT_MS, NOTIFICATION_TIMEOUT_MS, POLL_INTERVAL_DISABLED_SECS,
+    ATTEMPT_COOLDOWN_SECONDS, AUTO_LOCK_SNOOZE_CHOICES_MINUTES, PAUSE_CHOICES_MINUTES, POLL_INTERVAL_DISABLED_SECS,
     POLL_INTERVAL_ENABLED_MS,
 };
-use handsoff::{config, config_file::Config, HandsOffCore};
+use handsoff::health::HealthAction;
+use handsoff::instance_lock::{self, AlreadyRunning, InstanceLock};
+use handsoff::power_source::PowerAutoLockPolicy;
+use handsoff::strings::{self, MessageKey};
+use handsoff::ui::{self, app_activation, dialogs};
+use handsoff::ui::menu::{self, format_count, format_duration, MenuState};
+use handsoff::ui::notify::{Level, NotificationSettings};
+use handsoff::ui::overlay::{self, LockOverlay};
+use handsoff::ui::sleep_wake::{self, LockTriggers};
+use handsoff::scripting;
+use handsoff::setup_prompts;
+use handsoff::url_scheme::{self, Command as UrlCommand};
+use handsoff::utils::keycode;
+use handsoff::utils::modifiers::HotkeyModifiers;
+use handsoff::audit::{self, UnlockMethod};
+use handsoff::{config, config_file::{Config, HOTKEY_DISABLED}, HandsOffCore};
 use log::{error, info, warn};
 use std::cell::RefCell;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use tao::event::Event;
 use tao::event_loop::{ControlFlow, EventLoopBuilder};
-use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
 use tray_icon::TrayIconBuilder;
+use zeroize::Zeroizing;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const GIT_HASH: &str = env!("GIT_COMMIT_HASH");
@@ -32,27 +50,34 @@ struct Args {
     /// Run interactive setup to configure passphrase and timeouts
     #[arg(long)]
     setup: bool,
-}
 
-/// Helper function to prompt for a number with a default value
-fn prompt_number(prompt: &str, default: u64) -> Result<u64> {
-    print!("{}", prompt);
-    io::stdout().flush()?;
+    /// Config file path (overrides HANDS_OFF_CONFIG and the default location)
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let input = input.trim();
+    /// Use a named profile: config-<NAME>.toml in the config directory (overrides HANDS_OFF_PROFILE)
+    #[arg(long, value_name = "NAME", conflicts_with = "config")]
+    profile: Option<String>,
 
-    if input.is_empty() {
-        Ok(default)
-    } else {
-        input
-            .parse::<u64>()
-            .with_context(|| format!("Invalid number: {}", input))
-    }
+    /// Also write logs to this file, rotated by size (overrides HANDS_OFF_LOG_FILE)
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// More log output (-v debug, -vv trace; RUST_LOG takes precedence)
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Less log output (-q warnings and errors, -qq errors only)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    quiet: u8,
+
+    /// Never block input; log at debug level what would have been blocked
+    #[arg(long)]
+    dry_run: bool,
 }
 
-/// Prompt for a hotkey (single letter A-Z), returns Some(key) or None for default
+/// Prompt for a hotkey (single letter A-Z, or "none" to disable), returns Some(key) or
+/// None for default
 fn prompt_hotkey(prompt: &str, _default: &str) -> Result<Option<String>> {
     print!("{}", prompt);
     io::stdout().flush()?;
@@ -65,52 +90,65 @@ fn prompt_hotkey(prompt: &str, _default: &str) -> Result<Option<String>> {
         Ok(None) // Use default
     } else {
         // Validate the input
-        Config::validate_hotkey(input)?;
+        Config::validate_lock_talk_hotkey(input)?;
+        if Config::is_hotkey_disabled(input) {
+            return Ok(Some(HOTKEY_DISABLED.to_string()));
+        }
         Ok(Some(input.to_uppercase()))
     }
 }
 
 /// Run interactive setup to configure passphrase and timeouts
-fn run_setup() -> Result<()> {
+fn run_setup(config_path: &Path) -> Result<()> {
     println!("HandsOff Setup");
     println!("==============\n");
 
     // Prompt for passphrase (non-echoing)
-    let passphrase =
-        rpassword::prompt_password("Enter passphrase: ").context("Failed to read passphrase")?;
+    // Plaintext is scrubbed from memory when these go out of scope
+    let passphrase = Zeroizing::new(
+        rpassword::prompt_password("Enter passphrase: ").context("Failed to read passphrase")?,
+    );
 
-    if passphrase.is_empty() {
-        anyhow::bail!("Error: Passphrase cannot be empty");
-    }
+    Config::validate_passphrase(&passphrase)?;
 
     // Confirm passphrase
-    let confirm = rpassword::prompt_password("Confirm passphrase: ")
-        .context("Failed to read confirmation")?;
+    let confirm = Zeroizing::new(
+        rpassword::prompt_password("Confirm passphrase: ").context("Failed to read confirmation")?,
+    );
 
     if passphrase != confirm {
         anyhow::bail!("Error: Passphrases do not match");
     }
 
+    setup_prompts::confirm_typable_passphrase(&passphrase)?;
+
     // Prompt for hotkeys
     println!("\nHotkey Configuration");
     println!("--------------------");
-    println!("Configure the hotkeys (modifiers Cmd+Ctrl+Shift are mandatory, but choose the last key).");
-    println!("Enter a single letter A-Z, or press Enter to use the default.\n");
+    println!("Configure the hotkeys (choose the last key; modifiers default to Cmd+Ctrl+Shift and can be changed with lock_modifiers/talk_modifiers in config.toml).");
+    println!("Enter a single letter A-Z, 'none' to disable the hotkey, or press Enter to use the default.\n");
 
     let lock_key = prompt_hotkey("Lock hotkey (default: L): ", "L")?;
     let talk_key = prompt_hotkey("Talk hotkey (Hotkey to Unmute, default: T): ", "T")?;
 
-    // Validate that lock and talk keys are different
-    if let (Some(ref lock), Some(ref talk)) = (&lock_key, &talk_key) {
-        if lock == talk {
-            anyhow::bail!("Error: Lock and Talk hotkeys must be different");
-        }
-    }
+    // Validate that lock and talk keys are different (defaults included)
+    Config::validate_distinct_hotkeys(lock_key.as_deref(), talk_key.as_deref())?;
+
+    // Prompt for the key passed through while the Talk hotkey is held
+    println!("\nWhile the Talk hotkey is held, one key is passed through to the focused app");
+    println!("(e.g. the push-to-talk key of your conferencing app).");
+    let talk_passthrough_key = setup_prompts::prompt_talk_passthrough_key(
+        "Talk passthrough key (space or A-Z, default: space): ",
+    )?;
 
     // Prompt for timeouts
     println!("\nTimeout Configuration");
     println!("---------------------\n");
-    let auto_lock = prompt_number("Auto-lock timeout in seconds (default: 120): ", 120)?;
+    let auto_lock = setup_prompts::prompt_timeout(
+        "Auto-lock timeout in seconds (default: 120): ",
+        120,
+        Config::validate_auto_lock_timeout,
+    )?;
 
     // Build-dependent default for auto-unlock:
     // - Release builds: 0 seconds (disabled by default for end users)
@@ -119,18 +157,44 @@ fn run_setup() -> Result<()> {
         "Auto-unlock timeout in seconds (default: {}): ",
         AUTO_UNLOCK_DEFAULT_SECONDS
     );
-    let auto_unlock = prompt_number(&auto_unlock_prompt, AUTO_UNLOCK_DEFAULT_SECONDS)?;
+    let auto_unlock = setup_prompts::prompt_timeout(
+        &auto_unlock_prompt,
+        AUTO_UNLOCK_DEFAULT_SECONDS,
+        Config::validate_auto_unlock_timeout,
+    )?;
+
+    // Prompt for lock scope
+    println!("\nLock Scope");
+    println!("----------");
+    println!("Choose which input is blocked while locked: all, keyboard or mouse.");
+    println!("Note: with 'mouse' the keyboard stays usable, so the passphrase cannot be typed to unlock");
+    println!("(use Touch ID or auto-unlock instead).\n");
+    let lock_scope = setup_prompts::prompt_lock_scope("Lock scope (default: all): ")?;
 
     // Create and save config
-    let config = Config::new(&passphrase, auto_lock, auto_unlock, lock_key, talk_key)
+    let mut config = Config::new(&passphrase, auto_lock, auto_unlock, lock_key, talk_key)
         .context("Failed to create configuration")?;
+    config.lock_scope = lock_scope;
+    config.talk_passthrough_key = talk_passthrough_key;
+    config.validate_talk_passthrough_conflicts()?;
 
-    config.save().context("Failed to save configuration")?;
+    // Generate a one-time emergency recovery code (only its hash is stored)
+    let recovery_code =
+        handsoff::auth::generate_recovery_code().context("Failed to generate recovery code")?;
+    config.set_recovery_code(&recovery_code);
+
+    config
+        .save_to_path(config_path)
+        .context("Failed to save configuration")?;
 
     println!(
         "\nConfiguration saved to: {}",
-        Config::config_path().display()
+        config_path.display()
     );
+    println!("\nEmergency recovery code (shown only once - write it down!):");
+    println!("    {}", recovery_code);
+    println!("If you forget your passphrase, type this code while locked to unlock.");
+    println!("The code works only once; run setup again to generate a new one.");
     println!("Setup complete!");
     println!("\nThe tray app will use this configuration at next startup.");
 
@@ -141,15 +205,21 @@ fn main() -> Result<()> {
     // Parse command-line arguments
     let args = Args::parse();
 
+    // Resolve config file path
+    // (precedence: --config > --profile > HANDS_OFF_CONFIG > HANDS_OFF_PROFILE > default)
+    let config_path = config::resolve_config_path(args.config.clone(), args.profile.clone())?;
+
     // Handle setup command
     if args.setup {
-        return run_setup();
+        return run_setup(&config_path);
     }
 
-    // Initialize logger
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .init();
+    // Initialize logger (precedence: --log-file > HANDS_OFF_LOG_FILE > stderr only)
+    let log_file = config::resolve_log_file_path(args.log_file.clone());
+    handsoff::logging::init(
+        handsoff::logging::level_from_flags(args.verbose, args.quiet),
+        log_file.as_deref(),
+    );
 
     info!("Starting HandsOff Tray App v{}", VERSION);
 
@@ -163,8 +233,18 @@ fn main() -> Result<()> {
         info!("Accessibility permissions verified");
     }
 
+    // Establish the machine secret used to encrypt the config file
+    if let Err(e) = handsoff::crypto::init_key() {
+        error!("Failed to initialize config encryption: {:#}", e);
+        show_alert(
+            "HandsOff - Configuration Error",
+            &format!("Unable to initialize config encryption.\n\nError: {:#}", e),
+        );
+        std::process::exit(1);
+    }
+
     // Load configuration, or create default if missing
-    let cfg = match Config::load() {
+    let cfg = match Config::load_from_path(&config_path) {
         Ok(cfg) => cfg,
         Err(e) => {
             info!("Configuration not found, creating default config with passphrase '{}': {}", DEFAULT_PASSPHRASE, e);
@@ -177,10 +257,10 @@ fn main() -> Result<()> {
             // - talk_hotkey: None (defaults to T)
             match Config::new(DEFAULT_PASSPHRASE, 120, 0, None, None) {
                 Ok(config) => {
-                    if let Err(save_err) = config.save() {
+                    if let Err(save_err) = config.save_to_path(&config_path) {
                         warn!("Failed to save default config: {}", save_err);
                     } else {
-                        info!("Default configuration saved to: {}", Config::config_path().display());
+                        info!("Default configuration saved to: {}", config_path.display());
                     }
                     config
                 }
@@ -196,17 +276,17 @@ fn main() -> Result<()> {
         }
     };
 
-    // Decrypt passphrase
-    let passphrase = match cfg.get_passphrase() {
+    // Load passphrase hash (from Keychain if enabled, otherwise decrypted from config file)
+    let passphrase_hash = match cfg.get_passphrase_hash() {
         Ok(p) => {
             info!(
                 "Configuration loaded from: {}",
-                Config::config_path().display()
+                config_path.display()
             );
             p
         }
         Err(e) => {
-            error!("Failed to decrypt passphrase: {}", e);
+            error!("Failed to load passphrase: {}", e);
             show_alert(
                 "HandsOff - Configuration Error",
                 &format!("Unable to read your saved passphrase.\nYour settings file may need to be recreated.\n\nRun setup again:\n~/Applications/HandsOff.app/Contents/MacOS/handsoff-tray --setup\n\nError: {}", e)
@@ -216,7 +296,8 @@ fn main() -> Result<()> {
     };
 
     // Create HandsOffCore instance
-    let mut core = HandsOffCore::new(&passphrase).context("Failed to initialize HandsOff")?;
+    let mut core = HandsOffCore::with_passphrase_hash(passphrase_hash)
+        .context("Failed to initialize HandsOff")?;
 
     // Configure auto-unlock timeout (precedence: env var > config file > build default)
     let auto_unlock_timeout = config::resolve_auto_unlock_timeout(cfg.auto_unlock_timeout);
@@ -226,6 +307,105 @@ fn main() -> Result<()> {
     let auto_lock_timeout = config::parse_auto_lock_timeout().or(Some(cfg.auto_lock_timeout));
     core.set_auto_lock_timeout(auto_lock_timeout);
 
+    // Configure the pre-lock warning (config file, default 10 seconds, 0 = off)
+    core.set_auto_lock_warning(config::resolve_auto_lock_warning(cfg.auto_lock_warning));
+
+    // Configure the post-unlock grace period (config file, default 10 seconds, 0 = off)
+    core.set_unlock_grace(config::resolve_unlock_grace(cfg.unlock_grace_secs));
+
+    // Apply notification settings before background threads can notify
+    // Custom/localized message texts (config [messages] table)
+    strings::set_overrides(strings::parse_overrides(&cfg.messages));
+
+    ui::notify::configure(NotificationSettings {
+        enabled: cfg.notifications_enabled,
+        timeout_ms: config::resolve_notification_timeout(cfg.notification_timeout_ms),
+    });
+
+    // A second instance (tray or CLI) would add another tray icon and event tap fighting
+    // over input - leave the running one in charge
+    let mut instance_lock = match InstanceLock::acquire(instance_lock::default_path(&config_path)) {
+        Ok(lock) => Some(lock),
+        Err(e) => match e.downcast_ref::<AlreadyRunning>() {
+            Some(running) => {
+                warn!("{} - exiting", running);
+                ui::notify::notify_with(
+                    Level::Important,
+                    &strings::get(MessageKey::NotificationTitle),
+                    &strings::format(
+                        MessageKey::AlreadyRunning,
+                        &[("pid", &running.pid.to_string())],
+                    ),
+                );
+                return Ok(());
+            }
+            None => {
+                // Not worth refusing to start over
+                warn!("{:#} - not guarding against a second instance", e);
+                None
+            }
+        },
+    };
+
+    // Hard cap on lock duration, independent of auto-unlock (config file, default 60 minutes)
+    core.set_max_lock_duration(config::resolve_max_lock_duration(cfg.max_lock_duration_mins));
+
+    // Configure the event loop watchdog (config file, default 30 seconds)
+    core.set_watchdog_timeout(config::resolve_watchdog_timeout(cfg.watchdog_timeout_secs));
+
+    // Optionally hold off auto-lock while media is playing
+    core.set_respect_media_playback(cfg.respect_media_playback);
+
+    // Optionally keep a twitchy mouse from holding off auto-lock
+    core.set_auto_lock_ignore_mouse_move(cfg.auto_lock_ignore_mouse_move);
+
+    // Optionally adjust auto-lock to the power source (config file, default off)
+    core.set_power_auto_lock_policy(PowerAutoLockPolicy {
+        timeout_on_battery: config::resolve_auto_lock_timeout_on_battery(
+            cfg.auto_lock_timeout_on_battery,
+        ),
+        lock_on_ac: !cfg.auto_lock_only_on_battery,
+    });
+
+    // Optionally ignore isolated stray events for auto-lock (config file, default off)
+    core.set_activity_debounce(
+        config::resolve_activity_debounce_events(cfg.activity_debounce_events),
+        config::resolve_activity_debounce_window(cfg.activity_debounce_window_ms),
+    );
+
+    // Configure buffer reset timeout (precedence: env var > config file > default)
+    core.set_buffer_reset_timeout(config::resolve_buffer_reset_timeout(cfg.buffer_reset_timeout));
+
+    // Configure lock scope (precedence: env var > config file > default all)
+    core.set_lock_scope(config::parse_lock_scope().unwrap_or(cfg.lock_scope));
+
+    // Configure media/volume/brightness key blocking while locked
+    core.set_block_media_keys(cfg.block_media_keys);
+
+    // Configure power / Touch ID key blocking while locked (best effort, see README)
+    core.set_block_power_key(cfg.block_power_key);
+
+    // Dry-run: run everything but never block (for trying hotkeys and timeouts safely)
+    core.set_dry_run(args.dry_run);
+
+    // Beep when a passphrase attempt times out (config file, default off)
+    core.set_feedback_sound(cfg.feedback_sound);
+
+    // Discard attempts longer than the passphrase right away (config file, default off)
+    core.set_clear_overlong_attempts(cfg.clear_overlong_attempts, cfg.get_passphrase_len());
+
+    // Wait after a full-length wrong attempt (config file, default on)
+    core.set_attempt_cooldown(cfg.attempt_cooldown);
+
+    // Configure scheduled lock windows (validated when the config was loaded)
+    match cfg.schedule.parse_windows() {
+        Ok(windows) => core.set_schedule(windows),
+        Err(e) => warn!("Ignoring invalid lock schedule: {:#}", e),
+    }
+
+    // Configure apps that stay interactive while locked
+    core.set_app_allowlist(cfg.app_allowlist.clone());
+
     // Configure hotkeys from config file only (tray app does not support env var overrides)
     let lock_key = cfg.get_lock_key_code().with_context(|| {
         "Failed to parse lock hotkey from config file. Run setup: ~/Applications/HandsOff.app/Contents/MacOS/handsoff-tray --setup"
@@ -234,14 +414,14 @@ fn main() -> Result<()> {
         "Failed to parse talk hotkey from config file. Run setup: ~/Applications/HandsOff.app/Contents/MacOS/handsoff-tray --setup"
     })?;
 
-    // Validate that configured hotkeys are different
-    if lock_key == talk_key {
-        error!("Lock and Talk hotkeys cannot be the same: {:?}", lock_key);
+    // Validate that configured hotkeys are different (both disabled is fine)
+    if let Some(key) = lock_key.filter(|_| lock_key == talk_key) {
+        error!("Lock and Talk hotkeys cannot be the same: {:?}", key);
         show_alert(
             "HandsOff - Configuration Error",
             &format!(
                 "Lock and Talk hotkeys cannot be the same.\n\nBoth are set to: {:?}\n\nThis is likely because the config file was manually edited.\n\nPlease run setup to reconfigure:\n~/Applications/HandsOff.app/Contents/MacOS/handsoff-tray --setup",
-                lock_key
+                key
             ),
         );
         std::process::exit(1);
@@ -249,11 +429,90 @@ fn main() -> Result<()> {
 
     core.set_hotkey_config(lock_key, talk_key);
 
+    // Configure hotkey modifiers (config file > default Ctrl+Cmd+Shift)
+    let lock_modifiers = cfg.get_lock_modifiers().unwrap_or_else(|e| {
+        warn!("Invalid lock_modifiers in config file, using default (Ctrl+Cmd+Shift): {}", e);
+        HotkeyModifiers::DEFAULT
+    });
+    let talk_modifiers = cfg.get_talk_modifiers().unwrap_or_else(|e| {
+        warn!("Invalid talk_modifiers in config file, using default (Ctrl+Cmd+Shift): {}", e);
+        HotkeyModifiers::DEFAULT
+    });
+    core.set_hotkey_modifiers(lock_modifiers, talk_modifiers);
+    core.set_lock_double_press(
+        cfg.lock_double_press
+            .then(|| config::resolve_lock_double_press_window(cfg.lock_double_press_window_ms)),
+    );
+    core.set_recovery_code_hash(cfg.recovery_code_hash.clone());
+    core.set_audit_log_path(audit::default_path(&config_path));
+
+    // Configure the key passed through in Talk mode (config file > default spacebar)
+    match cfg.get_talk_passthrough_keycode() {
+        Ok(keycode) => core.set_talk_passthrough_keycode(keycode),
+        Err(e) => warn!("Invalid talk passthrough key in config file, using spacebar: {}", e),
+    }
+
+    // Configure Talk hotkey behaviour (hold or toggle)
+    core.set_talk_mode(cfg.talk_mode);
+
+    // What happens when accessibility permissions are lost (unlock, exit or retry)
+    core.set_permission_loss_policy(cfg.on_permission_loss);
+
+    // Keyboard layout used to translate keycodes while typing the passphrase
+    core.set_keyboard_layout(cfg.keyboard_layout);
+
+    // Configure Touch ID unlock hotkey (config file > default U)
+    match cfg.get_unlock_key_code() {
+        Ok(unlock_key) => core.set_unlock_hotkey(unlock_key),
+        Err(e) => warn!("Invalid unlock hotkey in config file, using default (U): {}", e),
+    }
+
+    // Configure emergency unlock (panic) hotkey (config file only, disabled by default)
+    match (cfg.get_panic_key_code(), cfg.get_panic_modifiers()) {
+        (Ok(panic_key), Ok(panic_modifiers)) => {
+            core.set_panic_hotkey(panic_key, panic_modifiers, cfg.panic_requires_touchid)
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            warn!("Invalid panic hotkey in config file, panic hotkey disabled: {}", e)
+        }
+    }
+
+    // Configure the unlock chord sequence (config file only, disabled by default)
+    match cfg.get_unlock_chord() {
+        Ok(chord) => core.set_unlock_chord(chord),
+        Err(e) => warn!("Invalid unlock chord in config file, unlock sequence disabled: {}", e),
+    }
+
+    // Configure hold-to-unlock (config file only, disabled by default)
+    match cfg.get_hold_unlock() {
+        Ok(hold) => core.set_hold_unlock(hold),
+        Err(e) => warn!("Invalid hold-to-unlock settings in config file, hold-to-unlock disabled: {}", e),
+    }
+
+    // Optionally require Touch ID after the passphrase (config file only)
+    core.set_two_factor_unlock(cfg.two_factor_unlock);
+
     // Start core components only if we have accessibility permissions
     if initial_permissions {
         core.start_event_tap()
             .context("Failed to start input blocking")?;
-        core.start_hotkeys().context("Failed to start hotkeys")?;
+        if let Err(e) = core.start_hotkeys() {
+            match e.hotkey_registration_error().filter(|err| err.is_conflict()) {
+                Some(conflict) => {
+                    warn!("{:#}", e);
+                    show_alert(
+                        "HandsOff - Hotkey Conflict",
+                        &format!(
+                            "{}.\n\nThe HandsOff {} hotkey may not work. Choose different modifiers with {} in config.toml (suggested alternatives are in the log).",
+                            conflict,
+                            conflict.kind(),
+                            conflict.kind().modifiers_config_key()
+                        ),
+                    );
+                }
+                None => return Err(e).context("Failed to start hotkeys"),
+            }
+        }
         info!("HandsOff core components started");
     } else {
         info!("Skipping event tap and hotkeys start - waiting for accessibility permissions");
@@ -263,6 +522,46 @@ fn main() -> Result<()> {
     core.start_background_threads()
         .context("Failed to start background threads")?;
 
+    // Optional control socket for scripting
+    if cfg.control_socket {
+        if let Err(e) = core.start_ipc_server() {
+            warn!("Failed to start control socket: {:#}", e);
+        }
+    }
+
+    // Optional localhost HTTP API for home-automation dashboards
+    if let Some(port) = cfg.http_api_port {
+        match cfg.http_api_token.as_deref() {
+            Some(token) => {
+                if let Err(e) = core.start_http_api(port, token) {
+                    warn!("Failed to start HTTP API: {:#}", e);
+                }
+            }
+            None => warn!("http_api_port is set but http_api_token is missing - HTTP API not started"),
+        }
+    }
+
+    // Log sleep/wake and screen events, optionally locking on them (main thread)
+    let lock_triggers = LockTriggers {
+        on_wake: cfg.lock_on_wake,
+        on_screensaver: cfg.lock_on_screensaver,
+    };
+    if let Err(e) = sleep_wake::install(core.state.clone(), lock_triggers) {
+        warn!("Failed to observe sleep/wake: {:#}", e);
+    }
+
+    // Lock when a listed app comes to the front (main thread)
+    if !cfg.lock_on_app.is_empty() {
+        if let Err(e) = app_activation::install(core.state.clone(), cfg.lock_on_app.clone()) {
+            warn!("Failed to observe the frontmost app: {:#}", e);
+        }
+    }
+
+    // AppleScript commands (lock, disable, is locked - see HandsOff.sdef)
+    if let Err(e) = scripting::install(core.state.clone()) {
+        warn!("Failed to install AppleScript handlers: {:#}", e);
+    }
+
     // NOTE: CFRunLoop thread is now managed by HandsOffCore
     // It starts when event tap is created and stops when event tap is destroyed
     // This eliminates the zombie CFRunLoop connection that caused WindowServer issues
@@ -278,17 +577,53 @@ fn main() -> Result<()> {
     // Lock menu item only works when unlocked; unlock requires typing passphrase
     let lock_item = MenuItem::new("Lock Input", true, None);
     let disable_item = MenuItem::new("Disable", true, None);
+    let pause_menu = Submenu::new("Pause", true);
+    let pause_items: Vec<(MenuItem, u64)> = PAUSE_CHOICES_MINUTES
+        .iter()
+        .map(|&minutes| (MenuItem::new(format!("{} minutes", minutes), true, None), minutes))
+        .collect();
+    for (item, _) in &pause_items {
+        pause_menu
+            .append(item)
+            .context("Failed to add pause menu item")?;
+    }
+    let snooze_menu = Submenu::new("Snooze Auto-Lock", true);
+    let snooze_items: Vec<(MenuItem, u64)> = AUTO_LOCK_SNOOZE_CHOICES_MINUTES
+        .iter()
+        .map(|&minutes| (MenuItem::new(format!("{} minutes", minutes), true, None), minutes))
+        .collect();
+    for (item, _) in &snooze_items {
+        snooze_menu
+            .append(item)
+            .context("Failed to add snooze menu item")?;
+    }
     let separator = PredefinedMenuItem::separator();
+    let settings_item = MenuItem::new("Settings…", true, None);
+    let change_passphrase_item = MenuItem::new("Change Passphrase…", true, None);
+    let test_passphrase_item = MenuItem::new("Test Passphrase…", true, None);
     let reset_item = MenuItem::new("Reset", true, None);
+    let quit_item = MenuItem::new("Quit HandsOff", true, None);
 
     let menu = Menu::new();
     menu.append(&lock_item)
         .context("Failed to add lock menu item")?;
     menu.append(&disable_item)
         .context("Failed to add disable menu item")?;
+    menu.append(&pause_menu)
+        .context("Failed to add pause submenu")?;
+    menu.append(&snooze_menu)
+        .context("Failed to add snooze submenu")?;
     menu.append(&separator).context("Failed to add separator")?;
+    menu.append(&settings_item)
+        .context("Failed to add settings menu item")?;
+    menu.append(&change_passphrase_item)
+        .context("Failed to add change passphrase menu item")?;
+    menu.append(&test_passphrase_item)
+        .context("Failed to add test passphrase menu item")?;
     menu.append(&reset_item)
         .context("Failed to add reset menu item")?;
+    menu.append(&quit_item)
+        .context("Failed to add quit menu item")?;
 
     // Create tray icon
     let icon = create_icon_unlocked();
@@ -299,24 +634,54 @@ fn main() -> Result<()> {
         .build()
         .context("Failed to create tray icon")?;
 
+    // Optional click-through overlay shown while locked (AppKit: must be created on the main thread)
+    let mut lock_overlay = if cfg.lock_overlay {
+        let overlay = LockOverlay::new();
+        if overlay.is_none() {
+            warn!("Failed to create lock overlay window - continuing without it");
+        }
+        overlay
+    } else {
+        None
+    };
+
+    // Optional countdown next to the icon (tooltips only show on hover)
+    let menu_bar_countdown = cfg.menu_bar_countdown;
+
+    // Optional OK/Cancel prompt before Disable/Reset, against accidental clicks
+    let confirm_guard = dialogs::ConfirmGuard {
+        disable: cfg.confirm_disable,
+        reset: cfg.confirm_reset,
+    };
+
     info!("Tray icon created, running event loop");
 
     // Clone IDs for event handling
     let lock_id = lock_item.id().clone();
     let disable_id = disable_item.id().clone();
+    let settings_id = settings_item.id().clone();
+    let change_passphrase_id = change_passphrase_item.id().clone();
+    let test_passphrase_id = test_passphrase_item.id().clone();
     let reset_id = reset_item.id().clone();
-
-    // Store passphrase for reset functionality
-    let passphrase_for_reset = passphrase.clone();
+    let quit_id = quit_item.id().clone();
+    let pause_ids: Vec<_> = pause_items
+        .iter()
+        .map(|(item, minutes)| (item.id().clone(), *minutes))
+        .collect();
+    let snooze_ids: Vec<_> = snooze_items
+        .iter()
+        .map(|(item, minutes)| (item.id().clone(), *minutes))
+        .collect();
 
     // Track state for tooltip updates and permission state
     let mut was_locked = false;
     let mut was_disabled = false;
     let mut last_tooltip = String::new();
+    let mut last_title: Option<String> = None;
     let mut has_permissions = true; // Assume true at start (already verified at startup)
 
     // Run event loop with periodic updates
-    event_loop.run(move |_event, _, control_flow| {
+    event_loop.run(move |event, _, control_flow| {
         // Adjust polling interval based on disabled state
         // When disabled: minimal WindowServer interaction
         // When enabled: responsive UI updates
@@ -333,6 +698,16 @@ fn main() -> Result<()> {
             std::time::Instant::now() + poll_interval
         );
 
+        // Snapshot the active keyboard layout for passphrase entry (TIS needs the main thread)
+        keycode::refresh_keyboard_layout();
+
+        // Handle handsoff:// URLs (Shortcuts/automation)
+        if let Event::Opened { urls } = &event {
+            for url in urls {
+                handle_url(core.clone(), url.as_str(), confirm_guard);
+            }
+        }
+
         // Handle menu events
         if let Ok(event) = MenuEvent::receiver().try_recv() {
             let event_id = event.id;
@@ -341,72 +716,94 @@ fn main() -> Result<()> {
                 handle_lock_toggle(core.clone());
             } else if event_id == disable_id {
                 info!("Disable menu item clicked");
-                handle_disable(core.clone());
+                handle_confirmed_disable(core.clone(), confirm_guard);
+            } else if event_id == settings_id {
+                info!("Settings menu item clicked");
+                handle_settings(&config_path);
+            } else if event_id == change_passphrase_id {
+                info!("Change Passphrase menu item clicked");
+                handle_change_passphrase(core.clone(), &config_path);
+            } else if event_id == test_passphrase_id {
+                info!("Test Passphrase menu item clicked");
+                handle_test_passphrase(core.clone());
             } else if event_id == reset_id {
                 info!("Reset menu item clicked, resetting app state");
-                handle_reset(core.clone(), &passphrase_for_reset);
+                let ran = confirm_guard.run(
+                    dialogs::GuardedAction::Reset,
+                    dialogs::show_confirm_dialog,
+                    || handle_reset(core.clone()),
+                );
+                if !ran {
+                    info!("Reset cancelled");
+                }
+            } else if let Some((_, minutes)) = pause_ids.iter().find(|(id, _)| *id == event_id) {
+                info!("Pause menu item clicked ({} minutes)", minutes);
+                handle_pause(core.clone(), *minutes);
+            } else if let Some((_, minutes)) = snooze_ids.iter().find(|(id, _)| *id == event_id) {
+                info!("Snooze auto-lock menu item clicked ({} minutes)", minutes);
+                handle_snooze(core.clone(), *minutes);
+            } else if event_id == quit_id {
+                info!("Quit menu item clicked");
+                if handle_quit(core.clone()) {
+                    if let Some(lock_overlay) = lock_overlay.as_mut() {
+                        lock_overlay.hide();
+                    }
+                    // The event loop exits the process without dropping its captures
+                    drop(instance_lock.take());
+                    *control_flow = ControlFlow::Exit;
+                    return;
+                }
             }
         }
 
-        // Check if event tap should be stopped (due to permission loss)
-        {
-            let mut core_borrow = core.borrow_mut();
-            if core_borrow.state.should_stop_event_tap_and_clear() {
-                warn!("Tray: Stopping input blocking due to permission loss");
-                core_borrow.stop_event_tap();
-                info!("Tray: Input blocking stopped - normal input restored");
+        // Re-enable once a pause from the menu has run out
+        if core.borrow().state.is_pause_expired() {
+            info!("Tray: Pause expired, re-enabling HandsOff");
+            handle_pause_expired(core.clone());
+        }
+
+        // Apply disable/enable requested over the control socket
+        if core.borrow().state.should_disable_and_clear() {
+            info!("Tray: Disable requested via control socket");
+            handle_disable(core.clone());
+        }
+        let enable_requested = core.borrow().state.should_enable_and_clear();
+        if enable_requested && core.borrow().state.is_disabled() {
+            info!("Tray: Enable requested via control socket");
+            let result = core.borrow_mut().enable();
+            if let Err(e) = result {
+                error!("Tray: Failed to enable: {}", e);
             }
         }
 
-        // Check if existing event tap should be re-enabled (post sleep/wake timeout recovery).
-        // This reuses the same CGEventTapRef — no new WindowServer connection is created,
-        // which prevents zombie Mach port accumulation across sleep/wake cycles.
+        // Check if event tap should be stopped (due to permission loss)
         {
             let mut core_borrow = core.borrow_mut();
-            if core_borrow.state.should_reenable_event_tap_and_clear() {
-                info!("Tray: Re-enabling existing event tap after sleep/wake timeout");
-                if let Err(e) = core_borrow.reenable_event_tap() {
-                    warn!("Tray: Failed to re-enable event tap: {} — will attempt full restart", e);
-                    // reenable_event_tap already falls back to restart internally,
-                    // but log the failure so it's visible in telemetry
-                }
+
+            // Stop, re-enable (post sleep/wake timeout) or restart (permissions restored)
+            // the event tap as requested by the background threads
+            for action in core_borrow.ensure_running().actions {
+                report_health_action(&action);
+            }
+
+            // Permissions lost while running: wait for them to return (the monitor
+            // requests the restart), or exit for a supervisor to relaunch the app
+            let policy = core_borrow.state.get_permission_loss_policy();
+            if core_borrow.state.should_exit_and_clear()
+                && policy.action(true) == PermissionLossAction::ExitNow
+            {
+                warn!("Tray: Accessibility permissions lost - exiting (on_permission_loss = exit)");
+                core_borrow.shutdown();
+                let _ = std::fs::remove_file(handsoff::status::status_path());
+                std::process::exit(1);
             }
         }
 
-        // Check if event tap should be started (permission restored)
-        {
-            let mut core_borrow = core.borrow_mut();
-            if core_borrow.state.should_start_event_tap_and_clear() {
-                info!("Tray: Restarting input blocking - permissions restored");
-                match core_borrow.restart_event_tap() {
-                    Ok(()) => {
-                        info!("Tray: Input blocking restarted successfully");
-
-                        #[cfg(target_os = "macos")]
-                        {
-                            let _ = notify_rust::Notification::new()
-                                .summary("HandsOff - Input Blocking Restarted")
-                                .body("Input blocking restarted successfully.\nHandsOff is now active.")
-                                .timeout(notify_rust::Timeout::Milliseconds(NOTIFICATION_TIMEOUT_MS))
-                                .show();
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Tray: Failed to restart input blocking: {}", e);
-
-                        #[cfg(target_os = "macos")]
-                        {
-                            let _ = notify_rust::Notification::new()
-                                .summary("HandsOff - Restart Failed")
-                                .body(&format!(
-                                    "Failed to restart input blocking: {}\n\nUse Reset menu to try again.",
-                                    e
-                                ))
-                                .timeout(notify_rust::Timeout::Milliseconds(NOTIFICATION_ERROR_TIMEOUT_MS))
-                                .show();
-                        }
-                    }
-                }
+        // Persist the invalidated recovery code after it was used to unlock
+        if core.borrow().state.should_persist_recovery_code_consumed_and_clear() {
+            match Config::persist_recovery_code_consumed(&config_path) {
+                Ok(()) => info!("Tray: Used recovery code cleared from configuration"),
+                Err(e) => error!("Tray: Failed to clear used recovery code: {}", e),
             }
         }
 
@@ -416,15 +813,14 @@ fn main() -> Result<()> {
         let is_disabled = core_borrow.state.is_disabled();
         let current_permissions = core_borrow.has_accessibility_permissions();
 
-        // Update Lock menu item enabled state based on permissions and disabled state
-        // Only enable Lock when we have permissions AND are not already locked AND not disabled
-        let should_enable_lock = current_permissions && !is_locked && !is_disabled;
-        lock_item.set_enabled(should_enable_lock);
-
-        // Update Disable menu item enabled state
-        // Only enable Disable when we have permissions AND are not locked AND not already disabled
-        let should_enable_disable = current_permissions && !is_locked && !is_disabled;
-        disable_item.set_enabled(should_enable_disable);
+        // Update menu item enabled states (see ui::menu for the rules)
+        let menu_state = MenuState::new(current_permissions, is_locked, is_disabled);
+        lock_item.set_enabled(menu_state.lock);
+        disable_item.set_enabled(menu_state.disable);
+        pause_menu.set_enabled(menu_state.pause);
+        snooze_menu.set_enabled(menu_state.snooze);
+        change_passphrase_item.set_enabled(menu_state.change_passphrase);
+        quit_item.set_enabled(menu_state.quit);
 
         // Track permission state changes for logging
         if has_permissions != current_permissions {
@@ -453,19 +849,33 @@ fn main() -> Result<()> {
             }
 
             // Show notification on state change (but not for disabled, handled elsewhere)
-            #[cfg(target_os = "macos")]
-            {
-                if !is_disabled {
-                    let _ = notify_rust::Notification::new()
-                        .summary("HandsOff")
-                        .body(if is_locked {
-                            "Input locked - Type passphrase to unlock"
-                        } else {
-                            "Input unlocked"
-                        })
-                        .timeout(notify_rust::Timeout::Milliseconds(NOTIFICATION_TIMEOUT_MS))
-                        .show();
-                }
+            if !is_disabled {
+                let body = if is_locked {
+                    strings::get(MessageKey::InputLocked)
+                } else {
+                    unlocked_message(core_borrow.state.get_blocked_event_count())
+                };
+                ui::notify(&strings::get(MessageKey::NotificationTitle), &body);
+            }
+        }
+
+        // Show the lock overlay while locked and keep its countdown current
+        if let Some(lock_overlay) = lock_overlay.as_mut() {
+            if is_locked && !is_disabled {
+                let typed = match core_borrow.get_hold_unlock_progress() {
+                    Some(progress) => overlay::hold_progress_text(progress),
+                    None => overlay::typed_text(
+                        core_borrow.get_buffer_len(),
+                        core_borrow.get_attempt_cooldown_remaining(),
+                    ),
+                };
+                lock_overlay.set_typed(&typed);
+                lock_overlay.set_countdown(&overlay::countdown_text(
+                    core_borrow.get_auto_unlock_remaining_secs(),
+                ));
+                lock_overlay.show();
+            } else {
+                lock_overlay.hide();
             }
         }
 
@@ -477,12 +887,61 @@ fn main() -> Result<()> {
             }
             last_tooltip = tooltip;
         }
+
+        if menu_bar_countdown {
+            let remaining = if is_disabled {
+                None
+            } else if is_locked {
+                core_borrow.get_auto_unlock_remaining_secs()
+            } else if core_borrow.get_snooze_remaining_secs().is_some()
+                || core_borrow.is_auto_lock_paused_for_media()
+            {
+                None
+            } else {
+                core_borrow.get_auto_lock_remaining_secs()
+            };
+            let title = menu::countdown_title(is_locked, remaining);
+            if title != last_title {
+                tray.set_title(title.as_deref());
+                last_title = title;
+            }
+        }
     });
 }
 
 /// Handle lock from menu
 /// Note: This only handles locking, not unlocking. When locked, mouse clicks are blocked,
 /// so the menu is inaccessible. Users must type their passphrase to unlock (same as CLI).
+/// Log (and where useful, notify) what the event tap health check did
+fn report_health_action(action: &HealthAction) {
+    match action {
+        HealthAction::StoppedTap => {
+            warn!("Tray: Stopped input blocking due to permission loss or a stalled event loop - normal input restored");
+        }
+        HealthAction::ReenabledTap => {
+            info!("Tray: Re-enabled existing event tap after sleep/wake timeout");
+        }
+        HealthAction::ReenableFailed(e) => {
+            warn!("Tray: Failed to re-enable event tap: {}", e);
+        }
+        HealthAction::RestartedTap => {
+            info!("Tray: Input blocking restarted successfully");
+            ui::notify(
+                &strings::get(MessageKey::BlockingRestartedTitle),
+                &strings::get(MessageKey::BlockingRestarted),
+            );
+        }
+        HealthAction::RestartFailed(e) => {
+            warn!("Tray: Failed to restart input blocking: {}", e);
+            ui::notify::notify_with(
+                Level::Important,
+                &strings::get(MessageKey::RestartFailedTitle),
+                &strings::format(MessageKey::RestartFailed, &[("error", e)]),
+            );
+        }
+    }
+}
+
 fn handle_lock_toggle(core: Rc<RefCell<HandsOffCore>>) {
     let core = core.borrow();
 
@@ -501,6 +960,94 @@ fn handle_lock_toggle(core: Rc<RefCell<HandsOffCore>>) {
     }
 }
 
+/// Handle a handsoff:// URL (allowed in the same states as the matching menu item)
+fn handle_url(core: Rc<RefCell<HandsOffCore>>, url: &str, confirm_guard: dialogs::ConfirmGuard) {
+    // Don't log the URL itself - a mistaken unlock URL could carry a passphrase
+    let Some(command) = url_scheme::parse_command_url(url) else {
+        warn!("Ignoring unsupported handsoff:// URL");
+        return;
+    };
+
+    let menu_state = {
+        let core = core.borrow();
+        MenuState::new(
+            core.has_accessibility_permissions(),
+            core.is_locked(),
+            core.state.is_disabled(),
+        )
+    };
+    info!("URL command received: {:?}", command);
+    match command {
+        UrlCommand::Lock if menu_state.lock => handle_lock_toggle(core),
+        UrlCommand::Disable if menu_state.disable => handle_confirmed_disable(core, confirm_guard),
+        UrlCommand::Snooze { minutes } if menu_state.snooze => handle_snooze(core, minutes),
+        _ => warn!("URL command {:?} is not available right now", command),
+    }
+}
+
+/// Handle snooze from menu
+/// Suspends auto-lock for the chosen number of minutes (cleared by locking or Reset)
+fn handle_snooze(core: Rc<RefCell<HandsOffCore>>, minutes: u64) {
+    let core = core.borrow();
+    core.snooze_auto_lock(std::time::Duration::from_secs(minutes * 60));
+
+    ui::notify(
+        &strings::get(MessageKey::NotificationTitle),
+        &strings::format(MessageKey::AutoLockSnoozed, &[("minutes", &minutes.to_string())]),
+    );
+}
+
+/// Handle pause from menu
+/// Disables HandsOff for the chosen number of minutes; the event loop re-enables it
+fn handle_pause(core: Rc<RefCell<HandsOffCore>>, minutes: u64) {
+    let mut core = core.borrow_mut();
+
+    if let Err(e) = core.pause(std::time::Duration::from_secs(minutes * 60)) {
+        error!("Error pausing: {}", e);
+        show_alert("HandsOff - Error", &format!("Failed to pause: {}", e));
+    } else {
+        ui::notify(
+            &strings::get(MessageKey::NotificationTitle),
+            &strings::format(MessageKey::Paused, &[("minutes", &minutes.to_string())]),
+        );
+    }
+}
+
+/// Re-enable HandsOff after a pause ran out
+fn handle_pause_expired(core: Rc<RefCell<HandsOffCore>>) {
+    let mut core = core.borrow_mut();
+
+    match core.enable() {
+        Ok(()) => {
+            ui::notify(
+                &strings::get(MessageKey::NotificationTitle),
+                &strings::get(MessageKey::PauseOver),
+            );
+        }
+        Err(e) => {
+            // enable() cleared the pause, so this is reported once; Reset retries
+            error!("Tray: Failed to re-enable after pause: {}", e);
+            ui::notify::notify_with(
+                Level::Important,
+                &strings::get(MessageKey::ResumeFailedTitle),
+                &strings::format(MessageKey::ResumeFailed, &[("error", &e.to_string())]),
+            );
+        }
+    }
+}
+
+/// Disable, after asking first when `confirm_disable` is set (menu item and URL)
+fn handle_confirmed_disable(core: Rc<RefCell<HandsOffCore>>, confirm_guard: dialogs::ConfirmGuard) {
+    let ran = confirm_guard.run(
+        dialogs::GuardedAction::Disable,
+        dialogs::show_confirm_dialog,
+        || handle_disable(core),
+    );
+    if !ran {
+        info!("Disable cancelled - HandsOff stays enabled");
+    }
+}
+
 /// Handle disable from menu
 /// Disables HandsOff by stopping event tap and hotkeys for minimal CPU usage
 fn handle_disable(core: Rc<RefCell<HandsOffCore>>) {
@@ -511,47 +1058,183 @@ fn handle_disable(core: Rc<RefCell<HandsOffCore>>) {
         show_alert("HandsOff - Error", &format!("Failed to disable: {}", e));
     } else {
         info!("HandsOff disabled - low system resources mode (input blocking paused)");
-        #[cfg(target_os = "macos")]
-        {
-            let _ = notify_rust::Notification::new()
-                .summary("HandsOff")
-                .body("Disabled - Low system resources mode\nInput blocking paused. Use Reset to re-enable")
-                .timeout(notify_rust::Timeout::Milliseconds(NOTIFICATION_TIMEOUT_MS))
-                .show();
+        ui::notify(
+            &strings::get(MessageKey::NotificationTitle),
+            &strings::get(MessageKey::Disabled),
+        );
+    }
+}
+
+/// Handle settings from menu
+/// Shows the effective settings (config file plus environment overrides)
+fn handle_settings(config_path: &Path) {
+    match Config::load_from_path(config_path) {
+        Ok(cfg) => {
+            let settings = config::resolve_effective_settings(&cfg);
+            show_alert(
+                "HandsOff - Settings",
+                &dialogs::settings_dialog_text(&settings, config_path),
+            );
+        }
+        Err(e) => {
+            error!("Failed to load configuration for settings dialog: {:#}", e);
+            show_alert(
+                "HandsOff - Error",
+                &format!("Failed to read {}:\n{:#}", config_path.display(), e),
+            );
+        }
+    }
+}
+
+/// Handle change passphrase from menu
+/// Asks for the new passphrase twice, saves it to the config file and switches unlock
+/// checks over to it. Only while unlocked (the menu item is disabled while locked).
+fn handle_change_passphrase(core: Rc<RefCell<HandsOffCore>>, config_path: &Path) {
+    if core.borrow().is_locked() {
+        warn!("Change Passphrase ignored while input is locked");
+        return;
+    }
+
+    let passphrase = match dialogs::show_set_passphrase_dialog("Enter new passphrase:") {
+        Ok(Some(passphrase)) => passphrase,
+        Ok(None) => {
+            info!("Passphrase change cancelled");
+            return;
+        }
+        Err(e) => {
+            error!("Failed to show passphrase dialog: {}", e);
+            show_alert("HandsOff - Error", &format!("Failed to show passphrase dialog: {}", e));
+            return;
+        }
+    };
+    if let Err(e) = Config::validate_passphrase(&passphrase) {
+        show_alert("HandsOff - Passphrase Not Changed", &format!("{}.", e));
+        return;
+    }
+
+    match dialogs::show_set_passphrase_dialog("Confirm new passphrase:") {
+        Ok(Some(confirm)) if confirm == passphrase => {}
+        Ok(Some(_)) => {
+            show_alert("HandsOff - Passphrase Not Changed", "Passphrases do not match.");
+            return;
+        }
+        Ok(None) => {
+            info!("Passphrase change cancelled");
+            return;
+        }
+        Err(e) => {
+            error!("Failed to show passphrase dialog: {}", e);
+            show_alert("HandsOff - Error", &format!("Failed to show passphrase dialog: {}", e));
+            return;
+        }
+    }
+
+    // Characters the locked keyboard handling may not reproduce could lock the user out
+    let layout = core.borrow().state.get_keyboard_layout();
+    if let Err(chars) = keycode::can_type_while_locked(&passphrase, layout) {
+        let message = format!(
+            "{}\n\nWhile locked, the passphrase is read key by key, so these characters may never match.",
+            keycode::untypable_passphrase_warning(&chars)
+        );
+        if !dialogs::show_confirm(&message, "Use Anyway") {
+            info!("Passphrase change cancelled - hard-to-type characters");
+            return;
         }
     }
+
+    // Save first so the running app never accepts a passphrase the config doesn't have
+    if let Err(e) = Config::persist_passphrase_change(config_path, &passphrase) {
+        error!("Failed to change passphrase: {:#}", e);
+        show_alert(
+            "HandsOff - Error",
+            &format!("Failed to save the new passphrase:\n{:#}\n\nYour old passphrase still works.", e),
+        );
+        return;
+    }
+    core.borrow().set_passphrase(&passphrase);
+
+    ui::notify(
+        &strings::get(MessageKey::NotificationTitle),
+        &strings::get(MessageKey::PassphraseChanged),
+    );
+}
+
+/// Handle test passphrase from menu
+/// Asks for the passphrase and says whether it would unlock; input is never locked.
+fn handle_test_passphrase(core: Rc<RefCell<HandsOffCore>>) {
+    let passphrase = match dialogs::show_set_passphrase_dialog(
+        "Type your passphrase to check it (input will not be locked):",
+    ) {
+        Ok(Some(passphrase)) => passphrase,
+        Ok(None) => {
+            info!("Passphrase test cancelled");
+            return;
+        }
+        Err(e) => {
+            error!("Failed to show passphrase dialog: {}", e);
+            show_alert("HandsOff - Error", &format!("Failed to show passphrase dialog: {}", e));
+            return;
+        }
+    };
+
+    if core.borrow().verify_passphrase(&passphrase) {
+        info!("Passphrase test succeeded");
+        show_alert(
+            "HandsOff - Passphrase OK",
+            "That's your passphrase. Type it while locked to unlock.",
+        );
+    } else {
+        info!("Passphrase test failed");
+        show_alert(
+            "HandsOff - Passphrase Does Not Match",
+            "That isn't your passphrase. Try again, or use Change Passphrase… to set a new one.",
+        );
+    }
+}
+
+/// Handle quit from menu: release the event tap and hotkeys so the process exits cleanly
+///
+/// Returns false (and stays running) if input is locked while permissions are still
+/// granted - the menu item is disabled then, this guards against a stale click.
+fn handle_quit(core: Rc<RefCell<HandsOffCore>>) -> bool {
+    let mut core = core.borrow_mut();
+    let menu_state = MenuState::new(
+        core.has_accessibility_permissions(),
+        core.is_locked(),
+        core.state.is_disabled(),
+    );
+    if !menu_state.quit {
+        warn!("Quit ignored while input is locked");
+        return false;
+    }
+
+    core.shutdown();
+
+    // Don't leave a snapshot behind that claims we're still running
+    let _ = std::fs::remove_file(handsoff::status::status_path());
+    info!("HandsOff tray app exiting");
+    true
 }
 
 /// Handle reset from menu
 /// Resets the app state to default: unlocked with all timers reset
 /// If disabled, re-enables the app. Otherwise, restarts the event tap if permissions are available
-fn handle_reset(core: Rc<RefCell<HandsOffCore>>, passphrase: &str) {
+fn handle_reset(core: Rc<RefCell<HandsOffCore>>) {
     let mut core = core.borrow_mut();
 
     // Check if disabled - if so, enable instead of just restarting
     let is_disabled = core.state.is_disabled();
 
+    // Resume auto-lock if it was snoozed
+    core.clear_auto_lock_snooze();
+
     // Unlock if currently locked (this also resets lock timer)
+    // Reset is a trusted menu action, so no passphrase is required (the plaintext
+    // passphrase may not even be available when it is stored in Keychain)
     if core.is_locked() {
-        match core.unlock(passphrase) {
-            Ok(true) => {
-                info!("App state reset: unlocked successfully");
-            }
-            Ok(false) => {
-                // This shouldn't happen as we're using the stored passphrase
-                error!("Failed to unlock during reset: invalid passphrase");
-                show_alert(
-                    "HandsOff - Reset Error",
-                    "Failed to unlock. This is unexpected - please check logs.",
-                );
-                return;
-            }
-            Err(e) => {
-                error!("Error during reset unlock: {}", e);
-                show_alert("HandsOff - Reset Error", &format!("Failed to reset: {}", e));
-                return;
-            }
-        }
+        core.state.unlock_with(UnlockMethod::Reset);
+        core.state.clear_buffer();
+        info!("App state reset: unlocked successfully");
     }
 
     // If disabled, re-enable (which also restarts event tap and hotkeys)
@@ -560,14 +1243,10 @@ fn handle_reset(core: Rc<RefCell<HandsOffCore>>, passphrase: &str) {
         match core.enable() {
             Ok(()) => {
                 info!("HandsOff re-enabled successfully during reset");
-                #[cfg(target_os = "macos")]
-                {
-                    let _ = notify_rust::Notification::new()
-                        .summary("HandsOff")
-                        .body("App reset complete - Re-enabled and ready to use")
-                        .timeout(notify_rust::Timeout::Milliseconds(NOTIFICATION_TIMEOUT_MS))
-                        .show();
-                }
+                ui::notify(
+                    &strings::get(MessageKey::NotificationTitle),
+                    &strings::get(MessageKey::ResetReenabled),
+                );
             }
             Err(e) => {
                 warn!("Could not re-enable during reset: {}", e);
@@ -582,14 +1261,10 @@ fn handle_reset(core: Rc<RefCell<HandsOffCore>>, passphrase: &str) {
         match core.restart_event_tap() {
             Ok(()) => {
                 info!("Input blocking restarted successfully during reset");
-                #[cfg(target_os = "macos")]
-                {
-                    let _ = notify_rust::Notification::new()
-                        .summary("HandsOff")
-                        .body("Reset complete - Input blocking restarted\nReady to use")
-                        .timeout(notify_rust::Timeout::Milliseconds(NOTIFICATION_TIMEOUT_MS))
-                        .show();
-                }
+                ui::notify(
+                    &strings::get(MessageKey::NotificationTitle),
+                    &strings::get(MessageKey::ResetRestarted),
+                );
             }
             Err(e) => {
                 warn!("Could not restart input blocking during reset: {}", e);
@@ -619,6 +1294,20 @@ fn show_alert(title: &str, message: &str) {
     let _ = Command::new("osascript").arg("-e").arg(&script).output();
 }
 
+/// Unlock notification text, with how many events the lock blocked
+fn unlocked_message(blocked: u64) -> String {
+    let unlocked = strings::get(MessageKey::InputUnlocked);
+    if blocked == 0 {
+        return unlocked;
+    }
+    let count = format_count(blocked);
+    format!(
+        "{}\n{}",
+        unlocked,
+        strings::format(MessageKey::BlockedEvents, &[("count", &count)])
+    )
+}
+
 /// Build tooltip text based on lock state, disabled state, and permission status
 fn build_tooltip(
     core: &HandsOffCore,
@@ -633,21 +1322,46 @@ fn build_tooltip(
     tooltip.push_str("A macOS utility to block unsolicited input\n\n");
 
     // Current status
-    if is_disabled {
-        tooltip.push_str("STATUS: DISABLED\n");
+    if let Some(paused) = core.get_pause_remaining_secs().filter(|_| is_disabled) {
+        tooltip.push_str(&format!(
+            "{} ({} left)\n",
+            strings::get(MessageKey::StatusPaused),
+            format_duration(paused)
+        ));
+        tooltip.push_str("HandsOff re-enables itself when the pause ends\n");
+        tooltip.push_str("Use Reset menu to resume early\n\n");
+    } else if is_disabled {
+        tooltip.push_str(&format!("{}\n", strings::get(MessageKey::StatusDisabled)));
         tooltip.push_str("Low system resources mode - all features paused\n");
         tooltip.push_str("Use Reset menu to re-enable HandsOff\n\n");
     } else if !has_permissions {
-        tooltip.push_str("STATUS: NO PERMISSIONS\n");
+        tooltip.push_str(&format!("{}\n", strings::get(MessageKey::StatusNoPermissions)));
         tooltip.push_str("Restore Accessibility Permissions in:\n");
         tooltip.push_str("System Settings > Privacy & Security\n");
         tooltip.push_str("Then use Reset menu to restart\n\n");
     } else if is_locked {
         // Show lock duration
         if let Some(elapsed) = core.get_lock_elapsed_secs() {
-            tooltip.push_str(&format!("STATUS: LOCKED ({})\n", format_duration(elapsed)));
+            tooltip.push_str(&format!(
+                "{} ({})\n",
+                strings::get(MessageKey::StatusLocked),
+                format_duration(elapsed)
+            ));
         } else {
-            tooltip.push_str("STATUS: LOCKED\n");
+            tooltip.push_str(&format!("{}\n", strings::get(MessageKey::StatusLocked)));
+        }
+
+        tooltip.push_str(&format!(
+            "Blocked: {} events\n",
+            format_count(core.state.get_blocked_event_count())
+        ));
+
+        // Masked feedback for blind typing (length only, never the characters)
+        let typed = core.get_buffer_len();
+        if let Some(cooldown) = core.get_attempt_cooldown_remaining() {
+            tooltip.push_str(&format!("Wrong passphrase - try again in {} sec\n", cooldown));
+        } else if typed > 0 {
+            tooltip.push_str(&format!("Typed: {}\n", overlay::masked_text(typed)));
         }
 
         // Show auto-unlock countdown if enabled
@@ -659,10 +1373,14 @@ fn build_tooltip(
             }
         }
     } else {
-        tooltip.push_str("STATUS: Unlocked\n");
-
-        // Show auto-lock countdown if enabled
-        if let Some(remaining) = core.get_auto_lock_remaining_secs() {
+        tooltip.push_str(&format!("{}\n", strings::get(MessageKey::StatusUnlocked)));
+
+        // Show snooze or auto-lock countdown
+        if let Some(snoozed) = core.get_snooze_remaining_secs() {
+            tooltip.push_str(&format!("Auto-lock snoozed ({} left)\n", format_duration(snoozed)));
+        } else if core.is_auto_lock_paused_for_media() {
+            tooltip.push_str("Auto-lock paused (media playing)\n");
+        } else if let Some(remaining) = core.get_auto_lock_remaining_secs() {
             if remaining > 0 {
                 tooltip.push_str(&format!("Auto-lock in {}\n", format_duration(remaining)));
             } else {
@@ -671,6 +1389,12 @@ fn build_tooltip(
         }
     }
 
+    // Permission flapping shows up here (compare with changes made in System Settings)
+    let permission_changes = core.state.permission_transition_count();
+    if permission_changes > 0 {
+        tooltip.push_str(&format!("Permission changes: {}\n", permission_changes));
+    }
+
     tooltip.push_str("\n\n");
 
     // Menu items
@@ -678,27 +1402,56 @@ fn build_tooltip(
     tooltip.push_str("• Lock Input: Lock immediately\n");
     tooltip.push_str("• Disable: Pause input blocking and reduce system resources\n");
     tooltip.push_str("  (Use Reset to re-enable HandsOff)\n");
+    tooltip.push_str("• Pause: Disable for a few minutes, then re-enable\n");
+    tooltip.push_str("• Snooze Auto-Lock: Pause auto-lock for a while\n");
+    tooltip.push_str("• Settings: Show the current hotkeys and timeouts\n");
+    tooltip.push_str("• Change Passphrase: Set a new unlock passphrase\n");
+    tooltip.push_str("• Test Passphrase: Check your passphrase without locking\n");
     tooltip.push_str("• Reset: Clear all timers and restart input blocking\n\n");
 
     // Instructions
-    let lock_key = core.get_lock_key_display();
-    let talk_key = core.get_talk_key_display();
+    let lock_hotkey = core.get_lock_hotkey_display();
+    let talk_hotkey = core.get_talk_hotkey_display();
+    let unlock_key = core.get_unlock_key_display();
 
     tooltip.push_str("TO LOCK:\n");
-    tooltip.push_str("• Click 'Lock Input' menu, OR\n");
-    tooltip.push_str(&format!("• Press Ctrl+Cmd+Shift+{}\n\n", lock_key));
+    match &lock_hotkey {
+        Some(lock_hotkey) => {
+            tooltip.push_str("• Click 'Lock Input' menu, OR\n");
+            tooltip.push_str(&format!("• Press {}\n\n", lock_hotkey));
+        }
+        None => tooltip.push_str("• Click 'Lock Input' menu\n\n"),
+    }
 
     tooltip.push_str("TO UNLOCK:\n");
     tooltip.push_str("• Type your passphrase on keyboard (default: qwet)\n");
+    tooltip.push_str(&format!("• Or press Ctrl+Cmd+Shift+{} to use Touch ID\n", unlock_key));
     tooltip.push_str("• Press Escape to clear buffer immediately if you mistype\n");
-    tooltip.push_str("• Or wait 3 seconds for auto-clear\n\n");
+    if core.is_attempt_cooldown_enabled() {
+        tooltip.push_str(&format!(
+            "• After a wrong passphrase, wait {} sec before the next attempt\n",
+            ATTEMPT_COOLDOWN_SECONDS
+        ));
+    }
+    tooltip.push_str(&format!(
+        "• Or wait {} seconds for auto-clear\n\n",
+        core.get_buffer_reset_timeout()
+    ));
 
     // Hotkeys
     tooltip.push_str("HOTKEYS:\n");
-    tooltip.push_str(&format!("• Ctrl+Cmd+Shift+{}: Lock input\n", lock_key));
+    if let Some(lock_hotkey) = &lock_hotkey {
+        tooltip.push_str(&format!("• {}: Lock input\n", lock_hotkey));
+    }
+    if let Some(talk_hotkey) = &talk_hotkey {
+        tooltip.push_str(&format!(
+            "• {} (hold): Hotkey to Unmute (Spacebar)\n",
+            talk_hotkey
+        ));
+    }
     tooltip.push_str(&format!(
-        "• Ctrl+Cmd+Shift+{} (hold): Hotkey to Unmute (Spacebar)\n\n",
-        talk_key
+        "• Ctrl+Cmd+Shift+{} (while locked): Unlock with Touch ID\n\n",
+        unlock_key
     ));
 
     // Setup
@@ -711,21 +1464,6 @@ fn build_tooltip(
     tooltip
 }
 
-/// Format duration in human-readable form (e.g., "2m 30s" or "45s")
-fn format_duration(seconds: u64) -> String {
-    if seconds >= 60 {
-        let mins = seconds / 60;
-        let secs = seconds % 60;
-        if secs > 0 {
-            format!("{}m {}s", mins, secs)
-        } else {
-            format!("{}m", mins)
-        }
-    } else {
-        format!("{}s", seconds)
-    }
-}
-
 /// Create unlocked icon (green circle)
 fn create_icon_unlocked() -> tray_icon::Icon {
     let png_data = include_bytes!("../../assets/tray_unlocked.png");
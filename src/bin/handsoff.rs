@@ -2,12 +2,28 @@
 // This binary provides a terminal-based interface with argument parsing
 
 use anyhow::{Context, Result};
-use clap::Parser;
-use handsoff::app_state::{AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS};
-use handsoff::constants::CFRUNLOOP_POLL_INTERVAL_MS;
-use handsoff::{config, config_file::Config, HandsOffCore};
+use clap::{Parser, Subcommand};
+use handsoff::app_state::{
+    PermissionLossAction, AUTO_LOCK_DEFAULT_SECONDS, AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS,
+    AUTO_UNLOCK_DEFAULT_SECONDS,
+};
+use handsoff::constants::{CFRUNLOOP_POLL_INTERVAL_MS, STATUS_STALE_SECS};
+use handsoff::input_blocking::{self, PermissionLossDebounce};
+use handsoff::status::{self, StatusSnapshot};
+use handsoff::strings;
+use handsoff::utils::keycode;
+use handsoff::ui::notify::{self, NotificationSettings};
+use handsoff::utils::modifiers::HotkeyModifiers;
+use handsoff::audit;
+use handsoff::instance_lock::{self, AlreadyRunning, InstanceLock};
+use handsoff::ipc::{ControlCommand, ControlResponse};
+use handsoff::power_source::PowerAutoLockPolicy;
+use handsoff::setup_prompts;
+use handsoff::{config, config_file::{Config, HOTKEY_DISABLED}, ipc, HandsOffCore};
 use log::{error, info, warn};
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
 
 /// macOS utility to block unsolicited input from unwanted hands
 #[derive(Parser, Debug)]
@@ -35,12 +51,24 @@ SETUP:
     - Auto-lock timeout (default: 120 seconds)
     - Auto-unlock timeout (default: 0 seconds/disabled)
 
+  To check the passphrase before relying on it (input is never locked):
+    handsoff --verify-passphrase
+
   Configuration is stored encrypted at:
     ~/Library/Application Support/handsoff/config.toml
 
 HOTKEYS:
   Ctrl+Cmd+Shift+L          Lock input (blocks all keyboard/mouse input)
   Ctrl+Cmd+Shift+T          Talk mode (hold to allow spacebar keypress, for unmuting conf calls)
+  Ctrl+Cmd+Shift+U          Unlock with Touch ID (while locked)
+  Ctrl+Opt+Cmd+Shift+<key>  Emergency unlock (disabled unless panic_hotkey is set)
+  (letters and lock/talk modifiers are configurable in config.toml)
+
+STATUS:
+  handsoff status           Print the running instance's state as JSON
+  handsoff config show      Print the effective settings (config file + environment overrides)
+  handsoff config export F  Write all settings except the passphrase to file F
+  handsoff config import F  Merge settings from file F, asking for a new passphrase
 
 When locked, type your passphrase to unlock (input won't be visible on screen)."
 )]
@@ -54,30 +82,82 @@ struct Args {
     #[arg(long)]
     auto_lock: Option<u64>,
 
+    /// Auto-unlock timeout in seconds (60-900, 0 disables; overrides environment and config file)
+    /// NOTE: Keep range/default values in sync with AUTO_UNLOCK_* constants
+    #[arg(long, value_name = "SECS", value_parser = config::parse_auto_unlock_arg)]
+    auto_unlock: Option<u64>,
+
     /// Run interactive setup to configure passphrase and timeouts
     #[arg(long)]
     setup: bool,
-}
 
-/// Helper function to prompt for a number with a default value
-fn prompt_number(prompt: &str, default: u64) -> Result<u64> {
-    print!("{}", prompt);
-    io::stdout().flush()?;
+    /// Check a typed passphrase against the configured one, without locking input
+    #[arg(long, conflicts_with = "setup")]
+    verify_passphrase: bool,
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let input = input.trim();
+    /// Config file path (overrides HANDS_OFF_CONFIG and the default location)
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
 
-    if input.is_empty() {
-        Ok(default)
-    } else {
-        input
-            .parse::<u64>()
-            .with_context(|| format!("Invalid number: {}", input))
-    }
+    /// Use a named profile: config-<NAME>.toml in the config directory (overrides HANDS_OFF_PROFILE)
+    #[arg(long, value_name = "NAME", conflicts_with = "config")]
+    profile: Option<String>,
+
+    /// Also write logs to this file, rotated by size (overrides HANDS_OFF_LOG_FILE)
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// More log output (-v debug, -vv trace; RUST_LOG takes precedence)
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Less log output (-q warnings and errors, -qq errors only)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    quiet: u8,
+
+    /// Never block input; log at debug level what would have been blocked
+    #[arg(long)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the state of the running instance as JSON (exit code 1 if not running)
+    Status,
+    /// Lock input on the running instance (needs control_socket = true)
+    Lock,
+    /// Unlock the running instance (prompts for the passphrase; needs control_socket = true)
+    Unlock,
+    /// Disable the running instance (needs control_socket = true; refused while locked)
+    Disable,
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
 }
 
-/// Prompt for a hotkey (single letter A-Z), returns Some(key) or None for default
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print the effective settings (config file with environment overrides applied)
+    Show,
+    /// Write all settings except the passphrase to FILE (to move them to another machine)
+    Export {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+    /// Merge settings from an exported FILE into the config, asking for a new passphrase
+    Import {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+/// Prompt for a hotkey (single letter A-Z, or "none" to disable), returns Some(key) or
+/// None for default
 fn prompt_hotkey(prompt: &str, _default: &str) -> Result<Option<String>> {
     print!("{}", prompt);
     io::stdout().flush()?;
@@ -90,65 +170,222 @@ fn prompt_hotkey(prompt: &str, _default: &str) -> Result<Option<String>> {
         Ok(None) // Use default
     } else {
         // Validate the input
-        Config::validate_hotkey(input)?;
+        Config::validate_lock_talk_hotkey(input)?;
+        if Config::is_hotkey_disabled(input) {
+            return Ok(Some(HOTKEY_DISABLED.to_string()));
+        }
         Ok(Some(input.to_uppercase()))
     }
 }
 
-/// Run interactive setup to configure passphrase and timeouts
-fn run_setup() -> Result<()> {
-    println!("HandsOff Setup");
-    println!("==============\n");
+/// Query the running instance: control socket first, then the published status file
+fn query_status() -> Option<StatusSnapshot> {
+    let socket = ipc::socket_path();
+    if socket.exists() {
+        if let Some(snapshot) = ipc::send_command(&socket, "status")
+            .ok()
+            .and_then(|response| StatusSnapshot::from_json(&response).ok())
+        {
+            return Some(snapshot);
+        }
+    }
+
+    let snapshot = status::read_snapshot(&status::status_path()).ok()?;
+    let now = status::unix_now();
+    (snapshot.age_secs(now) <= STATUS_STALE_SECS).then(|| snapshot.aged(now))
+}
 
-    // Prompt for passphrase (non-echoing)
-    let passphrase =
-        rpassword::prompt_password("Enter passphrase: ").context("Failed to read passphrase")?;
+/// Print the running instance's state as JSON
+fn run_status() -> Result<()> {
+    match query_status() {
+        Some(snapshot) => {
+            println!("{}", snapshot.to_json()?);
+            Ok(())
+        }
+        None => {
+            eprintln!("HandsOff is not running");
+            std::process::exit(1);
+        }
+    }
+}
 
-    if passphrase.is_empty() {
-        anyhow::bail!("Error: Passphrase cannot be empty");
+/// Send a command to the running instance over the control socket
+fn run_control(command: ControlCommand, done: &str) -> Result<()> {
+    let socket = ipc::socket_path();
+    if !socket.exists() {
+        eprintln!("HandsOff is not running, or its control socket is off (set control_socket = true)");
+        std::process::exit(1);
     }
 
+    let reply = ipc::send_command(&socket, &command.to_line())?;
+    let response = ControlResponse::parse(&reply)?;
+    if response.ok {
+        println!("{}", done);
+        Ok(())
+    } else {
+        eprintln!("Failed: {}", response.error_message());
+        std::process::exit(1);
+    }
+}
+
+/// Print the settings HandsOff would run with
+fn run_config_show(config_path: &Path) -> Result<()> {
+    let cfg = Config::load_from_path(config_path).context("Failed to load configuration")?;
+    println!("Config file:   {}", config_path.display());
+    println!("{}", config::resolve_effective_settings(&cfg));
+    Ok(())
+}
+
+/// Write the settings to `file`, without the passphrase, for `config import` elsewhere
+fn run_config_export(config_path: &Path, file: &Path) -> Result<()> {
+    let cfg = Config::load_from_path(config_path).context("Failed to load configuration")?;
+    let contents = toml::to_string_pretty(&cfg.export_table()?)
+        .context("Failed to serialize settings")?;
+    std::fs::write(file, contents)
+        .with_context(|| format!("Failed to write {}", file.display()))?;
+    println!("Settings exported to: {}", file.display());
+    println!("The passphrase isn't included; 'handsoff config import' asks for a new one.");
+    Ok(())
+}
+
+/// Prompt for the passphrase and report whether it matches, without locking input
+fn run_verify_passphrase(config_path: &Path) -> Result<()> {
+    let cfg = Config::load_from_path(config_path).context("Failed to load configuration")?;
+    let passphrase = Zeroizing::new(
+        rpassword::prompt_password("Passphrase: ").context("Failed to read passphrase")?,
+    );
+
+    if cfg.verify_passphrase(&passphrase)? {
+        println!("Passphrase OK - type it while locked to unlock");
+        Ok(())
+    } else {
+        eprintln!("Passphrase does not match (run 'handsoff --setup' to set a new one)");
+        std::process::exit(1);
+    }
+}
+
+/// Merge settings exported with `config export` into the config, with a new passphrase
+fn run_config_import(config_path: &Path, file: &Path) -> Result<()> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let imported: toml::Table =
+        toml::from_str(&contents).context("Failed to parse exported settings")?;
+
+    println!("Importing settings from: {}", file.display());
+    println!("Choose a passphrase for this machine.\n");
+    let passphrase = prompt_new_passphrase()?;
+
+    // Merge into the existing config, or into defaults on a new machine
+    let base = if config_path.exists() {
+        Config::load_from_path(config_path).context("Failed to load configuration")?
+    } else {
+        Config::new(&passphrase, AUTO_LOCK_DEFAULT_SECONDS, AUTO_UNLOCK_DEFAULT_SECONDS, None, None)
+            .context("Failed to create configuration")?
+    };
+    base.merge_imported(imported, &passphrase)?
+        .save_to_path(config_path)
+        .context("Failed to save configuration")?;
+
+    println!("\nConfiguration saved to: {}", config_path.display());
+    Ok(())
+}
+
+/// Prompt for a new passphrase twice (non-echoing)
+///
+/// The plaintext is scrubbed from memory when the returned value goes out of scope.
+fn prompt_new_passphrase() -> Result<Zeroizing<String>> {
+    let passphrase = Zeroizing::new(
+        rpassword::prompt_password("Enter passphrase: ").context("Failed to read passphrase")?,
+    );
+
+    Config::validate_passphrase(&passphrase)?;
+
     // Confirm passphrase
-    let confirm = rpassword::prompt_password("Confirm passphrase: ")
-        .context("Failed to read confirmation")?;
+    let confirm = Zeroizing::new(
+        rpassword::prompt_password("Confirm passphrase: ").context("Failed to read confirmation")?,
+    );
 
     if passphrase != confirm {
         anyhow::bail!("Error: Passphrases do not match");
     }
+    Ok(passphrase)
+}
+
+/// Run interactive setup to configure passphrase and timeouts
+fn run_setup(config_path: &Path) -> Result<()> {
+    println!("HandsOff Setup");
+    println!("==============\n");
+
+    let passphrase = prompt_new_passphrase()?;
+
+    setup_prompts::confirm_typable_passphrase(&passphrase)?;
 
     // Prompt for hotkeys
     println!("\nHotkey Configuration");
     println!("--------------------");
-    println!("Configure the hotkeys (modifiers Cmd+Ctrl+Shift are mandatory, but choose the last key).");
-    println!("Enter a single letter A-Z, or press Enter to use the default.\n");
+    println!("Configure the hotkeys (choose the last key; modifiers default to Cmd+Ctrl+Shift and can be changed with lock_modifiers/talk_modifiers in config.toml).");
+    println!("Enter a single letter A-Z, 'none' to disable the hotkey, or press Enter to use the default.\n");
 
     let lock_key = prompt_hotkey("Lock hotkey (default: L): ", "L")?;
     let talk_key = prompt_hotkey("Talk hotkey (Hotkey to Unmute, default: T): ", "T")?;
 
-    // Validate that lock and talk keys are different
-    if let (Some(ref lock), Some(ref talk)) = (&lock_key, &talk_key) {
-        if lock == talk {
-            anyhow::bail!("Error: Lock and Talk hotkeys must be different");
-        }
-    }
+    // Validate that lock and talk keys are different (defaults included)
+    Config::validate_distinct_hotkeys(lock_key.as_deref(), talk_key.as_deref())?;
+
+    // Prompt for the key passed through while the Talk hotkey is held
+    println!("\nWhile the Talk hotkey is held, one key is passed through to the focused app");
+    println!("(e.g. the push-to-talk key of your conferencing app).");
+    let talk_passthrough_key = setup_prompts::prompt_talk_passthrough_key(
+        "Talk passthrough key (space or A-Z, default: space): ",
+    )?;
 
     // Prompt for timeouts
     println!("\nTimeout Configuration");
     println!("---------------------\n");
-    let auto_lock = prompt_number("Auto-lock timeout in seconds (default: 120): ", 120)?;
-
-    let auto_unlock = prompt_number("Auto-unlock timeout in seconds (default: 0/disabled): ", 0)?;
+    let auto_lock = setup_prompts::prompt_timeout(
+        "Auto-lock timeout in seconds (default: 120): ",
+        120,
+        Config::validate_auto_lock_timeout,
+    )?;
+
+    let auto_unlock = setup_prompts::prompt_timeout(
+        "Auto-unlock timeout in seconds (default: 0/disabled): ",
+        0,
+        Config::validate_auto_unlock_timeout,
+    )?;
+
+    // Prompt for lock scope
+    println!("\nLock Scope");
+    println!("----------");
+    println!("Choose which input is blocked while locked: all, keyboard or mouse.");
+    println!("Note: with 'mouse' the keyboard stays usable, so the passphrase cannot be typed to unlock");
+    println!("(use Touch ID or auto-unlock instead).\n");
+    let lock_scope = setup_prompts::prompt_lock_scope("Lock scope (default: all): ")?;
 
     // Create and save config
-    let config = Config::new(&passphrase, auto_lock, auto_unlock, lock_key, talk_key)
+    let mut config = Config::new(&passphrase, auto_lock, auto_unlock, lock_key, talk_key)
         .context("Failed to create configuration")?;
+    config.lock_scope = lock_scope;
+    config.talk_passthrough_key = talk_passthrough_key;
+    config.validate_talk_passthrough_conflicts()?;
 
-    config.save().context("Failed to save configuration")?;
+    // Generate a one-time emergency recovery code (only its hash is stored)
+    let recovery_code =
+        handsoff::auth::generate_recovery_code().context("Failed to generate recovery code")?;
+    config.set_recovery_code(&recovery_code);
+
+    config
+        .save_to_path(config_path)
+        .context("Failed to save configuration")?;
 
     println!(
         "\nConfiguration saved to: {}",
-        Config::config_path().display()
+        config_path.display()
     );
+    println!("\nEmergency recovery code (shown only once - write it down!):");
+    println!("    {}", recovery_code);
+    println!("If you forget your passphrase, type this code while locked to unlock.");
+    println!("The code works only once; run setup again to generate a new one.");
     println!("Setup complete!");
     println!("\nYou can now run 'handsoff' to start the application.");
 
@@ -159,15 +396,56 @@ fn main() -> Result<()> {
     // Parse command-line arguments
     let args = Args::parse();
 
+    // Resolve config file path
+    // (precedence: --config > --profile > HANDS_OFF_CONFIG > HANDS_OFF_PROFILE > default)
+    let config_path = config::resolve_config_path(args.config.clone(), args.profile.clone())?;
+
+    // Handle status subcommand (queries a running instance, no permissions needed)
+    match args.command {
+        Some(Command::Status) => return run_status(),
+        Some(Command::Lock) => return run_control(ControlCommand::Lock, "Locked"),
+        Some(Command::Unlock) => {
+            let passphrase = Zeroizing::new(
+                rpassword::prompt_password("Passphrase: ").context("Failed to read passphrase")?,
+            );
+            return run_control(ControlCommand::Unlock(&passphrase), "Unlocked");
+        }
+        Some(Command::Disable) => return run_control(ControlCommand::Disable, "Disabled"),
+        Some(Command::Config {
+            action: ConfigCommand::Show,
+        }) => {
+            // Warnings only, so invalid environment overrides are reported on stderr
+            handsoff::logging::init(
+                handsoff::logging::level_from_flags(args.verbose, args.quiet.max(1)),
+                None,
+            );
+            return run_config_show(&config_path);
+        }
+        Some(Command::Config {
+            action: ConfigCommand::Export { file },
+        }) => return run_config_export(&config_path, &file),
+        Some(Command::Config {
+            action: ConfigCommand::Import { file },
+        }) => return run_config_import(&config_path, &file),
+        None => {}
+    }
+
     // Handle setup command
     if args.setup {
-        return run_setup();
+        return run_setup(&config_path);
     }
 
-    // Initialize logger
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .init();
+    // Handle passphrase check (never locks, so no permissions needed)
+    if args.verify_passphrase {
+        return run_verify_passphrase(&config_path);
+    }
+
+    // Initialize logger (precedence: --log-file > HANDS_OFF_LOG_FILE > stderr only)
+    let log_file = config::resolve_log_file_path(args.log_file.clone());
+    handsoff::logging::init(
+        handsoff::logging::level_from_flags(args.verbose, args.quiet),
+        log_file.as_deref(),
+    );
 
     info!("Starting HandsOff Input Lock");
 
@@ -178,8 +456,14 @@ fn main() -> Result<()> {
         std::process::exit(1);
     }
 
+    // Establish the machine secret used to encrypt the config file
+    if let Err(e) = handsoff::crypto::init_key() {
+        error!("Failed to initialize config encryption: {:#}", e);
+        std::process::exit(1);
+    }
+
     // Load configuration
-    let cfg = match Config::load() {
+    let cfg = match Config::load_from_path(&config_path) {
         Ok(cfg) => cfg,
         Err(e) => {
             error!("Failed to load configuration: {}", e);
@@ -188,17 +472,30 @@ fn main() -> Result<()> {
         }
     };
 
-    // Decrypt passphrase
-    let passphrase = match cfg.get_passphrase() {
+    // Refuse to start a second instance (two event taps would fight over input); the
+    // lock file is removed when this returns
+    let _instance_lock = match InstanceLock::acquire(instance_lock::default_path(&config_path)) {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("{:#}", e);
+            if e.downcast_ref::<AlreadyRunning>().is_some() {
+                error!("Quit the running instance first ('handsoff status' shows its state)");
+            }
+            std::process::exit(1);
+        }
+    };
+
+    // Load passphrase hash (from Keychain if enabled, otherwise decrypted from config file)
+    let passphrase_hash = match cfg.get_passphrase_hash() {
         Ok(p) => {
             info!(
                 "Configuration loaded from: {}",
-                Config::config_path().display()
+                config_path.display()
             );
             p
         }
         Err(e) => {
-            error!("Failed to decrypt passphrase: {}", e);
+            error!("Failed to load passphrase: {}", e);
             error!("Your configuration file may be corrupted.");
             error!("Run 'handsoff --setup' to reconfigure.");
             std::process::exit(1);
@@ -206,10 +503,18 @@ fn main() -> Result<()> {
     };
 
     // Create HandsOffCore instance
-    let mut core = HandsOffCore::new(&passphrase).context("Failed to initialize HandsOff")?;
+    let mut core = HandsOffCore::with_passphrase_hash(passphrase_hash)
+        .context("Failed to initialize HandsOff")?;
+
+    // Ctrl+C / kill stop the event tap before exiting instead of killing the process
+    // (handled by the main loop; a signal during startup is picked up once it runs)
+    if let Err(e) = core.install_shutdown_signal_handlers() {
+        warn!("{:#} - Ctrl+C will exit without a clean shutdown", e);
+    }
 
-    // Configure auto-unlock timeout (precedence: env var > config file > build default)
-    let auto_unlock_timeout = config::resolve_auto_unlock_timeout(cfg.auto_unlock_timeout);
+    // Configure auto-unlock timeout (precedence: CLI arg > env var > config file > build default)
+    let auto_unlock_timeout =
+        config::resolve_auto_unlock_timeout_with_arg(args.auto_unlock, cfg.auto_unlock_timeout);
     core.set_auto_unlock_timeout(auto_unlock_timeout);
 
     // Configure auto-lock timeout (precedence: CLI arg > env var > config file)
@@ -232,11 +537,85 @@ fn main() -> Result<()> {
     };
     core.set_auto_lock_timeout(auto_lock_timeout);
 
+    // Configure the pre-lock warning (config file, default 10 seconds, 0 = off)
+    core.set_auto_lock_warning(config::resolve_auto_lock_warning(cfg.auto_lock_warning));
+
+    // Configure the post-unlock grace period (config file, default 10 seconds, 0 = off)
+    core.set_unlock_grace(config::resolve_unlock_grace(cfg.unlock_grace_secs));
+
+    // Apply notification settings before background threads can notify
+    // Custom/localized message texts (config [messages] table)
+    strings::set_overrides(strings::parse_overrides(&cfg.messages));
+
+    notify::configure(NotificationSettings {
+        enabled: cfg.notifications_enabled,
+        timeout_ms: config::resolve_notification_timeout(cfg.notification_timeout_ms),
+    });
+
+    // Hard cap on lock duration, independent of auto-unlock (config file, default 60 minutes)
+    core.set_max_lock_duration(config::resolve_max_lock_duration(cfg.max_lock_duration_mins));
+
+    // Configure the event loop watchdog (config file, default 30 seconds)
+    core.set_watchdog_timeout(config::resolve_watchdog_timeout(cfg.watchdog_timeout_secs));
+
+    // Optionally hold off auto-lock while media is playing
+    core.set_respect_media_playback(cfg.respect_media_playback);
+
+    // Optionally keep a twitchy mouse from holding off auto-lock
+    core.set_auto_lock_ignore_mouse_move(cfg.auto_lock_ignore_mouse_move);
+
+    // Optionally adjust auto-lock to the power source (config file, default off)
+    core.set_power_auto_lock_policy(PowerAutoLockPolicy {
+        timeout_on_battery: config::resolve_auto_lock_timeout_on_battery(
+            cfg.auto_lock_timeout_on_battery,
+        ),
+        lock_on_ac: !cfg.auto_lock_only_on_battery,
+    });
+
+    // Optionally ignore isolated stray events for auto-lock (config file, default off)
+    core.set_activity_debounce(
+        config::resolve_activity_debounce_events(cfg.activity_debounce_events),
+        config::resolve_activity_debounce_window(cfg.activity_debounce_window_ms),
+    );
+
+    // Configure buffer reset timeout (precedence: env var > config file > default)
+    core.set_buffer_reset_timeout(config::resolve_buffer_reset_timeout(cfg.buffer_reset_timeout));
+
+    // Configure lock scope (precedence: env var > config file > default all)
+    core.set_lock_scope(config::parse_lock_scope().unwrap_or(cfg.lock_scope));
+
+    // Configure media/volume/brightness key blocking while locked
+    core.set_block_media_keys(cfg.block_media_keys);
+
+    // Configure power / Touch ID key blocking while locked (best effort, see README)
+    core.set_block_power_key(cfg.block_power_key);
+
+    // Dry-run: run everything but never block (for trying hotkeys and timeouts safely)
+    core.set_dry_run(args.dry_run);
+
+    // Beep when a passphrase attempt times out (config file, default off)
+    core.set_feedback_sound(cfg.feedback_sound);
+
+    // Discard attempts longer than the passphrase right away (config file, default off)
+    core.set_clear_overlong_attempts(cfg.clear_overlong_attempts, cfg.get_passphrase_len());
+
+    // Wait after a full-length wrong attempt (config file, default on)
+    core.set_attempt_cooldown(cfg.attempt_cooldown);
+
+    // Configure scheduled lock windows (validated when the config was loaded)
+    match cfg.schedule.parse_windows() {
+        Ok(windows) => core.set_schedule(windows),
+        Err(e) => warn!("Ignoring invalid lock schedule: {:#}", e),
+    }
+
+    // Configure apps that stay interactive while locked
+    core.set_app_allowlist(cfg.app_allowlist.clone());
+
     // Configure hotkeys (precedence: env var > config file > defaults)
     let lock_key = if let Some(key_str) = config::parse_lock_hotkey() {
-        Config::parse_key_string(&key_str).with_context(|| {
+        Config::parse_lock_talk_hotkey(&key_str).with_context(|| {
             format!(
-                "Invalid lock hotkey from environment variable: '{}'. Must be A-Z.",
+                "Invalid lock hotkey from environment variable: '{}'. Must be A-Z or 'none'.",
                 key_str
             )
         })?
@@ -246,9 +625,9 @@ fn main() -> Result<()> {
         })?
     };
     let talk_key = if let Some(key_str) = config::parse_talk_hotkey() {
-        Config::parse_key_string(&key_str).with_context(|| {
+        Config::parse_lock_talk_hotkey(&key_str).with_context(|| {
             format!(
-                "Invalid talk hotkey from environment variable: '{}'. Must be A-Z.",
+                "Invalid talk hotkey from environment variable: '{}'. Must be A-Z or 'none'.",
                 key_str
             )
         })?
@@ -258,9 +637,9 @@ fn main() -> Result<()> {
         })?
     };
 
-    // Validate that resolved hotkeys are different
-    if lock_key == talk_key {
-        error!("Lock and Talk hotkeys cannot be the same: {:?}", lock_key);
+    // Validate that resolved hotkeys are different (both disabled is fine)
+    if let Some(key) = lock_key.filter(|_| lock_key == talk_key) {
+        error!("Lock and Talk hotkeys cannot be the same: {:?}", key);
         error!("This can happen if:");
         error!("  1. Both environment variables are set to the same key");
         error!("  2. The config file was manually edited with duplicate keys");
@@ -270,21 +649,124 @@ fn main() -> Result<()> {
 
     core.set_hotkey_config(lock_key, talk_key);
 
+    // Configure hotkey modifiers (config file > default Ctrl+Cmd+Shift)
+    let lock_modifiers = cfg.get_lock_modifiers().unwrap_or_else(|e| {
+        warn!("Invalid lock_modifiers in config file, using default (Ctrl+Cmd+Shift): {}", e);
+        HotkeyModifiers::DEFAULT
+    });
+    let talk_modifiers = cfg.get_talk_modifiers().unwrap_or_else(|e| {
+        warn!("Invalid talk_modifiers in config file, using default (Ctrl+Cmd+Shift): {}", e);
+        HotkeyModifiers::DEFAULT
+    });
+    core.set_hotkey_modifiers(lock_modifiers, talk_modifiers);
+    core.set_lock_double_press(
+        cfg.lock_double_press
+            .then(|| config::resolve_lock_double_press_window(cfg.lock_double_press_window_ms)),
+    );
+    core.set_recovery_code_hash(cfg.recovery_code_hash.clone());
+    core.set_audit_log_path(audit::default_path(&config_path));
+
+    // Configure the key passed through in Talk mode (config file > default spacebar)
+    match cfg.get_talk_passthrough_keycode() {
+        Ok(keycode) => core.set_talk_passthrough_keycode(keycode),
+        Err(e) => warn!("Invalid talk passthrough key in config file, using spacebar: {}", e),
+    }
+
+    // Configure Talk hotkey behaviour (hold or toggle)
+    core.set_talk_mode(cfg.talk_mode);
+
+    // What happens when accessibility permissions are lost (unlock, exit or retry)
+    core.set_permission_loss_policy(cfg.on_permission_loss);
+
+    // Keyboard layout used to translate keycodes while typing the passphrase
+    core.set_keyboard_layout(cfg.keyboard_layout);
+
+    // Configure Touch ID unlock hotkey (config file > default U)
+    match cfg.get_unlock_key_code() {
+        Ok(unlock_key) => core.set_unlock_hotkey(unlock_key),
+        Err(e) => warn!("Invalid unlock hotkey in config file, using default (U): {}", e),
+    }
+
+    // Configure emergency unlock (panic) hotkey (config file only, disabled by default)
+    match (cfg.get_panic_key_code(), cfg.get_panic_modifiers()) {
+        (Ok(panic_key), Ok(panic_modifiers)) => {
+            core.set_panic_hotkey(panic_key, panic_modifiers, cfg.panic_requires_touchid)
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            warn!("Invalid panic hotkey in config file, panic hotkey disabled: {}", e)
+        }
+    }
+
+    // Configure the unlock chord sequence (config file only, disabled by default)
+    match cfg.get_unlock_chord() {
+        Ok(chord) => core.set_unlock_chord(chord),
+        Err(e) => warn!("Invalid unlock chord in config file, unlock sequence disabled: {}", e),
+    }
+
+    // Configure hold-to-unlock (config file only, disabled by default)
+    match cfg.get_hold_unlock() {
+        Ok(hold) => core.set_hold_unlock(hold),
+        Err(e) => warn!("Invalid hold-to-unlock settings in config file, hold-to-unlock disabled: {}", e),
+    }
+
+    // Optionally require Touch ID after the passphrase (config file only)
+    core.set_two_factor_unlock(cfg.two_factor_unlock);
+
     // Set initial lock state
     if args.locked {
-        core.set_locked(true);
+        // Permissions were checked above; cache that so the lock isn't refused before
+        // the permission monitor's first check
+        core.state.set_cached_accessibility_permissions(true);
+        core.lock()
+            .context("Failed to start in locked mode")?;
         info!("Starting in LOCKED mode (--locked flag)");
+    } else if let Some(lock_hotkey) = core.get_lock_hotkey_display() {
+        info!(
+            "Starting in UNLOCKED mode (use --locked to start locked, or press {} to lock)",
+            lock_hotkey
+        );
     } else {
-        info!("Starting in UNLOCKED mode (use --locked to start locked, or press Ctrl+Cmd+Shift+L to lock)");
+        info!("Starting in UNLOCKED mode (use --locked to start locked; lock hotkey disabled)");
     }
 
     // Start core components
     core.start_event_tap()
         .context("Failed to start event tap")?;
-    core.start_hotkeys().context("Failed to start hotkeys")?;
+    if let Err(e) = core.start_hotkeys() {
+        match e.hotkey_registration_error().filter(|err| err.is_conflict()) {
+            Some(conflict) => {
+                warn!("{:#}", e);
+                eprintln!(
+                    "Warning: {}. Choose different modifiers with {} in config.toml (suggested alternatives are in the log).",
+                    conflict,
+                    conflict.kind().modifiers_config_key()
+                );
+            }
+            None => return Err(e).context("Failed to start hotkeys"),
+        }
+    }
     core.start_background_threads()
         .context("Failed to start background threads")?;
 
+    // Optional control socket for scripting
+    if cfg.control_socket {
+        if let Err(e) = core.start_ipc_server() {
+            warn!("Failed to start control socket: {:#}", e);
+        }
+    }
+
+    // Optional localhost HTTP API for home-automation dashboards
+    if let Some(port) = cfg.http_api_port {
+        match cfg.http_api_token.as_deref() {
+            Some(token) => {
+                if let Err(e) = core.start_http_api(port, token) {
+                    warn!("Failed to start HTTP API: {:#}", e);
+                }
+            }
+            None => warn!("http_api_port is set but http_api_token is missing - HTTP API not started"),
+        }
+    }
+
     // Display status and instructions
     info!("HandsOff is running - press Ctrl+C to quit");
     if core.is_locked() {
@@ -292,14 +774,26 @@ fn main() -> Result<()> {
         info!("- Type your passphrase to unlock (input won't be visible)");
     } else {
         info!("STATUS: INPUT IS UNLOCKED");
-        info!("- Press Ctrl+Cmd+Shift+L to lock input");
+        match core.get_lock_hotkey_display() {
+            Some(lock_hotkey) => info!("- Press {} to lock input", lock_hotkey),
+            None => info!("- Lock hotkey disabled: lock with 'handsoff lock' or a schedule"),
+        }
     }
 
-    // Run the event loop on the main thread - this is required for event tap to work!
-    info!("Starting event loop (required for event interception)...");
+    // Run the main thread's loop (keyboard layout snapshots need it; the event tap runs
+    // on its own run loop thread inside HandsOffCore)
+    info!("Starting event loop...");
     use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
     use std::time::Duration;
 
+    keycode::refresh_keyboard_layout();
+
+    // Suspected permission loss awaiting confirmation before exiting
+    let mut permission_loss = PermissionLossDebounce::default();
+    let permission_loss_action = core.state.get_permission_loss_policy().action(false);
+    // Set when exiting under on_permission_loss = "exit" (exit status 1 for a supervisor)
+    let mut exit_for_permission_loss = false;
+
     // Main event loop - polls every 500ms
     loop {
         // Run CFRunLoop for a brief period to process events
@@ -311,31 +805,105 @@ fn main() -> Result<()> {
             );
         }
 
-        // Check if we should exit (permission loss detected by event tap callback)
-        if core.state.should_exit_and_clear() {
-            warn!("Accessibility permissions lost - exiting");
-            eprintln!("\nERROR: Accessibility permissions were revoked.");
-            eprintln!("HandsOff cannot function without accessibility permissions.\n");
-            eprintln!("To restore:");
-            eprintln!("1. Open System Settings > Privacy & Security > Accessibility");
-            eprintln!("2. Enable HandsOff in the list");
-            eprintln!("3. Restart HandsOff CLI\n");
-            eprintln!("Exiting...");
-
-            // Clean shutdown
+        // Snapshot the active keyboard layout for passphrase entry (TIS needs the main thread)
+        keycode::refresh_keyboard_layout();
+
+        // Clean shutdown requested by SIGINT/SIGTERM
+        if core.state.is_shutdown_requested() {
+            info!("Shutdown requested - stopping input blocking");
             core.stop_event_tap();
             break;
         }
 
-        // Check if event tap should be stopped (fallback for permission monitor detection)
-        if core.state.should_stop_event_tap_and_clear() {
-            warn!("Stopping event tap due to permission loss (detected by monitor)");
+        // Permission loss detected by the event tap callback (exit requested) or by the
+        // permission monitor (stop requested, cached permissions cleared). The monitor also
+        // requests a stop when the event loop stalls.
+        let tap_lost_permissions = core.state.should_exit_and_clear();
+        let stop_requested = core.state.should_stop_event_tap_and_clear();
+        if tap_lost_permissions || stop_requested {
+            // Stop blocking right away so input is never stuck (the monitor has already
+            // unlocked)
             core.stop_event_tap();
 
-            // For CLI, if event tap stops, we should exit
-            eprintln!("\nInput blocking stopped due to permission loss. Exiting...");
-            break;
+            if !tap_lost_permissions && core.has_accessibility_permissions() {
+                warn!("Stopping event tap due to a stalled event loop (detected by monitor)");
+                eprintln!("\nInput blocking stopped for safety (stalled event loop). Exiting...");
+                break;
+            }
+            match permission_loss_action {
+                PermissionLossAction::ExitNow => {
+                    warn!("Accessibility permissions lost - exiting (on_permission_loss = exit)");
+                    exit_for_permission_loss = true;
+                    break;
+                }
+                PermissionLossAction::Wait => {
+                    warn!("Accessibility permissions lost - input blocking stopped, waiting for them to be restored");
+                }
+                PermissionLossAction::ExitWhenConfirmed => {
+                    if !permission_loss.is_pending() {
+                        warn!("Accessibility permissions appear lost - input blocking stopped, confirming before exit");
+                        permission_loss.record(false);
+                    }
+                }
+            }
+        } else if permission_loss_action == PermissionLossAction::Wait {
+            // The permission monitor requests a restart once restored permissions hold
+            if core.state.should_start_event_tap_and_clear() {
+                info!("Accessibility permissions restored - restarting input blocking");
+                if let Err(e) = core.restart_event_tap() {
+                    warn!("Failed to restart input blocking: {:#} - will retry", e);
+                }
+            }
+        } else if permission_loss.is_pending() {
+            // Permission checks can briefly fail (e.g. while System Settings updates the
+            // Accessibility list) - only exit once the loss is confirmed
+            let has_permissions = input_blocking::check_accessibility_permissions();
+            if permission_loss.record(has_permissions) {
+                warn!("Accessibility permissions lost - exiting");
+                eprintln!("\nERROR: Accessibility permissions were revoked.");
+                eprintln!("HandsOff cannot function without accessibility permissions.\n");
+                eprintln!("To restore:");
+                eprintln!("1. Open System Settings > Privacy & Security > Accessibility");
+                eprintln!("2. Enable HandsOff in the list");
+                eprintln!("3. Restart HandsOff CLI\n");
+                eprintln!("Exiting...");
+                break;
+            } else if has_permissions {
+                info!("Accessibility permissions are back (transient blip) - restarting input blocking");
+                if let Err(e) = core.start_event_tap() {
+                    error!("Failed to restart input blocking: {:#} - exiting", e);
+                    eprintln!("\nFailed to restart input blocking: {:#}. Exiting...", e);
+                    break;
+                }
+            }
+        }
+
+        // Persist the invalidated recovery code after it was used to unlock
+        if core.state.should_persist_recovery_code_consumed_and_clear() {
+            match Config::persist_recovery_code_consumed(&config_path) {
+                Ok(()) => info!("Used recovery code cleared from configuration"),
+                Err(e) => error!("Failed to clear used recovery code: {}", e),
+            }
+        }
+
+        // Apply disable/enable requested over the control socket
+        if core.state.should_disable_and_clear() {
+            if let Err(e) = core.disable() {
+                error!("Failed to disable: {}", e);
+            }
         }
+        if core.state.should_enable_and_clear() && core.state.is_disabled() {
+            if let Err(e) = core.enable() {
+                error!("Failed to enable: {}", e);
+            }
+        }
+    }
+
+    // Don't leave a snapshot behind that claims we're still running
+    let _ = std::fs::remove_file(status::status_path());
+
+    if exit_for_permission_loss {
+        anyhow::bail!("Accessibility permissions were revoked (on_permission_loss = exit)");
     }
 
     info!("CLI shutdown complete");
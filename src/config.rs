@@ -9,14 +9,34 @@
 //! - HANDS_OFF_AUTO_UNLOCK: Override auto-unlock timeout from config file
 //! - HANDS_OFF_LOCK_HOTKEY: Override lock hotkey last key (A-Z)
 //! - HANDS_OFF_TALK_HOTKEY: Override talk hotkey last key (A-Z)
+//! - HANDS_OFF_BUFFER_RESET: Override passphrase buffer reset timeout (2-15 seconds)
+//! - HANDS_OFF_LOCK_SCOPE: Override which devices are blocked (all, keyboard, mouse)
+//! - HANDS_OFF_CONFIG: Use a config file other than the platform default
+//! - HANDS_OFF_PROFILE: Use a named profile (config-<name>.toml in the config directory)
+//! - HANDS_OFF_LOG_FILE: Also write logs to this file (rotated by size)
 
 use crate::app_state::{
-    AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS, AUTO_UNLOCK_DEFAULT_SECONDS,
-    AUTO_UNLOCK_MAX_SECONDS, AUTO_UNLOCK_MIN_SECONDS,
+    AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS, AUTO_LOCK_WARNING_DEFAULT_SECONDS,
+    AUTO_LOCK_WARNING_MAX_SECONDS, AUTO_UNLOCK_DEFAULT_SECONDS, UNLOCK_GRACE_DEFAULT_SECONDS,
+    UNLOCK_GRACE_MAX_SECONDS,
+    AUTO_UNLOCK_MAX_SECONDS, AUTO_UNLOCK_MIN_SECONDS, BUFFER_RESET_DEFAULT_SECONDS,
+    BUFFER_RESET_MAX_SECONDS, BUFFER_RESET_MIN_SECONDS,
 };
+use crate::app_state::LockScope;
 use crate::config_file::Config;
+use crate::constants::{
+    ACTIVITY_DEBOUNCE_EVENTS_DEFAULT, ACTIVITY_DEBOUNCE_EVENTS_MAX,
+    ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS, ACTIVITY_DEBOUNCE_WINDOW_MAX_MS,
+    ACTIVITY_DEBOUNCE_WINDOW_MIN_MS, LOCK_DOUBLE_PRESS_DEFAULT_MS, LOCK_DOUBLE_PRESS_MAX_MS, LOCK_DOUBLE_PRESS_MIN_MS,
+    MAX_LOCK_DURATION_DEFAULT_MINUTES, MAX_LOCK_DURATION_MAX_MINUTES,
+    MAX_LOCK_DURATION_MIN_MINUTES, NOTIFICATION_TIMEOUT_MAX_MS, NOTIFICATION_TIMEOUT_MIN_MS, NOTIFICATION_TIMEOUT_MS,
+    WATCHDOG_TIMEOUT_DEFAULT_SECS, WATCHDOG_TIMEOUT_MAX_SECS, WATCHDOG_TIMEOUT_MIN_SECS,
+};
+use crate::utils::modifiers::HotkeyModifiers;
 use log::{debug, info, warn};
 use std::env;
+use std::fmt;
+use std::path::PathBuf;
 
 /// Parse the HANDS_OFF_AUTO_UNLOCK environment variable
 ///
@@ -96,11 +116,11 @@ pub fn parse_auto_lock_timeout() -> Option<u64> {
 
 /// Parse the HANDS_OFF_LOCK_HOTKEY environment variable
 ///
-/// Returns Some(key) if a valid letter A-Z is specified
+/// Returns Some(key) if a valid letter A-Z (or "none" to disable) is specified
 /// Returns None if not set or invalid
 pub fn parse_lock_hotkey() -> Option<String> {
     match env::var("HANDS_OFF_LOCK_HOTKEY") {
-        Ok(val) => match Config::validate_hotkey(&val) {
+        Ok(val) => match Config::validate_lock_talk_hotkey(&val) {
             Ok(()) => {
                 info!("Lock hotkey set via environment variable: {}", val);
                 Some(val.to_uppercase())
@@ -122,11 +142,11 @@ pub fn parse_lock_hotkey() -> Option<String> {
 
 /// Parse the HANDS_OFF_TALK_HOTKEY environment variable
 ///
-/// Returns Some(key) if a valid letter A-Z is specified
+/// Returns Some(key) if a valid letter A-Z (or "none" to disable) is specified
 /// Returns None if not set or invalid
 pub fn parse_talk_hotkey() -> Option<String> {
     match env::var("HANDS_OFF_TALK_HOTKEY") {
-        Ok(val) => match Config::validate_hotkey(&val) {
+        Ok(val) => match Config::validate_lock_talk_hotkey(&val) {
             Ok(()) => {
                 info!("Talk hotkey set via environment variable: {}", val);
                 Some(val.to_uppercase())
@@ -146,6 +166,382 @@ pub fn parse_talk_hotkey() -> Option<String> {
     }
 }
 
+/// Parse the HANDS_OFF_BUFFER_RESET environment variable
+///
+/// Returns Some(seconds) if a valid timeout is configured (2-15 seconds)
+/// Returns None if not set or invalid
+pub fn parse_buffer_reset_timeout() -> Option<u64> {
+    match env::var("HANDS_OFF_BUFFER_RESET") {
+        Ok(val) => match val.parse::<u64>() {
+            Ok(seconds)
+                if (BUFFER_RESET_MIN_SECONDS..=BUFFER_RESET_MAX_SECONDS).contains(&seconds) =>
+            {
+                info!("Buffer reset timeout set via environment variable: {} seconds", seconds);
+                Some(seconds)
+            }
+            Ok(seconds) => {
+                warn!(
+                    "Invalid buffer reset timeout: {} (must be {}-{} seconds). Ignoring environment variable.",
+                    seconds, BUFFER_RESET_MIN_SECONDS, BUFFER_RESET_MAX_SECONDS
+                );
+                None
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to parse HANDS_OFF_BUFFER_RESET: {}. Ignoring environment variable.",
+                    e
+                );
+                None
+            }
+        },
+        Err(_) => {
+            debug!("HANDS_OFF_BUFFER_RESET not set.");
+            None
+        }
+    }
+}
+
+/// Parse the HANDS_OFF_LOCK_SCOPE environment variable
+///
+/// Returns Some(scope) if set to all, keyboard or mouse
+/// Returns None if not set or invalid
+pub fn parse_lock_scope() -> Option<LockScope> {
+    match env::var("HANDS_OFF_LOCK_SCOPE") {
+        Ok(val) => match val.parse::<LockScope>() {
+            Ok(scope) => {
+                info!("Lock scope set via environment variable: {}", scope);
+                Some(scope)
+            }
+            Err(e) => {
+                warn!("{}. Ignoring environment variable.", e);
+                None
+            }
+        },
+        Err(_) => {
+            debug!("HANDS_OFF_LOCK_SCOPE not set.");
+            None
+        }
+    }
+}
+
+/// Parse the HANDS_OFF_CONFIG environment variable
+///
+/// Returns Some(path) if set to a non-empty value
+/// Returns None if not set or empty
+pub fn parse_config_path() -> Option<PathBuf> {
+    match env::var_os("HANDS_OFF_CONFIG") {
+        Some(val) if !val.is_empty() => {
+            let path = PathBuf::from(val);
+            info!("Config file path set via environment variable: {}", path.display());
+            Some(path)
+        }
+        _ => {
+            debug!("HANDS_OFF_CONFIG not set.");
+            None
+        }
+    }
+}
+
+/// Parse the HANDS_OFF_PROFILE environment variable
+///
+/// Returns Some(name) if set to a non-empty value
+/// Returns None if not set or empty
+pub fn parse_profile() -> Option<String> {
+    match env::var("HANDS_OFF_PROFILE") {
+        Ok(val) if !val.is_empty() => {
+            info!("Config profile set via environment variable: {}", val);
+            Some(val)
+        }
+        _ => {
+            debug!("HANDS_OFF_PROFILE not set.");
+            None
+        }
+    }
+}
+
+/// Where the config file path comes from, in precedence order
+struct ConfigPathSources {
+    cli_path: Option<PathBuf>,
+    cli_profile: Option<String>,
+    env_path: Option<PathBuf>,
+    env_profile: Option<String>,
+}
+
+/// Resolve config file path using proper precedence (internal, testable version)
+///
+/// Precedence order:
+/// 1. --config argument (if provided)
+/// 2. --profile argument (if provided)
+/// 3. HANDS_OFF_CONFIG value (if provided)
+/// 4. HANDS_OFF_PROFILE value (if provided)
+/// 5. The default profile (Config::config_path)
+fn resolve_config_path_internal(sources: ConfigPathSources) -> Result<PathBuf, anyhow::Error> {
+    if let Some(path) = sources.cli_path {
+        return Ok(path);
+    }
+    if let Some(profile) = sources.cli_profile {
+        return Config::profile_path(&profile);
+    }
+    if let Some(path) = sources.env_path {
+        return Ok(path);
+    }
+    match sources.env_profile {
+        Some(profile) => Config::profile_path(&profile),
+        None => Ok(Config::config_path()),
+    }
+}
+
+/// Resolve config file path using proper precedence
+///
+/// Precedence order:
+/// 1. --config argument
+/// 2. --profile argument
+/// 3. Environment variable (HANDS_OFF_CONFIG)
+/// 4. Environment variable (HANDS_OFF_PROFILE)
+/// 5. The default profile (platform default path)
+///
+/// Fails if the selected profile name is invalid.
+pub fn resolve_config_path(
+    cli_value: Option<PathBuf>,
+    cli_profile: Option<String>,
+) -> Result<PathBuf, anyhow::Error> {
+    let from_cli = cli_value.is_some() || cli_profile.is_some();
+    let env_path = if from_cli { None } else { parse_config_path() };
+    let env_profile = if from_cli || env_path.is_some() {
+        None
+    } else {
+        parse_profile()
+    };
+    resolve_config_path_internal(ConfigPathSources {
+        cli_path: cli_value,
+        cli_profile,
+        env_path,
+        env_profile,
+    })
+}
+
+/// Parse the HANDS_OFF_LOG_FILE environment variable
+///
+/// Returns Some(path) if set to a non-empty value
+/// Returns None if not set or empty
+pub fn parse_log_file_path() -> Option<PathBuf> {
+    match env::var_os("HANDS_OFF_LOG_FILE") {
+        Some(val) if !val.is_empty() => Some(PathBuf::from(val)),
+        _ => None,
+    }
+}
+
+/// Resolve the log file path (None = log to stderr only)
+///
+/// Precedence order:
+/// 1. --log-file argument
+/// 2. Environment variable (HANDS_OFF_LOG_FILE)
+pub fn resolve_log_file_path(cli_value: Option<PathBuf>) -> Option<PathBuf> {
+    cli_value.or_else(parse_log_file_path)
+}
+
+/// Resolve buffer reset timeout using proper precedence (internal, testable version)
+///
+/// Precedence order:
+/// 1. Environment variable value (if provided)
+/// 2. Config file value (if set and within 2-15 seconds)
+/// 3. BUFFER_RESET_DEFAULT_SECONDS
+fn resolve_buffer_reset_timeout_internal(env_value: Option<u64>, config_value: Option<u64>) -> u64 {
+    env_value
+        .or_else(|| {
+            config_value.filter(|seconds| {
+                let valid = (BUFFER_RESET_MIN_SECONDS..=BUFFER_RESET_MAX_SECONDS).contains(seconds);
+                if !valid {
+                    warn!(
+                        "Invalid buffer_reset_timeout in config file: {} (must be {}-{} seconds). Using default.",
+                        seconds, BUFFER_RESET_MIN_SECONDS, BUFFER_RESET_MAX_SECONDS
+                    );
+                }
+                valid
+            })
+        })
+        .unwrap_or(BUFFER_RESET_DEFAULT_SECONDS)
+}
+
+/// Resolve buffer reset timeout using proper precedence
+///
+/// Precedence order:
+/// 1. Environment variable (HANDS_OFF_BUFFER_RESET)
+/// 2. Config file value
+/// 3. BUFFER_RESET_DEFAULT_SECONDS
+pub fn resolve_buffer_reset_timeout(config_value: Option<u64>) -> u64 {
+    resolve_buffer_reset_timeout_internal(parse_buffer_reset_timeout(), config_value)
+}
+
+/// Resolve the auto-lock warning lead time from the config file
+///
+/// Returns the config value if set and within 0-15 seconds (0 = no warning),
+/// otherwise AUTO_LOCK_WARNING_DEFAULT_SECONDS.
+pub fn resolve_auto_lock_warning(config_value: Option<u64>) -> u64 {
+    config_value
+        .filter(|seconds| {
+            let valid = *seconds <= AUTO_LOCK_WARNING_MAX_SECONDS;
+            if !valid {
+                warn!(
+                    "Invalid auto_lock_warning in config file: {} (must be 0-{} seconds). Using default.",
+                    seconds, AUTO_LOCK_WARNING_MAX_SECONDS
+                );
+            }
+            valid
+        })
+        .unwrap_or(AUTO_LOCK_WARNING_DEFAULT_SECONDS)
+}
+
+/// Resolve the post-unlock grace period from the config file
+///
+/// Returns the config value if set and within 0-120 seconds (0 = no grace period),
+/// otherwise UNLOCK_GRACE_DEFAULT_SECONDS.
+pub fn resolve_unlock_grace(config_value: Option<u64>) -> u64 {
+    config_value
+        .filter(|seconds| {
+            let valid = *seconds <= UNLOCK_GRACE_MAX_SECONDS;
+            if !valid {
+                warn!(
+                    "Invalid unlock_grace_secs in config file: {} (must be 0-{} seconds). Using default.",
+                    seconds, UNLOCK_GRACE_MAX_SECONDS
+                );
+            }
+            valid
+        })
+        .unwrap_or(UNLOCK_GRACE_DEFAULT_SECONDS)
+}
+
+/// Resolve the activity debounce event count from the config file
+///
+/// Returns the config value if set and within 0-20 events (0 or 1 = off),
+/// otherwise ACTIVITY_DEBOUNCE_EVENTS_DEFAULT.
+pub fn resolve_activity_debounce_events(config_value: Option<u32>) -> u32 {
+    config_value
+        .filter(|events| {
+            let valid = *events <= ACTIVITY_DEBOUNCE_EVENTS_MAX;
+            if !valid {
+                warn!(
+                    "Invalid activity_debounce_events in config file: {} (must be 0-{}). Using default.",
+                    events, ACTIVITY_DEBOUNCE_EVENTS_MAX
+                );
+            }
+            valid
+        })
+        .unwrap_or(ACTIVITY_DEBOUNCE_EVENTS_DEFAULT)
+}
+
+/// Resolve the activity debounce window from the config file
+///
+/// Returns the config value if set and within 100-10000 milliseconds,
+/// otherwise ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS.
+pub fn resolve_activity_debounce_window(config_value: Option<u64>) -> u64 {
+    config_value
+        .filter(|ms| {
+            let valid =
+                (ACTIVITY_DEBOUNCE_WINDOW_MIN_MS..=ACTIVITY_DEBOUNCE_WINDOW_MAX_MS).contains(ms);
+            if !valid {
+                warn!(
+                    "Invalid activity_debounce_window_ms in config file: {} (must be {}-{} ms). Using default.",
+                    ms, ACTIVITY_DEBOUNCE_WINDOW_MIN_MS, ACTIVITY_DEBOUNCE_WINDOW_MAX_MS
+                );
+            }
+            valid
+        })
+        .unwrap_or(ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS)
+}
+
+/// Resolve the battery auto-lock timeout from the config file
+///
+/// Returns the config value if set and within 20-600 seconds (like auto_lock_timeout),
+/// otherwise None, so the regular timeout applies on battery too.
+pub fn resolve_auto_lock_timeout_on_battery(config_value: Option<u64>) -> Option<u64> {
+    config_value.filter(|seconds| {
+        let valid = (AUTO_LOCK_MIN_SECONDS..=AUTO_LOCK_MAX_SECONDS).contains(seconds);
+        if !valid {
+            warn!(
+                "Invalid auto_lock_timeout_on_battery in config file: {} (must be {}-{} seconds). Using auto_lock_timeout.",
+                seconds, AUTO_LOCK_MIN_SECONDS, AUTO_LOCK_MAX_SECONDS
+            );
+        }
+        valid
+    })
+}
+
+/// Resolve the event loop watchdog timeout from the config file
+///
+/// Returns the config value if set and within 10-300 seconds,
+/// otherwise WATCHDOG_TIMEOUT_DEFAULT_SECS.
+pub fn resolve_watchdog_timeout(config_value: Option<u64>) -> u64 {
+    config_value
+        .filter(|seconds| {
+            let valid = (WATCHDOG_TIMEOUT_MIN_SECS..=WATCHDOG_TIMEOUT_MAX_SECS).contains(seconds);
+            if !valid {
+                warn!(
+                    "Invalid watchdog_timeout_secs in config file: {} (must be {}-{} seconds). Using default.",
+                    seconds, WATCHDOG_TIMEOUT_MIN_SECS, WATCHDOG_TIMEOUT_MAX_SECS
+                );
+            }
+            valid
+        })
+        .unwrap_or(WATCHDOG_TIMEOUT_DEFAULT_SECS)
+}
+
+/// Resolve the max lock duration hard cap from the config file
+///
+/// Returns the config value clamped to 15-720 minutes (the cap can't be turned off),
+/// or MAX_LOCK_DURATION_DEFAULT_MINUTES when unset.
+pub fn resolve_max_lock_duration(config_value: Option<u64>) -> u64 {
+    let Some(minutes) = config_value else {
+        return MAX_LOCK_DURATION_DEFAULT_MINUTES;
+    };
+    let clamped = minutes.clamp(MAX_LOCK_DURATION_MIN_MINUTES, MAX_LOCK_DURATION_MAX_MINUTES);
+    if clamped != minutes {
+        warn!(
+            "Invalid max_lock_duration_mins in config file: {} (must be {}-{} minutes). Using {}.",
+            minutes, MAX_LOCK_DURATION_MIN_MINUTES, MAX_LOCK_DURATION_MAX_MINUTES, clamped
+        );
+    }
+    clamped
+}
+
+/// Resolve the notification display time from the config file
+///
+/// Returns the config value if set and within 1000-30000 milliseconds,
+/// otherwise NOTIFICATION_TIMEOUT_MS.
+pub fn resolve_notification_timeout(config_value: Option<u32>) -> u32 {
+    config_value
+        .filter(|ms| {
+            let valid = (NOTIFICATION_TIMEOUT_MIN_MS..=NOTIFICATION_TIMEOUT_MAX_MS).contains(ms);
+            if !valid {
+                warn!(
+                    "Invalid notification_timeout_ms in config file: {} (must be {}-{} ms). Using default.",
+                    ms, NOTIFICATION_TIMEOUT_MIN_MS, NOTIFICATION_TIMEOUT_MAX_MS
+                );
+            }
+            valid
+        })
+        .unwrap_or(NOTIFICATION_TIMEOUT_MS)
+}
+
+/// Resolve the lock hotkey double-press window from the config file
+///
+/// Returns the config value if set and within 200-1500 milliseconds,
+/// otherwise LOCK_DOUBLE_PRESS_DEFAULT_MS.
+pub fn resolve_lock_double_press_window(config_value: Option<u64>) -> u64 {
+    config_value
+        .filter(|ms| {
+            let valid = (LOCK_DOUBLE_PRESS_MIN_MS..=LOCK_DOUBLE_PRESS_MAX_MS).contains(ms);
+            if !valid {
+                warn!(
+                    "Invalid lock_double_press_window_ms in config file: {} (must be {}-{} ms). Using default.",
+                    ms, LOCK_DOUBLE_PRESS_MIN_MS, LOCK_DOUBLE_PRESS_MAX_MS
+                );
+            }
+            valid
+        })
+        .unwrap_or(LOCK_DOUBLE_PRESS_DEFAULT_MS)
+}
+
 /// Resolve auto-unlock timeout using proper precedence (internal, testable version)
 ///
 /// Precedence order:
@@ -202,6 +598,171 @@ pub fn resolve_auto_unlock_timeout(config_value: u64) -> Option<u64> {
     resolve_auto_unlock_timeout_internal(parse_auto_unlock_timeout(), config_value)
 }
 
+/// Parse an `--auto-unlock` command-line value
+///
+/// Accepts 0 (disable auto-unlock) or a timeout within 60-900 seconds.
+/// Used as the clap value parser, so invalid values are rejected at startup.
+pub fn parse_auto_unlock_arg(value: &str) -> Result<u64, String> {
+    let seconds = value
+        .parse::<u64>()
+        .map_err(|e| format!("invalid number of seconds: {}", e))?;
+    if seconds == 0 || (AUTO_UNLOCK_MIN_SECONDS..=AUTO_UNLOCK_MAX_SECONDS).contains(&seconds) {
+        Ok(seconds)
+    } else {
+        Err(format!(
+            "must be {}-{} seconds or 0 to disable",
+            AUTO_UNLOCK_MIN_SECONDS, AUTO_UNLOCK_MAX_SECONDS
+        ))
+    }
+}
+
+/// Resolve auto-unlock timeout with a command-line override (internal, testable version)
+///
+/// * `arg_value` - The validated `--auto-unlock` value (None if not given, 0 disables)
+fn resolve_auto_unlock_timeout_with_arg_internal(
+    arg_value: Option<u64>,
+    env_value: Option<u64>,
+    config_value: u64,
+) -> Option<u64> {
+    match arg_value {
+        Some(0) => None,
+        Some(seconds) => Some(seconds),
+        None => resolve_auto_unlock_timeout_internal(env_value, config_value),
+    }
+}
+
+/// Resolve auto-unlock timeout, letting the `--auto-unlock` argument win
+///
+/// Precedence order:
+/// 1. Command-line argument (0 disables auto-unlock)
+/// 2. Environment variable (HANDS_OFF_AUTO_UNLOCK)
+/// 3. Config file value
+/// 4. Build-time default
+pub fn resolve_auto_unlock_timeout_with_arg(
+    arg_value: Option<u64>,
+    config_value: u64,
+) -> Option<u64> {
+    if let Some(seconds) = arg_value {
+        info!("Auto-unlock timeout set via --auto-unlock argument: {} seconds", seconds);
+    }
+    resolve_auto_unlock_timeout_with_arg_internal(
+        arg_value,
+        parse_auto_unlock_timeout(),
+        config_value,
+    )
+}
+
+/// Environment variable overrides, already parsed and validated
+#[derive(Debug, Default, Clone)]
+pub(crate) struct EnvOverrides {
+    auto_lock: Option<u64>,
+    auto_unlock: Option<u64>,
+    lock_hotkey: Option<String>,
+    talk_hotkey: Option<String>,
+    buffer_reset: Option<u64>,
+    lock_scope: Option<LockScope>,
+}
+
+impl EnvOverrides {
+    fn from_env() -> Self {
+        Self {
+            auto_lock: parse_auto_lock_timeout(),
+            auto_unlock: parse_auto_unlock_timeout(),
+            lock_hotkey: parse_lock_hotkey(),
+            talk_hotkey: parse_talk_hotkey(),
+            buffer_reset: parse_buffer_reset_timeout(),
+            lock_scope: parse_lock_scope(),
+        }
+    }
+}
+
+/// Settings in effect after environment overrides are applied to the config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveSettings {
+    pub auto_lock_timeout: u64,
+    /// None = auto-unlock disabled
+    pub auto_unlock_timeout: Option<u64>,
+    /// Full hotkey, e.g. "Ctrl+Cmd+Shift+L"
+    pub lock_hotkey: String,
+    pub talk_hotkey: String,
+    pub lock_scope: LockScope,
+    pub buffer_reset_timeout: u64,
+    /// Whether a passphrase is stored (in the config file or Keychain)
+    pub passphrase_set: bool,
+}
+
+impl fmt::Display for EffectiveSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Auto-lock:     {}s", self.auto_lock_timeout)?;
+        match self.auto_unlock_timeout {
+            Some(seconds) => writeln!(f, "Auto-unlock:   {}s", seconds)?,
+            None => writeln!(f, "Auto-unlock:   disabled")?,
+        }
+        writeln!(f, "Lock hotkey:   {}", self.lock_hotkey)?;
+        writeln!(f, "Talk hotkey:   {}", self.talk_hotkey)?;
+        writeln!(f, "Lock scope:    {}", self.lock_scope)?;
+        writeln!(f, "Buffer reset:  {}s", self.buffer_reset_timeout)?;
+        write!(
+            f,
+            "Passphrase:    {}",
+            if self.passphrase_set { "set" } else { "not set" }
+        )
+    }
+}
+
+/// Combine hotkey modifiers and the last key into a display string ("disabled" for "none")
+fn hotkey_display(modifiers: Result<HotkeyModifiers, anyhow::Error>, key: &str) -> String {
+    if Config::is_hotkey_disabled(key) {
+        return "disabled".to_string();
+    }
+    format!("{}+{}", modifiers.unwrap_or(HotkeyModifiers::DEFAULT), key.to_uppercase())
+}
+
+/// Resolve the effective settings (internal, testable version)
+///
+/// Uses the same precedence as startup: environment variable > config file > default.
+pub(crate) fn resolve_effective_settings_internal(
+    cfg: &Config,
+    env: &EnvOverrides,
+) -> EffectiveSettings {
+    let lock_key = env
+        .lock_hotkey
+        .as_deref()
+        .or(cfg.lock_hotkey.as_deref())
+        .unwrap_or("L");
+    let talk_key = env
+        .talk_hotkey
+        .as_deref()
+        .or(cfg.talk_hotkey.as_deref())
+        .unwrap_or("T");
+
+    EffectiveSettings {
+        auto_lock_timeout: env.auto_lock.unwrap_or(cfg.auto_lock_timeout),
+        auto_unlock_timeout: resolve_auto_unlock_timeout_internal(
+            env.auto_unlock,
+            cfg.auto_unlock_timeout,
+        ),
+        lock_hotkey: hotkey_display(cfg.get_lock_modifiers(), lock_key),
+        talk_hotkey: hotkey_display(cfg.get_talk_modifiers(), talk_key),
+        lock_scope: env.lock_scope.unwrap_or(cfg.lock_scope),
+        buffer_reset_timeout: resolve_buffer_reset_timeout_internal(
+            env.buffer_reset,
+            cfg.buffer_reset_timeout,
+        ),
+        passphrase_set: cfg.use_keychain || !cfg.encrypted_passphrase.is_empty(),
+    }
+}
+
+/// Resolve the settings HandsOff would run with (`handsoff config show`, tray Settings…)
+///
+/// Precedence order:
+/// 1. Environment variables (HANDS_OFF_*)
+/// 2. Config file values
+/// 3. Defaults
+pub fn resolve_effective_settings(cfg: &Config) -> EffectiveSettings {
+    resolve_effective_settings_internal(cfg, &EnvOverrides::from_env())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,6 +911,49 @@ mod tests {
         env::remove_var("HANDS_OFF_AUTO_UNLOCK");
     }
 
+    #[test]
+    fn test_parse_auto_unlock_arg_accepts_range_and_zero() {
+        assert_eq!(parse_auto_unlock_arg("0"), Ok(0));
+        assert_eq!(
+            parse_auto_unlock_arg(&AUTO_UNLOCK_MIN_SECONDS.to_string()),
+            Ok(AUTO_UNLOCK_MIN_SECONDS)
+        );
+        assert_eq!(
+            parse_auto_unlock_arg(&AUTO_UNLOCK_MAX_SECONDS.to_string()),
+            Ok(AUTO_UNLOCK_MAX_SECONDS)
+        );
+    }
+
+    #[test]
+    fn test_parse_auto_unlock_arg_rejects_out_of_range() {
+        assert!(parse_auto_unlock_arg(&(AUTO_UNLOCK_MIN_SECONDS - 1).to_string()).is_err());
+        assert!(parse_auto_unlock_arg(&(AUTO_UNLOCK_MAX_SECONDS + 1).to_string()).is_err());
+        assert!(parse_auto_unlock_arg("-5").is_err());
+        assert!(parse_auto_unlock_arg("abc").is_err());
+    }
+
+    #[test]
+    fn test_resolve_auto_unlock_arg_overrides_env_and_config() {
+        assert_eq!(
+            resolve_auto_unlock_timeout_with_arg_internal(Some(90), Some(300), 120),
+            Some(90)
+        );
+        // 0 disables even when env and config enable auto-unlock
+        assert_eq!(
+            resolve_auto_unlock_timeout_with_arg_internal(Some(0), Some(300), 120),
+            None
+        );
+        // Without the argument the usual env > config precedence applies
+        assert_eq!(
+            resolve_auto_unlock_timeout_with_arg_internal(None, Some(300), 120),
+            Some(300)
+        );
+        assert_eq!(
+            resolve_auto_unlock_timeout_with_arg_internal(None, None, 120),
+            Some(120)
+        );
+    }
+
     #[test]
     fn test_parse_auto_unlock_boundary_cases() {
         // Test just below minimum
@@ -639,4 +1243,306 @@ mod tests {
             Some(250)
         );
     }
+
+    #[test]
+    fn test_parse_buffer_reset_values() {
+        env::set_var("HANDS_OFF_BUFFER_RESET", "2");
+        assert_eq!(parse_buffer_reset_timeout(), Some(2), "Should accept minimum");
+
+        env::set_var("HANDS_OFF_BUFFER_RESET", "15");
+        assert_eq!(parse_buffer_reset_timeout(), Some(15), "Should accept maximum");
+
+        env::set_var("HANDS_OFF_BUFFER_RESET", "1");
+        assert_eq!(parse_buffer_reset_timeout(), None, "Should reject below minimum");
+
+        env::set_var("HANDS_OFF_BUFFER_RESET", "16");
+        assert_eq!(parse_buffer_reset_timeout(), None, "Should reject above maximum");
+
+        env::set_var("HANDS_OFF_BUFFER_RESET", "abc");
+        assert_eq!(parse_buffer_reset_timeout(), None, "Should reject non-numeric");
+
+        env::remove_var("HANDS_OFF_BUFFER_RESET");
+        assert_eq!(parse_buffer_reset_timeout(), None, "Should return None when not set");
+    }
+
+    #[test]
+    fn test_resolve_buffer_reset_precedence() {
+        // env > config > default
+        assert_eq!(resolve_buffer_reset_timeout_internal(Some(10), Some(5)), 10);
+        assert_eq!(resolve_buffer_reset_timeout_internal(None, Some(5)), 5);
+        assert_eq!(
+            resolve_buffer_reset_timeout_internal(None, None),
+            BUFFER_RESET_DEFAULT_SECONDS
+        );
+    }
+
+    #[test]
+    fn test_resolve_buffer_reset_out_of_range_config_uses_default() {
+        assert_eq!(
+            resolve_buffer_reset_timeout_internal(None, Some(1)),
+            BUFFER_RESET_DEFAULT_SECONDS
+        );
+        assert_eq!(
+            resolve_buffer_reset_timeout_internal(None, Some(60)),
+            BUFFER_RESET_DEFAULT_SECONDS
+        );
+    }
+
+    #[test]
+    fn test_resolve_auto_lock_warning() {
+        assert_eq!(resolve_auto_lock_warning(None), AUTO_LOCK_WARNING_DEFAULT_SECONDS);
+        assert_eq!(resolve_auto_lock_warning(Some(0)), 0, "0 suppresses the warning");
+        assert_eq!(resolve_auto_lock_warning(Some(5)), 5);
+        assert_eq!(
+            resolve_auto_lock_warning(Some(AUTO_LOCK_WARNING_MAX_SECONDS + 1)),
+            AUTO_LOCK_WARNING_DEFAULT_SECONDS
+        );
+    }
+
+    #[test]
+    fn test_resolve_unlock_grace() {
+        assert_eq!(resolve_unlock_grace(None), UNLOCK_GRACE_DEFAULT_SECONDS);
+        assert_eq!(resolve_unlock_grace(Some(0)), 0);
+        assert_eq!(resolve_unlock_grace(Some(30)), 30);
+        assert_eq!(
+            resolve_unlock_grace(Some(UNLOCK_GRACE_MAX_SECONDS + 1)),
+            UNLOCK_GRACE_DEFAULT_SECONDS
+        );
+    }
+
+    #[test]
+    fn test_resolve_activity_debounce() {
+        assert_eq!(resolve_activity_debounce_events(None), ACTIVITY_DEBOUNCE_EVENTS_DEFAULT);
+        assert_eq!(resolve_activity_debounce_events(Some(4)), 4);
+        assert_eq!(
+            resolve_activity_debounce_events(Some(ACTIVITY_DEBOUNCE_EVENTS_MAX + 1)),
+            ACTIVITY_DEBOUNCE_EVENTS_DEFAULT
+        );
+
+        assert_eq!(resolve_activity_debounce_window(None), ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS);
+        assert_eq!(resolve_activity_debounce_window(Some(500)), 500);
+        assert_eq!(
+            resolve_activity_debounce_window(Some(ACTIVITY_DEBOUNCE_WINDOW_MIN_MS - 1)),
+            ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS
+        );
+        assert_eq!(
+            resolve_activity_debounce_window(Some(ACTIVITY_DEBOUNCE_WINDOW_MAX_MS + 1)),
+            ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS
+        );
+    }
+
+    #[test]
+    fn test_resolve_auto_lock_timeout_on_battery() {
+        assert_eq!(resolve_auto_lock_timeout_on_battery(None), None);
+        assert_eq!(resolve_auto_lock_timeout_on_battery(Some(60)), Some(60));
+        assert_eq!(resolve_auto_lock_timeout_on_battery(Some(AUTO_LOCK_MIN_SECONDS - 1)), None);
+        assert_eq!(resolve_auto_lock_timeout_on_battery(Some(AUTO_LOCK_MAX_SECONDS + 1)), None);
+    }
+
+    #[test]
+    fn test_resolve_notification_timeout() {
+        assert_eq!(resolve_notification_timeout(None), NOTIFICATION_TIMEOUT_MS);
+        assert_eq!(resolve_notification_timeout(Some(8000)), 8000);
+        assert_eq!(
+            resolve_notification_timeout(Some(NOTIFICATION_TIMEOUT_MIN_MS - 1)),
+            NOTIFICATION_TIMEOUT_MS
+        );
+        assert_eq!(
+            resolve_notification_timeout(Some(NOTIFICATION_TIMEOUT_MAX_MS + 1)),
+            NOTIFICATION_TIMEOUT_MS
+        );
+    }
+
+    #[test]
+    fn test_resolve_watchdog_timeout() {
+        assert_eq!(resolve_watchdog_timeout(None), WATCHDOG_TIMEOUT_DEFAULT_SECS);
+        assert_eq!(resolve_watchdog_timeout(Some(60)), 60);
+        assert_eq!(
+            resolve_watchdog_timeout(Some(WATCHDOG_TIMEOUT_MIN_SECS - 1)),
+            WATCHDOG_TIMEOUT_DEFAULT_SECS
+        );
+        assert_eq!(
+            resolve_watchdog_timeout(Some(WATCHDOG_TIMEOUT_MAX_SECS + 1)),
+            WATCHDOG_TIMEOUT_DEFAULT_SECS
+        );
+    }
+
+    #[test]
+    fn test_resolve_max_lock_duration() {
+        assert_eq!(resolve_max_lock_duration(None), MAX_LOCK_DURATION_DEFAULT_MINUTES);
+        assert_eq!(resolve_max_lock_duration(Some(120)), 120);
+        // The minimum is enforced - the hard cap can't be shortened below it or turned off
+        assert_eq!(resolve_max_lock_duration(Some(0)), MAX_LOCK_DURATION_MIN_MINUTES);
+        assert_eq!(
+            resolve_max_lock_duration(Some(MAX_LOCK_DURATION_MAX_MINUTES + 1)),
+            MAX_LOCK_DURATION_MAX_MINUTES
+        );
+    }
+
+    #[test]
+    fn test_resolve_lock_double_press_window() {
+        assert_eq!(resolve_lock_double_press_window(None), LOCK_DOUBLE_PRESS_DEFAULT_MS);
+        assert_eq!(resolve_lock_double_press_window(Some(800)), 800);
+        assert_eq!(
+            resolve_lock_double_press_window(Some(LOCK_DOUBLE_PRESS_MIN_MS - 1)),
+            LOCK_DOUBLE_PRESS_DEFAULT_MS
+        );
+        assert_eq!(
+            resolve_lock_double_press_window(Some(LOCK_DOUBLE_PRESS_MAX_MS + 1)),
+            LOCK_DOUBLE_PRESS_DEFAULT_MS
+        );
+    }
+
+    #[test]
+    fn test_resolve_config_path_precedence() {
+        let cli = PathBuf::from("/tmp/cli.toml");
+        let env_path = PathBuf::from("/tmp/env.toml");
+        let resolve = |cli_path: Option<&PathBuf>,
+                       cli_profile: Option<&str>,
+                       env_path: Option<&PathBuf>,
+                       env_profile: Option<&str>| {
+            resolve_config_path_internal(ConfigPathSources {
+                cli_path: cli_path.cloned(),
+                cli_profile: cli_profile.map(str::to_string),
+                env_path: env_path.cloned(),
+                env_profile: env_profile.map(str::to_string),
+            })
+            .unwrap()
+        };
+        let work = Config::profile_path("work").unwrap();
+        let home = Config::profile_path("home").unwrap();
+
+        // --config > --profile > HANDS_OFF_CONFIG > HANDS_OFF_PROFILE > default
+        assert_eq!(resolve(Some(&cli), Some("work"), Some(&env_path), Some("home")), cli);
+        assert_eq!(resolve(None, Some("work"), Some(&env_path), Some("home")), work);
+        assert_eq!(resolve(None, None, Some(&env_path), Some("home")), env_path);
+        assert_eq!(resolve(None, None, None, Some("home")), home);
+        assert_eq!(resolve(None, None, None, None), Config::config_path());
+        assert_eq!(resolve(None, Some("default"), None, None), Config::config_path());
+
+        let invalid = resolve_config_path_internal(ConfigPathSources {
+            cli_path: None,
+            cli_profile: Some("../work".to_string()),
+            env_path: None,
+            env_profile: None,
+        });
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        env::set_var("HANDS_OFF_PROFILE", "work");
+        assert_eq!(parse_profile().as_deref(), Some("work"));
+
+        env::set_var("HANDS_OFF_PROFILE", "");
+        assert_eq!(parse_profile(), None, "Empty value should be ignored");
+
+        env::remove_var("HANDS_OFF_PROFILE");
+        assert_eq!(parse_profile(), None);
+    }
+
+    #[test]
+    fn test_parse_config_path() {
+        env::set_var("HANDS_OFF_CONFIG", "/tmp/profile.toml");
+        assert_eq!(parse_config_path(), Some(PathBuf::from("/tmp/profile.toml")));
+
+        env::set_var("HANDS_OFF_CONFIG", "");
+        assert_eq!(parse_config_path(), None, "Empty value should be ignored");
+
+        env::remove_var("HANDS_OFF_CONFIG");
+        assert_eq!(parse_config_path(), None);
+    }
+
+    #[test]
+    fn test_parse_lock_scope() {
+        env::set_var("HANDS_OFF_LOCK_SCOPE", "keyboard");
+        assert_eq!(parse_lock_scope(), Some(LockScope::KeyboardOnly));
+
+        env::set_var("HANDS_OFF_LOCK_SCOPE", "MOUSE");
+        assert_eq!(parse_lock_scope(), Some(LockScope::MouseOnly));
+
+        env::set_var("HANDS_OFF_LOCK_SCOPE", "everything");
+        assert_eq!(parse_lock_scope(), None, "Should reject unknown scope");
+
+        env::remove_var("HANDS_OFF_LOCK_SCOPE");
+        assert_eq!(parse_lock_scope(), None);
+    }
+
+    #[test]
+    fn test_effective_settings_from_config_file() {
+        let mut cfg = Config::new("test_passphrase", 90, 300, Some("K".to_string()), None).unwrap();
+        cfg.lock_modifiers = Some("cmd+opt".to_string());
+        cfg.lock_scope = LockScope::KeyboardOnly;
+
+        let settings = resolve_effective_settings_internal(&cfg, &EnvOverrides::default());
+        assert_eq!(settings.auto_lock_timeout, 90);
+        assert_eq!(settings.auto_unlock_timeout, Some(300));
+        assert_eq!(settings.lock_hotkey, "Opt+Cmd+K");
+        assert_eq!(settings.talk_hotkey, "Ctrl+Cmd+Shift+T");
+        assert_eq!(settings.lock_scope, LockScope::KeyboardOnly);
+        assert_eq!(settings.buffer_reset_timeout, BUFFER_RESET_DEFAULT_SECONDS);
+        assert!(settings.passphrase_set);
+    }
+
+    #[test]
+    fn test_effective_settings_env_overrides_config_file() {
+        let mut cfg = Config::new("test_passphrase", 90, 300, Some("K".to_string()), None).unwrap();
+        cfg.buffer_reset_timeout = Some(5);
+        let env = EnvOverrides {
+            auto_lock: Some(45),
+            auto_unlock: Some(120),
+            lock_hotkey: Some("J".to_string()),
+            talk_hotkey: Some("Y".to_string()),
+            buffer_reset: Some(8),
+            lock_scope: Some(LockScope::MouseOnly),
+        };
+
+        let settings = resolve_effective_settings_internal(&cfg, &env);
+        assert_eq!(settings.auto_lock_timeout, 45);
+        assert_eq!(settings.auto_unlock_timeout, Some(120));
+        assert_eq!(settings.lock_hotkey, "Ctrl+Cmd+Shift+J");
+        assert_eq!(settings.talk_hotkey, "Ctrl+Cmd+Shift+Y");
+        assert_eq!(settings.buffer_reset_timeout, 8);
+        assert_eq!(settings.lock_scope, LockScope::MouseOnly);
+    }
+
+    #[test]
+    fn test_effective_settings_show_disabled_hotkeys() {
+        let cfg = Config::new("test_passphrase", 90, 0, Some("none".to_string()), None).unwrap();
+        let settings = resolve_effective_settings_internal(&cfg, &EnvOverrides::default());
+        assert_eq!(settings.lock_hotkey, "disabled");
+        assert_eq!(settings.talk_hotkey, "Ctrl+Cmd+Shift+T");
+
+        let env = EnvOverrides {
+            talk_hotkey: Some("NONE".to_string()),
+            ..EnvOverrides::default()
+        };
+        let settings = resolve_effective_settings_internal(&cfg, &env);
+        assert_eq!(settings.talk_hotkey, "disabled");
+    }
+
+    #[test]
+    fn test_effective_settings_partial_env_and_passphrase_state() {
+        let mut cfg = Config::new("test_passphrase", 120, 0, None, Some("M".to_string())).unwrap();
+        cfg.buffer_reset_timeout = Some(6);
+        cfg.encrypted_passphrase.clear();
+        let env = EnvOverrides {
+            lock_hotkey: Some("P".to_string()),
+            ..EnvOverrides::default()
+        };
+
+        let settings = resolve_effective_settings_internal(&cfg, &env);
+        assert_eq!(settings.auto_lock_timeout, 120);
+        assert_eq!(settings.lock_hotkey, "Ctrl+Cmd+Shift+P");
+        assert_eq!(settings.talk_hotkey, "Ctrl+Cmd+Shift+M");
+        assert_eq!(settings.buffer_reset_timeout, 6);
+        assert!(!settings.passphrase_set);
+        assert!(settings.to_string().contains("Passphrase:    not set"));
+
+        // A Keychain-backed passphrase leaves the config field empty
+        cfg.use_keychain = true;
+        let settings = resolve_effective_settings_internal(&cfg, &env);
+        assert!(settings.passphrase_set);
+        assert!(settings.to_string().contains("Passphrase:    set"));
+    }
 }
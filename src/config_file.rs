@@ -3,13 +3,29 @@
 //! This module handles loading and saving the application configuration file,
 //! which includes the encrypted passphrase and timeout settings.
 
-use crate::constants::{CONFIG_FILE_PERMISSIONS, CONFIG_PERMISSION_MASK_GROUP_OTHER};
+use crate::app_state::{LockScope, PermissionLossPolicy, TalkMode};
+use crate::auth::keychain;
+use crate::constants::{
+    AUTO_LOCK_DEFAULT_SECONDS, AUTO_LOCK_MAX_SECONDS, AUTO_LOCK_MIN_SECONDS,
+    AUTO_UNLOCK_MAX_SECONDS, AUTO_UNLOCK_MIN_SECONDS, CONFIG_FILE_PERMISSIONS,
+    CONFIG_PERMISSION_MASK_GROUP_OTHER, HOLD_UNLOCK_SECS_DEFAULT, HOLD_UNLOCK_SECS_MAX,
+    HOLD_UNLOCK_SECS_MIN, MAX_BUFFER_LEN_DEFAULT, SPACEBAR_KEYCODE, UNLOCK_CHORD_PRESSES_DEFAULT,
+    UNLOCK_CHORD_PRESSES_MAX, UNLOCK_CHORD_PRESSES_MIN,
+};
 use crate::crypto;
+use crate::input_blocking::chord::UnlockChord;
+use crate::input_blocking::hold_unlock::{hold_key_keycode, HoldUnlock};
+use crate::schedule::Schedule;
+use crate::utils::modifiers::HotkeyModifiers;
+use crate::utils::keycode::{code_to_keycode, KeyboardLayout};
 use anyhow::{anyhow, Context, Result};
 use global_hotkey::hotkey::Code;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use zeroize::Zeroizing;
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
@@ -17,18 +33,228 @@ use std::os::unix::fs::PermissionsExt;
 /// Application configuration stored in config.toml
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
-    /// Base64-encoded AES-256-GCM encrypted passphrase
+    /// Schema version of the file (missing in files written before versioning = 1)
+    #[serde(default = "default_config_version")]
+    pub version: u32,
+    /// Base64-encoded AES-256-GCM encrypted passphrase (empty when stored in Keychain)
+    #[serde(default)]
     pub encrypted_passphrase: String,
     /// Auto-lock timeout in seconds (default: 120)
     pub auto_lock_timeout: u64,
     /// Auto-unlock timeout in seconds (default: 0/disabled in Release, 60 in Debug)
     pub auto_unlock_timeout: u64,
-    /// Lock hotkey last key (A-Z, default: L)
+    /// Lock hotkey last key (A-Z, or "none" to disable; default: L)
     #[serde(default)]
     pub lock_hotkey: Option<String>,
-    /// Talk hotkey last key (A-Z, default: T)
+    /// Talk hotkey last key (A-Z, or "none" to disable; default: T)
     #[serde(default)]
     pub talk_hotkey: Option<String>,
+    /// Lock hotkey modifiers, e.g. "cmd+opt+ctrl" (default: ctrl+cmd+shift)
+    #[serde(default)]
+    pub lock_modifiers: Option<String>,
+    /// Talk hotkey modifiers, e.g. "cmd+opt+ctrl" (default: ctrl+cmd+shift)
+    #[serde(default)]
+    pub talk_modifiers: Option<String>,
+    /// Require pressing the lock hotkey twice in quick succession to lock (default: false)
+    #[serde(default)]
+    pub lock_double_press: bool,
+    /// Window for the second lock hotkey press in milliseconds (200-1500, default: 500)
+    #[serde(default)]
+    pub lock_double_press_window_ms: Option<u64>,
+    /// Keyboard layout the passphrase is typed with (us, dvorak, colemak; default: us)
+    #[serde(default)]
+    pub keyboard_layout: KeyboardLayout,
+    /// Passphrase buffer reset timeout in seconds (2-15, default: 3)
+    #[serde(default)]
+    pub buffer_reset_timeout: Option<u64>,
+    /// Which input devices are blocked while locked (all, keyboard_only, mouse_only)
+    #[serde(default)]
+    pub lock_scope: LockScope,
+    /// Block media/volume/brightness keys while locked (default: true)
+    #[serde(default = "default_block_media_keys")]
+    pub block_media_keys: bool,
+    /// Block the power / Touch ID key while locked, as far as macOS allows (default: true)
+    #[serde(default = "default_block_power_key")]
+    pub block_power_key: bool,
+    /// Touch ID unlock hotkey last key (A-Z, default: U)
+    #[serde(default)]
+    pub unlock_hotkey: Option<String>,
+    /// Emergency unlock hotkey last key (A-Z, default: None = disabled)
+    ///
+    /// Unlocks without the passphrase - intended for "I'm locked out" emergencies.
+    #[serde(default)]
+    pub panic_hotkey: Option<String>,
+    /// Emergency unlock hotkey modifiers (default: ctrl+opt+cmd+shift)
+    #[serde(default)]
+    pub panic_modifiers: Option<String>,
+    /// Require Touch ID before the emergency unlock hotkey unlocks (default: false)
+    #[serde(default)]
+    pub panic_requires_touchid: bool,
+    /// Modifier chord that unlocks when pressed a few times in quick succession, e.g.
+    /// "cmd+shift" (at least two modifiers; default: None = disabled)
+    ///
+    /// Much weaker than the passphrase, which keeps working alongside it.
+    #[serde(default)]
+    pub unlock_chord: Option<String>,
+    /// Presses of the unlock chord that unlock (2-6, default: 3)
+    #[serde(default)]
+    pub unlock_chord_presses: Option<u32>,
+    /// Unlock by holding hold_to_unlock_key for hold_to_unlock_secs (default: false)
+    ///
+    /// Much weaker than the passphrase, which keeps working alongside it.
+    #[serde(default)]
+    pub hold_to_unlock: bool,
+    /// Key held to unlock: "escape" or "f1"-"f12" (default: "escape")
+    #[serde(default)]
+    pub hold_to_unlock_key: Option<String>,
+    /// Seconds the hold-to-unlock key has to be held (2-10, default: 3)
+    #[serde(default)]
+    pub hold_to_unlock_secs: Option<u64>,
+    /// Unlock only after the passphrase AND a Touch ID confirmation (default: false)
+    #[serde(default)]
+    pub two_factor_unlock: bool,
+    /// SHA-256 hash of the one-time emergency recovery code (None = no code or already used)
+    #[serde(default)]
+    pub recovery_code_hash: Option<String>,
+    /// Store the passphrase hash in the macOS Keychain instead of this file (default: false)
+    #[serde(default)]
+    pub use_keychain: bool,
+    /// Bundle identifiers of apps that stay interactive while locked (e.g. "org.videolan.vlc")
+    #[serde(default)]
+    pub app_allowlist: Vec<String>,
+    /// Key passed through while the Talk hotkey is held ("space" or A-Z, default: space)
+    #[serde(default)]
+    pub talk_passthrough_key: Option<String>,
+    /// Talk hotkey behaviour: "hold" (default) or "toggle" (each press latches on/off)
+    #[serde(default)]
+    pub talk_mode: TalkMode,
+    /// When accessibility permissions are lost: "unlock" (default), "exit" or "retry"
+    #[serde(default)]
+    pub on_permission_loss: PermissionLossPolicy,
+    /// Listen on a Unix domain control socket for scripting (default: false)
+    #[serde(default)]
+    pub control_socket: bool,
+    /// Serve the HTTP control API on 127.0.0.1 at this port (None = off, the default)
+    #[serde(default)]
+    pub http_api_port: Option<u16>,
+    /// Bearer token the HTTP API requires (at least 16 characters)
+    #[serde(default)]
+    pub http_api_token: Option<String>,
+    /// Seconds before auto-lock to show a warning (0-15, 0 = no warning, default: 10)
+    #[serde(default)]
+    pub auto_lock_warning: Option<u64>,
+    /// Don't auto-lock while an app keeps the display awake for media playback (default: false)
+    #[serde(default)]
+    pub respect_media_playback: bool,
+    /// Only clicks, scrolls and keys reset the auto-lock timer, not mouse moves or drags (default: false)
+    #[serde(default)]
+    pub auto_lock_ignore_mouse_move: bool,
+    /// Auto-lock timeout in seconds while on battery power (20-600, default: None = auto_lock_timeout)
+    #[serde(default)]
+    pub auto_lock_timeout_on_battery: Option<u64>,
+    /// Only auto-lock on battery power, never while plugged in (default: false)
+    #[serde(default)]
+    pub auto_lock_only_on_battery: bool,
+    /// Input events needed within activity_debounce_window_ms to reset the auto-lock timer (0-20, default: 0 = off)
+    #[serde(default)]
+    pub activity_debounce_events: Option<u32>,
+    /// Window for activity_debounce_events in milliseconds (100-10000, default: 1000)
+    #[serde(default)]
+    pub activity_debounce_window_ms: Option<u64>,
+    /// Seconds after unlocking during which auto-lock cannot re-engage (0-120, default: 10)
+    #[serde(default)]
+    pub unlock_grace_secs: Option<u64>,
+    /// Seconds the event loop may stall while locked before a forced unlock (10-300, default: 30)
+    #[serde(default)]
+    pub watchdog_timeout_secs: Option<u64>,
+    /// Minutes after which input is force-unlocked, even with auto-unlock off (15-720, default: 60)
+    #[serde(default)]
+    pub max_lock_duration_mins: Option<u64>,
+    /// Show a click-through "locked" overlay with the auto-unlock countdown (tray app, default: false)
+    #[serde(default)]
+    pub lock_overlay: bool,
+    /// Show the auto-lock/auto-unlock countdown next to the tray icon (tray app, default: false)
+    #[serde(default)]
+    pub menu_bar_countdown: bool,
+    /// Beep when a passphrase attempt times out without unlocking (default: false)
+    #[serde(default)]
+    pub feedback_sound: bool,
+    /// Ignore typing for a few seconds after a full-length wrong attempt (default: true)
+    #[serde(default = "default_attempt_cooldown")]
+    pub attempt_cooldown: bool,
+    /// Discard the passphrase buffer as soon as it grows longer than the passphrase,
+    /// instead of waiting for the buffer reset timeout (default: false; only used with
+    /// `attempt_cooldown = false`)
+    #[serde(default)]
+    pub clear_overlong_attempts: bool,
+    /// Ask for confirmation before the tray's Disable menu item disables HandsOff (default: false)
+    #[serde(default)]
+    pub confirm_disable: bool,
+    /// Ask for confirmation before the tray's Reset menu item runs (default: false)
+    #[serde(default)]
+    pub confirm_reset: bool,
+    /// Lock input when the Mac wakes from sleep (tray app, default: false)
+    #[serde(default)]
+    pub lock_on_wake: bool,
+    /// Lock input when the screen saver starts or the display turns off (tray app, default: false)
+    #[serde(default)]
+    pub lock_on_screensaver: bool,
+    /// Bundle identifiers of apps that lock input when they become frontmost (tray app)
+    #[serde(default)]
+    pub lock_on_app: Vec<String>,
+    /// Show desktop notifications for state changes (emergency unlocks are always shown, default: true)
+    #[serde(default = "default_notifications_enabled")]
+    pub notifications_enabled: bool,
+    /// How long notifications stay on screen in milliseconds (1000-30000, default: 3000)
+    #[serde(default)]
+    pub notification_timeout_ms: Option<u32>,
+    /// Time-of-day windows during which input is locked automatically
+    #[serde(default, skip_serializing_if = "Schedule::is_empty")]
+    pub schedule: Schedule,
+    /// Replacement notification/tooltip texts keyed by message name (see `strings`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub messages: BTreeMap<String, String>,
+}
+
+/// Config value selecting the spacebar as the Talk passthrough key
+pub const TALK_PASSTHROUGH_SPACE: &str = "space";
+
+/// Config file keys left out of `handsoff config export` (secrets bound to this machine)
+pub const EXPORT_EXCLUDED_KEYS: [&str; 2] = ["encrypted_passphrase", "recovery_code_hash"];
+
+/// Default `hold_to_unlock_key`
+pub const HOLD_UNLOCK_KEY_DEFAULT: &str = "escape";
+
+/// Config value for `lock_hotkey` / `talk_hotkey` that disables the hotkey
+pub const HOTKEY_DISABLED: &str = "none";
+
+/// Profile used when none is selected (stored as plain `config.toml`)
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Current config file schema version
+///
+/// Bump when fields are added or renamed, and add the matching step to
+/// `Config::migrate`.
+pub const CONFIG_VERSION: u32 = 2;
+
+fn default_config_version() -> u32 {
+    1
+}
+
+fn default_block_media_keys() -> bool {
+    true
+}
+
+fn default_block_power_key() -> bool {
+    true
+}
+
+fn default_notifications_enabled() -> bool {
+    true
+}
+
+fn default_attempt_cooldown() -> bool {
+    true
 }
 
 impl Config {
@@ -39,8 +265,8 @@ impl Config {
     /// * `plaintext_passphrase` - The passphrase to encrypt and store
     /// * `auto_lock` - Auto-lock timeout in seconds
     /// * `auto_unlock` - Auto-unlock timeout in seconds
-    /// * `lock_key` - Optional lock hotkey (A-Z), defaults to None (which becomes L)
-    /// * `talk_key` - Optional talk hotkey (A-Z), defaults to None (which becomes T)
+    /// * `lock_key` - Optional lock hotkey (A-Z or "none"), defaults to None (which becomes L)
+    /// * `talk_key` - Optional talk hotkey (A-Z or "none"), defaults to None (which becomes T)
     pub fn new(
         plaintext_passphrase: &str,
         auto_lock: u64,
@@ -48,30 +274,81 @@ impl Config {
         lock_key: Option<String>,
         talk_key: Option<String>,
     ) -> Result<Self> {
+        Self::validate_passphrase(plaintext_passphrase)?;
+        Self::validate_auto_lock_timeout(auto_lock)?;
+        Self::validate_auto_unlock_timeout(auto_unlock)?;
         let encrypted_passphrase = crypto::encrypt_passphrase(plaintext_passphrase)
             .context("Failed to encrypt passphrase")?;
 
         // Validate hotkeys if provided
         if let Some(ref key) = lock_key {
-            Self::validate_hotkey(key)?;
+            Self::validate_lock_talk_hotkey(key)?;
         }
         if let Some(ref key) = talk_key {
-            Self::validate_hotkey(key)?;
+            Self::validate_lock_talk_hotkey(key)?;
         }
 
-        // Validate that lock and talk keys are different
-        if let (Some(ref lock), Some(ref talk)) = (&lock_key, &talk_key) {
-            if lock.to_uppercase() == talk.to_uppercase() {
-                return Err(anyhow!("Lock and Talk hotkeys must be different (both set to '{}')", lock));
-            }
-        }
+        Self::validate_distinct_hotkeys(lock_key.as_deref(), talk_key.as_deref())?;
 
         Ok(Self {
+            version: CONFIG_VERSION,
             encrypted_passphrase,
             auto_lock_timeout: auto_lock,
             auto_unlock_timeout: auto_unlock,
             lock_hotkey: lock_key,
             talk_hotkey: talk_key,
+            lock_modifiers: None,
+            talk_modifiers: None,
+            lock_double_press: false,
+            lock_double_press_window_ms: None,
+            keyboard_layout: KeyboardLayout::Us,
+            buffer_reset_timeout: None,
+            lock_scope: LockScope::All,
+            block_media_keys: true,
+            block_power_key: true,
+            unlock_hotkey: None,
+            panic_hotkey: None,
+            panic_modifiers: None,
+            unlock_chord: None,
+            unlock_chord_presses: None,
+            hold_to_unlock: false,
+            hold_to_unlock_key: None,
+            hold_to_unlock_secs: None,
+            panic_requires_touchid: false,
+            two_factor_unlock: false,
+            recovery_code_hash: None,
+            use_keychain: false,
+            app_allowlist: Vec::new(),
+            talk_passthrough_key: None,
+            talk_mode: TalkMode::Hold,
+            on_permission_loss: PermissionLossPolicy::Unlock,
+            control_socket: false,
+            http_api_port: None,
+            http_api_token: None,
+            auto_lock_warning: None,
+            respect_media_playback: false,
+            auto_lock_ignore_mouse_move: false,
+            auto_lock_timeout_on_battery: None,
+            auto_lock_only_on_battery: false,
+            activity_debounce_events: None,
+            activity_debounce_window_ms: None,
+            unlock_grace_secs: None,
+            watchdog_timeout_secs: None,
+            max_lock_duration_mins: None,
+            lock_overlay: false,
+            menu_bar_countdown: false,
+            feedback_sound: false,
+            attempt_cooldown: true,
+            clear_overlong_attempts: false,
+            confirm_disable: false,
+            confirm_reset: false,
+            lock_on_wake: false,
+            lock_on_screensaver: false,
+            lock_on_app: Vec::new(),
+            notifications_enabled: true,
+            notification_timeout_ms: None,
+            schedule: Schedule::default(),
+            messages: BTreeMap::new(),
         })
     }
 
@@ -88,6 +365,44 @@ impl Config {
         config_dir.join("config.toml")
     }
 
+    /// Get the config file path for a named profile
+    ///
+    /// The default profile is `config.toml`; any other profile is `config-<name>.toml`
+    /// in the same directory.
+    pub fn profile_path(name: &str) -> Result<PathBuf> {
+        let config_path = Self::config_path();
+        let dir = config_path.parent().unwrap_or(Path::new("."));
+        Self::profile_path_in(dir, name)
+    }
+
+    /// Get the config file path for a named profile in `dir`
+    ///
+    /// Profile names are limited to letters, digits, '-' and '_', so a name can't
+    /// point outside the config directory.
+    pub fn profile_path_in(dir: &Path, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            anyhow::bail!(
+                "Invalid profile name '{}': use letters, digits, '-' and '_' only",
+                name
+            );
+        }
+        let file_name = if name == DEFAULT_PROFILE {
+            "config.toml".to_string()
+        } else {
+            format!("config-{}.toml", name)
+        };
+        Ok(dir.join(file_name))
+    }
+
+    /// Load a named profile's config from the standard location (see `profile_path`)
+    pub fn load_profile(name: &str) -> Result<Self> {
+        Self::load_from_path(&Self::profile_path(name)?)
+    }
+
     /// Load config from standard location
     ///
     /// # Errors
@@ -141,23 +456,89 @@ impl Config {
         // Read and parse config file
         let contents = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let mut config = Self::from_toml_str(&contents)?;
+        config.reencrypt_legacy_passphrase(path);
+        Ok(config)
+    }
+
+    /// Re-encrypt a passphrase written with the legacy static key under this machine's
+    /// key and save the file, so it no longer decrypts with a key derivable from the source
+    ///
+    /// Failures are only logged: the legacy passphrase still decrypts, so loading goes on.
+    fn reencrypt_legacy_passphrase(&mut self, path: &Path) {
+        if self.encrypted_passphrase.is_empty() {
+            return; // Stored in Keychain
+        }
+        match crypto::reencrypt_if_legacy(&self.encrypted_passphrase) {
+            Ok(None) => {}
+            Ok(Some(reencrypted)) => {
+                self.encrypted_passphrase = reencrypted;
+                match self.save_to_path(path) {
+                    Ok(()) => log::info!("Passphrase re-encrypted with this machine's key"),
+                    Err(e) => log::warn!(
+                        "Failed to save the passphrase re-encrypted with this machine's key: {:#}",
+                        e
+                    ),
+                }
+            }
+            // Reported when the passphrase is used
+            Err(e) => log::debug!("Passphrase not checked for legacy encryption: {:#}", e),
+        }
+    }
 
-        let config: Config = toml::from_str(&contents).context("Failed to parse config file")?;
+    /// Parse and validate config file contents (migrating older versions)
+    pub fn from_toml_str(contents: &str) -> Result<Self> {
+        let table: toml::Table = toml::from_str(contents).context("Failed to parse config file")?;
+        let config: Config = toml::Value::Table(Config::migrate(table)?)
+            .try_into()
+            .context("Failed to parse config file")?;
 
         // Validate loaded config
         // 1. Validate hotkey format if provided
         if let Some(ref key) = config.lock_hotkey {
-            Config::validate_hotkey(key)
+            Config::validate_lock_talk_hotkey(key)
                 .with_context(|| format!("Invalid lock_hotkey in config file: '{}'", key))?;
         }
         if let Some(ref key) = config.talk_hotkey {
-            Config::validate_hotkey(key)
+            Config::validate_lock_talk_hotkey(key)
                 .with_context(|| format!("Invalid talk_hotkey in config file: '{}'", key))?;
         }
+        if let Some(ref key) = config.unlock_hotkey {
+            Config::validate_hotkey(key)
+                .with_context(|| format!("Invalid unlock_hotkey in config file: '{}'", key))?;
+        }
+        if let Some(ref key) = config.panic_hotkey {
+            Config::validate_hotkey(key)
+                .with_context(|| format!("Invalid panic_hotkey in config file: '{}'", key))?;
+        }
+
+        if let Some(ref key) = config.talk_passthrough_key {
+            Config::validate_talk_passthrough_key(key).with_context(|| {
+                format!("Invalid talk_passthrough_key in config file: '{}'", key)
+            })?;
+        }
+
+        // Hotkey modifiers must parse and include at least one modifier
+        config
+            .get_lock_modifiers()
+            .context("Invalid lock_modifiers in config file")?;
+        config
+            .get_talk_modifiers()
+            .context("Invalid talk_modifiers in config file")?;
+        config
+            .get_panic_modifiers()
+            .context("Invalid panic_modifiers in config file")?;
+        config
+            .get_unlock_chord()
+            .context("Invalid unlock_chord in config file")?;
+        config
+            .get_hold_unlock()
+            .context("Invalid hold_to_unlock settings in config file")?;
 
         // 2. Validate that lock and talk keys are different
+        //    (two disabled hotkeys don't collide)
         if let (Some(ref lock), Some(ref talk)) = (&config.lock_hotkey, &config.talk_hotkey) {
-            if lock.to_uppercase() == talk.to_uppercase() {
+            if lock.to_uppercase() == talk.to_uppercase() && !Config::is_hotkey_disabled(lock) {
                 anyhow::bail!(
                     "Invalid config: Lock and Talk hotkeys must be different (both set to '{}'). Please run 'handsoff --setup' to reconfigure.",
                     lock
@@ -165,34 +546,155 @@ impl Config {
             }
         }
 
+        // 3. Validate that the Talk passthrough key doesn't collide with the hotkey letters
+        config.validate_talk_passthrough_conflicts()?;
+
+        // 4. Validate schedule windows
+        config
+            .schedule
+            .parse_windows()
+            .context("Invalid [schedule] in config file")?;
+
+        // 5. Validate timeout ranges
+        config.validate_timeouts()?;
+
         Ok(config)
     }
 
+    /// Settings for `handsoff config export`: the config file without its secrets
+    ///
+    /// The encrypted passphrase only decrypts on the machine that wrote it, so it (and
+    /// the recovery code hash) is left out; importing asks for a fresh passphrase.
+    pub fn export_table(&self) -> Result<toml::Table> {
+        let exported = Self {
+            version: CONFIG_VERSION,
+            ..self.clone()
+        };
+        let mut table = toml::Table::try_from(&exported).context("Failed to serialize config")?;
+        for key in EXPORT_EXCLUDED_KEYS {
+            table.remove(key);
+        }
+        Ok(table)
+    }
+
+    /// Merge exported settings (see `export_table`) into this config, with a new passphrase
+    ///
+    /// Settings in `imported` replace the ones here; settings it lacks are kept. Secrets
+    /// in `imported` are ignored. The result is validated like a loaded config file.
+    pub fn merge_imported(&self, imported: toml::Table, plaintext_passphrase: &str) -> Result<Self> {
+        let mut imported = Self::migrate(imported).context("Failed to migrate imported settings")?;
+        for key in EXPORT_EXCLUDED_KEYS {
+            imported.remove(key);
+        }
+
+        let base = Self {
+            version: CONFIG_VERSION,
+            encrypted_passphrase: crypto::encrypt_passphrase(plaintext_passphrase)
+                .context("Failed to encrypt passphrase")?,
+            ..self.clone()
+        };
+        let mut table = toml::Table::try_from(&base).context("Failed to serialize config")?;
+        table.extend(imported);
+
+        let contents = toml::to_string(&table).context("Failed to serialize config")?;
+        Self::from_toml_str(&contents).context("Invalid imported settings")
+    }
+
+    /// Upgrade a parsed config file to CONFIG_VERSION, one version at a time
+    ///
+    /// Files without a `version` field are version 1. Fails for files written by a
+    /// newer HandsOff, rather than silently dropping settings it doesn't know.
+    pub fn migrate(mut table: toml::Table) -> Result<toml::Table> {
+        let version = match table.get("version") {
+            None => 1,
+            Some(value) => value
+                .as_integer()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| anyhow!("Invalid config version: {}", value))?,
+        };
+        if version > CONFIG_VERSION {
+            anyhow::bail!(
+                "Config file version {} is newer than this HandsOff supports ({}). Please update HandsOff.",
+                version,
+                CONFIG_VERSION
+            );
+        }
+
+        if version < 2 {
+            Self::migrate_v1_to_v2(&mut table)?;
+        }
+
+        if version < CONFIG_VERSION {
+            log::info!(
+                "Migrated config file from version {} to {} (saved on next write)",
+                version,
+                CONFIG_VERSION
+            );
+        }
+        table.insert("version".to_string(), toml::Value::Integer(CONFIG_VERSION.into()));
+        Ok(table)
+    }
+
+    /// v1 -> v2: fill in the timeouts hand-written v1 files could leave out and
+    /// normalize the short `lock_scope` names ("keyboard", "mouse") to the saved form
+    fn migrate_v1_to_v2(table: &mut toml::Table) -> Result<()> {
+        table
+            .entry("auto_lock_timeout")
+            .or_insert(toml::Value::Integer(AUTO_LOCK_DEFAULT_SECONDS as i64));
+        table
+            .entry("auto_unlock_timeout")
+            .or_insert(toml::Value::Integer(0));
+
+        if let Some(scope) = table.get("lock_scope").and_then(toml::Value::as_str) {
+            let scope: LockScope = scope.parse().context("Invalid lock_scope in config file")?;
+            table.insert("lock_scope".to_string(), toml::Value::try_from(scope)?);
+        }
+        Ok(())
+    }
+
     /// Save config to standard location
     ///
     /// Creates the config directory if it doesn't exist.
     /// Sets file permissions to 600 (user read/write only).
+    /// When `use_keychain` is enabled, the passphrase hash is moved into Keychain
+    /// and `encrypted_passphrase` is written out empty.
     pub fn save(&self) -> Result<()> {
-        let path = Self::config_path();
+        self.save_to_path(&Self::config_path())
+    }
 
+    /// Save config to a specific path (see `save` for details)
+    pub fn save_to_path(&self, path: &Path) -> Result<()> {
         // Create config directory if it doesn't exist
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
-        // Serialize to TOML
-        let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        // Serialize to TOML at the current schema version (keeping the passphrase out
+        // of the file when using Keychain)
+        let mut file_config = Self {
+            version: CONFIG_VERSION,
+            ..self.clone()
+        };
+        if self.use_keychain && !self.encrypted_passphrase.is_empty() {
+            let passphrase = self.get_passphrase()?;
+            keychain::store_passphrase_hash(&crate::auth::hash_passphrase(&passphrase))?;
+            keychain::store_passphrase_len(passphrase.chars().count())?;
+            log::info!("Passphrase hash stored in Keychain");
+            file_config.encrypted_passphrase = String::new();
+        }
+        let contents =
+            toml::to_string_pretty(&file_config).context("Failed to serialize config")?;
 
         // Write to file
-        fs::write(&path, contents)
+        fs::write(path, contents)
             .with_context(|| format!("Failed to write config file: {}", path.display()))?;
 
         // Set permissions (user read/write only)
         #[cfg(unix)]
         {
-            let mut permissions = fs::metadata(&path)?.permissions();
+            let mut permissions = fs::metadata(path)?.permissions();
             permissions.set_mode(CONFIG_FILE_PERMISSIONS);
-            fs::set_permissions(&path, permissions)
+            fs::set_permissions(path, permissions)
                 .context("Failed to set config file permissions")?;
         }
 
@@ -201,25 +703,334 @@ impl Config {
     }
 
     /// Decrypt and return the plaintext passphrase
-    pub fn get_passphrase(&self) -> Result<String> {
+    ///
+    /// Not available when the passphrase lives in Keychain (only its hash is stored there);
+    /// use `get_passphrase_hash` instead.
+    pub fn get_passphrase(&self) -> Result<Zeroizing<String>> {
+        if self.encrypted_passphrase.is_empty() {
+            anyhow::bail!("No encrypted passphrase in config file (passphrase is stored in Keychain)");
+        }
         crypto::decrypt_passphrase(&self.encrypted_passphrase)
             .context("Failed to decrypt passphrase")
     }
 
-    /// Get the lock hotkey Code, defaulting to KeyL if not configured
-    pub fn get_lock_key_code(&self) -> Result<Code> {
+    /// Number of characters in the passphrase, read from Keychain alongside the hash when
+    /// `use_keychain` is enabled (None when it is unknown)
+    pub fn get_passphrase_len(&self) -> Option<usize> {
+        if let Ok(passphrase) = self.get_passphrase() {
+            return Some(passphrase.chars().count());
+        }
+        if !self.use_keychain {
+            return None;
+        }
+        keychain::retrieve_passphrase_len().unwrap_or_else(|e| {
+            log::warn!("Failed to read passphrase length from Keychain: {:#}", e);
+            None
+        })
+    }
+
+    /// Return the passphrase hash used to verify unlock attempts
+    ///
+    /// With `use_keychain` enabled the hash is read from Keychain, falling back to the
+    /// encrypted passphrase in the config file when the Keychain entry is missing.
+    pub fn get_passphrase_hash(&self) -> Result<String> {
+        if self.use_keychain {
+            match keychain::retrieve_passphrase_hash() {
+                Ok(Some(hash)) => return Ok(hash),
+                Ok(None) => log::warn!(
+                    "use_keychain is enabled but no Keychain entry was found - falling back to config file"
+                ),
+                Err(e) => log::warn!(
+                    "Failed to read passphrase hash from Keychain, falling back to config file: {}",
+                    e
+                ),
+            }
+        }
+
+        let passphrase = self.get_passphrase().context(
+            "Passphrase not found in Keychain or config file. Run 'handsoff --setup' to reconfigure.",
+        )?;
+        Ok(crate::auth::hash_passphrase(&passphrase))
+    }
+
+    /// Check a typed passphrase against the stored one (Keychain or config file)
+    ///
+    /// Errors only if no passphrase can be loaded; a wrong passphrase is `Ok(false)`.
+    pub fn verify_passphrase(&self, passphrase: &str) -> Result<bool> {
+        let hash = self.get_passphrase_hash()?;
+        Ok(crate::auth::verify_passphrase(passphrase, &hash))
+    }
+
+    /// Replace the passphrase (encrypted here; moved into Keychain on save when `use_keychain` is set)
+    pub fn set_passphrase(&mut self, plaintext_passphrase: &str) -> Result<()> {
+        Self::validate_passphrase(plaintext_passphrase)?;
+        self.encrypted_passphrase = crypto::encrypt_passphrase(plaintext_passphrase)
+            .context("Failed to encrypt passphrase")?;
+        Ok(())
+    }
+
+    /// Change the passphrase in the saved config file, keeping all other settings
+    pub fn persist_passphrase_change(path: &Path, plaintext_passphrase: &str) -> Result<()> {
+        let mut config =
+            Self::load_from_path(path).context("Failed to reload config to change passphrase")?;
+        config.set_passphrase(plaintext_passphrase)?;
+        config
+            .save_to_path(path)
+            .context("Failed to save config after changing passphrase")
+    }
+
+    /// Store the hash of a newly generated recovery code (the plaintext is never saved)
+    pub fn set_recovery_code(&mut self, code: &str) {
+        self.recovery_code_hash = Some(crate::auth::hash_passphrase(code));
+    }
+
+    /// Invalidate the recovery code after it has been used
+    pub fn clear_recovery_code(&mut self) {
+        self.recovery_code_hash = None;
+    }
+
+    /// Clear the recovery code in the saved config file (called after the code was used)
+    pub fn persist_recovery_code_consumed(path: &Path) -> Result<()> {
+        let mut config =
+            Self::load_from_path(path).context("Failed to reload config to clear recovery code")?;
+        config.clear_recovery_code();
+        config
+            .save_to_path(path)
+            .context("Failed to save config after clearing recovery code")
+    }
+
+    /// Get the lock hotkey Code, defaulting to KeyL if not configured (None = disabled)
+    pub fn get_lock_key_code(&self) -> Result<Option<Code>> {
         self.lock_hotkey
             .as_ref()
-            .map(|s| Self::parse_key_string(s))
-            .unwrap_or(Ok(Code::KeyL))
+            .map(|s| Self::parse_lock_talk_hotkey(s))
+            .unwrap_or(Ok(Some(Code::KeyL)))
     }
 
-    /// Get the talk hotkey Code, defaulting to KeyT if not configured
-    pub fn get_talk_key_code(&self) -> Result<Code> {
+    /// Get the talk hotkey Code, defaulting to KeyT if not configured (None = disabled)
+    pub fn get_talk_key_code(&self) -> Result<Option<Code>> {
         self.talk_hotkey
+            .as_ref()
+            .map(|s| Self::parse_lock_talk_hotkey(s))
+            .unwrap_or(Ok(Some(Code::KeyT)))
+    }
+
+    /// Get the lock hotkey modifiers, defaulting to Ctrl+Cmd+Shift if not configured
+    pub fn get_lock_modifiers(&self) -> Result<HotkeyModifiers> {
+        self.lock_modifiers
+            .as_deref()
+            .map(str::parse)
+            .unwrap_or(Ok(HotkeyModifiers::DEFAULT))
+    }
+
+    /// Get the talk hotkey modifiers, defaulting to Ctrl+Cmd+Shift if not configured
+    pub fn get_talk_modifiers(&self) -> Result<HotkeyModifiers> {
+        self.talk_modifiers
+            .as_deref()
+            .map(str::parse)
+            .unwrap_or(Ok(HotkeyModifiers::DEFAULT))
+    }
+
+    /// Get the Touch ID unlock hotkey Code, defaulting to KeyU if not configured
+    pub fn get_unlock_key_code(&self) -> Result<Code> {
+        self.unlock_hotkey
             .as_ref()
             .map(|s| Self::parse_key_string(s))
-            .unwrap_or(Ok(Code::KeyT))
+            .unwrap_or(Ok(Code::KeyU))
+    }
+
+    /// Get the emergency unlock hotkey Code (None when the panic hotkey is disabled)
+    pub fn get_panic_key_code(&self) -> Result<Option<Code>> {
+        self.panic_hotkey
+            .as_ref()
+            .map(|s| Self::parse_key_string(s))
+            .transpose()
+    }
+
+    /// Get the emergency unlock hotkey modifiers, defaulting to Ctrl+Opt+Cmd+Shift
+    pub fn get_panic_modifiers(&self) -> Result<HotkeyModifiers> {
+        self.panic_modifiers
+            .as_deref()
+            .map(str::parse)
+            .unwrap_or(Ok(HotkeyModifiers::PANIC_DEFAULT))
+    }
+
+    /// Get the unlock chord sequence (None when `unlock_chord` isn't set)
+    ///
+    /// The chord needs at least two modifiers, and `unlock_chord_presses` must be in
+    /// UNLOCK_CHORD_PRESSES_MIN..=UNLOCK_CHORD_PRESSES_MAX.
+    pub fn get_unlock_chord(&self) -> Result<Option<UnlockChord>> {
+        let Some(chord) = self.unlock_chord.as_deref() else {
+            return Ok(None);
+        };
+        let modifiers: HotkeyModifiers = chord.parse()?;
+        if modifiers.count() < 2 {
+            anyhow::bail!(
+                "Unlock chord '{}' needs at least two modifiers (e.g. cmd+shift)",
+                chord
+            );
+        }
+        let presses = self.unlock_chord_presses.unwrap_or(UNLOCK_CHORD_PRESSES_DEFAULT);
+        if !(UNLOCK_CHORD_PRESSES_MIN..=UNLOCK_CHORD_PRESSES_MAX).contains(&presses) {
+            anyhow::bail!(
+                "unlock_chord_presses must be between {} and {} (got {})",
+                UNLOCK_CHORD_PRESSES_MIN,
+                UNLOCK_CHORD_PRESSES_MAX,
+                presses
+            );
+        }
+        Ok(Some(UnlockChord { modifiers, presses }))
+    }
+
+    /// Get the hold-to-unlock key and duration (None unless `hold_to_unlock` is set)
+    ///
+    /// The key must be "escape" or "f1"-"f12", and `hold_to_unlock_secs` must be in
+    /// HOLD_UNLOCK_SECS_MIN..=HOLD_UNLOCK_SECS_MAX.
+    pub fn get_hold_unlock(&self) -> Result<Option<HoldUnlock>> {
+        if !self.hold_to_unlock {
+            return Ok(None);
+        }
+        let key = self.hold_to_unlock_key.as_deref().unwrap_or(HOLD_UNLOCK_KEY_DEFAULT);
+        let keycode = hold_key_keycode(key)
+            .ok_or_else(|| anyhow!("Hold-to-unlock key must be 'escape' or 'f1'-'f12' (got '{}')", key))?;
+        let secs = self.hold_to_unlock_secs.unwrap_or(HOLD_UNLOCK_SECS_DEFAULT);
+        if !(HOLD_UNLOCK_SECS_MIN..=HOLD_UNLOCK_SECS_MAX).contains(&secs) {
+            anyhow::bail!(
+                "hold_to_unlock_secs must be between {} and {} (got {})",
+                HOLD_UNLOCK_SECS_MIN,
+                HOLD_UNLOCK_SECS_MAX,
+                secs
+            );
+        }
+        Ok(Some(HoldUnlock {
+            keycode,
+            duration: Duration::from_secs(secs),
+        }))
+    }
+
+    /// Get the macOS keycode passed through in Talk mode, defaulting to the spacebar
+    pub fn get_talk_passthrough_keycode(&self) -> Result<i64> {
+        self.talk_passthrough_key
+            .as_ref()
+            .map(|s| Self::parse_talk_passthrough_key(s))
+            .unwrap_or(Ok(SPACEBAR_KEYCODE))
+    }
+
+    /// Validate a Talk passthrough key: "space" or a single letter A-Z (case insensitive)
+    pub fn validate_talk_passthrough_key(key: &str) -> Result<()> {
+        if key.eq_ignore_ascii_case(TALK_PASSTHROUGH_SPACE) {
+            return Ok(());
+        }
+        Self::validate_hotkey(key)
+            .map_err(|_| anyhow!("Talk passthrough key must be 'space' or a letter A-Z"))
+    }
+
+    /// Parse a Talk passthrough key ("space" or A-Z) to a macOS keycode
+    pub fn parse_talk_passthrough_key(key: &str) -> Result<i64> {
+        Self::validate_talk_passthrough_key(key)?;
+        if key.eq_ignore_ascii_case(TALK_PASSTHROUGH_SPACE) {
+            return Ok(SPACEBAR_KEYCODE);
+        }
+        let code = Self::parse_key_string(key)?;
+        code_to_keycode(code).ok_or_else(|| anyhow!("Invalid talk passthrough key: {}", key))
+    }
+
+    /// Ensure the Talk passthrough key is not the lock or talk hotkey letter
+    ///
+    /// Uses the default letters (L/T) when hotkeys are not configured.
+    pub fn validate_talk_passthrough_conflicts(&self) -> Result<()> {
+        let Some(ref key) = self.talk_passthrough_key else {
+            return Ok(());
+        };
+        let lock = self.lock_hotkey.as_deref().unwrap_or("L");
+        let talk = self.talk_hotkey.as_deref().unwrap_or("T");
+        if key.eq_ignore_ascii_case(lock) || key.eq_ignore_ascii_case(talk) {
+            anyhow::bail!(
+                "Invalid config: Talk passthrough key '{}' must differ from the Lock ({}) and Talk ({}) hotkeys. Please run 'handsoff --setup' to reconfigure.",
+                key,
+                lock.to_uppercase(),
+                talk.to_uppercase()
+            );
+        }
+        Ok(())
+    }
+
+    /// Ensure the lock and talk hotkeys differ (case insensitive)
+    ///
+    /// Uses the default letters (L/T) for hotkeys that are not set, so e.g. a lock
+    /// hotkey of T with the default talk hotkey is rejected. Shared by both setups.
+    pub fn validate_distinct_hotkeys(lock_key: Option<&str>, talk_key: Option<&str>) -> Result<()> {
+        let lock = lock_key.unwrap_or("L");
+        let talk = talk_key.unwrap_or("T");
+        if lock.eq_ignore_ascii_case(talk) && !Self::is_hotkey_disabled(lock) {
+            return Err(anyhow!(
+                "Lock and Talk hotkeys must be different (both set to '{}')",
+                lock.to_uppercase()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Ensure the timeouts are within their documented bounds: auto-lock 20-600
+    /// seconds, auto-unlock 0 (disabled) or 60-900 seconds
+    ///
+    /// Out-of-range values are rejected rather than clamped, so a hand-edited value
+    /// never silently turns into a different timeout.
+    pub fn validate_timeouts(&self) -> Result<()> {
+        Self::validate_auto_lock_timeout(self.auto_lock_timeout)
+            .and_then(|()| Self::validate_auto_unlock_timeout(self.auto_unlock_timeout))
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid config: {}. Please run 'handsoff --setup' to reconfigure.",
+                    e
+                )
+            })
+    }
+
+    /// Ensure an auto-lock timeout is within AUTO_LOCK_MIN_SECONDS-AUTO_LOCK_MAX_SECONDS
+    pub fn validate_auto_lock_timeout(secs: u64) -> Result<()> {
+        if !(AUTO_LOCK_MIN_SECONDS..=AUTO_LOCK_MAX_SECONDS).contains(&secs) {
+            anyhow::bail!(
+                "auto_lock_timeout = {} is out of range ({}-{} seconds)",
+                secs,
+                AUTO_LOCK_MIN_SECONDS,
+                AUTO_LOCK_MAX_SECONDS
+            );
+        }
+        Ok(())
+    }
+
+    /// Ensure an auto-unlock timeout is 0 (disabled) or within
+    /// AUTO_UNLOCK_MIN_SECONDS-AUTO_UNLOCK_MAX_SECONDS
+    pub fn validate_auto_unlock_timeout(secs: u64) -> Result<()> {
+        if secs != 0 && !(AUTO_UNLOCK_MIN_SECONDS..=AUTO_UNLOCK_MAX_SECONDS).contains(&secs) {
+            anyhow::bail!(
+                "auto_unlock_timeout = {} is out of range (0 to disable, or {}-{} seconds)",
+                secs,
+                AUTO_UNLOCK_MIN_SECONDS,
+                AUTO_UNLOCK_MAX_SECONDS
+            );
+        }
+        Ok(())
+    }
+
+    /// Validate a new passphrase: not empty, and short enough to fit the input buffer
+    ///
+    /// The buffer keeps only the last MAX_BUFFER_LEN_DEFAULT characters typed while
+    /// locked, so a longer passphrase could never match.
+    pub fn validate_passphrase(plaintext_passphrase: &str) -> Result<()> {
+        if plaintext_passphrase.is_empty() {
+            anyhow::bail!("Passphrase cannot be empty");
+        }
+        let len = plaintext_passphrase.chars().count();
+        if len > MAX_BUFFER_LEN_DEFAULT {
+            anyhow::bail!(
+                "Passphrase is too long ({} characters, at most {} can be typed to unlock)",
+                len,
+                MAX_BUFFER_LEN_DEFAULT
+            );
+        }
+        Ok(())
     }
 
     /// Validate that a hotkey string is a single letter A-Z (case insensitive)
@@ -235,6 +1046,29 @@ impl Config {
         Ok(())
     }
 
+    /// Whether a lock/talk hotkey value is the "none" sentinel (case insensitive)
+    pub fn is_hotkey_disabled(key: &str) -> bool {
+        key.eq_ignore_ascii_case(HOTKEY_DISABLED)
+    }
+
+    /// Validate a lock/talk hotkey: a single letter A-Z, or "none" to disable it
+    pub fn validate_lock_talk_hotkey(key: &str) -> Result<()> {
+        if Self::is_hotkey_disabled(key) {
+            return Ok(());
+        }
+        Self::validate_hotkey(key)
+            .map_err(|_| anyhow!("Hotkey must be a letter A-Z or '{}'", HOTKEY_DISABLED))
+    }
+
+    /// Parse a lock/talk hotkey (A-Z or "none") to a Code, None when disabled
+    pub fn parse_lock_talk_hotkey(key: &str) -> Result<Option<Code>> {
+        Self::validate_lock_talk_hotkey(key)?;
+        if Self::is_hotkey_disabled(key) {
+            return Ok(None);
+        }
+        Self::parse_key_string(key).map(Some)
+    }
+
     /// Parse a hotkey string (A-Z) to a Code enum value
     pub fn parse_key_string(key: &str) -> Result<Code> {
         Self::validate_hotkey(key)?;
@@ -327,23 +1161,143 @@ mod tests {
 
         let decrypted = config.get_passphrase().expect("Failed to get passphrase");
 
-        assert_eq!(original, decrypted);
+        assert_eq!(original, *decrypted);
     }
 
     #[test]
-    fn test_config_save_load_roundtrip() {
-        let temp_path = temp_config_path();
-
-        // Ensure clean slate
-        let _ = fs::remove_file(&temp_path);
+    fn test_passphrase_longer_than_the_input_buffer_rejected() {
+        let longest = "x".repeat(MAX_BUFFER_LEN_DEFAULT);
+        assert!(Config::validate_passphrase(&longest).is_ok());
+        assert!(Config::validate_passphrase("").is_err());
+
+        // It could never be typed to unlock: the buffer drops the oldest characters
+        let too_long = format!("{}x", longest);
+        assert!(Config::validate_passphrase(&too_long).is_err());
+        assert!(Config::new(&too_long, 30, 60, None, None).is_err());
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert!(config.set_passphrase(&too_long).is_err());
+        assert_eq!(*config.get_passphrase().unwrap(), "test_passphrase");
+    }
 
-        // Create config
-        let original_config = Config {
-            encrypted_passphrase: "test_encrypted_data".to_string(),
-            auto_lock_timeout: 45,
-            auto_unlock_timeout: 120,
-            lock_hotkey: None,
+    #[test]
+    fn test_profile_path() {
+        let dir = Path::new("/tmp/handsoff");
+        assert_eq!(
+            Config::profile_path_in(dir, DEFAULT_PROFILE).unwrap(),
+            dir.join("config.toml")
+        );
+        assert_eq!(
+            Config::profile_path_in(dir, "work").unwrap(),
+            dir.join("config-work.toml")
+        );
+        assert_eq!(
+            Config::profile_path_in(dir, "home_2-b").unwrap(),
+            dir.join("config-home_2-b.toml")
+        );
+        for name in ["", "../work", "a/b", "work.toml", "my profile"] {
+            assert!(Config::profile_path_in(dir, name).is_err(), "{:?}", name);
+        }
+
+        assert_eq!(Config::profile_path(DEFAULT_PROFILE).unwrap(), Config::config_path());
+        assert_eq!(
+            Config::profile_path("work").unwrap(),
+            Config::config_path().with_file_name("config-work.toml")
+        );
+    }
+
+    #[test]
+    fn test_profiles_coexist() {
+        let temp_path = temp_config_path();
+        let dir = temp_path.parent().unwrap();
+        let work_path = Config::profile_path_in(dir, "work").unwrap();
+        let home_path = Config::profile_path_in(dir, "home").unwrap();
+
+        let work = Config::new("work_passphrase", 30, 0, None, None).unwrap();
+        let home = Config::new("home_passphrase", 300, 600, Some("K".to_string()), None).unwrap();
+        work.save_to_path(&work_path).unwrap();
+        home.save_to_path(&home_path).unwrap();
+
+        let loaded_work = Config::load_from_path(&work_path).unwrap();
+        let loaded_home = Config::load_from_path(&home_path).unwrap();
+        assert_eq!(loaded_work.auto_lock_timeout, 30);
+        assert_eq!(loaded_work.auto_unlock_timeout, 0);
+        assert_eq!(loaded_work.lock_hotkey, None);
+        assert_eq!(loaded_home.auto_lock_timeout, 300);
+        assert_eq!(loaded_home.auto_unlock_timeout, 600);
+        assert_eq!(loaded_home.lock_hotkey.as_deref(), Some("K"));
+        assert_eq!(loaded_work.get_passphrase().unwrap().as_str(), "work_passphrase");
+        assert_eq!(loaded_home.get_passphrase().unwrap().as_str(), "home_passphrase");
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_config_save_load_roundtrip() {
+        let temp_path = temp_config_path();
+
+        // Ensure clean slate
+        let _ = fs::remove_file(&temp_path);
+
+        // Create config
+        let original_config = Config {
+            version: CONFIG_VERSION,
+            encrypted_passphrase: "test_encrypted_data".to_string(),
+            auto_lock_timeout: 45,
+            auto_unlock_timeout: 120,
+            lock_hotkey: None,
             talk_hotkey: None,
+            lock_modifiers: None,
+            talk_modifiers: None,
+            lock_double_press: false,
+            lock_double_press_window_ms: None,
+            keyboard_layout: KeyboardLayout::Us,
+            buffer_reset_timeout: None,
+            lock_scope: LockScope::All,
+            block_media_keys: true,
+            block_power_key: true,
+            unlock_hotkey: None,
+            panic_hotkey: None,
+            panic_modifiers: None,
+            unlock_chord: None,
+            unlock_chord_presses: None,
+            hold_to_unlock: false,
+            hold_to_unlock_key: None,
+            hold_to_unlock_secs: None,
+            panic_requires_touchid: false,
+            two_factor_unlock: false,
+            recovery_code_hash: None,
+            use_keychain: false,
+            app_allowlist: Vec::new(),
+            talk_passthrough_key: None,
+            talk_mode: TalkMode::Hold,
+            on_permission_loss: PermissionLossPolicy::Unlock,
+            control_socket: false,
+            http_api_port: None,
+            http_api_token: None,
+            auto_lock_warning: None,
+            respect_media_playback: false,
+            auto_lock_ignore_mouse_move: false,
+            auto_lock_timeout_on_battery: None,
+            auto_lock_only_on_battery: false,
+            activity_debounce_events: None,
+            activity_debounce_window_ms: None,
+            unlock_grace_secs: None,
+            watchdog_timeout_secs: None,
+            max_lock_duration_mins: None,
+            lock_overlay: false,
+            menu_bar_countdown: false,
+            feedback_sound: false,
+            attempt_cooldown: true,
+            clear_overlong_attempts: false,
+            confirm_disable: false,
+            confirm_reset: false,
+            lock_on_wake: false,
+            lock_on_screensaver: false,
+            lock_on_app: Vec::new(),
+            notifications_enabled: true,
+            notification_timeout_ms: None,
+            schedule: Schedule::default(),
+            messages: BTreeMap::new(),
         };
 
         // Write to temp file
@@ -377,11 +1331,64 @@ mod tests {
         let temp_path = temp_config_path();
 
         let config = Config {
+            version: CONFIG_VERSION,
             encrypted_passphrase: "test".to_string(),
             auto_lock_timeout: 30,
             auto_unlock_timeout: 60,
             lock_hotkey: None,
             talk_hotkey: None,
+            lock_modifiers: None,
+            talk_modifiers: None,
+            lock_double_press: false,
+            lock_double_press_window_ms: None,
+            keyboard_layout: KeyboardLayout::Us,
+            buffer_reset_timeout: None,
+            lock_scope: LockScope::All,
+            block_media_keys: true,
+            block_power_key: true,
+            unlock_hotkey: None,
+            panic_hotkey: None,
+            panic_modifiers: None,
+            unlock_chord: None,
+            unlock_chord_presses: None,
+            hold_to_unlock: false,
+            hold_to_unlock_key: None,
+            hold_to_unlock_secs: None,
+            panic_requires_touchid: false,
+            two_factor_unlock: false,
+            recovery_code_hash: None,
+            use_keychain: false,
+            app_allowlist: Vec::new(),
+            talk_passthrough_key: None,
+            talk_mode: TalkMode::Hold,
+            on_permission_loss: PermissionLossPolicy::Unlock,
+            control_socket: false,
+            http_api_port: None,
+            http_api_token: None,
+            auto_lock_warning: None,
+            respect_media_playback: false,
+            auto_lock_ignore_mouse_move: false,
+            auto_lock_timeout_on_battery: None,
+            auto_lock_only_on_battery: false,
+            activity_debounce_events: None,
+            activity_debounce_window_ms: None,
+            unlock_grace_secs: None,
+            watchdog_timeout_secs: None,
+            max_lock_duration_mins: None,
+            lock_overlay: false,
+            menu_bar_countdown: false,
+            feedback_sound: false,
+            attempt_cooldown: true,
+            clear_overlong_attempts: false,
+            confirm_disable: false,
+            confirm_reset: false,
+            lock_on_wake: false,
+            lock_on_screensaver: false,
+            lock_on_app: Vec::new(),
+            notifications_enabled: true,
+            notification_timeout_ms: None,
+            schedule: Schedule::default(),
+            messages: BTreeMap::new(),
         };
 
         // Write config
@@ -402,6 +1409,483 @@ mod tests {
         fs::remove_file(temp_path).ok();
     }
 
+    #[test]
+    fn test_recovery_code_roundtrip_and_clear() {
+        let temp_path = temp_config_path();
+        let _ = fs::remove_file(&temp_path);
+
+        let code = crate::auth::generate_recovery_code().expect("Failed to generate code");
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        config.set_recovery_code(&code);
+
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(!contents.contains(&code), "Plaintext recovery code must not be saved");
+        fs::write(&temp_path, contents).unwrap();
+
+        let mut loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        let hash = loaded.recovery_code_hash.clone().expect("Hash should be saved");
+        assert!(crate::auth::verify_passphrase(&code, &hash));
+
+        loaded.clear_recovery_code();
+        assert!(loaded.recovery_code_hash.is_none());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_passphrase_change_roundtrip() {
+        let temp_path = temp_config_path();
+        let _ = fs::remove_file(&temp_path);
+
+        let mut config = Config::new("old_passphrase", 45, 0, Some("K".to_string()), None).unwrap();
+        config.set_recovery_code("recovery");
+        config.save_to_path(&temp_path).unwrap();
+
+        Config::persist_passphrase_change(&temp_path, "new_passphrase").unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(*loaded.get_passphrase().unwrap(), "new_passphrase");
+        let hash = loaded.get_passphrase_hash().unwrap();
+        assert!(crate::auth::verify_passphrase("new_passphrase", &hash));
+        assert!(!crate::auth::verify_passphrase("old_passphrase", &hash));
+
+        // Everything else is kept
+        assert_eq!(loaded.auto_lock_timeout, 45);
+        assert_eq!(loaded.lock_hotkey.as_deref(), Some("K"));
+        assert_eq!(loaded.recovery_code_hash, config.recovery_code_hash);
+
+        let contents = fs::read_to_string(&temp_path).unwrap();
+        assert!(!contents.contains("new_passphrase"), "Plaintext passphrase must not be saved");
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_legacy_passphrase_reencrypted_on_load() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        let legacy = crypto::encrypt_with_legacy_key("old_passphrase");
+        config.encrypted_passphrase = legacy.clone();
+        config.save_to_path(&temp_path).unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).unwrap();
+        assert_ne!(loaded.encrypted_passphrase, legacy);
+        assert_eq!(*loaded.get_passphrase().unwrap(), "old_passphrase");
+        assert_eq!(crypto::reencrypt_if_legacy(&loaded.encrypted_passphrase).unwrap(), None);
+
+        // The migration was saved
+        let reloaded = Config::load_from_path(&temp_path).unwrap();
+        assert_eq!(reloaded.encrypted_passphrase, loaded.encrypted_passphrase);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_empty_passphrase_change_rejected() {
+        let temp_path = temp_config_path();
+        let _ = fs::remove_file(&temp_path);
+        Config::new("old_passphrase", 30, 0, None, None)
+            .unwrap()
+            .save_to_path(&temp_path)
+            .unwrap();
+
+        assert!(Config::persist_passphrase_change(&temp_path, "").is_err());
+        let loaded = Config::load_from_path(&temp_path).unwrap();
+        assert_eq!(*loaded.get_passphrase().unwrap(), "old_passphrase");
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_get_passphrase_hash_from_config_file() {
+        let config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+
+        let hash = config.get_passphrase_hash().expect("Failed to get passphrase hash");
+        assert!(crate::auth::verify_passphrase("test_passphrase", &hash));
+    }
+
+    #[test]
+    fn test_missing_encrypted_passphrase_loads_but_cannot_decrypt() {
+        let temp_path = temp_config_path();
+        let contents = r#"
+auto_lock_timeout = 30
+auto_unlock_timeout = 60
+use_keychain = true
+"#;
+        fs::write(&temp_path, contents).expect("Failed to write temp config");
+
+        let config = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert!(config.use_keychain);
+        assert!(config.encrypted_passphrase.is_empty());
+        assert!(config.get_passphrase().is_err());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_messages_table_roundtrip() {
+        let temp_path = temp_config_path();
+        let contents = r#"
+encrypted_passphrase = "test_encrypted_data"
+auto_lock_timeout = 30
+auto_unlock_timeout = 60
+
+[messages]
+input_locked = "Eingabe gesperrt"
+"#;
+        fs::write(&temp_path, contents).expect("Failed to write temp config");
+        let config = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(config.messages.get("input_locked").map(String::as_str), Some("Eingabe gesperrt"));
+
+        config.save_to_path(&temp_path).unwrap();
+        let reloaded = Config::load_from_path(&temp_path).unwrap();
+        assert_eq!(reloaded.messages, config.messages);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_block_media_keys_defaults_to_true() {
+        let temp_path = temp_config_path();
+        let contents = r#"
+encrypted_passphrase = "test_encrypted_data"
+auto_lock_timeout = 30
+auto_unlock_timeout = 60
+"#;
+        fs::write(&temp_path, contents).expect("Failed to write temp config");
+        let config = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert!(config.block_media_keys, "Older configs should block media keys by default");
+        assert!(config.block_power_key, "Older configs should block the power key by default");
+        assert!(config.attempt_cooldown, "Older configs should keep the attempt cooldown");
+
+        fs::write(&temp_path, format!("{}block_media_keys = false\n", contents)).unwrap();
+        let config = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert!(!config.block_media_keys);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_v1_config_migrates_with_defaults() {
+        let temp_path = temp_config_path();
+        // Unversioned file as hand-written before versioning: no auto_unlock_timeout,
+        // short lock_scope name
+        fs::write(
+            &temp_path,
+            r#"
+encrypted_passphrase = "test"
+auto_lock_timeout = 90
+lock_hotkey = "K"
+lock_scope = "keyboard"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load_from_path(&temp_path).expect("v1 config should migrate");
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.auto_lock_timeout, 90);
+        assert_eq!(config.auto_unlock_timeout, 0);
+        assert_eq!(config.lock_hotkey, Some("K".to_string()));
+        assert_eq!(config.lock_scope, LockScope::KeyboardOnly);
+        assert!(config.block_media_keys);
+        assert_eq!(config.talk_mode, TalkMode::Hold);
+        assert!(config.schedule.is_empty());
+
+        // Saving writes the current version
+        config.save_to_path(&temp_path).unwrap();
+        let saved: toml::Table = toml::from_str(&fs::read_to_string(&temp_path).unwrap()).unwrap();
+        assert_eq!(saved["version"].as_integer(), Some(CONFIG_VERSION as i64));
+        assert_eq!(saved["lock_scope"].as_str(), Some("keyboard_only"));
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_migrate_fills_missing_timeouts() {
+        let table = Config::migrate(toml::Table::new()).unwrap();
+        assert_eq!(table["version"].as_integer(), Some(CONFIG_VERSION as i64));
+        assert_eq!(
+            table["auto_lock_timeout"].as_integer(),
+            Some(AUTO_LOCK_DEFAULT_SECONDS as i64)
+        );
+        assert_eq!(table["auto_unlock_timeout"].as_integer(), Some(0));
+    }
+
+    #[test]
+    fn test_newer_config_version_rejected() {
+        let mut table = toml::Table::new();
+        table.insert(
+            "version".to_string(),
+            toml::Value::Integer(CONFIG_VERSION as i64 + 1),
+        );
+        let err = Config::migrate(table).unwrap_err();
+        assert!(err.to_string().contains("newer"), "{}", err);
+
+        let mut table = toml::Table::new();
+        table.insert("version".to_string(), toml::Value::String("two".to_string()));
+        assert!(Config::migrate(table).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_timeouts_rejected() {
+        let temp_path = temp_config_path();
+        let load = |auto_lock: u64, auto_unlock: u64| {
+            let mut config = Config::new("test", 120, 0, None, None).unwrap();
+            config.auto_lock_timeout = auto_lock;
+            config.auto_unlock_timeout = auto_unlock;
+            fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+            Config::load_from_path(&temp_path)
+        };
+
+        for (auto_lock, auto_unlock) in [(5, 0), (99999, 0), (19, 0), (601, 0), (120, 59), (120, 901)] {
+            let err = load(auto_lock, auto_unlock)
+                .expect_err(&format!("{}/{} should be rejected", auto_lock, auto_unlock));
+            assert!(err.to_string().contains("--setup"), "{}", err);
+        }
+
+        // Bounds are inclusive; 0 disables auto-unlock
+        for (auto_lock, auto_unlock) in [(20, 0), (600, 60), (120, 900)] {
+            assert!(
+                load(auto_lock, auto_unlock).is_ok(),
+                "{}/{} should be accepted",
+                auto_lock,
+                auto_unlock
+            );
+        }
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_new_rejects_out_of_range_timeouts() {
+        for (auto_lock, auto_unlock) in [(5, 0), (99999, 0), (120, 59), (120, 901)] {
+            assert!(
+                Config::new("test", auto_lock, auto_unlock, None, None).is_err(),
+                "{}/{} should be rejected",
+                auto_lock,
+                auto_unlock
+            );
+        }
+        assert!(Config::new("test", 20, 0, None, None).is_ok());
+        assert!(Config::new("test", 600, 900, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_lock_scope_roundtrip() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.lock_scope, LockScope::All);
+
+        config.lock_scope = LockScope::MouseOnly;
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(contents.contains("lock_scope = \"mouse_only\""));
+        fs::write(&temp_path, contents).unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.lock_scope, LockScope::MouseOnly);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_save_and_load_custom_path() {
+        // A profile in a directory that doesn't exist yet
+        let temp_path = temp_config_path()
+            .parent()
+            .unwrap()
+            .join("profiles")
+            .join("work.toml");
+
+        let mut config =
+            Config::new("profile_passphrase", 45, 0, Some("K".to_string()), None).unwrap();
+        config.set_recovery_code("ABCD-EFGH");
+        config.save_to_path(&temp_path).expect("Failed to save to custom path");
+
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load custom path");
+        assert_eq!(loaded.auto_lock_timeout, 45);
+        assert_eq!(loaded.lock_hotkey, Some("K".to_string()));
+        assert_eq!(&*loaded.get_passphrase().unwrap(), "profile_passphrase");
+
+        Config::persist_recovery_code_consumed(&temp_path).unwrap();
+        assert!(Config::load_from_path(&temp_path).unwrap().recovery_code_hash.is_none());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_hotkey_modifiers_config() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.get_lock_modifiers().unwrap(), HotkeyModifiers::DEFAULT);
+
+        config.lock_modifiers = Some("cmd+opt+ctrl".to_string());
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.get_lock_modifiers().unwrap().to_string(), "Ctrl+Opt+Cmd");
+        assert_eq!(loaded.get_talk_modifiers().unwrap(), HotkeyModifiers::DEFAULT);
+
+        for invalid in ["", "hyper+l"] {
+            config.talk_modifiers = Some(invalid.to_string());
+            fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+            assert!(
+                Config::load_from_path(&temp_path).is_err(),
+                "talk_modifiers '{}' should be rejected",
+                invalid
+            );
+        }
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_panic_hotkey_config() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.get_panic_key_code().unwrap(), None, "Disabled by default");
+        assert_eq!(config.get_panic_modifiers().unwrap(), HotkeyModifiers::PANIC_DEFAULT);
+
+        config.panic_hotkey = Some("x".to_string());
+        config.panic_requires_touchid = true;
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.get_panic_key_code().unwrap(), Some(Code::KeyX));
+        assert!(loaded.panic_requires_touchid);
+
+        config.panic_hotkey = Some("12".to_string());
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(Config::load_from_path(&temp_path).is_err());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_unlock_chord_config() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.get_unlock_chord().unwrap(), None, "Disabled by default");
+
+        config.unlock_chord = Some("cmd+shift".to_string());
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        let chord = loaded.get_unlock_chord().unwrap().unwrap();
+        assert!(chord.modifiers.command && chord.modifiers.shift);
+        assert_eq!(chord.presses, UNLOCK_CHORD_PRESSES_DEFAULT);
+
+        // A single modifier or an out-of-range press count is rejected
+        config.unlock_chord = Some("shift".to_string());
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(Config::load_from_path(&temp_path).is_err());
+        config.unlock_chord = Some("ctrl+opt".to_string());
+        config.unlock_chord_presses = Some(UNLOCK_CHORD_PRESSES_MAX + 1);
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(Config::load_from_path(&temp_path).is_err());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_hold_unlock_config() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        config.hold_to_unlock_key = Some("f5".to_string());
+        assert_eq!(config.get_hold_unlock().unwrap(), None, "Disabled by default");
+
+        config.hold_to_unlock = true;
+        config.hold_to_unlock_key = None;
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        let hold = loaded.get_hold_unlock().unwrap().unwrap();
+        assert_eq!(hold.keycode, crate::constants::ESCAPE_KEYCODE);
+        assert_eq!(hold.duration, Duration::from_secs(HOLD_UNLOCK_SECS_DEFAULT));
+
+        // A letter key or an out-of-range duration is rejected
+        config.hold_to_unlock_key = Some("a".to_string());
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(Config::load_from_path(&temp_path).is_err());
+        config.hold_to_unlock_key = Some("F5".to_string());
+        config.hold_to_unlock_secs = Some(HOLD_UNLOCK_SECS_MAX + 1);
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(Config::load_from_path(&temp_path).is_err());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_schedule_roundtrip_and_validation() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(!contents.contains("[schedule]"), "Empty schedule should not be written");
+
+        config.schedule.windows.push(crate::schedule::ScheduleWindow {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        });
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.schedule, config.schedule);
+
+        config.schedule.windows[0].end = "7am".to_string();
+        fs::write(&temp_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(Config::load_from_path(&temp_path).is_err());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_on_permission_loss_roundtrip() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.on_permission_loss, PermissionLossPolicy::Unlock);
+
+        config.on_permission_loss = PermissionLossPolicy::Retry;
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(contents.contains("on_permission_loss = \"retry\""));
+        fs::write(&temp_path, contents).unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.on_permission_loss, PermissionLossPolicy::Retry);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_talk_mode_roundtrip() {
+        let temp_path = temp_config_path();
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.talk_mode, TalkMode::Hold);
+
+        config.talk_mode = TalkMode::Toggle;
+        let contents = toml::to_string_pretty(&config).unwrap();
+        assert!(contents.contains("talk_mode = \"toggle\""));
+        fs::write(&temp_path, contents).unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.talk_mode, TalkMode::Toggle);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_app_allowlist_defaults_to_empty() {
+        let temp_path = temp_config_path();
+        let config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        let contents = toml::to_string_pretty(&config).unwrap();
+        fs::write(&temp_path, &contents).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert!(loaded.app_allowlist.is_empty());
+
+        let contents = contents.replace(
+            "app_allowlist = []",
+            "app_allowlist = [\"org.videolan.vlc\"]",
+        );
+        fs::write(&temp_path, contents).unwrap();
+        let loaded = Config::load_from_path(&temp_path).expect("Failed to load config");
+        assert_eq!(loaded.app_allowlist, vec!["org.videolan.vlc".to_string()]);
+
+        fs::remove_file(temp_path).ok();
+    }
+
     #[test]
     fn test_config_portability() {
         // This test verifies that a config created in one "session" works in another
@@ -428,11 +1912,53 @@ mod tests {
         let decrypted1 = config1.get_passphrase().expect("Failed to decrypt 1");
         let decrypted2 = config2.get_passphrase().expect("Failed to decrypt 2");
 
-        assert_eq!(decrypted1, passphrase);
-        assert_eq!(decrypted2, passphrase);
+        assert_eq!(*decrypted1, passphrase);
+        assert_eq!(*decrypted2, passphrase);
         assert_eq!(decrypted1, decrypted2);
     }
 
+    #[test]
+    fn test_export_omits_secrets() {
+        let mut config = Config::new("secret", 45, 0, Some("K".to_string()), None).unwrap();
+        config.set_recovery_code("recovery");
+        let table = config.export_table().unwrap();
+
+        for key in EXPORT_EXCLUDED_KEYS {
+            assert!(!table.contains_key(key), "{} must not be exported", key);
+        }
+        assert!(!toml::to_string(&table).unwrap().contains(&config.encrypted_passphrase));
+        assert_eq!(table["version"].as_integer(), Some(CONFIG_VERSION as i64));
+        assert_eq!(table["auto_lock_timeout"].as_integer(), Some(45));
+        assert_eq!(table["lock_hotkey"].as_str(), Some("K"));
+    }
+
+    #[test]
+    fn test_import_merges_settings_with_a_new_passphrase() {
+        let exported = Config::new("old machine", 45, 0, Some("K".to_string()), None)
+            .unwrap()
+            .export_table()
+            .unwrap();
+
+        let mut existing = Config::new("existing", 300, 0, None, None).unwrap();
+        existing.set_recovery_code("recovery");
+        existing.control_socket = true;
+        let mut imported = exported.clone();
+        imported.remove("control_socket");
+        imported.insert("encrypted_passphrase".into(), "ignored".into());
+
+        let merged = existing.merge_imported(imported, "new passphrase").unwrap();
+        assert_eq!(*merged.get_passphrase().unwrap(), "new passphrase");
+        assert_eq!(merged.auto_lock_timeout, 45, "Imported settings replace existing ones");
+        assert_eq!(merged.lock_hotkey.as_deref(), Some("K"));
+        assert!(merged.control_socket, "Settings missing from the import are kept");
+        assert_eq!(merged.recovery_code_hash, existing.recovery_code_hash);
+
+        // Imported settings are validated like a config file
+        let mut invalid = exported;
+        invalid.insert("auto_lock_timeout".into(), 5.into());
+        assert!(existing.merge_imported(invalid, "new passphrase").is_err());
+    }
+
     #[test]
     fn test_missing_config_file() {
         // Use a guaranteed-nonexistent path to test missing config handling
@@ -486,6 +2012,40 @@ mod tests {
         assert!(result.is_err(), "Should reject duplicate hotkeys (case-insensitive)");
     }
 
+    #[test]
+    fn test_hotkey_clashing_with_default_rejected() {
+        assert!(Config::validate_distinct_hotkeys(Some("t"), None).is_err());
+        assert!(Config::validate_distinct_hotkeys(None, Some("L")).is_err());
+        assert!(Config::validate_distinct_hotkeys(None, None).is_ok());
+        assert!(Config::validate_distinct_hotkeys(Some("T"), Some("L")).is_ok());
+    }
+
+    #[test]
+    fn test_setup_with_custom_hotkeys_roundtrip() {
+        // Mirrors run_setup in both binaries: validate, build, save, then load as at startup
+        let temp_path = temp_config_path();
+        let (lock_key, talk_key) = (Some("K".to_string()), Some("J".to_string()));
+        Config::validate_distinct_hotkeys(lock_key.as_deref(), talk_key.as_deref()).unwrap();
+
+        let mut config = Config::new("setup_passphrase", 60, 300, lock_key, talk_key).unwrap();
+        config.lock_scope = LockScope::KeyboardOnly;
+        config.talk_passthrough_key = Some("M".to_string());
+        config.validate_talk_passthrough_conflicts().unwrap();
+        config.set_recovery_code("ABCD-EFGH");
+        config.save_to_path(&temp_path).unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).unwrap();
+        assert_eq!(loaded.get_lock_key_code().unwrap(), Some(Code::KeyK));
+        assert_eq!(loaded.get_talk_key_code().unwrap(), Some(Code::KeyJ));
+        assert_eq!(loaded.auto_lock_timeout, 60);
+        assert_eq!(loaded.auto_unlock_timeout, 300);
+        assert_eq!(loaded.lock_scope, LockScope::KeyboardOnly);
+        assert_eq!(&*loaded.get_passphrase().unwrap(), "setup_passphrase");
+        assert!(loaded.recovery_code_hash.is_some());
+
+        fs::remove_file(temp_path).ok();
+    }
+
     #[test]
     fn test_different_hotkeys_accepted() {
         // Test that different hotkeys are accepted
@@ -565,4 +2125,111 @@ talk_hotkey = "M"
         // Cleanup
         fs::remove_file(temp_path).ok();
     }
+
+    #[test]
+    fn test_verify_passphrase_against_saved_config() {
+        let temp_path = temp_config_path();
+        Config::new("open sesame", 30, 60, None, None)
+            .unwrap()
+            .save_to_path(&temp_path)
+            .unwrap();
+
+        let loaded = Config::load_from_path(&temp_path).unwrap();
+        assert!(loaded.verify_passphrase("open sesame").unwrap());
+        assert!(!loaded.verify_passphrase("open sesame ").unwrap());
+        assert!(!loaded.verify_passphrase("").unwrap());
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_verify_passphrase_without_stored_passphrase_errors() {
+        let mut config = Config::new("open sesame", 30, 60, None, None).unwrap();
+        config.encrypted_passphrase.clear();
+        assert!(config.verify_passphrase("open sesame").is_err());
+    }
+
+    #[test]
+    fn test_disabled_hotkeys_in_loaded_config() {
+        let temp_path = temp_config_path();
+        let contents = r#"
+encrypted_passphrase = "test_encrypted_data"
+auto_lock_timeout = 30
+auto_unlock_timeout = 60
+lock_hotkey = "none"
+talk_hotkey = "NONE"
+"#;
+        fs::write(&temp_path, contents).expect("Failed to write temp config");
+
+        // Both disabled don't count as duplicates
+        let loaded = Config::load_from_path(&temp_path).unwrap();
+        assert_eq!(loaded.get_lock_key_code().unwrap(), None);
+        assert_eq!(loaded.get_talk_key_code().unwrap(), None);
+
+        fs::remove_file(temp_path).ok();
+    }
+
+    #[test]
+    fn test_lock_talk_hotkey_none_sentinel() {
+        assert_eq!(Config::parse_lock_talk_hotkey("none").unwrap(), None);
+        assert_eq!(Config::parse_lock_talk_hotkey("k").unwrap(), Some(Code::KeyK));
+        assert!(Config::parse_lock_talk_hotkey("nope").is_err());
+        // Other hotkeys can't be disabled this way
+        assert!(Config::validate_hotkey(HOTKEY_DISABLED).is_err());
+
+        assert!(Config::validate_distinct_hotkeys(Some("none"), Some("None")).is_ok());
+        assert!(Config::validate_distinct_hotkeys(Some("none"), None).is_ok());
+
+        let config = Config::new("test_passphrase", 30, 60, Some("none".to_string()), None).unwrap();
+        assert_eq!(config.get_lock_key_code().unwrap(), None);
+        assert_eq!(config.get_talk_key_code().unwrap(), Some(Code::KeyT));
+    }
+
+    #[test]
+    fn test_talk_passthrough_key_parsing() {
+        assert_eq!(Config::parse_talk_passthrough_key("space").unwrap(), SPACEBAR_KEYCODE);
+        assert_eq!(Config::parse_talk_passthrough_key("SPACE").unwrap(), SPACEBAR_KEYCODE);
+        assert_eq!(Config::parse_talk_passthrough_key("m").unwrap(), 46);
+        assert!(Config::parse_talk_passthrough_key("F5").is_err());
+        assert!(Config::parse_talk_passthrough_key("1").is_err());
+
+        let mut config = Config::new("test_passphrase", 30, 60, None, None).unwrap();
+        assert_eq!(config.get_talk_passthrough_keycode().unwrap(), SPACEBAR_KEYCODE);
+        config.talk_passthrough_key = Some("M".to_string());
+        assert_eq!(config.get_talk_passthrough_keycode().unwrap(), 46);
+    }
+
+    #[test]
+    fn test_talk_passthrough_key_conflicts_with_hotkeys() {
+        let mut config =
+            Config::new("test_passphrase", 30, 60, Some("K".to_string()), None).unwrap();
+
+        config.talk_passthrough_key = Some("k".to_string());
+        assert!(config.validate_talk_passthrough_conflicts().is_err());
+
+        // Default talk hotkey letter (T) is also reserved
+        config.talk_passthrough_key = Some("T".to_string());
+        assert!(config.validate_talk_passthrough_conflicts().is_err());
+
+        config.talk_passthrough_key = Some("M".to_string());
+        assert!(config.validate_talk_passthrough_conflicts().is_ok());
+    }
+
+    #[test]
+    fn test_talk_passthrough_conflict_in_loaded_config() {
+        let temp_path = temp_config_path();
+        let contents = r#"
+encrypted_passphrase = "test_encrypted_data"
+auto_lock_timeout = 30
+auto_unlock_timeout = 60
+lock_hotkey = "M"
+talk_passthrough_key = "m"
+"#;
+        fs::write(&temp_path, contents).expect("Failed to write temp config");
+
+        let result = Config::load_from_path(&temp_path);
+        assert!(result.is_err(), "Should reject passthrough key equal to lock hotkey");
+
+        fs::remove_file(temp_path).ok();
+    }
 }
@@ -23,6 +23,68 @@ pub const AUTO_LOCK_MAX_SECONDS: u64 = 600;
 /// Recommended range: 60-300 (1-5 minutes)
 pub const AUTO_LOCK_DEFAULT_SECONDS: u64 = 120;
 
+/// Default lead time for the notification shown before auto-lock engages.
+/// Unit: seconds (0 = no warning)
+/// Recommended range: 5-15 (warning fires within one AUTO_LOCK_CHECK_INTERVAL_SECS of this)
+pub const AUTO_LOCK_WARNING_DEFAULT_SECONDS: u64 = 10;
+
+/// Maximum auto-lock warning lead time.
+/// Unit: seconds
+/// Range: Fixed maximum, must stay below AUTO_LOCK_MIN_SECONDS
+pub const AUTO_LOCK_WARNING_MAX_SECONDS: u64 = 15;
+
+/// Default grace period after unlocking during which auto-lock cannot re-engage.
+/// Unit: seconds (0 = no grace period)
+/// Recommended range: 5-30 (time to act after unlocking)
+pub const UNLOCK_GRACE_DEFAULT_SECONDS: u64 = 10;
+
+/// Maximum post-unlock grace period.
+/// Unit: seconds
+/// Range: Fixed maximum, keeps auto-lock meaningful
+pub const UNLOCK_GRACE_MAX_SECONDS: u64 = 120;
+
+/// Default number of input events that must arrive within the activity debounce
+/// window before input resets the auto-lock timer.
+/// Unit: events (0 or 1 = off, every event counts; a click or keystroke is 2 events)
+/// Recommended range: 3-6 (filters out a single stray click or keystroke)
+pub const ACTIVITY_DEBOUNCE_EVENTS_DEFAULT: u32 = 0;
+
+/// Maximum activity debounce event count.
+/// Unit: events
+/// Range: Fixed maximum, so ordinary use still counts as activity
+pub const ACTIVITY_DEBOUNCE_EVENTS_MAX: u32 = 20;
+
+/// Default activity debounce window.
+/// Unit: milliseconds
+/// Recommended range: 500-2000
+pub const ACTIVITY_DEBOUNCE_WINDOW_DEFAULT_MS: u64 = 1000;
+
+/// Minimum activity debounce window.
+/// Unit: milliseconds
+/// Range: Fixed minimum
+pub const ACTIVITY_DEBOUNCE_WINDOW_MIN_MS: u64 = 100;
+
+/// Maximum activity debounce window.
+/// Unit: milliseconds
+/// Range: Fixed maximum
+pub const ACTIVITY_DEBOUNCE_WINDOW_MAX_MS: u64 = 10_000;
+
+/// Snooze durations offered in the tray menu's "Snooze Auto-Lock" submenu.
+/// Unit: minutes
+/// Recommended range: 5-120 (long enough for a meeting, short enough to not be forgotten)
+pub const AUTO_LOCK_SNOOZE_CHOICES_MINUTES: [u64; 3] = [15, 30, 60];
+
+/// Longest snooze accepted from a `handsoff://snooze?min=N` URL.
+/// Unit: minutes
+/// Range: Fixed maximum, so a scripted snooze can't disable auto-lock for the day
+pub const URL_SNOOZE_MAX_MINUTES: u64 = 240;
+
+/// Durations offered in the tray menu's "Pause" submenu (input blocking off, then
+/// re-enabled automatically).
+/// Unit: minutes
+/// Recommended range: 1-60 (short breaks; Disable is for open-ended pauses)
+pub const PAUSE_CHOICES_MINUTES: [u64; 3] = [5, 15, 30];
+
 // ============================================================================
 // AUTO-UNLOCK CONFIGURATION
 // ============================================================================
@@ -45,6 +107,22 @@ pub const AUTO_UNLOCK_MIN_SECONDS: u64 = 60;
 /// Range: Fixed maximum (15 minutes)
 pub const AUTO_UNLOCK_MAX_SECONDS: u64 = 900;
 
+/// Default hard cap on how long input stays locked, enforced even with auto-unlock
+/// disabled so a forgotten passphrase can't lock you out indefinitely.
+/// Unit: minutes
+/// Range: MAX_LOCK_DURATION_MIN_MINUTES-MAX_LOCK_DURATION_MAX_MINUTES
+pub const MAX_LOCK_DURATION_DEFAULT_MINUTES: u64 = 60;
+
+/// Minimum hard cap on the lock duration.
+/// Unit: minutes
+/// Range: Fixed, no shorter than the longest auto-unlock timeout
+pub const MAX_LOCK_DURATION_MIN_MINUTES: u64 = 15;
+
+/// Maximum hard cap on the lock duration.
+/// Unit: minutes
+/// Range: Fixed (12 hours)
+pub const MAX_LOCK_DURATION_MAX_MINUTES: u64 = 720;
+
 // ============================================================================
 // INPUT BUFFER CONFIGURATION
 // ============================================================================
@@ -54,6 +132,201 @@ pub const AUTO_UNLOCK_MAX_SECONDS: u64 = 900;
 /// Recommended range: 2-10 (short enough for security, long enough for typing)
 pub const BUFFER_RESET_DEFAULT_SECONDS: u64 = 3;
 
+/// Minimum buffer reset timeout allowed.
+/// Unit: seconds
+/// Range: Fixed minimum (shorter makes typing a passphrase impractical)
+pub const BUFFER_RESET_MIN_SECONDS: u64 = 2;
+
+/// Maximum buffer reset timeout allowed.
+/// Unit: seconds
+/// Range: Fixed maximum (longer leaves stray keystrokes in the buffer too long)
+pub const BUFFER_RESET_MAX_SECONDS: u64 = 15;
+
+/// Cooldown after a full-length wrong passphrase attempt, during which typing is ignored.
+/// Unit: seconds
+/// Recommended range: 2-10 (slows guessing without locking out a typo for long)
+pub const ATTEMPT_COOLDOWN_SECONDS: u64 = 5;
+
+/// Maximum number of characters kept in the passphrase buffer.
+/// Oldest characters are dropped beyond this, bounding memory while locked.
+/// Unit: characters
+/// Recommended range: 64-1024 (also the longest passphrase setup accepts)
+pub const MAX_BUFFER_LEN_DEFAULT: usize = 256;
+
+// ============================================================================
+// HOTKEYS
+// ============================================================================
+
+/// Default window for the second press when the lock hotkey requires a double press.
+/// Unit: milliseconds
+/// Recommended range: 300-800 (comfortable double press, unlikely by accident)
+pub const LOCK_DOUBLE_PRESS_DEFAULT_MS: u64 = 500;
+
+/// Minimum double-press window for the lock hotkey.
+/// Unit: milliseconds
+/// Range: Fixed minimum, shorter windows are hard to hit deliberately
+pub const LOCK_DOUBLE_PRESS_MIN_MS: u64 = 200;
+
+/// Maximum double-press window for the lock hotkey.
+/// Unit: milliseconds
+/// Range: Fixed maximum, longer windows make two separate presses count
+pub const LOCK_DOUBLE_PRESS_MAX_MS: u64 = 1500;
+
+/// Presses closer together than this are treated as one press delivered twice
+/// (e.g. by both the event tap and the global hotkey).
+/// Unit: milliseconds
+/// Range: Fixed, well below human double-press speed
+pub const LOCK_DOUBLE_PRESS_MIN_GAP_MS: u64 = 50;
+
+/// Default number of presses of the unlock chord that unlock.
+/// Unit: presses
+/// Recommended range: 3-5 (fewer presses are easier to hit by accident)
+pub const UNLOCK_CHORD_PRESSES_DEFAULT: u32 = 3;
+
+/// Minimum number of presses of the unlock chord.
+/// Unit: presses
+/// Range: Fixed minimum (a single press is too easy to hit by accident)
+pub const UNLOCK_CHORD_PRESSES_MIN: u32 = 2;
+
+/// Maximum number of presses of the unlock chord.
+/// Unit: presses
+/// Range: Fixed maximum (longer sequences are hard to press in time)
+pub const UNLOCK_CHORD_PRESSES_MAX: u32 = 6;
+
+/// Longest pause between two presses of the unlock chord before the count starts over.
+/// Unit: milliseconds
+/// Recommended range: 400-1000 (quick presses only, like a double click)
+pub const UNLOCK_CHORD_MAX_GAP_MS: u64 = 700;
+
+/// Default time the hold-to-unlock key has to be held.
+/// Unit: seconds
+/// Recommended range: 3-5 (long enough not to happen by accident)
+pub const HOLD_UNLOCK_SECS_DEFAULT: u64 = 3;
+
+/// Minimum time the hold-to-unlock key has to be held.
+/// Unit: seconds
+/// Range: Fixed minimum (shorter holds happen by accident)
+pub const HOLD_UNLOCK_SECS_MIN: u64 = 2;
+
+/// Maximum time the hold-to-unlock key has to be held.
+/// Unit: seconds
+/// Range: Fixed maximum (longer holds are impractical)
+pub const HOLD_UNLOCK_SECS_MAX: u64 = 10;
+
+// ============================================================================
+// TOUCH ID
+// ============================================================================
+
+/// Maximum time to wait for the Touch ID prompt to reply before giving up.
+/// Unit: seconds
+/// Recommended range: 15-60 (long enough to reach the sensor, short enough to not hang)
+pub const TOUCHID_TIMEOUT_SECS: u64 = 30;
+
+// ============================================================================
+// WATCHDOG
+// ============================================================================
+
+/// Default time the event loop may go without a heartbeat while locked before the
+/// watchdog force-unlocks.
+/// Unit: seconds
+/// Range: WATCHDOG_TIMEOUT_MIN_SECS-WATCHDOG_TIMEOUT_MAX_SECS
+pub const WATCHDOG_TIMEOUT_DEFAULT_SECS: u64 = 30;
+
+/// Minimum watchdog timeout.
+/// Unit: seconds
+/// Range: Fixed, well above CFRUNLOOP_POLL_INTERVAL_MS to avoid false alarms
+pub const WATCHDOG_TIMEOUT_MIN_SECS: u64 = 10;
+
+/// Maximum watchdog timeout.
+/// Unit: seconds
+/// Range: Fixed (5 minutes)
+pub const WATCHDOG_TIMEOUT_MAX_SECS: u64 = 300;
+
+// ============================================================================
+// CONTROL SOCKET
+// ============================================================================
+
+/// File name of the control socket (placed in $XDG_RUNTIME_DIR or the temp dir).
+/// Unit: file name
+/// Range: Fixed
+pub const IPC_SOCKET_FILE_NAME: &str = "handsoff.sock";
+
+/// Control socket file permissions (owner only, so other users can't drive HandsOff).
+/// Unit: Unix permission bits (octal)
+/// Range: Fixed, do not loosen
+pub const IPC_SOCKET_PERMISSIONS: u32 = 0o600;
+
+/// Permissions of the private directory the control socket is bound in before it is
+/// moved into place (so it is never reachable with looser permissions).
+/// Unit: Unix permission bits (octal)
+/// Range: Fixed, do not loosen
+pub const IPC_SOCKET_DIR_PERMISSIONS: u32 = 0o700;
+
+/// How long a client may stay idle on the control socket before being disconnected.
+/// Unit: seconds
+/// Recommended range: 2-30
+pub const IPC_READ_TIMEOUT_SECS: u64 = 5;
+
+/// Longest command line accepted on the control socket (a 256-character passphrase
+/// needs up to 1 KiB of UTF-8).
+/// Unit: bytes
+/// Range: Fixed, at least 4 x MAX_BUFFER_LEN_DEFAULT plus the command name
+pub const IPC_MAX_COMMAND_BYTES: usize = 2048;
+
+/// Failed `unlock` attempts over the control socket before further attempts are refused.
+/// Unit: attempts
+/// Recommended range: 3-10
+pub const IPC_MAX_FAILED_UNLOCKS: u32 = 5;
+
+/// How long `unlock` is refused after too many failed attempts.
+/// Unit: seconds
+/// Recommended range: 30-300
+pub const IPC_UNLOCK_LOCKOUT_SECS: u64 = 60;
+
+/// Shortest accepted `http_api_token` (guessable tokens would expose the HTTP API).
+/// Unit: characters
+/// Recommended range: 16-64
+pub const HTTP_API_MIN_TOKEN_LEN: usize = 16;
+
+/// How long an HTTP API client may take to send its request before being disconnected.
+/// Unit: seconds
+/// Recommended range: 2-30 (connections are served one at a time)
+pub const HTTP_API_READ_TIMEOUT_SECS: u64 = 5;
+
+/// Largest HTTP API request (request line and headers) that is read.
+/// Unit: bytes
+/// Recommended range: 1024-16384
+pub const HTTP_API_MAX_REQUEST_BYTES: usize = 8192;
+
+/// File name of the published status snapshot (next to the control socket).
+/// Unit: file name
+/// Range: Fixed
+pub const STATUS_FILE_NAME: &str = "handsoff-status.json";
+
+/// How often the running instance publishes its status snapshot.
+/// Unit: seconds
+/// Recommended range: 1-10 (readers adjust countdowns for the snapshot's age)
+pub const STATUS_PUBLISH_INTERVAL_SECS: u64 = 5;
+
+/// Snapshots older than this are treated as coming from an instance that is no longer running.
+/// Unit: seconds
+/// Recommended range: 3-5x STATUS_PUBLISH_INTERVAL_SECS
+pub const STATUS_STALE_SECS: u64 = 20;
+
+// ============================================================================
+// LOG FILE
+// ============================================================================
+
+/// Size at which the log file is rotated.
+/// Unit: bytes
+/// Recommended range: 1-50 MiB
+pub const LOG_FILE_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Number of rotated log files kept (log.1 ... log.N).
+/// Unit: count (0 = discard the old log on rotation)
+/// Recommended range: 1-10
+pub const LOG_FILE_BACKUPS: usize = 3;
+
 // ============================================================================
 // POLLING & THREAD INTERVALS
 // ============================================================================
@@ -63,26 +336,84 @@ pub const BUFFER_RESET_DEFAULT_SECONDS: u64 = 3;
 /// Recommended range: 100-1000 (lower = more responsive, higher = less CPU)
 pub const CFRUNLOOP_POLL_INTERVAL_MS: u64 = 500;
 
-/// Buffer reset thread check interval.
+// Scheduler thread checks run about half the time left until their next deadline,
+// between a minimum and a maximum interval (see utils::poll_interval).
+
+/// Buffer reset check interval near a reset deadline.
 /// Unit: milliseconds
 /// Recommended range: 100-500 (must be < BUFFER_RESET_DEFAULT_SECONDS * 1000)
 pub const BUFFER_RESET_CHECK_INTERVAL_MS: u64 = 250;
 
-/// Auto-lock state monitoring interval.
+/// Buffer reset check interval while the buffer is empty or HandsOff is disabled.
+/// Unit: milliseconds
+/// Range: Fixed, below BUFFER_RESET_MIN_SECONDS * 1000
+pub const BUFFER_RESET_CHECK_MAX_MS: u64 = 1000;
+
+/// Shortest check interval of the auto-lock and auto-unlock checks (near a deadline).
+/// Unit: seconds
+/// Recommended range: 1-2
+pub const TIMEOUT_CHECK_MIN_SECS: u64 = 1;
+
+/// Auto-lock state monitoring interval while no auto-lock countdown is running.
 /// Unit: seconds
 /// Recommended range: 1-10 (balance between responsiveness and CPU usage)
 pub const AUTO_LOCK_CHECK_INTERVAL_SECS: u64 = 5;
 
-/// Auto-unlock state monitoring interval.
+/// Longest auto-lock check interval (far from auto-lock, or disabled).
+/// Unit: seconds
+/// Range: Fixed, below AUTO_LOCK_MIN_SECONDS
+pub const AUTO_LOCK_CHECK_MAX_SECS: u64 = 15;
+
+/// Auto-unlock state monitoring interval while unlocked.
 /// Unit: seconds
 /// Recommended range: 5-30 (less critical, can be longer)
 pub const AUTO_UNLOCK_CHECK_INTERVAL_SECS: u64 = 10;
 
+/// Longest auto-unlock check interval (far from auto-unlock/hard cap, or disabled).
+/// Unit: seconds
+/// Range: Fixed, below AUTO_UNLOCK_MIN_SECONDS
+pub const AUTO_UNLOCK_CHECK_MAX_SECS: u64 = 30;
+
+/// Lock schedule check interval (how often the wall clock is compared to lock windows).
+/// Unit: seconds
+/// Recommended range: 15-60 (windows are minute-granular)
+pub const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Watchdog check interval (how often the event loop heartbeat is inspected).
+/// Unit: seconds
+/// Recommended range: 1-10 (must be well below WATCHDOG_TIMEOUT_MIN_SECS)
+pub const WATCHDOG_CHECK_INTERVAL_SECS: u64 = 5;
+
 /// Accessibility permission check interval.
 /// Unit: seconds
 /// Recommended range: 10-60 (infrequent check, permission rarely changes)
 pub const PERMISSION_CHECK_INTERVAL_SECS: u64 = 15;
 
+/// Accessibility permission check interval while HandsOff is disabled (no event tap).
+/// Unit: seconds
+/// Recommended range: 30-120
+pub const PERMISSION_CHECK_DISABLED_INTERVAL_SECS: u64 = 60;
+
+/// Consecutive negative permission checks before the CLI exits on permission loss.
+/// Input blocking stops on the first one; the exit waits so a momentary blip (e.g.
+/// while System Settings updates the Accessibility list) doesn't end the process.
+/// Unit: checks (one per CFRUNLOOP_POLL_INTERVAL_MS)
+/// Recommended range: 2-4
+pub const PERMISSION_LOSS_CONFIRMATIONS: u32 = 2;
+
+/// Consecutive positive permission checks before the event tap is restarted after
+/// permissions come back. Toggling the Accessibility switch in System Settings can
+/// flap, and each restart creates a new tap (WindowServer work), so a restore must
+/// hold before acting on it. Stopping on loss stays immediate.
+/// Unit: checks (one per PERMISSION_CHECK_INTERVAL_SECS)
+/// Recommended range: 2-3
+pub const PERMISSION_RESTORE_CONFIRMATIONS: u32 = 2;
+
+/// Minimum time between event tap restarts requested by the permission monitor.
+/// Unit: seconds
+/// Recommended range: 15-120 (at least one PERMISSION_CHECK_INTERVAL_SECS)
+pub const EVENT_TAP_RESTART_MIN_INTERVAL_SECS: u64 = 30;
+
 /// Tray app polling interval when app is disabled (low-power mode).
 /// Unit: seconds
 /// Recommended range: 1-10 (minimal activity when disabled)
@@ -99,7 +430,13 @@ pub const POLL_INTERVAL_ENABLED_MS: u64 = 500;
 /// Recommended range: 200-1000 (low enough to detect issues, high enough to avoid noise)
 pub const CALLBACK_SLOW_THRESHOLD_US: u64 = 500;
 
-/// Interval for logging callback telemetry summaries from the permission monitor thread.
+/// Minimum time between repeats of the same log message from the event tap callback
+/// (key-mashing would otherwise log every event).
+/// Unit: milliseconds
+/// Recommended range: 500-5000
+pub const HOT_PATH_LOG_INTERVAL_MS: u64 = 1000;
+
+/// Interval for logging callback telemetry summaries from the permission check.
 /// Unit: seconds
 /// Recommended range: 30-120
 pub const CALLBACK_TELEMETRY_INTERVAL_SECS: u64 = 60;
@@ -127,11 +464,24 @@ pub const REENABLE_DEBOUNCE_SECS: u64 = 10;
 /// Recommended range: 2000-5000 (long enough to read, short enough to not annoy)
 pub const NOTIFICATION_TIMEOUT_MS: u32 = 3000;
 
+/// Minimum `notification_timeout_ms` accepted from the config file.
+/// Unit: milliseconds
+pub const NOTIFICATION_TIMEOUT_MIN_MS: u32 = 1000;
+
+/// Maximum `notification_timeout_ms` accepted from the config file.
+/// Unit: milliseconds
+pub const NOTIFICATION_TIMEOUT_MAX_MS: u32 = 30000;
+
 /// Error notification display duration (longer for important messages).
 /// Unit: milliseconds
 /// Recommended range: 4000-10000 (errors need more attention)
 pub const NOTIFICATION_ERROR_TIMEOUT_MS: u32 = 5000;
 
+/// Display duration for critical notifications (accessibility permissions lost).
+/// Unit: milliseconds
+/// Recommended range: 8000-15000 (explains why input blocking stopped)
+pub const NOTIFICATION_CRITICAL_TIMEOUT_MS: u32 = 10000;
+
 // ============================================================================
 // MACOS KEYCODES
 // ============================================================================
@@ -141,6 +491,11 @@ pub const NOTIFICATION_ERROR_TIMEOUT_MS: u32 = 5000;
 /// Range: Fixed, do not change (hardware constant)
 pub const BACKSPACE_KEYCODE: i64 = 51;
 
+/// macOS keycode for Escape (clears the passphrase buffer while locked).
+/// Unit: macOS virtual keycode
+/// Range: Fixed, do not change (hardware constant)
+pub const ESCAPE_KEYCODE: i64 = 53;
+
 /// Default lock hotkey keycode ('L' key).
 /// Unit: macOS virtual keycode
 /// Recommended: Any letter key (0-50 range)
@@ -151,6 +506,16 @@ pub const DEFAULT_LOCK_KEYCODE: i64 = 37;
 /// Recommended: Any letter key (0-50 range)
 pub const DEFAULT_TALK_KEYCODE: i64 = 17;
 
+/// macOS keycode for the spacebar (default Talk mode passthrough key).
+/// Unit: macOS virtual keycode
+/// Range: Fixed, do not change (hardware constant)
+pub const SPACEBAR_KEYCODE: i64 = 49;
+
+/// Default Touch ID unlock hotkey keycode ('U' key).
+/// Unit: macOS virtual keycode
+/// Recommended: Any letter key (0-50 range)
+pub const DEFAULT_UNLOCK_KEYCODE: i64 = 32;
+
 // ============================================================================
 // FILE PERMISSIONS
 // ============================================================================
@@ -165,6 +530,16 @@ pub const CONFIG_FILE_PERMISSIONS: u32 = 0o600;
 /// Range: Fixed, used for security validation
 pub const CONFIG_PERMISSION_MASK_GROUP_OTHER: u32 = 0o077;
 
+/// Log file permissions (owner only: logs record when input was locked and unlocked).
+/// Unit: Unix permission bits (octal)
+/// Range: Fixed, do not loosen
+pub const LOG_FILE_PERMISSIONS: u32 = 0o600;
+
+/// Audit log permissions (owner only: the audit trail records every lock and unlock).
+/// Unit: Unix permission bits (octal)
+/// Range: Fixed, do not loosen
+pub const AUDIT_LOG_PERMISSIONS: u32 = 0o600;
+
 // ============================================================================
 // CRYPTOGRAPHY
 // ============================================================================
@@ -1,7 +1,9 @@
 //! Passphrase encryption and decryption using AES-256-GCM
 //!
 //! This module provides functions to encrypt and decrypt the secret passphrase
-//! using AES-256-GCM authenticated encryption with a statically derived key.
+//! using AES-256-GCM authenticated encryption. The key is derived from a static
+//! seed mixed with a machine-specific secret (the Mac's IOPlatformUUID), so an
+//! encrypted config file cannot be decrypted on another machine.
 
 use crate::constants::NONCE_LENGTH_BYTES;
 use aes_gcm::{
@@ -11,20 +13,117 @@ use aes_gcm::{
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
 
-/// Static seed for key derivation (consistent across all builds/versions)
+/// Seed for machine-bound key derivation (combined with the machine secret)
+const KEY_SEED: &str = "com.handsoff.inputlock.config.encryption.v2";
+
+/// Legacy static seed used by configs written before keys were machine-bound
+///
+/// Only used as a decryption fallback so existing config files keep working.
+const LEGACY_KEY_SEED: &str = "com.handsoff.inputlock.config.encryption.v1";
+
+/// Fixed machine secret used by unit tests (no hardware lookup)
+#[cfg(test)]
+const TEST_MACHINE_SECRET: &str = "00000000-TEST-MACHINE-SECRET-000000000000";
+
+/// Cached machine secret (read once per process)
+static MACHINE_SECRET: OnceLock<String> = OnceLock::new();
+
+/// Establish and cache the machine secret used for key derivation
 ///
-/// This ensures that config files remain portable across different versions
-/// and builds of the application.
-const KEY_SEED: &str = "com.handsoff.inputlock.config.encryption.v1";
+/// Called at startup so that a missing machine secret is reported early. Encryption
+/// and decryption call this lazily as well, so calling it is optional.
+pub fn init_key() -> Result<()> {
+    machine_secret().map(|_| ())
+}
+
+fn machine_secret() -> Result<&'static str> {
+    if let Some(secret) = MACHINE_SECRET.get() {
+        return Ok(secret);
+    }
+
+    #[cfg(test)]
+    let secret = TEST_MACHINE_SECRET.to_string();
+    #[cfg(not(test))]
+    let secret = read_platform_uuid().context("Failed to read machine identifier for config encryption")?;
+
+    Ok(MACHINE_SECRET.get_or_init(|| secret))
+}
+
+/// Read the hardware IOPlatformUUID via IOKit
+#[cfg(not(test))]
+fn read_platform_uuid() -> Result<String> {
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::string::CFString;
+    use std::ffi::{c_char, c_void};
+
+    type IoObject = u32;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> IoObject;
+        fn IORegistryEntryCreateCFProperty(
+            entry: IoObject,
+            key: *const c_void,
+            allocator: *const c_void,
+            options: u32,
+        ) -> *const c_void;
+        fn IOObjectRelease(object: IoObject) -> i32;
+    }
 
-/// Derive 32-byte AES-256 key from static seed
+    // kIOMainPortDefault is MACH_PORT_NULL
+    const IO_MAIN_PORT_DEFAULT: u32 = 0;
+
+    unsafe {
+        // IOServiceGetMatchingService consumes the matching dictionary reference
+        let matching = IOServiceMatching(c"IOPlatformExpertDevice".as_ptr());
+        if matching.is_null() {
+            anyhow::bail!("IOServiceMatching(IOPlatformExpertDevice) failed");
+        }
+        let service = IOServiceGetMatchingService(IO_MAIN_PORT_DEFAULT, matching);
+        if service == 0 {
+            anyhow::bail!("IOPlatformExpertDevice service not found");
+        }
+
+        let key = CFString::new("IOPlatformUUID");
+        let property = IORegistryEntryCreateCFProperty(
+            service,
+            key.as_concrete_TypeRef() as *const c_void,
+            std::ptr::null(),
+            0,
+        );
+        IOObjectRelease(service);
+
+        if property.is_null() {
+            anyhow::bail!("IOPlatformUUID property not found");
+        }
+        let property = CFType::wrap_under_create_rule(property as _);
+        let uuid = property
+            .downcast::<CFString>()
+            .context("IOPlatformUUID is not a string")?
+            .to_string();
+        Ok(uuid)
+    }
+}
+
+/// Derive 32-byte AES-256 key from the seed and a machine secret
 ///
-/// Uses SHA-256 to generate a deterministic key from the constant seed.
-/// This ensures the same key is used across all builds and versions.
-fn derive_key() -> [u8; 32] {
+/// Uses SHA-256 over seed || secret, so the same machine always derives the
+/// same key while other machines derive a different one.
+fn derive_key(machine_secret: &str) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(KEY_SEED.as_bytes());
+    hasher.update(machine_secret.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Derive the legacy (machine-independent) key from the v1 static seed
+fn derive_legacy_key() -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(LEGACY_KEY_SEED.as_bytes());
     hasher.finalize().into()
 }
 
@@ -42,7 +141,11 @@ fn derive_key() -> [u8; 32] {
 ///
 /// Returns an error if random number generation or encryption fails.
 pub fn encrypt_passphrase(plaintext: &str) -> Result<String> {
-    let key = derive_key();
+    encrypt_with_secret(plaintext, machine_secret()?)
+}
+
+fn encrypt_with_secret(plaintext: &str, machine_secret: &str) -> Result<String> {
+    let key = derive_key(machine_secret);
     let cipher = Aes256Gcm::new(&key.into());
 
     // Generate random nonce
@@ -73,15 +176,48 @@ pub fn encrypt_passphrase(plaintext: &str) -> Result<String> {
 ///
 /// # Returns
 ///
-/// The decrypted plaintext passphrase
+/// The decrypted plaintext passphrase (scrubbed from memory when dropped)
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - Base64 decoding fails
 /// - Data is too short (< NONCE_LENGTH_BYTES)
-/// - Decryption fails (wrong key, corrupted data, or failed authentication)
-pub fn decrypt_passphrase(encrypted: &str) -> Result<String> {
+/// - Decryption fails (config copied from another machine, corrupted data, or
+///   failed authentication)
+pub fn decrypt_passphrase(encrypted: &str) -> Result<Zeroizing<String>> {
+    decrypt_with_secret(encrypted, machine_secret()?)
+}
+
+fn decrypt_with_secret(encrypted: &str, machine_secret: &str) -> Result<Zeroizing<String>> {
+    let (plaintext, legacy) = decrypt_detecting_legacy(encrypted, machine_secret)?;
+    if legacy {
+        log::warn!("Passphrase was encrypted with the legacy static key - run setup again to bind it to this machine");
+    }
+    Ok(plaintext)
+}
+
+/// Re-encrypt a passphrase written with the legacy static key under the machine-bound key
+///
+/// Returns None when `encrypted` already uses the machine-bound key. Fails like
+/// `decrypt_passphrase` when it can't be decrypted at all.
+pub fn reencrypt_if_legacy(encrypted: &str) -> Result<Option<String>> {
+    reencrypt_if_legacy_with_secret(encrypted, machine_secret()?)
+}
+
+fn reencrypt_if_legacy_with_secret(encrypted: &str, machine_secret: &str) -> Result<Option<String>> {
+    let (plaintext, legacy) = decrypt_detecting_legacy(encrypted, machine_secret)?;
+    if !legacy {
+        return Ok(None);
+    }
+    encrypt_with_secret(&plaintext, machine_secret).map(Some)
+}
+
+/// Decrypt, also returning whether the legacy static key was needed
+fn decrypt_detecting_legacy(
+    encrypted: &str,
+    machine_secret: &str,
+) -> Result<(Zeroizing<String>, bool)> {
     // Decode base64
     let data = BASE64
         .decode(encrypted)
@@ -97,15 +233,39 @@ pub fn decrypt_passphrase(encrypted: &str) -> Result<String> {
         nonce_bytes.try_into().context("Invalid nonce length")?;
     let nonce = &nonce_array.into();
 
-    // Decrypt
-    let key = derive_key();
-    let cipher = Aes256Gcm::new(&key.into());
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|e| anyhow::anyhow!("Decryption failed: {}", e))?;
+    // Decrypt with the machine-bound key, falling back to the legacy static key
+    // for config files written by older versions
+    let cipher = Aes256Gcm::new(&derive_key(machine_secret).into());
+    let (plaintext, legacy) = match cipher.decrypt(nonce, ciphertext) {
+        Ok(plaintext) => (plaintext, false),
+        Err(_) => {
+            let legacy_cipher = Aes256Gcm::new(&derive_legacy_key().into());
+            let plaintext = legacy_cipher.decrypt(nonce, ciphertext).map_err(|_| {
+                anyhow::anyhow!(
+                    "Decryption failed: the config file was encrypted on a different machine or is corrupted. \
+                     Run setup again on this machine to recreate it."
+                )
+            })?;
+            (plaintext, true)
+        }
+    };
 
-    // Convert to string
-    String::from_utf8(plaintext).context("Invalid UTF-8 in decrypted data")
+    // Convert to string (the plaintext bytes move into the Zeroizing wrapper)
+    let plaintext = String::from_utf8(plaintext)
+        .map(Zeroizing::new)
+        .context("Invalid UTF-8 in decrypted data")?;
+    Ok((plaintext, legacy))
+}
+
+/// Encrypt with the legacy static key, as versions before machine-bound keys did
+#[cfg(test)]
+pub(crate) fn encrypt_with_legacy_key(plaintext: &str) -> String {
+    let cipher = Aes256Gcm::new(&derive_legacy_key().into());
+    let nonce_bytes = [7u8; NONCE_LENGTH_BYTES];
+    let ciphertext = cipher.encrypt(&nonce_bytes.into(), plaintext.as_bytes()).unwrap();
+    let mut data = nonce_bytes.to_vec();
+    data.extend_from_slice(&ciphertext);
+    BASE64.encode(&data)
 }
 
 #[cfg(test)]
@@ -117,7 +277,7 @@ mod tests {
         let original = "my_secret_passphrase_123";
         let encrypted = encrypt_passphrase(original).expect("Encryption failed");
         let decrypted = decrypt_passphrase(&encrypted).expect("Decryption failed");
-        assert_eq!(original, decrypted);
+        assert_eq!(original, *decrypted);
     }
 
     #[test]
@@ -132,8 +292,8 @@ mod tests {
         // But both should decrypt to the same plaintext
         let decrypted1 = decrypt_passphrase(&encrypted1).expect("Decryption 1 failed");
         let decrypted2 = decrypt_passphrase(&encrypted2).expect("Decryption 2 failed");
-        assert_eq!(plaintext, decrypted1);
-        assert_eq!(plaintext, decrypted2);
+        assert_eq!(plaintext, *decrypted1);
+        assert_eq!(plaintext, *decrypted2);
     }
 
     #[test]
@@ -155,15 +315,63 @@ mod tests {
 
     #[test]
     fn test_static_key_consistency() {
-        // Key derivation should be deterministic
-        let key1 = derive_key();
-        let key2 = derive_key();
+        // Key derivation should be deterministic for the same machine secret
+        let key1 = derive_key(TEST_MACHINE_SECRET);
+        let key2 = derive_key(TEST_MACHINE_SECRET);
         assert_eq!(key1, key2, "Key derivation must be deterministic");
 
         // Verify key is exactly 32 bytes for AES-256
         assert_eq!(key1.len(), 32, "Key must be 32 bytes for AES-256");
     }
 
+    #[test]
+    fn test_key_differs_per_machine() {
+        let key_a = derive_key("machine-a");
+        let key_b = derive_key("machine-b");
+        assert_ne!(key_a, key_b, "Different machines must derive different keys");
+        assert_ne!(key_a, derive_legacy_key());
+    }
+
+    #[test]
+    fn test_roundtrip_with_stubbed_machine_secret() {
+        let encrypted = encrypt_with_secret("portable?", "machine-a").unwrap();
+        let decrypted = decrypt_with_secret(&encrypted, "machine-a").unwrap();
+        assert_eq!(*decrypted, "portable?");
+    }
+
+    #[test]
+    fn test_decrypt_on_other_machine_fails_with_clear_error() {
+        let encrypted = encrypt_with_secret("secret", "machine-a").unwrap();
+
+        let err = decrypt_with_secret(&encrypted, "machine-b").unwrap_err();
+        assert!(
+            err.to_string().contains("different machine"),
+            "Error should explain the machine mismatch: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_legacy_config_still_decrypts() {
+        // Simulate a config written before keys were machine-bound
+        let legacy = encrypt_with_legacy_key("old");
+        let decrypted = decrypt_with_secret(&legacy, "any-machine").unwrap();
+        assert_eq!(*decrypted, "old");
+    }
+
+    #[test]
+    fn test_reencrypt_if_legacy() {
+        let legacy = encrypt_with_legacy_key("old");
+        let reencrypted = reencrypt_if_legacy_with_secret(&legacy, "machine-a")
+            .unwrap()
+            .expect("Legacy ciphertext is re-encrypted");
+        assert_eq!(*decrypt_with_secret(&reencrypted, "machine-a").unwrap(), "old");
+        assert!(decrypt_with_secret(&reencrypted, "machine-b").is_err(), "Now machine-bound");
+
+        // Already machine-bound: nothing to do
+        assert_eq!(reencrypt_if_legacy_with_secret(&reencrypted, "machine-a").unwrap(), None);
+    }
+
     #[test]
     fn test_invalid_base64() {
         let result = decrypt_passphrase("not-valid-base64!!!");
@@ -183,7 +391,7 @@ mod tests {
         let empty = "";
         let encrypted = encrypt_passphrase(empty).expect("Should encrypt empty string");
         let decrypted = decrypt_passphrase(&encrypted).expect("Should decrypt empty string");
-        assert_eq!(empty, decrypted);
+        assert_eq!(empty, *decrypted);
     }
 
     #[test]
@@ -191,6 +399,6 @@ mod tests {
         let unicode = "🔒 Secure パスワード 密码 🔐";
         let encrypted = encrypt_passphrase(unicode).expect("Should encrypt unicode");
         let decrypted = decrypt_passphrase(&encrypted).expect("Should decrypt unicode");
-        assert_eq!(unicode, decrypted);
+        assert_eq!(unicode, *decrypted);
     }
 }
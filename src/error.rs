@@ -0,0 +1,120 @@
+//! Typed errors for the `HandsOffCore` lock and startup API
+//!
+//! Library consumers can match on why locking, unlocking or starting input blocking
+//! failed. Binaries keep using `anyhow`: `?` converts a `HandsOffError` (it implements
+//! `std::error::Error`), and the wrapped cause stays in the error chain, so
+//! `HotkeyRegistrationError::find` still finds a hotkey conflict.
+
+use crate::app_state::LockRefused;
+use crate::input_blocking::hotkeys::HotkeyRegistrationError;
+use std::fmt;
+
+/// Why a `HandsOffCore` operation failed
+#[derive(Debug)]
+pub enum HandsOffError {
+    /// Accessibility permissions aren't granted, so input can't be blocked
+    PermissionsMissing,
+    /// No passphrase is set, so locked input couldn't be unlocked
+    NoPassphraseConfigured,
+    /// The event tap couldn't be created
+    EventTapCreationFailed(anyhow::Error),
+    /// A global hotkey couldn't be registered (the first failure if several did)
+    HotkeyRegistrationFailed(anyhow::Error),
+    /// The passphrase was missing or didn't match
+    InvalidPassphrase,
+    /// The event tap is already running
+    AlreadyRunning,
+}
+
+impl HandsOffError {
+    /// The hotkey registration failure behind this error, if that's what it is
+    pub fn hotkey_registration_error(&self) -> Option<&HotkeyRegistrationError> {
+        match self {
+            Self::HotkeyRegistrationFailed(cause) => HotkeyRegistrationError::find(cause),
+            _ => None,
+        }
+    }
+
+    fn cause(&self) -> Option<&anyhow::Error> {
+        match self {
+            Self::EventTapCreationFailed(cause) | Self::HotkeyRegistrationFailed(cause) => {
+                Some(cause)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HandsOffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PermissionsMissing => write!(
+                f,
+                "Accessibility permissions not granted. Please enable permissions in System Settings > Privacy & Security > Accessibility"
+            ),
+            Self::NoPassphraseConfigured => write!(
+                f,
+                "No passphrase configured, so input couldn't be unlocked. Run 'handsoff --setup' to set one"
+            ),
+            Self::EventTapCreationFailed(_) => write!(f, "Failed to create event tap"),
+            Self::HotkeyRegistrationFailed(_) => write!(f, "Failed to register hotkeys"),
+            Self::InvalidPassphrase => write!(f, "Invalid passphrase"),
+            Self::AlreadyRunning => write!(f, "Event tap already running"),
+        }?;
+        // `{:#}` includes the cause, like it does for anyhow errors
+        match self.cause() {
+            Some(cause) if f.alternate() => write!(f, ": {:#}", cause),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<LockRefused> for HandsOffError {
+    fn from(refused: LockRefused) -> Self {
+        match refused {
+            LockRefused::PermissionsMissing => Self::PermissionsMissing,
+            LockRefused::NoPassphrase => Self::NoPassphraseConfigured,
+        }
+    }
+}
+
+impl std::error::Error for HandsOffError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause().map(|cause| cause.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_blocking::hotkeys::HotkeyKind;
+
+    fn conflict() -> anyhow::Error {
+        anyhow::Error::new(HotkeyRegistrationError::Conflict {
+            kind: HotkeyKind::Lock,
+            combo: "Ctrl+Cmd+Shift+L".to_string(),
+        })
+        .context("Failed to register lock hotkey")
+    }
+
+    #[test]
+    fn test_hotkey_conflict_is_found_before_and_after_conversion() {
+        let error = HandsOffError::HotkeyRegistrationFailed(conflict());
+        assert!(error.hotkey_registration_error().is_some_and(|e| e.is_conflict()));
+
+        let error = anyhow::Error::from(error);
+        assert!(HotkeyRegistrationError::find(&error).is_some_and(|e| e.is_conflict()));
+        assert!(matches!(
+            error.downcast_ref::<HandsOffError>(),
+            Some(HandsOffError::HotkeyRegistrationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_alternate_display_includes_the_cause() {
+        let error = HandsOffError::EventTapCreationFailed(anyhow::anyhow!("tap is null"));
+        assert_eq!(error.to_string(), "Failed to create event tap");
+        assert_eq!(format!("{:#}", error), "Failed to create event tap: tap is null");
+        assert_eq!(format!("{:#}", HandsOffError::AlreadyRunning), "Event tap already running");
+    }
+}
@@ -0,0 +1,129 @@
+//! Event tap health check run from the tray's event loop (`HandsOffCore::ensure_running`)
+//!
+//! Background threads can't touch the event tap; they raise stop / re-enable / start
+//! requests on `AppState` instead (permission loss, a stalled event loop, a tap macOS
+//! disabled after sleep, restored permissions). The health check consumes those
+//! requests, decides which steps are needed given whether the tap is running, and
+//! reports what it did.
+
+/// Pending event tap requests, consumed by the health check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TapRequests {
+    /// Permissions were lost or the event loop stalled
+    pub stop: bool,
+    /// macOS disabled the tap (e.g. callback timeout on sleep/wake)
+    pub reenable: bool,
+    /// Permissions were restored
+    pub start: bool,
+}
+
+/// A step the health check takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStep {
+    StopTap,
+    ReenableTap,
+    RestartTap,
+}
+
+/// What the health check did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthAction {
+    /// The tap was stopped, so input is no longer blocked
+    StoppedTap,
+    /// The existing tap was re-enabled (or restarted if it couldn't be)
+    ReenabledTap,
+    /// A new tap was started after permissions were restored
+    RestartedTap,
+    /// Re-enabling failed, along with the restart it falls back to
+    ReenableFailed(String),
+    /// Starting a new tap failed
+    RestartFailed(String),
+}
+
+/// Result of `HandsOffCore::ensure_running`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Actions taken, in order (empty when nothing was needed)
+    pub actions: Vec<HealthAction>,
+}
+
+impl HealthReport {
+    /// Whether the check found nothing to do
+    pub fn is_noop(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// Decide which steps the pending requests need
+///
+/// A stop wins over a re-enable raised in the same pass. Re-enabling a tap that isn't
+/// running means restarting it. A start request is a no-op while a tap is running
+/// with permissions, and otherwise restarts the tap (which checks permissions itself).
+pub fn plan(requests: TapRequests, tap_running: bool, has_permissions: bool) -> Vec<HealthStep> {
+    let mut steps = Vec::new();
+    let mut running = tap_running;
+
+    if requests.stop && running {
+        steps.push(HealthStep::StopTap);
+        running = false;
+    }
+    if requests.reenable && !requests.stop {
+        steps.push(if running {
+            HealthStep::ReenableTap
+        } else {
+            HealthStep::RestartTap
+        });
+        running = true;
+    }
+    let restart_planned = steps.contains(&HealthStep::RestartTap);
+    if requests.start && !restart_planned && !(running && has_permissions) {
+        steps.push(HealthStep::RestartTap);
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use HealthStep::*;
+
+    fn requests(stop: bool, reenable: bool, start: bool) -> TapRequests {
+        TapRequests { stop, reenable, start }
+    }
+
+    #[test]
+    fn test_no_requests_is_a_noop() {
+        for (running, permissions) in [(true, true), (false, false), (false, true)] {
+            assert!(plan(TapRequests::default(), running, permissions).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_stop_only_stops_a_running_tap() {
+        assert_eq!(plan(requests(true, false, false), true, false), [StopTap]);
+        assert!(plan(requests(true, false, false), false, false).is_empty());
+    }
+
+    #[test]
+    fn test_reenable_restarts_a_missing_tap() {
+        assert_eq!(plan(requests(false, true, false), true, true), [ReenableTap]);
+        assert_eq!(plan(requests(false, true, false), false, true), [RestartTap]);
+        assert_eq!(plan(requests(true, true, false), true, false), [StopTap], "Stop wins");
+    }
+
+    #[test]
+    fn test_start_restarts_only_when_needed() {
+        assert_eq!(plan(requests(false, false, true), false, true), [RestartTap]);
+        assert_eq!(
+            plan(requests(false, false, true), false, false),
+            [RestartTap],
+            "The cached permission state may lag behind the restore"
+        );
+        assert!(plan(requests(false, false, true), true, true).is_empty(), "Already alive");
+        assert_eq!(plan(requests(false, false, true), true, false), [RestartTap]);
+
+        // Stopped and restored in the same pass, or restarted once for two requests
+        assert_eq!(plan(requests(true, false, true), true, true), [StopTap, RestartTap]);
+        assert_eq!(plan(requests(false, true, true), false, true), [RestartTap]);
+    }
+}
@@ -0,0 +1,362 @@
+//! Minimal HTTP control endpoint for home-automation dashboards
+//!
+//! When `http_api_port` is set, a tiny HTTP/1.1 server listens on 127.0.0.1 only and
+//! answers:
+//!
+//! - `GET /status`: the current `StatusSnapshot` (same JSON as the control socket)
+//! - `POST /lock`: lock input
+//! - `POST /disable`: enter minimal CPU mode (refused while locked)
+//! - `GET /metrics`: counters in the Prometheus text format (see `metrics`)
+//!
+//! Every request needs `Authorization: Bearer <http_api_token>`. Unlocking is
+//! deliberately not offered over HTTP. One request is served per connection, one
+//! connection at a time, on a background thread.
+
+use crate::app_state::AppState;
+use crate::constants::{
+    HTTP_API_MAX_REQUEST_BYTES, HTTP_API_MIN_TOKEN_LEN, HTTP_API_READ_TIMEOUT_SECS,
+};
+use crate::ipc;
+use crate::metrics::PROMETHEUS_CONTENT_TYPE;
+use crate::utils;
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener};
+use std::thread;
+use std::time::Duration;
+
+/// The parts of an HTTP request the API looks at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    /// Value of the `Authorization` header, if any
+    pub authorization: Option<String>,
+}
+
+/// Response sent back to the client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    /// JSON body (Prometheus text for `/metrics`)
+    pub body: String,
+}
+
+impl HttpResponse {
+    fn json(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, format!(r#"{{"ok":false,"error":"{}"}}"#, message))
+    }
+
+    /// Serialize as an HTTP/1.1 response (the connection is closed afterwards)
+    pub fn to_http(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            _ => "Error",
+        };
+        let challenge = if self.status == 401 {
+            "WWW-Authenticate: Bearer\r\n"
+        } else {
+            ""
+        };
+        format!(
+            "HTTP/1.1 {} {}\r\n{}Content-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            challenge,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+/// Parse the request line and headers (any body is ignored)
+pub fn parse_request<R: BufRead>(reader: R) -> Result<HttpRequest> {
+    // Bound what a client can make us buffer
+    let mut lines = reader.take(HTTP_API_MAX_REQUEST_BYTES as u64).lines();
+
+    let request_line = lines.next().context("Empty request")??;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        anyhow::bail!("Malformed request line");
+    };
+    // Query strings aren't used by any endpoint
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut authorization = None;
+    for line in lines {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    Ok(HttpRequest {
+        method: method.to_string(),
+        path,
+        authorization,
+    })
+}
+
+/// Check the bearer token (compared via hashes, in constant time)
+fn is_authorized(request: &HttpRequest, token_hash: &str) -> bool {
+    let provided = request
+        .authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    provided.is_some_and(|token| utils::verify_passphrase(token, token_hash))
+}
+
+/// Route an authenticated request to the matching control command
+pub fn route(request: &HttpRequest, token_hash: &str, state: &AppState) -> HttpResponse {
+    if !is_authorized(request, token_hash) {
+        return HttpResponse::error(401, "unauthorized");
+    }
+
+    if request.path == "/metrics" {
+        if request.method != "GET" {
+            return HttpResponse::error(405, "method not allowed");
+        }
+        return HttpResponse {
+            status: 200,
+            content_type: PROMETHEUS_CONTENT_TYPE,
+            body: state.metrics_snapshot().to_prometheus(),
+        };
+    }
+
+    let method = match request.path.as_str() {
+        "/status" => "GET",
+        "/lock" | "/disable" => "POST",
+        _ => return HttpResponse::error(404, "not found"),
+    };
+    if request.method != method {
+        return HttpResponse::error(405, "method not allowed");
+    }
+
+    // Same semantics as the control socket; `unlock` is never routed here
+    let result = match request.path.as_str() {
+        "/lock" => ipc::lock_command(state, "HTTP API").map(|()| ipc::status_json(state)),
+        "/disable" => ipc::disable_command(state, "HTTP API").map(|()| ipc::disabled_json(true)),
+        _ => Ok(ipc::status_json(state)),
+    };
+    match result {
+        Ok(body) => HttpResponse::json(200, body),
+        Err(e) => HttpResponse::error(409, &e.to_string()),
+    }
+}
+
+/// Read one request from a connection and write the response
+pub fn handle_connection<S: Read + Write>(
+    mut stream: S,
+    token_hash: &str,
+    state: &AppState,
+) -> Result<()> {
+    let response = match parse_request(BufReader::new(&mut stream)) {
+        Ok(request) => {
+            let response = route(&request, token_hash, state);
+            debug!("HTTP {} {} -> {}", request.method, request.path, response.status);
+            response
+        }
+        Err(e) => {
+            debug!("Bad HTTP request: {}", e);
+            HttpResponse::error(400, "bad request")
+        }
+    };
+    stream.write_all(response.to_http().as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Bind 127.0.0.1:`port` and serve the API on a background thread
+pub fn start_server(state: AppState, port: u16, token: &str) -> Result<()> {
+    if token.chars().count() < HTTP_API_MIN_TOKEN_LEN {
+        anyhow::bail!(
+            "http_api_token must be at least {} characters",
+            HTTP_API_MIN_TOKEN_LEN
+        );
+    }
+    let token_hash = utils::hash_passphrase(token);
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .with_context(|| format!("Failed to bind HTTP API to 127.0.0.1:{}", port))?;
+    info!("HTTP API listening at http://127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let timeout = Some(Duration::from_secs(HTTP_API_READ_TIMEOUT_SECS));
+                    if let Err(e) = stream.set_read_timeout(timeout) {
+                        warn!("Failed to set HTTP API read timeout: {}", e);
+                        continue;
+                    }
+                    if let Err(e) = handle_connection(stream, &token_hash, &state) {
+                        debug!("HTTP API connection closed: {}", e);
+                    }
+                }
+                Err(e) => warn!("HTTP API accept failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::StatusSnapshot;
+    use crate::utils::fake_stream::FakeStream;
+    use std::io::Cursor;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    fn request(method: &str, path: &str, token: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            authorization: token.map(|t| format!("Bearer {}", t)),
+        }
+    }
+
+    fn send(state: &AppState, method: &str, path: &str, token: Option<&str>) -> HttpResponse {
+        route(&request(method, path, token), &utils::hash_passphrase(TOKEN), state)
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let raw = "POST /lock?now=1 HTTP/1.1\r\nHost: localhost\r\nauthorization:  Bearer abc \r\n\r\nbody";
+        let parsed = parse_request(Cursor::new(raw)).unwrap();
+        assert_eq!(parsed, request("POST", "/lock", Some("abc")));
+
+        let parsed = parse_request(Cursor::new("GET /status HTTP/1.1\r\n\r\n")).unwrap();
+        assert_eq!(parsed.authorization, None);
+
+        assert!(parse_request(Cursor::new("")).is_err());
+        assert!(parse_request(Cursor::new("GET\r\n\r\n")).is_err());
+    }
+
+    #[test]
+    fn test_requests_need_the_token() {
+        let state = AppState::new();
+        assert_eq!(send(&state, "POST", "/lock", None).status, 401);
+        assert_eq!(send(&state, "POST", "/lock", Some("wrong-token-12345")).status, 401);
+        assert!(!state.is_locked());
+
+        let basic = HttpRequest {
+            authorization: Some(format!("Basic {}", TOKEN)),
+            ..request("GET", "/status", None)
+        };
+        assert_eq!(route(&basic, &utils::hash_passphrase(TOKEN), &state).status, 401);
+    }
+
+    /// Unlocked, with permissions and a passphrase, so it can lock
+    fn lockable_state() -> AppState {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("secret"));
+        state
+    }
+
+    #[test]
+    fn test_status_lock_and_disable() {
+        let state = lockable_state();
+        let status = send(&state, "GET", "/status", Some(TOKEN));
+        assert_eq!(status.status, 200);
+        assert!(!StatusSnapshot::from_json(&status.body).unwrap().locked);
+
+        let lock = send(&state, "POST", "/lock", Some(TOKEN));
+        assert_eq!(lock.status, 200);
+        assert!(state.is_locked());
+
+        // Same rule as the control socket: no disabling while locked
+        let disable = send(&state, "POST", "/disable", Some(TOKEN));
+        assert_eq!(disable.status, 409);
+        assert!(!state.should_disable_and_clear());
+
+        state.set_locked(false);
+        assert_eq!(send(&state, "POST", "/disable", Some(TOKEN)).status, 200);
+        assert!(state.should_disable_and_clear());
+    }
+
+    #[test]
+    fn test_lock_refused_without_passphrase() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        let lock = send(&state, "POST", "/lock", Some(TOKEN));
+        assert_eq!(lock.status, 409);
+        assert_eq!(lock.body, r#"{"ok":false,"error":"no passphrase configured"}"#);
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_metrics() {
+        let state = AppState::new();
+        assert_eq!(send(&state, "GET", "/metrics", None).status, 401);
+        assert_eq!(send(&state, "POST", "/metrics", Some(TOKEN)).status, 405);
+
+        state.set_locked(true);
+        state.record_blocked_event();
+        state.record_blocked_event();
+        let metrics = send(&state, "GET", "/metrics", Some(TOKEN));
+        assert_eq!(metrics.status, 200);
+        assert_eq!(metrics.content_type, PROMETHEUS_CONTENT_TYPE);
+        assert!(metrics.body.contains("\nhandsoff_locks_total 1\n"));
+        assert!(metrics.body.contains("\nhandsoff_events_blocked_total 2\n"));
+        assert!(metrics.body.contains("\nhandsoff_locked 1\n"));
+        let http = metrics.to_http();
+        assert!(http.contains("Content-Type: text/plain; version=0.0.4\r\n"));
+    }
+
+    #[test]
+    fn test_unknown_routes_and_methods() {
+        let state = AppState::new();
+        assert_eq!(send(&state, "POST", "/unlock", Some(TOKEN)).status, 404);
+        assert_eq!(send(&state, "POST", "/enable", Some(TOKEN)).status, 404);
+        assert_eq!(send(&state, "GET", "/lock", Some(TOKEN)).status, 405);
+        assert_eq!(send(&state, "POST", "/status", Some(TOKEN)).status, 405);
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_handle_connection_writes_http_response() {
+        let state = AppState::new();
+        let raw = format!("GET /status HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", TOKEN);
+        let mut stream = FakeStream::new(&raw);
+        handle_connection(&mut stream, &utils::hash_passphrase(TOKEN), &state).unwrap();
+
+        let response = stream.output_text();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(response.contains(&format!("Content-Length: {}\r\n", body.len())));
+        assert!(body.starts_with(r#"{"ok":true,"#));
+    }
+
+    #[test]
+    fn test_short_token_refused() {
+        let error = start_server(AppState::new(), 0, "short").unwrap_err();
+        assert!(error.to_string().contains("at least"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Application allowlist: apps that stay interactive while locked
+//!
+//! Events targeting an allowlisted app (matched by bundle identifier) pass through
+//! the event tap even while locked. The target process ID comes from the event
+//! (kCGEventTargetUnixProcessID) and is resolved to a bundle identifier via
+//! `NSRunningApplication`; results are cached per PID so the Objective-C lookup
+//! happens at most once per process.
+
+use crate::app_state::AppState;
+use std::ffi::{c_char, c_void, CStr};
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+
+/// Upper bound on cached PID lookups (cleared when exceeded)
+const BUNDLE_ID_CACHE_MAX_ENTRIES: usize = 256;
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+}
+
+// Ensure NSRunningApplication is registered with the Objective-C runtime
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+/// Check whether a bundle identifier is on the allowlist (case insensitive, exact match)
+pub fn matches(allowlist: &[String], bundle_id: &str) -> bool {
+    let bundle_id = bundle_id.trim();
+    !bundle_id.is_empty()
+        && allowlist
+            .iter()
+            .any(|allowed| allowed.trim().eq_ignore_ascii_case(bundle_id))
+}
+
+/// Check whether events targeting `pid` should bypass the lock
+pub fn is_pid_allowlisted(state: &AppState, pid: i64) -> bool {
+    is_pid_allowlisted_with(state, pid, bundle_id_for_pid)
+}
+
+/// Allowlist check with an injectable PID -> bundle identifier resolver (for tests)
+pub fn is_pid_allowlisted_with<F>(state: &AppState, pid: i64, resolve: F) -> bool
+where
+    F: FnOnce(i64) -> Option<String>,
+{
+    if pid <= 0 {
+        return false;
+    }
+
+    let cached = {
+        let inner = state.lock();
+        if inner.app_allowlist.is_empty() {
+            return false;
+        }
+        inner.bundle_id_cache.get(&pid).cloned()
+    };
+
+    // Resolve outside the state lock so the Objective-C call never blocks other threads
+    let bundle_id = match cached {
+        Some(bundle_id) => bundle_id,
+        None => {
+            let bundle_id = resolve(pid);
+            let mut inner = state.lock();
+            if inner.bundle_id_cache.len() >= BUNDLE_ID_CACHE_MAX_ENTRIES {
+                inner.bundle_id_cache.clear();
+            }
+            inner.bundle_id_cache.insert(pid, bundle_id.clone());
+            bundle_id
+        }
+    };
+
+    match bundle_id {
+        Some(bundle_id) => matches(&state.get_app_allowlist(), &bundle_id),
+        None => false,
+    }
+}
+
+/// Resolve a process ID to its bundle identifier via NSRunningApplication
+fn bundle_id_for_pid(pid: i64) -> Option<String> {
+    unsafe {
+        let class = objc_getClass(c"NSRunningApplication".as_ptr());
+        if class.is_null() {
+            return None;
+        }
+
+        let app_for_pid: unsafe extern "C" fn(Id, Sel, i32) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let app = app_for_pid(
+            class,
+            sel_registerName(c"runningApplicationWithProcessIdentifier:".as_ptr()),
+            pid as i32,
+        );
+        if app.is_null() {
+            return None;
+        }
+
+        let get_object: unsafe extern "C" fn(Id, Sel) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let bundle_id = get_object(app, sel_registerName(c"bundleIdentifier".as_ptr()));
+        if bundle_id.is_null() {
+            return None;
+        }
+
+        let get_utf8: unsafe extern "C" fn(Id, Sel) -> *const c_char =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let utf8 = get_utf8(bundle_id, sel_registerName(c"UTF8String".as_ptr()));
+        if utf8.is_null() {
+            return None;
+        }
+
+        Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn allowlist(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matches_exact_case_insensitive() {
+        let list = allowlist(&["org.videolan.vlc", "com.apple.QuickTimePlayerX"]);
+        assert!(matches(&list, "org.videolan.vlc"));
+        assert!(matches(&list, "com.apple.quicktimeplayerx"));
+        assert!(!matches(&list, "org.videolan"));
+        assert!(!matches(&list, "com.apple.Terminal"));
+    }
+
+    #[test]
+    fn test_matches_empty_inputs() {
+        assert!(!matches(&[], "org.videolan.vlc"));
+        assert!(!matches(&allowlist(&["org.videolan.vlc"]), ""));
+        assert!(!matches(&allowlist(&[""]), "  "));
+    }
+
+    #[test]
+    fn test_pid_lookup_is_cached() {
+        let state = AppState::new();
+        state.set_app_allowlist(allowlist(&["org.videolan.vlc"]));
+
+        let calls = Cell::new(0);
+        let resolve = |_pid: i64| {
+            calls.set(calls.get() + 1);
+            Some("org.videolan.vlc".to_string())
+        };
+
+        assert!(is_pid_allowlisted_with(&state, 42, resolve));
+        assert!(is_pid_allowlisted_with(&state, 42, resolve));
+        assert_eq!(calls.get(), 1, "Bundle lookup should happen once per PID");
+    }
+
+    #[test]
+    fn test_pid_not_allowlisted() {
+        let state = AppState::new();
+        state.set_app_allowlist(allowlist(&["org.videolan.vlc"]));
+
+        assert!(!is_pid_allowlisted_with(&state, 7, |_| Some("com.apple.Terminal".to_string())));
+        assert!(!is_pid_allowlisted_with(&state, 8, |_| None));
+        assert!(!is_pid_allowlisted_with(&state, 0, |_| Some("org.videolan.vlc".to_string())));
+    }
+
+    #[test]
+    fn test_empty_allowlist_skips_lookup() {
+        let state = AppState::new();
+        assert!(!is_pid_allowlisted_with(&state, 42, |_| {
+            panic!("Lookup should not run with an empty allowlist")
+        }));
+    }
+}
@@ -0,0 +1,157 @@
+//! Unlock sequence: a modifier chord pressed a few times in quick succession
+//!
+//! An optional alternative to typing the passphrase (e.g. three quick Cmd+Shift
+//! presses) for when typing is impractical, like holding a child on your lap. It is
+//! much weaker than a passphrase - anyone who knows or stumbles on the sequence can
+//! unlock - so it's off by default and the passphrase keeps working alongside it.
+//!
+//! Modifier keys arrive as FlagsChanged events. A press is the moment the held
+//! modifiers become exactly the chord; another key, or too long a pause between
+//! presses, starts the count over.
+
+use crate::constants::UNLOCK_CHORD_MAX_GAP_MS;
+use crate::utils::modifiers::HotkeyModifiers;
+use std::time::{Duration, Instant};
+
+/// Configured unlock sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnlockChord {
+    /// Modifiers held together (and no others) for each press
+    pub modifiers: HotkeyModifiers,
+    /// Presses in a row that unlock
+    pub presses: u32,
+}
+
+/// Counts chord presses toward the unlock sequence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChordRecognizer {
+    /// Whether the chord is held right now (a press counts once until released)
+    held: bool,
+    presses: u32,
+    last_press: Option<Instant>,
+}
+
+impl ChordRecognizer {
+    /// Record the modifiers held after a FlagsChanged event at `now`
+    ///
+    /// Returns true when this press completes the sequence (the count then starts over).
+    pub fn record(&mut self, chord: UnlockChord, held: HotkeyModifiers, now: Instant) -> bool {
+        if held != chord.modifiers {
+            self.held = false;
+            return false;
+        }
+        if self.held {
+            return false;
+        }
+        self.held = true;
+
+        let max_gap = Duration::from_millis(UNLOCK_CHORD_MAX_GAP_MS);
+        let in_sequence = self
+            .last_press
+            .is_some_and(|last| now.saturating_duration_since(last) <= max_gap);
+        self.presses = if in_sequence { self.presses + 1 } else { 1 };
+        self.last_press = Some(now);
+
+        if self.presses >= chord.presses {
+            self.reset();
+            return true;
+        }
+        false
+    }
+
+    /// Start over (another key was pressed, or the lock state changed)
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CMD_SHIFT: HotkeyModifiers = HotkeyModifiers {
+        control: false,
+        option: false,
+        command: true,
+        shift: true,
+    };
+    const CMD: HotkeyModifiers = HotkeyModifiers {
+        control: false,
+        option: false,
+        command: true,
+        shift: false,
+    };
+    const NONE: HotkeyModifiers = HotkeyModifiers {
+        control: false,
+        option: false,
+        command: false,
+        shift: false,
+    };
+
+    fn chord(presses: u32) -> UnlockChord {
+        UnlockChord {
+            modifiers: CMD_SHIFT,
+            presses,
+        }
+    }
+
+    /// Press and release the chord `ms` milliseconds after `t0`; true if it completed
+    fn press(recognizer: &mut ChordRecognizer, t0: Instant, ms: u64) -> bool {
+        let at = t0 + Duration::from_millis(ms);
+        let done = recognizer.record(chord(3), CMD, at)
+            | recognizer.record(chord(3), CMD_SHIFT, at);
+        recognizer.record(chord(3), NONE, at + Duration::from_millis(50));
+        done
+    }
+
+    #[test]
+    fn test_quick_presses_complete_the_sequence() {
+        let mut recognizer = ChordRecognizer::default();
+        let t0 = Instant::now();
+        assert!(!press(&mut recognizer, t0, 0));
+        assert!(!press(&mut recognizer, t0, 300));
+        assert!(press(&mut recognizer, t0, 600));
+
+        // The count starts over after a completed sequence
+        assert!(!press(&mut recognizer, t0, 900));
+    }
+
+    #[test]
+    fn test_holding_the_chord_counts_once() {
+        let mut recognizer = ChordRecognizer::default();
+        let t0 = Instant::now();
+        for ms in [0, 100, 200, 300] {
+            assert!(!recognizer.record(chord(3), CMD_SHIFT, t0 + Duration::from_millis(ms)));
+        }
+        assert_eq!(recognizer.presses, 1);
+    }
+
+    #[test]
+    fn test_slow_presses_start_over() {
+        let mut recognizer = ChordRecognizer::default();
+        let t0 = Instant::now();
+        let gap = UNLOCK_CHORD_MAX_GAP_MS + 1;
+        assert!(!press(&mut recognizer, t0, 0));
+        assert!(!press(&mut recognizer, t0, gap));
+        assert!(!press(&mut recognizer, t0, 2 * gap));
+        assert_eq!(recognizer.presses, 1);
+    }
+
+    #[test]
+    fn test_extra_modifiers_and_reset_break_the_sequence() {
+        let mut recognizer = ChordRecognizer::default();
+        let t0 = Instant::now();
+        let cmd_shift_opt = HotkeyModifiers {
+            option: true,
+            ..CMD_SHIFT
+        };
+        assert!(!press(&mut recognizer, t0, 0));
+        assert!(!recognizer.record(chord(3), cmd_shift_opt, t0 + Duration::from_millis(200)));
+        assert_eq!(recognizer.presses, 1, "Not exactly the chord");
+
+        assert!(!press(&mut recognizer, t0, 300));
+        recognizer.reset();
+        assert!(!press(&mut recognizer, t0, 600));
+        assert_eq!(recognizer.presses, 1);
+    }
+}
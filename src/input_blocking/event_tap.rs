@@ -1,14 +1,25 @@
 use crate::app_state::AppState;
-use crate::constants::CALLBACK_SLOW_THRESHOLD_US;
-use crate::input_blocking::{handle_keyboard_event, handle_mouse_event};
+use crate::constants::{CALLBACK_SLOW_THRESHOLD_US, CFRUNLOOP_POLL_INTERVAL_MS};
+use crate::input_blocking::app_allowlist::is_pid_allowlisted;
+use crate::input_blocking::filter::{self, Decision, EventKind};
+use crate::input_blocking::system_keys::system_key_for_event;
+use crate::utils::rate_limit::log_throttled;
+use crate::input_blocking::{
+    apply_dry_run, handle_keyboard_event, handle_mouse_event, handle_system_defined_event,
+    record_mouse_activity,
+};
 use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
-use core_graphics::event::CGEventType;
+use core_graphics::event::{CGEventType, EventField};
 use core_graphics::sys::{CGEventRef, CGEventTapRef};
+use anyhow::{anyhow, Result};
 use foreign_types::ForeignType;
 use log::{error, info, warn};
 use std::ffi::c_void;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 /// Counts total CGEventTap handles created since process start.
 /// Compared with TAPS_DESTROYED to detect accumulation across sleep/wake cycles.
@@ -103,18 +114,257 @@ extern "C" {
     fn CFRelease(cf: *const c_void);
 }
 
-const K_CGSESSION_EVENT_TAP: u32 = 1;
-const K_CGHEAD_INSERT_EVENT_TAP: u32 = 0;
-const K_CGEVENT_TAP_OPTION_DEFAULT: u32 = 0;
+pub(crate) const K_CGSESSION_EVENT_TAP: u32 = 1;
+pub(crate) const K_CGHEAD_INSERT_EVENT_TAP: u32 = 0;
+pub(crate) const K_CGEVENT_TAP_OPTION_DEFAULT: u32 = 0;
 
-/// Create and enable the event tap for input blocking
-/// Returns (tap, state_ptr) tuple - caller must free state_ptr when done
-pub fn create_event_tap(state: Arc<AppState>) -> Option<(CGEventTapRef, *mut c_void)> {
+/// NX_SYSDEFINED event type (media, volume, brightness and power keys); not exposed by CGEventType
+pub(crate) const NX_SYSDEFINED_EVENT_TYPE: u32 = 14;
+
+/// kCGEventTapDisabledByTimeout: macOS disabled the tap because a callback was too slow
+/// (most commonly around sleep/wake)
+const K_CGEVENT_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFFFFFE;
+
+/// kCGEventTapDisabledByUserInput: the tap was disabled because accessibility
+/// permissions were removed
+const K_CGEVENT_TAP_DISABLED_BY_USER_INPUT: u32 = 0xFFFFFFFF;
+
+/// What to do when macOS reports that it disabled the event tap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TapDisabledAction {
+    /// Re-enable the existing tap (it is still valid)
+    Reenable,
+    /// Stop the tap; it must be recreated once permissions are granted again
+    Stop,
+}
+
+/// Map a callback event type to the recovery for a disabled tap (None for normal events)
+///
+/// The callback only requests the action; the main thread performs it. Calling
+/// `CGEventTapEnable` from the callback isn't possible: the callback receives a
+/// `CGEventTapProxy`, not the `CGEventTapRef` (see `event_tap_callback`).
+pub(crate) fn tap_disabled_action(event_type: u32) -> Option<TapDisabledAction> {
+    match event_type {
+        K_CGEVENT_TAP_DISABLED_BY_TIMEOUT => Some(TapDisabledAction::Reenable),
+        K_CGEVENT_TAP_DISABLED_BY_USER_INPUT => Some(TapDisabledAction::Stop),
+        _ => None,
+    }
+}
+
+/// Owns a running event tap: the tap, its run loop source and the boxed state passed to
+/// the callback as `user_info`
+///
+/// Dropping the handle removes the tap from the run loop, releases it and frees the
+/// state, exactly once.
+pub struct EventTapHandle {
+    tap: CGEventTapRef,
+    source: CFRunLoopSourceRef,
+    state_ptr: *mut c_void,
+}
+
+impl EventTapHandle {
+    /// Create the event tap and add it to the current run loop
+    ///
+    /// Returns None if the tap could not be created (accessibility permissions missing).
+    pub fn start(state: Arc<AppState>) -> Option<Self> {
+        let (tap, state_ptr) = create_event_tap(state)?;
+        let source = unsafe { enable_event_tap(tap) };
+        Some(Self {
+            tap,
+            source,
+            state_ptr,
+        })
+    }
+
+    /// The underlying tap (valid for as long as the handle lives)
+    pub fn tap(&self) -> CGEventTapRef {
+        self.tap
+    }
+}
+
+impl Drop for EventTapHandle {
+    fn drop(&mut self) {
+        // A null tap or source never made it into the run loop (only in tests)
+        if !self.tap.is_null() && !self.source.is_null() {
+            unsafe {
+                remove_event_tap_from_runloop(self.tap, self.source);
+            }
+        }
+
+        // The callback can no longer fire, so the state it borrowed can go
+        unsafe {
+            free_state_ptr(self.state_ptr);
+        }
+        info!("Event tap state pointer freed");
+    }
+}
+
+/// Tap reference handed back from the thread that owns the tap
+///
+/// Only used for `CGEventTapEnable`, which may be called from any thread.
+#[derive(Clone, Copy)]
+struct TapRef(CGEventTapRef);
+
+// SAFETY: the tap is owned (and released) by its run loop thread; other threads only
+// enable/disable it while that thread is alive
+unsafe impl Send for TapRef {}
+
+/// Background thread that owns the event tap and runs the run loop it is attached to
+///
+/// The tap source is added to the current thread's run loop, so the tap is created,
+/// polled and torn down on this one thread. Adding it on the caller's thread would
+/// attach it to a run loop nobody runs (e.g. in the tray app).
+pub struct EventTapThread {
+    handle: JoinHandle<()>,
+    shutdown: Sender<()>,
+    tap: TapRef,
+}
+
+impl EventTapThread {
+    /// Spawn the run loop thread and create the event tap on it
+    ///
+    /// Fails if the tap could not be created (accessibility permissions missing).
+    pub fn start(state: Arc<AppState>) -> Result<Self> {
+        let poll_state = state.clone();
+        let (handle, shutdown, tap) = spawn_owning_thread(
+            "cfrunloop",
+            move || {
+                let handle = EventTapHandle::start(state)?;
+                let tap = TapRef(handle.tap());
+                Some((handle, tap))
+            },
+            move || {
+                use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoopRunResult};
+
+                // Let the watchdog know the event loop is still turning
+                poll_state.pet_watchdog();
+
+                // Run the loop for 0.5 seconds, then check for shutdown
+                let result = unsafe {
+                    CFRunLoop::run_in_mode(
+                        kCFRunLoopDefaultMode,
+                        Duration::from_millis(CFRUNLOOP_POLL_INTERVAL_MS),
+                        false,
+                    )
+                };
+                if result != CFRunLoopRunResult::TimedOut {
+                    log::trace!("CFRunLoop run_in_mode returned: {:?}", result);
+                }
+            },
+        )?;
+
+        Ok(Self {
+            handle,
+            shutdown,
+            tap,
+        })
+    }
+
+    /// The running tap (for re-enabling after a timeout)
+    pub fn tap(&self) -> CGEventTapRef {
+        self.tap.0
+    }
+
+    /// Stop the thread, which removes and releases the tap on its own run loop
+    ///
+    /// With `unresponsive` set the tap is only disabled and the thread is left
+    /// detached, since joining a hung thread would hang the caller too.
+    pub fn stop(self, unresponsive: bool) {
+        if let Err(e) = self.shutdown.send(()) {
+            warn!("Failed to send shutdown signal to CFRunLoop thread: {}", e);
+        }
+
+        if unresponsive {
+            unsafe {
+                disable_event_tap(self.tap.0);
+            }
+            error!("CFRunLoop thread is unresponsive - event tap disabled, thread left detached");
+            return;
+        }
+
+        match self.handle.join() {
+            Ok(()) => info!("CFRunLoop thread stopped successfully"),
+            Err(e) => warn!("CFRunLoop thread panicked: {:?}", e),
+        }
+    }
+}
+
+/// Spawn a thread that runs `setup`, then calls `poll` until told to shut down
+///
+/// The value owned by `setup` lives and is dropped on the new thread; the report is
+/// sent back to the caller. Fails if `setup` returns None.
+fn spawn_owning_thread<T, R, S, P>(
+    name: &str,
+    setup: S,
+    mut poll: P,
+) -> Result<(JoinHandle<()>, Sender<()>, R)>
+where
+    T: 'static,
+    R: Send + 'static,
+    S: FnOnce() -> Option<(T, R)> + Send + 'static,
+    P: FnMut() + Send + 'static,
+{
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let thread_name = name.to_string();
+
+    let handle = thread::Builder::new()
+        .name(thread_name.clone())
+        .spawn(move || {
+            let Some((owned, report)) = setup() else {
+                let _ = ready_tx.send(None);
+                return;
+            };
+            let _ = ready_tx.send(Some(report));
+            info!("{} thread started", thread_name);
+
+            loop {
+                poll();
+
+                // Stop when asked to, or when the owner is gone
+                if !matches!(shutdown_rx.try_recv(), Err(TryRecvError::Empty)) {
+                    info!("{} thread received shutdown signal", thread_name);
+                    break;
+                }
+            }
+
+            drop(owned);
+            info!("{} thread stopped", thread_name);
+        })
+        .map_err(|e| anyhow!("Failed to spawn {} thread: {}", name, e))?;
+
+    match ready_rx.recv() {
+        Ok(Some(report)) => Ok((handle, shutdown_tx, report)),
+        _ => {
+            let _ = handle.join();
+            Err(anyhow!("Failed to set up {} thread", name))
+        }
+    }
+}
+
+/// Box the callback state so it can be passed as the tap's `user_info`
+fn box_state_ptr(state: Arc<AppState>) -> *mut c_void {
+    Box::into_raw(Box::new(state)) as *mut c_void
+}
+
+/// Free state boxed by `box_state_ptr`
+///
+/// # Safety
+/// `state_ptr` must come from `box_state_ptr` and must not be freed again or used by a
+/// live event tap afterwards.
+unsafe fn free_state_ptr(state_ptr: *mut c_void) {
+    drop(Box::from_raw(state_ptr as *mut Arc<AppState>));
+}
+
+/// Create the event tap for input blocking
+/// Returns (tap, state_ptr) tuple - the caller owns state_ptr (see `EventTapHandle`)
+fn create_event_tap(state: Arc<AppState>) -> Option<(CGEventTapRef, *mut c_void)> {
     info!("Creating event tap for input blocking");
 
     // Event types to monitor - create event mask
     let event_mask: u64 = (1 << CGEventType::KeyDown as u64)
         | (1 << CGEventType::KeyUp as u64)
+        | (1 << CGEventType::FlagsChanged as u64)
         | (1 << CGEventType::MouseMoved as u64)
         | (1 << CGEventType::LeftMouseDown as u64)
         | (1 << CGEventType::LeftMouseUp as u64)
@@ -123,10 +373,11 @@ pub fn create_event_tap(state: Arc<AppState>) -> Option<(CGEventTapRef, *mut c_v
         | (1 << CGEventType::RightMouseUp as u64)
         | (1 << CGEventType::RightMouseDragged as u64)
         | (1 << CGEventType::OtherMouseDragged as u64)
-        | (1 << CGEventType::ScrollWheel as u64);
+        | (1 << CGEventType::ScrollWheel as u64)
+        | (1 << NX_SYSDEFINED_EVENT_TYPE as u64);
 
     // Box the state so we can pass it as user_info
-    let state_ptr = Box::into_raw(Box::new(state)) as *mut c_void;
+    let state_ptr = box_state_ptr(state);
 
     unsafe {
         let tap = CGEventTapCreate(
@@ -140,8 +391,8 @@ pub fn create_event_tap(state: Arc<AppState>) -> Option<(CGEventTapRef, *mut c_v
 
         if tap.is_null() {
             error!("Failed to create event tap - accessibility permissions may not be granted");
-            // Clean up the boxed state
-            let _ = Box::from_raw(state_ptr as *mut Arc<AppState>);
+            // Clean up the boxed state (no tap was created to use it)
+            free_state_ptr(state_ptr);
             return None;
         }
 
@@ -159,10 +410,6 @@ unsafe extern "C" fn event_tap_callback(
     event: CGEventRef,
     user_info: *mut c_void,
 ) -> CGEventRef {
-    // Constants for special event types that indicate the tap has been disabled
-    const K_CGEVENT_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFFFFFE;
-    const K_CGEVENT_TAP_DISABLED_BY_USER_INPUT: u32 = 0xFFFFFFFF;
-
     // Early null check - if user_info is null, pass through all events
     // This can happen if callback fires during/after teardown
     if user_info.is_null() {
@@ -171,13 +418,10 @@ unsafe extern "C" fn event_tap_callback(
 
     // Handle event tap disabled events
     // These events are sent by macOS when the tap is disabled
-    if event_type == K_CGEVENT_TAP_DISABLED_BY_TIMEOUT
-        || event_type == K_CGEVENT_TAP_DISABLED_BY_USER_INPUT
-    {
-        let reason = if event_type == K_CGEVENT_TAP_DISABLED_BY_USER_INPUT {
-            "user removed accessibility permissions"
-        } else {
-            "timeout (callback was too slow)"
+    if let Some(action) = tap_disabled_action(event_type) {
+        let reason = match action {
+            TapDisabledAction::Stop => "user removed accessibility permissions",
+            TapDisabledAction::Reenable => "timeout (callback was too slow)",
         };
 
         log::warn!(
@@ -198,11 +442,12 @@ unsafe extern "C" fn event_tap_callback(
 
         let state = &*(user_info as *const Arc<AppState>);
 
-        if event_type == K_CGEVENT_TAP_DISABLED_BY_USER_INPUT {
+        if action == TapDisabledAction::Stop {
             // Permissions revoked - request full stop (tap must be recreated after permissions restored)
             state.request_stop_event_tap();
-            state.request_exit(); // Request CLI to exit (ignored by tray app)
-            log::warn!("Requested event tap stop and CLI exit due to permission loss");
+            // Each binary decides whether to exit from its PermissionLossAction
+            state.request_exit();
+            log::warn!("Requested event tap stop and exit due to permission loss");
         } else {
             // Timeout — most commonly triggered by sleep/wake. The tap is still valid;
             // re-enabling it reuses the existing WindowServer connection rather than
@@ -233,6 +478,13 @@ unsafe extern "C" fn event_tap_callback(
 
     let cg_event = core_graphics::event::CGEvent::from_ptr(event);
 
+    // A custom event filter sees the event before the built-in logic below (keyboard
+    // events consult it in handle_keyboard_event)
+    let filter_decision = match EventKind::from_event_type(event_type) {
+        Some(EventKind::Keyboard) | None => Decision::Default,
+        Some(_) => filter::consult(state, &cg_event, event_type),
+    };
+
     // Handle different event types - use safe pattern matching instead of transmute
     let should_block = match event_type {
         t if t == CGEventType::KeyDown as u32 => {
@@ -243,17 +495,21 @@ unsafe extern "C" fn event_tap_callback(
             // Always handle keyboard events (for hotkeys even when unlocked)
             handle_keyboard_event(&cg_event, CGEventType::KeyUp, state)
         }
+        t if t == CGEventType::FlagsChanged as u32 => {
+            // Modifier keys: never blocked, only watched for the unlock chord sequence
+            handle_keyboard_event(&cg_event, CGEventType::FlagsChanged, state)
+        }
         t if t == CGEventType::MouseMoved as u32 => {
             // Always allow mouse movement (needed for tooltips and cursor position)
             // This is a passive event and doesn't trigger any actions
-            state.update_input_time();
+            record_mouse_activity(CGEventType::MouseMoved, state);
             false // Always pass through
         }
         t if t == CGEventType::LeftMouseDown as u32 => {
             if state.is_locked() {
                 handle_mouse_event(CGEventType::LeftMouseDown, state)
             } else {
-                state.update_input_time();
+                state.record_input_activity();
                 false
             }
         }
@@ -261,7 +517,7 @@ unsafe extern "C" fn event_tap_callback(
             if state.is_locked() {
                 handle_mouse_event(CGEventType::LeftMouseUp, state)
             } else {
-                state.update_input_time();
+                state.record_input_activity();
                 false
             }
         }
@@ -269,7 +525,7 @@ unsafe extern "C" fn event_tap_callback(
             if state.is_locked() {
                 handle_mouse_event(CGEventType::RightMouseDown, state)
             } else {
-                state.update_input_time();
+                state.record_input_activity();
                 false
             }
         }
@@ -277,7 +533,7 @@ unsafe extern "C" fn event_tap_callback(
             if state.is_locked() {
                 handle_mouse_event(CGEventType::RightMouseUp, state)
             } else {
-                state.update_input_time();
+                state.record_input_activity();
                 false
             }
         }
@@ -285,39 +541,57 @@ unsafe extern "C" fn event_tap_callback(
             if state.is_locked() {
                 handle_mouse_event(CGEventType::ScrollWheel, state)
             } else {
-                state.update_input_time();
+                state.record_input_activity();
                 false
             }
         }
         t if t == CGEventType::LeftMouseDragged as u32 => {
             // Mouse drag with left button - reset auto-lock timer
-            state.update_input_time();
             if state.is_locked() {
-                true // Block during lock
+                handle_mouse_event(CGEventType::LeftMouseDragged, state)
             } else {
+                record_mouse_activity(CGEventType::LeftMouseDragged, state);
                 false // Pass through when unlocked
             }
         }
         t if t == CGEventType::RightMouseDragged as u32 => {
             // Mouse drag with right button - reset auto-lock timer
-            state.update_input_time();
             if state.is_locked() {
-                true // Block during lock
+                handle_mouse_event(CGEventType::RightMouseDragged, state)
             } else {
+                record_mouse_activity(CGEventType::RightMouseDragged, state);
                 false // Pass through when unlocked
             }
         }
         t if t == CGEventType::OtherMouseDragged as u32 => {
             // Mouse drag with other button (middle/wheel) - reset auto-lock timer
-            state.update_input_time();
             if state.is_locked() {
-                true // Block during lock
+                handle_mouse_event(CGEventType::OtherMouseDragged, state)
             } else {
+                record_mouse_activity(CGEventType::OtherMouseDragged, state);
                 false // Pass through when unlocked
             }
         }
+        NX_SYSDEFINED_EVENT_TYPE => {
+            // Media/volume/brightness and power keys - blocked while locked if configured.
+            // Only classify (an NSEvent round trip) while locked.
+            state.is_locked() && handle_system_defined_event(state, system_key_for_event(event))
+        }
         _ => false, // Pass through other events
     };
+    let should_block = filter_decision.resolve(should_block);
+
+    // Let events aimed at allowlisted apps through while locked. Keyboard events have
+    // already been fed to the passphrase buffer above, so unlocking still works.
+    let should_block = should_block
+        && !(state.is_locked()
+            && is_pid_allowlisted(
+                state,
+                cg_event.get_integer_value_field(EventField::EVENT_TARGET_UNIX_PROCESS_ID),
+            ));
+
+    // In dry-run mode nothing is blocked, only logged
+    let should_block = apply_dry_run(state, should_block, event_type);
 
     // CRITICAL: Prevent cg_event from being dropped/freed since we're returning the same pointer!
     // The event is owned by the system, not by us.
@@ -327,7 +601,9 @@ unsafe extern "C" fn event_tap_callback(
     let elapsed_us = callback_start.elapsed().as_micros() as u64;
     if elapsed_us > CALLBACK_SLOW_THRESHOLD_US {
         CALLBACK_SLOW_COUNT.fetch_add(1, Ordering::Relaxed);
-        log::debug!(
+        // Throttled: the count above stays exact
+        log_throttled!(
+            debug,
             "[telemetry] slow callback: {}us (threshold: {}us, event_type: {})",
             elapsed_us,
             CALLBACK_SLOW_THRESHOLD_US,
@@ -389,7 +665,6 @@ pub unsafe fn enable_event_tap(tap: CGEventTapRef) -> CFRunLoopSourceRef {
 ///
 /// # Safety
 /// The `tap` parameter must be a valid CGEventTapRef pointer returned from `CGEventTapCreate`.
-#[allow(dead_code)]
 pub unsafe fn disable_event_tap(tap: CGEventTapRef) {
     CGEventTapEnable(tap, false);
     info!("Event tap disabled");
@@ -446,3 +721,112 @@ pub unsafe fn remove_event_tap_from_runloop(tap: CGEventTapRef, source: CFRunLoo
     info!("Event tap released and removed from run loop (lifetime tap #{} destroyed)", count);
     log_mach_port_count("after remove_event_tap_from_runloop");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tap_disabled_event_types() {
+        // Values of kCGEventTapDisabledByTimeout / kCGEventTapDisabledByUserInput
+        assert_eq!(K_CGEVENT_TAP_DISABLED_BY_TIMEOUT, 0xFFFF_FFFE);
+        assert_eq!(K_CGEVENT_TAP_DISABLED_BY_USER_INPUT, 0xFFFF_FFFF);
+
+        // A timed-out tap is still valid and is re-enabled; a permission loss stops it
+        assert_eq!(
+            tap_disabled_action(K_CGEVENT_TAP_DISABLED_BY_TIMEOUT),
+            Some(TapDisabledAction::Reenable)
+        );
+        assert_eq!(
+            tap_disabled_action(K_CGEVENT_TAP_DISABLED_BY_USER_INPUT),
+            Some(TapDisabledAction::Stop)
+        );
+
+        // Regular events are handled normally
+        assert_eq!(tap_disabled_action(CGEventType::KeyDown as u32), None);
+        assert_eq!(tap_disabled_action(NX_SYSDEFINED_EVENT_TYPE), None);
+    }
+
+    /// Handle owning only the boxed state (null tap/source skip the FFI teardown)
+    fn state_only_handle(state: &Arc<AppState>) -> EventTapHandle {
+        EventTapHandle {
+            tap: std::ptr::null_mut(),
+            source: std::ptr::null_mut(),
+            state_ptr: box_state_ptr(state.clone()),
+        }
+    }
+
+    #[test]
+    fn test_handle_frees_state_once_on_drop() {
+        let state = Arc::new(AppState::new());
+
+        let handle = state_only_handle(&state);
+        assert_eq!(Arc::strong_count(&state), 2, "Handle holds one reference");
+        drop(handle);
+        assert_eq!(Arc::strong_count(&state), 1, "Dropping the handle frees the state");
+    }
+
+    #[test]
+    fn test_owning_thread_creates_runs_and_drops_on_one_thread() {
+        use std::sync::Mutex;
+        use std::thread::ThreadId;
+
+        /// Records which thread drops it (stands in for the tap and its source)
+        struct Owned(Arc<Mutex<Vec<(&'static str, ThreadId)>>>);
+        impl Drop for Owned {
+            fn drop(&mut self) {
+                self.0.lock().unwrap().push(("drop", thread::current().id()));
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let setup_events = events.clone();
+        let poll_events = events.clone();
+        let (handle, shutdown, setup_thread) = spawn_owning_thread(
+            "test-loop",
+            move || {
+                setup_events.lock().unwrap().push(("setup", thread::current().id()));
+                Some((Owned(setup_events), thread::current().id()))
+            },
+            move || {
+                poll_events.lock().unwrap().push(("poll", thread::current().id()));
+                thread::sleep(Duration::from_millis(1));
+            },
+        )
+        .unwrap();
+
+        shutdown.send(()).unwrap();
+        handle.join().unwrap();
+
+        let events = events.lock().unwrap();
+        assert_ne!(setup_thread, thread::current().id());
+        assert!(events.iter().any(|(what, _)| *what == "poll"));
+        assert_eq!(events.last().map(|(what, _)| *what), Some("drop"));
+        assert!(
+            events.iter().all(|(_, id)| *id == setup_thread),
+            "Setup, polling and teardown must share the loop thread"
+        );
+    }
+
+    #[test]
+    fn test_owning_thread_setup_failure() {
+        let result = spawn_owning_thread("test-fail", || None::<((), ())>, || {});
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_repeated_stop_frees_state_once() {
+        let state = Arc::new(AppState::new());
+
+        // Mirrors HandsOffCore: stop, stop again (e.g. disable then restart), restart
+        let mut slot = Some(state_only_handle(&state));
+        drop(slot.take());
+        drop(slot.take());
+        assert_eq!(Arc::strong_count(&state), 1);
+
+        slot = Some(state_only_handle(&state));
+        assert_eq!(Arc::strong_count(&state), 2);
+        drop(slot);
+        assert_eq!(Arc::strong_count(&state), 1);
+    }
+}
@@ -0,0 +1,143 @@
+//! Custom event filters for crates embedding HandsOff
+//!
+//! `HandsOffCore::set_event_filter` installs a predicate that sees each tapped event
+//! before the built-in lock logic and may force it to pass or be blocked. Returning
+//! `Decision::Default` leaves the decision to HandsOff.
+//!
+//! The filter only decides whether an event reaches applications: hotkeys and
+//! passphrase entry still see every keystroke, so a filter can't lock the user out.
+//! It runs on the event tap thread for every event, so it must return quickly.
+
+use crate::app_state::AppState;
+use crate::input_blocking::event_tap::NX_SYSDEFINED_EVENT_TYPE;
+use core_graphics::event::{CGEvent, CGEventFlags, CGEventType, EventField};
+
+/// Signature of an event filter
+pub type EventFilterFn = dyn Fn(EventKind, &EventInfo) -> Decision + Send + Sync;
+
+/// Event filter installed with `HandsOffCore::set_event_filter`
+pub type EventFilter = Box<EventFilterFn>;
+
+/// Broad category of a tapped event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// KeyDown / KeyUp
+    Keyboard,
+    /// Mouse buttons, movement, drags and scrolling
+    Mouse,
+    /// NX system-defined events (media, volume, brightness and power keys)
+    SystemDefined,
+}
+
+/// Details of a tapped event passed to the filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventInfo {
+    /// Raw CGEventType value
+    pub event_type: u32,
+    /// Virtual keycode (keyboard events only)
+    pub keycode: Option<i64>,
+    /// Modifier flags held during the event
+    pub flags: CGEventFlags,
+    /// Whether input was locked when the event arrived
+    pub locked: bool,
+}
+
+impl EventInfo {
+    /// Collect the filter's view of a CGEvent
+    pub fn from_event(event: &CGEvent, event_type: u32, kind: EventKind, locked: bool) -> Self {
+        let keycode = (kind == EventKind::Keyboard)
+            .then(|| event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE));
+        Self {
+            event_type,
+            keycode,
+            flags: event.get_flags(),
+            locked,
+        }
+    }
+}
+
+/// What a filter wants done with an event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Decision {
+    /// Let HandsOff decide (lock state, lock scope, hotkeys)
+    #[default]
+    Default,
+    /// Let the event through
+    Pass,
+    /// Block the event
+    Block,
+}
+
+impl Decision {
+    /// Final block decision given what the built-in logic decided
+    pub fn resolve(self, default_block: bool) -> bool {
+        match self {
+            Decision::Default => default_block,
+            Decision::Pass => false,
+            Decision::Block => true,
+        }
+    }
+}
+
+/// Mouse event types in the event tap's mask
+const MOUSE_EVENT_TYPES: [CGEventType; 9] = [
+    CGEventType::MouseMoved,
+    CGEventType::LeftMouseDown,
+    CGEventType::LeftMouseUp,
+    CGEventType::LeftMouseDragged,
+    CGEventType::RightMouseDown,
+    CGEventType::RightMouseUp,
+    CGEventType::RightMouseDragged,
+    CGEventType::OtherMouseDragged,
+    CGEventType::ScrollWheel,
+];
+
+impl EventKind {
+    /// Kind of a tapped event type (None for events filters don't see)
+    pub fn from_event_type(event_type: u32) -> Option<Self> {
+        if event_type == CGEventType::KeyDown as u32 || event_type == CGEventType::KeyUp as u32 {
+            Some(EventKind::Keyboard)
+        } else if event_type == NX_SYSDEFINED_EVENT_TYPE {
+            Some(EventKind::SystemDefined)
+        } else if MOUSE_EVENT_TYPES.iter().any(|t| *t as u32 == event_type) {
+            Some(EventKind::Mouse)
+        } else {
+            None
+        }
+    }
+}
+
+/// Ask the installed filter about an event (`Decision::Default` when none is installed)
+pub fn consult(state: &AppState, event: &CGEvent, event_type: u32) -> Decision {
+    let Some(filter) = state.get_event_filter() else {
+        return Decision::Default;
+    };
+    let Some(kind) = EventKind::from_event_type(event_type) else {
+        return Decision::Default;
+    };
+    filter(kind, &EventInfo::from_event(event, event_type, kind, state.is_locked()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_resolve() {
+        assert!(Decision::Default.resolve(true));
+        assert!(!Decision::Default.resolve(false));
+        assert!(!Decision::Pass.resolve(true));
+        assert!(Decision::Block.resolve(false));
+    }
+
+    #[test]
+    fn test_event_kind_from_event_type() {
+        let kind = |t: CGEventType| EventKind::from_event_type(t as u32);
+        assert_eq!(kind(CGEventType::KeyDown), Some(EventKind::Keyboard));
+        assert_eq!(kind(CGEventType::KeyUp), Some(EventKind::Keyboard));
+        assert_eq!(kind(CGEventType::ScrollWheel), Some(EventKind::Mouse));
+        assert_eq!(kind(CGEventType::FlagsChanged), None);
+        let system_defined = EventKind::from_event_type(NX_SYSDEFINED_EVENT_TYPE);
+        assert_eq!(system_defined, Some(EventKind::SystemDefined));
+    }
+}
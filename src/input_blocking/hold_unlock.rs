@@ -0,0 +1,175 @@
+//! Hold-to-unlock: hold a key (Escape by default) for a few seconds to unlock
+//!
+//! Another alternative to typing the passphrase. Like the unlock chord it is much
+//! weaker than a passphrase - anyone who knows about it can unlock - so it's off by
+//! default. The hold starts with the first KeyDown of the key while locked and is
+//! cancelled by its KeyUp; it completes on an auto-repeated KeyDown, or on the
+//! scheduler's next check, once the configured duration has passed. The scheduler
+//! also asks the HID system whether the key is really still down, so a KeyUp the
+//! event tap missed can't complete a hold nobody is doing.
+
+use crate::constants::ESCAPE_KEYCODE;
+use std::time::{Duration, Instant};
+
+/// kCGEventSourceStateHIDSystemState
+const HID_SYSTEM_STATE: i32 = 1;
+
+/// Hold keys by config name, with their macOS keycodes (a letter would type into the
+/// passphrase, so only keys that don't are allowed)
+const HOLD_KEYS: [(&str, i64); 13] = [
+    ("escape", ESCAPE_KEYCODE),
+    ("f1", 122),
+    ("f2", 120),
+    ("f3", 99),
+    ("f4", 118),
+    ("f5", 96),
+    ("f6", 97),
+    ("f7", 98),
+    ("f8", 100),
+    ("f9", 101),
+    ("f10", 109),
+    ("f11", 103),
+    ("f12", 111),
+];
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventSourceKeyState(state_id: i32, key: u16) -> bool;
+}
+
+/// Configured hold-to-unlock key and duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoldUnlock {
+    /// macOS keycode of the key to hold
+    pub keycode: i64,
+    /// How long the key has to be held
+    pub duration: Duration,
+}
+
+/// Keycode of a hold key name ("escape" or "f1"-"f12", case insensitive)
+pub fn hold_key_keycode(name: &str) -> Option<i64> {
+    HOLD_KEYS
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|&(_, keycode)| keycode)
+}
+
+/// Whether the HID system reports `keycode` as held down right now
+pub fn is_key_down(keycode: i64) -> bool {
+    u16::try_from(keycode)
+        .is_ok_and(|key| unsafe { CGEventSourceKeyState(HID_SYSTEM_STATE, key) })
+}
+
+/// Tracks a hold of the hold-to-unlock key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HoldRecognizer {
+    /// When the current hold started (None = key not held)
+    held_since: Option<Instant>,
+}
+
+impl HoldRecognizer {
+    /// Record a KeyDown of the hold key at `now` (auto-repeats keep the original start)
+    ///
+    /// Returns true when the hold is complete (the recognizer then starts over).
+    pub fn press(&mut self, hold: HoldUnlock, now: Instant) -> bool {
+        self.held_since.get_or_insert(now);
+        self.poll(hold, now)
+    }
+
+    /// Record a KeyUp of the hold key: the hold is cancelled
+    pub fn release(&mut self) {
+        self.held_since = None;
+    }
+
+    /// Check a hold in progress at `now`; true when it is complete (and starts over)
+    pub fn poll(&mut self, hold: HoldUnlock, now: Instant) -> bool {
+        if self.remaining(hold, now) == Some(Duration::ZERO) {
+            self.release();
+            return true;
+        }
+        false
+    }
+
+    /// Whether the key is being held
+    pub fn is_holding(&self) -> bool {
+        self.held_since.is_some()
+    }
+
+    /// Time left until the hold completes (None = key not held)
+    pub fn remaining(&self, hold: HoldUnlock, now: Instant) -> Option<Duration> {
+        let held_since = self.held_since?;
+        Some(hold.duration.saturating_sub(now.saturating_duration_since(held_since)))
+    }
+
+    /// Fraction of the hold done at `now`, 0.0-1.0 (None = key not held)
+    pub fn progress(&self, hold: HoldUnlock, now: Instant) -> Option<f64> {
+        let held_since = self.held_since?;
+        if hold.duration.is_zero() {
+            return Some(1.0);
+        }
+        let held = now.saturating_duration_since(held_since);
+        Some((held.as_secs_f64() / hold.duration.as_secs_f64()).min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOLD: HoldUnlock = HoldUnlock {
+        keycode: ESCAPE_KEYCODE,
+        duration: Duration::from_secs(3),
+    };
+
+    fn at(t0: Instant, ms: u64) -> Instant {
+        t0 + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_hold_key_names() {
+        assert_eq!(hold_key_keycode("escape"), Some(ESCAPE_KEYCODE));
+        assert_eq!(hold_key_keycode("F12"), Some(111));
+        assert_eq!(hold_key_keycode("a"), None, "Letters would type into the passphrase");
+        assert_eq!(hold_key_keycode("f13"), None);
+    }
+
+    #[test]
+    fn test_hold_completes_after_the_duration() {
+        let t0 = Instant::now();
+        let mut recognizer = HoldRecognizer::default();
+        assert!(!recognizer.press(HOLD, t0));
+        // Auto-repeated KeyDowns keep the original start
+        assert!(!recognizer.press(HOLD, at(t0, 1500)));
+        assert_eq!(recognizer.progress(HOLD, at(t0, 1500)), Some(0.5));
+        assert_eq!(recognizer.remaining(HOLD, at(t0, 2000)), Some(Duration::from_secs(1)));
+        assert!(recognizer.press(HOLD, at(t0, 3000)));
+
+        // The recognizer starts over after a completed hold
+        assert!(!recognizer.is_holding());
+        assert_eq!(recognizer.progress(HOLD, at(t0, 3000)), None);
+    }
+
+    #[test]
+    fn test_poll_completes_a_hold_without_key_repeat() {
+        let t0 = Instant::now();
+        let mut recognizer = HoldRecognizer::default();
+        assert!(!recognizer.poll(HOLD, at(t0, 5000)), "Nothing held");
+        recognizer.press(HOLD, t0);
+        assert!(!recognizer.poll(HOLD, at(t0, 2999)));
+        assert!(recognizer.poll(HOLD, at(t0, 3000)));
+    }
+
+    #[test]
+    fn test_early_release_cancels_the_hold() {
+        let t0 = Instant::now();
+        let mut recognizer = HoldRecognizer::default();
+        recognizer.press(HOLD, t0);
+        recognizer.release();
+        assert!(!recognizer.poll(HOLD, at(t0, 3000)));
+
+        // Holding again starts from zero
+        assert!(!recognizer.press(HOLD, at(t0, 4000)));
+        assert!(!recognizer.press(HOLD, at(t0, 6000)));
+        assert!(recognizer.press(HOLD, at(t0, 7000)));
+    }
+}
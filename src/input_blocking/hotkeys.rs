@@ -1,14 +1,226 @@
+use crate::utils::modifiers::HotkeyModifiers;
 use anyhow::{Context, Result};
 use global_hotkey::{
-    hotkey::{Code, HotKey, Modifiers},
+    hotkey::{Code, HotKey},
     GlobalHotKeyManager,
 };
-use log::info;
+use log::{info, warn};
+use std::fmt;
+
+/// Prefix of the error global-hotkey reports when macOS `RegisterEventHotKey` fails,
+/// which in practice means another app already owns the combination
+const REGISTER_EVENT_HOTKEY_FAILED: &str = "RegisterEventHotKey failed";
+
+/// Modifier sets suggested when a hotkey combination is already taken
+const ALTERNATIVE_MODIFIERS: [HotkeyModifiers; 4] = [
+    HotkeyModifiers::DEFAULT,
+    HotkeyModifiers {
+        control: true,
+        option: true,
+        command: true,
+        shift: false,
+    },
+    HotkeyModifiers {
+        control: false,
+        option: true,
+        command: true,
+        shift: true,
+    },
+    HotkeyModifiers::PANIC_DEFAULT,
+];
+
+/// Which of HandsOff's global hotkeys a registration refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyKind {
+    Lock,
+    Talk,
+    Panic,
+}
+
+impl HotkeyKind {
+    /// Config key holding this hotkey's modifiers
+    pub fn modifiers_config_key(self) -> &'static str {
+        match self {
+            HotkeyKind::Lock => "lock_modifiers",
+            HotkeyKind::Talk => "talk_modifiers",
+            HotkeyKind::Panic => "panic_modifiers",
+        }
+    }
+}
+
+impl fmt::Display for HotkeyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyKind::Lock => write!(f, "lock"),
+            HotkeyKind::Talk => write!(f, "talk"),
+            HotkeyKind::Panic => write!(f, "panic"),
+        }
+    }
+}
+
+/// Why a global hotkey could not be registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyRegistrationError {
+    /// The combination is already taken (usually by another app)
+    Conflict { kind: HotkeyKind, combo: String },
+    /// Any other registration failure
+    Failed {
+        kind: HotkeyKind,
+        combo: String,
+        reason: String,
+    },
+}
+
+impl HotkeyRegistrationError {
+    /// Classify a global-hotkey registration error for the given hotkey
+    pub fn classify(kind: HotkeyKind, combo: String, error: &global_hotkey::Error) -> Self {
+        match error {
+            global_hotkey::Error::AlreadyRegistered(_) => Self::Conflict { kind, combo },
+            global_hotkey::Error::FailedToRegister(reason)
+                if reason.starts_with(REGISTER_EVENT_HOTKEY_FAILED) =>
+            {
+                Self::Conflict { kind, combo }
+            }
+            other => Self::Failed {
+                kind,
+                combo,
+                reason: other.to_string(),
+            },
+        }
+    }
+
+    /// Find a registration error anywhere in an error's context chain
+    pub fn find(error: &anyhow::Error) -> Option<&Self> {
+        error.chain().find_map(|cause| cause.downcast_ref::<Self>())
+    }
+
+    /// Which hotkey failed to register
+    pub fn kind(&self) -> HotkeyKind {
+        match self {
+            Self::Conflict { kind, .. } | Self::Failed { kind, .. } => *kind,
+        }
+    }
+
+    /// Whether the combination is taken by another app
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, Self::Conflict { .. })
+    }
+}
+
+impl fmt::Display for HotkeyRegistrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict { kind, combo } => write!(
+                f,
+                "{} is already in use by another app (HandsOff {} hotkey)",
+                combo, kind
+            ),
+            Self::Failed { kind, combo, reason } => write!(
+                f,
+                "Failed to register {} hotkey {}: {}",
+                kind, combo, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyRegistrationError {}
+
+/// Format a hotkey combination for display (e.g. "Ctrl+Cmd+Shift+L")
+pub fn combo_display(key: Code, modifiers: HotkeyModifiers) -> String {
+    format!("{}+{}", modifiers, format!("{:?}", key).replace("Key", ""))
+}
+
+/// Modifier sets to suggest instead of a conflicting one (excluding the one in use)
+pub fn alternative_modifiers(current: HotkeyModifiers) -> Vec<HotkeyModifiers> {
+    ALTERNATIVE_MODIFIERS
+        .iter()
+        .copied()
+        .filter(|candidate| *candidate != current)
+        .collect()
+}
+
+/// IDs of the currently registered global hotkeys, shared with the listener thread
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HotkeyIds {
+    pub lock: Option<u32>,
+    pub talk: Option<u32>,
+    pub panic: Option<u32>,
+}
+
+/// Registration backend (the global hotkey manager, or a fake in tests)
+pub(crate) trait HotkeyRegistrar {
+    fn register(&self, hotkey: HotKey) -> global_hotkey::Result<()>;
+    fn unregister(&self, hotkey: HotKey) -> global_hotkey::Result<()>;
+}
+
+impl HotkeyRegistrar for GlobalHotKeyManager {
+    fn register(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+        GlobalHotKeyManager::register(self, hotkey)
+    }
+
+    fn unregister(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+        GlobalHotKeyManager::unregister(self, hotkey)
+    }
+}
+
+/// Replace the `current` hotkeys with `new` ones, all or nothing
+///
+/// On failure, any of the new hotkeys already registered are unregistered again and the
+/// previous ones restored. Returns the index into `new` of the hotkey that failed.
+pub(crate) fn swap_hotkeys<R: HotkeyRegistrar + ?Sized>(
+    registrar: &R,
+    current: &[HotKey],
+    new: &[HotKey],
+) -> std::result::Result<(), (usize, global_hotkey::Error)> {
+    for hotkey in current {
+        if let Err(e) = registrar.unregister(*hotkey) {
+            warn!("Failed to unregister hotkey {:?}: {}", hotkey, e);
+        }
+    }
+
+    for (index, hotkey) in new.iter().enumerate() {
+        if let Err(e) = registrar.register(*hotkey) {
+            for registered in &new[..index] {
+                let _ = registrar.unregister(*registered);
+            }
+            for previous in current {
+                if let Err(restore_err) = registrar.register(*previous) {
+                    warn!("Failed to restore previous hotkey {:?}: {}", previous, restore_err);
+                }
+            }
+            return Err((index, e));
+        }
+    }
+    Ok(())
+}
+
+/// The lock and talk hotkeys to register, skipping disabled ones (`None` keys)
+pub(crate) fn requested_hotkeys(
+    lock: Option<Code>,
+    lock_modifiers: HotkeyModifiers,
+    talk: Option<Code>,
+    talk_modifiers: HotkeyModifiers,
+) -> Vec<(HotkeyKind, Code, HotkeyModifiers)> {
+    [
+        lock.map(|key| (HotkeyKind::Lock, key, lock_modifiers)),
+        talk.map(|key| (HotkeyKind::Talk, key, talk_modifiers)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Display a possibly disabled hotkey for logs (e.g. "Ctrl+Cmd+Shift+L" or "none")
+fn optional_combo_display(key: Option<Code>, modifiers: HotkeyModifiers) -> String {
+    key.map_or_else(|| "none".to_string(), |key| combo_display(key, modifiers))
+}
 
 pub struct HotkeyManager {
     manager: GlobalHotKeyManager,
     pub lock_hotkey: Option<HotKey>,
     pub talk_hotkey: Option<HotKey>,
+    pub panic_hotkey: Option<HotKey>,
 }
 
 impl HotkeyManager {
@@ -20,49 +232,150 @@ impl HotkeyManager {
             manager,
             lock_hotkey: None,
             talk_hotkey: None,
+            panic_hotkey: None,
         })
     }
 
-    /// Register the lock hotkey with configurable key (modifiers: Ctrl+Cmd+Shift)
+    /// Register the lock hotkey with configurable modifiers and key
     ///
     /// # Arguments
     ///
     /// * `key` - The key code to use (e.g., Code::KeyL for Ctrl+Cmd+Shift+L)
-    pub fn register_lock_hotkey(&mut self, key: Code) -> Result<()> {
-        let hotkey = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::SUPER | Modifiers::SHIFT),
-            key,
-        );
-
-        self.manager
-            .register(hotkey)
-            .context("Failed to register lock hotkey")?;
-
+    /// * `modifiers` - Modifiers held with the key (default: Ctrl+Cmd+Shift)
+    pub fn register_lock_hotkey(&mut self, key: Code, modifiers: HotkeyModifiers) -> Result<()> {
+        let hotkey = self.register(HotkeyKind::Lock, key, modifiers)?;
         self.lock_hotkey = Some(hotkey);
-        info!("Lock hotkey registered: Ctrl+Cmd+Shift+{:?}", key);
+        info!("Lock hotkey registered: {}", combo_display(key, modifiers));
         Ok(())
     }
 
-    /// Register the talk hotkey with configurable key (modifiers: Ctrl+Cmd+Shift)
+    /// Register the talk hotkey with configurable modifiers and key
     ///
     /// # Arguments
     ///
     /// * `key` - The key code to use (e.g., Code::KeyT for Ctrl+Cmd+Shift+T)
-    pub fn register_talk_hotkey(&mut self, key: Code) -> Result<()> {
-        let hotkey = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::SUPER | Modifiers::SHIFT),
-            key,
-        );
+    /// * `modifiers` - Modifiers held with the key (default: Ctrl+Cmd+Shift)
+    pub fn register_talk_hotkey(&mut self, key: Code, modifiers: HotkeyModifiers) -> Result<()> {
+        let hotkey = self.register(HotkeyKind::Talk, key, modifiers)?;
+        self.talk_hotkey = Some(hotkey);
+        info!("Talk hotkey registered: {}", combo_display(key, modifiers));
+        Ok(())
+    }
+
+    /// Register the emergency unlock (panic) hotkey
+    ///
+    /// While locked the event tap swallows the keystroke before it reaches the global
+    /// hotkey, so the tap handles it there; this registration covers the case where the
+    /// tap isn't intercepting keyboard input.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key code to use (e.g., Code::KeyX for Ctrl+Opt+Cmd+Shift+X)
+    /// * `modifiers` - Modifiers held with the key (default: Ctrl+Opt+Cmd+Shift)
+    pub fn register_panic_hotkey(&mut self, key: Code, modifiers: HotkeyModifiers) -> Result<()> {
+        let hotkey = self.register(HotkeyKind::Panic, key, modifiers)?;
+        self.panic_hotkey = Some(hotkey);
+        info!("Panic (emergency unlock) hotkey registered: {}", combo_display(key, modifiers));
+        Ok(())
+    }
 
-        self.manager
-            .register(hotkey)
-            .context("Failed to register talk hotkey")?;
+    /// Swap the lock and talk hotkeys for new combinations, rolling back on failure
+    ///
+    /// Either both new hotkeys are registered, or the previous ones stay in place
+    /// and the error names the hotkey that could not be registered. A `None` key
+    /// disables that hotkey (its previous registration is removed).
+    pub fn reconfigure(
+        &mut self,
+        lock: Option<Code>,
+        lock_modifiers: HotkeyModifiers,
+        talk: Option<Code>,
+        talk_modifiers: HotkeyModifiers,
+    ) -> Result<()> {
+        let current: Vec<HotKey> = [self.lock_hotkey, self.talk_hotkey]
+            .into_iter()
+            .flatten()
+            .collect();
+        let requested = requested_hotkeys(lock, lock_modifiers, talk, talk_modifiers);
+        let new: Vec<HotKey> = requested
+            .iter()
+            .map(|(_, key, modifiers)| HotKey::new(Some(modifiers.to_global_hotkey()), *key))
+            .collect();
 
-        self.talk_hotkey = Some(hotkey);
-        info!("Talk hotkey registered: Ctrl+Cmd+Shift+{:?}", key);
+        if let Err((index, e)) = swap_hotkeys(&self.manager, &current, &new) {
+            let (kind, key, modifiers) = requested[index];
+            return Err(HotkeyRegistrationError::classify(kind, combo_display(key, modifiers), &e))
+                .context("Failed to reconfigure hotkeys (previous hotkeys kept)");
+        }
+
+        let registered = |kind: HotkeyKind| {
+            requested
+                .iter()
+                .position(|(requested_kind, _, _)| *requested_kind == kind)
+                .map(|index| new[index])
+        };
+        self.lock_hotkey = registered(HotkeyKind::Lock);
+        self.talk_hotkey = registered(HotkeyKind::Talk);
+        info!(
+            "Hotkeys reconfigured: lock {}, talk {}",
+            optional_combo_display(lock, lock_modifiers),
+            optional_combo_display(talk, talk_modifiers)
+        );
         Ok(())
     }
 
+    /// IDs of the currently registered hotkeys
+    pub fn ids(&self) -> HotkeyIds {
+        HotkeyIds {
+            lock: self.lock_hotkey.map(|hk| hk.id()),
+            talk: self.talk_hotkey.map(|hk| hk.id()),
+            panic: self.panic_hotkey.map(|hk| hk.id()),
+        }
+    }
+
+    /// Register a hotkey, classifying failures and logging free alternatives on conflict
+    fn register(&self, kind: HotkeyKind, key: Code, modifiers: HotkeyModifiers) -> Result<HotKey> {
+        let hotkey = HotKey::new(Some(modifiers.to_global_hotkey()), key);
+        let combo = combo_display(key, modifiers);
+
+        if let Err(e) = self.manager.register(hotkey) {
+            let error = HotkeyRegistrationError::classify(kind, combo, &e);
+            if error.is_conflict() {
+                let free = self.free_alternatives(key, modifiers);
+                if free.is_empty() {
+                    warn!("{} - no free alternative modifiers found for this key", error);
+                } else {
+                    warn!(
+                        "{} - free alternatives: {} (set {} in config.toml)",
+                        error,
+                        free.join(", "),
+                        kind.modifiers_config_key()
+                    );
+                }
+            }
+            return Err(error)
+                .with_context(|| format!("Failed to register {} hotkey", kind));
+        }
+        Ok(hotkey)
+    }
+
+    /// Probe alternative modifier sets for `key`, returning the combinations that are free
+    ///
+    /// Each candidate is registered and immediately unregistered again.
+    fn free_alternatives(&self, key: Code, current: HotkeyModifiers) -> Vec<String> {
+        alternative_modifiers(current)
+            .into_iter()
+            .filter(|modifiers| {
+                let candidate = HotKey::new(Some(modifiers.to_global_hotkey()), key);
+                let free = self.manager.register(candidate).is_ok();
+                if free {
+                    let _ = self.manager.unregister(candidate);
+                }
+                free
+            })
+            .map(|modifiers| combo_display(key, modifiers))
+            .collect()
+    }
+
     /// Unregister all hotkeys
     #[allow(dead_code)]
     pub fn unregister_all(&mut self) -> Result<()> {
@@ -72,6 +385,186 @@ impl HotkeyManager {
         if let Some(hotkey) = self.talk_hotkey.take() {
             self.manager.unregister(hotkey)?;
         }
+        if let Some(hotkey) = self.panic_hotkey.take() {
+            self.manager.unregister(hotkey)?;
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+
+    /// In-memory registrar that rejects one configured hotkey
+    #[derive(Default)]
+    struct FakeRegistrar {
+        registered: RefCell<HashSet<u32>>,
+        taken: Option<HotKey>,
+    }
+
+    impl HotkeyRegistrar for FakeRegistrar {
+        fn register(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+            if self.taken == Some(hotkey) || !self.registered.borrow_mut().insert(hotkey.id()) {
+                return Err(global_hotkey::Error::AlreadyRegistered(hotkey));
+            }
+            Ok(())
+        }
+
+        fn unregister(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+            if !self.registered.borrow_mut().remove(&hotkey.id()) {
+                return Err(global_hotkey::Error::FailedToUnRegister(hotkey));
+            }
+            Ok(())
+        }
+    }
+
+    fn hotkey(key: Code) -> HotKey {
+        HotKey::new(Some(HotkeyModifiers::DEFAULT.to_global_hotkey()), key)
+    }
+
+    fn ids(hotkeys: &[HotKey]) -> HashSet<u32> {
+        hotkeys.iter().map(|hk| hk.id()).collect()
+    }
+
+    #[test]
+    fn test_swap_hotkeys_replaces_all() {
+        let registrar = FakeRegistrar::default();
+        let current = [hotkey(Code::KeyL), hotkey(Code::KeyT)];
+        let new = [hotkey(Code::KeyK), hotkey(Code::KeyY)];
+        swap_hotkeys(&registrar, &[], &current).unwrap();
+
+        swap_hotkeys(&registrar, &current, &new).unwrap();
+        assert_eq!(*registrar.registered.borrow(), ids(&new));
+    }
+
+    #[test]
+    fn test_swap_hotkeys_rolls_back_when_second_registration_fails() {
+        let registrar = FakeRegistrar {
+            taken: Some(hotkey(Code::KeyY)),
+            ..Default::default()
+        };
+        let current = [hotkey(Code::KeyL), hotkey(Code::KeyT)];
+        swap_hotkeys(&registrar, &[], &current).unwrap();
+
+        let result = swap_hotkeys(&registrar, &current, &[hotkey(Code::KeyK), hotkey(Code::KeyY)]);
+        let (index, error) = result.unwrap_err();
+        assert_eq!(index, 1, "Talk hotkey (second) should be reported");
+        assert!(matches!(error, global_hotkey::Error::AlreadyRegistered(_)));
+        assert_eq!(
+            *registrar.registered.borrow(),
+            ids(&current),
+            "New lock hotkey must be released and the previous hotkeys restored"
+        );
+    }
+
+    #[test]
+    fn test_swap_hotkeys_keeping_one_combo() {
+        let registrar = FakeRegistrar::default();
+        let current = [hotkey(Code::KeyL), hotkey(Code::KeyT)];
+        swap_hotkeys(&registrar, &[], &current).unwrap();
+
+        // Only the talk letter changes; the unchanged lock hotkey re-registers cleanly
+        let new = [hotkey(Code::KeyL), hotkey(Code::KeyY)];
+        swap_hotkeys(&registrar, &current, &new).unwrap();
+        assert_eq!(*registrar.registered.borrow(), ids(&new));
+    }
+
+    #[test]
+    fn test_classify_already_registered_as_conflict() {
+        let hotkey = HotKey::new(Some(HotkeyModifiers::DEFAULT.to_global_hotkey()), Code::KeyL);
+        let error = HotkeyRegistrationError::classify(
+            HotkeyKind::Lock,
+            combo_display(Code::KeyL, HotkeyModifiers::DEFAULT),
+            &global_hotkey::Error::AlreadyRegistered(hotkey),
+        );
+        assert!(error.is_conflict());
+        assert_eq!(error.kind(), HotkeyKind::Lock);
+        assert!(error
+            .to_string()
+            .starts_with("Ctrl+Cmd+Shift+L is already in use by another app"));
+    }
+
+    #[test]
+    fn test_classify_macos_register_failure_as_conflict() {
+        let error = HotkeyRegistrationError::classify(
+            HotkeyKind::Talk,
+            "Ctrl+Cmd+Shift+T".to_string(),
+            &global_hotkey::Error::FailedToRegister("RegisterEventHotKey failed for KeyT".to_string()),
+        );
+        assert_eq!(
+            error,
+            HotkeyRegistrationError::Conflict {
+                kind: HotkeyKind::Talk,
+                combo: "Ctrl+Cmd+Shift+T".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_classify_other_failures() {
+        let error = HotkeyRegistrationError::classify(
+            HotkeyKind::Panic,
+            "Ctrl+Opt+Cmd+Shift+X".to_string(),
+            &global_hotkey::Error::FailedToRegister("Unknown scancode for KeyX".to_string()),
+        );
+        assert!(!error.is_conflict());
+        assert_eq!(error.kind(), HotkeyKind::Panic);
+        assert!(error.to_string().contains("Unknown scancode"));
+    }
+
+    #[test]
+    fn test_find_registration_error_through_context() {
+        let error = anyhow::Error::new(HotkeyRegistrationError::Conflict {
+            kind: HotkeyKind::Lock,
+            combo: "Ctrl+Cmd+Shift+L".to_string(),
+        })
+        .context("Failed to register lock hotkey")
+        .context("Failed to start hotkeys");
+        let found = HotkeyRegistrationError::find(&error).expect("should find conflict");
+        assert!(found.is_conflict());
+
+        assert!(HotkeyRegistrationError::find(&anyhow::anyhow!("unrelated")).is_none());
+    }
+
+    #[test]
+    fn test_requested_hotkeys_skip_disabled() {
+        let modifiers = HotkeyModifiers::DEFAULT;
+        let both = requested_hotkeys(Some(Code::KeyL), modifiers, Some(Code::KeyT), modifiers);
+        assert_eq!(
+            both,
+            vec![
+                (HotkeyKind::Lock, Code::KeyL, modifiers),
+                (HotkeyKind::Talk, Code::KeyT, modifiers)
+            ]
+        );
+
+        let talk_only = requested_hotkeys(None, modifiers, Some(Code::KeyT), modifiers);
+        assert_eq!(talk_only, vec![(HotkeyKind::Talk, Code::KeyT, modifiers)]);
+        assert!(requested_hotkeys(None, modifiers, None, modifiers).is_empty());
+    }
+
+    #[test]
+    fn test_swap_hotkeys_dropping_disabled_lock_hotkey() {
+        let registrar = FakeRegistrar::default();
+        let current = [hotkey(Code::KeyL), hotkey(Code::KeyT)];
+        swap_hotkeys(&registrar, &[], &current).unwrap();
+
+        let new = [hotkey(Code::KeyT)];
+        swap_hotkeys(&registrar, &current, &new).unwrap();
+        assert_eq!(*registrar.registered.borrow(), ids(&new), "Lock hotkey unregistered");
+    }
+
+    #[test]
+    fn test_alternative_modifiers_exclude_current() {
+        let alternatives = alternative_modifiers(HotkeyModifiers::DEFAULT);
+        assert_eq!(alternatives.len(), ALTERNATIVE_MODIFIERS.len() - 1);
+        assert!(!alternatives.contains(&HotkeyModifiers::DEFAULT));
+        assert_eq!(
+            combo_display(Code::KeyL, alternatives[0]),
+            "Ctrl+Opt+Cmd+L"
+        );
+    }
+}
@@ -1,133 +1,385 @@
+pub mod app_allowlist;
+pub mod chord;
 pub mod event_tap;
+pub mod filter;
+pub mod hold_unlock;
 pub mod hotkeys;
+pub mod system_keys;
 
-use crate::app_state::AppState;
+use crate::app_state::{AppState, TalkMode};
+use crate::audit::UnlockMethod;
 use crate::auth;
-use crate::constants::BACKSPACE_KEYCODE;
-use crate::utils::keycode::keycode_to_char;
+use crate::constants::{
+    ATTEMPT_COOLDOWN_SECONDS, BACKSPACE_KEYCODE, ESCAPE_KEYCODE, EVENT_TAP_RESTART_MIN_INTERVAL_SECS,
+    PERMISSION_LOSS_CONFIRMATIONS, PERMISSION_RESTORE_CONFIRMATIONS,
+};
+use crate::ui;
+use crate::utils::keycode;
+use crate::utils::modifiers::HotkeyModifiers;
+use crate::utils::rate_limit::log_throttled;
 use core_graphics::event::{CGEvent, CGEventFlags, CGEventType, EventField};
 use log::{debug, error, info};
+use std::time::{Duration, Instant};
+use system_keys::SystemKey;
 
 /// Handle a keyboard event during lock
 ///
-/// Returns true if the event should be blocked, false if it should pass through
+/// Returns true if the event should be blocked, false if it should pass through.
+/// Events blocked while locked are counted (see `AppState::get_blocked_event_count`).
+/// A custom event filter (see `filter`) can force the decision either way.
 pub fn handle_keyboard_event(event: &CGEvent, event_type: CGEventType, state: &AppState) -> bool {
+    // Ask the filter about the key as typed, before the Talk hotkey rewrites it
+    let decision = filter::consult(state, event, event_type as u32);
+    // Hotkeys and passphrase entry always run, so a filter can't lock the user out
+    let block = decision.resolve(keyboard_event_action(event, event_type, state));
+    // Hotkeys are blocked while unlocked too; only count what a lock swallowed
+    if block && state.is_locked() {
+        state.record_blocked_event();
+    }
+    block
+}
+
+/// Decide whether a keyboard event is blocked (handling hotkeys and passphrase entry)
+fn keyboard_event_action(event: &CGEvent, event_type: CGEventType, state: &AppState) -> bool {
     let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
     let flags = event.get_flags();
 
+    // Modifier changes only feed the unlock chord sequence; they always pass
+    if (event_type as u32) == (CGEventType::FlagsChanged as u32) {
+        if state.record_unlock_chord(HotkeyModifiers::from_flags(flags)) {
+            auth::chord_unlock(state);
+        }
+        return false;
+    }
+
     // Get configured hotkey keycodes from AppState
     let lock_keycode = state.get_lock_keycode();
-    let talk_keycode = state.get_talk_keycode();
+    let unlock_keycode = state.get_unlock_keycode();
 
-    // Check for Lock hotkey (Ctrl+Cmd+Shift+<configured key>)
-    // This only LOCKS, never unlocks (unlock requires passphrase)
-    if keycode == lock_keycode
-        && flags.contains(CGEventFlags::CGEventFlagControl)
-        && flags.contains(CGEventFlags::CGEventFlagCommand)
-        && flags.contains(CGEventFlags::CGEventFlagShift)
-    {
+    // Check for the emergency unlock (panic) hotkey first, so a lock hotkey sharing its
+    // letter with fewer modifiers can't shadow it. Only intercepted while locked: the
+    // global hotkey can't fire then because this tap swallows the keystroke.
+    if state.is_locked() && is_panic_hotkey(state, keycode, flags) {
         if (event_type as u32) == (CGEventType::KeyDown as u32) {
+            auth::panic_unlock(state);
+        }
+        return true; // Block the hotkey itself
+    }
+
+    // Check for Lock hotkey (<configured modifiers>+<configured key>)
+    // This only LOCKS, never unlocks (unlock requires passphrase)
+    if Some(keycode) == lock_keycode && state.get_lock_modifiers().matches(flags) {
+        let is_autorepeat =
+            event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0;
+        if (event_type as u32) == (CGEventType::KeyDown as u32) && !is_autorepeat {
             if !state.is_locked() {
-                info!("Lock hotkey pressed - locking input");
-                state.set_locked(true);
+                if let Err(refused) = state.can_lock() {
+                    log_throttled!(warn, "Lock hotkey ignored - {}", refused);
+                } else if state.register_lock_hotkey_press() {
+                    info!("Lock hotkey pressed - locking input");
+                    if let Err(refused) = state.try_lock() {
+                        log_throttled!(warn, "Lock hotkey ignored - {}", refused);
+                    }
+                } else {
+                    log_throttled!(info, "Lock hotkey pressed - press again to lock");
+                }
             } else {
-                info!("Lock hotkey pressed but already locked (use passphrase to unlock)");
+                log_throttled!(
+                    info,
+                    "Lock hotkey pressed but already locked (use passphrase to unlock)"
+                );
             }
         }
         return true; // Block the hotkey itself
     }
 
-    // Check for Talk hotkey (Ctrl+Cmd+Shift+<configured key>)
-    // Transform it into a spacebar event by modifying the keycode and removing modifiers
-    if keycode == talk_keycode
-        && flags.contains(CGEventFlags::CGEventFlagControl)
-        && flags.contains(CGEventFlags::CGEventFlagCommand)
-        && flags.contains(CGEventFlags::CGEventFlagShift)
-    {
-        const SPACEBAR_KEYCODE: i64 = 49;
+    // Check for Talk hotkey (<configured modifiers>+<configured key>)
+    // Transform it into the configured passthrough key (default: spacebar) by modifying
+    // the keycode and removing modifiers
+    if let Some(passthrough_keycode) = talk_passthrough_keycode(state, keycode, flags) {
+        let is_key_down = (event_type as u32) == (CGEventType::KeyDown as u32);
+        let is_autorepeat =
+            event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0;
 
-        if (event_type as u32) == (CGEventType::KeyDown as u32) {
-            info!("Talk hotkey pressed - transforming to spacebar");
-            state.set_talk_key_pressed(true);
-        } else if (event_type as u32) == (CGEventType::KeyUp as u32) {
-            info!("Talk hotkey released - transforming to spacebar");
-            state.set_talk_key_pressed(false);
+        if !talk_hotkey_passes(state, is_key_down, is_autorepeat) {
+            return true; // Swallowed by the toggle latch
         }
 
-        // Transform the event: change keycode to spacebar and remove modifier flags
-        event.set_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE, SPACEBAR_KEYCODE);
+        // Transform the event: change keycode to the passthrough key and remove modifier flags
+        event.set_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE, passthrough_keycode);
         event.set_flags(CGEventFlags::CGEventFlagNull);
 
         return false; // Allow the transformed event to pass through
     }
 
+    // Check for Touch ID unlock hotkey (Ctrl+Cmd+Shift+<configured key>)
+    // Only meaningful while locked; otherwise the keystroke passes through untouched
+    if state.is_locked()
+        && keycode == unlock_keycode
+        && flags.contains(CGEventFlags::CGEventFlagControl)
+        && flags.contains(CGEventFlags::CGEventFlagCommand)
+        && flags.contains(CGEventFlags::CGEventFlagShift)
+    {
+        if (event_type as u32) == (CGEventType::KeyDown as u32) {
+            if state.get_two_factor_unlock() {
+                // Touch ID alone mustn't unlock; it's prompted for after the passphrase
+                info!("Touch ID unlock hotkey ignored in two-factor mode - type the passphrase first");
+            } else {
+                info!("Touch ID unlock hotkey pressed - requesting biometric authentication");
+                auth::spawn_touchid_unlock(state.clone());
+            }
+        }
+        return true; // Block the hotkey itself
+    }
+
     // If not locked, pass through all non-hotkey events
     if !state.is_locked() {
-        state.update_input_time();
+        state.record_input_activity();
+        return false; // Pass through
+    }
+
+    // Any other key breaks the unlock chord sequence
+    if (event_type as u32) == (CGEventType::KeyDown as u32) {
+        state.reset_unlock_chord();
+    }
+
+    // Keyboard not in lock scope (MouseOnly): let keys through, no passphrase entry
+    if !state.get_lock_scope().blocks_keyboard() {
+        state.record_input_activity();
         return false; // Pass through
     }
 
     // From here on, we're locked - block events and handle passphrase entry
 
+    // Hold-to-unlock key: KeyDown (and its auto-repeats) holds, KeyUp cancels
+    if state.get_hold_unlock().is_some_and(|hold| hold.keycode == keycode) {
+        if (event_type as u32) == (CGEventType::KeyDown as u32) {
+            let is_autorepeat =
+                event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0;
+            // Escape still clears the buffer when first pressed
+            if keycode == ESCAPE_KEYCODE && !is_autorepeat {
+                state.clear_buffer();
+                keycode::clear_pending_dead_key();
+            }
+            if state.press_hold_unlock_key() {
+                auth::hold_unlock(state);
+            }
+        } else {
+            state.release_hold_unlock_key();
+        }
+        return true; // Block the hold key itself
+    }
+
     // Only process KeyDown events for passphrase entry
     // CGEventType doesn't implement PartialEq, so we compare as u32
     if (event_type as u32) != (CGEventType::KeyDown as u32) {
         return true; // Block KeyUp events too
     }
 
-    let shift = flags.contains(CGEventFlags::CGEventFlagShift);
+    // Passphrase input is ignored for a few seconds after a full-length wrong attempt
+    if state.get_attempt_cooldown_remaining().is_some() {
+        return true;
+    }
 
     // Handle Escape key to immediately clear buffer
-    const ESCAPE_KEYCODE: i64 = 53;
     if keycode == ESCAPE_KEYCODE {
         state.clear_buffer();
+        keycode::clear_pending_dead_key();
         debug!("Buffer cleared via Escape key");
         return true; // Block the escape key event
     }
 
     // Handle backspace
     if keycode == BACKSPACE_KEYCODE {
-        let mut buffer = state.get_buffer();
-        if !buffer.is_empty() {
-            buffer.pop();
-            state.lock().input_buffer = buffer;
-        }
+        state.pop_buffer();
         state.update_key_time();
         return true; // Block the event
     }
 
     // Convert keycode to character
-    if let Some(ch) = keycode_to_char(keycode, shift) {
+    if let Some(ch) =
+        keycode::translate_with_current_layout_or(keycode, flags, state.get_keyboard_layout())
+    {
         state.append_to_buffer(ch);
         state.update_key_time();
 
-        debug!("Buffer updated: {}", state.get_buffer());
+        // Length only - the buffer contents are never logged
+        log_throttled!(debug, "Buffer updated ({} chars)", state.buffer_len());
 
         // Check if passphrase matches
         if let Some(hash) = state.get_passphrase_hash() {
             let buffer = state.get_buffer();
             if auth::verify_passphrase(&buffer, &hash) {
-                info!("Passphrase verified - input unlocked");
-                state.set_locked(false);
+                if auth::unlock_with_passphrase(state, UnlockMethod::Passphrase) {
+                    info!("Passphrase verified - input unlocked");
+                }
                 state.clear_buffer();
                 return true; // Block the final matching event
             }
         }
+
+        // Check the one-time emergency recovery code (invalidated after use)
+        if state.try_recovery_code(&state.get_buffer()) {
+            info!("Recovery code verified - input unlocked");
+            return true; // Block the final matching event
+        }
+
+        // A wrong attempt as long as the longest secret it could match: wait before retrying
+        if state.start_attempt_cooldown_if_full_length() {
+            info!(
+                "Wrong passphrase - ignoring input for {} seconds",
+                ATTEMPT_COOLDOWN_SECONDS
+            );
+            if state.is_feedback_sound_enabled() {
+                ui::sound::beep();
+            }
+        } else if state.clear_buffer_if_overlong() {
+            // Cooldown off: longer than the passphrase and still no match - start over now
+            debug!("Cleared passphrase buffer - attempt longer than the passphrase");
+            if state.is_feedback_sound_enabled() {
+                ui::sound::beep();
+            }
+        }
     }
 
     // Block all keyboard events during lock
     true
 }
 
+/// Check whether a key event is the emergency unlock hotkey (disabled unless configured)
+fn is_panic_hotkey(state: &AppState, keycode: i64, flags: CGEventFlags) -> bool {
+    state.get_panic_keycode() == Some(keycode) && state.get_panic_modifiers().matches(flags)
+}
+
+/// Check whether a key event is the Talk hotkey (<configured modifiers>+<configured key>)
+///
+/// Returns the configured passthrough keycode the event should be transformed into,
+/// or None if this is not the Talk hotkey (and so is handled like any other key).
+fn talk_passthrough_keycode(state: &AppState, keycode: i64, flags: CGEventFlags) -> Option<i64> {
+    let is_talk_hotkey =
+        Some(keycode) == state.get_talk_keycode() && state.get_talk_modifiers().matches(flags);
+
+    is_talk_hotkey.then(|| state.get_talk_passthrough_keycode())
+}
+
+/// Update Talk state for a Talk hotkey event and decide whether it passes through
+///
+/// Hold mode: KeyDown opens passthrough, KeyUp closes it; every event passes.
+/// Toggle mode: the first press passes its KeyDown and swallows its KeyUp, leaving the
+/// passthrough key held down; the next press swallows its KeyDown and passes its KeyUp,
+/// releasing it. Auto-repeated KeyDowns are swallowed so holding the hotkey doesn't flap.
+fn talk_hotkey_passes(state: &AppState, is_key_down: bool, is_autorepeat: bool) -> bool {
+    match state.get_talk_mode() {
+        TalkMode::Hold => {
+            if is_key_down {
+                if !is_autorepeat {
+                    log_throttled!(info, "Talk hotkey pressed - passthrough open");
+                }
+                state.set_talk_key_pressed(true);
+            } else {
+                log_throttled!(info, "Talk hotkey released - passthrough closed");
+                state.set_talk_key_pressed(false);
+            }
+            true
+        }
+        TalkMode::Toggle => {
+            let latched = state.is_talk_key_pressed();
+            if !is_key_down {
+                // Release of the press that latched on is swallowed; the one that
+                // latched off releases the passthrough key
+                return !latched;
+            }
+            if is_autorepeat {
+                return false;
+            }
+            if latched {
+                log_throttled!(info, "Talk hotkey pressed - passthrough toggled off");
+            } else {
+                log_throttled!(info, "Talk hotkey pressed - passthrough toggled on");
+            }
+            state.set_talk_key_pressed(!latched);
+            !latched
+        }
+    }
+}
+
+/// Whether a mouse event only moves the pointer (a move or a drag)
+pub fn is_pointer_motion(event_type: CGEventType) -> bool {
+    [
+        CGEventType::MouseMoved,
+        CGEventType::LeftMouseDragged,
+        CGEventType::RightMouseDragged,
+        CGEventType::OtherMouseDragged,
+    ]
+    .iter()
+    .any(|&motion| motion as u32 == event_type as u32)
+}
+
+/// Reset the auto-lock timer for a mouse/trackpad event
+///
+/// Pointer motion doesn't count when `auto_lock_ignore_mouse_move` is set, so a
+/// twitchy mouse can't hold off auto-lock; clicks and scrolls always do.
+pub fn record_mouse_activity(event_type: CGEventType, state: &AppState) {
+    if !(is_pointer_motion(event_type) && state.get_auto_lock_ignore_mouse_move()) {
+        state.record_input_activity();
+    }
+}
+
 /// Handle a mouse/trackpad event during lock
 ///
-/// Returns true if the event should be blocked
-pub fn handle_mouse_event(_event_type: CGEventType, state: &AppState) -> bool {
+/// Returns true if the event should be blocked (false in KeyboardOnly lock scope)
+pub fn handle_mouse_event(event_type: CGEventType, state: &AppState) -> bool {
     // Update input time for auto-lock tracking
-    state.update_input_time();
+    record_mouse_activity(event_type, state);
 
-    // Block all mouse/trackpad events during lock
-    true
+    // Block all mouse/trackpad events during lock, unless mouse is outside the lock scope
+    let block = state.get_lock_scope().blocks_mouse();
+    if block {
+        state.record_blocked_event();
+    }
+    block
+}
+
+/// Handle an NX system-defined event (media, volume, brightness and other special keys)
+///
+/// These arrive as their own event type rather than KeyDown/KeyUp, so they bypass
+/// keyboard handling. Returns true if the event should be blocked: only while locked
+/// and only when `block_power_key` (power / Touch ID key) or `block_media_keys`
+/// (everything else) is enabled.
+///
+/// To verify: lock input, then press the F-row volume/brightness/play keys (without Fn
+/// on a Mac keyboard) - nothing should change. Unlock and they should work again.
+/// A short press of the power / Touch ID key should not bring up the sleep dialog.
+pub fn handle_system_defined_event(state: &AppState, key: SystemKey) -> bool {
+    if !state.is_locked() || !state.get_lock_scope().blocks_keyboard() {
+        return false; // Pass through when unlocked or keyboard is outside the lock scope
+    }
+
+    let block = match key {
+        SystemKey::Power => state.get_block_power_key(),
+        SystemKey::Media => state.get_block_media_keys(),
+    };
+    if block {
+        state.record_blocked_event();
+    }
+    block
+}
+
+/// Final blocking decision for an event, honouring dry-run mode
+///
+/// In dry-run mode a would-be block is logged (with the passphrase buffer progress)
+/// and the event passes through. Returns true if the event should be blocked.
+pub fn apply_dry_run(state: &AppState, should_block: bool, event_type: u32) -> bool {
+    if should_block && state.is_dry_run() {
+        debug!(
+            "[dry-run] would block event type {} (locked: {}, passphrase buffer: {} chars)",
+            event_type,
+            state.is_locked(),
+            state.buffer_len()
+        );
+        return false;
+    }
+    should_block
 }
 
 /// Lightweight accessibility permission check using only AXIsProcessTrusted().
@@ -149,9 +401,148 @@ pub fn check_accessibility_permissions_lightweight() -> bool {
     unsafe { AXIsProcessTrusted() }
 }
 
+/// Accessibility permission state as of the last full probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionProbe {
+    /// AXIsProcessTrusted() at probe time
+    pub ax_trusted: bool,
+    /// Whether the test event tap could be created (authoritative)
+    pub has_permissions: bool,
+}
+
+/// Whether a fresh AXIsProcessTrusted() result calls for a new full probe
+///
+/// Only a change since the last probe does: AXIsProcessTrusted() can disagree with
+/// the tap test (it caches stale results after a grant), so its value alone isn't
+/// trusted, but a transition means the permissions may really have changed.
+pub fn needs_full_probe(last: &PermissionProbe, ax_trusted: bool) -> bool {
+    ax_trusted != last.ax_trusted
+}
+
+/// Periodic permission check: the cheap AXIsProcessTrusted() result decides whether
+/// to rerun the expensive `probe`; otherwise the last probe stands
+pub fn refresh_permission_probe(
+    last: PermissionProbe,
+    ax_trusted: bool,
+    probe: impl FnOnce() -> PermissionProbe,
+) -> PermissionProbe {
+    if needs_full_probe(&last, ax_trusted) {
+        info!(
+            "AXIsProcessTrusted changed ({} -> {}) - re-probing event tap creation",
+            last.ax_trusted, ax_trusted
+        );
+        probe()
+    } else {
+        last
+    }
+}
+
+/// Structured log entry for an accessibility permission change, e.g.
+/// `permission_change from=true to=false at=2026-10-16T09:30:00.000Z`
+///
+/// Every transition is logged in this one key=value shape, so flapping permissions
+/// are easy to find in the logs and line up with changes made in System Settings.
+/// `at` is an ISO 8601 timestamp, so entries from different days stay distinct.
+pub fn permission_change_entry(from: bool, to: bool, at: &str) -> String {
+    format!("permission_change from={} to={} at={}", from, to, at)
+}
+
+/// Counts consecutive negative permission checks until a loss is confirmed
+///
+/// A single negative check can be a transient blip, so a loss is only acted on after
+/// PERMISSION_LOSS_CONFIRMATIONS negative checks in a row.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionLossDebounce {
+    consecutive_misses: u32,
+}
+
+impl PermissionLossDebounce {
+    /// Record a permission check, returning true once the loss is confirmed
+    ///
+    /// A positive check resets the count.
+    pub fn record(&mut self, has_permissions: bool) -> bool {
+        if has_permissions {
+            self.consecutive_misses = 0;
+            return false;
+        }
+        self.consecutive_misses = self.consecutive_misses.saturating_add(1);
+        self.consecutive_misses >= PERMISSION_LOSS_CONFIRMATIONS
+    }
+
+    /// Whether a suspected loss is waiting for confirmation
+    pub fn is_pending(&self) -> bool {
+        self.consecutive_misses > 0
+    }
+}
+
+/// Decides when restored permissions are stable enough to restart the event tap
+///
+/// A restart is requested only after PERMISSION_RESTORE_CONFIRMATIONS positive checks
+/// in a row, and at most once per EVENT_TAP_RESTART_MIN_INTERVAL_SECS; a restart held
+/// back by the interval is requested on a later check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionRestoreDebounce {
+    /// Whether the tap was stopped for missing permissions and awaits a restart
+    restart_needed: bool,
+    consecutive_grants: u32,
+    last_restart: Option<Instant>,
+}
+
+impl PermissionRestoreDebounce {
+    /// Start tracking, given whether permissions are granted right now
+    pub fn new(has_permissions: bool) -> Self {
+        Self {
+            restart_needed: !has_permissions,
+            consecutive_grants: 0,
+            last_restart: None,
+        }
+    }
+
+    /// Record a permission check, returning true when the tap should be restarted now
+    ///
+    /// A negative check resets the count (and makes a restart necessary again).
+    pub fn record(&mut self, has_permissions: bool, now: Instant) -> bool {
+        if !has_permissions {
+            self.restart_needed = true;
+            self.consecutive_grants = 0;
+            return false;
+        }
+        if !self.restart_needed {
+            return false;
+        }
+
+        self.consecutive_grants = self.consecutive_grants.saturating_add(1);
+        let min_interval = Duration::from_secs(EVENT_TAP_RESTART_MIN_INTERVAL_SECS);
+        let interval_elapsed = self
+            .last_restart
+            .map_or(true, |last| now.saturating_duration_since(last) >= min_interval);
+        if self.consecutive_grants < PERMISSION_RESTORE_CONFIRMATIONS || !interval_elapsed {
+            return false;
+        }
+
+        self.restart_needed = false;
+        self.consecutive_grants = 0;
+        self.last_restart = Some(now);
+        true
+    }
+
+    /// Whether restored permissions are waiting to be confirmed before a restart
+    pub fn is_pending(&self) -> bool {
+        self.restart_needed && self.consecutive_grants > 0
+    }
+}
+
 /// Check accessibility permissions (full check with test tap creation).
 /// Use only at startup or for one-time validation — NOT for periodic monitoring.
 pub fn check_accessibility_permissions() -> bool {
+    probe_accessibility_permissions().has_permissions
+}
+
+/// Full permission probe: AXIsProcessTrusted() plus a throwaway test event tap
+///
+/// Creating a tap interacts with WindowServer, so the permission monitor only calls
+/// this at startup and when AXIsProcessTrusted() changes (see `refresh_permission_probe`).
+pub fn probe_accessibility_permissions() -> PermissionProbe {
     use core_graphics::sys::CGEventTapRef;
     use std::ffi::c_void;
 
@@ -194,9 +585,7 @@ pub fn check_accessibility_permissions() -> bool {
         event
     }
 
-    const K_CGSESSION_EVENT_TAP: u32 = 1;
-    const K_CGHEAD_INSERT_EVENT_TAP: u32 = 0;
-    const K_CGEVENT_TAP_OPTION_DEFAULT: u32 = 0;
+    use event_tap::{K_CGEVENT_TAP_OPTION_DEFAULT, K_CGHEAD_INSERT_EVENT_TAP, K_CGSESSION_EVENT_TAP};
 
     unsafe {
         // Check using AXIsProcessTrusted first (informational)
@@ -241,7 +630,659 @@ pub fn check_accessibility_permissions() -> bool {
             error!("  - Please check System Settings > Privacy & Security > Accessibility");
         }
 
-        // Return true if event tap can be created (the actual test that matters)
-        tap_created
+        // Event tap creation is the actual test that matters
+        PermissionProbe {
+            ax_trusted,
+            has_permissions: tap_created,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_state::LockScope;
+    use crate::constants::{DEFAULT_LOCK_KEYCODE, DEFAULT_TALK_KEYCODE, SPACEBAR_KEYCODE};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+    use filter::{Decision, EventKind};
+
+    #[test]
+    fn test_permission_change_entry() {
+        assert_eq!(
+            permission_change_entry(true, false, "2026-10-16T09:30:05.250Z"),
+            "permission_change from=true to=false at=2026-10-16T09:30:05.250Z"
+        );
+        assert_eq!(
+            permission_change_entry(false, true, "2026-10-16T09:30:05.250Z"),
+            "permission_change from=false to=true at=2026-10-16T09:30:05.250Z"
+        );
+    }
+
+    #[test]
+    fn test_permission_loss_needs_consecutive_misses() {
+        let mut debounce = PermissionLossDebounce::default();
+        assert!(!debounce.is_pending());
+        assert!(!debounce.record(true));
+
+        assert!(!debounce.record(false), "A single miss is only suspected");
+        assert!(debounce.is_pending());
+        assert!(debounce.record(false), "Second miss in a row confirms the loss");
+    }
+
+    #[test]
+    fn test_permission_blip_resets_debounce() {
+        let mut debounce = PermissionLossDebounce::default();
+        assert!(!debounce.record(false));
+        assert!(!debounce.record(true), "Permissions came back");
+        assert!(!debounce.is_pending());
+        assert!(!debounce.record(false), "Count starts over");
+        assert!(debounce.record(false));
+    }
+
+    #[test]
+    fn test_restore_needs_consecutive_grants() {
+        let t0 = Instant::now();
+        let mut debounce = PermissionRestoreDebounce::new(false);
+        assert!(!debounce.record(false, t0));
+        assert!(!debounce.record(true, t0), "First grant only arms");
+        assert!(debounce.is_pending());
+        assert!(debounce.record(true, t0 + Duration::from_secs(15)));
+        assert!(!debounce.is_pending());
+
+        // Stays granted: nothing more to restart
+        assert!(!debounce.record(true, t0 + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_restore_flapping_does_not_restart() {
+        let t0 = Instant::now();
+        let mut debounce = PermissionRestoreDebounce::new(true);
+        assert!(!debounce.record(true, t0), "Never lost: no restart");
+
+        for i in 0..6 {
+            let has_permissions = i % 2 == 0;
+            assert!(!debounce.record(has_permissions, t0 + Duration::from_secs(i)));
+        }
+    }
+
+    #[test]
+    fn test_restarts_are_rate_limited() {
+        let t0 = Instant::now();
+        let mut debounce = PermissionRestoreDebounce::new(false);
+        assert!(!debounce.record(true, t0));
+        assert!(debounce.record(true, t0 + Duration::from_secs(1)));
+
+        // Lost and stably restored again right away: held back by the minimum interval
+        let t1 = t0 + Duration::from_secs(2);
+        assert!(!debounce.record(false, t1));
+        assert!(!debounce.record(true, t1));
+        assert!(!debounce.record(true, t1 + Duration::from_secs(1)));
+        assert!(debounce.is_pending(), "Still owed a restart");
+
+        let later = t0 + Duration::from_secs(1 + EVENT_TAP_RESTART_MIN_INTERVAL_SECS);
+        assert!(debounce.record(true, later));
+    }
+
+    #[test]
+    fn test_full_probe_only_on_ax_transitions() {
+        let granted = PermissionProbe {
+            ax_trusted: true,
+            has_permissions: true,
+        };
+        let revoked = PermissionProbe {
+            ax_trusted: false,
+            has_permissions: false,
+        };
+
+        // Steady state: no probe, previous result stands
+        let mut probes = 0;
+        let result = refresh_permission_probe(granted, true, || {
+            probes += 1;
+            revoked
+        });
+        assert_eq!((result, probes), (granted, 0));
+
+        // AX flips to untrusted: re-probe and take the new result
+        let result = refresh_permission_probe(granted, false, || {
+            probes += 1;
+            revoked
+        });
+        assert_eq!((result, probes), (revoked, 1));
+        assert!(needs_full_probe(&revoked, true), "Grant is re-probed too");
+    }
+
+    #[test]
+    fn test_stale_ax_result_does_not_flap() {
+        // Startup probe: tap works although AXIsProcessTrusted() reports false (caching issue)
+        let stale = PermissionProbe {
+            ax_trusted: false,
+            has_permissions: true,
+        };
+        let result = refresh_permission_probe(stale, false, || unreachable!("No re-probe"));
+        assert!(result.has_permissions, "Stale AX result doesn't look like a revocation");
+    }
+
+    #[test]
+    fn test_media_keys_pass_through_when_unlocked() {
+        let state = AppState::new();
+        assert!(!handle_system_defined_event(&state, SystemKey::Media));
+    }
+
+    #[test]
+    fn test_media_keys_blocked_when_locked_by_default() {
+        let state = AppState::new();
+        state.set_locked(true);
+        assert!(handle_system_defined_event(&state, SystemKey::Media));
+    }
+
+    #[test]
+    fn test_mouse_blocked_when_locked_in_all_and_mouse_only_scope() {
+        let state = AppState::new();
+        state.set_locked(true);
+        assert!(handle_mouse_event(CGEventType::LeftMouseDown, &state));
+
+        state.set_lock_scope(LockScope::MouseOnly);
+        assert!(handle_mouse_event(CGEventType::LeftMouseDown, &state));
+    }
+
+    #[test]
+    fn test_which_mouse_events_reset_the_auto_lock_timer() {
+        let motion = [
+            CGEventType::MouseMoved,
+            CGEventType::LeftMouseDragged,
+            CGEventType::RightMouseDragged,
+            CGEventType::OtherMouseDragged,
+        ];
+        let deliberate = [
+            CGEventType::LeftMouseDown,
+            CGEventType::RightMouseUp,
+            CGEventType::ScrollWheel,
+        ];
+        let resets = |state: &AppState, event_type: CGEventType| {
+            let idle_since = Instant::now() - Duration::from_secs(60);
+            state.lock().last_input_time = idle_since;
+            record_mouse_activity(event_type, state);
+            state.lock().last_input_time != idle_since
+        };
+
+        let state = AppState::new();
+        for event_type in motion.into_iter().chain(deliberate) {
+            assert!(resets(&state, event_type), "Every mouse event counts by default");
+        }
+
+        state.set_auto_lock_ignore_mouse_move(true);
+        for event_type in motion {
+            assert!(!resets(&state, event_type));
+        }
+        for event_type in deliberate {
+            assert!(resets(&state, event_type));
+        }
+    }
+
+    #[test]
+    fn test_blocked_events_counted_per_lock() {
+        let state = AppState::new();
+        assert!(!handle_system_defined_event(&state, SystemKey::Media));
+        assert_eq!(state.get_blocked_event_count(), 0, "Passed-through events aren't counted");
+
+        state.set_locked(true);
+        assert!(handle_mouse_event(CGEventType::LeftMouseDown, &state));
+        assert!(handle_mouse_event(CGEventType::ScrollWheel, &state));
+        assert!(handle_system_defined_event(&state, SystemKey::Media));
+        assert_eq!(state.get_blocked_event_count(), 3);
+
+        state.set_locked(false);
+        state.set_locked(true);
+        assert_eq!(state.get_blocked_event_count(), 0, "Reset when a new lock engages");
+
+        state.set_lock_scope(LockScope::KeyboardOnly);
+        assert!(!handle_mouse_event(CGEventType::LeftMouseDown, &state));
+        assert_eq!(state.get_blocked_event_count(), 0);
+    }
+
+    #[test]
+    fn test_mouse_passes_in_keyboard_only_scope() {
+        let state = AppState::new();
+        state.set_lock_scope(LockScope::KeyboardOnly);
+        state.set_locked(true);
+        assert!(!handle_mouse_event(CGEventType::LeftMouseDown, &state));
+        assert!(!handle_mouse_event(CGEventType::ScrollWheel, &state));
+    }
+
+    #[test]
+    fn test_media_keys_pass_in_mouse_only_scope() {
+        let state = AppState::new();
+        state.set_lock_scope(LockScope::MouseOnly);
+        state.set_locked(true);
+        assert!(!handle_system_defined_event(&state, SystemKey::Media));
+    }
+
+    #[test]
+    fn test_dry_run_passes_blocked_events_through() {
+        let state = AppState::new();
+        state.set_locked(true);
+        let mouse_down = CGEventType::LeftMouseDown as u32;
+
+        // The handler still decides to block...
+        assert!(handle_mouse_event(CGEventType::LeftMouseDown, &state));
+        assert!(apply_dry_run(&state, true, mouse_down));
+
+        // ...but in dry-run mode the callback passes the event through
+        state.set_dry_run(true);
+        assert!(handle_mouse_event(CGEventType::LeftMouseDown, &state));
+        assert!(!apply_dry_run(&state, true, mouse_down));
+        assert!(!apply_dry_run(&state, false, mouse_down));
+        assert!(state.is_locked(), "Dry-run doesn't change the lock state");
+    }
+
+    #[test]
+    fn test_media_keys_allowed_when_blocking_disabled() {
+        let state = AppState::new();
+        state.set_block_media_keys(false);
+        state.set_locked(true);
+        assert!(!handle_system_defined_event(&state, SystemKey::Media));
+        assert!(handle_system_defined_event(&state, SystemKey::Power), "Separate flag");
+    }
+
+    /// Synthesize a key event (never posted, only handed to the handler)
+    fn key_event(keycode: u16, key_down: bool) -> CGEvent {
+        let source = CGEventSource::new(CGEventSourceStateID::Private).unwrap();
+        CGEvent::new_keyboard_event(source, keycode, key_down).unwrap()
+    }
+
+    /// Key event with the given modifiers held
+    fn key_event_with_flags(keycode: u16, flags: CGEventFlags) -> CGEvent {
+        let event = key_event(keycode, true);
+        event.set_flags(flags);
+        event
+    }
+
+    #[test]
+    fn test_configured_lock_letter_locks_via_tap() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(auth::hash_passphrase("test"));
+        let k = keycode::code_to_keycode(global_hotkey::hotkey::Code::KeyK).unwrap();
+        state.set_lock_keycode(Some(k));
+
+        // The default L is an ordinary key now
+        let l = keycode::code_to_keycode(global_hotkey::hotkey::Code::KeyL).unwrap();
+        let old_hotkey = key_event_with_flags(l as u16, hotkey_flags());
+        assert!(!handle_keyboard_event(&old_hotkey, CGEventType::KeyDown, &state));
+        assert!(!state.is_locked());
+
+        let new_hotkey = key_event_with_flags(k as u16, hotkey_flags());
+        assert!(handle_keyboard_event(&new_hotkey, CGEventType::KeyDown, &state));
+        assert!(state.is_locked());
+    }
+
+    #[test]
+    fn test_lock_hotkey_ignored_without_passphrase() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        let hotkey = key_event_with_flags(DEFAULT_LOCK_KEYCODE as u16, hotkey_flags());
+        assert!(handle_keyboard_event(&hotkey, CGEventType::KeyDown, &state), "Still swallowed");
+        assert!(!state.is_locked());
+
+        state.set_passphrase_hash(auth::hash_passphrase("test"));
+        assert!(handle_keyboard_event(&hotkey, CGEventType::KeyDown, &state));
+        assert!(state.is_locked());
+    }
+
+    #[test]
+    fn test_configured_talk_letter_detected_via_tap() {
+        let state = AppState::new();
+        let y = keycode::code_to_keycode(global_hotkey::hotkey::Code::KeyY).unwrap();
+        state.set_talk_keycode(Some(y));
+
+        // The default T is no longer rewritten into the passthrough key
+        let t = key_event_with_flags(DEFAULT_TALK_KEYCODE as u16, hotkey_flags());
+        assert!(!handle_keyboard_event(&t, CGEventType::KeyDown, &state));
+        assert_eq!(
+            t.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE),
+            DEFAULT_TALK_KEYCODE
+        );
+
+        let talk = key_event_with_flags(y as u16, hotkey_flags());
+        assert!(!handle_keyboard_event(&talk, CGEventType::KeyDown, &state), "Talk passes");
+        assert_eq!(
+            talk.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE),
+            SPACEBAR_KEYCODE
+        );
+        assert!(state.is_talk_key_pressed());
+    }
+
+    #[test]
+    fn test_disabled_lock_hotkey_is_normal_input() {
+        let state = AppState::new();
+        state.set_lock_keycode(None);
+
+        let hotkey = key_event_with_flags(DEFAULT_LOCK_KEYCODE as u16, hotkey_flags());
+        assert!(!handle_keyboard_event(&hotkey, CGEventType::KeyDown, &state));
+        assert!(!state.is_locked());
+
+        // While locked it's blocked like any other key, not treated as a hotkey
+        state.set_locked(true);
+        assert!(handle_keyboard_event(&hotkey, CGEventType::KeyDown, &state));
+        assert!(state.is_locked());
+    }
+
+    #[test]
+    fn test_disabled_talk_hotkey_is_normal_input() {
+        let state = AppState::new();
+        state.set_talk_keycode(None);
+        state.set_locked(true);
+
+        let t = key_event_with_flags(DEFAULT_TALK_KEYCODE as u16, hotkey_flags());
+        assert!(handle_keyboard_event(&t, CGEventType::KeyDown, &state), "Blocked, not passed");
+        assert_eq!(
+            t.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE),
+            DEFAULT_TALK_KEYCODE
+        );
+        assert!(!state.is_talk_key_pressed());
+    }
+
+    #[test]
+    fn test_event_filter_forces_block_and_pass() {
+        let state = AppState::new();
+        // Block Q (keycode 12) even while unlocked; leave everything else to HandsOff
+        state.set_event_filter(Some(Box::new(|kind, info| {
+            if kind == EventKind::Keyboard && info.keycode == Some(12) {
+                Decision::Block
+            } else {
+                Decision::Default
+            }
+        })));
+        assert!(handle_keyboard_event(&key_event(12, true), CGEventType::KeyDown, &state));
+        assert!(!handle_keyboard_event(&key_event(0, true), CGEventType::KeyDown, &state));
+
+        // Let every key through, even while locked
+        state.set_event_filter(Some(Box::new(|_, _| Decision::Pass)));
+        state.set_locked(true);
+        assert!(!handle_keyboard_event(&key_event(12, true), CGEventType::KeyDown, &state));
+        assert!(!handle_keyboard_event(&key_event(12, false), CGEventType::KeyUp, &state));
+        assert_eq!(state.get_blocked_event_count(), 0);
+
+        state.set_event_filter(None);
+        assert!(handle_keyboard_event(&key_event(12, true), CGEventType::KeyDown, &state));
+    }
+
+    #[test]
+    fn test_event_filter_sees_lock_state_and_keeps_passphrase_entry() {
+        let state = AppState::new();
+        state.set_passphrase_hash(auth::hash_passphrase("asd"));
+        state.set_locked(true);
+        state.set_event_filter(Some(Box::new(|_, info| {
+            if info.locked {
+                Decision::Pass
+            } else {
+                Decision::Block
+            }
+        })));
+
+        // A, S, D on a US layout: passed through by the filter, yet still unlock
+        for keycode in [0, 1, 2] {
+            assert!(!handle_keyboard_event(&key_event(keycode, true), CGEventType::KeyDown, &state));
+        }
+        assert!(!state.is_locked());
+        assert!(handle_keyboard_event(&key_event(0, true), CGEventType::KeyDown, &state));
+    }
+
+    #[test]
+    fn test_passphrase_ignored_during_attempt_cooldown() {
+        let state = AppState::new();
+        state.set_passphrase_hash(auth::hash_passphrase("asd"));
+        state.set_passphrase_len(Some(3));
+        state.set_locked(true);
+
+        // A, S, F on a US layout: a full-length wrong attempt starts the cooldown
+        for keycode in [0, 1, 3] {
+            assert!(handle_keyboard_event(&key_event(keycode, true), CGEventType::KeyDown, &state));
+        }
+        assert_eq!(state.get_attempt_cooldown_remaining(), Some(ATTEMPT_COOLDOWN_SECONDS));
+        assert_eq!(state.buffer_len(), 0);
+
+        // The right passphrase typed during the cooldown is ignored (and still blocked)
+        for keycode in [0, 1, 2] {
+            assert!(handle_keyboard_event(&key_event(keycode, true), CGEventType::KeyDown, &state));
+        }
+        assert!(state.is_locked());
+        assert_eq!(state.buffer_len(), 0);
+    }
+
+    /// Press and release Cmd+Shift, returning whether any of its events was blocked
+    fn press_cmd_shift(state: &AppState) -> bool {
+        let cmd = CGEventFlags::CGEventFlagCommand;
+        let cmd_shift = cmd | CGEventFlags::CGEventFlagShift;
+        [cmd, cmd_shift, cmd, CGEventFlags::CGEventFlagNull]
+            .into_iter()
+            .any(|flags| {
+                handle_keyboard_event(&key_event_with_flags(55, flags), CGEventType::FlagsChanged, state)
+            })
+    }
+
+    #[test]
+    fn test_unlock_chord_sequence_unlocks() {
+        let state = AppState::new();
+        state.set_passphrase_hash(auth::hash_passphrase("asd"));
+        state.set_unlock_chord(Some(chord::UnlockChord {
+            modifiers: "cmd+shift".parse().unwrap(),
+            presses: 3,
+        }));
+        state.set_locked(true);
+
+        // Modifier changes are never blocked
+        assert!(!press_cmd_shift(&state));
+        assert!(!press_cmd_shift(&state));
+
+        // Another key in between starts the count over
+        assert!(handle_keyboard_event(&key_event(0, true), CGEventType::KeyDown, &state));
+        assert!(!press_cmd_shift(&state));
+        assert!(!press_cmd_shift(&state));
+        assert!(state.is_locked());
+        press_cmd_shift(&state);
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_unlock_chord_disabled_by_default_and_in_two_factor_mode() {
+        let state = AppState::new();
+        state.set_locked(true);
+        for _ in 0..5 {
+            press_cmd_shift(&state);
+        }
+        assert!(state.is_locked(), "No unlock chord configured");
+
+        state.set_unlock_chord(Some(chord::UnlockChord {
+            modifiers: "cmd+shift".parse().unwrap(),
+            presses: 2,
+        }));
+        state.set_two_factor_unlock(true);
+        press_cmd_shift(&state);
+        press_cmd_shift(&state);
+        assert!(state.is_locked(), "Two-factor mode needs the passphrase and Touch ID");
+    }
+
+    #[test]
+    fn test_hold_to_unlock_key() {
+        let state = AppState::new();
+        state.set_passphrase_hash(auth::hash_passphrase("asd"));
+        state.set_hold_unlock(Some(hold_unlock::HoldUnlock {
+            keycode: ESCAPE_KEYCODE,
+            duration: Duration::from_secs(3),
+        }));
+        state.set_locked(true);
+        state.append_to_buffer('a');
+
+        // Pressing Escape starts the hold (and still clears the buffer); releasing cancels it
+        let esc = ESCAPE_KEYCODE as u16;
+        assert!(handle_keyboard_event(&key_event(esc, true), CGEventType::KeyDown, &state));
+        assert_eq!(state.buffer_len(), 0);
+        assert!(state.get_hold_unlock_progress().is_some());
+        assert!(handle_keyboard_event(&key_event(esc, false), CGEventType::KeyUp, &state));
+        assert_eq!(state.get_hold_unlock_progress(), None);
+        assert!(state.is_locked());
+
+        // A hold that's already long enough unlocks on the next KeyDown
+        state.set_hold_unlock(Some(hold_unlock::HoldUnlock {
+            keycode: ESCAPE_KEYCODE,
+            duration: Duration::ZERO,
+        }));
+        state.set_two_factor_unlock(true);
+        handle_keyboard_event(&key_event(esc, true), CGEventType::KeyDown, &state);
+        assert!(state.is_locked(), "Two-factor mode needs the passphrase and Touch ID");
+        state.set_two_factor_unlock(false);
+        handle_keyboard_event(&key_event(esc, true), CGEventType::KeyDown, &state);
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_power_key_blocked_only_when_locked_and_enabled() {
+        let state = AppState::new();
+        assert!(!handle_system_defined_event(&state, SystemKey::Power));
+
+        state.set_locked(true);
+        assert!(handle_system_defined_event(&state, SystemKey::Power), "Blocked by default");
+
+        state.set_block_power_key(false);
+        assert!(!handle_system_defined_event(&state, SystemKey::Power));
+        assert!(handle_system_defined_event(&state, SystemKey::Media), "Separate flag");
+    }
+
+    fn hotkey_flags() -> CGEventFlags {
+        CGEventFlags::CGEventFlagControl
+            | CGEventFlags::CGEventFlagCommand
+            | CGEventFlags::CGEventFlagShift
+    }
+
+    #[test]
+    fn test_talk_hotkey_passes_spacebar_by_default() {
+        let state = AppState::new();
+        assert_eq!(
+            talk_passthrough_keycode(&state, DEFAULT_TALK_KEYCODE, hotkey_flags()),
+            Some(SPACEBAR_KEYCODE)
+        );
+    }
+
+    #[test]
+    fn test_talk_hotkey_passes_configured_key() {
+        let state = AppState::new();
+        state.set_talk_passthrough_keycode(46); // 'M'
+        assert_eq!(
+            talk_passthrough_keycode(&state, DEFAULT_TALK_KEYCODE, hotkey_flags()),
+            Some(46)
+        );
+    }
+
+    #[test]
+    fn test_other_keys_not_passed_through_in_talk_mode() {
+        let state = AppState::new();
+        state.set_talk_passthrough_keycode(46); // 'M'
+
+        // The passthrough key itself, or the talk letter without modifiers, is not the hotkey
+        assert_eq!(talk_passthrough_keycode(&state, 46, CGEventFlags::CGEventFlagNull), None);
+        assert_eq!(talk_passthrough_keycode(&state, 46, hotkey_flags()), None);
+        assert_eq!(
+            talk_passthrough_keycode(&state, DEFAULT_TALK_KEYCODE, CGEventFlags::CGEventFlagShift),
+            None
+        );
+        assert_eq!(
+            talk_passthrough_keycode(&state, SPACEBAR_KEYCODE, CGEventFlags::CGEventFlagNull),
+            None
+        );
+    }
+
+    fn all_modifier_flags() -> CGEventFlags {
+        hotkey_flags() | CGEventFlags::CGEventFlagAlternate
+    }
+
+    #[test]
+    fn test_panic_hotkey_disabled_by_default() {
+        let state = AppState::new();
+        for keycode in 0..=50 {
+            assert!(!is_panic_hotkey(&state, keycode, all_modifier_flags()));
+        }
+    }
+
+    #[test]
+    fn test_panic_hotkey_requires_all_configured_modifiers() {
+        let state = AppState::new();
+        state.set_panic_hotkey(Some(7), HotkeyModifiers::PANIC_DEFAULT); // 'X'
+
+        assert!(is_panic_hotkey(&state, 7, all_modifier_flags()));
+        assert!(!is_panic_hotkey(&state, 7, hotkey_flags()), "Opt is missing");
+        assert!(!is_panic_hotkey(&state, 7, CGEventFlags::CGEventFlagNull));
+        assert!(!is_panic_hotkey(&state, 8, all_modifier_flags()), "Wrong key");
+
+        state.set_panic_hotkey(Some(7), "cmd+opt".parse().unwrap());
+        assert!(is_panic_hotkey(
+            &state,
+            7,
+            CGEventFlags::CGEventFlagCommand | CGEventFlags::CGEventFlagAlternate
+        ));
+    }
+
+    #[test]
+    fn test_panic_unlock_bypasses_passphrase() {
+        let state = AppState::new();
+        state.set_panic_hotkey(Some(7), HotkeyModifiers::PANIC_DEFAULT);
+        state.set_locked(true);
+        state.append_to_buffer('a');
+
+        auth::panic_unlock(&state);
+        assert!(!state.is_locked());
+        assert_eq!(state.buffer_len(), 0);
+    }
+
+    /// Feed a full press (KeyDown then KeyUp) of the Talk hotkey, returning (down, up) passes
+    fn press_talk(state: &AppState) -> (bool, bool) {
+        (talk_hotkey_passes(state, true, false), talk_hotkey_passes(state, false, false))
+    }
+
+    #[test]
+    fn test_talk_hold_mode_passes_down_and_up() {
+        let state = AppState::new();
+        assert!(talk_hotkey_passes(&state, true, false));
+        assert!(state.is_talk_key_pressed());
+        assert!(talk_hotkey_passes(&state, true, true), "Auto-repeat passes while held");
+        assert!(talk_hotkey_passes(&state, false, false));
+        assert!(!state.is_talk_key_pressed());
+    }
+
+    #[test]
+    fn test_talk_toggle_mode_latches() {
+        let state = AppState::new();
+        state.set_talk_mode(TalkMode::Toggle);
+
+        // First press: KeyDown passes (passthrough key down), KeyUp swallowed
+        assert_eq!(press_talk(&state), (true, false));
+        assert!(state.is_talk_key_pressed());
+
+        // Second press: KeyDown swallowed, KeyUp passes (passthrough key up)
+        assert_eq!(press_talk(&state), (false, true));
+        assert!(!state.is_talk_key_pressed());
+    }
+
+    #[test]
+    fn test_talk_toggle_mode_ignores_autorepeat() {
+        let state = AppState::new();
+        state.set_talk_mode(TalkMode::Toggle);
+
+        assert!(talk_hotkey_passes(&state, true, false));
+        assert!(!talk_hotkey_passes(&state, true, true));
+        assert!(!talk_hotkey_passes(&state, true, true));
+        assert!(state.is_talk_key_pressed(), "Auto-repeat must not toggle the latch");
+    }
+
+    #[test]
+    fn test_talk_toggle_reset_by_lock() {
+        let state = AppState::new();
+        state.set_talk_mode(TalkMode::Toggle);
+
+        assert_eq!(press_talk(&state), (true, false));
+        state.set_locked(true);
+
+        // Next press starts a fresh latch rather than closing the old one
+        assert_eq!(press_talk(&state), (true, false));
     }
 }
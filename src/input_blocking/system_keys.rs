@@ -0,0 +1,122 @@
+//! Classify NX system-defined events (media keys vs. the power / Touch ID key)
+//!
+//! Special keys arrive as NX_SYSDEFINED events rather than KeyDown/KeyUp. The event's
+//! subtype and `data1` (only reachable through `NSEvent`) tell the power key apart
+//! from volume, brightness and playback keys, so each can be blocked separately.
+//!
+//! OS limitations: blocking the power key event only stops what the session sees
+//! (e.g. the sleep / shutdown dialog). Holding the power button to force a shutdown is
+//! handled by the hardware and can't be blocked, and on some Macs a short press of the
+//! Touch ID key locks the screen or sleeps before the event reaches the tap.
+
+use core_graphics::sys::CGEventRef;
+use std::ffi::{c_char, c_void};
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+
+/// NX_SUBTYPE_POWER_KEY: the power button (or Touch ID key) was pressed
+const NX_SUBTYPE_POWER_KEY: i16 = 1;
+
+/// NX_SUBTYPE_AUX_CONTROL_BUTTONS: special keys, with the key code in data1 bits 16-31
+const NX_SUBTYPE_AUX_CONTROL_BUTTONS: i16 = 8;
+
+/// NX_POWER_KEY: key code of the power key in an aux control button event
+const NX_POWER_KEY: i64 = 6;
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+    fn objc_autoreleasePoolPush() -> *mut c_void;
+    fn objc_autoreleasePoolPop(pool: *mut c_void);
+}
+
+// Ensure NSEvent is registered with the Objective-C runtime
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+/// Which kind of special key a system-defined event is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemKey {
+    /// Power button / Touch ID key (governed by `block_power_key`)
+    Power,
+    /// Media, volume, brightness and other special keys (governed by `block_media_keys`)
+    Media,
+}
+
+/// Classify a system-defined event from its NSEvent subtype and data1
+pub fn classify(subtype: i16, data1: i64) -> SystemKey {
+    let aux_key_code = (data1 >> 16) & 0xFFFF;
+    match subtype {
+        NX_SUBTYPE_POWER_KEY => SystemKey::Power,
+        NX_SUBTYPE_AUX_CONTROL_BUTTONS if aux_key_code == NX_POWER_KEY => SystemKey::Power,
+        _ => SystemKey::Media,
+    }
+}
+
+/// Classify a system-defined CGEvent (treated as a media key if NSEvent can't wrap it)
+///
+/// # Safety
+/// `event` must be null or a valid CGEventRef (e.g. the one passed to the tap callback).
+pub unsafe fn system_key_for_event(event: CGEventRef) -> SystemKey {
+    let class = objc_getClass(c"NSEvent".as_ptr());
+    if class.is_null() || event.is_null() {
+        return SystemKey::Media;
+    }
+
+    // The callback thread has no autorelease pool; drain the NSEvent right away
+    let pool = objc_autoreleasePoolPush();
+
+    let event_with_cg_event: unsafe extern "C" fn(Id, Sel, CGEventRef) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    let ns_event =
+        event_with_cg_event(class, sel_registerName(c"eventWithCGEvent:".as_ptr()), event);
+
+    let key = if ns_event.is_null() {
+        SystemKey::Media
+    } else {
+        let get_subtype: unsafe extern "C" fn(Id, Sel) -> i16 =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let get_data1: unsafe extern "C" fn(Id, Sel) -> isize =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let subtype = get_subtype(ns_event, sel_registerName(c"subtype".as_ptr()));
+        let data1 = get_data1(ns_event, sel_registerName(c"data1".as_ptr()));
+        classify(subtype, data1 as i64)
+    };
+
+    objc_autoreleasePoolPop(pool);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// data1 of an aux control button event: key code in bits 16-31, key state in 8-15
+    fn aux_data1(key_code: i64, key_down: bool) -> i64 {
+        let state = if key_down { 0x0A } else { 0x0B };
+        (key_code << 16) | (state << 8)
+    }
+
+    #[test]
+    fn test_classify_power_key() {
+        assert_eq!(classify(NX_SUBTYPE_POWER_KEY, 0), SystemKey::Power);
+        let down = aux_data1(NX_POWER_KEY, true);
+        assert_eq!(classify(NX_SUBTYPE_AUX_CONTROL_BUTTONS, down), SystemKey::Power);
+        let up = aux_data1(NX_POWER_KEY, false);
+        assert_eq!(classify(NX_SUBTYPE_AUX_CONTROL_BUTTONS, up), SystemKey::Power);
+    }
+
+    #[test]
+    fn test_classify_media_keys() {
+        // NX_KEYTYPE_SOUND_UP (0), NX_KEYTYPE_BRIGHTNESS_UP (2), NX_KEYTYPE_PLAY (16)
+        for key_code in [0, 2, 16] {
+            let data1 = aux_data1(key_code, true);
+            assert_eq!(classify(NX_SUBTYPE_AUX_CONTROL_BUTTONS, data1), SystemKey::Media);
+        }
+        // Other subtypes carry no power key, whatever data1 holds
+        assert_eq!(classify(0, aux_data1(NX_POWER_KEY, true)), SystemKey::Media);
+    }
+}
@@ -0,0 +1,196 @@
+//! Single-instance lock file shared by the CLI and the tray app
+//!
+//! Two running instances would have two event taps fighting over input, so both apps
+//! create `handsoff.pid` next to the config file at startup, holding their PID, and
+//! refuse to start while a live process holds it. The file is removed on clean
+//! shutdown; one left behind by a crash (its PID no longer running) is taken over.
+
+use crate::constants::CONFIG_FILE_PERMISSIONS;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// Lock file name, stored in the same directory as the config file
+const LOCK_FILE_NAME: &str = "handsoff.pid";
+
+/// Another instance holds the lock file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyRunning {
+    pub pid: u32,
+}
+
+impl fmt::Display for AlreadyRunning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HandsOff already running, PID {}", self.pid)
+    }
+}
+
+impl std::error::Error for AlreadyRunning {}
+
+/// Default lock file location for a config file path
+pub fn default_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(LOCK_FILE_NAME)
+}
+
+/// PID of the live instance holding the lock file, if any
+///
+/// A missing or unreadable file, or one naming a process that no longer runs, isn't held.
+pub fn running_pid(path: &Path) -> Option<u32> {
+    let pid = fs::read_to_string(path).ok()?.trim().parse().ok()?;
+    is_process_alive(pid).then_some(pid)
+}
+
+/// Held lock file; dropping it removes the file
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Create the lock file for this process
+    ///
+    /// Fails with `AlreadyRunning` (inside the anyhow error) while another live
+    /// instance holds it. A stale file is replaced.
+    pub fn acquire(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        // Second attempt after removing a stale file; losing that race to another
+        // starting instance reports it as running
+        for _ in 0..2 {
+            match create_lock_file(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if let Some(pid) = running_pid(&path) {
+                        if pid != std::process::id() {
+                            return Err(AlreadyRunning { pid }.into());
+                        }
+                    }
+                    log::warn!("Removing stale lock file {}", path.display());
+                    fs::remove_file(&path).with_context(|| {
+                        format!("Failed to remove stale lock file {}", path.display())
+                    })?;
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to create lock file {}", path.display()))
+                }
+            }
+        }
+        match running_pid(&path) {
+            Some(pid) => Err(AlreadyRunning { pid }.into()),
+            None => anyhow::bail!("Failed to create lock file {}", path.display()),
+        }
+    }
+
+    /// Lock file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Leave the file alone if another instance has since taken it over
+        let ours = fs::read_to_string(&self.path)
+            .is_ok_and(|pid| pid.trim() == std::process::id().to_string());
+        if ours {
+            if let Err(e) = fs::remove_file(&self.path) {
+                log::warn!("Failed to remove lock file {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+/// Atomically create the lock file (owner-only) holding this process's PID
+fn create_lock_file(path: &Path) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(CONFIG_FILE_PERMISSIONS)
+        .open(path)?;
+    writeln!(file, "{}", std::process::id())
+}
+
+/// Whether a process with `pid` exists (signal 0 only checks)
+fn is_process_alive(pid: u32) -> bool {
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    /// "Operation not permitted": the process exists but belongs to another user
+    const EPERM: i32 = 1;
+
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    unsafe { kill(pid, 0) == 0 || io::Error::last_os_error().raw_os_error() == Some(EPERM) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_lock_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("handsoff_lock_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        default_path(&dir.join("config.toml"))
+    }
+
+    /// A PID that is (almost certainly) not running
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    #[test]
+    fn test_acquire_and_release() {
+        let path = temp_lock_path("release");
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(lock.path(), path);
+        assert_eq!(running_pid(&path), Some(std::process::id()));
+
+        drop(lock);
+        assert!(!path.exists(), "Lock file removed on release");
+        assert_eq!(running_pid(&path), None);
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_live_instance_detected() {
+        let path = temp_lock_path("live");
+        // Stand-in for another running instance
+        let mut other = std::process::Command::new("sleep").arg("5").spawn().unwrap();
+        fs::write(&path, format!("{}\n", other.id())).unwrap();
+
+        let err = InstanceLock::acquire(&path).unwrap_err();
+        let running = err.downcast_ref::<AlreadyRunning>().expect("AlreadyRunning error");
+        assert_eq!(running.pid, other.id());
+        assert_eq!(err.to_string(), format!("HandsOff already running, PID {}", other.id()));
+        assert!(path.exists(), "The other instance's file is left alone");
+
+        other.kill().ok();
+        other.wait().ok();
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_stale_or_garbage_file_taken_over() {
+        let path = temp_lock_path("stale");
+        for contents in [format!("{}\n", dead_pid()), "not a pid".to_string()] {
+            fs::write(&path, contents).unwrap();
+            assert_eq!(running_pid(&path), None);
+
+            let lock = InstanceLock::acquire(&path).unwrap();
+            assert_eq!(running_pid(&path), Some(std::process::id()));
+            drop(lock);
+        }
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,623 @@
+//! Control socket for scripting HandsOff
+//!
+//! Listens on a Unix domain socket (`$XDG_RUNTIME_DIR/handsoff.sock`, falling back to
+//! the per-user temp dir) for newline-delimited commands and answers each with a
+//! single line of JSON:
+//!
+//! - `status`: current lock/disabled state
+//! - `lock`: lock input
+//! - `unlock <passphrase>`: unlock input (passphrase verified like typed input, with
+//!   failed attempts rate limited)
+//! - `disable` / `enable`: enter or leave minimal CPU mode (handled by the main thread)
+//!
+//! The socket is created with owner-only permissions. Each connection is served on its
+//! own thread, so an idle client can't hold up the others; failed `unlock` attempts are
+//! rate limited across all connections.
+//!
+//! `handsoff lock` / `unlock` / `disable` are clients of this socket (see
+//! `ControlCommand`).
+
+use crate::app_state::{AppState, LockRefused};
+use crate::audit::UnlockMethod;
+use crate::auth;
+use crate::constants::{
+    IPC_MAX_COMMAND_BYTES, IPC_MAX_FAILED_UNLOCKS, IPC_READ_TIMEOUT_SECS,
+    IPC_SOCKET_DIR_PERMISSIONS, IPC_SOCKET_FILE_NAME, IPC_SOCKET_PERMISSIONS,
+    IPC_UNLOCK_LOCKOUT_SECS,
+};
+use crate::status::StatusSnapshot;
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// Directory for the control socket and status file ($XDG_RUNTIME_DIR, else the temp dir)
+pub fn runtime_dir() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Get the control socket path
+pub fn socket_path() -> PathBuf {
+    runtime_dir().join(IPC_SOCKET_FILE_NAME)
+}
+
+/// Send a single command to a running instance and return its JSON response
+pub fn send_command(path: &Path, command: &str) -> Result<String> {
+    let mut stream = UnixStream::connect(path)
+        .with_context(|| format!("Failed to connect to control socket {}", path.display()))?;
+    stream.set_read_timeout(Some(Duration::from_secs(IPC_READ_TIMEOUT_SECS)))?;
+    stream.write_all(command.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()?;
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .context("Failed to read control socket response")?;
+    Ok(response.trim_end().to_string())
+}
+
+/// Command sent to a running instance by the `handsoff` CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand<'a> {
+    Lock,
+    /// The passphrase is verified by the running instance, never by the client
+    Unlock(&'a str),
+    Disable,
+}
+
+impl ControlCommand<'_> {
+    /// Line sent over the socket (without the newline)
+    pub fn to_line(&self) -> Zeroizing<String> {
+        Zeroizing::new(match self {
+            ControlCommand::Lock => "lock".to_string(),
+            ControlCommand::Unlock(passphrase) => format!("unlock {}", passphrase),
+            ControlCommand::Disable => "disable".to_string(),
+        })
+    }
+}
+
+/// Outcome of a control command (fields of status replies are ignored)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Set when `unlock` is refused after too many failed attempts
+    #[serde(default)]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl ControlResponse {
+    /// Parse a reply line from the control socket
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .with_context(|| format!("Invalid control socket response: {}", json))
+    }
+
+    /// Human-readable reason for a refused command
+    pub fn error_message(&self) -> String {
+        let error = self.error.as_deref().unwrap_or("unknown error");
+        match self.retry_after_secs {
+            Some(secs) => format!("{} (try again in {}s)", error, secs),
+            None => error.to_string(),
+        }
+    }
+}
+
+/// Tracks failed `unlock` attempts and refuses further attempts for a while
+pub struct UnlockRateLimiter {
+    failed_attempts: u32,
+    locked_out_until: Option<Instant>,
+}
+
+impl UnlockRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            failed_attempts: 0,
+            locked_out_until: None,
+        }
+    }
+
+    /// Remaining lockout in seconds, or None if an attempt is allowed at `now`
+    pub fn retry_after_secs(&mut self, now: Instant) -> Option<u64> {
+        match self.locked_out_until {
+            Some(until) if now < until => Some((until - now).as_secs().max(1)),
+            Some(_) => {
+                self.locked_out_until = None;
+                self.failed_attempts = 0;
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Record a failed attempt, starting a lockout once the limit is reached
+    pub fn record_failure(&mut self, now: Instant) {
+        self.failed_attempts += 1;
+        if self.failed_attempts >= IPC_MAX_FAILED_UNLOCKS {
+            warn!(
+                "Too many failed unlock attempts over control socket - refusing unlock for {}s",
+                IPC_UNLOCK_LOCKOUT_SECS
+            );
+            self.locked_out_until = Some(now + Duration::from_secs(IPC_UNLOCK_LOCKOUT_SECS));
+        }
+    }
+
+    /// Reset the failure count after a successful unlock
+    pub fn record_success(&mut self) {
+        self.failed_attempts = 0;
+        self.locked_out_until = None;
+    }
+}
+
+impl Default for UnlockRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bind the control socket and serve it on a background thread
+pub fn start_server(state: AppState, path: &Path) -> Result<()> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            anyhow::bail!(
+                "Control socket {} is in use (is another HandsOff instance running?)",
+                path.display()
+            );
+        }
+        // Stale socket left behind by a previous run
+        fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale control socket {}", path.display()))?;
+    }
+
+    let listener = bind_private(path)?;
+    info!("Control socket listening at {}", path.display());
+
+    thread::spawn(move || {
+        let limiter = Arc::new(Mutex::new(UnlockRateLimiter::new()));
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) =
+                        stream.set_read_timeout(Some(Duration::from_secs(IPC_READ_TIMEOUT_SECS)))
+                    {
+                        warn!("Failed to set control socket read timeout: {}", e);
+                        continue;
+                    }
+                    let state = state.clone();
+                    let limiter = Arc::clone(&limiter);
+                    thread::spawn(move || {
+                        if let Err(e) = handle_stream(stream, &state, &limiter) {
+                            debug!("Control socket connection closed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Control socket accept failed: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Bind the control socket at `path` without ever exposing it with looser permissions
+///
+/// The socket is bound in a fresh owner-only directory next to `path`, restricted, and
+/// only then moved into place, so this is safe even in a shared temp dir.
+fn bind_private(path: &Path) -> Result<UnixListener> {
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let private_dir = parent.join(format!(".handsoff-{}", std::process::id()));
+    fs::DirBuilder::new()
+        .mode(IPC_SOCKET_DIR_PERMISSIONS)
+        .create(&private_dir)
+        .with_context(|| format!("Failed to create {}", private_dir.display()))?;
+
+    let staged = private_dir.join(IPC_SOCKET_FILE_NAME);
+    let bound = UnixListener::bind(&staged)
+        .with_context(|| format!("Failed to bind control socket {}", path.display()))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(IPC_SOCKET_PERMISSIONS))
+                .context("Failed to set control socket permissions")?;
+            fs::rename(&staged, path).with_context(|| {
+                format!("Failed to move control socket to {}", path.display())
+            })?;
+            Ok(listener)
+        });
+    if bound.is_err() {
+        fs::remove_file(&staged).ok();
+    }
+    fs::remove_dir(&private_dir).ok();
+    bound
+}
+
+/// Serve newline-delimited commands from a connection until it closes
+///
+/// Lines are read with a bound of IPC_MAX_COMMAND_BYTES; a longer line is answered with
+/// an error and the connection is closed.
+pub fn handle_stream<S: Read + Write>(
+    stream: S,
+    state: &AppState,
+    limiter: &Mutex<UnlockRateLimiter>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        // The line may carry a passphrase, so scrub it after each command
+        let mut line = Zeroizing::new(String::new());
+        let read = (&mut reader)
+            .take(IPC_MAX_COMMAND_BYTES as u64)
+            .read_line(&mut line)?;
+        if read == 0 {
+            return Ok(());
+        }
+        let too_long = read == IPC_MAX_COMMAND_BYTES && !line.ends_with('\n');
+        let response = if too_long {
+            error_json("command too long")
+        } else {
+            handle_command(&line, state, &mut limiter.lock())
+        };
+        let stream = reader.get_mut();
+        stream.write_all(response.as_bytes())?;
+        stream.write_all(b"\n")?;
+        stream.flush()?;
+        if too_long {
+            return Ok(());
+        }
+    }
+}
+
+/// Reason a control command (control socket or HTTP API) was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlError {
+    /// `lock` while HandsOff is disabled
+    Disabled,
+    /// `disable` while locked (stopping the event tap would bypass the lock)
+    Locked,
+    /// `lock` without permissions or without a passphrase
+    LockRefused(LockRefused),
+}
+
+impl std::fmt::Display for ControlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlError::Disabled => write!(f, "disabled"),
+            ControlError::Locked => write!(f, "locked"),
+            ControlError::LockRefused(refused) => write!(f, "{}", refused),
+        }
+    }
+}
+
+/// Lock input for a remote `lock` command (already locked counts as success)
+///
+/// `via` names the interface in the log, e.g. "control socket".
+pub fn lock_command(state: &AppState, via: &str) -> Result<(), ControlError> {
+    if state.is_disabled() {
+        return Err(ControlError::Disabled);
+    }
+    if !state.is_locked() {
+        if let Err(refused) = state.try_lock() {
+            warn!("Lock via {} refused - {}", via, refused);
+            return Err(ControlError::LockRefused(refused));
+        }
+        info!("Input locked via {}", via);
+    }
+    Ok(())
+}
+
+/// Ask the main thread to enter minimal CPU mode for a remote `disable` command
+pub fn disable_command(state: &AppState, via: &str) -> Result<(), ControlError> {
+    if state.is_locked() {
+        return Err(ControlError::Locked);
+    }
+    info!("Disable requested via {}", via);
+    state.request_disable();
+    Ok(())
+}
+
+/// Execute a single command line and return its JSON response (without newline)
+pub fn handle_command(line: &str, state: &AppState, limiter: &mut UnlockRateLimiter) -> String {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (command, argument) = match line.split_once(' ') {
+        Some((command, argument)) => (command, Some(argument)),
+        None => (line, None),
+    };
+
+    match command {
+        "status" => status_json(state),
+        "lock" => match lock_command(state, "control socket") {
+            Ok(()) => status_json(state),
+            Err(e) => error_json(&e.to_string()),
+        },
+        "unlock" => {
+            let Some(passphrase) = argument.filter(|p| !p.is_empty()) else {
+                return error_json("missing passphrase");
+            };
+            handle_unlock(passphrase, state, limiter, Instant::now())
+        }
+        "disable" => match disable_command(state, "control socket") {
+            Ok(()) => disabled_json(true),
+            Err(e) => error_json(&e.to_string()),
+        },
+        "enable" => {
+            info!("Enable requested via control socket");
+            state.request_enable();
+            disabled_json(false)
+        }
+        _ => error_json("unknown command"),
+    }
+}
+
+fn handle_unlock(
+    passphrase: &str,
+    state: &AppState,
+    limiter: &mut UnlockRateLimiter,
+    now: Instant,
+) -> String {
+    if let Some(retry_after) = limiter.retry_after_secs(now) {
+        return format!(
+            r#"{{"ok":false,"error":"too many failed attempts","retry_after_secs":{}}}"#,
+            retry_after
+        );
+    }
+
+    let verified = state
+        .get_passphrase_hash()
+        .is_some_and(|hash| auth::verify_passphrase(passphrase, &hash));
+
+    if !verified {
+        warn!("Invalid passphrase attempt via control socket");
+        limiter.record_failure(now);
+        return error_json("invalid passphrase");
+    }
+
+    limiter.record_success();
+    if state.is_locked() && auth::unlock_with_passphrase(state, UnlockMethod::ControlSocket) {
+        info!("Passphrase verified via control socket - input unlocked");
+        state.clear_buffer();
+    }
+    status_json(state)
+}
+
+/// Status response: `"ok":true` followed by the fields of the current `StatusSnapshot`
+#[derive(Serialize)]
+struct StatusResponse {
+    ok: bool,
+    #[serde(flatten)]
+    snapshot: StatusSnapshot,
+}
+
+/// `status` response, also the body of a successful `lock`
+pub(crate) fn status_json(state: &AppState) -> String {
+    let response = StatusResponse {
+        ok: true,
+        snapshot: StatusSnapshot::from_state(state),
+    };
+    serde_json::to_string(&response).unwrap_or_else(|_| error_json("failed to serialize status"))
+}
+
+/// Response to `disable` / `enable`
+pub(crate) fn disabled_json(disabled: bool) -> String {
+    format!(r#"{{"ok":true,"disabled":{}}}"#, disabled)
+}
+
+/// Error messages are fixed strings, so no JSON escaping is needed
+fn error_json(message: &str) -> String {
+    format!(r#"{{"ok":false,"error":"{}"}}"#, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fake_stream::FakeStream;
+
+    fn state_with_passphrase(passphrase: &str) -> AppState {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(auth::hash_passphrase(passphrase));
+        state
+    }
+
+    fn run(state: &AppState, input: &str) -> Vec<String> {
+        let mut stream = FakeStream::new(input);
+        let limiter = Mutex::new(UnlockRateLimiter::new());
+        handle_stream(&mut stream, state, &limiter).unwrap();
+        stream
+            .output_text()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_status_lock_unlock_over_stream() {
+        let state = state_with_passphrase("secret");
+        let responses = run(&state, "status\nlock\nunlock wrong\nunlock secret\n");
+        assert_eq!(responses.len(), 4);
+
+        assert!(responses[0].starts_with(r#"{"ok":true,"#));
+        let locked: Vec<bool> = [&responses[0], &responses[1], &responses[3]]
+            .iter()
+            .map(|r| StatusSnapshot::from_json(r).unwrap().locked)
+            .collect();
+        assert_eq!(locked, vec![false, true, false]);
+        assert_eq!(responses[2], r#"{"ok":false,"error":"invalid passphrase"}"#);
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_passphrase_with_spaces() {
+        let state = state_with_passphrase("correct horse battery");
+        state.set_locked(true);
+        let responses = run(&state, "unlock correct horse battery\r\n");
+        assert!(responses[0].contains(r#""locked":false"#));
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_unlock_rate_limited() {
+        let state = state_with_passphrase("secret");
+        state.set_locked(true);
+        let mut limiter = UnlockRateLimiter::new();
+        let now = Instant::now();
+
+        for _ in 0..IPC_MAX_FAILED_UNLOCKS {
+            let response = handle_unlock("wrong", &state, &mut limiter, now);
+            assert!(response.contains("invalid passphrase"));
+        }
+
+        // Even the correct passphrase is refused during the lockout
+        let response = handle_unlock("secret", &state, &mut limiter, now);
+        assert!(response.contains("too many failed attempts"), "{}", response);
+        assert!(state.is_locked());
+
+        // Allowed again once the lockout expires
+        let later = now + Duration::from_secs(IPC_UNLOCK_LOCKOUT_SECS + 1);
+        let response = handle_unlock("secret", &state, &mut limiter, later);
+        assert!(response.contains(r#""locked":false"#), "{}", response);
+    }
+
+    #[test]
+    fn test_disable_refused_while_locked() {
+        let state = state_with_passphrase("secret");
+        state.set_locked(true);
+        assert_eq!(run(&state, "disable\n"), vec![r#"{"ok":false,"error":"locked"}"#]);
+        assert!(!state.should_disable_and_clear());
+
+        state.set_locked(false);
+        assert_eq!(run(&state, "disable\n"), vec![r#"{"ok":true,"disabled":true}"#]);
+        assert!(state.should_disable_and_clear());
+
+        assert_eq!(run(&state, "enable\n"), vec![r#"{"ok":true,"disabled":false}"#]);
+        assert!(state.should_enable_and_clear());
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_commands() {
+        let state = state_with_passphrase("secret");
+        let responses = run(&state, "reboot\nunlock\n\n");
+        assert_eq!(
+            responses,
+            vec![
+                r#"{"ok":false,"error":"unknown command"}"#,
+                r#"{"ok":false,"error":"missing passphrase"}"#,
+                r#"{"ok":false,"error":"unknown command"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlong_command_closes_connection() {
+        let state = state_with_passphrase("secret");
+        let input = format!("unlock {}\nstatus\n", "x".repeat(IPC_MAX_COMMAND_BYTES));
+        assert_eq!(run(&state, &input), vec![r#"{"ok":false,"error":"command too long"}"#]);
+    }
+
+    #[test]
+    fn test_socket_bound_owner_only() {
+        let dir = std::env::temp_dir().join(format!("handsoff-ipc-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(IPC_SOCKET_FILE_NAME);
+
+        let _listener = bind_private(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, IPC_SOCKET_PERMISSIONS);
+        assert!(UnixStream::connect(&path).is_ok());
+        // The staging directory is gone
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_shared_commands_return_typed_errors() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        assert_eq!(
+            lock_command(&state, "test"),
+            Err(ControlError::LockRefused(LockRefused::NoPassphrase))
+        );
+
+        let state = state_with_passphrase("secret");
+        assert_eq!(lock_command(&state, "test"), Ok(()));
+        assert_eq!(lock_command(&state, "test"), Ok(()), "Already locked");
+        assert_eq!(disable_command(&state, "test"), Err(ControlError::Locked));
+
+        state.set_locked(false);
+        state.set_disabled(true);
+        assert_eq!(lock_command(&state, "test"), Err(ControlError::Disabled));
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_lock_refused_while_disabled() {
+        let state = state_with_passphrase("secret");
+        state.set_disabled(true);
+        assert_eq!(run(&state, "lock\n"), vec![r#"{"ok":false,"error":"disabled"}"#]);
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_lock_refused_without_passphrase_or_permissions() {
+        let mut limiter = UnlockRateLimiter::new();
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        assert_eq!(
+            handle_command("lock", &state, &mut limiter),
+            r#"{"ok":false,"error":"no passphrase configured"}"#
+        );
+        assert!(!state.is_locked());
+
+        let state = state_with_passphrase("secret");
+        state.set_cached_accessibility_permissions(false);
+        assert_eq!(
+            handle_command("lock", &state, &mut limiter),
+            r#"{"ok":false,"error":"accessibility permissions missing"}"#
+        );
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_control_command_lines() {
+        assert_eq!(*ControlCommand::Lock.to_line(), "lock");
+        assert_eq!(*ControlCommand::Unlock("my secret").to_line(), "unlock my secret");
+        assert_eq!(*ControlCommand::Disable.to_line(), "disable");
+    }
+
+    #[test]
+    fn test_control_response_parsing() {
+        let state = state_with_passphrase("secret");
+        let mut limiter = UnlockRateLimiter::new();
+
+        let lock = handle_command(&ControlCommand::Lock.to_line(), &state, &mut limiter);
+        let response = ControlResponse::parse(&lock).unwrap();
+        assert!(response.ok && response.error.is_none());
+        assert!(state.is_locked());
+
+        let disable = handle_command(&ControlCommand::Disable.to_line(), &state, &mut limiter);
+        let response = ControlResponse::parse(&disable).unwrap();
+        assert!(!response.ok);
+        assert_eq!(response.error_message(), "locked");
+
+        let unlock = handle_command(&ControlCommand::Unlock("secret").to_line(), &state, &mut limiter);
+        assert!(ControlResponse::parse(&unlock).unwrap().ok);
+        assert!(!state.is_locked());
+
+        let limited = r#"{"ok":false,"error":"too many failed attempts","retry_after_secs":42}"#;
+        assert_eq!(
+            ControlResponse::parse(limited).unwrap().error_message(),
+            "too many failed attempts (try again in 42s)"
+        );
+        assert!(ControlResponse::parse("not json").is_err());
+    }
+}
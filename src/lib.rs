@@ -2,29 +2,55 @@
 // This allows tests and other modules to access the crate's functionality
 
 pub mod app_state;
+pub mod audit;
 pub mod auth;
 pub mod config;
 pub mod config_file;
 pub mod constants;
 pub mod crypto;
+pub mod error;
+pub mod health;
+pub mod http_api;
 pub mod input_blocking;
+pub mod instance_lock;
+pub mod ipc;
+pub mod logging;
+pub mod media_playback;
+pub mod metrics;
+pub mod power_source;
+pub mod schedule;
+pub mod scripting;
+pub mod setup_prompts;
+pub mod status;
+pub mod strings;
+pub mod ui;
+pub mod url_scheme;
 pub mod utils;
 
 use anyhow::{Context, Result};
 use app_state::AppState;
+use audit::{AuditEvent, UnlockMethod};
+use error::HandsOffError;
+use health::{HealthAction, HealthReport, HealthStep, TapRequests};
 use constants::{
-    AUTO_LOCK_CHECK_INTERVAL_SECS, AUTO_UNLOCK_CHECK_INTERVAL_SECS,
-    BUFFER_RESET_CHECK_INTERVAL_MS, CALLBACK_TELEMETRY_INTERVAL_SECS,
-    CFRUNLOOP_POLL_INTERVAL_MS, PERMISSION_CHECK_INTERVAL_SECS,
+    AUTO_LOCK_CHECK_INTERVAL_SECS, AUTO_LOCK_CHECK_MAX_SECS, AUTO_UNLOCK_CHECK_INTERVAL_SECS,
+    AUTO_UNLOCK_CHECK_MAX_SECS, BUFFER_RESET_CHECK_INTERVAL_MS, BUFFER_RESET_CHECK_MAX_MS,
+    CALLBACK_TELEMETRY_INTERVAL_SECS, PERMISSION_CHECK_DISABLED_INTERVAL_SECS,
+    PERMISSION_CHECK_INTERVAL_SECS, SCHEDULE_CHECK_INTERVAL_SECS, STATUS_PUBLISH_INTERVAL_SECS,
+    TIMEOUT_CHECK_MIN_SECS, WATCHDOG_CHECK_INTERVAL_SECS,
 };
-use core_graphics::sys::CGEventTapRef;
-use input_blocking::event_tap;
+use input_blocking::event_tap::{self, EventTapThread};
+use input_blocking::filter::EventFilter;
 use input_blocking::hotkeys::HotkeyManager;
+use strings::MessageKey;
+use utils::poll_interval::{next_poll_interval, PollPolicy};
+use utils::scheduler::{MonitorTask, TaskSchedule};
+use ui::notify::Level;
 use log::{error, info, warn};
-use std::sync::mpsc::{self, Sender};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::thread::{self, JoinHandle};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Return current wall-clock time as a human-readable string for correlation with external logs.
 fn wall_clock_now() -> String {
@@ -40,41 +66,66 @@ fn wall_clock_now() -> String {
     }
 }
 
-// Type alias for CFRunLoopSourceRef (from event_tap.rs)
-type CFRunLoopSourceRef = *mut std::ffi::c_void;
+/// Point SIGINT and SIGTERM at the state's shutdown flag
+pub fn register_shutdown_signals(state: &AppState) -> Result<()> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register(signal, state.shutdown_flag())
+            .with_context(|| format!("Failed to install handler for signal {}", signal))?;
+    }
+    Ok(())
+}
+
+/// Seconds until the auto-lock check next has work: the pre-lock warning while it's
+/// still ahead, otherwise the lock itself
+///
+/// None once the deadline has passed without locking (held off by media playback, the
+/// unlock grace period or missing permissions), so the check falls back to its idle interval.
+fn auto_lock_next_deadline_secs(state: &AppState) -> Option<u64> {
+    let remaining = state.get_auto_lock_remaining_secs().filter(|&secs| secs > 0)?;
+    let warning = state.get_auto_lock_warning_secs();
+    Some(if remaining > warning { remaining - warning } else { remaining })
+}
 
 /// Core HandsOff functionality shared between CLI and Tray App
 pub struct HandsOffCore {
     pub state: Arc<AppState>,
-    event_tap: Option<CGEventTapRef>,
-    run_loop_source: Option<CFRunLoopSourceRef>,
+    /// Run loop thread owning the event tap (None while stopped or disabled)
+    event_tap: Option<EventTapThread>,
     hotkey_manager: Option<HotkeyManager>,
-    /// Lock hotkey key code (default: Code::KeyL)
-    lock_key: global_hotkey::hotkey::Code,
-    /// Talk hotkey key code (default: Code::KeyT)
-    talk_key: global_hotkey::hotkey::Code,
-    /// CFRunLoop thread handle and shutdown channel
-    cfrunloop_thread: Option<(JoinHandle<()>, Sender<()>)>,
-    /// State pointer passed to event tap (for cleanup)
-    event_tap_state_ptr: Option<*mut std::ffi::c_void>,
+    /// Lock hotkey key code (default: Code::KeyL, None = disabled)
+    lock_key: Option<global_hotkey::hotkey::Code>,
+    /// Talk hotkey key code (default: Code::KeyT, None = disabled)
+    talk_key: Option<global_hotkey::hotkey::Code>,
+    /// Touch ID unlock hotkey key code (default: Code::KeyU)
+    unlock_key: global_hotkey::hotkey::Code,
+    /// Emergency unlock hotkey key code (default: None = disabled)
+    panic_key: Option<global_hotkey::hotkey::Code>,
+    /// Time-of-day windows during which input is locked automatically
+    schedule: Vec<schedule::LockWindow>,
 }
 
 impl HandsOffCore {
-    /// Create a new HandsOffCore instance with the given passphrase hash
+    /// Create a new HandsOffCore instance with the given passphrase
     pub fn new(passphrase: &str) -> Result<Self> {
+        Self::with_passphrase_hash(auth::hash_passphrase(passphrase))
+    }
+
+    /// Create a new HandsOffCore instance from an already hashed passphrase
+    /// (e.g. loaded from Keychain, where the plaintext is never stored)
+    pub fn with_passphrase_hash(hash: String) -> Result<Self> {
         let state = Arc::new(AppState::new());
-        let hash = auth::hash_passphrase(passphrase);
         state.set_passphrase_hash(hash);
 
         Ok(Self {
             state,
             event_tap: None,
-            run_loop_source: None,
             hotkey_manager: None,
-            lock_key: global_hotkey::hotkey::Code::KeyL,
-            talk_key: global_hotkey::hotkey::Code::KeyT,
-            cfrunloop_thread: None,
-            event_tap_state_ptr: None,
+            lock_key: Some(global_hotkey::hotkey::Code::KeyL),
+            talk_key: Some(global_hotkey::hotkey::Code::KeyT),
+            unlock_key: global_hotkey::hotkey::Code::KeyU,
+            panic_key: None,
+            schedule: Vec::new(),
         })
     }
 
@@ -82,47 +133,259 @@ impl HandsOffCore {
     ///
     /// # Arguments
     ///
-    /// * `lock_key` - The key code for the lock hotkey (e.g., Code::KeyL)
-    /// * `talk_key` - The key code for the talk hotkey (e.g., Code::KeyT)
+    /// * `lock_key` - The key code for the lock hotkey (e.g., Code::KeyL), or None to
+    ///   disable it (lock from the tray, schedule or control socket only)
+    /// * `talk_key` - The key code for the talk hotkey (e.g., Code::KeyT), or None to
+    ///   disable it
     pub fn set_hotkey_config(
         &mut self,
-        lock_key: global_hotkey::hotkey::Code,
-        talk_key: global_hotkey::hotkey::Code,
+        lock_key: Option<global_hotkey::hotkey::Code>,
+        talk_key: Option<global_hotkey::hotkey::Code>,
     ) {
         self.lock_key = lock_key;
         self.talk_key = talk_key;
 
         // Convert to macOS keycodes and store in AppState so event tap can use them
-        match utils::keycode::code_to_keycode(lock_key) {
-            Some(lock_keycode) => {
-                self.state.set_lock_keycode(lock_keycode);
-                info!("Lock hotkey configured: {:?} (macOS keycode: {})", lock_key, lock_keycode);
+        match lock_key.map(|key| (key, utils::keycode::code_to_keycode(key))) {
+            Some((key, Some(lock_keycode))) => {
+                self.state.set_lock_keycode(Some(lock_keycode));
+                info!("Lock hotkey configured: {:?} (macOS keycode: {})", key, lock_keycode);
             }
-            None => {
-                error!("CRITICAL: Failed to convert lock hotkey {:?} to macOS keycode", lock_key);
+            Some((key, None)) => {
+                error!("CRITICAL: Failed to convert lock hotkey {:?} to macOS keycode", key);
                 error!("Lock hotkey will use default keycode (L). This is likely a bug.");
             }
+            None => {
+                self.state.set_lock_keycode(None);
+                info!("Lock hotkey disabled");
+            }
         }
-        match utils::keycode::code_to_keycode(talk_key) {
-            Some(talk_keycode) => {
-                self.state.set_talk_keycode(talk_keycode);
-                info!("Talk hotkey configured: {:?} (macOS keycode: {})", talk_key, talk_keycode);
+        match talk_key.map(|key| (key, utils::keycode::code_to_keycode(key))) {
+            Some((key, Some(talk_keycode))) => {
+                self.state.set_talk_keycode(Some(talk_keycode));
+                info!("Talk hotkey configured: {:?} (macOS keycode: {})", key, talk_keycode);
             }
-            None => {
-                error!("CRITICAL: Failed to convert talk hotkey {:?} to macOS keycode", talk_key);
+            Some((key, None)) => {
+                error!("CRITICAL: Failed to convert talk hotkey {:?} to macOS keycode", key);
                 error!("Talk hotkey will use default keycode (T). This is likely a bug.");
             }
+            None => {
+                self.state.set_talk_keycode(None);
+                info!("Talk hotkey disabled");
+            }
+        }
+    }
+
+    /// The configured (lock, talk) hotkey key codes, as registered by `start_hotkeys`
+    /// (None for a disabled hotkey)
+    pub fn get_hotkey_config(
+        &self,
+    ) -> (Option<global_hotkey::hotkey::Code>, Option<global_hotkey::hotkey::Code>) {
+        (self.lock_key, self.talk_key)
+    }
+
+    /// Change the lock and talk hotkey keys at runtime (e.g. from a settings UI)
+    ///
+    /// If hotkeys are registered, the new ones replace them atomically: on failure the
+    /// previous hotkeys stay active and the configuration is unchanged. When disabled
+    /// (or not started yet) only the configuration changes and the new keys are
+    /// registered on the next `start_hotkeys`/`enable`. Configured modifiers are kept.
+    /// A `None` key disables that hotkey.
+    pub fn update_hotkeys(
+        &mut self,
+        lock_key: Option<global_hotkey::hotkey::Code>,
+        talk_key: Option<global_hotkey::hotkey::Code>,
+    ) -> Result<()> {
+        if let Some(key) = lock_key.filter(|_| lock_key == talk_key) {
+            anyhow::bail!("Lock and Talk hotkeys must be different (both set to {:?})", key);
+        }
+
+        let ids = self.state.get_hotkey_ids();
+        let is_registered = ids.lock.is_some() || ids.talk.is_some();
+        if let Some(manager) = self.hotkey_manager.as_mut().filter(|_| is_registered) {
+            manager.reconfigure(
+                lock_key,
+                self.state.get_lock_modifiers(),
+                talk_key,
+                self.state.get_talk_modifiers(),
+            )?;
+            self.state.set_hotkey_ids(manager.ids());
+        }
+
+        self.set_hotkey_config(lock_key, talk_key);
+        Ok(())
+    }
+
+    /// Set the modifiers held with the lock and talk hotkey keys (default: Ctrl+Cmd+Shift)
+    ///
+    /// Shared with the event tap so in-tap hotkey detection matches the registered hotkeys.
+    pub fn set_hotkey_modifiers(
+        &self,
+        lock_modifiers: utils::modifiers::HotkeyModifiers,
+        talk_modifiers: utils::modifiers::HotkeyModifiers,
+    ) {
+        self.state.set_lock_modifiers(lock_modifiers);
+        self.state.set_talk_modifiers(talk_modifiers);
+        info!(
+            "Hotkey modifiers configured: lock {}, talk {}",
+            lock_modifiers, talk_modifiers
+        );
+    }
+
+    /// Set the Touch ID unlock hotkey (modifiers: Ctrl+Cmd+Shift, active only while locked)
+    pub fn set_unlock_hotkey(&mut self, unlock_key: global_hotkey::hotkey::Code) {
+        if Some(unlock_key) == self.lock_key || Some(unlock_key) == self.talk_key {
+            warn!(
+                "Touch ID unlock hotkey {:?} conflicts with lock/talk hotkey - Touch ID unlock will not trigger",
+                unlock_key
+            );
+        }
+        self.unlock_key = unlock_key;
+
+        match utils::keycode::code_to_keycode(unlock_key) {
+            Some(unlock_keycode) => {
+                self.state.set_unlock_keycode(unlock_keycode);
+                info!("Touch ID unlock hotkey configured: {:?} (macOS keycode: {})", unlock_key, unlock_keycode);
+            }
+            None => {
+                error!("Failed to convert unlock hotkey {:?} to macOS keycode, using default (U)", unlock_key);
+            }
+        }
+    }
+
+    /// Configure the emergency unlock (panic) hotkey, or disable it with `None`
+    ///
+    /// The panic hotkey unlocks without the passphrase (or with Touch ID only, when
+    /// `requires_touchid` is set). Call before `start_hotkeys`.
+    pub fn set_panic_hotkey(
+        &mut self,
+        panic_key: Option<global_hotkey::hotkey::Code>,
+        modifiers: utils::modifiers::HotkeyModifiers,
+        requires_touchid: bool,
+    ) {
+        let keycode = panic_key.and_then(|key| {
+            let keycode = utils::keycode::code_to_keycode(key);
+            if keycode.is_none() {
+                error!("Failed to convert panic hotkey {:?} to macOS keycode - panic hotkey disabled", key);
+            }
+            keycode
+        });
+        self.panic_key = panic_key.filter(|_| keycode.is_some());
+        self.state.set_panic_hotkey(keycode, modifiers);
+        self.state.set_panic_requires_touchid(requires_touchid);
+
+        match self.panic_key {
+            Some(key) => warn!(
+                "Panic hotkey enabled: {}+{:?} unlocks without the passphrase{}",
+                modifiers,
+                key,
+                if requires_touchid { " (Touch ID required)" } else { "" }
+            ),
+            None => info!("Panic hotkey disabled"),
+        }
+    }
+
+    /// Configure the unlock chord sequence, or disable it with `None`
+    ///
+    /// The sequence unlocks without the passphrase, which keeps working alongside it.
+    pub fn set_unlock_chord(&self, chord: Option<input_blocking::chord::UnlockChord>) {
+        self.state.set_unlock_chord(chord);
+        match chord {
+            Some(chord) => warn!(
+                "Unlock sequence enabled: pressing {} {} times quickly unlocks without the passphrase",
+                chord.modifiers, chord.presses
+            ),
+            None => info!("Unlock sequence disabled"),
+        }
+    }
+
+    /// Configure hold-to-unlock, or disable it with `None`
+    ///
+    /// Holding the key unlocks without the passphrase, which keeps working alongside it.
+    pub fn set_hold_unlock(&self, hold: Option<input_blocking::hold_unlock::HoldUnlock>) {
+        self.state.set_hold_unlock(hold);
+        match hold {
+            Some(hold) => warn!(
+                "Hold-to-unlock enabled: holding keycode {} for {} seconds unlocks without the passphrase",
+                hold.keycode,
+                hold.duration.as_secs()
+            ),
+            None => info!("Hold-to-unlock disabled"),
+        }
+    }
+
+    /// Require Touch ID after the passphrase to unlock (two-factor mode)
+    ///
+    /// Fails closed: without Touch ID, the passphrase alone can't unlock (the recovery
+    /// code, panic hotkey and auto-unlock/max lock duration still do).
+    pub fn set_two_factor_unlock(&self, enabled: bool) {
+        self.state.set_two_factor_unlock(enabled);
+        if enabled {
+            info!("Two-factor unlock enabled: passphrase + Touch ID");
+            if !auth::touchid::is_available() {
+                warn!("Two-factor unlock is enabled but Touch ID is unavailable - the passphrase alone won't unlock");
+            }
         }
     }
 
-    /// Get the lock hotkey as a displayable string (e.g., "L", "M", etc.)
-    pub fn get_lock_key_display(&self) -> String {
-        Self::key_code_to_string(self.lock_key)
+    /// Get the panic hotkey as a displayable string (e.g., "Ctrl+Opt+Cmd+Shift+X")
+    pub fn get_panic_hotkey_display(&self) -> Option<String> {
+        self.panic_key.map(|key| {
+            format!("{}+{}", self.state.get_panic_modifiers(), Self::key_code_to_string(key))
+        })
     }
 
-    /// Get the talk hotkey as a displayable string (e.g., "T", "S", etc.)
-    pub fn get_talk_key_display(&self) -> String {
-        Self::key_code_to_string(self.talk_key)
+    /// Set the key passed through while the Talk hotkey is held (macOS keycode)
+    pub fn set_talk_passthrough_keycode(&self, keycode: i64) {
+        if Some(keycode) == self.state.get_lock_keycode()
+            || Some(keycode) == self.state.get_talk_keycode()
+        {
+            warn!(
+                "Talk passthrough keycode {} matches the lock/talk hotkey letter",
+                keycode
+            );
+        }
+        self.state.set_talk_passthrough_keycode(keycode);
+        info!("Talk passthrough key configured (macOS keycode: {})", keycode);
+    }
+
+    /// Set whether the Talk hotkey works while held or latches on/off per press
+    pub fn set_talk_mode(&self, mode: app_state::TalkMode) {
+        self.state.set_talk_mode(mode);
+        info!("Talk mode set to {}", mode);
+    }
+
+    /// Set what happens when accessibility permissions are lost while running
+    pub fn set_permission_loss_policy(&self, policy: app_state::PermissionLossPolicy) {
+        self.state.set_permission_loss_policy(policy);
+        info!("Permission loss policy set to {}", policy);
+    }
+
+    /// Get the lock hotkey as a displayable string (e.g., "L", "M", etc.; None when disabled)
+    pub fn get_lock_key_display(&self) -> Option<String> {
+        self.lock_key.map(Self::key_code_to_string)
+    }
+
+    /// Get the talk hotkey as a displayable string (e.g., "T", "S", etc.; None when disabled)
+    pub fn get_talk_key_display(&self) -> Option<String> {
+        self.talk_key.map(Self::key_code_to_string)
+    }
+
+    /// Get the full lock hotkey as a displayable string (e.g., "Ctrl+Cmd+Shift+L")
+    pub fn get_lock_hotkey_display(&self) -> Option<String> {
+        self.get_lock_key_display()
+            .map(|key| format!("{}+{}", self.state.get_lock_modifiers(), key))
+    }
+
+    /// Get the full talk hotkey as a displayable string (e.g., "Ctrl+Cmd+Shift+T")
+    pub fn get_talk_hotkey_display(&self) -> Option<String> {
+        self.get_talk_key_display()
+            .map(|key| format!("{}+{}", self.state.get_talk_modifiers(), key))
+    }
+
+    /// Get the Touch ID unlock hotkey as a displayable string (e.g., "U")
+    pub fn get_unlock_key_display(&self) -> String {
+        Self::key_code_to_string(self.unlock_key)
     }
 
     /// Convert a Code enum to a displayable string
@@ -138,6 +401,174 @@ impl HandsOffCore {
         }
     }
 
+    /// Set how many seconds before auto-lock the user is warned (0 = no warning)
+    pub fn set_auto_lock_warning(&self, secs: u64) {
+        self.state.set_auto_lock_warning_secs(secs);
+        if secs == 0 {
+            info!("Auto-lock warning disabled");
+        } else {
+            info!("Auto-lock warning set to {} seconds", secs);
+        }
+    }
+
+    /// Require a second lock hotkey press within `window_ms` to lock (None = single press)
+    pub fn set_lock_double_press(&self, window_ms: Option<u64>) {
+        self.state
+            .set_lock_double_press_window(window_ms.map(Duration::from_millis));
+        match window_ms {
+            Some(ms) => info!("Lock hotkey requires a double press within {} ms", ms),
+            None => info!("Lock hotkey locks on a single press"),
+        }
+    }
+
+    /// Set the post-unlock grace period in seconds (0 = none)
+    pub fn set_unlock_grace(&self, secs: u64) {
+        self.state.set_unlock_grace_secs(secs);
+        info!("Post-unlock grace period set to {} seconds", secs);
+    }
+
+    /// Set the max lock duration hard cap in minutes (enforced even with auto-unlock off)
+    pub fn set_max_lock_duration(&self, minutes: u64) {
+        self.state.set_max_lock_duration_secs(minutes * 60);
+        info!("Max lock duration (hard cap) set to {} minutes", minutes);
+    }
+
+    /// Set how long the event loop may stall while locked before the watchdog force-unlocks
+    pub fn set_watchdog_timeout(&self, secs: u64) {
+        self.state.set_watchdog_timeout_secs(secs);
+        info!("Event loop watchdog timeout set to {} seconds", secs);
+    }
+
+    /// Set the passphrase buffer reset timeout in seconds
+    pub fn set_buffer_reset_timeout(&self, timeout: u64) {
+        self.state.set_buffer_reset_timeout(timeout);
+        info!("Buffer reset timeout set to {} seconds", timeout);
+    }
+
+    /// Get the passphrase buffer reset timeout in seconds
+    pub fn get_buffer_reset_timeout(&self) -> u64 {
+        self.state.get_buffer_reset_timeout()
+    }
+
+    /// Set the keyboard layout used to translate keycodes during passphrase entry
+    pub fn set_keyboard_layout(&self, layout: utils::keycode::KeyboardLayout) {
+        self.state.set_keyboard_layout(layout);
+        info!("Passphrase keyboard layout set to {}", layout);
+    }
+
+    /// Set which input devices are blocked while locked
+    pub fn set_lock_scope(&self, scope: app_state::LockScope) {
+        self.state.set_lock_scope(scope);
+        info!("Lock scope set to {}", scope);
+        if !scope.blocks_keyboard() {
+            warn!("Keyboard is not blocked while locked - passphrase entry is unavailable; unlock with Touch ID or auto-unlock");
+        }
+    }
+
+    /// Get which input devices are blocked while locked
+    pub fn get_lock_scope(&self) -> app_state::LockScope {
+        self.state.get_lock_scope()
+    }
+
+    /// Set whether media/volume/brightness keys are blocked while locked
+    pub fn set_block_media_keys(&self, block: bool) {
+        self.state.set_block_media_keys(block);
+        info!("Media key blocking while locked: {}", if block { "enabled" } else { "disabled" });
+    }
+
+    /// Install a custom event filter, consulted for every tapped event
+    ///
+    /// The filter can force an event to pass or be blocked on top of the lock logic;
+    /// `Decision::Default` keeps HandsOff's own decision. Hotkeys and passphrase entry
+    /// still see every keystroke. See `input_blocking::filter`.
+    pub fn set_event_filter(&self, filter: EventFilter) {
+        self.state.set_event_filter(Some(filter));
+        info!("Custom event filter installed");
+    }
+
+    /// Remove the custom event filter
+    pub fn clear_event_filter(&self) {
+        self.state.set_event_filter(None);
+    }
+
+    /// Set whether the power / Touch ID key is blocked while locked (best effort)
+    pub fn set_block_power_key(&self, block: bool) {
+        self.state.set_block_power_key(block);
+        info!("Power key blocking while locked: {}", if block { "enabled" } else { "disabled" });
+    }
+
+    /// Beep when a passphrase attempt times out unmatched while locked
+    pub fn set_feedback_sound(&self, enabled: bool) {
+        self.state.set_feedback_sound(enabled);
+        if enabled {
+            info!("Feedback sound enabled for failed passphrase attempts");
+        }
+    }
+
+    /// Ignore typing for a few seconds after a full-length wrong attempt (on by default)
+    pub fn set_attempt_cooldown(&self, enabled: bool) {
+        self.state.set_attempt_cooldown(enabled);
+        if !enabled {
+            info!("Attempt cooldown disabled - wrong attempts can be retried right away");
+        }
+    }
+
+    /// Whether a full-length wrong attempt starts the attempt cooldown
+    pub fn is_attempt_cooldown_enabled(&self) -> bool {
+        self.state.is_attempt_cooldown_enabled()
+    }
+
+    /// Clear the passphrase buffer as soon as an attempt is longer than the passphrase
+    ///
+    /// Only matters with the attempt cooldown off: otherwise a full-length wrong attempt
+    /// is already discarded when its cooldown starts.
+    ///
+    /// `passphrase_len` is None when it is unknown (a Keychain entry saved before the
+    /// length was stored), in which case failed attempts are still only cleared by the
+    /// buffer reset timeout and no attempt cooldown starts.
+    pub fn set_clear_overlong_attempts(&self, enabled: bool, passphrase_len: Option<usize>) {
+        self.state.set_passphrase_len(passphrase_len);
+        self.state.set_clear_overlong_attempts(enabled);
+        if enabled {
+            match passphrase_len {
+                Some(_) => info!("Over-length passphrase attempts are cleared immediately"),
+                None => warn!(
+                    "clear_overlong_attempts needs the passphrase length, which this Keychain \
+                     entry lacks - waiting for the buffer reset timeout instead (save the \
+                     passphrase again to store it)"
+                ),
+            }
+        }
+    }
+
+    /// Enable dry-run mode: the full pipeline runs, but events are never blocked
+    pub fn set_dry_run(&self, dry_run: bool) {
+        self.state.set_dry_run(dry_run);
+        if dry_run {
+            warn!("Dry-run mode: input will NOT be blocked (would-be blocks are logged at debug level)");
+        }
+    }
+
+    /// Set the bundle identifiers of apps that stay interactive while locked
+    pub fn set_app_allowlist(&self, allowlist: Vec<String>) {
+        if !allowlist.is_empty() {
+            info!("App allowlist while locked: {}", allowlist.join(", "));
+        }
+        self.state.set_app_allowlist(allowlist);
+    }
+
+    /// Set the time-of-day lock windows (takes effect when background threads start)
+    pub fn set_schedule(&mut self, windows: Vec<schedule::LockWindow>) {
+        for window in &windows {
+            info!(
+                "Scheduled lock window: {} - {}",
+                window.start.format("%H:%M"),
+                window.end.format("%H:%M")
+            );
+        }
+        self.schedule = windows;
+    }
+
     /// Set the auto-unlock timeout in seconds
     pub fn set_auto_unlock_timeout(&self, timeout: Option<u64>) {
         self.state.set_auto_unlock_timeout(timeout);
@@ -146,9 +577,15 @@ impl HandsOffCore {
         }
     }
 
-    /// Set the initial lock state
-    pub fn set_locked(&self, locked: bool) {
-        self.state.set_locked(locked);
+    /// Record lock/unlock events in an append-only audit file
+    pub fn set_audit_log_path(&self, path: PathBuf) {
+        info!("Audit log: {}", path.display());
+        self.state.set_audit_log_path(Some(path));
+    }
+
+    /// Unlock input without a passphrase (locking always goes through `lock()` and its checks)
+    pub fn set_unlocked(&self) {
+        self.state.set_locked(false);
     }
 
     /// Check if currently locked
@@ -166,6 +603,91 @@ impl HandsOffCore {
         self.state.get_auto_lock_remaining_secs()
     }
 
+    /// Suspend auto-lock for `duration` (cleared by locking or reset)
+    pub fn snooze_auto_lock(&self, duration: Duration) {
+        self.state.snooze_auto_lock(duration);
+        info!("Auto-lock snoozed for {} seconds", duration.as_secs());
+    }
+
+    /// End an auto-lock snooze early
+    pub fn clear_auto_lock_snooze(&self) {
+        if self.state.get_snooze_remaining_secs().is_some() {
+            info!("Auto-lock snooze cleared");
+        }
+        self.state.clear_auto_lock_snooze();
+    }
+
+    /// Set whether active media playback (display-sleep assertions) holds off auto-lock
+    pub fn set_respect_media_playback(&self, respect: bool) {
+        self.state.set_respect_media_playback(respect);
+        if respect {
+            info!("Auto-lock pauses while media is playing");
+        }
+    }
+
+    /// Set how auto-lock depends on the power source (battery timeout, off on AC power)
+    pub fn set_power_auto_lock_policy(&self, policy: power_source::PowerAutoLockPolicy) {
+        self.state.set_power_auto_lock_policy(policy);
+        if let Some(secs) = policy.timeout_on_battery {
+            info!("Auto-lock timeout on battery: {} seconds", secs);
+        }
+        if !policy.lock_on_ac {
+            info!("Auto-lock only runs on battery power");
+        }
+    }
+
+    /// Set whether pointer motion (moves and drags) leaves the auto-lock timer running
+    pub fn set_auto_lock_ignore_mouse_move(&self, ignore: bool) {
+        self.state.set_auto_lock_ignore_mouse_move(ignore);
+        if ignore {
+            info!("Mouse movement doesn't reset the auto-lock timer");
+        }
+    }
+
+    /// Require `min_events` input events within `window_ms` before input resets the
+    /// auto-lock timer (0 or 1 = every event counts)
+    pub fn set_activity_debounce(&self, min_events: u32, window_ms: u64) {
+        self.state
+            .set_activity_debounce(min_events, Duration::from_millis(window_ms));
+        if min_events > 1 {
+            info!(
+                "Input counts as activity after {} events within {} ms",
+                min_events, window_ms
+            );
+        }
+    }
+
+    /// Check whether auto-lock is currently paused because media is playing
+    pub fn is_auto_lock_paused_for_media(&self) -> bool {
+        self.state.is_auto_lock_paused_for_media()
+    }
+
+    /// Get remaining auto-lock snooze time (in seconds, None if not snoozed)
+    pub fn get_snooze_remaining_secs(&self) -> Option<u64> {
+        self.state.get_snooze_remaining_secs()
+    }
+
+    /// Seconds until a pause ends and HandsOff re-enables itself (None if not paused)
+    pub fn get_pause_remaining_secs(&self) -> Option<u64> {
+        self.state.get_pause_remaining_secs()
+    }
+
+    /// Number of characters typed into the passphrase buffer (never the contents)
+    pub fn get_buffer_len(&self) -> usize {
+        self.state.buffer_len()
+    }
+
+    /// Seconds until passphrase input is accepted again after a wrong attempt
+    /// (None when no cooldown is running)
+    pub fn get_attempt_cooldown_remaining(&self) -> Option<u64> {
+        self.state.get_attempt_cooldown_remaining()
+    }
+
+    /// Fraction of a hold-to-unlock hold done, 0.0-1.0 (None when no hold is in progress)
+    pub fn get_hold_unlock_progress(&self) -> Option<f64> {
+        self.state.get_hold_unlock_progress()
+    }
+
     /// Get remaining time until auto-unlock (in seconds)
     pub fn get_auto_unlock_remaining_secs(&self) -> Option<u64> {
         self.state.get_auto_unlock_remaining_secs()
@@ -177,7 +699,7 @@ impl HandsOffCore {
     }
 
     /// Check if accessibility permissions are currently granted
-    /// Returns cached value updated by background permission monitor thread
+    /// Returns cached value updated by the scheduler thread's permission check
     pub fn has_accessibility_permissions(&self) -> bool {
         self.state.get_cached_accessibility_permissions()
     }
@@ -185,150 +707,142 @@ impl HandsOffCore {
     /// Lock input immediately
     ///
     /// # Safety Note
-    /// If accessibility permissions are not granted, this will set the locked state
-    /// but input blocking will NOT work. The app will think it's locked but events
-    /// won't actually be blocked, leading to a broken state where passphrase entry
-    /// doesn't work properly. This is why the tray app should check permissions
-    /// before allowing lock() to be called.
+    /// Locking is refused (`PermissionsMissing`) while accessibility permissions are not
+    /// granted, because the event tap could not block input or read the passphrase, and
+    /// (`NoPassphrase`) when no passphrase is set to end the lock.
     ///
-    /// The permission monitor thread will detect this condition and perform an
-    /// emergency unlock, but it's better to prevent the lock attempt in the first place.
-    pub fn lock(&self) -> Result<()> {
-        // Check permissions before locking
-        if !self.has_accessibility_permissions() {
-            warn!("Cannot lock: Accessibility permissions not granted");
-            anyhow::bail!("Cannot lock input - accessibility permissions not granted. Please enable permissions in System Settings > Privacy & Security > Accessibility");
+    /// The check uses the cached permission state, so a permission loss since the last
+    /// check is still caught by the scheduler thread, which performs an emergency unlock.
+    pub fn lock(&self) -> Result<(), HandsOffError> {
+        // Already locked: keep the running lock session as it is
+        if self.state.is_locked() {
+            return Ok(());
+        }
+        // Refused without permissions, or without a passphrase to end the lock
+        if let Err(refused) = self.state.try_lock() {
+            warn!("Cannot lock: {}", refused);
+            return Err(refused.into());
         }
-
-        self.state.set_locked(true);
         info!("Input locked");
         Ok(())
     }
 
     /// Unlock input with passphrase
-    pub fn unlock(&self, passphrase: &str) -> Result<bool> {
-        let hash = auth::hash_passphrase(passphrase);
-        let expected_hash = self.state.get_passphrase_hash();
-
-        if Some(hash) == expected_hash {
-            self.state.set_locked(false);
-            info!("Input unlocked");
-            Ok(true)
-        } else {
+    ///
+    /// Returns whether input unlocked right away: false in two-factor mode, where it
+    /// unlocks only once the Touch ID prompt that follows succeeds.
+    pub fn unlock(&self, passphrase: &str) -> Result<bool, HandsOffError> {
+        if !self.verify_passphrase(passphrase) {
             warn!("Invalid passphrase attempt");
-            Ok(false)
+            return Err(HandsOffError::InvalidPassphrase);
+        }
+        let unlocked = auth::unlock_with_passphrase(&self.state, UnlockMethod::Passphrase);
+        if unlocked {
+            info!("Input unlocked");
         }
+        Ok(unlocked)
     }
 
-    /// Start CFRunLoop in a background thread
-    /// Required for event tap to receive events
-    fn start_cfrunloop_thread(&mut self) {
-        if self.cfrunloop_thread.is_some() {
-            warn!("CFRunLoop thread already running");
-            return;
+    /// Lock when unlocked, or unlock when locked, and return whether input is now locked
+    ///
+    /// Locking has the same permission check as `lock`. Unlocking needs the correct
+    /// passphrase (None counts as invalid), and in two-factor mode input stays locked
+    /// until the Touch ID prompt succeeds.
+    pub fn toggle_lock(&self, passphrase: Option<&str>) -> Result<bool, HandsOffError> {
+        if !self.is_locked() {
+            self.lock()?;
+            return Ok(true);
         }
+        let passphrase = passphrase.ok_or(HandsOffError::InvalidPassphrase)?;
+        self.unlock(passphrase)?;
+        Ok(self.is_locked())
+    }
 
-        let (shutdown_tx, shutdown_rx) = mpsc::channel();
-
-        let handle = thread::spawn(move || {
-            info!("CFRunLoop thread started");
-            use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop, CFRunLoopRunResult};
-
-            loop {
-                // Run the loop for 0.5 seconds, then check for shutdown
-                let result = unsafe {
-                    CFRunLoop::run_in_mode(
-                        kCFRunLoopDefaultMode,
-                        Duration::from_millis(CFRUNLOOP_POLL_INTERVAL_MS),
-                        false,
-                    )
-                };
-
-                // Check if shutdown requested
-                if shutdown_rx.try_recv().is_ok() {
-                    info!("CFRunLoop thread received shutdown signal");
-                    break;
-                }
-
-                // Log result for debugging (will be removed later if too verbose)
-                if result != CFRunLoopRunResult::TimedOut {
-                    log::trace!("CFRunLoop run_in_mode returned: {:?}", result);
-                }
-            }
-
-            info!("CFRunLoop thread stopped");
-        });
-
-        self.cfrunloop_thread = Some((handle, shutdown_tx));
-        info!("CFRunLoop thread spawned successfully");
+    /// Switch unlock checks to a new passphrase (the caller saves it to the config)
+    pub fn set_passphrase(&self, passphrase: &str) {
+        self.state.set_passphrase_hash(auth::hash_passphrase(passphrase));
+        self.state.set_passphrase_len(Some(passphrase.chars().count()));
+        info!("Passphrase changed");
     }
 
-    /// Stop CFRunLoop background thread
-    fn stop_cfrunloop_thread(&mut self) {
-        if let Some((handle, shutdown_tx)) = self.cfrunloop_thread.take() {
-            info!("Stopping CFRunLoop thread");
+    /// Check a passphrase against the one that unlocks input, without locking or unlocking
+    pub fn verify_passphrase(&self, passphrase: &str) -> bool {
+        self.state
+            .get_passphrase_hash()
+            .is_some_and(|hash| auth::verify_passphrase(passphrase, &hash))
+    }
 
-            // Send shutdown signal
-            if let Err(e) = shutdown_tx.send(()) {
-                warn!("Failed to send shutdown signal to CFRunLoop thread: {}", e);
-            }
+    /// Set the one-time emergency recovery code hash (None = no recovery code)
+    pub fn set_recovery_code_hash(&self, hash: Option<String>) {
+        let has_code = hash.is_some();
+        self.state.set_recovery_code_hash(hash);
+        if has_code {
+            info!("Emergency recovery code configured");
+        }
+    }
 
-            // Wait for thread to finish (with timeout)
-            match handle.join() {
-                Ok(()) => info!("CFRunLoop thread stopped successfully"),
-                Err(e) => warn!("CFRunLoop thread panicked: {:?}", e),
-            }
+    /// Unlock input with the one-time recovery code
+    ///
+    /// The code is invalidated on success. Callers should persist the cleared config
+    /// when `state.should_persist_recovery_code_consumed_and_clear()` returns true.
+    pub fn unlock_with_recovery_code(&self, code: &str) -> Result<bool> {
+        if self.state.try_recovery_code(code) {
+            info!("Input unlocked with recovery code");
+            Ok(true)
         } else {
-            warn!("CFRunLoop thread not running, nothing to stop");
+            warn!("Invalid recovery code attempt");
+            Ok(false)
         }
     }
 
     /// Start the event tap for input blocking
-    pub fn start_event_tap(&mut self) -> Result<()> {
-        // Start CFRunLoop thread first (required for event tap)
-        self.start_cfrunloop_thread();
+    pub fn start_event_tap(&mut self) -> Result<(), HandsOffError> {
+        if self.event_tap.is_some() {
+            warn!("Event tap already running");
+            return Err(HandsOffError::AlreadyRunning);
+        }
 
+        // The tap is created on, and attached to, the run loop of the thread that runs it
         info!("[tap-lifecycle] Starting event tap at {}", wall_clock_now());
-        let (tap, state_ptr) = event_tap::create_event_tap(self.state.clone())
-            .context("Failed to create event tap")?;
-        let source = unsafe { event_tap::enable_event_tap(tap) };
-        self.event_tap = Some(tap);
-        self.run_loop_source = Some(source);
-        self.event_tap_state_ptr = Some(state_ptr);
+        self.state.pet_watchdog();
+        match EventTapThread::start(self.state.clone()) {
+            Ok(thread) => self.event_tap = Some(thread),
+            Err(e) => {
+                self.state.clear_watchdog_heartbeat();
+                return Err(HandsOffError::EventTapCreationFailed(e));
+            }
+        }
         info!("Event tap started");
         Ok(())
     }
 
+    /// Request a clean shutdown on SIGINT/SIGTERM instead of dying mid-lock (CLI)
+    ///
+    /// The handlers only set `state.shutdown_flag()`; the main loop sees
+    /// `state.is_shutdown_requested()`, stops the event tap and exits.
+    pub fn install_shutdown_signal_handlers(&self) -> Result<()> {
+        register_shutdown_signals(&self.state)
+    }
+
     /// Stop the event tap and remove it from run loop
     /// This should be called when permissions are lost to stop blocking input
     pub fn stop_event_tap(&mut self) {
-        if let (Some(tap), Some(source)) = (self.event_tap, self.run_loop_source) {
+        // The run loop thread removes the tap and frees its state as it exits
+        if let Some(thread) = self.event_tap.take() {
             warn!("[tap-lifecycle] Stopping event tap at {}", wall_clock_now());
-            unsafe {
-                event_tap::remove_event_tap_from_runloop(tap, source);
-            }
-            self.event_tap = None;
-            self.run_loop_source = None;
+            let unresponsive =
+                self.state.is_event_loop_heartbeat_stale_at(Instant::now());
+            self.state.clear_watchdog_heartbeat();
+            thread.stop(unresponsive);
             info!("Event tap stopped - input should now be accessible");
         } else {
             warn!("Attempted to stop event tap but it was not running");
         }
-
-        // Free the state pointer to prevent memory leak
-        if let Some(state_ptr) = self.event_tap_state_ptr.take() {
-            unsafe {
-                let _ = Box::from_raw(state_ptr as *mut Arc<AppState>);
-                info!("Event tap state pointer freed");
-            }
-        }
-
-        // Stop CFRunLoop thread (no longer needed without event tap)
-        self.stop_cfrunloop_thread();
     }
 
     /// Restart the event tap after permissions are restored
     /// Returns Ok if successful, Err if permissions are still missing or creation fails
-    pub fn restart_event_tap(&mut self) -> Result<()> {
+    pub fn restart_event_tap(&mut self) -> Result<(), HandsOffError> {
         // First check if we already have an event tap running
         if self.event_tap.is_some() {
             warn!("Event tap already running, stopping it first");
@@ -337,7 +851,8 @@ impl HandsOffCore {
 
         // Verify permissions before attempting to create tap
         if !input_blocking::check_accessibility_permissions() {
-            anyhow::bail!("Cannot restart event tap - accessibility permissions not granted");
+            warn!("Cannot restart event tap: Accessibility permissions not granted");
+            return Err(HandsOffError::PermissionsMissing);
         }
 
         info!("Restarting event tap");
@@ -355,7 +870,7 @@ impl HandsOffCore {
     /// If no tap is currently held (e.g. it was stopped due to permission loss), falls back
     /// to a full restart so the caller never needs to distinguish the two cases.
     pub fn reenable_event_tap(&mut self) -> Result<()> {
-        match self.event_tap {
+        match self.event_tap.as_ref().map(EventTapThread::tap) {
             Some(tap) => {
                 info!(
                     "[tap-lifecycle] Re-enabling existing event tap at {} (reusing WindowServer connection, no new Mach port)",
@@ -372,7 +887,7 @@ impl HandsOffCore {
                         "[tap-lifecycle] Re-enable failed (tap still disabled), falling back to full restart at {}",
                         wall_clock_now()
                     );
-                    self.restart_event_tap()
+                    Ok(self.restart_event_tap()?)
                 }
             }
             None => {
@@ -380,7 +895,60 @@ impl HandsOffCore {
                     "[tap-lifecycle] Re-enable requested but no tap handle held — falling back to full restart at {}",
                     wall_clock_now()
                 );
-                self.restart_event_tap()
+                Ok(self.restart_event_tap()?)
+            }
+        }
+    }
+
+    /// Act on pending event tap requests, stopping, re-enabling or restarting the tap
+    /// only as needed (see `health::plan`)
+    ///
+    /// Called from the tray's event loop; idempotent, so nothing happens until a
+    /// background thread raises another request.
+    pub fn ensure_running(&mut self) -> HealthReport {
+        let requests = TapRequests {
+            stop: self.state.should_stop_event_tap_and_clear(),
+            reenable: self.state.should_reenable_event_tap_and_clear(),
+            start: self.state.should_start_event_tap_and_clear(),
+        };
+        let steps = health::plan(
+            requests,
+            self.event_tap.is_some(),
+            self.has_accessibility_permissions(),
+        );
+
+        let mut report = HealthReport::default();
+        for step in steps {
+            let action = match step {
+                HealthStep::StopTap => {
+                    self.stop_event_tap();
+                    HealthAction::StoppedTap
+                }
+                HealthStep::ReenableTap => match self.reenable_event_tap() {
+                    Ok(()) => HealthAction::ReenabledTap,
+                    Err(e) => HealthAction::ReenableFailed(e.to_string()),
+                },
+                HealthStep::RestartTap => match self.restart_event_tap() {
+                    Ok(()) => HealthAction::RestartedTap,
+                    Err(e) => HealthAction::RestartFailed(e.to_string()),
+                },
+            };
+            report.actions.push(action);
+        }
+        report
+    }
+
+    /// Release the event tap and hotkeys before exiting (safe to call more than once)
+    pub fn shutdown(&mut self) {
+        // Stop the event tap to release CGEventTapRef and prevent WindowServer resource leak
+        if self.event_tap.is_some() {
+            self.stop_event_tap();
+        }
+
+        // Unregister hotkeys to clean up global_hotkey resources
+        if let Some(mut manager) = self.hotkey_manager.take() {
+            if let Err(e) = manager.unregister_all() {
+                warn!("Failed to unregister hotkeys during shutdown: {}", e);
             }
         }
     }
@@ -389,8 +957,12 @@ impl HandsOffCore {
     pub fn disable(&mut self) -> Result<()> {
         info!("Disabling HandsOff - entering minimal CPU mode");
 
+        // A manual disable stays until re-enabled (pause() sets a new deadline afterwards)
+        self.state.clear_pause();
+
         // Set disabled flag first (background threads will become inactive)
         self.state.set_disabled(true);
+        self.state.record_audit(AuditEvent::Disabled);
 
         // Stop event tap
         self.stop_event_tap();
@@ -401,6 +973,7 @@ impl HandsOffCore {
                 .unregister_all()
                 .context("Failed to unregister hotkeys")?;
         }
+        self.state.set_hotkey_ids(Default::default());
 
         // Clear input buffer for clean state
         self.state.clear_buffer();
@@ -409,9 +982,18 @@ impl HandsOffCore {
         Ok(())
     }
 
+    /// Disable HandsOff for `duration`; the caller re-enables once `state.is_pause_expired()`
+    pub fn pause(&mut self, duration: Duration) -> Result<()> {
+        self.disable()?;
+        self.state.pause_for(duration);
+        info!("HandsOff paused for {} seconds", duration.as_secs());
+        Ok(())
+    }
+
     /// Enable HandsOff (restarts event tap and hotkeys)
     pub fn enable(&mut self) -> Result<()> {
         info!("Enabling HandsOff - resuming normal operation");
+        self.state.clear_pause();
 
         // Reset last_input_time for fresh auto-lock countdown
         // Note: if don't do this first, auto-lock may kick in right after set_disabled(false)
@@ -432,111 +1014,149 @@ impl HandsOffCore {
     }
 
     /// Start the hotkey manager using configured keys
-    pub fn start_hotkeys(&mut self) -> Result<()> {
+    pub fn start_hotkeys(&mut self) -> Result<(), HandsOffError> {
         if self.hotkey_manager.is_none() {
-            let new_mgr = HotkeyManager::new().context("Failed to create hotkey manager")?;
+            let new_mgr = HotkeyManager::new()
+                .context("Failed to create hotkey manager")
+                .map_err(HandsOffError::HotkeyRegistrationFailed)?;
             info!("Instantiated new hotkey manager");
             self.hotkey_manager = Some(new_mgr);
         }
 
         let manager: &mut HotkeyManager = self.hotkey_manager.as_mut().unwrap();
 
-        manager
-            .register_lock_hotkey(self.lock_key)
-            .context("Failed to register lock hotkey")?;
-        manager
-            .register_talk_hotkey(self.talk_key)
-            .context("Failed to register talk hotkey")?;
+        // Try every hotkey so one conflict doesn't leave the others unregistered.
+        // The in-tap detection in handle_keyboard_event keeps lock/talk working even
+        // when the global registration is taken by another app. Disabled hotkeys
+        // are skipped.
+        let mut results = Vec::new();
+        if let Some(lock_key) = self.lock_key {
+            results.push(manager.register_lock_hotkey(lock_key, self.state.get_lock_modifiers()));
+        }
+        if let Some(talk_key) = self.talk_key {
+            results.push(manager.register_talk_hotkey(talk_key, self.state.get_talk_modifiers()));
+        }
+        if let Some(panic_key) = self.panic_key {
+            results.push(manager.register_panic_hotkey(panic_key, self.state.get_panic_modifiers()));
+        }
+
+        self.state.set_hotkey_ids(manager.ids());
 
-        info!("Hotkeys registered");
-        Ok(())
+        let mut errors = results.into_iter().filter_map(Result::err);
+        match errors.next() {
+            None => {
+                info!("Hotkeys registered");
+                Ok(())
+            }
+            Some(first) => {
+                for other in errors {
+                    error!("{:#}", other);
+                }
+                Err(HandsOffError::HotkeyRegistrationFailed(first))
+            }
+        }
     }
 
-    /// Start all background threads (buffer reset, auto-lock, hotkey listener, auto-unlock, permission monitor)
+    /// Start all background threads (scheduler, hotkey listener, schedule, watchdog, status)
     pub fn start_background_threads(&self) -> Result<()> {
-        self.start_buffer_reset_thread();
-        self.start_auto_lock_thread();
+        // Buffer reset, auto-lock, auto-unlock (and the max lock duration hard cap) and the
+        // permission monitor all run on the scheduler thread
+        self.start_scheduler_thread();
 
-        if let Some(ref manager) = self.hotkey_manager {
-            self.start_hotkey_listener_thread(manager);
-        }
+        // Hotkey IDs are read from shared state, so the listener also picks up
+        // hotkeys registered (or re-registered) after this point
+        self.start_hotkey_listener_thread();
 
-        // Start auto-unlock thread if timeout is configured
-        if self.state.get_auto_unlock_timeout().is_some() {
-            self.start_auto_unlock_thread();
+        // Start schedule thread if lock windows are configured
+        if !self.schedule.is_empty() {
+            self.start_schedule_thread();
         }
 
-        // Start permission monitoring thread for safety
-        self.start_permission_monitor_thread();
+        // Force-unlock if the event loop stalls while locked
+        self.start_watchdog_thread();
+
+        // Publish state snapshots for `handsoff status`
+        self.start_status_publisher_thread();
 
         info!("Background threads started");
         Ok(())
     }
 
-    /// Background thread to reset input buffer after timeout
-    fn start_buffer_reset_thread(&self) {
+    /// Background thread running the periodic monitoring checks (see `MonitorTask`)
+    ///
+    /// Wakes at the nearest due time and runs whichever checks are due; each is then
+    /// rescheduled by its own adaptive interval. Checks are skipped while disabled.
+    fn start_scheduler_thread(&self) {
         let state = self.state.clone();
-        thread::spawn(move || loop {
-            thread::sleep(Duration::from_millis(BUFFER_RESET_CHECK_INTERVAL_MS));
-
-            // Skip processing when disabled
-            if state.is_disabled() {
-                continue;
-            }
+        thread::Builder::new()
+            .name("scheduler".to_string())
+            .spawn(move || {
+                info!(
+                    "Scheduler thread started (auto-unlock: {:?}s, hard cap: {}s, permission check every {}s)",
+                    state.get_auto_unlock_timeout(),
+                    state.get_max_lock_duration_secs(),
+                    PERMISSION_CHECK_INTERVAL_SECS
+                );
 
-            if state.should_reset_buffer() {
-                let buffer = state.get_buffer();
-                if !buffer.is_empty() {
-                    info!("Resetting input buffer after timeout");
-                    state.clear_buffer();
+                let mut monitors = Monitors::new(state);
+                let start = Instant::now();
+                let mut schedule = TaskSchedule::new(start);
+                for task in MonitorTask::ALL {
+                    schedule.schedule(task, start + monitors.next_interval(task));
                 }
-            }
-        });
-    }
 
-    /// Background thread to enable auto-lock after inactivity
-    fn start_auto_lock_thread(&self) {
-        let state = self.state.clone();
-        thread::spawn(move || {
-            let mut check_count = 0u32;
-            loop {
-                thread::sleep(Duration::from_secs(AUTO_LOCK_CHECK_INTERVAL_SECS));
+                loop {
+                    thread::sleep(schedule.next_wake().saturating_duration_since(Instant::now()));
 
-                // Skip processing when disabled
-                if state.is_disabled() {
-                    continue;
+                    for task in schedule.due_tasks(Instant::now()) {
+                        if !monitors.state.is_disabled() {
+                            monitors.run(task);
+                        }
+                        schedule.schedule(task, Instant::now() + monitors.next_interval(task));
+                    }
                 }
+            })
+            .expect("Failed to spawn scheduler thread");
+    }
 
-                check_count += 1;
-
-                // Log remaining time every 30 seconds (6 checks of 5 seconds each)
-                if check_count.is_multiple_of(6) {
-                    if let Some(remaining_secs) = state.get_auto_lock_remaining_secs() {
-                        let minutes = remaining_secs / 60;
-                        let seconds = remaining_secs % 60;
-                        info!(
-                            "Auto-lock in {} seconds ({} min {} sec remaining)",
-                            remaining_secs, minutes, seconds
-                        );
+    /// Background thread to lock input when a scheduled lock window starts
+    ///
+    /// Locks only on entering a window, so unlocking inside a window (passphrase, Touch ID,
+    /// auto-unlock) sticks until the next window begins.
+    fn start_schedule_thread(&self) {
+        let state = self.state.clone();
+        let windows = self.schedule.clone();
+        thread::Builder::new()
+            .name("schedule".to_string())
+            .spawn(move || {
+                info!("Schedule thread started ({} lock window(s))", windows.len());
+                let mut was_inside = false;
+                loop {
+                    let now = chrono::Local::now().time();
+                    let inside = schedule::is_within_any(&windows, now);
+
+                    if inside && !was_inside {
+                        if state.is_disabled() {
+                            info!("Scheduled lock window started but HandsOff is disabled - not locking");
+                        } else if !state.is_locked() {
+                            match state.try_lock() {
+                                Ok(()) => info!("Scheduled lock window started - input now locked"),
+                                Err(refused) => warn!("Scheduled lock window started but not locking - {}", refused),
+                            }
+                        }
                     }
-                }
+                    was_inside = inside;
 
-                if state.should_auto_lock() {
-                    info!("Auto-lock triggered after inactivity - input now locked");
-                    state.set_locked(true);
+                    thread::sleep(Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS));
                 }
-            }
-        });
+            })
+            .expect("Failed to spawn schedule thread");
     }
 
     /// Background thread to listen for hotkey events
-    fn start_hotkey_listener_thread(&self, manager: &HotkeyManager) {
+    fn start_hotkey_listener_thread(&self) {
         let state = self.state.clone();
 
-        // Extract hotkey IDs to avoid needing to clone manager
-        let lock_hotkey_id = manager.lock_hotkey.map(|hk| hk.id());
-        let talk_hotkey_id = manager.talk_hotkey.map(|hk| hk.id());
-
         thread::spawn(move || {
             use global_hotkey::GlobalHotKeyEvent;
 
@@ -549,194 +1169,569 @@ impl HandsOffCore {
                     }
 
                     let event_id = event.id;
+                    let ids = state.get_hotkey_ids();
 
                     // Check if it's the lock hotkey
-                    if lock_hotkey_id.is_some_and(|id| id == event_id) {
+                    if ids.lock == Some(event_id) {
                         info!("Lock hotkey triggered");
-                        if !state.is_locked() {
-                            state.set_locked(true);
-                            info!("Input locked via hotkey");
+                        if !state.is_locked() && state.register_lock_hotkey_press() {
+                            match state.try_lock() {
+                                Ok(()) => info!("Input locked via hotkey"),
+                                Err(refused) => warn!("Lock hotkey ignored - {}", refused),
+                            }
                         }
                     }
                     // Check if it's the talk hotkey
-                    else if talk_hotkey_id.is_some_and(|id| id == event_id) {
+                    else if ids.talk == Some(event_id) {
                         info!("Talk hotkey triggered");
                         // Note: Spacebar passthrough is handled in the event tap
                     }
+                    // Check if it's the emergency unlock hotkey
+                    else if ids.panic == Some(event_id) {
+                        auth::panic_unlock(&state);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Start the control socket on a background thread (see `ipc` module)
+    ///
+    /// `disable`/`enable` commands are only requested here; the main loop applies them
+    /// via `state.should_disable_and_clear()` / `state.should_enable_and_clear()`.
+    pub fn start_ipc_server(&self) -> Result<()> {
+        ipc::start_server((*self.state).clone(), &ipc::socket_path())
+    }
+
+    /// Start the localhost HTTP control API on a background thread (see `http_api` module)
+    ///
+    /// Like the control socket, `disable` is only requested here and applied by the
+    /// main loop.
+    pub fn start_http_api(&self, port: u16, token: &str) -> Result<()> {
+        http_api::start_server((*self.state).clone(), port, token)
+    }
+
+    /// Background thread to publish a status snapshot for `handsoff status`
+    fn start_status_publisher_thread(&self) {
+        let state = self.state.clone();
+        let path = status::status_path();
+        thread::spawn(move || {
+            let mut last_error_logged = false;
+            loop {
+                let snapshot = status::StatusSnapshot::from_state(&state);
+                match status::write_snapshot(&path, &snapshot) {
+                    Ok(()) => last_error_logged = false,
+                    Err(e) if !last_error_logged => {
+                        warn!("Failed to publish status snapshot: {:#}", e);
+                        last_error_logged = true;
+                    }
+                    Err(_) => {}
                 }
+                thread::sleep(Duration::from_secs(STATUS_PUBLISH_INTERVAL_SECS));
             }
         });
     }
 
-    /// Background thread to trigger auto-unlock after timeout
-    fn start_auto_unlock_thread(&self) {
+    /// Background thread that force-unlocks if the event loop stops responding while locked
+    /// CRITICAL SAFETY FEATURE: last resort against a lockout caused by a hung CFRunLoop thread
+    fn start_watchdog_thread(&self) {
         let state = self.state.clone();
+
         thread::Builder::new()
-            .name("auto-unlock".to_string())
+            .name("watchdog".to_string())
             .spawn(move || {
-                info!("Auto-unlock monitoring thread started");
+                info!(
+                    "Event loop watchdog started - will force-unlock after {} seconds without a heartbeat",
+                    state.get_watchdog_timeout_secs()
+                );
 
                 loop {
-                    thread::sleep(Duration::from_secs(AUTO_UNLOCK_CHECK_INTERVAL_SECS));
+                    thread::sleep(Duration::from_secs(WATCHDOG_CHECK_INTERVAL_SECS));
 
-                    // Skip processing when disabled
-                    if state.is_disabled() {
+                    // Skip checking when disabled (no event loop running)
+                    if state.is_disabled() || !state.is_event_loop_stalled() {
                         continue;
                     }
 
-                    if state.should_auto_unlock() {
-                        warn!("Auto-unlock timeout expired - disabling input interception");
+                    error!(
+                        "CRITICAL: Event loop has not responded for over {} seconds while locked - forcing unlock",
+                        state.get_watchdog_timeout_secs()
+                    );
+                    state.unlock_with(UnlockMethod::Watchdog);
 
-                        // Unlock the device
-                        state.trigger_auto_unlock();
-                        info!("Input unlocked due to auto-unlock timeout");
-                    }
+                    // Signal to stop event tap (same path as permission loss)
+                    state.request_stop_event_tap();
+
+                    ui::notify::notify_with(
+                        Level::Critical,
+                        &strings::get(MessageKey::EmergencyUnlockTitle),
+                        &strings::get(MessageKey::EmergencyUnlock),
+                    );
                 }
             })
-            .expect("Failed to spawn auto-unlock thread");
+            .expect("Failed to spawn watchdog thread");
     }
+}
 
-    /// Background thread to monitor accessibility permissions and signal when to stop event tap
-    /// CRITICAL SAFETY FEATURE: Prevents user lockout if permissions are revoked while app is running
-    fn start_permission_monitor_thread(&self) {
-        let state = self.state.clone();
+/// Sleep bounds of each scheduler task
+fn poll_policy(task: MonitorTask) -> PollPolicy {
+    match task {
+        // No deadline to track - check at a fixed interval, less often while disabled
+        MonitorTask::PermissionCheck => PollPolicy {
+            min: Duration::from_secs(PERMISSION_CHECK_INTERVAL_SECS),
+            idle: Duration::from_secs(PERMISSION_CHECK_INTERVAL_SECS),
+            max: Duration::from_secs(PERMISSION_CHECK_DISABLED_INTERVAL_SECS),
+        },
+        MonitorTask::BufferReset => PollPolicy {
+            min: Duration::from_millis(BUFFER_RESET_CHECK_INTERVAL_MS),
+            idle: Duration::from_millis(BUFFER_RESET_CHECK_MAX_MS),
+            max: Duration::from_millis(BUFFER_RESET_CHECK_MAX_MS),
+        },
+        MonitorTask::AutoLock => PollPolicy {
+            min: Duration::from_secs(TIMEOUT_CHECK_MIN_SECS),
+            idle: Duration::from_secs(AUTO_LOCK_CHECK_INTERVAL_SECS),
+            max: Duration::from_secs(AUTO_LOCK_CHECK_MAX_SECS),
+        },
+        MonitorTask::AutoUnlock => PollPolicy {
+            min: Duration::from_secs(TIMEOUT_CHECK_MIN_SECS),
+            idle: Duration::from_secs(AUTO_UNLOCK_CHECK_INTERVAL_SECS),
+            max: Duration::from_secs(AUTO_UNLOCK_CHECK_MAX_SECS),
+        },
+    }
+}
 
-        thread::Builder::new()
-            .name("permission-monitor".to_string())
-            .spawn(move || {
-                info!(
-                    "Permission monitoring thread started - will check every {} seconds",
-                    PERMISSION_CHECK_INTERVAL_SECS
-                );
+/// Time until the scheduler runs `task` again
+fn monitor_interval(task: MonitorTask, state: &AppState) -> Duration {
+    let remaining = match task {
+        MonitorTask::PermissionCheck => None,
+        // Whichever comes first: the buffer reset or a hold-to-unlock hold
+        MonitorTask::BufferReset => [
+            state.get_buffer_reset_remaining_ms().map(Duration::from_millis),
+            state.get_hold_unlock_remaining(),
+        ]
+        .into_iter()
+        .flatten()
+        .min(),
+        MonitorTask::AutoLock => auto_lock_next_deadline_secs(state).map(Duration::from_secs),
+        // Whichever comes first: auto-unlock or the hard cap
+        MonitorTask::AutoUnlock => [
+            state.get_auto_unlock_remaining_secs().map(Duration::from_secs),
+            state.get_max_lock_duration_remaining_secs().map(Duration::from_secs),
+        ]
+        .into_iter()
+        .flatten()
+        .min(),
+    };
+    next_poll_interval(&poll_policy(task), state.is_disabled(), remaining)
+}
 
-                // CRITICAL: Check initial permission state rather than assuming true
-                // This handles the edge case where permissions are removed before the first check
-                let mut last_permission_state = input_blocking::check_accessibility_permissions();
+/// Checks run by the scheduler thread, and the state they keep between runs
+struct Monitors {
+    state: Arc<AppState>,
+    /// Result of the last accessibility permission probe
+    probe: input_blocking::PermissionProbe,
+    last_permission_state: bool,
+    /// Holds the event tap restart back until restored permissions are stable
+    restore: input_blocking::PermissionRestoreDebounce,
+    /// Permission checks since startup (callback telemetry is logged every few checks)
+    permission_checks: u64,
+    last_remaining_log: Instant,
+}
 
-                // Cache the initial permission state
-                state.set_cached_accessibility_permissions(last_permission_state);
+impl Monitors {
+    /// Set up the checks, probing accessibility permissions right away
+    fn new(state: Arc<AppState>) -> Self {
+        // CRITICAL: Check initial permission state rather than assuming true
+        // This handles the edge case where permissions are removed before the first check
+        let probe = input_blocking::probe_accessibility_permissions();
+        let last_permission_state = probe.has_permissions;
+
+        // Cache the initial permission state
+        state.set_cached_accessibility_permissions(last_permission_state);
+
+        // If permissions are already missing, request event tap stop
+        if !last_permission_state {
+            warn!("CRITICAL: Accessibility permissions are missing at startup");
+            state.record_audit(AuditEvent::PermissionLost);
+
+            // Unlock if locked (unless the policy holds the lock until permissions return)
+            if state.is_locked() && state.get_permission_loss_policy().releases_lock() {
+                state.unlock_with(UnlockMethod::PermissionLost);
+                info!("Unlocked - permissions missing");
+            }
 
-                // If permissions are already missing, request event tap stop
-                if !last_permission_state {
-                    warn!("CRITICAL: Accessibility permissions are missing at startup");
+            // Signal to stop event tap
+            state.request_stop_event_tap();
 
-                    // Unlock if locked
-                    if state.is_locked() {
-                        state.set_locked(false);
-                        info!("Unlocked - permissions missing");
-                    }
+            ui::notify::notify_with(
+                Level::Critical,
+                &strings::get(MessageKey::PermissionsMissingTitle),
+                &strings::get(MessageKey::PermissionsMissing),
+            );
+        }
 
-                    // Signal to stop event tap
-                    state.request_stop_event_tap();
+        Self {
+            state,
+            probe,
+            last_permission_state,
+            restore: input_blocking::PermissionRestoreDebounce::new(last_permission_state),
+            permission_checks: 0,
+            last_remaining_log: Instant::now(),
+        }
+    }
 
-                    #[cfg(target_os = "macos")]
-                    {
-                        let _ = notify_rust::Notification::new()
-                            .summary("HandsOff - Permissions Missing")
-                            .body("Accessibility permissions are missing.\nInput blocking stopped to restore normal keyboard and mouse.\n\nUse Reset menu to restart after granting permissions.")
-                            .timeout(notify_rust::Timeout::Milliseconds(10000))
-                            .show();
-                    }
-                }
+    /// How long until `task` should run again
+    fn next_interval(&self, task: MonitorTask) -> Duration {
+        monitor_interval(task, &self.state)
+    }
 
-                // Track elapsed checks for periodic telemetry logging
-                let telemetry_checks_per_interval =
-                    (CALLBACK_TELEMETRY_INTERVAL_SECS / PERMISSION_CHECK_INTERVAL_SECS).max(1);
-                let mut check_counter: u64 = 0;
+    fn run(&mut self, task: MonitorTask) {
+        match task {
+            MonitorTask::PermissionCheck => self.check_permissions(),
+            MonitorTask::BufferReset => {
+                self.reset_buffer_if_expired();
+                self.check_hold_unlock();
+            }
+            MonitorTask::AutoLock => self.check_auto_lock(),
+            MonitorTask::AutoUnlock => self.check_auto_unlock(),
+        }
+    }
 
-                loop {
-                    thread::sleep(Duration::from_secs(PERMISSION_CHECK_INTERVAL_SECS));
+    /// Reset the input buffer after timeout
+    fn reset_buffer_if_expired(&self) {
+        let state = &self.state;
+        if state.should_reset_buffer() && state.buffer_len() > 0 {
+            info!("Resetting input buffer after timeout");
+            if state.clear_buffer_after_timeout() && state.is_feedback_sound_enabled() {
+                ui::sound::beep();
+            }
+        }
+    }
 
-                    // Skip permission checking when disabled (no event tap running)
-                    if state.is_disabled() {
-                        continue;
-                    }
+    /// Enable auto-lock after inactivity
+    fn check_auto_lock(&mut self) {
+        let state = &self.state;
 
-                    check_counter += 1;
-
-                    // Log callback telemetry periodically
-                    if check_counter % telemetry_checks_per_interval == 0 {
-                        let (count, slow, max_us) =
-                            event_tap::reset_callback_telemetry();
-                        if count > 0 {
-                            info!(
-                                "[telemetry] callback stats (last {}s): total={}, slow={}, max_duration={}us",
-                                CALLBACK_TELEMETRY_INTERVAL_SECS, count, slow, max_us
-                            );
-                        }
-                    }
+        // Log remaining time every 30 seconds
+        if self.last_remaining_log.elapsed() >= Duration::from_secs(30) {
+            self.last_remaining_log = Instant::now();
+            if let Some(remaining_secs) = state.get_auto_lock_remaining_secs() {
+                let minutes = remaining_secs / 60;
+                let seconds = remaining_secs % 60;
+                info!(
+                    "Auto-lock in {} seconds ({} min {} sec remaining)",
+                    remaining_secs, minutes, seconds
+                );
+            }
+        }
 
-                    // Lightweight check: only AXIsProcessTrusted(), no WindowServer interaction.
-                    // Avoids the CGEventTapCreate/CFRelease cycle that degrades WindowServer
-                    // over hundreds of calls (root cause of "callback was too slow" timeouts).
-                    let has_permissions = input_blocking::check_accessibility_permissions_lightweight();
+        // Playback only matters while unlocked (it can hold off auto-lock)
+        if !state.is_locked() && state.get_respect_media_playback() {
+            media_playback::refresh(state);
+        }
 
-                    // Detect permission loss (transition from true to false)
-                    if last_permission_state && !has_permissions {
-                        warn!("CRITICAL: Accessibility permissions were revoked while app is running!");
+        // Same for the power source (it picks the timeout, or turns auto-lock off)
+        if !state.is_locked() {
+            power_source::refresh(state);
+        }
 
-                        // Unlock if currently locked
-                        if state.is_locked() {
-                            warn!("App is locked - unlocking to restore input");
-                            state.set_locked(false);
-                            info!("Unlocked - permissions revoked");
-                        }
+        if state.should_auto_lock() {
+            match state.try_lock() {
+                Ok(()) => info!("Auto-lock triggered after inactivity - input now locked"),
+                Err(refused) => warn!("Auto-lock triggered but not locking - {}", refused),
+            }
+        } else if let Some(remaining_secs) = state.take_auto_lock_warning() {
+            // Any input before the deadline resets the idle timer and cancels the lock
+            info!("Auto-lock in {} seconds - warning user", remaining_secs);
+
+            ui::notify::notify_for(
+                &strings::get(MessageKey::LockingSoonTitle),
+                &strings::format(
+                    MessageKey::LockingSoon,
+                    &[("seconds", &remaining_secs.to_string())],
+                ),
+                Duration::from_secs(remaining_secs),
+            );
+        }
+    }
 
-                        // Signal to stop event tap (main thread will handle the actual stop)
-                        state.request_stop_event_tap();
-
-                        // Show notification
-                        #[cfg(target_os = "macos")]
-                        {
-                            let _ = notify_rust::Notification::new()
-                                .summary("HandsOff - Permissions Revoked")
-                                .body("Accessibility permissions were revoked.\nInput blocking stopped - your keyboard and mouse work normally now.\n\nRestore permissions and use Reset menu to restart.")
-                                .timeout(notify_rust::Timeout::Milliseconds(10000))
-                                .show();
-                        }
+    /// Complete a hold-to-unlock hold when the key doesn't auto-repeat
+    ///
+    /// Runs with the sub-second buffer reset check, so a hold started by the event tap
+    /// unlocks on time however long the auto-unlock check sleeps.
+    fn check_hold_unlock(&self) {
+        let state = &self.state;
+        if state.poll_hold_unlock_with(input_blocking::hold_unlock::is_key_down) {
+            auth::hold_unlock(state);
+        }
+    }
 
-                        warn!("Event tap stop requested - main thread will handle cleanup");
-                    }
-                    // Detect permission restoration
-                    else if !last_permission_state && has_permissions {
-                        info!("Accessibility permissions have been restored");
-
-                        // Request automatic restart (Tray app will handle this)
-                        state.request_start_event_tap();
-
-                        #[cfg(target_os = "macos")]
-                        {
-                            let _ = notify_rust::Notification::new()
-                                .summary("HandsOff - Permissions Restored")
-                                .body("Accessibility permissions restored.\n\nRestarting input blocking automatically...")
-                                .timeout(notify_rust::Timeout::Milliseconds(5000))
-                                .show();
-                        }
-                    }
+    /// Trigger auto-unlock after timeout, and force-unlock once the max lock duration hard
+    /// cap is reached (even with auto-unlock disabled)
+    fn check_auto_unlock(&self) {
+        let state = &self.state;
+        if state.should_auto_unlock() {
+            warn!("Auto-unlock timeout expired - disabling input interception");
+
+            // Unlock the device
+            state.trigger_auto_unlock();
+            info!("Input unlocked due to auto-unlock timeout");
+        } else if state.should_enforce_max_lock_duration() {
+            warn!("Max lock duration (hard cap) reached - forcing unlock regardless of auto-unlock");
+            state.trigger_max_lock_duration_unlock();
+            info!("Input unlocked due to max lock duration hard cap");
+        }
+    }
+
+    /// Monitor accessibility permissions and signal when to stop event tap
+    /// CRITICAL SAFETY FEATURE: Prevents user lockout if permissions are revoked while app is running
+    fn check_permissions(&mut self) {
+        let state = &self.state;
+        self.permission_checks += 1;
+
+        // Log callback telemetry periodically
+        let telemetry_checks_per_interval =
+            (CALLBACK_TELEMETRY_INTERVAL_SECS / PERMISSION_CHECK_INTERVAL_SECS).max(1);
+        if self.permission_checks % telemetry_checks_per_interval == 0 {
+            let (count, slow, max_us) = event_tap::reset_callback_telemetry();
+            if count > 0 {
+                info!(
+                    "[telemetry] callback stats (last {}s): total={}, slow={}, max_duration={}us",
+                    CALLBACK_TELEMETRY_INTERVAL_SECS, count, slow, max_us
+                );
+            }
+        }
+
+        // Lightweight check: only AXIsProcessTrusted(), no WindowServer interaction.
+        // Avoids the CGEventTapCreate/CFRelease cycle that degrades WindowServer
+        // over hundreds of calls (root cause of "callback was too slow" timeouts).
+        // The full tap probe reruns only when the AX result changes.
+        self.probe = input_blocking::refresh_permission_probe(
+            self.probe,
+            input_blocking::check_accessibility_permissions_lightweight(),
+            input_blocking::probe_accessibility_permissions,
+        );
+        let has_permissions = self.probe.has_permissions;
+
+        // Log every transition in one structured format (for diagnosing flapping)
+        if has_permissions != self.last_permission_state {
+            state.record_permission_transition();
+            info!(
+                "{}",
+                input_blocking::permission_change_entry(
+                    self.last_permission_state,
+                    has_permissions,
+                    &chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                )
+            );
+        }
 
-                    // Update cached state
-                    state.set_cached_accessibility_permissions(has_permissions);
-                    last_permission_state = has_permissions;
+        // Detect permission loss (transition from true to false)
+        if self.last_permission_state && !has_permissions {
+            warn!("CRITICAL: Accessibility permissions were revoked while app is running!");
+            state.record_audit(AuditEvent::PermissionLost);
+
+            // Unlock if currently locked (unless the policy holds the lock until
+            // permissions return - input isn't blocked either way once the tap stops)
+            if state.is_locked() {
+                if state.get_permission_loss_policy().releases_lock() {
+                    warn!("App is locked - unlocking to restore input");
+                    state.unlock_with(UnlockMethod::PermissionLost);
+                    info!("Unlocked - permissions revoked");
+                } else {
+                    warn!("App is locked - keeping the lock until permissions are restored");
                 }
-            })
-            .expect("Failed to spawn permission monitor thread");
+            }
+
+            // Signal to stop event tap (main thread will handle the actual stop) and let
+            // the binaries apply their permission loss policy
+            state.request_stop_event_tap();
+            state.request_exit();
+
+            // Show notification
+            ui::notify::notify_with(
+                Level::Critical,
+                &strings::get(MessageKey::PermissionsRevokedTitle),
+                &strings::get(MessageKey::PermissionsRevoked),
+            );
+
+            warn!("Event tap stop requested - main thread will handle cleanup");
+        }
+        // Detect permission restoration
+        else if !self.last_permission_state && has_permissions {
+            info!("Accessibility permissions have been restored - waiting for them to hold before restarting");
+        }
+
+        // Restart only once restored permissions are stable (stopping above is immediate)
+        if self.restore.record(has_permissions, Instant::now()) {
+            info!("Accessibility permissions stable - requesting event tap restart");
+
+            // Request automatic restart (Tray app will handle this)
+            state.request_start_event_tap();
+
+            ui::notify::notify_with(
+                Level::Important,
+                &strings::get(MessageKey::PermissionsRestoredTitle),
+                &strings::get(MessageKey::PermissionsRestored),
+            );
+        }
+
+        // Update cached state
+        state.set_cached_accessibility_permissions(has_permissions);
+        self.last_permission_state = has_permissions;
     }
 }
 
 impl Drop for HandsOffCore {
     fn drop(&mut self) {
         info!("HandsOffCore dropping - cleaning up resources");
+        self.shutdown();
+        info!("HandsOffCore cleanup complete");
+    }
+}
 
-        // Stop the event tap to release CGEventTapRef and prevent WindowServer resource leak
-        self.stop_event_tap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use global_hotkey::hotkey::Code;
+
+    #[test]
+    fn test_hotkey_display_defaults() {
+        let core = HandsOffCore::with_passphrase_hash(auth::hash_passphrase("test")).unwrap();
+        assert_eq!(core.get_hotkey_config(), (Some(Code::KeyL), Some(Code::KeyT)));
+        assert_eq!(core.get_lock_key_display().as_deref(), Some("L"));
+        assert_eq!(core.get_talk_key_display().as_deref(), Some("T"));
+        assert_eq!(core.get_unlock_key_display(), "U");
+        assert_eq!(core.get_lock_hotkey_display().as_deref(), Some("Ctrl+Cmd+Shift+L"));
+    }
 
-        // Unregister hotkeys to clean up global_hotkey resources
-        if let Some(ref mut manager) = self.hotkey_manager {
-            if let Err(e) = manager.unregister_all() {
-                warn!("Failed to unregister hotkeys during drop: {}", e);
-            }
-        }
+    #[test]
+    fn test_set_hotkey_config_is_used_for_display_and_registration() {
+        let mut core = HandsOffCore::with_passphrase_hash(auth::hash_passphrase("test")).unwrap();
+        core.set_hotkey_config(Some(Code::KeyK), Some(Code::KeyY));
 
-        info!("HandsOffCore cleanup complete");
+        assert_eq!(core.get_hotkey_config(), (Some(Code::KeyK), Some(Code::KeyY)));
+        assert_eq!(core.get_lock_key_display().as_deref(), Some("K"));
+        assert_eq!(core.get_talk_key_display().as_deref(), Some("Y"));
+        assert_eq!(core.get_talk_hotkey_display().as_deref(), Some("Ctrl+Cmd+Shift+Y"));
+
+        // The event tap matches the same keys as the global registration
+        assert_eq!(core.state.get_lock_keycode(), utils::keycode::code_to_keycode(Code::KeyK));
+        assert_eq!(core.state.get_talk_keycode(), utils::keycode::code_to_keycode(Code::KeyY));
+    }
+
+    #[test]
+    fn test_disabled_hotkeys_are_not_registered_or_matched() {
+        let mut core = HandsOffCore::with_passphrase_hash(auth::hash_passphrase("test")).unwrap();
+        core.set_hotkey_config(None, Some(Code::KeyT));
+
+        assert_eq!(core.get_hotkey_config(), (None, Some(Code::KeyT)));
+        assert_eq!(core.get_lock_hotkey_display(), None);
+        assert_eq!(core.get_talk_hotkey_display().as_deref(), Some("Ctrl+Cmd+Shift+T"));
+        // The event tap no longer treats L as the lock hotkey
+        assert_eq!(core.state.get_lock_keycode(), None);
+        assert_eq!(core.state.get_hotkey_ids().lock, None);
+
+        core.set_hotkey_config(None, None);
+        assert_eq!(core.state.get_talk_keycode(), None);
+        assert_eq!(core.get_talk_key_display(), None);
+    }
+
+    #[test]
+    fn test_verify_passphrase_leaves_lock_state_alone() {
+        let core = HandsOffCore::new("correct horse").unwrap();
+        assert!(core.verify_passphrase("correct horse"));
+        assert!(!core.verify_passphrase("correct hors"));
+        assert!(!core.is_locked());
+
+        core.set_passphrase("battery staple");
+        assert!(core.verify_passphrase("battery staple"));
+        assert!(!core.verify_passphrase("correct horse"));
+    }
+
+    #[test]
+    fn test_toggle_lock_locks_only_with_permissions() {
+        let core = HandsOffCore::new("correct horse").unwrap();
+        core.state.set_cached_accessibility_permissions(false);
+        assert!(matches!(core.toggle_lock(None), Err(HandsOffError::PermissionsMissing)));
+        assert!(!core.is_locked());
+
+        core.state.set_cached_accessibility_permissions(true);
+        assert!(core.toggle_lock(None).unwrap());
+        assert!(core.is_locked());
+    }
+
+    #[test]
+    fn test_toggle_lock_unlocks_only_with_correct_passphrase() {
+        let core = HandsOffCore::new("correct horse").unwrap();
+        core.state.set_cached_accessibility_permissions(true);
+        core.lock().unwrap();
+
+        assert!(matches!(core.toggle_lock(None), Err(HandsOffError::InvalidPassphrase)));
+        assert!(core.is_locked());
+        let wrong = core.toggle_lock(Some("correct hors"));
+        assert!(matches!(wrong, Err(HandsOffError::InvalidPassphrase)));
+        assert!(core.is_locked());
+
+        assert!(!core.toggle_lock(Some("correct horse")).unwrap());
+        assert!(!core.is_locked());
+    }
+
+    #[test]
+    fn test_hold_unlock_polled_within_a_second() {
+        let state = AppState::new();
+        state.set_auto_unlock_timeout(None);
+        state.set_hold_unlock(Some(input_blocking::hold_unlock::HoldUnlock {
+            keycode: constants::ESCAPE_KEYCODE,
+            duration: Duration::from_secs(3),
+        }));
+        state.set_locked(true);
+        state.press_hold_unlock_key();
+        assert!(state.get_hold_unlock_remaining().is_some(), "Hold in progress");
+
+        // The auto-unlock check may sleep far longer than the hold
+        assert!(monitor_interval(MonitorTask::AutoUnlock, &state) > Duration::from_secs(3));
+        assert!(monitor_interval(MonitorTask::BufferReset, &state) <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_lock_refused_without_passphrase() {
+        let core = HandsOffCore::new("correct horse").unwrap();
+        core.state.set_cached_accessibility_permissions(true);
+        core.state.lock().passphrase_hash = None;
+
+        assert!(matches!(core.lock(), Err(HandsOffError::NoPassphraseConfigured)));
+        assert!(matches!(core.toggle_lock(None), Err(HandsOffError::NoPassphraseConfigured)));
+        assert!(!core.is_locked());
+    }
+
+    #[test]
+    fn test_lock_and_unlock_errors() {
+        let core = HandsOffCore::new("correct horse").unwrap();
+        core.state.set_cached_accessibility_permissions(false);
+        assert!(matches!(core.lock(), Err(HandsOffError::PermissionsMissing)));
+
+        core.state.set_cached_accessibility_permissions(true);
+        core.lock().unwrap();
+        assert!(matches!(core.unlock("wrong"), Err(HandsOffError::InvalidPassphrase)));
+        assert!(core.is_locked());
+        assert!(core.unlock("correct horse").unwrap(), "Unlocked right away");
+        assert!(!core.is_locked());
+    }
+
+    #[test]
+    fn test_update_hotkeys_before_registration() {
+        let mut core = HandsOffCore::with_passphrase_hash(auth::hash_passphrase("test")).unwrap();
+        let unchanged = (Some(Code::KeyL), Some(Code::KeyT));
+        assert!(core.update_hotkeys(Some(Code::KeyJ), Some(Code::KeyJ)).is_err(), "Keys must differ");
+        assert_eq!(core.get_hotkey_config(), unchanged, "Unchanged on error");
+
+        // Nothing registered yet: only the configuration changes
+        core.update_hotkeys(Some(Code::KeyJ), Some(Code::KeyS)).unwrap();
+        assert_eq!(core.get_hotkey_config(), (Some(Code::KeyJ), Some(Code::KeyS)));
+
+        // Both disabled is fine (they don't collide)
+        core.update_hotkeys(None, None).unwrap();
+        assert_eq!(core.get_hotkey_config(), (None, None));
     }
 }
@@ -0,0 +1,243 @@
+//! Logger setup: stderr, optionally teed to a size-rotated log file
+//!
+//! With `--log-file <path>` (or HANDS_OFF_LOG_FILE) every log line is also appended
+//! to `path`, so there is a record of permission loss, locks and unlocks after a
+//! crash. When a write would grow the file past LOG_FILE_MAX_BYTES, it is renamed to
+//! `path.1` (older backups shift to `path.2`, ... up to LOG_FILE_BACKUPS) and a fresh
+//! file is started. Log files are owner-only (0600).
+
+use crate::constants::{LOG_FILE_BACKUPS, LOG_FILE_MAX_BYTES, LOG_FILE_PERMISSIONS};
+use anyhow::{Context, Result};
+use log::{warn, LevelFilter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// Initialize the global logger at `level` (RUST_LOG, when set, takes precedence)
+///
+/// If the log file can't be opened, logging continues on stderr only and a warning
+/// is logged.
+pub fn init(level: LevelFilter, log_file: Option<&Path>) {
+    // Directives parsed from RUST_LOG replace the default level set first
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level);
+    builder.parse_default_env();
+
+    let file = log_file.map(|path| {
+        RotatingFile::open(path, LOG_FILE_MAX_BYTES, LOG_FILE_BACKUPS)
+            .with_context(|| format!("Failed to open log file {}", path.display()))
+    });
+    let file_error = match file {
+        Some(Ok(file)) => {
+            builder.target(env_logger::Target::Pipe(Box::new(TeeWriter { file })));
+            None
+        }
+        Some(Err(e)) => Some(e),
+        None => None,
+    };
+
+    builder.init();
+    if let Some(e) = file_error {
+        warn!("{:#} - logging to stderr only", e);
+    }
+}
+
+/// Log level for the `-v`/`-q` flag counts
+///
+/// Info by default; `-v` Debug, `-vv` Trace; `-q` Warn, `-qq` Error. The flags
+/// conflict on the command line; should both be given, `-v` wins.
+pub fn level_from_flags(verbose: u8, quiet: u8) -> LevelFilter {
+    match (verbose, quiet) {
+        (0, 0) => LevelFilter::Info,
+        (0, 1) => LevelFilter::Warn,
+        (0, _) => LevelFilter::Error,
+        (1, _) => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Whether writing `incoming` bytes to a file of `current_len` bytes should rotate it first
+///
+/// An empty file is never rotated, so a single oversized line can't trigger a
+/// rotation on every write.
+pub fn needs_rotation(current_len: u64, incoming: usize, max_bytes: u64) -> bool {
+    current_len > 0 && current_len.saturating_add(incoming as u64) > max_bytes
+}
+
+/// Path of the `n`th rotated backup (`handsoff.log` -> `handsoff.log.1`)
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Append-only log file that rotates itself by size
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    backups: usize,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    /// Open (or create) `path` for appending
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, backups: usize) -> Result<Self> {
+        let path = path.into();
+        let file = open_log_file(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            backups,
+            file,
+            len,
+        })
+    }
+
+    /// Shift backups up by one, move the current file to `.1` and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.backups == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.backups).rev() {
+                let from = backup_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, backup_path(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, backup_path(&self.path, 1))?;
+        }
+        self.file = open_log_file(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if needs_rotation(self.len, buf.len(), self.max_bytes) {
+            if let Err(e) = self.rotate() {
+                // Can't log from inside the logger - report on stderr and keep appending
+                eprintln!("Failed to rotate log file {}: {}", self.path.display(), e);
+            }
+        }
+        self.file.write_all(buf)?;
+        self.len += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Open a log file for appending with owner-only permissions
+///
+/// Permissions are also tightened on an existing file.
+fn open_log_file(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(LOG_FILE_PERMISSIONS)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(LOG_FILE_PERMISSIONS))?;
+    Ok(file)
+}
+
+/// Writes each log record to stderr and the log file
+struct TeeWriter {
+    file: RotatingFile,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // stderr is best effort (it may be closed when launched at login)
+        let _ = io::stderr().write_all(buf);
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = io::stderr().flush();
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("handsoff_logging_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("handsoff.log")
+    }
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(level_from_flags(0, 0), LevelFilter::Info);
+        assert_eq!(level_from_flags(1, 0), LevelFilter::Debug);
+        assert_eq!(level_from_flags(2, 0), LevelFilter::Trace);
+        assert_eq!(level_from_flags(5, 0), LevelFilter::Trace);
+        assert_eq!(level_from_flags(0, 1), LevelFilter::Warn);
+        assert_eq!(level_from_flags(0, 2), LevelFilter::Error);
+        assert_eq!(level_from_flags(0, 7), LevelFilter::Error);
+        assert_eq!(level_from_flags(1, 1), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn test_needs_rotation() {
+        assert!(!needs_rotation(0, 10, 100));
+        assert!(!needs_rotation(90, 10, 100));
+        assert!(needs_rotation(91, 10, 100));
+        // An oversized line goes into an empty file rather than rotating forever
+        assert!(!needs_rotation(0, 500, 100));
+        assert!(needs_rotation(u64::MAX, 1, 100));
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            backup_path(Path::new("/tmp/handsoff.log"), 2),
+            PathBuf::from("/tmp/handsoff.log.2")
+        );
+    }
+
+    #[test]
+    fn test_rotation_keeps_configured_backups() {
+        let path = temp_log_path("rotation");
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(backup_path(&path, 2)).unwrap(), "second\n");
+        assert!(!backup_path(&path, 3).exists());
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, LOG_FILE_PERMISSIONS);
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_reopen_appends_and_tightens_permissions() {
+        let path = temp_log_path("reopen");
+        fs::write(&path, "old\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut file = RotatingFile::open(&path, 100, 1).unwrap();
+        file.write_all(b"new\n").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\nnew\n");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, LOG_FILE_PERMISSIONS);
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
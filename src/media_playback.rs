@@ -0,0 +1,120 @@
+//! Media playback detection for pausing auto-lock
+//!
+//! Video players, browsers playing video and conferencing apps hold an IOKit power
+//! assertion that prevents the display from idle-sleeping. When `respect_media_playback`
+//! is enabled, an active display-sleep assertion counts as activity and auto-lock
+//! is held off until it is released.
+
+use crate::app_state::AppState;
+use core_foundation::base::TCFType;
+use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+use core_foundation::number::CFNumber;
+use core_foundation::string::CFString;
+use log::{debug, info};
+
+/// kIOPMAssertionTypePreventUserIdleDisplaySleep
+const ASSERTION_PREVENT_USER_IDLE_DISPLAY_SLEEP: &str = "PreventUserIdleDisplaySleep";
+
+/// kIOPMAssertionTypeNoDisplaySleep (deprecated alias still used by older apps)
+const ASSERTION_NO_DISPLAY_SLEEP: &str = "NoDisplaySleepAssertion";
+
+/// kIOReturnSuccess
+const IO_RETURN_SUCCESS: i32 = 0;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPMCopyAssertionsStatus(assertions_status: *mut CFDictionaryRef) -> i32;
+}
+
+/// Check whether any process holds an assertion preventing display sleep
+///
+/// Returns false if the assertion status cannot be read.
+pub fn is_display_sleep_prevented() -> bool {
+    let mut status: CFDictionaryRef = std::ptr::null();
+    let result = unsafe { IOPMCopyAssertionsStatus(&mut status) };
+    if result != IO_RETURN_SUCCESS || status.is_null() {
+        debug!("IOPMCopyAssertionsStatus failed: {:#x}", result);
+        return false;
+    }
+
+    let status: CFDictionary<CFString, CFNumber> =
+        unsafe { CFDictionary::wrap_under_create_rule(status) };
+    [ASSERTION_PREVENT_USER_IDLE_DISPLAY_SLEEP, ASSERTION_NO_DISPLAY_SLEEP]
+        .iter()
+        .any(|name| {
+            status
+                .find(CFString::from_static_string(name))
+                .and_then(|count| count.to_i64())
+                .is_some_and(|count| count > 0)
+        })
+}
+
+/// Refresh the cached media playback state using the IOKit assertion query
+pub fn refresh(state: &AppState) {
+    refresh_with(state, is_display_sleep_prevented);
+}
+
+/// Refresh the cached media playback state with an injectable query (for tests)
+///
+/// The query only runs when `respect_media_playback` is enabled.
+pub fn refresh_with<F>(state: &AppState, query: F)
+where
+    F: FnOnce() -> bool,
+{
+    let active = state.get_respect_media_playback() && query();
+    if active != state.is_media_playback_active() {
+        if active {
+            info!("Media playback detected - auto-lock paused");
+        } else {
+            info!("Media playback stopped - auto-lock resumed");
+        }
+    }
+    state.set_media_playback_active(active);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idle_state() -> AppState {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("test"));
+        state.lock().auto_lock_timeout = 0; // idle timeout already exceeded
+        state
+    }
+
+    #[test]
+    fn test_playback_pauses_auto_lock_when_respected() {
+        let state = idle_state();
+        state.set_respect_media_playback(true);
+
+        refresh_with(&state, || true);
+        assert!(!state.should_auto_lock(), "Playback should hold off auto-lock");
+
+        refresh_with(&state, || false);
+        assert!(state.should_auto_lock(), "Auto-lock resumes when playback stops");
+    }
+
+    #[test]
+    fn test_playback_ignored_when_not_respected() {
+        let state = idle_state();
+
+        refresh_with(&state, || panic!("Query should not run when the flag is off"));
+        assert!(!state.is_media_playback_active());
+        assert!(state.should_auto_lock());
+    }
+
+    #[test]
+    fn test_disabling_flag_clears_cached_playback() {
+        let state = idle_state();
+        state.set_respect_media_playback(true);
+        refresh_with(&state, || true);
+        assert!(state.is_media_playback_active());
+
+        state.set_respect_media_playback(false);
+        refresh_with(&state, || true);
+        assert!(!state.is_media_playback_active());
+        assert!(state.should_auto_lock());
+    }
+}
@@ -0,0 +1,184 @@
+//! Prometheus-style counters for long-term monitoring
+//!
+//! `AppState` owns one `Metrics` and bumps it at state transitions; `GET /metrics` on
+//! the HTTP API serves a snapshot in the Prometheus text exposition format. Counters
+//! start at zero with each process (scrapers handle resets).
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Content-Type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Process-lifetime counters (relaxed atomics: bumped from the event tap callback)
+#[derive(Debug, Default)]
+pub struct Metrics {
+    locks: AtomicU64,
+    auto_unlocks: AtomicU64,
+    events_blocked: AtomicU64,
+    permission_losses: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Input went from unlocked to locked
+    pub fn record_lock(&self) {
+        self.locks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A lock was released by the auto-unlock timeout or the max lock duration
+    pub fn record_auto_unlock(&self) {
+        self.auto_unlocks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The event tap blocked an event
+    pub fn record_event_blocked(&self) {
+        self.events_blocked.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Accessibility permissions were revoked while running
+    pub fn record_permission_loss(&self) {
+        self.permission_losses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current counter values, plus the lock state gauge
+    pub fn snapshot(&self, locked: bool) -> MetricsSnapshot {
+        MetricsSnapshot {
+            locks_total: self.locks.load(Ordering::Relaxed),
+            auto_unlocks_total: self.auto_unlocks.load(Ordering::Relaxed),
+            events_blocked_total: self.events_blocked.load(Ordering::Relaxed),
+            permission_losses_total: self.permission_losses.load(Ordering::Relaxed),
+            locked,
+        }
+    }
+}
+
+/// Point-in-time copy of the metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub locks_total: u64,
+    pub auto_unlocks_total: u64,
+    pub events_blocked_total: u64,
+    pub permission_losses_total: u64,
+    pub locked: bool,
+}
+
+impl MetricsSnapshot {
+    /// Render in the Prometheus text exposition format (version 0.0.4)
+    pub fn to_prometheus(&self) -> String {
+        let metrics: [(&str, &str, &str, u64); 5] = [
+            (
+                "handsoff_locks_total",
+                "counter",
+                "Times input was locked.",
+                self.locks_total,
+            ),
+            (
+                "handsoff_auto_unlocks_total",
+                "counter",
+                "Locks released by the auto-unlock timeout or max lock duration.",
+                self.auto_unlocks_total,
+            ),
+            (
+                "handsoff_events_blocked_total",
+                "counter",
+                "Input events blocked while locked.",
+                self.events_blocked_total,
+            ),
+            (
+                "handsoff_permission_losses_total",
+                "counter",
+                "Times accessibility permissions were lost.",
+                self.permission_losses_total,
+            ),
+            (
+                "handsoff_locked",
+                "gauge",
+                "Whether input is currently locked (1) or not (0).",
+                u64::from(self.locked),
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, kind, help, value) in metrics {
+            // Writing to a String can't fail
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_accumulate() {
+        let metrics = Metrics::new();
+        metrics.record_lock();
+        metrics.record_lock();
+        metrics.record_auto_unlock();
+        for _ in 0..5 {
+            metrics.record_event_blocked();
+        }
+        metrics.record_permission_loss();
+
+        let snapshot = metrics.snapshot(true);
+        assert_eq!(
+            snapshot,
+            MetricsSnapshot {
+                locks_total: 2,
+                auto_unlocks_total: 1,
+                events_blocked_total: 5,
+                permission_losses_total: 1,
+                locked: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_prometheus_exposition_format() {
+        let text = MetricsSnapshot {
+            locks_total: 3,
+            auto_unlocks_total: 1,
+            events_blocked_total: 42,
+            permission_losses_total: 0,
+            locked: false,
+        }
+        .to_prometheus();
+
+        assert!(text.ends_with('\n'));
+        assert!(text.contains(
+            "# HELP handsoff_locks_total Times input was locked.\n\
+             # TYPE handsoff_locks_total counter\n\
+             handsoff_locks_total 3\n"
+        ));
+        assert!(text.contains(
+            "# TYPE handsoff_auto_unlocks_total counter\nhandsoff_auto_unlocks_total 1\n"
+        ));
+        assert!(text.contains("handsoff_events_blocked_total 42\n"));
+        assert!(text.contains("handsoff_permission_losses_total 0\n"));
+        assert!(text.contains("# TYPE handsoff_locked gauge\nhandsoff_locked 0\n"));
+
+        // Every sample line is "name value", preceded by its HELP and TYPE lines
+        let samples: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(samples.len(), 5);
+        for sample in samples {
+            let (name, value) = sample.split_once(' ').unwrap();
+            assert!(name.starts_with("handsoff_"));
+            assert!(value.parse::<u64>().is_ok());
+            assert!(text.contains(&format!("# TYPE {} ", name)));
+        }
+    }
+
+    #[test]
+    fn test_locked_gauge() {
+        let text = Metrics::new().snapshot(true).to_prometheus();
+        assert!(text.contains("\nhandsoff_locked 1\n"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Power source detection for power-aware auto-lock
+//!
+//! A laptop on battery is usually away from the desk, so auto-lock can be stricter
+//! there: `auto_lock_timeout_on_battery` sets a separate (typically shorter) timeout,
+//! and `auto_lock_only_on_battery` turns auto-lock off entirely while on AC power.
+//! The providing power source comes from IOKit (`IOPSCopyPowerSourcesInfo`).
+
+use crate::app_state::AppState;
+use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+use core_foundation::string::{CFString, CFStringRef};
+use log::{debug, info};
+use std::fmt;
+
+/// kIOPMACPowerKey
+const AC_POWER: &str = "AC Power";
+
+/// kIOPMBatteryPowerKey
+const BATTERY_POWER: &str = "Battery Power";
+
+/// kIOPMUPSPowerKey
+const UPS_POWER: &str = "UPS Power";
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+    fn IOPSGetProvidingPowerSourceType(snapshot: CFTypeRef) -> CFStringRef;
+}
+
+/// Where the Mac currently draws power from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PowerSource {
+    /// Plugged in (desktops always report this)
+    Ac,
+    /// Running on the internal battery (or a UPS)
+    Battery,
+    /// Not queried yet, or the query failed
+    #[default]
+    Unknown,
+}
+
+impl PowerSource {
+    /// Classify an IOKit providing power source type
+    pub fn from_type_name(name: &str) -> Self {
+        match name {
+            AC_POWER => PowerSource::Ac,
+            BATTERY_POWER | UPS_POWER => PowerSource::Battery,
+            _ => PowerSource::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PowerSource::Ac => write!(f, "AC power"),
+            PowerSource::Battery => write!(f, "battery"),
+            PowerSource::Unknown => write!(f, "unknown power source"),
+        }
+    }
+}
+
+/// How auto-lock depends on the power source (the default ignores it)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerAutoLockPolicy {
+    /// Auto-lock timeout in seconds on battery (None = same as on AC)
+    pub timeout_on_battery: Option<u64>,
+    /// Whether auto-lock runs on AC power
+    pub lock_on_ac: bool,
+}
+
+impl Default for PowerAutoLockPolicy {
+    fn default() -> Self {
+        Self {
+            timeout_on_battery: None,
+            lock_on_ac: true,
+        }
+    }
+}
+
+impl PowerAutoLockPolicy {
+    /// Whether the power source matters at all (only then is it queried)
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+/// Auto-lock timeout to use on `power` (None = no auto-lock)
+///
+/// An unknown power source counts as battery, so a failed query errs on the side of
+/// locking.
+pub fn effective_auto_lock_timeout(
+    power: PowerSource,
+    base_timeout: u64,
+    policy: &PowerAutoLockPolicy,
+) -> Option<u64> {
+    match power {
+        PowerSource::Ac if !policy.lock_on_ac => None,
+        PowerSource::Ac => Some(base_timeout),
+        PowerSource::Battery | PowerSource::Unknown => {
+            Some(policy.timeout_on_battery.unwrap_or(base_timeout))
+        }
+    }
+}
+
+/// Query the providing power source from IOKit
+pub fn current() -> PowerSource {
+    unsafe {
+        let snapshot = IOPSCopyPowerSourcesInfo();
+        if snapshot.is_null() {
+            debug!("IOPSCopyPowerSourcesInfo returned null");
+            return PowerSource::Unknown;
+        }
+        let type_name = IOPSGetProvidingPowerSourceType(snapshot);
+        let source = if type_name.is_null() {
+            PowerSource::Unknown
+        } else {
+            // Owned by the snapshot: read it before releasing the snapshot
+            PowerSource::from_type_name(&CFString::wrap_under_get_rule(type_name).to_string())
+        };
+        CFRelease(snapshot);
+        source
+    }
+}
+
+/// Refresh the cached power source using the IOKit query
+pub fn refresh(state: &AppState) {
+    refresh_with(state, current);
+}
+
+/// Refresh the cached power source with an injectable query (for tests)
+///
+/// The query only runs when a power-aware auto-lock policy is configured.
+pub fn refresh_with<F>(state: &AppState, query: F)
+where
+    F: FnOnce() -> PowerSource,
+{
+    if !state.get_power_auto_lock_policy().is_active() {
+        return;
+    }
+    let source = query();
+    if source != state.get_power_source() {
+        info!("Now on {} - auto-lock timeout adjusted", source);
+    }
+    state.set_power_source(source);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BATTERY_60: PowerAutoLockPolicy = PowerAutoLockPolicy {
+        timeout_on_battery: Some(60),
+        lock_on_ac: true,
+    };
+
+    #[test]
+    fn test_from_type_name() {
+        assert_eq!(PowerSource::from_type_name("AC Power"), PowerSource::Ac);
+        assert_eq!(PowerSource::from_type_name("Battery Power"), PowerSource::Battery);
+        assert_eq!(PowerSource::from_type_name("UPS Power"), PowerSource::Battery);
+        assert_eq!(PowerSource::from_type_name("Solar"), PowerSource::Unknown);
+    }
+
+    #[test]
+    fn test_default_policy_ignores_power_source() {
+        let policy = PowerAutoLockPolicy::default();
+        assert!(!policy.is_active());
+        for power in [PowerSource::Ac, PowerSource::Battery, PowerSource::Unknown] {
+            assert_eq!(effective_auto_lock_timeout(power, 300, &policy), Some(300));
+        }
+    }
+
+    #[test]
+    fn test_battery_timeout_selection() {
+        assert!(BATTERY_60.is_active());
+        assert_eq!(effective_auto_lock_timeout(PowerSource::Ac, 300, &BATTERY_60), Some(300));
+        assert_eq!(effective_auto_lock_timeout(PowerSource::Battery, 300, &BATTERY_60), Some(60));
+        assert_eq!(
+            effective_auto_lock_timeout(PowerSource::Unknown, 300, &BATTERY_60),
+            Some(60),
+            "Unknown errs on the side of locking"
+        );
+    }
+
+    #[test]
+    fn test_only_on_battery() {
+        let policy = PowerAutoLockPolicy {
+            timeout_on_battery: None,
+            lock_on_ac: false,
+        };
+        assert_eq!(effective_auto_lock_timeout(PowerSource::Ac, 300, &policy), None);
+        assert_eq!(effective_auto_lock_timeout(PowerSource::Battery, 300, &policy), Some(300));
+
+        let policy = PowerAutoLockPolicy {
+            lock_on_ac: false,
+            ..BATTERY_60
+        };
+        assert_eq!(effective_auto_lock_timeout(PowerSource::Ac, 300, &policy), None);
+        assert_eq!(effective_auto_lock_timeout(PowerSource::Battery, 300, &policy), Some(60));
+    }
+
+    #[test]
+    fn test_refresh_only_queries_with_an_active_policy() {
+        let state = AppState::new();
+        refresh_with(&state, || panic!("Query should not run with the default policy"));
+        assert_eq!(state.get_power_source(), PowerSource::Unknown);
+
+        state.set_power_auto_lock_policy(BATTERY_60);
+        refresh_with(&state, || PowerSource::Ac);
+        assert_eq!(state.get_power_source(), PowerSource::Ac);
+    }
+}
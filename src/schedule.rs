@@ -0,0 +1,161 @@
+//! Time-of-day lock schedule
+//!
+//! Lock windows are configured in `config.toml` as local wall-clock times:
+//!
+//! ```toml
+//! [schedule]
+//! windows = [{ start = "22:00", end = "07:00" }]
+//! ```
+//!
+//! A window whose end is earlier than its start wraps past midnight. Input is locked
+//! when a window is entered; unlocking inside a window (passphrase, Touch ID,
+//! auto-unlock) works normally and is not undone until the next window starts.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+/// Time format for window boundaries (24-hour clock)
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Lock schedule as stored in the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Lock windows (local time)
+    #[serde(default)]
+    pub windows: Vec<ScheduleWindow>,
+}
+
+/// A lock window as stored in the config file ("HH:MM" local time)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleWindow {
+    pub start: String,
+    pub end: String,
+}
+
+/// A parsed lock window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Schedule {
+    /// Whether no lock windows are configured
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Parse and validate all configured windows
+    pub fn parse_windows(&self) -> Result<Vec<LockWindow>> {
+        self.windows.iter().map(ScheduleWindow::parse).collect()
+    }
+}
+
+impl ScheduleWindow {
+    /// Parse "HH:MM" boundaries into a `LockWindow`
+    pub fn parse(&self) -> Result<LockWindow> {
+        let start = parse_time(&self.start)
+            .with_context(|| format!("Invalid schedule window start '{}'", self.start))?;
+        let end = parse_time(&self.end)
+            .with_context(|| format!("Invalid schedule window end '{}'", self.end))?;
+        if start == end {
+            return Err(anyhow!(
+                "Schedule window {}-{} is empty (start and end must differ)",
+                self.start,
+                self.end
+            ));
+        }
+        Ok(LockWindow { start, end })
+    }
+}
+
+impl LockWindow {
+    /// Whether `now` falls inside the window (start inclusive, end exclusive)
+    ///
+    /// Windows with end < start wrap past midnight (e.g. 22:00-07:00).
+    pub fn contains(&self, now: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= now && now < self.end
+        } else {
+            now >= self.start || now < self.end
+        }
+    }
+}
+
+/// Whether `now` falls inside any of the windows
+pub fn is_within_any(windows: &[LockWindow], now: NaiveTime) -> bool {
+    windows.iter().any(|w| w.contains(now))
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), TIME_FORMAT)
+        .map_err(|_| anyhow!("Expected 24-hour time as HH:MM"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str) -> LockWindow {
+        ScheduleWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+        .parse()
+        .unwrap()
+    }
+
+    fn t(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_same_day_window() {
+        let w = window("09:00", "17:30");
+        assert!(!w.contains(t(8, 59)));
+        assert!(w.contains(t(9, 0)));
+        assert!(w.contains(t(12, 0)));
+        assert!(w.contains(t(17, 29)));
+        assert!(!w.contains(t(17, 30)));
+    }
+
+    #[test]
+    fn test_window_wrapping_midnight() {
+        let w = window("22:00", "07:00");
+        assert!(w.contains(t(22, 0)));
+        assert!(w.contains(t(23, 59)));
+        assert!(w.contains(t(0, 0)));
+        assert!(w.contains(t(6, 59)));
+        assert!(!w.contains(t(7, 0)));
+        assert!(!w.contains(t(12, 0)));
+        assert!(!w.contains(t(21, 59)));
+    }
+
+    #[test]
+    fn test_multiple_windows() {
+        let windows = vec![window("22:00", "07:00"), window("12:00", "13:00")];
+        assert!(is_within_any(&windows, t(12, 30)));
+        assert!(is_within_any(&windows, t(3, 0)));
+        assert!(!is_within_any(&windows, t(9, 0)));
+        assert!(!is_within_any(&[], t(3, 0)));
+    }
+
+    #[test]
+    fn test_invalid_windows_rejected() {
+        let cases = [
+            ("25:00", "07:00"),
+            ("22:00", "7pm"),
+            ("", "07:00"),
+            ("08:00", "08:00"),
+        ];
+        for (start, end) in cases {
+            let result = ScheduleWindow {
+                start: start.to_string(),
+                end: end.to_string(),
+            }
+            .parse();
+            assert!(result.is_err(), "{}-{} should be rejected", start, end);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! AppleScript support: `lock`, `disable` and `is locked`
+//!
+//! The tray app installs Apple Event handlers for the commands declared in
+//! `HandsOff.sdef`, so Script Editor, Automator and `osascript` can drive it:
+//!
+//! ```applescript
+//! tell application "HandsOff"
+//!     if not (is locked) then lock
+//! end tell
+//! ```
+//!
+//! Like the control socket, commands act on `AppState` directly (disable is
+//! requested and applied by the main thread). There is deliberately no unlock
+//! command: unlocking needs the passphrase, and scripts are the wrong place for it.
+
+use crate::app_state::{AppState, LockRefused};
+use log::info;
+
+/// Build a four-character Apple Event code (`b"HOff"` -> 0x484F6666)
+pub const fn four_char_code(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+/// Event class of every HandsOff command (suite code in `HandsOff.sdef`)
+pub const EVENT_CLASS: u32 = four_char_code(b"HOff");
+/// `lock`
+pub const EVENT_LOCK: u32 = four_char_code(b"Lock");
+/// `disable`
+pub const EVENT_DISABLE: u32 = four_char_code(b"Disa");
+/// `is locked`
+pub const EVENT_IS_LOCKED: u32 = four_char_code(b"IsLk");
+
+/// Command sent by a script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptCommand {
+    Lock,
+    Disable,
+    IsLocked,
+}
+
+impl ScriptCommand {
+    /// Every command, with its event ID
+    pub const ALL: [(ScriptCommand, u32); 3] = [
+        (ScriptCommand::Lock, EVENT_LOCK),
+        (ScriptCommand::Disable, EVENT_DISABLE),
+        (ScriptCommand::IsLocked, EVENT_IS_LOCKED),
+    ];
+
+    /// Command for an Apple Event (None for other classes and unknown IDs)
+    pub fn from_event(class: u32, id: u32) -> Option<Self> {
+        if class != EVENT_CLASS {
+            return None;
+        }
+        Self::ALL
+            .into_iter()
+            .find(|(_, event_id)| *event_id == id)
+            .map(|(command, _)| command)
+    }
+}
+
+/// Result returned to the script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptReply {
+    /// Command carried out (no result value)
+    Done,
+    /// Boolean result (`is locked`)
+    Boolean(bool),
+}
+
+/// Carry out a command, or return the error message shown to the script
+pub fn handle_command(
+    command: ScriptCommand,
+    state: &AppState,
+) -> Result<ScriptReply, &'static str> {
+    match command {
+        ScriptCommand::Lock => {
+            if state.is_disabled() {
+                return Err("HandsOff is disabled");
+            }
+            if !state.is_locked() {
+                match state.try_lock() {
+                    Ok(()) => info!("Input locked via AppleScript"),
+                    Err(LockRefused::PermissionsMissing) => {
+                        return Err("Accessibility permissions are not granted")
+                    }
+                    Err(LockRefused::NoPassphrase) => {
+                        return Err("No passphrase configured - run 'handsoff --setup' first")
+                    }
+                }
+            }
+            Ok(ScriptReply::Done)
+        }
+        ScriptCommand::Disable => {
+            if state.is_locked() {
+                // Disabling stops the event tap, which would bypass the lock
+                return Err("Input is locked - type the passphrase to unlock first");
+            }
+            info!("Disable requested via AppleScript");
+            state.request_disable();
+            Ok(ScriptReply::Done)
+        }
+        ScriptCommand::IsLocked => Ok(ScriptReply::Boolean(state.is_locked())),
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod apple_events {
+    use super::{four_char_code, handle_command, ScriptCommand, ScriptReply, EVENT_CLASS};
+    use crate::app_state::AppState;
+    use anyhow::{anyhow, Result};
+    use log::warn;
+    use std::ffi::c_void;
+    use std::sync::{Arc, OnceLock};
+
+    /// AEDesc / AppleEvent
+    #[repr(C)]
+    struct AEDesc {
+        descriptor_type: u32,
+        data_handle: *mut c_void,
+    }
+
+    type AEEventHandler = unsafe extern "C" fn(*const AEDesc, *mut AEDesc, *mut c_void) -> i16;
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn AEInstallEventHandler(
+            event_class: u32,
+            event_id: u32,
+            handler: AEEventHandler,
+            refcon: *mut c_void,
+            is_sys_handler: u8,
+        ) -> i16;
+        fn AEPutParamPtr(
+            event: *mut AEDesc,
+            keyword: u32,
+            type_code: u32,
+            data: *const c_void,
+            size: isize,
+        ) -> i16;
+    }
+
+    const NO_ERR: i16 = 0;
+    /// errAEEventFailed
+    const ERR_EVENT_FAILED: i16 = -10000;
+    /// errAEEventNotHandled
+    const ERR_EVENT_NOT_HANDLED: i16 = -1708;
+
+    const KEY_DIRECT_OBJECT: u32 = four_char_code(b"----");
+    const KEY_ERROR_STRING: u32 = four_char_code(b"errs");
+    const TYPE_BOOLEAN: u32 = four_char_code(b"bool");
+    const TYPE_UTF8_TEXT: u32 = four_char_code(b"utf8");
+
+    /// State the handlers act on (set once by `install`)
+    static STATE: OnceLock<Arc<AppState>> = OnceLock::new();
+
+    /// Install the Apple Event handlers (main thread, before the event loop runs)
+    pub fn install(state: Arc<AppState>) -> Result<()> {
+        if STATE.set(state).is_err() {
+            return Err(anyhow!("AppleScript handlers already installed"));
+        }
+        for (_, event_id) in ScriptCommand::ALL {
+            // The event ID travels in the refcon so one handler serves every command
+            let refcon = event_id as usize as *mut c_void;
+            let err =
+                unsafe { AEInstallEventHandler(EVENT_CLASS, event_id, handle_event, refcon, 0) };
+            if err != NO_ERR {
+                return Err(anyhow!("AEInstallEventHandler failed with error {}", err));
+            }
+        }
+        Ok(())
+    }
+
+    unsafe extern "C" fn handle_event(
+        _event: *const AEDesc,
+        reply: *mut AEDesc,
+        refcon: *mut c_void,
+    ) -> i16 {
+        let (Some(command), Some(state)) = (
+            ScriptCommand::from_event(EVENT_CLASS, refcon as usize as u32),
+            STATE.get(),
+        ) else {
+            return ERR_EVENT_NOT_HANDLED;
+        };
+
+        match handle_command(command, state) {
+            Ok(ScriptReply::Done) => NO_ERR,
+            Ok(ScriptReply::Boolean(value)) => {
+                let value = value as u8;
+                put_reply(reply, KEY_DIRECT_OBJECT, TYPE_BOOLEAN, &[value]);
+                NO_ERR
+            }
+            Err(message) => {
+                warn!("AppleScript command {:?} refused: {}", command, message);
+                put_reply(reply, KEY_ERROR_STRING, TYPE_UTF8_TEXT, message.as_bytes());
+                ERR_EVENT_FAILED
+            }
+        }
+    }
+
+    /// Add a parameter to the reply (the reply is null when the sender doesn't wait)
+    unsafe fn put_reply(reply: *mut AEDesc, keyword: u32, type_code: u32, data: &[u8]) {
+        if reply.is_null() || (*reply).descriptor_type == four_char_code(b"null") {
+            return;
+        }
+        let err = AEPutParamPtr(
+            reply,
+            keyword,
+            type_code,
+            data.as_ptr() as *const c_void,
+            data.len() as isize,
+        );
+        if err != NO_ERR {
+            warn!("Failed to set AppleScript reply (error {})", err);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use apple_events::install;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_mapping() {
+        assert_eq!(EVENT_CLASS, 0x484F_6666);
+        assert_eq!(ScriptCommand::from_event(EVENT_CLASS, EVENT_LOCK), Some(ScriptCommand::Lock));
+        assert_eq!(
+            ScriptCommand::from_event(EVENT_CLASS, EVENT_DISABLE),
+            Some(ScriptCommand::Disable)
+        );
+        assert_eq!(
+            ScriptCommand::from_event(EVENT_CLASS, EVENT_IS_LOCKED),
+            Some(ScriptCommand::IsLocked)
+        );
+
+        // No unlock command, and other suites' events are left alone
+        assert_eq!(ScriptCommand::from_event(EVENT_CLASS, four_char_code(b"Unlk")), None);
+        assert_eq!(ScriptCommand::from_event(four_char_code(b"aevt"), EVENT_LOCK), None);
+    }
+
+    #[test]
+    fn test_lock_and_query() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("secret"));
+        assert_eq!(
+            handle_command(ScriptCommand::IsLocked, &state),
+            Ok(ScriptReply::Boolean(false))
+        );
+        assert_eq!(handle_command(ScriptCommand::Lock, &state), Ok(ScriptReply::Done));
+        assert!(state.is_locked());
+        assert_eq!(
+            handle_command(ScriptCommand::IsLocked, &state),
+            Ok(ScriptReply::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn test_lock_refused_without_passphrase() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        assert!(handle_command(ScriptCommand::Lock, &state).is_err());
+        assert!(!state.is_locked());
+    }
+
+    #[test]
+    fn test_disable_refused_while_locked() {
+        let state = AppState::new();
+        state.set_locked(true);
+        assert!(handle_command(ScriptCommand::Disable, &state).is_err());
+        assert!(!state.should_disable_and_clear());
+
+        state.set_locked(false);
+        assert_eq!(handle_command(ScriptCommand::Disable, &state), Ok(ScriptReply::Done));
+        assert!(state.should_disable_and_clear());
+    }
+
+    #[test]
+    fn test_lock_refused_while_disabled() {
+        let state = AppState::new();
+        state.set_disabled(true);
+        assert!(handle_command(ScriptCommand::Lock, &state).is_err());
+        assert!(!state.is_locked());
+    }
+}
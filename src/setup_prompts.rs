@@ -0,0 +1,93 @@
+//! Interactive `--setup` prompts shared by the CLI and tray binaries
+//!
+//! Each prompt prints to stdout, reads one line from stdin and validates it the same
+//! way the config file is validated on load, so both setups accept the same values.
+
+use crate::app_state::LockScope;
+use crate::config_file::Config;
+use crate::utils::keycode;
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+
+/// Prompt for a timeout in seconds, returns `default` for empty input
+///
+/// Values rejected by `validate` are reported and asked for again, so setup never
+/// saves a timeout that the next config load would refuse.
+pub fn prompt_timeout(prompt: &str, default: u64, validate: fn(u64) -> Result<()>) -> Result<u64> {
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        let input = input.trim();
+
+        let secs = if input.is_empty() {
+            default
+        } else {
+            input
+                .parse::<u64>()
+                .with_context(|| format!("Invalid number: {}", input))?
+        };
+        match validate(secs) {
+            Ok(()) => return Ok(secs),
+            Err(e) => println!("{}, please try again.", e),
+        }
+    }
+}
+
+/// Prompt for the Talk passthrough key ("space" or A-Z), returns None for default (space)
+pub fn prompt_talk_passthrough_key(prompt: &str) -> Result<Option<String>> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+
+    if input.is_empty() {
+        Ok(None) // Use default
+    } else {
+        Config::validate_talk_passthrough_key(input)?;
+        Ok(Some(input.to_lowercase()))
+    }
+}
+
+/// Prompt for the lock scope (all/keyboard/mouse), returns LockScope::All for default
+pub fn prompt_lock_scope(prompt: &str) -> Result<LockScope> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+
+    if input.is_empty() {
+        Ok(LockScope::All)
+    } else {
+        input.parse::<LockScope>()
+    }
+}
+
+/// Warn about passphrase characters that may not be typable while locked, and ask
+/// whether to keep the passphrase anyway
+pub fn confirm_typable_passphrase(passphrase: &str) -> Result<()> {
+    // Setup writes a new config, which uses the default (US) keyboard layout
+    let Err(chars) = keycode::can_type_while_locked(passphrase, keycode::KeyboardLayout::default())
+    else {
+        return Ok(());
+    };
+
+    println!("\nWarning: {}", keycode::untypable_passphrase_warning(&chars));
+    println!("While locked, the passphrase is read key by key, so characters that need an input method or a keyboard layout you don't use may never match.");
+    print!("Use this passphrase anyway? [y/N]: ");
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    if matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+        Ok(())
+    } else {
+        anyhow::bail!("Error: Setup cancelled - choose a passphrase you can type while locked")
+    }
+}
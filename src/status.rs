@@ -0,0 +1,178 @@
+//! Status snapshots for querying a running instance
+//!
+//! The running app periodically publishes a `StatusSnapshot` of its `AppState` to a
+//! small JSON file next to the control socket; `handsoff status` reads it back (or
+//! asks the control socket directly when it is enabled). The same struct is used
+//! by both the writer and the reader.
+
+use crate::app_state::{AppState, LockScope};
+use crate::constants::{CONFIG_FILE_PERMISSIONS, STATUS_FILE_NAME};
+use crate::ipc;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Point-in-time view of the running instance's state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    /// Whether input is locked
+    pub locked: bool,
+    /// Whether HandsOff is disabled (minimal CPU mode)
+    pub disabled: bool,
+    /// Which input devices are blocked while locked
+    pub lock_scope: LockScope,
+    /// Seconds until auto-lock (None while locked or snoozed)
+    pub auto_lock_remaining: Option<u64>,
+    /// Seconds until auto-unlock (None if unlocked or auto-unlock is disabled)
+    pub auto_unlock_remaining: Option<u64>,
+    /// Seconds since input was locked (None while unlocked)
+    pub locked_for: Option<u64>,
+    /// Process ID of the publishing instance
+    pub pid: u32,
+    /// When the snapshot was taken (Unix time, seconds)
+    pub updated_at: u64,
+}
+
+impl StatusSnapshot {
+    /// Capture the current state
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            locked: state.is_locked(),
+            disabled: state.is_disabled(),
+            lock_scope: state.get_lock_scope(),
+            auto_lock_remaining: state.get_auto_lock_remaining_secs(),
+            auto_unlock_remaining: state.get_auto_unlock_remaining_secs(),
+            locked_for: state.get_lock_elapsed_secs(),
+            pid: std::process::id(),
+            updated_at: unix_now(),
+        }
+    }
+
+    /// Serialize to a single line of JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).context("Failed to serialize status snapshot")
+    }
+
+    /// Parse a snapshot from JSON (unknown fields are ignored)
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Failed to parse status snapshot")
+    }
+
+    /// Age of the snapshot in seconds at `now` (Unix time)
+    pub fn age_secs(&self, now: u64) -> u64 {
+        now.saturating_sub(self.updated_at)
+    }
+
+    /// Adjust countdowns for the time elapsed since the snapshot was taken
+    pub fn aged(mut self, now: u64) -> Self {
+        let age = self.age_secs(now);
+        self.auto_lock_remaining = self.auto_lock_remaining.map(|s| s.saturating_sub(age));
+        self.auto_unlock_remaining = self.auto_unlock_remaining.map(|s| s.saturating_sub(age));
+        self.locked_for = self.locked_for.map(|s| s + age);
+        self
+    }
+}
+
+/// Current Unix time in seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Get the status file path (next to the control socket)
+pub fn status_path() -> PathBuf {
+    ipc::runtime_dir().join(STATUS_FILE_NAME)
+}
+
+/// Write a snapshot atomically (write to a temp file, then rename) with owner-only permissions
+pub fn write_snapshot(path: &Path, snapshot: &StatusSnapshot) -> Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, snapshot.to_json()?)
+        .with_context(|| format!("Failed to write status file {}", tmp_path.display()))?;
+    fs::set_permissions(&tmp_path, fs::Permissions::from_mode(CONFIG_FILE_PERMISSIONS))
+        .context("Failed to set status file permissions")?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace status file {}", path.display()))
+}
+
+/// Read the last published snapshot
+pub fn read_snapshot(path: &Path) -> Result<StatusSnapshot> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read status file {}", path.display()))?;
+    StatusSnapshot::from_json(&contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> StatusSnapshot {
+        StatusSnapshot {
+            locked: true,
+            disabled: false,
+            lock_scope: LockScope::KeyboardOnly,
+            auto_lock_remaining: None,
+            auto_unlock_remaining: Some(42),
+            locked_for: Some(18),
+            pid: 4242,
+            updated_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_serialization() {
+        let json = sample().to_json().unwrap();
+        assert_eq!(
+            json,
+            r#"{"locked":true,"disabled":false,"lock_scope":"keyboard_only","auto_lock_remaining":null,"auto_unlock_remaining":42,"locked_for":18,"pid":4242,"updated_at":1700000000}"#
+        );
+        assert_eq!(StatusSnapshot::from_json(&json).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_parse_sample_snapshot_ignores_extra_fields() {
+        let json = r#"{"ok":true,"locked":false,"disabled":true,"lock_scope":"all",
+            "auto_lock_remaining":42,"auto_unlock_remaining":null,"locked_for":null,
+            "pid":1,"updated_at":100}"#;
+        let snapshot = StatusSnapshot::from_json(json).unwrap();
+        assert!(!snapshot.locked);
+        assert!(snapshot.disabled);
+        assert_eq!(snapshot.lock_scope, LockScope::All);
+        assert_eq!(snapshot.auto_lock_remaining, Some(42));
+        assert!(StatusSnapshot::from_json("{\"locked\":true}").is_err());
+    }
+
+    #[test]
+    fn test_aged_adjusts_countdowns() {
+        let aged = sample().aged(1_700_000_005);
+        assert_eq!(aged.auto_unlock_remaining, Some(37));
+        assert_eq!(aged.locked_for, Some(23));
+        assert_eq!(sample().aged(1_700_000_100).auto_unlock_remaining, Some(0));
+    }
+
+    #[test]
+    fn test_snapshot_from_state() {
+        let state = AppState::new();
+        state.set_locked(true);
+        let snapshot = StatusSnapshot::from_state(&state);
+        assert!(snapshot.locked);
+        assert_eq!(snapshot.auto_lock_remaining, None);
+        assert_eq!(snapshot.locked_for, Some(0));
+        assert_eq!(snapshot.pid, std::process::id());
+    }
+
+    #[test]
+    fn test_write_and_read_snapshot() {
+        let path = std::env::temp_dir().join(format!("handsoff_status_{}.json", std::process::id()));
+        write_snapshot(&path, &sample()).unwrap();
+        assert_eq!(read_snapshot(&path).unwrap(), sample());
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, CONFIG_FILE_PERMISSIONS);
+        fs::remove_file(path).ok();
+    }
+}
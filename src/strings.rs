@@ -0,0 +1,304 @@
+//! User-facing notification and tooltip texts, overridable from the config file
+//!
+//! Every notification and the tray tooltip's status line look their text up by
+//! `MessageKey`. A `[messages]` table in `config.toml` replaces any of them, e.g. to
+//! translate them:
+//!
+//! ```toml
+//! [messages]
+//! input_locked = "Eingabe gesperrt - Passphrase eingeben"
+//! auto_lock_snoozed = "Automatische Sperre für {minutes} Minuten ausgesetzt"
+//! ```
+//!
+//! Placeholders in braces (`{minutes}`, `{seconds}`, `{count}`, `{pid}`, `{error}`) are
+//! filled in by `format`. Keys that aren't overridden fall back to the English defaults.
+
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+
+/// Overrides applied at startup (empty until `set_overrides` is called)
+static OVERRIDES: OnceLock<HashMap<MessageKey, String>> = OnceLock::new();
+
+/// A user-facing message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKey {
+    NotificationTitle,
+    AlreadyRunning,
+    InputLocked,
+    InputUnlocked,
+    BlockedEvents,
+    AutoLockSnoozed,
+    Paused,
+    PauseOver,
+    Disabled,
+    PassphraseChanged,
+    ResetReenabled,
+    ResetRestarted,
+    BlockingRestartedTitle,
+    BlockingRestarted,
+    RestartFailedTitle,
+    RestartFailed,
+    ResumeFailedTitle,
+    ResumeFailed,
+    LockingSoonTitle,
+    LockingSoon,
+    EmergencyUnlockTitle,
+    EmergencyUnlock,
+    TwoFactorFailed,
+    TwoFactorUnavailable,
+    PermissionsMissingTitle,
+    PermissionsMissing,
+    PermissionsRevokedTitle,
+    PermissionsRevoked,
+    PermissionsRestoredTitle,
+    PermissionsRestored,
+    StatusLocked,
+    StatusUnlocked,
+    StatusDisabled,
+    StatusPaused,
+    StatusNoPermissions,
+}
+
+impl MessageKey {
+    /// Every message, in config documentation order
+    pub const ALL: [MessageKey; 35] = [
+        MessageKey::NotificationTitle,
+        MessageKey::AlreadyRunning,
+        MessageKey::InputLocked,
+        MessageKey::InputUnlocked,
+        MessageKey::BlockedEvents,
+        MessageKey::AutoLockSnoozed,
+        MessageKey::Paused,
+        MessageKey::PauseOver,
+        MessageKey::Disabled,
+        MessageKey::PassphraseChanged,
+        MessageKey::ResetReenabled,
+        MessageKey::ResetRestarted,
+        MessageKey::BlockingRestartedTitle,
+        MessageKey::BlockingRestarted,
+        MessageKey::RestartFailedTitle,
+        MessageKey::RestartFailed,
+        MessageKey::ResumeFailedTitle,
+        MessageKey::ResumeFailed,
+        MessageKey::LockingSoonTitle,
+        MessageKey::LockingSoon,
+        MessageKey::EmergencyUnlockTitle,
+        MessageKey::EmergencyUnlock,
+        MessageKey::TwoFactorFailed,
+        MessageKey::TwoFactorUnavailable,
+        MessageKey::PermissionsMissingTitle,
+        MessageKey::PermissionsMissing,
+        MessageKey::PermissionsRevokedTitle,
+        MessageKey::PermissionsRevoked,
+        MessageKey::PermissionsRestoredTitle,
+        MessageKey::PermissionsRestored,
+        MessageKey::StatusLocked,
+        MessageKey::StatusUnlocked,
+        MessageKey::StatusDisabled,
+        MessageKey::StatusPaused,
+        MessageKey::StatusNoPermissions,
+    ];
+
+    /// Key name used in the config file's `[messages]` table
+    pub fn name(self) -> &'static str {
+        self.spec().0
+    }
+
+    /// English text used when the key isn't overridden
+    pub fn default_text(self) -> &'static str {
+        self.spec().1
+    }
+
+    fn spec(self) -> (&'static str, &'static str) {
+        match self {
+            MessageKey::NotificationTitle => ("notification_title", "HandsOff"),
+            MessageKey::AlreadyRunning => (
+                "already_running",
+                "HandsOff is already running (PID {pid})\nUse its menu bar icon",
+            ),
+            MessageKey::InputLocked => ("input_locked", "Input locked - Type passphrase to unlock"),
+            MessageKey::InputUnlocked => ("input_unlocked", "Input unlocked"),
+            MessageKey::BlockedEvents => ("blocked_events", "Blocked {count} events during this lock"),
+            MessageKey::AutoLockSnoozed => (
+                "auto_lock_snoozed",
+                "Auto-lock snoozed for {minutes} minutes\nLock or Reset to resume early",
+            ),
+            MessageKey::Paused => ("paused", "Paused for {minutes} minutes\nUse Reset to resume early"),
+            MessageKey::PauseOver => ("pause_over", "Pause over - HandsOff is active again"),
+            MessageKey::Disabled => (
+                "disabled",
+                "Disabled - Low system resources mode\nInput blocking paused. Use Reset to re-enable",
+            ),
+            MessageKey::PassphraseChanged => (
+                "passphrase_changed",
+                "Passphrase changed\nUse the new passphrase to unlock",
+            ),
+            MessageKey::ResetReenabled => (
+                "reset_reenabled",
+                "App reset complete - Re-enabled and ready to use",
+            ),
+            MessageKey::ResetRestarted => (
+                "reset_restarted",
+                "Reset complete - Input blocking restarted\nReady to use",
+            ),
+            MessageKey::BlockingRestartedTitle => (
+                "blocking_restarted_title",
+                "HandsOff - Input Blocking Restarted",
+            ),
+            MessageKey::BlockingRestarted => (
+                "blocking_restarted",
+                "Input blocking restarted successfully.\nHandsOff is now active.",
+            ),
+            MessageKey::RestartFailedTitle => ("restart_failed_title", "HandsOff - Restart Failed"),
+            MessageKey::RestartFailed => (
+                "restart_failed",
+                "Failed to restart input blocking: {error}\n\nUse Reset menu to try again.",
+            ),
+            MessageKey::ResumeFailedTitle => ("resume_failed_title", "HandsOff - Resume Failed"),
+            MessageKey::ResumeFailed => (
+                "resume_failed",
+                "Failed to re-enable after pause: {error}\n\nUse Reset menu to try again.",
+            ),
+            MessageKey::LockingSoonTitle => ("locking_soon_title", "HandsOff - Locking Soon"),
+            MessageKey::LockingSoon => (
+                "locking_soon",
+                "Input will lock in {seconds} seconds.\nMove the mouse or press any key to stay unlocked.",
+            ),
+            MessageKey::EmergencyUnlockTitle => ("emergency_unlock_title", "HandsOff - Emergency Unlock"),
+            MessageKey::EmergencyUnlock => (
+                "emergency_unlock",
+                "HandsOff stopped responding while locked.\nInput was unlocked and blocking stopped.\n\nUse Reset menu to restart.",
+            ),
+            MessageKey::TwoFactorFailed => (
+                "two_factor_failed",
+                "Touch ID not confirmed - input remains locked\nType the passphrase again to retry",
+            ),
+            MessageKey::TwoFactorUnavailable => (
+                "two_factor_unavailable",
+                "Touch ID unavailable - input remains locked\nTwo-factor unlock needs Touch ID ({error})",
+            ),
+            MessageKey::PermissionsMissingTitle => (
+                "permissions_missing_title",
+                "HandsOff - Permissions Missing",
+            ),
+            MessageKey::PermissionsMissing => (
+                "permissions_missing",
+                "Accessibility permissions are missing.\nInput blocking stopped to restore normal keyboard and mouse.\n\nUse Reset menu to restart after granting permissions.",
+            ),
+            MessageKey::PermissionsRevokedTitle => (
+                "permissions_revoked_title",
+                "HandsOff - Permissions Revoked",
+            ),
+            MessageKey::PermissionsRevoked => (
+                "permissions_revoked",
+                "Accessibility permissions were revoked.\nInput blocking stopped - your keyboard and mouse work normally now.\n\nRestore permissions and use Reset menu to restart.",
+            ),
+            MessageKey::PermissionsRestoredTitle => (
+                "permissions_restored_title",
+                "HandsOff - Permissions Restored",
+            ),
+            MessageKey::PermissionsRestored => (
+                "permissions_restored",
+                "Accessibility permissions restored.\n\nRestarting input blocking automatically...",
+            ),
+            MessageKey::StatusLocked => ("status_locked", "STATUS: LOCKED"),
+            MessageKey::StatusUnlocked => ("status_unlocked", "STATUS: Unlocked"),
+            MessageKey::StatusDisabled => ("status_disabled", "STATUS: DISABLED"),
+            MessageKey::StatusPaused => ("status_paused", "STATUS: PAUSED"),
+            MessageKey::StatusNoPermissions => ("status_no_permissions", "STATUS: NO PERMISSIONS"),
+        }
+    }
+
+    /// Look up a key by its config file name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.name() == name)
+    }
+}
+
+/// Convert the config file's `[messages]` table, skipping (and warning about) unknown keys
+pub fn parse_overrides(messages: &BTreeMap<String, String>) -> HashMap<MessageKey, String> {
+    messages
+        .iter()
+        .filter_map(|(name, text)| match MessageKey::from_name(name) {
+            Some(key) => Some((key, text.clone())),
+            None => {
+                warn!("Unknown key '{}' in [messages] of config file - ignored", name);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Install the message overrides for this process (only the first call takes effect)
+pub fn set_overrides(overrides: HashMap<MessageKey, String>) {
+    if OVERRIDES.set(overrides).is_err() {
+        warn!("Message overrides already set - ignoring");
+    }
+}
+
+/// Text for `key`: the override if one is set, otherwise the English default
+pub fn lookup(overrides: &HashMap<MessageKey, String>, key: MessageKey) -> &str {
+    overrides
+        .get(&key)
+        .map(String::as_str)
+        .unwrap_or_else(|| key.default_text())
+}
+
+/// Replace `{name}` placeholders in `template`
+pub fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Text for `key` (without placeholders)
+pub fn get(key: MessageKey) -> String {
+    match OVERRIDES.get() {
+        Some(overrides) => lookup(overrides, key).to_string(),
+        None => key.default_text().to_string(),
+    }
+}
+
+/// Text for `key` with its placeholders filled in
+pub fn format(key: MessageKey, args: &[(&str, &str)]) -> String {
+    fill(&get(key), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_names_are_unique_and_round_trip() {
+        for key in MessageKey::ALL {
+            assert_eq!(MessageKey::from_name(key.name()), Some(key));
+            assert!(!key.default_text().is_empty());
+        }
+        assert_eq!(MessageKey::from_name("no_such_message"), None);
+    }
+
+    #[test]
+    fn test_overrides_returned_and_missing_keys_fall_back() {
+        let mut messages = BTreeMap::new();
+        messages.insert("input_locked".to_string(), "Eingabe gesperrt".to_string());
+        messages.insert("no_such_message".to_string(), "ignored".to_string());
+        let overrides = parse_overrides(&messages);
+        assert_eq!(overrides.len(), 1, "Unknown keys are skipped");
+
+        assert_eq!(lookup(&overrides, MessageKey::InputLocked), "Eingabe gesperrt");
+        assert_eq!(lookup(&overrides, MessageKey::InputUnlocked), "Input unlocked");
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let text = fill(MessageKey::Paused.default_text(), &[("minutes", "15")]);
+        assert_eq!(text, "Paused for 15 minutes\nUse Reset to resume early");
+
+        let text = fill(MessageKey::AlreadyRunning.default_text(), &[("pid", "1234")]);
+        assert!(text.starts_with("HandsOff is already running (PID 1234)"));
+
+        // Placeholders without a value, or values without a placeholder, are left alone
+        assert_eq!(fill("{a} and {b}", &[("a", "1"), ("c", "3")]), "1 and {b}");
+    }
+}
@@ -0,0 +1,215 @@
+//! Frontmost-app observer: lock input when a listed app becomes active
+//!
+//! Registers an Objective-C observer for `NSWorkspaceDidActivateApplicationNotification`
+//! and locks input when the newly active app's bundle identifier is in the config
+//! file's `lock_on_app` list (e.g. lock as soon as the mail client comes to the front,
+//! so a draft can't be sent). Matching is the same as for `app_allowlist`: exact and
+//! case insensitive.
+//!
+//! Notifications are delivered on the main thread, so `install` must be called from
+//! the tray app's main thread before its event loop runs.
+
+use crate::app_state::AppState;
+use crate::input_blocking::app_allowlist;
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use std::ffi::{c_char, c_void, CStr};
+use std::sync::{Arc, OnceLock};
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+
+/// Decide whether activating `bundle_id` should lock input
+///
+/// Only for a listed app, and only while HandsOff is enabled, unlocked and able to
+/// lock (see `AppState::can_lock`).
+pub fn should_lock_for_app(bundle_id: &str, lock_on_app: &[String], state: &AppState) -> bool {
+    app_allowlist::matches(lock_on_app, bundle_id)
+        && !state.is_locked()
+        && !state.is_disabled()
+        && state.can_lock().is_ok()
+}
+
+/// Shared with the observer callback (set once by `install`)
+struct Observed {
+    state: Arc<AppState>,
+    lock_on_app: Vec<String>,
+}
+
+static OBSERVED: OnceLock<Observed> = OnceLock::new();
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn objc_allocateClassPair(superclass: Id, name: *const c_char, extra_bytes: usize) -> Id;
+    fn objc_registerClassPair(class: Id);
+    fn class_addMethod(class: Id, name: Sel, imp: *const c_void, types: *const c_char) -> u8;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+}
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    static NSWorkspaceDidActivateApplicationNotification: Id;
+    static NSWorkspaceApplicationKey: Id;
+}
+
+unsafe extern "C" fn did_activate_application(_this: Id, _cmd: Sel, notification: Id) {
+    let Some(observed) = OBSERVED.get() else {
+        return;
+    };
+    let Some(bundle_id) = activated_bundle_id(notification) else {
+        return;
+    };
+    debug!("Frontmost app changed: {}", bundle_id);
+    if should_lock_for_app(&bundle_id, &observed.lock_on_app, &observed.state) {
+        match observed.state.try_lock() {
+            Ok(()) => info!("Locking input - {} became active", bundle_id),
+            Err(refused) => warn!("Not locking for {} - {}", bundle_id, refused),
+        }
+    }
+}
+
+/// Bundle identifier of the app in an activation notification's userInfo
+unsafe fn activated_bundle_id(notification: Id) -> Option<String> {
+    if notification.is_null() {
+        return None;
+    }
+    let send: unsafe extern "C" fn(Id, Sel) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    let object_for_key: unsafe extern "C" fn(Id, Sel, Id) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    let get_utf8: unsafe extern "C" fn(Id, Sel) -> *const c_char =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+
+    let user_info = send(notification, sel_registerName(c"userInfo".as_ptr()));
+    if user_info.is_null() {
+        return None;
+    }
+    let app = object_for_key(
+        user_info,
+        sel_registerName(c"objectForKey:".as_ptr()),
+        NSWorkspaceApplicationKey,
+    );
+    if app.is_null() {
+        return None;
+    }
+    let bundle_id = send(app, sel_registerName(c"bundleIdentifier".as_ptr()));
+    if bundle_id.is_null() {
+        return None;
+    }
+    let utf8 = get_utf8(bundle_id, sel_registerName(c"UTF8String".as_ptr()));
+    if utf8.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
+
+/// Register the frontmost-app observer (main thread, once)
+pub fn install(state: Arc<AppState>, lock_on_app: Vec<String>) -> Result<()> {
+    let apps = lock_on_app.join(", ");
+    if OBSERVED.set(Observed { state, lock_on_app }).is_err() {
+        return Err(anyhow!("App activation observer already installed"));
+    }
+
+    unsafe {
+        let class = objc_allocateClassPair(
+            objc_getClass(c"NSObject".as_ptr()),
+            c"HandsOffAppActivationObserver".as_ptr(),
+            0,
+        );
+        if class.is_null() {
+            return Err(anyhow!("Failed to create app activation observer class"));
+        }
+        let imp: unsafe extern "C" fn(Id, Sel, Id) = did_activate_application;
+        class_addMethod(
+            class,
+            sel_registerName(c"didActivateApplication:".as_ptr()),
+            imp as *const c_void,
+            c"v@:@".as_ptr(),
+        );
+        objc_registerClassPair(class);
+
+        let send: unsafe extern "C" fn(Id, Sel) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        // Lives for the rest of the process, like the notification center's reference
+        let observer = send(
+            send(class, sel_registerName(c"alloc".as_ptr())),
+            sel_registerName(c"init".as_ptr()),
+        );
+        let workspace = send(
+            objc_getClass(c"NSWorkspace".as_ptr()),
+            sel_registerName(c"sharedWorkspace".as_ptr()),
+        );
+        let center = send(workspace, sel_registerName(c"notificationCenter".as_ptr()));
+        if observer.is_null() || center.is_null() {
+            return Err(anyhow!("Failed to access the NSWorkspace notification center"));
+        }
+
+        let add_observer: unsafe extern "C" fn(Id, Sel, Id, Sel, Id, Id) =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        add_observer(
+            center,
+            sel_registerName(c"addObserver:selector:name:object:".as_ptr()),
+            observer,
+            sel_registerName(c"didActivateApplication:".as_ptr()),
+            NSWorkspaceDidActivateApplicationNotification,
+            std::ptr::null_mut(),
+        );
+    }
+
+    info!("Locking input when these apps become active: {}", apps);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apps(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn enabled_state() -> AppState {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("secret"));
+        state
+    }
+
+    #[test]
+    fn test_locks_for_listed_app_only() {
+        let state = enabled_state();
+        let list = apps(&["com.apple.mail"]);
+        assert!(should_lock_for_app("com.apple.mail", &list, &state));
+        assert!(should_lock_for_app("com.apple.Mail", &list, &state), "Case insensitive");
+        assert!(!should_lock_for_app("com.apple.Safari", &list, &state));
+        assert!(!should_lock_for_app("com.apple", &list, &state), "No prefix matches");
+        assert!(!should_lock_for_app("com.apple.mail", &[], &state));
+    }
+
+    #[test]
+    fn test_ignored_when_locked_disabled_or_without_permissions() {
+        let list = apps(&["com.apple.mail"]);
+
+        let state = enabled_state();
+        state.set_locked(true);
+        assert!(!should_lock_for_app("com.apple.mail", &list, &state));
+
+        let state = enabled_state();
+        state.set_disabled(true);
+        assert!(!should_lock_for_app("com.apple.mail", &list, &state));
+
+        let state = enabled_state();
+        state.set_cached_accessibility_permissions(false);
+        assert!(!should_lock_for_app("com.apple.mail", &list, &state));
+    }
+
+    #[test]
+    fn test_ignored_without_passphrase() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        let list = apps(&["com.apple.mail"]);
+        assert!(!should_lock_for_app("com.apple.mail", &list, &state));
+    }
+}
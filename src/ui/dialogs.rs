@@ -0,0 +1,226 @@
+//! Native dialogs shown from the tray app (via `osascript`)
+//!
+//! AppleScript's `display dialog` gives a standard macOS prompt without pulling in
+//! an AppKit dialog implementation. The call blocks until the dialog is dismissed.
+
+use crate::config::EffectiveSettings;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+use zeroize::Zeroizing;
+
+/// Title shown on HandsOff dialogs
+const DIALOG_TITLE: &str = "HandsOff";
+
+/// Quote a string for use as an AppleScript string literal
+pub fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// AppleScript that asks for a passphrase with hidden (bulleted) input
+pub fn passphrase_dialog_script(prompt: &str) -> String {
+    format!(
+        "text returned of (display dialog {} with title {} default answer \"\" with hidden answer buttons {{\"Cancel\", \"OK\"}} default button \"OK\" cancel button \"Cancel\")",
+        applescript_string(prompt),
+        applescript_string(DIALOG_TITLE)
+    )
+}
+
+/// AppleScript that asks to confirm an action (Cancel is the default button)
+pub fn confirm_dialog_script(message: &str, confirm_button: &str) -> String {
+    format!(
+        "display dialog {} with title {} buttons {{\"Cancel\", {}}} default button \"Cancel\" cancel button \"Cancel\"",
+        applescript_string(message),
+        applescript_string(DIALOG_TITLE),
+        applescript_string(confirm_button)
+    )
+}
+
+/// Menu actions that can ask for confirmation first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardedAction {
+    Disable,
+    Reset,
+}
+
+impl GuardedAction {
+    /// Confirmation question and the button that goes ahead
+    pub fn prompt(self) -> (&'static str, &'static str) {
+        match self {
+            GuardedAction::Disable => (
+                "Disable HandsOff?\n\nInput blocking and auto-lock stop until you use Reset.",
+                "Disable",
+            ),
+            GuardedAction::Reset => (
+                "Reset HandsOff?\n\nThis unlocks input without the passphrase and restarts input blocking.",
+                "Reset",
+            ),
+        }
+    }
+}
+
+/// Which menu actions need confirmation (`confirm_disable` / `confirm_reset` in the config)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfirmGuard {
+    pub disable: bool,
+    pub reset: bool,
+}
+
+impl ConfirmGuard {
+    /// Whether `action` asks before running
+    pub fn should_prompt(self, action: GuardedAction) -> bool {
+        match action {
+            GuardedAction::Disable => self.disable,
+            GuardedAction::Reset => self.reset,
+        }
+    }
+
+    /// Run `run` unless the guard prompts and `confirm` declines; returns whether it ran
+    pub fn run<C, R>(self, action: GuardedAction, confirm: C, run: R) -> bool
+    where
+        C: FnOnce(GuardedAction) -> bool,
+        R: FnOnce(),
+    {
+        if self.should_prompt(action) && !confirm(action) {
+            return false;
+        }
+        run();
+        true
+    }
+}
+
+/// Ask to confirm `action`; true only if the user clicked the confirm button
+///
+/// Fails safe: if the dialog can't be shown, the action is treated as cancelled.
+pub fn show_confirm_dialog(action: GuardedAction) -> bool {
+    let (message, confirm_button) = action.prompt();
+    show_confirm(message, confirm_button)
+}
+
+/// Ask to confirm with `message`; true only if the user clicked `confirm_button`
+///
+/// Fails safe like `show_confirm_dialog`: false if the dialog can't be shown.
+pub fn show_confirm(message: &str, confirm_button: &str) -> bool {
+    match Command::new("osascript")
+        .arg("-e")
+        .arg(confirm_dialog_script(message, confirm_button))
+        .output()
+    {
+        // Cancel makes osascript exit with "User canceled. (-128)"
+        Ok(output) => output.status.success(),
+        Err(e) => {
+            log::warn!("Failed to show confirmation dialog: {} - not going ahead", e);
+            false
+        }
+    }
+}
+
+/// Body of the tray's Settings… dialog
+pub fn settings_dialog_text(settings: &EffectiveSettings, config_path: &Path) -> String {
+    format!(
+        "Current settings:\n\n{}\n\nTo change them, edit {} and restart HandsOff. HANDS_OFF_* environment variables override the file.",
+        settings,
+        config_path.display()
+    )
+}
+
+/// Ask for a passphrase in a dialog with hidden input
+///
+/// Returns None when the user cancels. The text may be empty; validating it is up
+/// to the caller.
+pub fn show_set_passphrase_dialog(prompt: &str) -> Result<Option<Zeroizing<String>>> {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(passphrase_dialog_script(prompt))
+        .output()
+        .context("Failed to run osascript")?;
+
+    // Cancel makes osascript exit with "User canceled. (-128)"
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let mut text = Zeroizing::new(
+        String::from_utf8(output.stdout).context("Dialog returned invalid UTF-8")?,
+    );
+    // osascript terminates the result with a single newline
+    if text.ends_with('\n') {
+        text.pop();
+    }
+    Ok(Some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_string_escapes_quotes_and_backslashes() {
+        assert_eq!(applescript_string("plain"), "\"plain\"");
+        assert_eq!(applescript_string(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+
+    #[test]
+    fn test_settings_dialog_text_reflects_config() {
+        use crate::config::{resolve_effective_settings_internal, EnvOverrides};
+        use crate::config_file::Config;
+
+        let mut cfg = Config::new("test_passphrase", 90, 300, Some("K".to_string()), None).unwrap();
+        cfg.talk_modifiers = Some("cmd+opt".to_string());
+        cfg.buffer_reset_timeout = Some(7);
+        let settings = resolve_effective_settings_internal(&cfg, &EnvOverrides::default());
+
+        let text = settings_dialog_text(&settings, Path::new("/tmp/handsoff/config.toml"));
+        assert!(text.contains("Auto-lock:     90s"));
+        assert!(text.contains("Auto-unlock:   300s"));
+        assert!(text.contains("Lock hotkey:   Ctrl+Cmd+Shift+K"));
+        assert!(text.contains("Talk hotkey:   Opt+Cmd+T"));
+        assert!(text.contains("Buffer reset:  7s"));
+        assert!(text.contains("Passphrase:    set"));
+        assert!(text.contains("/tmp/handsoff/config.toml"));
+    }
+
+    #[test]
+    fn test_passphrase_dialog_hides_input() {
+        let script = passphrase_dialog_script("New passphrase:");
+        assert!(script.starts_with("text returned of (display dialog \"New passphrase:\""));
+        assert!(script.contains("with hidden answer"));
+        assert!(script.contains("cancel button \"Cancel\""));
+    }
+
+    #[test]
+    fn test_confirm_dialog_defaults_to_cancel() {
+        let script = confirm_dialog_script("Disable HandsOff?", "Disable");
+        assert!(script.contains("buttons {\"Cancel\", \"Disable\"}"));
+        assert!(script.contains("default button \"Cancel\" cancel button \"Cancel\""));
+    }
+
+    #[test]
+    fn test_confirm_guard_should_prompt() {
+        let guard = ConfirmGuard::default();
+        assert!(!guard.should_prompt(GuardedAction::Disable), "Off by default");
+        assert!(!guard.should_prompt(GuardedAction::Reset));
+
+        let guard = ConfirmGuard { disable: true, reset: false };
+        assert!(guard.should_prompt(GuardedAction::Disable));
+        assert!(!guard.should_prompt(GuardedAction::Reset));
+    }
+
+    #[test]
+    fn test_confirm_guard_honours_cancel() {
+        let guard = ConfirmGuard { disable: true, reset: true };
+        let mut ran = false;
+        assert!(!guard.run(GuardedAction::Disable, |_| false, || ran = true));
+        assert!(!ran, "Cancelled action must not run");
+
+        assert!(guard.run(GuardedAction::Reset, |_| true, || ran = true));
+        assert!(ran);
+
+        // Without a guard the dialog is never shown
+        let mut ran = false;
+        let unguarded = ConfirmGuard::default();
+        let ask = |_| unreachable!("No prompt without the guard");
+        assert!(unguarded.run(GuardedAction::Disable, ask, || ran = true));
+        assert!(ran);
+    }
+}
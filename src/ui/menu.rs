@@ -0,0 +1,141 @@
+//! Which tray menu items can be clicked in each app state, and the texts shown
+//! in the menu bar
+//!
+//! While locked, mouse clicks are blocked, so the menu is only reachable when input
+//! is unlocked or the event tap is gone (permissions lost).
+
+/// Enabled state of the tray menu items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuState {
+    pub lock: bool,
+    pub disable: bool,
+    pub pause: bool,
+    pub snooze: bool,
+    pub change_passphrase: bool,
+    pub quit: bool,
+}
+
+impl MenuState {
+    /// Menu item states for the current permissions, lock and disabled state
+    pub fn new(has_permissions: bool, is_locked: bool, is_disabled: bool) -> Self {
+        // Lock/Disable/Pause/Snooze only make sense while HandsOff can block and isn't already locked
+        let can_lock = has_permissions && !is_locked && !is_disabled;
+        Self {
+            lock: can_lock,
+            disable: can_lock,
+            pause: can_lock,
+            snooze: can_lock,
+            // Needs the menu reachable and the old passphrase no longer in use
+            change_passphrase: !is_locked,
+            // Never quit into a stuck state: while locked, only once permissions are gone
+            // (input is no longer blocked then)
+            quit: !is_locked || !has_permissions,
+        }
+    }
+}
+
+/// Format duration in human-readable form (e.g., "2m 30s" or "45s")
+pub fn format_duration(seconds: u64) -> String {
+    if seconds >= 60 {
+        let mins = seconds / 60;
+        let secs = seconds % 60;
+        if secs > 0 {
+            format!("{}m {}s", mins, secs)
+        } else {
+            format!("{}m", mins)
+        }
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Format a count with thousands separators, e.g. "1,204"
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Format a duration as a compact clock, e.g. "2:05" or "1:02:05"
+pub fn format_clock(seconds: u64) -> String {
+    let (hours, mins, secs) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, mins, secs)
+    } else {
+        format!("{}:{:02}", mins, secs)
+    }
+}
+
+/// Countdown shown next to the tray icon: "🔒 1:23" until auto-unlock while locked,
+/// "🔓 1:23" until auto-lock while unlocked (None = no countdown running)
+pub fn countdown_title(is_locked: bool, remaining_secs: Option<u64>) -> Option<String> {
+    let icon = if is_locked { "🔒" } else { "🔓" };
+    remaining_secs.map(|secs| format!("{} {}", icon, format_clock(secs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_when_unlocked() {
+        let state = MenuState::new(true, false, false);
+        assert!(state.lock && state.disable && state.pause && state.snooze && state.quit);
+        assert!(state.change_passphrase);
+    }
+
+    #[test]
+    fn test_quit_disabled_while_locked() {
+        let state = MenuState::new(true, true, false);
+        assert!(!state.quit && !state.change_passphrase);
+        assert!(!state.lock && !state.disable && !state.pause && !state.snooze);
+    }
+
+    #[test]
+    fn test_quit_allowed_once_permissions_are_gone() {
+        let state = MenuState::new(false, true, false);
+        assert!(state.quit);
+        assert!(!state.change_passphrase, "Never while locked");
+        assert!(!state.lock && !state.disable);
+
+        let state = MenuState::new(false, false, false);
+        assert!(state.quit && !state.lock);
+    }
+
+    #[test]
+    fn test_quit_allowed_while_disabled() {
+        let state = MenuState::new(true, false, true);
+        assert!(state.quit && state.change_passphrase);
+        assert!(!state.lock && !state.disable && !state.pause && !state.snooze);
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1204), "1,204");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(60), "1m");
+        assert_eq!(format_duration(125), "2m 5s");
+    }
+
+    #[test]
+    fn test_countdown_title() {
+        assert_eq!(countdown_title(true, Some(83)).as_deref(), Some("🔒 1:23"));
+        assert_eq!(countdown_title(false, Some(5)).as_deref(), Some("🔓 0:05"));
+        assert_eq!(countdown_title(false, Some(3725)).as_deref(), Some("🔓 1:02:05"));
+        assert_eq!(countdown_title(true, None), None);
+    }
+}
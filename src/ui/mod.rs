@@ -0,0 +1,9 @@
+pub mod app_activation;
+pub mod dialogs;
+pub mod menu;
+pub mod notify;
+pub mod overlay;
+pub mod sleep_wake;
+pub mod sound;
+
+pub use notify::notify;
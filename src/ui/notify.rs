@@ -0,0 +1,144 @@
+//! Desktop notifications, gated by the `notifications_enabled` and
+//! `notification_timeout_ms` config settings
+//!
+//! Settings are process-wide (background threads notify too), set once at startup
+//! with `configure`. Critical notifications - the ones explaining that input was
+//! unlocked and blocking stopped - are shown even when notifications are off.
+
+use crate::constants::{
+    NOTIFICATION_CRITICAL_TIMEOUT_MS, NOTIFICATION_ERROR_TIMEOUT_MS, NOTIFICATION_TIMEOUT_MS,
+};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+static TIMEOUT_MS: AtomicU32 = AtomicU32::new(NOTIFICATION_TIMEOUT_MS);
+
+/// How important a notification is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// State changes (locked, paused, ...) - shown for the configured timeout
+    Info,
+    /// Failures and recoveries worth reading - shown at least NOTIFICATION_ERROR_TIMEOUT_MS
+    Important,
+    /// Emergency unlocks - always shown, at least NOTIFICATION_CRITICAL_TIMEOUT_MS
+    Critical,
+}
+
+/// Notification settings in effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Display time for informational notifications
+    pub timeout_ms: u32,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_ms: NOTIFICATION_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Apply the notification settings for this process
+pub fn configure(settings: NotificationSettings) {
+    ENABLED.store(settings.enabled, Ordering::Relaxed);
+    TIMEOUT_MS.store(settings.timeout_ms, Ordering::Relaxed);
+}
+
+/// Current notification settings
+pub fn settings() -> NotificationSettings {
+    NotificationSettings {
+        enabled: ENABLED.load(Ordering::Relaxed),
+        timeout_ms: TIMEOUT_MS.load(Ordering::Relaxed),
+    }
+}
+
+/// How long to show a notification of `level` (None = suppressed)
+pub fn display_timeout_ms(settings: NotificationSettings, level: Level) -> Option<u32> {
+    match level {
+        Level::Info if settings.enabled => Some(settings.timeout_ms),
+        Level::Important if settings.enabled => {
+            Some(settings.timeout_ms.max(NOTIFICATION_ERROR_TIMEOUT_MS))
+        }
+        Level::Critical => Some(settings.timeout_ms.max(NOTIFICATION_CRITICAL_TIMEOUT_MS)),
+        Level::Info | Level::Important => None,
+    }
+}
+
+/// Show an informational notification
+pub fn notify(summary: &str, body: &str) {
+    notify_with(Level::Info, summary, body);
+}
+
+/// Show a notification of the given level
+pub fn notify_with(level: Level, summary: &str, body: &str) {
+    if let Some(timeout_ms) = display_timeout_ms(settings(), level) {
+        show(summary, body, timeout_ms);
+    }
+}
+
+/// Show an informational notification for a fixed time (e.g. until a countdown ends)
+pub fn notify_for(summary: &str, body: &str, duration: Duration) {
+    if settings().enabled {
+        show(summary, body, duration.as_millis().min(u32::MAX as u128) as u32);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn show(summary: &str, body: &str, timeout_ms: u32) {
+    let _ = notify_rust::Notification::new()
+        .summary(summary)
+        .body(body)
+        .timeout(notify_rust::Timeout::Milliseconds(timeout_ms))
+        .show();
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show(_summary: &str, _body: &str, _timeout_ms: u32) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_notifications_use_configured_timeout() {
+        let settings = NotificationSettings {
+            enabled: true,
+            timeout_ms: 2000,
+        };
+        assert_eq!(display_timeout_ms(settings, Level::Info), Some(2000));
+        assert_eq!(
+            display_timeout_ms(settings, Level::Important),
+            Some(NOTIFICATION_ERROR_TIMEOUT_MS)
+        );
+        assert_eq!(
+            display_timeout_ms(settings, Level::Critical),
+            Some(NOTIFICATION_CRITICAL_TIMEOUT_MS)
+        );
+
+        // A longer configured timeout applies to every level
+        let settings = NotificationSettings {
+            enabled: true,
+            timeout_ms: 20000,
+        };
+        assert_eq!(display_timeout_ms(settings, Level::Important), Some(20000));
+        assert_eq!(display_timeout_ms(settings, Level::Critical), Some(20000));
+    }
+
+    #[test]
+    fn test_disabled_notifications_only_show_critical() {
+        let settings = NotificationSettings {
+            enabled: false,
+            ..NotificationSettings::default()
+        };
+        assert_eq!(display_timeout_ms(settings, Level::Info), None);
+        assert_eq!(display_timeout_ms(settings, Level::Important), None);
+        assert_eq!(
+            display_timeout_ms(settings, Level::Critical),
+            Some(NOTIFICATION_CRITICAL_TIMEOUT_MS)
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Click-through overlay window shown while input is locked
+//!
+//! A borderless, semi-transparent `NSWindow` covering the main display with a short
+//! "locked" message, one masked dot per typed passphrase character (or the cooldown
+//! after a wrong attempt, or the hold-to-unlock progress) and the auto-unlock
+//! countdown, so anyone looking at the screen (including a remote viewer) can tell
+//! input is locked. The window ignores mouse events and never becomes key, so it
+//! doesn't swallow input itself - blocking stays the event tap's job.
+//!
+//! All methods must be called from the main thread (AppKit requirement); the tray
+//! app drives the overlay from its event loop.
+
+use core_foundation::base::TCFType;
+use core_foundation::string::CFString;
+use core_graphics::display::CGDisplay;
+use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+use std::ffi::{c_char, c_void};
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+
+/// Message shown in the middle of the overlay
+const TITLE_TEXT: &str = "🔒 HandsOff — type passphrase to unlock";
+
+/// Character shown per typed passphrase character
+const MASK_CHAR: char = '•';
+
+/// Segments of the hold-to-unlock progress bar
+const HOLD_PROGRESS_STEPS: usize = 10;
+const HOLD_PROGRESS_FILLED: char = '▰';
+const HOLD_PROGRESS_EMPTY: char = '▱';
+
+/// NSWindowStyleMaskBorderless
+const STYLE_MASK_BORDERLESS: u64 = 0;
+
+/// NSBackingStoreBuffered
+const BACKING_STORE_BUFFERED: u64 = 2;
+
+/// NSScreenSaverWindowLevel (above full-screen apps and the menu bar)
+const SCREEN_SAVER_WINDOW_LEVEL: i64 = 1000;
+
+/// NSWindowCollectionBehavior: CanJoinAllSpaces | Stationary | IgnoresCycle | FullScreenAuxiliary
+const COLLECTION_BEHAVIOR: u64 = (1 << 0) | (1 << 4) | (1 << 6) | (1 << 8);
+
+/// NSTextAlignmentCenter (Apple Silicon uses the iOS enum values)
+#[cfg(target_arch = "aarch64")]
+const TEXT_ALIGNMENT_CENTER: i64 = 1;
+#[cfg(not(target_arch = "aarch64"))]
+const TEXT_ALIGNMENT_CENTER: i64 = 2;
+
+/// Overlay background opacity (0.0-1.0)
+const BACKGROUND_ALPHA: f64 = 0.35;
+
+const TITLE_FONT_SIZE: f64 = 42.0;
+const DETAIL_FONT_SIZE: f64 = 24.0;
+const LABEL_HEIGHT: f64 = 60.0;
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+}
+
+// Ensure NSWindow/NSTextField are registered with the Objective-C runtime
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+unsafe fn sel(name: &[u8]) -> Sel {
+    sel_registerName(name.as_ptr() as *const c_char)
+}
+
+unsafe fn class(name: &[u8]) -> Id {
+    objc_getClass(name.as_ptr() as *const c_char)
+}
+
+/// Send a message taking no arguments and returning an object
+unsafe fn send(obj: Id, selector: &[u8]) -> Id {
+    let msg: unsafe extern "C" fn(Id, Sel) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    msg(obj, sel(selector))
+}
+
+/// Send a message taking a single object argument
+unsafe fn send_id(obj: Id, selector: &[u8], arg: Id) -> Id {
+    let msg: unsafe extern "C" fn(Id, Sel, Id) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    msg(obj, sel(selector), arg)
+}
+
+/// Send a message taking a single BOOL argument
+unsafe fn send_bool(obj: Id, selector: &[u8], arg: bool) {
+    let msg: unsafe extern "C" fn(Id, Sel, u8) =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    msg(obj, sel(selector), arg as u8)
+}
+
+/// Send a message taking a single integer argument
+unsafe fn send_i64(obj: Id, selector: &[u8], arg: i64) {
+    let msg: unsafe extern "C" fn(Id, Sel, i64) =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    msg(obj, sel(selector), arg)
+}
+
+/// Send a message taking a single CGFloat argument and returning an object
+unsafe fn send_f64(obj: Id, selector: &[u8], arg: f64) -> Id {
+    let msg: unsafe extern "C" fn(Id, Sel, f64) -> Id =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    msg(obj, sel(selector), arg)
+}
+
+/// Send a message taking a single NSRect argument
+unsafe fn send_rect(obj: Id, selector: &[u8], rect: CGRect) {
+    let msg: unsafe extern "C" fn(Id, Sel, CGRect) =
+        std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+    msg(obj, sel(selector), rect)
+}
+
+/// Set an NSTextField's string value (CFStringRef is toll-free bridged with NSString)
+unsafe fn set_label_text(label: Id, text: &str) {
+    let text = CFString::new(text);
+    send_id(
+        label,
+        b"setStringValue:\0",
+        text.as_concrete_TypeRef() as Id,
+    );
+}
+
+/// Create a centered, non-editable white label
+unsafe fn create_label(frame: CGRect, font_size: f64, bold: bool) -> Id {
+    let empty = CFString::new("");
+    let label = send_id(
+        class(b"NSTextField\0"),
+        b"labelWithString:\0",
+        empty.as_concrete_TypeRef() as Id,
+    );
+    if label.is_null() {
+        return label;
+    }
+
+    let font_selector: &[u8] = if bold {
+        b"boldSystemFontOfSize:\0"
+    } else {
+        b"systemFontOfSize:\0"
+    };
+    send_id(label, b"setFont:\0", send_f64(class(b"NSFont\0"), font_selector, font_size));
+    send_id(label, b"setTextColor:\0", send(class(b"NSColor\0"), b"whiteColor\0"));
+    send_i64(label, b"setAlignment:\0", TEXT_ALIGNMENT_CENTER);
+    send_rect(label, b"setFrame:\0", frame);
+    label
+}
+
+/// Mask the typed passphrase: one dot per character, never the characters themselves
+pub fn masked_text(len: usize) -> String {
+    MASK_CHAR.to_string().repeat(len)
+}
+
+/// Format the overlay's passphrase line: masked typing, or the cooldown after a wrong attempt
+pub fn typed_text(len: usize, cooldown_remaining: Option<u64>) -> String {
+    match cooldown_remaining {
+        Some(secs) => format!("Wrong passphrase - try again in {}s", secs),
+        None => masked_text(len),
+    }
+}
+
+/// Format the overlay's passphrase line while the hold-to-unlock key is held
+pub fn hold_progress_text(progress: f64) -> String {
+    let filled = (progress.clamp(0.0, 1.0) * HOLD_PROGRESS_STEPS as f64).round() as usize;
+    format!(
+        "Hold to unlock {}{}",
+        HOLD_PROGRESS_FILLED.to_string().repeat(filled),
+        HOLD_PROGRESS_EMPTY.to_string().repeat(HOLD_PROGRESS_STEPS - filled)
+    )
+}
+
+/// Format the overlay's countdown line
+pub fn countdown_text(auto_unlock_remaining: Option<u64>) -> String {
+    match auto_unlock_remaining {
+        Some(0) => "Auto-unlocking...".to_string(),
+        Some(secs) if secs >= 60 => format!("Auto-unlock in {}m {:02}s", secs / 60, secs % 60),
+        Some(secs) => format!("Auto-unlock in {}s", secs),
+        None => String::new(),
+    }
+}
+
+/// Lock overlay window (main thread only)
+pub struct LockOverlay {
+    window: Id,
+    typed_label: Id,
+    countdown_label: Id,
+    typed: String,
+    countdown: String,
+    visible: bool,
+}
+
+impl LockOverlay {
+    /// Create the (hidden) overlay covering the main display
+    ///
+    /// Returns None if AppKit objects could not be created.
+    pub fn new() -> Option<Self> {
+        let bounds = CGDisplay::main().bounds();
+        let frame = CGRect::new(&CGPoint::new(0.0, 0.0), &bounds.size);
+
+        unsafe {
+            let window = send(class(b"NSWindow\0"), b"alloc\0");
+            let init: unsafe extern "C" fn(Id, Sel, CGRect, u64, u64, u8) -> Id =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            let window = init(
+                window,
+                sel(b"initWithContentRect:styleMask:backing:defer:\0"),
+                frame,
+                STYLE_MASK_BORDERLESS,
+                BACKING_STORE_BUFFERED,
+                0,
+            );
+            if window.is_null() {
+                return None;
+            }
+
+            send_bool(window, b"setReleasedWhenClosed:\0", false);
+            send_bool(window, b"setOpaque:\0", false);
+            send_bool(window, b"setHasShadow:\0", false);
+            // Click-through: the overlay must never take input itself
+            send_bool(window, b"setIgnoresMouseEvents:\0", true);
+            send_i64(window, b"setLevel:\0", SCREEN_SAVER_WINDOW_LEVEL);
+            send_i64(window, b"setCollectionBehavior:\0", COLLECTION_BEHAVIOR as i64);
+
+            let color_msg: unsafe extern "C" fn(Id, Sel, f64, f64) -> Id =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            let background = color_msg(
+                class(b"NSColor\0"),
+                sel(b"colorWithCalibratedWhite:alpha:\0"),
+                0.0,
+                BACKGROUND_ALPHA,
+            );
+            send_id(window, b"setBackgroundColor:\0", background);
+
+            let content = send(window, b"contentView\0");
+            let center_y = frame.size.height / 2.0;
+
+            let title_label = create_label(
+                CGRect::new(
+                    &CGPoint::new(0.0, center_y),
+                    &CGSize::new(frame.size.width, LABEL_HEIGHT),
+                ),
+                TITLE_FONT_SIZE,
+                true,
+            );
+            let typed_label = create_label(
+                CGRect::new(
+                    &CGPoint::new(0.0, center_y - LABEL_HEIGHT),
+                    &CGSize::new(frame.size.width, LABEL_HEIGHT),
+                ),
+                TITLE_FONT_SIZE,
+                true,
+            );
+            let countdown_label = create_label(
+                CGRect::new(
+                    &CGPoint::new(0.0, center_y - 2.0 * LABEL_HEIGHT),
+                    &CGSize::new(frame.size.width, LABEL_HEIGHT),
+                ),
+                DETAIL_FONT_SIZE,
+                false,
+            );
+            if content.is_null()
+                || title_label.is_null()
+                || typed_label.is_null()
+                || countdown_label.is_null()
+            {
+                send(window, b"release\0");
+                return None;
+            }
+
+            set_label_text(title_label, TITLE_TEXT);
+            send_id(content, b"addSubview:\0", title_label);
+            send_id(content, b"addSubview:\0", typed_label);
+            send_id(content, b"addSubview:\0", countdown_label);
+
+            Some(Self {
+                window,
+                typed_label,
+                countdown_label,
+                typed: String::new(),
+                countdown: String::new(),
+                visible: false,
+            })
+        }
+    }
+
+    /// Show the overlay without activating the app or taking focus
+    pub fn show(&mut self) {
+        if !self.visible {
+            unsafe {
+                send(self.window, b"orderFrontRegardless\0");
+            }
+            self.visible = true;
+        }
+    }
+
+    /// Hide the overlay
+    pub fn hide(&mut self) {
+        if self.visible {
+            unsafe {
+                send_id(self.window, b"orderOut:\0", std::ptr::null_mut());
+            }
+            self.visible = false;
+        }
+    }
+
+    /// Whether the overlay is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Update the passphrase line (see `typed_text`; redraws only when the text changes)
+    pub fn set_typed(&mut self, text: &str) {
+        if self.typed != text {
+            unsafe {
+                set_label_text(self.typed_label, text);
+            }
+            self.typed = text.to_string();
+        }
+    }
+
+    /// Update the countdown line (redraws only when the text changes)
+    pub fn set_countdown(&mut self, text: &str) {
+        if self.countdown != text {
+            unsafe {
+                set_label_text(self.countdown_label, text);
+            }
+            self.countdown = text.to_string();
+        }
+    }
+}
+
+impl Drop for LockOverlay {
+    fn drop(&mut self) {
+        unsafe {
+            send(self.window, b"close\0");
+            send(self.window, b"release\0");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_text() {
+        assert_eq!(masked_text(0), "");
+        assert_eq!(masked_text(4), "••••");
+    }
+
+    #[test]
+    fn test_typed_text() {
+        assert_eq!(typed_text(3, None), "•••");
+        assert_eq!(typed_text(0, Some(4)), "Wrong passphrase - try again in 4s");
+    }
+
+    #[test]
+    fn test_hold_progress_text() {
+        assert_eq!(hold_progress_text(0.0), "Hold to unlock ▱▱▱▱▱▱▱▱▱▱");
+        assert_eq!(hold_progress_text(0.5), "Hold to unlock ▰▰▰▰▰▱▱▱▱▱");
+        assert_eq!(hold_progress_text(1.0), "Hold to unlock ▰▰▰▰▰▰▰▰▰▰");
+    }
+
+    #[test]
+    fn test_countdown_text() {
+        assert_eq!(countdown_text(None), "");
+        assert_eq!(countdown_text(Some(0)), "Auto-unlocking...");
+        assert_eq!(countdown_text(Some(42)), "Auto-unlock in 42s");
+        assert_eq!(countdown_text(Some(125)), "Auto-unlock in 2m 05s");
+    }
+}
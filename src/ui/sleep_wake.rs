@@ -0,0 +1,312 @@
+//! Sleep/wake and screen observer: optionally lock input when the user walks away
+//!
+//! Registers an Objective-C observer with `NSWorkspace`'s notification center for
+//! `NSWorkspaceWillSleepNotification`, `NSWorkspaceDidWakeNotification` and
+//! `NSWorkspaceScreensDidSleepNotification` (display off), and with the distributed
+//! notification center for `com.apple.screensaver.didstart`. Configured triggers lock
+//! input (when HandsOff is enabled and has accessibility permissions):
+//!
+//! - `lock_on_wake = true`: lock when the Mac wakes, so an unattended wake is protected
+//! - `lock_on_screensaver = true`: lock when the screen saver starts or the display
+//!   turns off, complementing auto-lock by inactivity
+//!
+//! Notifications are delivered on the main thread, so `install` must be called from
+//! the tray app's main thread before its event loop runs.
+
+use crate::app_state::AppState;
+use anyhow::{anyhow, Result};
+use core_foundation::base::TCFType;
+use core_foundation::string::CFString;
+use log::{info, warn};
+use std::ffi::{c_char, c_void};
+use std::sync::{Arc, OnceLock};
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+
+/// Distributed notification posted when the screen saver starts
+const SCREENSAVER_DID_START: &str = "com.apple.screensaver.didstart";
+
+/// Sleep/wake or screen notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    WillSleep,
+    DidWake,
+    ScreensaverStarted,
+    DisplaySlept,
+}
+
+/// Which events lock input (from the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LockTriggers {
+    /// `lock_on_wake`
+    pub on_wake: bool,
+    /// `lock_on_screensaver` (screen saver start or display off)
+    pub on_screensaver: bool,
+}
+
+/// What to do in response to a `PowerEvent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    /// Lock input
+    Lock,
+    /// Nothing to do
+    Ignore,
+}
+
+/// Decide how to respond to a sleep/wake or screen event
+///
+/// Locks only for a configured trigger, and only while HandsOff is enabled, unlocked
+/// and able to lock (see `AppState::can_lock`).
+/// Going to sleep never locks by itself.
+pub fn action_for(event: PowerEvent, triggers: LockTriggers, state: &AppState) -> PowerAction {
+    let triggered = match event {
+        PowerEvent::WillSleep => false,
+        PowerEvent::DidWake => triggers.on_wake,
+        PowerEvent::ScreensaverStarted | PowerEvent::DisplaySlept => triggers.on_screensaver,
+    };
+    let can_lock = !state.is_locked() && !state.is_disabled() && state.can_lock().is_ok();
+    if triggered && can_lock {
+        PowerAction::Lock
+    } else {
+        PowerAction::Ignore
+    }
+}
+
+/// Shared with the observer callbacks (set once by `install`)
+struct Observed {
+    state: Arc<AppState>,
+    triggers: LockTriggers,
+}
+
+static OBSERVED: OnceLock<Observed> = OnceLock::new();
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn objc_allocateClassPair(superclass: Id, name: *const c_char, extra_bytes: usize) -> Id;
+    fn objc_registerClassPair(class: Id);
+    fn class_addMethod(class: Id, name: Sel, imp: *const c_void, types: *const c_char) -> u8;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+}
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    static NSWorkspaceWillSleepNotification: Id;
+    static NSWorkspaceDidWakeNotification: Id;
+    static NSWorkspaceScreensDidSleepNotification: Id;
+}
+
+/// Observer method signature (`- (void)method:(NSNotification *)notification`)
+type ObserverMethod = unsafe extern "C" fn(Id, Sel, Id);
+
+/// Objective-C type encoding of `ObserverMethod`
+const OBSERVER_METHOD_TYPES: &std::ffi::CStr = c"v@:@";
+
+unsafe extern "C" fn will_sleep(_this: Id, _cmd: Sel, _notification: Id) {
+    handle(PowerEvent::WillSleep);
+}
+
+unsafe extern "C" fn did_wake(_this: Id, _cmd: Sel, _notification: Id) {
+    handle(PowerEvent::DidWake);
+}
+
+unsafe extern "C" fn screensaver_started(_this: Id, _cmd: Sel, _notification: Id) {
+    handle(PowerEvent::ScreensaverStarted);
+}
+
+unsafe extern "C" fn display_slept(_this: Id, _cmd: Sel, _notification: Id) {
+    handle(PowerEvent::DisplaySlept);
+}
+
+fn handle(event: PowerEvent) {
+    let Some(observed) = OBSERVED.get() else {
+        return;
+    };
+    info!("System power event: {:?}", event);
+    if action_for(event, observed.triggers, &observed.state) == PowerAction::Lock {
+        match observed.state.try_lock() {
+            Ok(()) => info!("Locking input after {:?}", event),
+            Err(refused) => warn!("Not locking after {:?} - {}", event, refused),
+        }
+    }
+}
+
+/// Register the sleep/wake and screen observer (main thread, once)
+///
+/// Events are always logged; input is only locked for the configured `triggers`.
+pub fn install(state: Arc<AppState>, triggers: LockTriggers) -> Result<()> {
+    if OBSERVED.set(Observed { state, triggers }).is_err() {
+        return Err(anyhow!("Sleep/wake observer already installed"));
+    }
+
+    unsafe {
+        let class = objc_allocateClassPair(
+            objc_getClass(c"NSObject".as_ptr()),
+            c"HandsOffSleepWakeObserver".as_ptr(),
+            0,
+        );
+        if class.is_null() {
+            return Err(anyhow!("Failed to create sleep/wake observer class"));
+        }
+        let methods: [(&std::ffi::CStr, ObserverMethod); 4] = [
+            (c"willSleep:", will_sleep),
+            (c"didWake:", did_wake),
+            (c"screensaverStarted:", screensaver_started),
+            (c"displaySlept:", display_slept),
+        ];
+        for (name, imp) in methods {
+            class_addMethod(
+                class,
+                sel_registerName(name.as_ptr()),
+                imp as *const c_void,
+                OBSERVER_METHOD_TYPES.as_ptr(),
+            );
+        }
+        objc_registerClassPair(class);
+
+        let send: unsafe extern "C" fn(Id, Sel) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        // Lives for the rest of the process, like the notification center's reference
+        let observer = send(
+            send(class, sel_registerName(c"alloc".as_ptr())),
+            sel_registerName(c"init".as_ptr()),
+        );
+        let workspace = send(
+            objc_getClass(c"NSWorkspace".as_ptr()),
+            sel_registerName(c"sharedWorkspace".as_ptr()),
+        );
+        let center = send(workspace, sel_registerName(c"notificationCenter".as_ptr()));
+        if observer.is_null() || center.is_null() {
+            return Err(anyhow!("Failed to access the NSWorkspace notification center"));
+        }
+
+        let add_observer: unsafe extern "C" fn(Id, Sel, Id, Sel, Id, Id) =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let selector = sel_registerName(c"addObserver:selector:name:object:".as_ptr());
+        add_observer(
+            center,
+            selector,
+            observer,
+            sel_registerName(c"willSleep:".as_ptr()),
+            NSWorkspaceWillSleepNotification,
+            std::ptr::null_mut(),
+        );
+        add_observer(
+            center,
+            selector,
+            observer,
+            sel_registerName(c"didWake:".as_ptr()),
+            NSWorkspaceDidWakeNotification,
+            std::ptr::null_mut(),
+        );
+        add_observer(
+            center,
+            selector,
+            observer,
+            sel_registerName(c"displaySlept:".as_ptr()),
+            NSWorkspaceScreensDidSleepNotification,
+            std::ptr::null_mut(),
+        );
+
+        // The screen saver announces itself on the distributed notification center
+        let distributed = send(
+            objc_getClass(c"NSDistributedNotificationCenter".as_ptr()),
+            sel_registerName(c"defaultCenter".as_ptr()),
+        );
+        if distributed.is_null() {
+            return Err(anyhow!("Failed to access the distributed notification center"));
+        }
+        // CFStringRef is toll-free bridged with NSString; the center keeps its own copy
+        let name = CFString::new(SCREENSAVER_DID_START);
+        add_observer(
+            distributed,
+            selector,
+            observer,
+            sel_registerName(c"screensaverStarted:".as_ptr()),
+            name.as_concrete_TypeRef() as Id,
+            std::ptr::null_mut(),
+        );
+    }
+
+    info!(
+        "Sleep/wake and screen observer installed (lock on wake: {}, lock on screensaver: {})",
+        triggers.on_wake, triggers.on_screensaver
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ON_WAKE: LockTriggers = LockTriggers {
+        on_wake: true,
+        on_screensaver: false,
+    };
+    const ON_SCREENSAVER: LockTriggers = LockTriggers {
+        on_wake: false,
+        on_screensaver: true,
+    };
+    const ALL: LockTriggers = LockTriggers {
+        on_wake: true,
+        on_screensaver: true,
+    };
+
+    fn enabled_state() -> AppState {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        state.set_passphrase_hash(crate::auth::hash_passphrase("secret"));
+        state
+    }
+
+    #[test]
+    fn test_wake_locks_only_when_configured() {
+        let state = enabled_state();
+        assert_eq!(action_for(PowerEvent::DidWake, ON_WAKE, &state), PowerAction::Lock);
+        assert_eq!(
+            action_for(PowerEvent::DidWake, LockTriggers::default(), &state),
+            PowerAction::Ignore
+        );
+        assert_eq!(action_for(PowerEvent::DidWake, ON_SCREENSAVER, &state), PowerAction::Ignore);
+        assert_eq!(
+            action_for(PowerEvent::WillSleep, ALL, &state),
+            PowerAction::Ignore,
+            "Going to sleep never locks"
+        );
+    }
+
+    #[test]
+    fn test_screensaver_and_display_off_lock_when_configured() {
+        let state = enabled_state();
+        for event in [PowerEvent::ScreensaverStarted, PowerEvent::DisplaySlept] {
+            assert_eq!(action_for(event, ON_SCREENSAVER, &state), PowerAction::Lock);
+            assert_eq!(action_for(event, ON_WAKE, &state), PowerAction::Ignore);
+        }
+    }
+
+    #[test]
+    fn test_ignored_when_locked_disabled_or_without_permissions() {
+        let state = enabled_state();
+        state.set_locked(true);
+        assert_eq!(action_for(PowerEvent::DidWake, ALL, &state), PowerAction::Ignore);
+
+        let state = enabled_state();
+        state.set_disabled(true);
+        assert_eq!(action_for(PowerEvent::ScreensaverStarted, ALL, &state), PowerAction::Ignore);
+
+        let state = enabled_state();
+        state.set_cached_accessibility_permissions(false);
+        assert_eq!(action_for(PowerEvent::DisplaySlept, ALL, &state), PowerAction::Ignore);
+    }
+
+    #[test]
+    fn test_ignored_without_passphrase() {
+        let state = AppState::new();
+        state.set_cached_accessibility_permissions(true);
+        for event in [PowerEvent::DidWake, PowerEvent::ScreensaverStarted] {
+            assert_eq!(action_for(event, ALL, &state), PowerAction::Ignore);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Short audio cues (system alert sound)
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    fn NSBeep();
+}
+
+/// Play the system alert sound (safe from any thread)
+pub fn beep() {
+    unsafe { NSBeep() }
+}
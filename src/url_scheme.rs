@@ -0,0 +1,93 @@
+//! `handsoff://` URLs for Shortcuts and other automation
+//!
+//! The tray app registers the `handsoff` URL scheme, so `open handsoff://lock` (or an
+//! "Open URLs" action in Shortcuts) controls it:
+//!
+//! - `handsoff://lock`: lock input
+//! - `handsoff://disable`: disable HandsOff
+//! - `handsoff://snooze?min=30`: snooze auto-lock for 30 minutes
+//!
+//! There is deliberately no unlock URL: any app can open a URL, and a passphrase in
+//! a URL would end up in shell history and Shortcuts.
+
+use crate::constants::URL_SNOOZE_MAX_MINUTES;
+
+/// URL scheme registered by the tray app
+pub const URL_SCHEME: &str = "handsoff";
+
+/// Action requested by a `handsoff://` URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Lock,
+    Disable,
+    Snooze { minutes: u64 },
+}
+
+/// Parse a `handsoff://` URL (None for other schemes, unknown actions or bad parameters)
+pub fn parse_command_url(url: &str) -> Option<Command> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case(URL_SCHEME) {
+        return None;
+    }
+
+    let (action, query) = match rest.split_once('?') {
+        Some((action, query)) => (action, Some(query)),
+        None => (rest, None),
+    };
+    match action.trim_end_matches('/').to_ascii_lowercase().as_str() {
+        "lock" => Some(Command::Lock),
+        "disable" => Some(Command::Disable),
+        "snooze" => {
+            let minutes = query?
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("min="))?
+                .parse::<u64>()
+                .ok()?;
+            (1..=URL_SNOOZE_MAX_MINUTES)
+                .contains(&minutes)
+                .then_some(Command::Snooze { minutes })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lock_and_disable() {
+        assert_eq!(parse_command_url("handsoff://lock"), Some(Command::Lock));
+        assert_eq!(parse_command_url("handsoff://lock/"), Some(Command::Lock));
+        assert_eq!(parse_command_url("HandsOff://LOCK"), Some(Command::Lock));
+        assert_eq!(parse_command_url("handsoff://disable"), Some(Command::Disable));
+    }
+
+    #[test]
+    fn test_parse_snooze() {
+        assert_eq!(
+            parse_command_url("handsoff://snooze?min=30"),
+            Some(Command::Snooze { minutes: 30 })
+        );
+        assert_eq!(
+            parse_command_url("handsoff://snooze/?from=shortcuts&min=5"),
+            Some(Command::Snooze { minutes: 5 })
+        );
+        assert_eq!(parse_command_url("handsoff://snooze"), None, "Duration is required");
+        assert_eq!(parse_command_url("handsoff://snooze?min=abc"), None);
+        assert_eq!(parse_command_url("handsoff://snooze?min=0"), None);
+        assert_eq!(
+            parse_command_url(&format!("handsoff://snooze?min={}", URL_SNOOZE_MAX_MINUTES + 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reject_unlock_and_unknown_urls() {
+        assert_eq!(parse_command_url("handsoff://unlock"), None);
+        assert_eq!(parse_command_url("handsoff://unlock?passphrase=secret"), None);
+        assert_eq!(parse_command_url("handsoff://reboot"), None);
+        assert_eq!(parse_command_url("https://lock"), None);
+        assert_eq!(parse_command_url("handsoff:lock"), None);
+    }
+}
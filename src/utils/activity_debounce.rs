@@ -0,0 +1,119 @@
+//! Activity debounce for the auto-lock timer
+//!
+//! A single stray event (a bumped mouse button, a cat brushing a key) shouldn't reset
+//! a long inactivity timer. With the debounce on, input only counts as activity once
+//! `min_events` events arrive within `window`; isolated ones are ignored. Note that a
+//! click or keystroke is two events (press and release).
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Recent input event timestamps, to tell bursts of activity from stray events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityDebounce {
+    /// Events needed within the window (0 or 1 = every event counts)
+    min_events: u32,
+    window: Duration,
+    /// Timestamps of the latest events, oldest first (at most `min_events`)
+    recent: VecDeque<Instant>,
+}
+
+impl Default for ActivityDebounce {
+    fn default() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+}
+
+impl ActivityDebounce {
+    pub fn new(min_events: u32, window: Duration) -> Self {
+        Self {
+            min_events,
+            window,
+            recent: VecDeque::with_capacity(min_events as usize),
+        }
+    }
+
+    /// Events needed within the window (0 or 1 = off)
+    pub fn min_events(&self) -> u32 {
+        self.min_events
+    }
+
+    /// Window the events have to arrive in
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Record an input event at `now`; returns whether it counts as activity
+    pub fn record(&mut self, now: Instant) -> bool {
+        if self.min_events <= 1 {
+            return true;
+        }
+        while self
+            .recent
+            .front()
+            .is_some_and(|&t| now.saturating_duration_since(t) > self.window)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() == self.min_events as usize {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(now);
+        self.recent.len() == self.min_events as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(t0: Instant, ms: u64) -> Instant {
+        t0 + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_off_counts_every_event() {
+        let t0 = Instant::now();
+        for min_events in [0, 1] {
+            let mut debounce = ActivityDebounce::new(min_events, Duration::from_secs(1));
+            assert!(debounce.record(t0));
+            assert!(debounce.record(at(t0, 5000)));
+        }
+    }
+
+    #[test]
+    fn test_burst_within_window_counts() {
+        let t0 = Instant::now();
+        let mut debounce = ActivityDebounce::new(3, Duration::from_secs(1));
+        assert!(!debounce.record(t0));
+        assert!(!debounce.record(at(t0, 100)));
+        assert!(debounce.record(at(t0, 900)));
+
+        // Further events in a burst keep counting
+        assert!(debounce.record(at(t0, 950)));
+    }
+
+    #[test]
+    fn test_stray_events_are_ignored() {
+        let t0 = Instant::now();
+        let mut debounce = ActivityDebounce::new(3, Duration::from_secs(1));
+
+        // A single click (press and release), then nothing for a while
+        assert!(!debounce.record(t0));
+        assert!(!debounce.record(at(t0, 80)));
+        // The next event is outside the window of both
+        assert!(!debounce.record(at(t0, 1500)));
+        assert!(!debounce.record(at(t0, 1600)));
+        assert!(debounce.record(at(t0, 1700)));
+    }
+
+    #[test]
+    fn test_keeps_at_most_min_events_timestamps() {
+        let t0 = Instant::now();
+        let mut debounce = ActivityDebounce::new(2, Duration::from_secs(10));
+        for ms in 0..100 {
+            debounce.record(at(t0, ms));
+        }
+        assert_eq!(debounce.recent.len(), 2);
+    }
+}
@@ -0,0 +1,39 @@
+//! In-memory stream for testing the control socket and HTTP API connection handlers
+
+use std::io::{Cursor, Read, Result, Write};
+
+/// Reads scripted input, records everything written
+pub struct FakeStream {
+    input: Cursor<Vec<u8>>,
+    pub output: Vec<u8>,
+}
+
+impl FakeStream {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: Cursor::new(input.as_bytes().to_vec()),
+            output: Vec::new(),
+        }
+    }
+
+    /// Everything written so far, as text
+    pub fn output_text(&self) -> String {
+        String::from_utf8(self.output.clone()).expect("Output is UTF-8")
+    }
+}
+
+impl Read for FakeStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for FakeStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
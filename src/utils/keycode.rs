@@ -1,3 +1,540 @@
+use core_foundation::base::{CFRelease, TCFType};
+use core_foundation::data::{CFData, CFDataRef};
+use core_foundation::string::{CFString, CFStringRef};
+use core_graphics::event::CGEventFlags;
+use log::{debug, info};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_void;
+use std::fmt;
+use std::str::FromStr;
+
+type TISInputSourceRef = *const c_void;
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    static kTISPropertyInputSourceID: CFStringRef;
+    static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+
+    fn TISCopyCurrentKeyboardInputSource() -> TISInputSourceRef;
+    fn TISCopyCurrentKeyboardLayoutInputSource() -> TISInputSourceRef;
+    fn TISGetInputSourceProperty(source: TISInputSourceRef, key: CFStringRef) -> *const c_void;
+    fn LMGetKbdType() -> u8;
+    #[allow(clippy::too_many_arguments)]
+    fn UCKeyTranslate(
+        key_layout: *const c_void,
+        virtual_key_code: u16,
+        key_action: u16,
+        modifier_key_state: u32,
+        keyboard_type: u32,
+        key_translate_options: u32,
+        dead_key_state: *mut u32,
+        max_string_length: usize,
+        actual_string_length: *mut usize,
+        unicode_string: *mut u16,
+    ) -> i32;
+}
+
+/// kUCKeyActionDown
+const UC_KEY_ACTION_DOWN: u16 = 0;
+
+/// Carbon modifier bits (Events.h), passed to UCKeyTranslate shifted right by 8
+const CARBON_SHIFT_KEY: u32 = 1 << 9;
+const CARBON_ALPHA_LOCK: u32 = 1 << 10;
+const CARBON_OPTION_KEY: u32 = 1 << 11;
+
+/// Copy of the active input source's keyboard layout, used by the event tap
+///
+/// TIS calls must happen on the main thread on recent macOS, so the layout is
+/// snapshotted by `refresh_keyboard_layout` (called from the main loop) and the event
+/// tap only runs the thread-safe `UCKeyTranslate` against the copy.
+struct LayoutSnapshot {
+    /// Input source ID (e.g. "com.apple.keylayout.French")
+    source_id: String,
+    /// `UCKeyboardLayout` data (u32 storage keeps it suitably aligned)
+    data: Vec<u32>,
+    /// Physical keyboard type (LMGetKbdType)
+    keyboard_type: u32,
+}
+
+static CURRENT_LAYOUT: Mutex<Option<LayoutSnapshot>> = Mutex::new(None);
+
+static DEAD_KEYS: Mutex<DeadKeyState> = Mutex::new(DeadKeyState::new());
+
+/// Dead key pressed but not yet combined with the following keystroke
+#[derive(Debug, Default)]
+struct DeadKeyState {
+    /// UCKeyTranslate dead-key state for the layout snapshot
+    uc_state: u32,
+    /// Dead key pending in the static fallback table
+    pending: Option<DeadKey>,
+}
+
+impl DeadKeyState {
+    const fn new() -> Self {
+        Self {
+            uc_state: 0,
+            pending: None,
+        }
+    }
+}
+
+/// Outcome of translating one keystroke with a layout snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keystroke {
+    Char(char),
+    /// Dead key: the accent applies to the next keystroke
+    DeadKey,
+    /// Non-printing key (Return, Tab, arrows, ...) or translation error
+    NoChar,
+}
+
+/// Snapshot the active keyboard layout for `translate_with_current_layout`
+///
+/// Call from the main thread (TIS requirement). Cheap when the input source hasn't
+/// changed: the layout data is only copied when the source ID differs.
+pub fn refresh_keyboard_layout() {
+    unsafe {
+        let mut source = TISCopyCurrentKeyboardInputSource();
+        if !source.is_null()
+            && TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData).is_null()
+        {
+            // Input methods (e.g. Japanese) carry no layout data; use their keyboard layout
+            CFRelease(source);
+            source = TISCopyCurrentKeyboardLayoutInputSource();
+        }
+        if source.is_null() {
+            debug!("No current keyboard input source - using static keycode table");
+            *CURRENT_LAYOUT.lock() = None;
+            return;
+        }
+
+        update_snapshot(source);
+        CFRelease(source);
+    }
+}
+
+unsafe fn update_snapshot(source: TISInputSourceRef) {
+    let id_ref = TISGetInputSourceProperty(source, kTISPropertyInputSourceID) as CFStringRef;
+    let source_id = if id_ref.is_null() {
+        String::new()
+    } else {
+        CFString::wrap_under_get_rule(id_ref).to_string()
+    };
+
+    let mut current = CURRENT_LAYOUT.lock();
+    if current.as_ref().is_some_and(|snapshot| snapshot.source_id == source_id) {
+        return;
+    }
+
+    let data_ref = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData) as CFDataRef;
+    if data_ref.is_null() {
+        debug!("Input source {} has no keyboard layout data", source_id);
+        *current = None;
+        return;
+    }
+
+    let bytes = CFData::wrap_under_get_rule(data_ref);
+    let bytes = bytes.bytes();
+    let mut data = vec![0u32; bytes.len().div_ceil(4)];
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.as_mut_ptr() as *mut u8, bytes.len());
+
+    info!("Passphrase entry follows keyboard layout {}", source_id);
+    clear_pending_dead_key();
+    *current = Some(LayoutSnapshot {
+        source_id,
+        data,
+        keyboard_type: LMGetKbdType() as u32,
+    });
+}
+
+/// UCKeyTranslate modifier state for the character-producing modifiers in `flags`
+///
+/// Control and Command are left out: they don't change the typed character.
+fn carbon_modifier_state(flags: CGEventFlags) -> u32 {
+    let mut modifiers = 0;
+    if flags.contains(CGEventFlags::CGEventFlagShift) {
+        modifiers |= CARBON_SHIFT_KEY;
+    }
+    if flags.contains(CGEventFlags::CGEventFlagAlphaShift) {
+        modifiers |= CARBON_ALPHA_LOCK;
+    }
+    if flags.contains(CGEventFlags::CGEventFlagAlternate) {
+        modifiers |= CARBON_OPTION_KEY;
+    }
+    (modifiers >> 8) & 0xFF
+}
+
+/// Translate a keycode with a layout snapshot, carrying dead-key state between calls
+///
+/// A dead key followed by a key it doesn't combine with yields just that key's
+/// character; the standalone accent macOS would also type is dropped.
+fn translate_with_snapshot(
+    snapshot: &LayoutSnapshot,
+    keycode: i64,
+    flags: CGEventFlags,
+    dead_key_state: &mut u32,
+) -> Keystroke {
+    let Ok(keycode) = u16::try_from(keycode) else {
+        return Keystroke::NoChar;
+    };
+    let mut chars = [0u16; 4];
+    let mut len = 0usize;
+
+    let status = unsafe {
+        UCKeyTranslate(
+            snapshot.data.as_ptr() as *const c_void,
+            keycode,
+            UC_KEY_ACTION_DOWN,
+            carbon_modifier_state(flags),
+            snapshot.keyboard_type,
+            0,
+            dead_key_state,
+            chars.len(),
+            &mut len,
+            chars.as_mut_ptr(),
+        )
+    };
+    if status != 0 {
+        *dead_key_state = 0;
+        return Keystroke::NoChar;
+    }
+    if len == 0 {
+        return if *dead_key_state != 0 {
+            Keystroke::DeadKey
+        } else {
+            Keystroke::NoChar
+        };
+    }
+
+    match char::decode_utf16(chars[..len.min(chars.len())].iter().copied()).last() {
+        Some(Ok(ch)) if !ch.is_control() => Keystroke::Char(ch),
+        _ => Keystroke::NoChar,
+    }
+}
+
+/// Translate with the snapshot if there is one, otherwise (or if it yields nothing)
+/// with the static table for `fallback`
+fn translate_or_fallback(
+    snapshot: Option<&LayoutSnapshot>,
+    dead_keys: &mut DeadKeyState,
+    keycode: i64,
+    flags: CGEventFlags,
+    fallback: KeyboardLayout,
+) -> Option<char> {
+    if let Some(snapshot) = snapshot {
+        match translate_with_snapshot(snapshot, keycode, flags, &mut dead_keys.uc_state) {
+            Keystroke::Char(ch) => return Some(ch),
+            Keystroke::DeadKey => return None,
+            Keystroke::NoChar => {}
+        }
+    }
+    translate_static(&mut dead_keys.pending, keycode, flags, fallback)
+}
+
+/// Static-table translation, including the US/ABC Option layer and its dead keys
+fn translate_static(
+    pending: &mut Option<DeadKey>,
+    keycode: i64,
+    flags: CGEventFlags,
+    layout: KeyboardLayout,
+) -> Option<char> {
+    let shift = flags.contains(CGEventFlags::CGEventFlagShift);
+    let option =
+        flags.contains(CGEventFlags::CGEventFlagAlternate) && layout == KeyboardLayout::Us;
+
+    if option {
+        if let Some(dead_key) = option_dead_key(keycode, shift) {
+            *pending = Some(dead_key);
+            return None;
+        }
+    }
+
+    let ch = if option {
+        option_keycode_to_char(keycode, shift)
+    } else {
+        None
+    }
+    .or_else(|| {
+        static_char_with_modifiers(
+            layout,
+            keycode,
+            shift,
+            flags.contains(CGEventFlags::CGEventFlagAlphaShift),
+        )
+    });
+
+    match pending.take() {
+        Some(dead_key) => ch.map(|ch| dead_key.compose(ch).unwrap_or(ch)),
+        None => ch,
+    }
+}
+
+/// Static-table character for a key given the Shift and Caps Lock state
+///
+/// Caps Lock only affects letters, which it uppercases (with or without Shift, as on
+/// macOS); digits and symbols follow Shift alone.
+pub fn static_char_with_modifiers(
+    layout: KeyboardLayout,
+    keycode: i64,
+    shift: bool,
+    caps_lock: bool,
+) -> Option<char> {
+    let ch = layout.keycode_to_char(keycode, shift)?;
+    if caps_lock && ch.is_ascii_alphabetic() {
+        Some(ch.to_ascii_uppercase())
+    } else {
+        Some(ch)
+    }
+}
+
+/// Map a keycode to the character the user's active keyboard layout produces
+///
+/// Uses the layout snapshotted by `refresh_keyboard_layout` (Shift, Option and Caps
+/// Lock applied); falls back to the static US table when no snapshot is available or
+/// the key doesn't produce a printable character (Return, Tab, ...).
+pub fn translate_with_current_layout(keycode: i64, flags: CGEventFlags) -> Option<char> {
+    translate_with_current_layout_or(keycode, flags, KeyboardLayout::Us)
+}
+
+/// `translate_with_current_layout` falling back to the static table for `fallback`
+pub fn translate_with_current_layout_or(
+    keycode: i64,
+    flags: CGEventFlags,
+    fallback: KeyboardLayout,
+) -> Option<char> {
+    translate_or_fallback(
+        CURRENT_LAYOUT.lock().as_ref(),
+        &mut DEAD_KEYS.lock(),
+        keycode,
+        flags,
+        fallback,
+    )
+}
+
+/// Forget a dead key waiting for its next keystroke (e.g. when the buffer is cleared)
+pub fn clear_pending_dead_key() {
+    *DEAD_KEYS.lock() = DeadKeyState::new();
+}
+
+/// Option-key accent that modifies the next keystroke (US/ABC layout)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadKey {
+    /// Option+E
+    Acute,
+    /// Option+`
+    Grave,
+    /// Option+I
+    Circumflex,
+    /// Option+N
+    Tilde,
+    /// Option+U
+    Umlaut,
+}
+
+impl DeadKey {
+    /// The accent on its own (typed by the dead key followed by Space)
+    pub fn accent(self) -> char {
+        match self {
+            DeadKey::Acute => '´',
+            DeadKey::Grave => '`',
+            DeadKey::Circumflex => 'ˆ',
+            DeadKey::Tilde => '˜',
+            DeadKey::Umlaut => '¨',
+        }
+    }
+
+    /// Combine the accent with the next typed character (None if they don't combine)
+    pub fn compose(self, base: char) -> Option<char> {
+        if base == ' ' {
+            return Some(self.accent());
+        }
+        let composed = match (self, base.to_ascii_lowercase()) {
+            (DeadKey::Acute, 'a') => 'á',
+            (DeadKey::Acute, 'e') => 'é',
+            (DeadKey::Acute, 'i') => 'í',
+            (DeadKey::Acute, 'o') => 'ó',
+            (DeadKey::Acute, 'u') => 'ú',
+            (DeadKey::Acute, 'y') => 'ý',
+            (DeadKey::Grave, 'a') => 'à',
+            (DeadKey::Grave, 'e') => 'è',
+            (DeadKey::Grave, 'i') => 'ì',
+            (DeadKey::Grave, 'o') => 'ò',
+            (DeadKey::Grave, 'u') => 'ù',
+            (DeadKey::Circumflex, 'a') => 'â',
+            (DeadKey::Circumflex, 'e') => 'ê',
+            (DeadKey::Circumflex, 'i') => 'î',
+            (DeadKey::Circumflex, 'o') => 'ô',
+            (DeadKey::Circumflex, 'u') => 'û',
+            (DeadKey::Tilde, 'a') => 'ã',
+            (DeadKey::Tilde, 'n') => 'ñ',
+            (DeadKey::Tilde, 'o') => 'õ',
+            (DeadKey::Umlaut, 'a') => 'ä',
+            (DeadKey::Umlaut, 'e') => 'ë',
+            (DeadKey::Umlaut, 'i') => 'ï',
+            (DeadKey::Umlaut, 'o') => 'ö',
+            (DeadKey::Umlaut, 'u') => 'ü',
+            (DeadKey::Umlaut, 'y') => 'ÿ',
+            _ => return None,
+        };
+        if base.is_ascii_uppercase() {
+            composed.to_uppercase().next()
+        } else {
+            Some(composed)
+        }
+    }
+}
+
+/// Dead key produced by Option+key on the US/ABC layout (Shift+Option types the
+/// accent itself instead)
+pub fn option_dead_key(keycode: i64, shift: bool) -> Option<DeadKey> {
+    if shift {
+        return None;
+    }
+    match keycode {
+        14 => Some(DeadKey::Acute),
+        50 => Some(DeadKey::Grave),
+        34 => Some(DeadKey::Circumflex),
+        45 => Some(DeadKey::Tilde),
+        32 => Some(DeadKey::Umlaut),
+        _ => None,
+    }
+}
+
+/// US/ABC Option layer: (keycode, Option, Shift+Option)
+///
+/// The unshifted entries for dead keys (E, `, I, N, U) are only reached when the
+/// accent is typed on its own; `option_dead_key` intercepts them first.
+const OPTION_LAYER: &[(i64, char, char)] = &[
+    (0, 'å', 'Å'),
+    (1, 'ß', 'Í'),
+    (2, '∂', 'Î'),
+    (3, 'ƒ', 'Ï'),
+    (4, '˙', 'Ó'),
+    (5, '©', '˝'),
+    (6, 'Ω', '¸'),
+    (7, '≈', '˛'),
+    (8, 'ç', 'Ç'),
+    (9, '√', '◊'),
+    (11, '∫', 'ı'),
+    (12, 'œ', 'Œ'),
+    (13, '∑', '„'),
+    (14, '´', '´'),
+    (15, '®', '‰'),
+    (16, '¥', 'Á'),
+    (17, '†', 'ˇ'),
+    (31, 'ø', 'Ø'),
+    (32, '¨', '¨'),
+    (34, 'ˆ', 'ˆ'),
+    (35, 'π', '∏'),
+    (37, '¬', 'Ò'),
+    (38, '∆', 'Ô'),
+    (40, '˚', '\u{F8FF}'),
+    (45, '˜', '˜'),
+    (46, 'µ', 'Â'),
+    (18, '¡', '⁄'),
+    (19, '™', '€'),
+    (20, '£', '‹'),
+    (21, '¢', '›'),
+    (23, '∞', 'ﬁ'),
+    (22, '§', 'ﬂ'),
+    (26, '¶', '‡'),
+    (28, '•', '°'),
+    (25, 'ª', '·'),
+    (29, 'º', '‚'),
+    (27, '–', '—'),
+    (24, '≠', '±'),
+    (33, '“', '”'),
+    (30, '‘', '’'),
+    (42, '«', '»'),
+    (41, '…', 'Ú'),
+    (39, 'æ', 'Æ'),
+    (43, '≤', '¯'),
+    (47, '≥', '˘'),
+    (44, '÷', '¿'),
+    (50, '`', '`'),
+    (49, '\u{A0}', '\u{A0}'),
+];
+
+/// Character typed by Option (and optionally Shift) + key on the US/ABC layout
+///
+/// None for keys without an Option character (the numeric keypad, Return, ...).
+pub fn option_keycode_to_char(keycode: i64, shift: bool) -> Option<char> {
+    OPTION_LAYER
+        .iter()
+        .find(|(code, _, _)| *code == keycode)
+        .map(|(_, option, shift_option)| if shift { *shift_option } else { *option })
+}
+
+/// Highest virtual keycode searched when checking whether a character can be typed
+const MAX_KEYCODE: i64 = 127;
+
+/// Dead keys of the US/ABC Option layer
+const DEAD_KEYS_ALL: [DeadKey; 5] = [
+    DeadKey::Acute,
+    DeadKey::Grave,
+    DeadKey::Circumflex,
+    DeadKey::Tilde,
+    DeadKey::Umlaut,
+];
+
+/// Whether `ch` can be typed with the static tables for `layout`
+fn is_typable(ch: char, layout: KeyboardLayout) -> bool {
+    let plain = |ch: char| {
+        (0..=MAX_KEYCODE).any(|keycode| {
+            [false, true]
+                .into_iter()
+                .any(|shift| layout.keycode_to_char(keycode, shift) == Some(ch))
+        })
+    };
+    if plain(ch) {
+        return true;
+    }
+    if layout != KeyboardLayout::Us {
+        return false;
+    }
+
+    // Option layer, and accents composed with a dead key
+    let option = (0..=MAX_KEYCODE).any(|keycode| {
+        [false, true]
+            .into_iter()
+            .any(|shift| option_keycode_to_char(keycode, shift) == Some(ch))
+    });
+    option
+        || DEAD_KEYS_ALL
+            .into_iter()
+            .any(|dead_key| (' '..='~').any(|base| dead_key.compose(base) == Some(ch)))
+}
+
+/// Check that every character of a passphrase can be typed while locked
+///
+/// Checks against the static tables for `layout`, plus the US/ABC Option layer and its
+/// dead-key accents - what passphrase entry falls back to when the active input source
+/// can't be read. Returns the characters that can't be typed that way (each once, in
+/// order of appearance); they may still work with the active input source.
+pub fn can_type_while_locked(passphrase: &str, layout: KeyboardLayout) -> Result<(), Vec<char>> {
+    let mut untypable: Vec<char> = Vec::new();
+    for ch in passphrase.chars() {
+        if !untypable.contains(&ch) && !is_typable(ch, layout) {
+            untypable.push(ch);
+        }
+    }
+    if untypable.is_empty() {
+        Ok(())
+    } else {
+        Err(untypable)
+    }
+}
+
+/// Warning shown at setup for characters `can_type_while_locked` rejected
+pub fn untypable_passphrase_warning(chars: &[char]) -> String {
+    let list: Vec<String> = chars.iter().map(char::to_string).collect();
+    format!(
+        "Passphrase contains characters that may be hard to type while locked: {}",
+        list.join(" ")
+    )
+}
+
 /// Convert global_hotkey Code enum to macOS keycode
 /// Returns None if the Code is not a letter key
 pub fn code_to_keycode(code: global_hotkey::hotkey::Code) -> Option<i64> {
@@ -33,8 +570,139 @@ pub fn code_to_keycode(code: global_hotkey::hotkey::Code) -> Option<i64> {
     }
 }
 
+/// Static keyboard layout used when the active input source can't be read
+///
+/// macOS keycodes identify physical key positions (named after US QWERTY), so a
+/// passphrase set on another layout only matches if keycodes are translated with
+/// that layout. Selected with `keyboard_layout` in config.toml; normally the active
+/// input source is used instead (see `translate_with_current_layout`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardLayout {
+    /// US QWERTY (default)
+    #[default]
+    Us,
+    /// US Dvorak
+    Dvorak,
+    /// Colemak
+    Colemak,
+}
+
+/// Keys that differ from US QWERTY on Dvorak: (QWERTY char, unshifted, shifted)
+const DVORAK_REMAP: &[(char, char, char)] = &[
+    ('-', '[', '{'),
+    ('=', ']', '}'),
+    ('q', '\'', '"'),
+    ('w', ',', '<'),
+    ('e', '.', '>'),
+    ('r', 'p', 'P'),
+    ('t', 'y', 'Y'),
+    ('y', 'f', 'F'),
+    ('u', 'g', 'G'),
+    ('i', 'c', 'C'),
+    ('o', 'r', 'R'),
+    ('p', 'l', 'L'),
+    ('[', '/', '?'),
+    (']', '=', '+'),
+    ('s', 'o', 'O'),
+    ('d', 'e', 'E'),
+    ('f', 'u', 'U'),
+    ('g', 'i', 'I'),
+    ('h', 'd', 'D'),
+    ('j', 'h', 'H'),
+    ('k', 't', 'T'),
+    ('l', 'n', 'N'),
+    (';', 's', 'S'),
+    ('\'', '-', '_'),
+    ('z', ';', ':'),
+    ('x', 'q', 'Q'),
+    ('c', 'j', 'J'),
+    ('v', 'k', 'K'),
+    ('b', 'x', 'X'),
+    ('n', 'b', 'B'),
+    (',', 'w', 'W'),
+    ('.', 'v', 'V'),
+    ('/', 'z', 'Z'),
+];
+
+/// Keys that differ from US QWERTY on Colemak: (QWERTY char, unshifted, shifted)
+const COLEMAK_REMAP: &[(char, char, char)] = &[
+    ('e', 'f', 'F'),
+    ('r', 'p', 'P'),
+    ('t', 'g', 'G'),
+    ('y', 'j', 'J'),
+    ('u', 'l', 'L'),
+    ('i', 'u', 'U'),
+    ('o', 'y', 'Y'),
+    ('p', ';', ':'),
+    ('s', 'r', 'R'),
+    ('d', 's', 'S'),
+    ('f', 't', 'T'),
+    ('g', 'd', 'D'),
+    ('j', 'n', 'N'),
+    ('k', 'e', 'E'),
+    ('l', 'i', 'I'),
+    (';', 'o', 'O'),
+    ('n', 'k', 'K'),
+];
+
+impl KeyboardLayout {
+    /// Convert a macOS keycode to the character this layout produces
+    pub fn keycode_to_char(self, keycode: i64, shift: bool) -> Option<char> {
+        let remap = match self {
+            KeyboardLayout::Us => return keycode_to_char(keycode, shift),
+            KeyboardLayout::Dvorak => DVORAK_REMAP,
+            KeyboardLayout::Colemak => COLEMAK_REMAP,
+        };
+
+        // The numeric keypad is the same on every layout
+        if is_keypad_keycode(keycode) {
+            return keycode_to_char(keycode, shift);
+        }
+
+        let qwerty = keycode_to_char(keycode, false)?;
+        match remap.iter().find(|(from, _, _)| *from == qwerty) {
+            Some((_, unshifted, shifted)) => Some(if shift { *shifted } else { *unshifted }),
+            None => keycode_to_char(keycode, shift),
+        }
+    }
+}
+
+impl FromStr for KeyboardLayout {
+    type Err = anyhow::Error;
+
+    /// Parse "us"/"qwerty", "dvorak" or "colemak" (case insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "us" | "qwerty" => Ok(KeyboardLayout::Us),
+            "dvorak" => Ok(KeyboardLayout::Dvorak),
+            "colemak" => Ok(KeyboardLayout::Colemak),
+            other => Err(anyhow::anyhow!(
+                "Invalid keyboard layout '{}' (must be us, dvorak or colemak)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for KeyboardLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyboardLayout::Us => write!(f, "us"),
+            KeyboardLayout::Dvorak => write!(f, "dvorak"),
+            KeyboardLayout::Colemak => write!(f, "colemak"),
+        }
+    }
+}
+
+/// Whether a keycode is a numeric keypad key (digits, operators, Clear, Enter)
+pub fn is_keypad_keycode(keycode: i64) -> bool {
+    matches!(keycode, 65 | 67 | 69 | 71 | 75 | 76 | 78 | 81..=89 | 91 | 92)
+}
+
 /// Convert macOS keycode to character
 /// Based on HIToolbox/Events.h keycodes for US keyboard layout
+/// (see `KeyboardLayout::keycode_to_char` for other layouts)
 pub fn keycode_to_char(keycode: i64, shift: bool) -> Option<char> {
     match keycode {
         // Letters
@@ -90,6 +758,25 @@ pub fn keycode_to_char(keycode: i64, shift: bool) -> Option<char> {
         44 => Some(if shift { '?' } else { '/' }),
         50 => Some(if shift { '~' } else { '`' }),
 
+        // Numeric keypad (same characters as the main row, Shift has no effect;
+        // 90 is F20, not a keypad key)
+        82 => Some('0'),
+        83 => Some('1'),
+        84 => Some('2'),
+        85 => Some('3'),
+        86 => Some('4'),
+        87 => Some('5'),
+        88 => Some('6'),
+        89 => Some('7'),
+        91 => Some('8'),
+        92 => Some('9'),
+        65 => Some('.'),
+        67 => Some('*'),
+        69 => Some('+'),
+        75 => Some('/'),
+        78 => Some('-'),
+        81 => Some('='),
+
         // Space
         49 => Some(' '),
 
@@ -105,3 +792,79 @@ pub fn keycode_to_char(keycode: i64, shift: bool) -> Option<char> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_without_snapshot_uses_static_table() {
+        let shift = CGEventFlags::CGEventFlagShift;
+        assert_eq!(translate_or_fallback(None, &mut DeadKeyState::new(), 0, CGEventFlags::CGEventFlagNull, KeyboardLayout::Us), Some('a'));
+        assert_eq!(translate_or_fallback(None, &mut DeadKeyState::new(), 0, shift, KeyboardLayout::Us), Some('A'));
+        assert_eq!(translate_or_fallback(None, &mut DeadKeyState::new(), 36, shift, KeyboardLayout::Us), Some('\n'));
+        assert_eq!(translate_or_fallback(None, &mut DeadKeyState::new(), 123, shift, KeyboardLayout::Us), None);
+        assert_eq!(
+            translate_or_fallback(None, &mut DeadKeyState::new(), 1, CGEventFlags::CGEventFlagNull, KeyboardLayout::Dvorak),
+            Some('o')
+        );
+    }
+
+    #[test]
+    fn test_fallback_honors_caps_lock() {
+        let caps = CGEventFlags::CGEventFlagAlphaShift;
+        assert_eq!(translate_or_fallback(None, &mut DeadKeyState::new(), 0, caps, KeyboardLayout::Us), Some('A'));
+        assert_eq!(translate_or_fallback(None, &mut DeadKeyState::new(), 18, caps, KeyboardLayout::Us), Some('1'));
+    }
+
+    #[test]
+    fn test_fallback_option_layer_and_dead_keys() {
+        let option = CGEventFlags::CGEventFlagAlternate;
+        let shift = CGEventFlags::CGEventFlagShift;
+        let mut dead_keys = DeadKeyState::new();
+        let mut type_key = |keycode, flags| {
+            translate_or_fallback(None, &mut dead_keys, keycode, flags, KeyboardLayout::Us)
+        };
+
+        assert_eq!(type_key(1, option), Some('ß'));
+        // Option+E then E types é
+        assert_eq!(type_key(14, option), None);
+        assert_eq!(type_key(14, CGEventFlags::CGEventFlagNull), Some('é'));
+        // Option+N then Shift+N types Ñ
+        assert_eq!(type_key(45, option), None);
+        assert_eq!(type_key(45, shift), Some('Ñ'));
+        // A dead key followed by a key it doesn't combine with types only that key
+        assert_eq!(type_key(32, option), None);
+        assert_eq!(type_key(7, CGEventFlags::CGEventFlagNull), Some('x'));
+        assert_eq!(type_key(0, CGEventFlags::CGEventFlagNull), Some('a'));
+    }
+
+    #[test]
+    fn test_carbon_modifier_state() {
+        assert_eq!(carbon_modifier_state(CGEventFlags::CGEventFlagNull), 0);
+        assert_eq!(carbon_modifier_state(CGEventFlags::CGEventFlagShift), 0x02);
+        assert_eq!(carbon_modifier_state(CGEventFlags::CGEventFlagAlphaShift), 0x04);
+        assert_eq!(
+            carbon_modifier_state(CGEventFlags::CGEventFlagAlternate | CGEventFlags::CGEventFlagShift),
+            0x0A
+        );
+        // Control and Command don't affect the typed character
+        assert_eq!(
+            carbon_modifier_state(CGEventFlags::CGEventFlagControl | CGEventFlags::CGEventFlagCommand),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(target_os = "macos"), ignore)]
+    fn test_current_layout_smoke() {
+        refresh_keyboard_layout();
+        assert_eq!(
+            translate_with_current_layout(49, CGEventFlags::CGEventFlagNull),
+            Some(' ')
+        );
+        // Keycode 0 is a letter on every common layout (a on QWERTY, q on AZERTY)
+        let ch = translate_with_current_layout(0, CGEventFlags::CGEventFlagNull);
+        assert!(ch.is_some_and(char::is_alphabetic), "got {:?}", ch);
+    }
+}
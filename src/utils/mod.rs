@@ -1,4 +1,12 @@
+pub mod activity_debounce;
+#[cfg(test)]
+pub mod fake_stream;
 pub mod keycode;
+pub mod modifiers;
+pub mod poll_interval;
+pub mod rate_limit;
+pub mod scheduler;
+pub mod secret;
 
 use ring::digest;
 
@@ -9,6 +17,17 @@ pub fn hash_passphrase(passphrase: &str) -> String {
 }
 
 /// Verify a passphrase against a stored hash
+///
+/// The comparison runs in constant time with respect to the hash contents so that
+/// timing does not leak how many leading characters of the hash matched.
 pub fn verify_passphrase(passphrase: &str, hash: &str) -> bool {
-    hash_passphrase(passphrase) == hash
+    constant_time_eq(hash_passphrase(passphrase).as_bytes(), hash.as_bytes())
+}
+
+/// Compare two byte slices without short-circuiting on the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
@@ -0,0 +1,192 @@
+use anyhow::{anyhow, Result};
+use core_graphics::event::CGEventFlags;
+use global_hotkey::hotkey::Modifiers;
+use std::fmt;
+use std::str::FromStr;
+
+/// Modifier keys that must be held for a hotkey (default: Ctrl+Cmd+Shift)
+///
+/// Parsed from strings like "cmd+opt+ctrl" (case insensitive, any order).
+/// Accepted names: ctrl/control, opt/option/alt, cmd/command/super, shift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotkeyModifiers {
+    pub control: bool,
+    pub option: bool,
+    pub command: bool,
+    pub shift: bool,
+}
+
+impl HotkeyModifiers {
+    /// Ctrl+Cmd+Shift
+    pub const DEFAULT: Self = Self {
+        control: true,
+        option: false,
+        command: true,
+        shift: true,
+    };
+
+    /// Ctrl+Opt+Cmd+Shift (emergency unlock default - hard to press by accident)
+    pub const PANIC_DEFAULT: Self = Self {
+        control: true,
+        option: true,
+        command: true,
+        shift: true,
+    };
+
+    /// Whether no modifier is selected
+    pub fn is_empty(&self) -> bool {
+        !(self.control || self.option || self.command || self.shift)
+    }
+
+    /// Convert to global-hotkey modifiers for registration
+    pub fn to_global_hotkey(self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        if self.control {
+            modifiers |= Modifiers::CONTROL;
+        }
+        if self.option {
+            modifiers |= Modifiers::ALT;
+        }
+        if self.command {
+            modifiers |= Modifiers::SUPER;
+        }
+        if self.shift {
+            modifiers |= Modifiers::SHIFT;
+        }
+        modifiers
+    }
+
+    /// Modifiers held in an event's flags (Caps Lock and Fn are ignored)
+    pub fn from_flags(flags: CGEventFlags) -> Self {
+        Self {
+            control: flags.contains(CGEventFlags::CGEventFlagControl),
+            option: flags.contains(CGEventFlags::CGEventFlagAlternate),
+            command: flags.contains(CGEventFlags::CGEventFlagCommand),
+            shift: flags.contains(CGEventFlags::CGEventFlagShift),
+        }
+    }
+
+    /// Number of modifiers selected
+    pub fn count(&self) -> usize {
+        [self.control, self.option, self.command, self.shift]
+            .iter()
+            .filter(|held| **held)
+            .count()
+    }
+
+    /// Check whether all of these modifiers are held in an event's flags
+    pub fn matches(&self, flags: CGEventFlags) -> bool {
+        (!self.control || flags.contains(CGEventFlags::CGEventFlagControl))
+            && (!self.option || flags.contains(CGEventFlags::CGEventFlagAlternate))
+            && (!self.command || flags.contains(CGEventFlags::CGEventFlagCommand))
+            && (!self.shift || flags.contains(CGEventFlags::CGEventFlagShift))
+    }
+}
+
+impl Default for HotkeyModifiers {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl FromStr for HotkeyModifiers {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = Self {
+            control: false,
+            option: false,
+            command: false,
+            shift: false,
+        };
+
+        for part in s.split('+').map(|p| p.trim().to_lowercase()) {
+            match part.as_str() {
+                "ctrl" | "control" => modifiers.control = true,
+                "opt" | "option" | "alt" => modifiers.option = true,
+                "cmd" | "command" | "super" => modifiers.command = true,
+                "shift" => modifiers.shift = true,
+                "" => {}
+                other => {
+                    return Err(anyhow!(
+                        "Unknown modifier '{}' (expected ctrl, opt, cmd or shift)",
+                        other
+                    ))
+                }
+            }
+        }
+
+        if modifiers.is_empty() {
+            return Err(anyhow!(
+                "At least one modifier (ctrl, opt, cmd, shift) is required"
+            ));
+        }
+        Ok(modifiers)
+    }
+}
+
+impl fmt::Display for HotkeyModifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = [
+            (self.control, "Ctrl"),
+            (self.option, "Opt"),
+            (self.command, "Cmd"),
+            (self.shift, "Shift"),
+        ]
+        .iter()
+        .filter(|(held, _)| *held)
+        .map(|(_, name)| *name)
+        .collect();
+        write!(f, "{}", names.join("+"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modifier_strings() {
+        let mods: HotkeyModifiers = "cmd+opt+ctrl".parse().unwrap();
+        assert!(mods.command && mods.option && mods.control && !mods.shift);
+
+        let mods: HotkeyModifiers = " Control + Shift ".parse().unwrap();
+        assert!(mods.control && mods.shift && !mods.command && !mods.option);
+
+        assert_eq!(
+            "ctrl+cmd+shift".parse::<HotkeyModifiers>().unwrap(),
+            HotkeyModifiers::DEFAULT
+        );
+        assert_eq!("super+alt".parse::<HotkeyModifiers>().unwrap().to_string(), "Opt+Cmd");
+    }
+
+    #[test]
+    fn test_reject_empty_and_invalid_modifiers() {
+        assert!("".parse::<HotkeyModifiers>().is_err());
+        assert!("+".parse::<HotkeyModifiers>().is_err());
+        assert!("cmd+hyper".parse::<HotkeyModifiers>().is_err());
+        assert!("cmd+l".parse::<HotkeyModifiers>().is_err());
+    }
+
+    #[test]
+    fn test_display_and_global_hotkey_conversion() {
+        assert_eq!(HotkeyModifiers::DEFAULT.to_string(), "Ctrl+Cmd+Shift");
+        assert_eq!(
+            HotkeyModifiers::DEFAULT.to_global_hotkey(),
+            Modifiers::CONTROL | Modifiers::SUPER | Modifiers::SHIFT
+        );
+    }
+
+    #[test]
+    fn test_matches_event_flags() {
+        let default_flags = CGEventFlags::CGEventFlagControl
+            | CGEventFlags::CGEventFlagCommand
+            | CGEventFlags::CGEventFlagShift;
+        assert!(HotkeyModifiers::DEFAULT.matches(default_flags));
+        assert!(!HotkeyModifiers::DEFAULT.matches(CGEventFlags::CGEventFlagCommand));
+
+        let cmd_opt: HotkeyModifiers = "cmd+opt".parse().unwrap();
+        assert!(cmd_opt.matches(CGEventFlags::CGEventFlagCommand | CGEventFlags::CGEventFlagAlternate));
+        assert!(!cmd_opt.matches(default_flags));
+    }
+}
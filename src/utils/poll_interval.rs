@@ -0,0 +1,74 @@
+//! Adaptive intervals for the background monitoring checks
+//!
+//! Rather than running on a fixed interval, a check runs again after about half the
+//! time left until its next deadline - long waits while it's far away, short ones close
+//! to it - and after its longest interval while HandsOff is disabled. This cuts idle
+//! wakeups on battery without making deadlines less precise.
+
+use std::time::Duration;
+
+/// Interval bounds for one background check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollPolicy {
+    /// Shortest interval (precision near a deadline)
+    pub min: Duration,
+    /// Interval while no deadline is pending
+    ///
+    /// Must not exceed the shortest time from a deadline appearing to it expiring.
+    pub idle: Duration,
+    /// Longest interval, also used while disabled
+    pub max: Duration,
+}
+
+/// How long to wait before the next run of a check
+///
+/// `remaining` is the time left until the check's next deadline (None if none is
+/// pending).
+pub fn next_poll_interval(
+    policy: &PollPolicy,
+    disabled: bool,
+    remaining: Option<Duration>,
+) -> Duration {
+    if disabled {
+        return policy.max;
+    }
+    match remaining {
+        Some(remaining) => (remaining / 2).clamp(policy.min, policy.max),
+        None => policy.idle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: PollPolicy = PollPolicy {
+        min: Duration::from_secs(1),
+        idle: Duration::from_secs(5),
+        max: Duration::from_secs(15),
+    };
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_disabled_sleeps_longest() {
+        assert_eq!(next_poll_interval(&POLICY, true, None), secs(15));
+        assert_eq!(next_poll_interval(&POLICY, true, Some(secs(2))), secs(15));
+    }
+
+    #[test]
+    fn test_no_deadline_uses_idle_interval() {
+        assert_eq!(next_poll_interval(&POLICY, false, None), secs(5));
+    }
+
+    #[test]
+    fn test_halves_time_to_deadline_within_bounds() {
+        assert_eq!(next_poll_interval(&POLICY, false, Some(secs(600))), secs(15), "Far away");
+        assert_eq!(next_poll_interval(&POLICY, false, Some(secs(20))), secs(10));
+        assert_eq!(next_poll_interval(&POLICY, false, Some(secs(3))), Duration::from_millis(1500));
+        assert_eq!(next_poll_interval(&POLICY, false, Some(secs(1))), secs(1), "Close by");
+        assert_eq!(next_poll_interval(&POLICY, false, Some(secs(0))), secs(1), "Overdue");
+    }
+}
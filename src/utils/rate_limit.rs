@@ -0,0 +1,132 @@
+//! Rate limiting for log messages in the event tap hot path
+//!
+//! Logging every keystroke during a key-mash floods the log and slows the tap
+//! callback enough to risk macOS disabling the tap for timing out. A `RateLimiter`
+//! lets a message through at most once per interval and counts the ones it
+//! suppressed in between, so the next message that gets through can report them.
+//!
+//! `log_throttled!(debug, "...", args)` wraps a log call in a per-call-site limiter
+//! allowing one line per HOT_PATH_LOG_INTERVAL_MS.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// `last_allowed_ms` before anything was allowed
+const NEVER: u64 = u64::MAX;
+
+/// Allows an action at most once per interval (lock-free, usable in a `static`)
+pub struct RateLimiter {
+    interval_ms: u64,
+    last_allowed_ms: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+impl RateLimiter {
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval_ms: interval.as_millis() as u64,
+            last_allowed_ms: AtomicU64::new(NEVER),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Check whether to log now
+    ///
+    /// Returns the number of calls suppressed since the last allowed one, or None if
+    /// this call is suppressed too.
+    pub fn check(&self) -> Option<u64> {
+        self.check_at(monotonic_ms())
+    }
+
+    /// `check` at an explicit time (milliseconds on any monotonic clock)
+    pub fn check_at(&self, now_ms: u64) -> Option<u64> {
+        let last = self.last_allowed_ms.load(Ordering::Relaxed);
+        let due = last == NEVER || now_ms.saturating_sub(last) >= self.interval_ms;
+        // Only one of several racing callers wins the slot
+        if due
+            && self
+                .last_allowed_ms
+                .compare_exchange(last, now_ms, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            Some(self.suppressed.swap(0, Ordering::Relaxed))
+        } else {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+}
+
+/// Suffix for a log line that stood in for `suppressed` others ("" when none were)
+pub fn suppressed_note(suppressed: u64) -> String {
+    if suppressed == 0 {
+        String::new()
+    } else {
+        format!(" ({} similar messages suppressed)", suppressed)
+    }
+}
+
+/// Log at most once per HOT_PATH_LOG_INTERVAL_MS from this call site
+///
+/// `log_throttled!(info, "Talk hotkey pressed")` - the level is a `log` macro name.
+macro_rules! log_throttled {
+    ($level:ident, $($arg:tt)+) => {{
+        static LIMITER: $crate::utils::rate_limit::RateLimiter =
+            $crate::utils::rate_limit::RateLimiter::new(std::time::Duration::from_millis(
+                $crate::constants::HOT_PATH_LOG_INTERVAL_MS,
+            ));
+        if let Some(suppressed) = LIMITER.check() {
+            log::$level!(
+                "{}{}",
+                format_args!($($arg)+),
+                $crate::utils::rate_limit::suppressed_note(suppressed)
+            );
+        }
+    }};
+}
+pub(crate) use log_throttled;
+
+/// Milliseconds since the first call (process-wide monotonic clock)
+fn monotonic_ms() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_once_per_interval() {
+        let limiter = RateLimiter::new(Duration::from_millis(1000));
+        assert_eq!(limiter.check_at(5000), Some(0), "First call is always allowed");
+        assert_eq!(limiter.check_at(5000), None);
+        assert_eq!(limiter.check_at(5999), None);
+        assert_eq!(limiter.check_at(6000), Some(2), "Reports what was suppressed");
+        assert_eq!(limiter.check_at(6001), None);
+        assert_eq!(limiter.check_at(9000), Some(1));
+        assert_eq!(limiter.check_at(10_000), Some(0), "Counter restarts after reporting");
+    }
+
+    #[test]
+    fn test_first_call_allowed_at_time_zero() {
+        let limiter = RateLimiter::new(Duration::from_secs(1));
+        assert_eq!(limiter.check_at(0), Some(0));
+        assert_eq!(limiter.check_at(999), None);
+        assert_eq!(limiter.check_at(1000), Some(1));
+    }
+
+    #[test]
+    fn test_check_uses_real_clock() {
+        let limiter = RateLimiter::new(Duration::from_secs(60));
+        assert_eq!(limiter.check(), Some(0));
+        assert_eq!(limiter.check(), None);
+    }
+
+    #[test]
+    fn test_suppressed_note() {
+        assert_eq!(suppressed_note(0), "");
+        assert_eq!(suppressed_note(3), " (3 similar messages suppressed)");
+    }
+}
@@ -0,0 +1,121 @@
+//! Due-time bookkeeping for the background scheduler thread
+//!
+//! One thread runs all periodic monitoring checks (`MonitorTask`). Each task keeps its
+//! own next due time; the thread sleeps until the earliest one, runs every task that's
+//! due and reschedules each with its own adaptive interval (see `poll_interval`).
+
+use std::time::Instant;
+
+/// A periodic check run by the scheduler thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorTask {
+    /// Accessibility permission check (unlocks and stops the event tap on loss)
+    PermissionCheck,
+    /// Clear a partially typed passphrase after the buffer reset timeout
+    BufferReset,
+    /// Lock after inactivity (and warn before)
+    AutoLock,
+    /// Unlock after the auto-unlock timeout or the max lock duration hard cap
+    AutoUnlock,
+}
+
+impl MonitorTask {
+    /// Every task, in the order they run when due together
+    ///
+    /// The permission check goes first so a revoked permission is handled before
+    /// anything else can lock input.
+    pub const ALL: [MonitorTask; 4] = [
+        MonitorTask::PermissionCheck,
+        MonitorTask::BufferReset,
+        MonitorTask::AutoLock,
+        MonitorTask::AutoUnlock,
+    ];
+}
+
+/// Next due time of each task
+#[derive(Debug, Clone)]
+pub struct TaskSchedule {
+    due: [Instant; MonitorTask::ALL.len()],
+}
+
+impl TaskSchedule {
+    /// Schedule with every task due at `start`
+    pub fn new(start: Instant) -> Self {
+        Self {
+            due: [start; MonitorTask::ALL.len()],
+        }
+    }
+
+    /// Set when `task` is next due
+    pub fn schedule(&mut self, task: MonitorTask, at: Instant) {
+        self.due[task as usize] = at;
+    }
+
+    /// When `task` is next due
+    pub fn due_at(&self, task: MonitorTask) -> Instant {
+        self.due[task as usize]
+    }
+
+    /// Tasks due at `now`, in `MonitorTask::ALL` order
+    pub fn due_tasks(&self, now: Instant) -> Vec<MonitorTask> {
+        MonitorTask::ALL
+            .into_iter()
+            .filter(|&task| self.due_at(task) <= now)
+            .collect()
+    }
+
+    /// When the scheduler thread should next wake (the earliest due time)
+    pub fn next_wake(&self) -> Instant {
+        self.due.into_iter().min().expect("at least one task")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_all_tasks_due_at_start() {
+        let start = Instant::now();
+        let schedule = TaskSchedule::new(start);
+        assert_eq!(schedule.due_tasks(start), MonitorTask::ALL.to_vec());
+        assert_eq!(schedule.next_wake(), start);
+    }
+
+    #[test]
+    fn test_only_due_tasks_selected_in_order() {
+        let start = Instant::now();
+        let mut schedule = TaskSchedule::new(start);
+        schedule.schedule(MonitorTask::PermissionCheck, start + secs(15));
+        schedule.schedule(MonitorTask::BufferReset, start + secs(1));
+        schedule.schedule(MonitorTask::AutoLock, start + secs(5));
+        schedule.schedule(MonitorTask::AutoUnlock, start + secs(10));
+
+        assert_eq!(schedule.next_wake(), start + secs(1));
+        assert!(schedule.due_tasks(start).is_empty(), "Nothing due yet");
+        assert_eq!(schedule.due_tasks(start + secs(1)), vec![MonitorTask::BufferReset]);
+        assert_eq!(
+            schedule.due_tasks(start + secs(12)),
+            vec![MonitorTask::BufferReset, MonitorTask::AutoLock, MonitorTask::AutoUnlock],
+            "Overdue tasks are still due"
+        );
+        assert_eq!(schedule.due_tasks(start + secs(15)), MonitorTask::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_rescheduling_moves_next_wake() {
+        let start = Instant::now();
+        let mut schedule = TaskSchedule::new(start + secs(30));
+        schedule.schedule(MonitorTask::AutoLock, start + secs(2));
+        assert_eq!(schedule.next_wake(), start + secs(2));
+
+        schedule.schedule(MonitorTask::AutoLock, start + secs(60));
+        assert_eq!(schedule.due_at(MonitorTask::AutoLock), start + secs(60));
+        assert_eq!(schedule.next_wake(), start + secs(30));
+    }
+}
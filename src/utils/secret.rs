@@ -0,0 +1,69 @@
+//! Wrapper for plaintext secrets that must never reach the logs
+//!
+//! `SecretString` derefs to `&str` for verification, but its `Display` and `Debug`
+//! output only give the length, so passing one to `info!`/`debug!` (or `{:?}` of a
+//! struct holding one) can't leak the passphrase. The contents are zeroed on drop.
+
+use std::fmt;
+use std::ops::Deref;
+use zeroize::Zeroizing;
+
+/// Plaintext secret (typed passphrase buffer) with redacted formatting
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    pub fn new(secret: String) -> Self {
+        Self(Zeroizing::new(secret))
+    }
+
+    /// Borrow the plaintext (for verification only - never log it)
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.expose()
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted: {} chars>", self.0.chars().count())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString({})", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatting_never_shows_contents() {
+        let secret = SecretString::new("hunter2 é".to_string());
+        let displayed = format!("Buffer updated: {}", secret);
+        let debugged = format!("{:?}", secret);
+
+        assert!(!displayed.contains("hunter2"), "{}", displayed);
+        assert!(!debugged.contains("hunter2"), "{}", debugged);
+        assert_eq!(displayed, "Buffer updated: <redacted: 9 chars>");
+        assert_eq!(debugged, "SecretString(<redacted: 9 chars>)");
+    }
+
+    #[test]
+    fn test_expose_for_verification() {
+        let secret = SecretString::new("hunter2".to_string());
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(&*secret, "hunter2");
+        assert!(SecretString::default().is_empty());
+    }
+}
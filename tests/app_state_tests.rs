@@ -1,12 +1,14 @@
-use handsoff::app_state::AppState;
+use handsoff::app_state::{AppState, ATTEMPT_COOLDOWN_SECONDS};
+use handsoff::audit::{AuditEvent, UnlockMethod};
+use handsoff::auth;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_initial_state() {
     let state = AppState::new();
     assert!(!state.is_locked());
-    assert_eq!(state.get_buffer(), "");
+    assert_eq!(state.get_buffer().expose(), "");
     assert!(state.get_passphrase_hash().is_none());
 }
 
@@ -25,9 +27,9 @@ fn test_buffer_operations() {
     state.append_to_buffer('a');
     state.append_to_buffer('b');
     state.append_to_buffer('c');
-    assert_eq!(state.get_buffer(), "abc");
+    assert_eq!(state.get_buffer().expose(), "abc");
     state.clear_buffer();
-    assert_eq!(state.get_buffer(), "");
+    assert_eq!(state.get_buffer().expose(), "");
 }
 
 #[test]
@@ -42,16 +44,125 @@ fn test_passphrase_hash() {
 fn test_buffer_reset_timing() {
     let state = AppState::new();
     state.lock().buffer_reset_timeout = 1; // 1 second for testing
+    assert_eq!(state.get_buffer_reset_remaining_ms(), None, "Nothing to reset yet");
 
     state.append_to_buffer('x');
     state.update_key_time();
 
     assert!(!state.should_reset_buffer());
+    assert!(state.get_buffer_reset_remaining_ms().is_some_and(|ms| ms > 0 && ms <= 1000));
 
     thread::sleep(Duration::from_millis(1100)); // Slightly over 1 second
     assert!(state.should_reset_buffer());
 }
 
+#[test]
+fn test_overlong_attempt_cleared_immediately() {
+    let state = AppState::new();
+    state.set_locked(true);
+    state.set_passphrase_len(Some(3));
+    for ch in "abcd".chars() {
+        state.append_to_buffer(ch);
+    }
+    assert!(!state.clear_buffer_if_overlong(), "Disabled by default");
+
+    state.set_clear_overlong_attempts(true);
+    assert!(state.clear_buffer_if_overlong());
+    assert_eq!(state.buffer_len(), 0);
+
+    // A configured recovery code keeps the buffer while it could be the start of the code
+    state.set_recovery_code_hash(Some(auth::hash_passphrase("unused")));
+    for ch in "abcde-fghi".chars() {
+        state.append_to_buffer(ch);
+    }
+    assert!(!state.clear_buffer_if_overlong());
+    assert_eq!(state.buffer_len(), 10);
+    state.append_to_buffer('x');
+    state.append_to_buffer('x');
+    assert!(state.clear_buffer_if_overlong(), "No dash after the second group");
+
+    // Unknown passphrase length (Keychain) or unlocked: never cleared early
+    state.set_recovery_code_hash(None);
+    state.set_passphrase_len(None);
+    assert!(!state.clear_buffer_if_overlong());
+    state.set_passphrase_len(Some(3));
+    state.set_locked(false);
+    assert!(!state.clear_buffer_if_overlong());
+}
+
+#[test]
+fn test_attempt_cooldown_starts_at_full_length_and_expires() {
+    let state = AppState::new();
+    state.set_locked(true);
+    state.set_passphrase_len(Some(3));
+    let t0 = Instant::now();
+
+    state.append_to_buffer('a');
+    state.append_to_buffer('b');
+    assert!(!state.start_attempt_cooldown_if_full_length_at(t0), "Attempt not finished yet");
+    assert_eq!(state.get_attempt_cooldown_remaining_at(t0), None);
+
+    state.append_to_buffer('c');
+    assert!(state.start_attempt_cooldown_if_full_length_at(t0));
+    assert_eq!(state.buffer_len(), 0, "Wrong attempt discarded");
+    assert_eq!(state.get_attempt_cooldown_remaining_at(t0), Some(ATTEMPT_COOLDOWN_SECONDS));
+    let almost = t0 + Duration::from_millis(ATTEMPT_COOLDOWN_SECONDS * 1000 - 500);
+    assert_eq!(state.get_attempt_cooldown_remaining_at(almost), Some(1));
+    let expired = t0 + Duration::from_secs(ATTEMPT_COOLDOWN_SECONDS);
+    assert_eq!(state.get_attempt_cooldown_remaining_at(expired), None);
+
+    // Unlocking ends a running cooldown
+    for ch in "abc".chars() {
+        state.append_to_buffer(ch);
+    }
+    assert!(state.start_attempt_cooldown_if_full_length());
+    state.set_locked(false);
+    assert_eq!(state.get_attempt_cooldown_remaining(), None);
+}
+
+#[test]
+fn test_attempt_cooldown_can_be_disabled() {
+    let state = AppState::new();
+    state.set_locked(true);
+    state.set_passphrase_len(Some(3));
+    state.set_clear_overlong_attempts(true);
+    state.set_attempt_cooldown(false);
+
+    // The full-length attempt is kept; one more character clears it without a cooldown
+    for ch in "abc".chars() {
+        state.append_to_buffer(ch);
+    }
+    assert!(!state.start_attempt_cooldown_if_full_length());
+    assert_eq!(state.buffer_len(), 3);
+    state.append_to_buffer('d');
+    assert!(!state.start_attempt_cooldown_if_full_length());
+    assert!(state.clear_buffer_if_overlong());
+    assert_eq!(state.get_attempt_cooldown_remaining(), None);
+}
+
+#[test]
+fn test_passphrase_matches_at_its_length_with_overlong_clearing() {
+    let passphrase = "secret";
+    let hash = auth::hash_passphrase(passphrase);
+    let state = AppState::new();
+    state.set_locked(true);
+    state.set_passphrase_len(Some(passphrase.len()));
+    state.set_clear_overlong_attempts(true);
+
+    // A failed attempt is discarded after one extra character, then the retry matches
+    let mut matched_at = None;
+    for (i, ch) in "wrongpwsecret".chars().enumerate() {
+        state.append_to_buffer(ch);
+        if auth::verify_passphrase(&state.get_buffer(), &hash) {
+            matched_at = Some(i);
+            break;
+        }
+        state.clear_buffer_if_overlong();
+    }
+    assert_eq!(matched_at, Some(12));
+    assert_eq!(state.get_buffer().expose(), passphrase);
+}
+
 #[test]
 fn test_auto_lock_timing() {
     let state = AppState::new();
@@ -59,6 +170,7 @@ fn test_auto_lock_timing() {
         let mut inner = state.lock();
         inner.auto_lock_timeout = 1; // 1 second for testing
         inner.has_accessibility_permissions = true; // allow auto-lock in this test
+        inner.passphrase_hash = Some(auth::hash_passphrase("test"));
     }
 
     assert!(!state.should_auto_lock()); // Starts unlocked
@@ -139,7 +251,7 @@ fn test_buffer_with_unicode() {
     state.append_to_buffer('🔒');
     state.append_to_buffer('a');
     state.append_to_buffer('🔓');
-    assert_eq!(state.get_buffer(), "🔒a🔓");
+    assert_eq!(state.get_buffer().expose(), "🔒a🔓");
 }
 
 #[test]
@@ -152,3 +264,38 @@ fn test_multiple_hash_updates() {
     state.set_passphrase_hash("hash2".to_string());
     assert_eq!(state.get_passphrase_hash(), Some("hash2".to_string()));
 }
+
+#[test]
+fn test_sigterm_requests_clean_shutdown() {
+    let state = AppState::new();
+    handsoff::register_shutdown_signals(&state).unwrap();
+    assert!(!state.is_shutdown_requested());
+
+    // The handler only sets the flag; the process keeps running for the main loop to
+    // stop the event tap and exit
+    signal_hook::low_level::raise(signal_hook::consts::SIGTERM).unwrap();
+    assert!(state.is_shutdown_requested());
+    assert!(!state.should_exit_and_clear(), "Not mistaken for a permission loss");
+}
+
+#[test]
+fn test_metrics_count_state_transitions() {
+    let state = AppState::new();
+    state.set_locked(true);
+    state.set_locked(true); // Already locked: not a new lock
+    state.record_blocked_event();
+    state.unlock_with(UnlockMethod::Passphrase);
+    state.set_locked(true);
+    state.record_blocked_event();
+    state.trigger_auto_unlock();
+    state.record_audit(AuditEvent::PermissionLost);
+
+    let metrics = state.metrics_snapshot();
+    assert_eq!(metrics.locks_total, 2);
+    assert_eq!(metrics.auto_unlocks_total, 1);
+    // Unlike the per-lock count, the total spans lock sessions
+    assert_eq!(metrics.events_blocked_total, 2);
+    assert_eq!(state.get_blocked_event_count(), 1);
+    assert_eq!(metrics.permission_losses_total, 1);
+    assert!(!metrics.locked);
+}
@@ -66,3 +66,32 @@ fn test_case_sensitivity() {
     assert!(!auth::verify_passphrase("password", &hash));
     assert!(!auth::verify_passphrase("PASSWORD", &hash));
 }
+
+#[test]
+fn test_generate_recovery_code_format() {
+    let code = auth::generate_recovery_code().expect("Failed to generate recovery code");
+    let groups: Vec<&str> = code.split('-').collect();
+    assert_eq!(groups.len(), 4);
+    for group in groups {
+        assert_eq!(group.len(), 5);
+        // Must be typeable while locked without Shift
+        assert!(group
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    }
+}
+
+#[test]
+fn test_generate_recovery_code_unique() {
+    let code1 = auth::generate_recovery_code().unwrap();
+    let code2 = auth::generate_recovery_code().unwrap();
+    assert_ne!(code1, code2);
+}
+
+#[test]
+fn test_recovery_code_verifies_against_hash() {
+    let code = auth::generate_recovery_code().unwrap();
+    let hash = auth::hash_passphrase(&code);
+    assert!(auth::verify_passphrase(&code, &hash));
+    assert!(!auth::verify_passphrase(&code.to_uppercase(), &hash));
+}
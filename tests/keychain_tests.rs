@@ -0,0 +1,51 @@
+// Keychain integration tests - require a real macOS login session
+// Run with: cargo test --features keychain-tests --test keychain_tests
+#![cfg(feature = "keychain-tests")]
+
+use handsoff::auth::{self, keychain};
+
+/// Separate service name so tests never touch the real HandsOff entry
+const TEST_SERVICE: &str = "com.handsoff.inputlock.tests";
+
+#[test]
+fn test_keychain_store_retrieve_delete() {
+    let hash = auth::hash_passphrase("keychain_test_passphrase");
+
+    keychain::store_passphrase_hash_for_service(TEST_SERVICE, &hash)
+        .expect("Failed to store hash in Keychain");
+    let retrieved = keychain::retrieve_passphrase_hash_for_service(TEST_SERVICE)
+        .expect("Failed to read hash from Keychain");
+    assert_eq!(retrieved, Some(hash));
+
+    keychain::delete_passphrase_hash_for_service(TEST_SERVICE)
+        .expect("Failed to delete hash from Keychain");
+    let after_delete = keychain::retrieve_passphrase_hash_for_service(TEST_SERVICE)
+        .expect("Missing entry should not be an error");
+    assert_eq!(after_delete, None);
+}
+
+#[test]
+fn test_keychain_store_overwrites_existing_entry() {
+    let service = "com.handsoff.inputlock.tests.overwrite";
+    let first = auth::hash_passphrase("first");
+    let second = auth::hash_passphrase("second");
+
+    keychain::store_passphrase_hash_for_service(service, &first).unwrap();
+    keychain::store_passphrase_hash_for_service(service, &second).unwrap();
+    assert_eq!(
+        keychain::retrieve_passphrase_hash_for_service(service).unwrap(),
+        Some(second)
+    );
+
+    keychain::delete_passphrase_hash_for_service(service).unwrap();
+}
+
+#[test]
+fn test_keychain_delete_missing_entry_is_ok() {
+    let service = "com.handsoff.inputlock.tests.missing";
+    assert!(keychain::delete_passphrase_hash_for_service(service).is_ok());
+    assert_eq!(
+        keychain::retrieve_passphrase_hash_for_service(service).unwrap(),
+        None
+    );
+}
@@ -1,4 +1,7 @@
-use handsoff::utils::keycode::keycode_to_char;
+use handsoff::utils::keycode::{
+    can_type_while_locked, keycode_to_char, option_dead_key, option_keycode_to_char,
+    static_char_with_modifiers, untypable_passphrase_warning, DeadKey, KeyboardLayout,
+};
 
 #[test]
 fn test_letter_keys_no_shift() {
@@ -150,3 +153,245 @@ fn test_all_letters_uppercase() {
         );
     }
 }
+
+/// Keycodes for the three letter rows, left to right (QWERTY: qwertyuiop, asdfghjkl;, zxcvbnm,./)
+const TOP_ROW: [i64; 10] = [12, 13, 14, 15, 17, 16, 32, 34, 31, 35];
+const HOME_ROW: [i64; 10] = [0, 1, 2, 3, 5, 4, 38, 40, 37, 41];
+const BOTTOM_ROW: [i64; 10] = [6, 7, 8, 9, 11, 45, 46, 43, 47, 44];
+
+fn row_chars(layout: KeyboardLayout, row: &[i64], shift: bool) -> String {
+    row.iter()
+        .map(|keycode| layout.keycode_to_char(*keycode, shift).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_us_layout_matches_keycode_to_char() {
+    for keycode in 0..=127 {
+        for shift in [false, true] {
+            assert_eq!(
+                KeyboardLayout::Us.keycode_to_char(keycode, shift),
+                keycode_to_char(keycode, shift),
+                "Keycode {} (shift: {})",
+                keycode,
+                shift
+            );
+        }
+    }
+    assert_eq!(row_chars(KeyboardLayout::Us, &TOP_ROW, false), "qwertyuiop");
+}
+
+#[test]
+fn test_dvorak_letter_rows() {
+    assert_eq!(row_chars(KeyboardLayout::Dvorak, &TOP_ROW, false), "',.pyfgcrl");
+    assert_eq!(row_chars(KeyboardLayout::Dvorak, &HOME_ROW, false), "aoeuidhtns");
+    assert_eq!(row_chars(KeyboardLayout::Dvorak, &BOTTOM_ROW, false), ";qjkxbmwvz");
+}
+
+#[test]
+fn test_dvorak_shifted_rows() {
+    assert_eq!(row_chars(KeyboardLayout::Dvorak, &TOP_ROW, true), "\"<>PYFGCRL");
+    assert_eq!(row_chars(KeyboardLayout::Dvorak, &HOME_ROW, true), "AOEUIDHTNS");
+    assert_eq!(row_chars(KeyboardLayout::Dvorak, &BOTTOM_ROW, true), ":QJKXBMWVZ");
+}
+
+#[test]
+fn test_dvorak_symbols() {
+    let dvorak = KeyboardLayout::Dvorak;
+    assert_eq!(dvorak.keycode_to_char(27, false), Some('[')); // QWERTY '-'
+    assert_eq!(dvorak.keycode_to_char(24, true), Some('}')); // QWERTY '='
+    assert_eq!(dvorak.keycode_to_char(33, false), Some('/')); // QWERTY '['
+    assert_eq!(dvorak.keycode_to_char(30, true), Some('+')); // QWERTY ']'
+    assert_eq!(dvorak.keycode_to_char(39, false), Some('-')); // QWERTY '\''
+    assert_eq!(dvorak.keycode_to_char(18, true), Some('!')); // Number row unchanged
+    assert_eq!(dvorak.keycode_to_char(49, false), Some(' '));
+    assert_eq!(dvorak.keycode_to_char(51, false), None); // Delete
+}
+
+#[test]
+fn test_colemak_letter_rows() {
+    assert_eq!(row_chars(KeyboardLayout::Colemak, &TOP_ROW, false), "qwfpgjluy;");
+    assert_eq!(row_chars(KeyboardLayout::Colemak, &HOME_ROW, false), "arstdhneio");
+    assert_eq!(row_chars(KeyboardLayout::Colemak, &BOTTOM_ROW, false), "zxcvbkm,./");
+}
+
+#[test]
+fn test_colemak_shifted_rows() {
+    assert_eq!(row_chars(KeyboardLayout::Colemak, &TOP_ROW, true), "QWFPGJLUY:");
+    assert_eq!(row_chars(KeyboardLayout::Colemak, &HOME_ROW, true), "ARSTDHNEIO");
+    assert_eq!(row_chars(KeyboardLayout::Colemak, &BOTTOM_ROW, true), "ZXCVBKM<>?");
+}
+
+#[test]
+fn test_keyboard_layout_parse() {
+    assert_eq!("Dvorak".parse::<KeyboardLayout>().unwrap(), KeyboardLayout::Dvorak);
+    assert_eq!("qwerty".parse::<KeyboardLayout>().unwrap(), KeyboardLayout::Us);
+    assert_eq!(" colemak ".parse::<KeyboardLayout>().unwrap(), KeyboardLayout::Colemak);
+    assert!("azerty".parse::<KeyboardLayout>().is_err());
+    assert_eq!(KeyboardLayout::default().to_string(), "us");
+}
+
+#[test]
+fn test_keypad_digits_match_main_row() {
+    // (keypad keycode, main-row keycode) for 0-9
+    let pairs = [
+        (82, 29),
+        (83, 18),
+        (84, 19),
+        (85, 20),
+        (86, 21),
+        (87, 23),
+        (88, 22),
+        (89, 26),
+        (91, 28),
+        (92, 25),
+    ];
+    for (keypad, main_row) in pairs {
+        assert_eq!(
+            keycode_to_char(keypad, false),
+            keycode_to_char(main_row, false),
+            "Keypad keycode {} should match main-row keycode {}",
+            keypad,
+            main_row
+        );
+        // Shift doesn't turn keypad digits into symbols
+        assert_eq!(keycode_to_char(keypad, true), keycode_to_char(main_row, false));
+    }
+    assert_eq!(keycode_to_char(82, false), Some('0'));
+    assert_eq!(keycode_to_char(92, false), Some('9'));
+}
+
+#[test]
+fn test_keypad_operators() {
+    assert_eq!(keycode_to_char(65, false), Some('.')); // Decimal
+    assert_eq!(keycode_to_char(65, false), keycode_to_char(47, false));
+    assert_eq!(keycode_to_char(67, false), Some('*')); // Multiply
+    assert_eq!(keycode_to_char(67, false), keycode_to_char(28, true));
+    assert_eq!(keycode_to_char(69, false), Some('+')); // Plus
+    assert_eq!(keycode_to_char(69, false), keycode_to_char(24, true));
+    assert_eq!(keycode_to_char(75, false), Some('/')); // Divide
+    assert_eq!(keycode_to_char(75, false), keycode_to_char(44, false));
+    assert_eq!(keycode_to_char(78, false), Some('-')); // Minus
+    assert_eq!(keycode_to_char(78, false), keycode_to_char(27, false));
+    assert_eq!(keycode_to_char(81, false), Some('=')); // Equals
+    assert_eq!(keycode_to_char(76, false), Some('\n')); // Enter, same as Return
+    assert_eq!(keycode_to_char(71, false), None); // Clear
+    assert_eq!(keycode_to_char(90, false), None); // F20, between keypad 7 and 8
+}
+
+#[test]
+fn test_keypad_unaffected_by_layout() {
+    for layout in [KeyboardLayout::Dvorak, KeyboardLayout::Colemak] {
+        for keycode in [65, 67, 69, 75, 78, 81, 82, 92] {
+            assert_eq!(
+                layout.keycode_to_char(keycode, false),
+                keycode_to_char(keycode, false),
+                "Keypad keycode {} on {}",
+                keycode,
+                layout
+            );
+        }
+    }
+}
+
+#[test]
+fn test_caps_lock_uppercases_letters_only() {
+    let us = KeyboardLayout::Us;
+    // Caps on, Shift off: letters uppercase
+    assert_eq!(static_char_with_modifiers(us, 0, false, true), Some('A'));
+    assert_eq!(static_char_with_modifiers(us, 46, false, true), Some('M'));
+    // Digits and symbols are unchanged by Caps Lock
+    assert_eq!(static_char_with_modifiers(us, 18, false, true), Some('1'));
+    assert_eq!(static_char_with_modifiers(us, 27, false, true), Some('-'));
+    assert_eq!(static_char_with_modifiers(us, 41, false, true), Some(';'));
+    assert_eq!(static_char_with_modifiers(us, 49, false, true), Some(' '));
+    assert_eq!(static_char_with_modifiers(us, 82, false, true), Some('0')); // Keypad
+}
+
+#[test]
+fn test_caps_lock_with_shift() {
+    let us = KeyboardLayout::Us;
+    // macOS keeps letters uppercase when Shift is held with Caps Lock on
+    assert_eq!(static_char_with_modifiers(us, 0, true, true), Some('A'));
+    // Shifted symbols still follow Shift
+    assert_eq!(static_char_with_modifiers(us, 18, true, true), Some('!'));
+    // Caps off: plain Shift behaviour
+    assert_eq!(static_char_with_modifiers(us, 0, false, false), Some('a'));
+    assert_eq!(static_char_with_modifiers(us, 0, true, false), Some('A'));
+}
+
+#[test]
+fn test_caps_lock_on_other_layouts() {
+    // Dvorak QWERTY-'q' position is an apostrophe: Caps Lock leaves it alone
+    assert_eq!(static_char_with_modifiers(KeyboardLayout::Dvorak, 12, false, true), Some('\''));
+    assert_eq!(static_char_with_modifiers(KeyboardLayout::Dvorak, 1, false, true), Some('O'));
+    assert_eq!(static_char_with_modifiers(KeyboardLayout::Colemak, 35, false, true), Some(';'));
+}
+
+#[test]
+fn test_option_layer_characters() {
+    assert_eq!(option_keycode_to_char(1, false), Some('ß')); // Option+S
+    assert_eq!(option_keycode_to_char(8, false), Some('ç')); // Option+C
+    assert_eq!(option_keycode_to_char(8, true), Some('Ç')); // Shift+Option+C
+    assert_eq!(option_keycode_to_char(31, false), Some('ø')); // Option+O
+    assert_eq!(option_keycode_to_char(0, true), Some('Å')); // Shift+Option+A
+    assert_eq!(option_keycode_to_char(19, true), Some('€')); // Shift+Option+2
+    assert_eq!(option_keycode_to_char(44, true), Some('¿')); // Shift+Option+/
+    assert_eq!(option_keycode_to_char(82, false), None); // Keypad has no Option layer
+}
+
+#[test]
+fn test_option_dead_keys() {
+    assert_eq!(option_dead_key(14, false), Some(DeadKey::Acute)); // Option+E
+    assert_eq!(option_dead_key(50, false), Some(DeadKey::Grave)); // Option+`
+    assert_eq!(option_dead_key(34, false), Some(DeadKey::Circumflex)); // Option+I
+    assert_eq!(option_dead_key(45, false), Some(DeadKey::Tilde)); // Option+N
+    assert_eq!(option_dead_key(32, false), Some(DeadKey::Umlaut)); // Option+U
+    // Shift+Option types the accent itself
+    assert_eq!(option_dead_key(14, true), None);
+    assert_eq!(option_keycode_to_char(14, true), Some('´'));
+    assert_eq!(option_dead_key(0, false), None);
+}
+
+#[test]
+fn test_dead_key_composition() {
+    assert_eq!(DeadKey::Acute.compose('e'), Some('é'));
+    assert_eq!(DeadKey::Acute.compose('E'), Some('É'));
+    assert_eq!(DeadKey::Grave.compose('a'), Some('à'));
+    assert_eq!(DeadKey::Circumflex.compose('o'), Some('ô'));
+    assert_eq!(DeadKey::Tilde.compose('n'), Some('ñ'));
+    assert_eq!(DeadKey::Umlaut.compose('U'), Some('Ü'));
+    // Space types the standalone accent
+    assert_eq!(DeadKey::Umlaut.compose(' '), Some('¨'));
+    // Keys that don't combine
+    assert_eq!(DeadKey::Tilde.compose('e'), None);
+    assert_eq!(DeadKey::Acute.compose('1'), None);
+}
+
+#[test]
+fn test_ascii_passphrase_typable_on_every_layout() {
+    for layout in [KeyboardLayout::Us, KeyboardLayout::Dvorak, KeyboardLayout::Colemak] {
+        assert_eq!(can_type_while_locked("Correct horse 9!~{}", layout), Ok(()));
+    }
+}
+
+#[test]
+fn test_option_layer_and_accents_typable_on_us_only() {
+    assert_eq!(can_type_while_locked("café ßñ Ü ´", KeyboardLayout::Us), Ok(()));
+    assert_eq!(
+        can_type_while_locked("café ß", KeyboardLayout::Dvorak),
+        Err(vec!['é', 'ß'])
+    );
+}
+
+#[test]
+fn test_untypable_characters_reported_once_in_order() {
+    assert_eq!(
+        can_type_while_locked("日本é日€ą", KeyboardLayout::Us),
+        Err(vec!['日', '本', 'ą'])
+    );
+    assert_eq!(
+        untypable_passphrase_warning(&['日', 'ą']),
+        "Passphrase contains characters that may be hard to type while locked: 日 ą"
+    );
+}